@@ -1,4 +1,7 @@
-use glyph_engine::{types::vectorizer_parameters::VectorizerParameters, GlyphEngine};
+use glyph_engine::{
+    types::vectorizer_parameters::{ModelDefinition, VectorizerParameters},
+    CancellationToken, GlyphEngine,
+};
 use glyphx_common::{
     types::{
         athena_connection_errors::ConstructorError as AthenaConstructorError,
@@ -199,6 +202,16 @@ fn run(mut cx: FunctionContext) -> JsResult<JsPromise> {
     //are done with our async function
     let (deffered, promise) = cx.promise();
 
+    //The promise gets a cancel function so that the caller can stop the GlyphEngine.  The token is
+    //created here, rather than by the GlyphEngine, so that cancel works before it has been built.
+    let cancellation_token = CancellationToken::new();
+    let cancel_token = cancellation_token.clone();
+    let cancel = JsFunction::new(&mut cx, move |mut cx| {
+        cancel_token.cancel();
+        Ok(cx.undefined())
+    })?;
+    promise.set(&mut cx, "cancel", cancel)?;
+
     //Now the fun part ... we spawn our async function
     rt.spawn(async move {
         //TODO: are we unnecessarily building this more than once?
@@ -227,7 +240,9 @@ fn run(mut cx: FunctionContext) -> JsResult<JsPromise> {
             });
             return;
         }
-        let mut glyph_engine = glyph_engine.unwrap();
+        let mut glyph_engine = glyph_engine
+            .unwrap()
+            .with_cancellation_token(cancellation_token);
         let process_results = glyph_engine.process().await;
         if process_results.is_err() {
             let error = process_results.as_ref().err().unwrap().clone();
@@ -248,6 +263,12 @@ fn run(mut cx: FunctionContext) -> JsResult<JsPromise> {
     Ok(promise)
 }
 
+///Returns the JSON schema of the model definitions that glyph_engine accepts.
+fn model_definition_schema(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let schema = ModelDefinition::get_json_schema();
+    Ok(convert_json_value_to_json_object(schema, &mut cx))
+}
+
 fn convert_neon_value(mut cx: FunctionContext) -> JsResult<JsString> {
     let args = cx.argument::<JsObject>(0)?;
     let val = convert_jsObject_to_JsonValue(&mut cx, args);
@@ -281,6 +302,7 @@ fn convert_glyphx_error_to_jsonObject(mut cx: FunctionContext) -> JsResult<JsObj
 #[neon::main]
 fn main(mut cx: ModuleContext) -> NeonResult<()> {
     cx.export_function("glyph_engine", run)?;
+    cx.export_function("modelDefinitionSchema", model_definition_schema)?;
     //These function are here to support testing.
     cx.export_function("convertNeonValue", convert_neon_value)?;
     cx.export_function("convertJsonValue", convert_json_value)?;
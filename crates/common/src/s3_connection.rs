@@ -1,10 +1,12 @@
 use async_trait::async_trait;
-use glyphx_core::aws::S3Manager;
+use glyphx_core::aws::{LocalObjectStore, ObjectStore, S3Manager};
 use glyphx_core::SecretBoundSingleton;
 use glyphx_core::aws::s3_manager::ConstructorError as S3ManagerConstructorError;
 use crate::types::s3_connection_errors::ConstructorError;
 use mockall::automock;
-use glyphx_core::SecretBoundError;
+use glyphx_core::{GlyphxErrorData, SecretBoundError};
+use serde_json::json;
+use std::sync::Arc;
 
 #[automock]
 #[async_trait]
@@ -13,6 +15,10 @@ trait S3ConnectionOps {
         &self,
         bucket_name: String,
     ) -> Result<S3Manager, S3ManagerConstructorError>;
+    async fn build_local_object_store(
+        &self,
+        directory: String,
+    ) -> Result<LocalObjectStore, S3ManagerConstructorError>;
 }
 
 #[derive(SecretBoundSingleton, Debug, Clone)]
@@ -21,7 +27,9 @@ pub struct S3Connection {
     #[bind_field({"secret_name": "bucketName" })]
     bucket_name: String,
     #[bind_field({"is_bound": false})]
-    s3_manager: S3Manager,
+    object_store: Arc<dyn ObjectStore>,
+    #[bind_field({"is_bound": false})]
+    is_local: bool,
 }
 
 struct S3ConnectionOpsImpl;
@@ -34,17 +42,41 @@ impl S3ConnectionOps for S3ConnectionOpsImpl {
     ) -> Result<S3Manager, S3ManagerConstructorError> {
         S3Manager::new(bucket_name).await
     }
+
+    async fn build_local_object_store(
+        &self,
+        directory: String,
+    ) -> Result<LocalObjectStore, S3ManagerConstructorError> {
+        LocalObjectStore::new(&directory).await
+    }
 }
 
 impl S3Connection {
-    pub fn get_s3_manager(&self) -> &S3Manager {
-        &self.s3_manager
+    ///The same as get_object_store, which we kept for the callers that predate local connections.
+    pub fn get_s3_manager(&self) -> &dyn ObjectStore {
+        self.get_object_store()
+    }
+
+    ///Returns the store that our files are read from and written to.  This is an S3Manager
+    ///unless the connection was built with new_local.
+    pub fn get_object_store(&self) -> &dyn ObjectStore {
+        self.object_store.as_ref()
+    }
+
+    ///The same as get_object_store, but shared, for the callers that need to hold onto the store,
+    ///i.e. the threads that write the vector files.
+    pub fn get_shared_object_store(&self) -> Arc<dyn ObjectStore> {
+        self.object_store.clone()
     }
 
     pub fn get_bucket_name(&self) -> &String {
         &self.bucket_name
     }
 
+    pub fn is_local(&self) -> bool {
+        self.is_local
+    }
+
     //Will return Err(AthenaManagerConstructorError) if something fails.  The T is 
     //required to make the compiler happy, but it will always be S3ManagerConstructorError 
     //which is defined in the attribute on the struct.
@@ -53,29 +85,91 @@ impl S3Connection {
         Self::new_impl(bucket_name, &S3ConnectionOpsImpl).await
     }
 
+    ///Builds an S3Connection whose files live in a local directory rather than in S3.  No
+    ///secrets are read, the directory stands in for the bucket.
+    pub async fn new_local<T>(directory: String) -> Result<Self, T>
+    where T : SecretBoundError {
+        Self::new_local_impl(directory, &S3ConnectionOpsImpl).await
+    }
+
+    ///Builds a local S3Connection and installs it as our singleton, so that get_instance and
+    ///build_singleton will return it.  The singleton is only ever set once, so if it has already
+    ///been built this returns an InstanceAlreadyExists error rather than replacing it.
+    pub async fn build_local_singleton(
+        directory: String,
+    ) -> Result<&'static Self, ConstructorError> {
+        if INSTANCE.get().is_some() {
+            return Err(Self::get_instance_already_exists_error(&directory));
+        }
+        let s3_connection = Self::new_local(directory.clone()).await;
+        if s3_connection.is_err() {
+            return Err(s3_connection.err().unwrap());
+        }
+        //Another caller may have built the singleton while we were opening the directory.
+        if INSTANCE.set(s3_connection.ok().unwrap()).is_err() {
+            return Err(Self::get_instance_already_exists_error(&directory));
+        }
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    ///Returns our singleton if it was built with build_local_singleton.
+    pub fn get_local_instance() -> Option<&'static Self> {
+        INSTANCE.get().filter(|s3_connection| s3_connection.is_local)
+    }
+
+    fn get_instance_already_exists_error(directory: &str) -> ConstructorError {
+        let message = "The S3Connection singleton has already been built, so a local one can not \
+            be installed"
+            .to_string();
+        let data = json!({ "directory": directory });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        let err = ConstructorError::InstanceAlreadyExists(error_data);
+        err.error();
+        err
+    }
+
     async fn new_impl<T, T2>(bucket_name: String, ops: &T) -> Result<Self, T2> 
     where T: S3ConnectionOps, T2 : SecretBoundError {
         let s3_manager = ops.build_s3_manager(bucket_name.clone()).await;
         if s3_manager.is_err() {
-            let err = s3_manager.err().unwrap();
-            let variant = err.parse_error_type();
-            let error_data = err.get_glyphx_error_data();
-            let err = T2::from_str(&variant, error_data.clone());
-
-            err.error();
-            return Err(err);
+            return Err(Self::convert_error(s3_manager.err().unwrap()));
         }
         Ok(Self {
             bucket_name,
-            s3_manager: s3_manager.unwrap(),
+            object_store: Arc::new(s3_manager.unwrap()),
+            is_local: false,
+        })
+    }
+
+    async fn new_local_impl<T, T2>(directory: String, ops: &T) -> Result<Self, T2>
+    where T: S3ConnectionOps, T2 : SecretBoundError {
+        let object_store = ops.build_local_object_store(directory.clone()).await;
+        if object_store.is_err() {
+            return Err(Self::convert_error(object_store.err().unwrap()));
+        }
+        Ok(Self {
+            bucket_name: directory,
+            object_store: Arc::new(object_store.unwrap()),
+            is_local: true,
         })
     }
+
+    fn convert_error<T2>(err: S3ManagerConstructorError) -> T2
+    where T2 : SecretBoundError {
+        let variant = err.parse_error_type();
+        let error_data = err.get_glyphx_error_data();
+        let err = T2::from_str(&variant, error_data.clone());
+
+        err.error();
+        err
+    }
 }
 impl Default for S3Connection {
     fn default() -> Self {
         Self {
             bucket_name: "".to_string(),
-            s3_manager: S3Manager::default(),
+            object_store: Arc::new(S3Manager::default()),
+            is_local: false,
         }
     }
 }
@@ -105,7 +199,8 @@ mod contructor {
         assert_eq!(struct_bucket_name, &bucket_name);
 
         //Nothing to really assert here, just making sure that we can call the function.
-        s3_connection.get_s3_manager();
+        s3_connection.get_object_store();
+        assert!(!s3_connection.is_local());
     }
 
     #[tokio::test]
@@ -133,3 +228,76 @@ mod contructor {
         }
     }
 }
+
+#[cfg(test)]
+mod new_local {
+    use super::*;
+    use glyphx_core::GlyphxErrorData;
+
+    #[tokio::test]
+    async fn is_ok() {
+        let directory = std::env::temp_dir().join("glyphx_s3_connection_new_local");
+        std::fs::create_dir_all(&directory).unwrap();
+        let directory = directory.to_str().unwrap().to_string();
+
+        let s3_connection: Result<S3Connection, S3ManagerConstructorError> =
+            S3Connection::new_local_impl(directory.clone(), &S3ConnectionOpsImpl).await;
+        assert!(s3_connection.is_ok());
+        let s3_connection = s3_connection.unwrap();
+        assert!(s3_connection.is_local());
+        assert_eq!(s3_connection.get_bucket_name(), &directory);
+        assert_eq!(s3_connection.get_object_store().get_bucket_name(), directory);
+    }
+
+    #[tokio::test]
+    async fn is_error() {
+        let mut mock_ops = MockS3ConnectionOps::new();
+
+        mock_ops
+            .expect_build_local_object_store()
+            .times(1)
+            .returning(|_| {
+                Err(S3ManagerConstructorError::BucketDoesNotExist(
+                    GlyphxErrorData::default(),
+                ))
+            });
+        mock_ops.expect_build_s3_manager().times(0);
+
+        let s3_connection: Result<S3Connection, S3ManagerConstructorError> =
+            S3Connection::new_local_impl("missing".to_string(), &mock_ops).await;
+        assert!(s3_connection.is_err());
+        match s3_connection.err().unwrap() {
+            S3ManagerConstructorError::BucketDoesNotExist(_) => {}
+            _ => panic!("Expected BucketDoesNotExist"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod build_local_singleton {
+    use super::*;
+    use glyphx_core::Singleton;
+
+    #[tokio::test]
+    async fn is_only_built_once() {
+        let directory = std::env::temp_dir().join("glyphx_s3_connection_build_local_singleton");
+        std::fs::create_dir_all(&directory).unwrap();
+        let directory = directory.to_str().unwrap().to_string();
+
+        let s3_connection = S3Connection::build_local_singleton(directory.clone()).await;
+        assert!(s3_connection.is_ok());
+        let s3_connection = s3_connection.unwrap();
+        assert!(s3_connection.is_local());
+        assert!(std::ptr::eq(S3Connection::get_local_instance().unwrap(), s3_connection));
+        assert!(std::ptr::eq(S3Connection::get_instance(), s3_connection));
+
+        //The singleton already exists, so it is returned rather than rebuilt from our secret.
+        let built: Result<&S3Connection, ConstructorError> = S3Connection::build_singleton().await;
+        assert!(std::ptr::eq(built.unwrap(), s3_connection));
+
+        match S3Connection::build_local_singleton(directory).await {
+            Err(ConstructorError::InstanceAlreadyExists(_)) => {}
+            _ => panic!("Expected InstanceAlreadyExists"),
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub enum ConstructorError {
    SecretBoundError(GlyphxErrorData),
    BucketDoesNotExist(GlyphxErrorData), 
    UnexpectedError(GlyphxErrorData),
+   InstanceAlreadyExists(GlyphxErrorData),
 }
 
 impl ConstructorError {
@@ -8,14 +8,35 @@ pub mod upload_stream;
 /// Our S3Manager Struct which is the primary interface for working with S3.
 #[cfg(feature = "s3_manager")]
 pub use s3_manager::S3Manager;
+/// The traits that let us store our files somewhere other than S3.
+#[cfg(feature = "s3_manager")]
+pub mod object_store;
+#[cfg(feature = "s3_manager")]
+pub use object_store::{ObjectStore, ObjectUploadStream};
+/// An ObjectStore that keeps its files in a local directory.
+#[cfg(feature = "s3_manager")]
+pub mod local_object_store;
+#[cfg(feature = "s3_manager")]
+pub use local_object_store::LocalObjectStore;
 /// Our AthenaManager Struct which is the primary interface for working with Athena.
 #[cfg(feature = "athena_manager")] 
 pub mod athena_manager;
 /// Our result set converter for converting result_sets to json.
 #[cfg(feature = "athena_manager")] 
 pub mod result_set_converter;
+/// Our record batch converter for converting result_sets to Arrow RecordBatches.
+#[cfg(feature = "athena_manager")]
+pub mod record_batch_converter;
 #[cfg(feature = "athena_manager")] 
 pub mod athena_stream_iterator;
+/// Our reader for the results files that Athena writes to the output location of a query.
+#[cfg(all(feature = "athena_manager", feature = "s3_manager"))]
+pub mod athena_output_reader;
+/// The policy that our managers use to retry throttled AWS calls.
+#[cfg(any(feature = "s3_manager", feature = "athena_manager"))]
+pub mod retry_policy;
+#[cfg(any(feature = "s3_manager", feature = "athena_manager"))]
+pub use retry_policy::RetryPolicy;
 #[cfg(feature = "secret_manager")]
 pub mod secret_manager;
 #[cfg(feature = "secret_manager")]
@@ -22,21 +22,41 @@ use aws_sdk_athena::operation::start_query_execution::{
 
 use aws_sdk_athena::operation::get_query_results::{GetQueryResultsError, GetQueryResultsOutput};
 
+use aws_sdk_athena::operation::stop_query_execution::{
+    StopQueryExecutionError, StopQueryExecutionOutput,
+};
+
 use aws_sdk_athena::operation::get_database::{GetDatabaseError, GetDatabaseOutput};
 
-use super::result_set_converter::convert_to_json;
+use super::result_set_converter::convert_to_json_with_options;
+pub use super::result_set_converter::{ConversionOptions, TemporalFormat};
+pub use super::retry_policy::RetryPolicy;
+use super::retry_policy::{record_retries, record_retries_in_error_data};
 use async_trait::async_trait;
 pub use crate::types::aws::athena_manager::athena_manager_errors::{
     ConstructorError, GetQueryPagerError, GetQueryResultsError as GlyphxGetQueryResultsError,
-    GetQueryStatusError, GetTableDescriptionError, RunQueryError, StartQueryError, AthenaStreamIteratorError
+    GetQueryStatusError, GetTableDescriptionError, RunQueryError, StartQueryError, AthenaStreamIteratorError,
+    StopQueryError, ResultSetConversionError,
 };
+pub use crate::types::aws::athena_manager::polling_policy::{PollingPolicy, QueryPoller};
 pub use crate::types::aws::athena_manager::query_status::AthenaQueryStatus;
+#[cfg(feature = "s3_manager")]
+pub use crate::types::aws::athena_manager::query_results_source::QueryResultsSource;
+#[cfg(feature = "s3_manager")]
+use super::athena_output_reader::{OutputLocation, OutputLocationPages};
+#[cfg(feature = "s3_manager")]
+use super::athena_stream_iterator::{AthenaStreamIterator, ResultSetMetadata};
+#[cfg(feature = "s3_manager")]
+use super::object_store::ObjectStore;
+#[cfg(feature = "s3_manager")]
+use crate::traits::ErrorTypeParser;
 pub use crate::types::aws::athena_manager::table_description::*;
 pub use crate::types::error::GlyphxErrorData;
 
 use mockall::*;
+use rand::Rng;
 use serde_json::{json, Value};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tokio_stream::Stream;
 
 /// This trait defines those methods that we will be mocking in our unit tests.  We will be
@@ -59,6 +79,7 @@ trait AthenaManagerOps {
         database: &str,
         query: &str,
         output_location: Option<String>,
+        client_request_token: &str,
     ) -> Result<StartQueryExecutionOutput, SdkError<StartQueryExecutionError>>;
 
     async fn get_query_execution(
@@ -73,6 +94,12 @@ trait AthenaManagerOps {
         query_id: &str,
     ) -> Result<GetQueryResultsOutput, SdkError<GetQueryResultsError>>;
 
+    async fn stop_query_execution(
+        &self,
+        client: &AthenaClient,
+        query_id: &str,
+    ) -> Result<StopQueryExecutionOutput, SdkError<StopQueryExecutionError>>;
+
     fn get_query_results_paginator(
         &self,
         client: &AthenaClient,
@@ -142,6 +169,9 @@ impl AthenaManagerOps for AthenaManagerOpsImpl {
     ///* `catalog` - The AWS catalog.
     ///* `database` - The AWS database.
     ///* `query` - The query to execute.
+    ///* `output_location` - The location to store the results of the query.
+    ///* `client_request_token` - Identifies the query to Athena, so that a call that is retried
+    ///  with the same token does not start a second query.
     async fn start_query_execution(
         &self,
         client: &AthenaClient,
@@ -149,6 +179,7 @@ impl AthenaManagerOps for AthenaManagerOpsImpl {
         database: &str,
         query: &str,
         output_location: Option<String>,
+        client_request_token: &str,
     ) -> Result<StartQueryExecutionOutput, SdkError<StartQueryExecutionError>> {
         let context = QueryExecutionContext::builder()
         .catalog(catalog)
@@ -157,7 +188,8 @@ impl AthenaManagerOps for AthenaManagerOpsImpl {
         let mut op = client
             .start_query_execution()
             .query_execution_context(context)
-            .query_string(query);
+            .query_string(query)
+            .client_request_token(client_request_token);
         if output_location.is_some() {
             let result_configuration = ResultConfiguration::builder()
                 .output_location(output_location.unwrap())
@@ -201,6 +233,22 @@ impl AthenaManagerOps for AthenaManagerOpsImpl {
             .await
     }
 
+    ///Calls the AWS Athena stop_query_execution method to cancel a running query.
+    ///# Arguments
+    ///* `client` - The AWS Athena client.
+    ///* `query_id` - The query id.
+    async fn stop_query_execution(
+        &self,
+        client: &AthenaClient,
+        query_id: &str,
+    ) -> Result<StopQueryExecutionOutput, SdkError<StopQueryExecutionError>> {
+        client
+            .stop_query_execution()
+            .query_execution_id(query_id)
+            .send()
+            .await
+    }
+
     ///In some instances/workflows we may not want to return all of query results all at once.
     ///This method returns a paginator that can be used to retrieve the results in chunks.
     ///# Arguments
@@ -302,12 +350,22 @@ impl AthenaManagerOps for AthenaManagerOpsImpl {
             .await
     }
 }
+
+///Returns a new client request token for start_query_execution.  Athena requires tokens to be
+///between 32 and 128 characters long.
+fn get_client_request_token() -> String {
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
 ///The AthenaManager is used to execute queries against AWS Athena.
 #[derive(Debug, Clone)]
 pub struct AthenaManager {
     catalog: String,
     database: String,
     client: AthenaClient,
+    polling_policy: PollingPolicy,
+    retry_policy: RetryPolicy,
+    conversion_options: ConversionOptions,
 }
 
 ///The impl of the functions for our AthenaManager.  in most cases you will see two versions of the
@@ -359,6 +417,14 @@ impl AthenaManager {
         self.get_query_status_impl(query_id, &AthenaManagerOpsImpl)
             .await
     }
+
+    ///Stops a query that was previously started by a call to start_query.  Stopping a query that
+    ///has already completed is not an error.
+    ///# Arguments
+    ///* `query_id` - The query id.
+    pub async fn stop_query(&self, query_id: &str) -> Result<(), StopQueryError> {
+        self.stop_query_impl(query_id, &AthenaManagerOpsImpl).await
+    }
     ///Once a query has been completed, this method can be called to return the results of the
     ///query.  Keep in mind that calling this method befire the query has completed will result in
     ///an error.
@@ -375,14 +441,46 @@ impl AthenaManager {
             .await
     }
 
+    ///Sets the policy that run_query uses to poll the status of its queries.  A time_out passed to
+    ///run_query replaces the max wait of the policy.
+    pub fn with_polling_policy(mut self, polling_policy: PollingPolicy) -> Self {
+        self.polling_policy = polling_policy;
+        self
+    }
+
+    pub fn get_polling_policy(&self) -> &PollingPolicy {
+        &self.polling_policy
+    }
+
+    ///Sets the policy that start_query, get_query_status and get_query_results use to retry
+    ///calls that AWS has throttled.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    ///Sets how get_query_results and run_query convert the values of the results to JSON.
+    pub fn with_conversion_options(mut self, conversion_options: ConversionOptions) -> Self {
+        self.conversion_options = conversion_options;
+        self
+    }
+
+    pub fn get_conversion_options(&self) -> &ConversionOptions {
+        &self.conversion_options
+    }
+
     ///This method effectivly wraps the call to start_query, get_query_status and
     ///get_query_results.  Additionally, the user may set a time out value that will cause the
     ///method to return an error if the query has not completed within the specified time.  The
-    ///default is 60 seconds.
+    ///default is the max wait of our polling policy, 60 seconds unless it has been changed.
     ///# Arguments
     ///* `query` - The query to execute.
     ///* `time_out` - The maximum amount of time to wait for the query to complete in seconds.  The
-    ///default is 60 seconds.
+    ///  default is the max wait of our polling policy.
     ///* `results_include_header_row` - Whether or not the results in the query output have a
     ///header row in addition to the information included in the result_metadata.
     pub async fn run_query(
@@ -417,6 +515,48 @@ impl AthenaManager {
             .await
     }
 
+    ///Athena writes the full results of a query as a CSV file to the output location of the
+    ///query.  This method streams that file instead of paging through get_query_results, which
+    ///is much faster for large results.  The pages hold the same rows, with the same column
+    ///information, as get_paged_query_results.  Do not call this method until the query has
+    ///completed.
+    ///# Arguments
+    ///* `query_id` - The query id.
+    ///* `object_store` - The ObjectStore for the bucket of the output location of the query.
+    ///* `page_size` - The number of results to return per page.  The default is 1000.
+    #[cfg(feature = "s3_manager")]
+    pub async fn get_output_location_query_results(
+        &self,
+        query_id: &str,
+        object_store: &dyn ObjectStore,
+        page_size: Option<i32>,
+    ) -> Result<OutputLocationPages, GetQueryPagerError> {
+        self.get_output_location_query_results_impl(
+            query_id,
+            object_store,
+            page_size,
+            &AthenaManagerOpsImpl,
+        )
+        .await
+    }
+
+    ///Returns an AthenaStreamIterator over the results of a completed query that reads them from
+    ///source, converting the rows with the ConversionOptions of this AthenaManager.
+    ///# Arguments
+    ///* `query_id` - The query id.
+    ///* `page_size` - The number of results to read per page.  The default is 1000.
+    ///* `source` - Where the results are read from.
+    #[cfg(feature = "s3_manager")]
+    pub async fn get_query_results_iterator(
+        &self,
+        query_id: &str,
+        page_size: Option<i32>,
+        source: QueryResultsSource<'_>,
+    ) -> Result<AthenaStreamIterator, GetQueryPagerError> {
+        self.get_query_results_iterator_impl(query_id, page_size, source, &AthenaManagerOpsImpl)
+            .await
+    }
+
     ///This method is used to check and see if a table exists in the database.
     ///# Arguments
     ///* `table_name` - The name of the table to check for.
@@ -538,6 +678,9 @@ impl AthenaManager {
                 catalog: catalog.to_string(),
                 database: database.to_string(),
                 client,
+                polling_policy: PollingPolicy::default(),
+                retry_policy: RetryPolicy::default(),
+                conversion_options: ConversionOptions::default(),
             })
         }
     }
@@ -563,71 +706,66 @@ impl AthenaManager {
             Some(loc) => Some(loc.to_string()),
             None => None,
         };
-        let res = aws_operations
-            .start_query_execution(
-                &self.client,
-                &self.catalog,
-                &self.database,
-                query,
-                output_location,
-            )
+        //A timed out call may still have started the query, so every attempt sends the same
+        //token and Athena will not start it a second time.
+        let client_request_token = get_client_request_token();
+        let (res, retries) = self
+            .retry_policy
+            .retry_when(StartQueryExecutionError::is_too_many_requests_exception, || {
+                aws_operations.start_query_execution(
+                    &self.client,
+                    &self.catalog,
+                    &self.database,
+                    query,
+                    output_location.clone(),
+                    &client_request_token,
+                )
+            })
             .await;
 
         if res.is_err() {
             let service_error = res.err().unwrap().into_service_error();
+            let data = record_retries(
+                json!({
+                    "catalog": self.catalog,
+                    "database": self.database,
+                    "query": query,
+                }),
+                retries,
+            );
             match service_error {
                 StartQueryExecutionError::InvalidRequestException(e) => {
                     return Err(StartQueryError::DatabaseDoesNotExist(GlyphxErrorData::new(
                         e.to_string(),
-                        Some(json!({
-                            "catalog": self.catalog,
-                            "database": self.database,
-                            "query": query,
-                        })),
+                        Some(data),
                         None,
                     )));
                 }
                 StartQueryExecutionError::TooManyRequestsException(e) => {
                     return Err(StartQueryError::RequestWasThrottled(GlyphxErrorData::new(
                         e.to_string(),
-                        Some(json!({
-                            "catalog": self.catalog,
-                            "database": self.database,
-                            "query": query,
-                        })),
+                        Some(data),
                         None,
                     )));
                 }
                 StartQueryExecutionError::InternalServerException(e) => {
                     return Err(StartQueryError::UnexpectedError(GlyphxErrorData::new(
                         e.to_string(),
-                        Some(json!({
-                            "catalog": self.catalog,
-                            "database": self.database,
-                            "query": query,
-                        })),
+                        Some(data),
                         None,
                     )));
                 }
                 StartQueryExecutionError::Unhandled(e) => {
                     return Err(StartQueryError::UnexpectedError(GlyphxErrorData::new(
                         e.to_string(),
-                        Some(json!({
-                            "catalog": self.catalog,
-                            "database": self.database,
-                            "query": query,
-                        })),
+                        Some(data),
                         None,
                     )));
                 }
                 _ => {
                     return Err(StartQueryError::UnexpectedError(GlyphxErrorData::new(
                         "An unknown error has occurred.  Unfortunatly I have no more information to share".to_string(),
-                        Some(json!({
-                            "catalog": self.catalog,
-                            "database": self.database,
-                            "query": query,
-                        })),
+                        Some(data),
                         None,
                     )));
                 }
@@ -649,38 +787,37 @@ impl AthenaManager {
         query_id: &str,
         aws_ops: &T,
     ) -> Result<AthenaQueryStatus, GetQueryStatusError> {
-        let res = aws_ops.get_query_execution(&self.client, query_id).await;
+        let (res, retries) = self
+            .retry_policy
+            .retry(|| aws_ops.get_query_execution(&self.client, query_id))
+            .await;
         if res.is_err() {
             let service_error = res.err().unwrap().into_service_error();
+            let data = record_retries(
+                json!({"catalog": self.catalog, "database": self.database, "query_id": query_id    }),
+                retries,
+            );
             match service_error {
                GetQueryExecutionError::InvalidRequestException(e) => {
                     return Err(
-                        GetQueryStatusError::QueryDoesNotExist(GlyphxErrorData::new(e.message().unwrap().to_string(), Some(
-                            json!({"catalog": self.catalog, "database": self.database, "query_id": query_id    }),
-                                    ), None) )
+                        GetQueryStatusError::QueryDoesNotExist(GlyphxErrorData::new(e.message().unwrap().to_string(), Some(data), None) )
                         )
                 },
                 GetQueryExecutionError::InternalServerException(e) => {
                     return Err(
-                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(e.message().unwrap().to_string(), Some(
-                            json!({"catalog": self.catalog, "database": self.database, "query_id": query_id    }),
-                                    ), None) )
+                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(e.message().unwrap().to_string(), Some(data), None) )
                         )
 
                 },
                 GetQueryExecutionError::Unhandled(e) => {
                     return Err(
-                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(e.meta().to_string(), Some(
-                            json!({"catalog": self.catalog, "database": self.database, "query_id": query_id    }),
-                                    ), None) )
+                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(e.meta().to_string(), Some(data), None) )
                         )
 
                 },
                 _ => {
                     return Err(
-                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(String::from("An unexpected error has occurred.  Unfortunatly I have no other information to give."), Some(
-                            json!({"catalog": self.catalog, "database": self.database, "query_id": query_id    }),
-                                    ), None) )
+                        GetQueryStatusError::UnexpectedError(GlyphxErrorData::new(String::from("An unexpected error has occurred.  Unfortunatly I have no other information to give."), Some(data), None) )
                         )
                 }
             }
@@ -702,6 +839,55 @@ impl AthenaManager {
         }
     }
 
+    ///The internal implementation of the stop_query method.  This method will make the actual calls to aws
+    ///when AthenaManagerOpsImpl is passed as the aws_operations parameter.  For unit tests, a mock
+    ///of AthenaManagerOps can be passed in to simulate the aws calls.
+    ///# Arguments
+    ///* `query_id` - The id of the query to stop.
+    ///* `aws_operations` - The implementation of AthenaManagerOps to use to make or mock the aws calls.
+    async fn stop_query_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        aws_ops: &T,
+    ) -> Result<(), StopQueryError> {
+        let res = aws_ops.stop_query_execution(&self.client, query_id).await;
+        if res.is_err() {
+            let service_error = res.err().unwrap().into_service_error();
+            let data = json!({"catalog": self.catalog, "database": self.database, "query_id": query_id});
+            match service_error {
+                StopQueryExecutionError::InvalidRequestException(e) => {
+                    return Err(StopQueryError::QueryDoesNotExist(GlyphxErrorData::new(
+                        e.to_string(),
+                        Some(data),
+                        None,
+                    )));
+                }
+                StopQueryExecutionError::InternalServerException(e) => {
+                    return Err(StopQueryError::UnexpectedError(GlyphxErrorData::new(
+                        e.to_string(),
+                        Some(data),
+                        None,
+                    )));
+                }
+                StopQueryExecutionError::Unhandled(e) => {
+                    return Err(StopQueryError::UnexpectedError(GlyphxErrorData::new(
+                        e.to_string(),
+                        Some(data),
+                        None,
+                    )));
+                }
+                _ => {
+                    return Err(StopQueryError::UnexpectedError(GlyphxErrorData::new(
+                        "An unknown error has occurred.  Unfortunatly I have no more information to share".to_string(),
+                        Some(data),
+                        None,
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     ///The internal implementation of the get_query_results method.  This method will make the actual calls to aws
     ///when AthenaManagerOpsImpl is passed as the aws_operations parameter.  For unit tests, a mock
     ///of AthenaManagerOps can be passed in to simulate the aws calls.
@@ -716,17 +902,22 @@ impl AthenaManager {
         results_include_header_row: Option<bool>,
         aws_operations: &T,
     ) -> Result<Value, GlyphxGetQueryResultsError> {
-        let res = aws_operations
-            .get_query_results(&self.client, query_id)
+        let (res, retries) = self
+            .retry_policy
+            .retry_when(GetQueryResultsError::is_too_many_requests_exception, || {
+                aws_operations.get_query_results(&self.client, query_id)
+            })
             .await;
         if res.is_err() {
             let service_error = res.err().unwrap().into_service_error();
-            return Err(GlyphxGetQueryResultsError::from_aws_get_query_result_error(
+            let mut error = GlyphxGetQueryResultsError::from_aws_get_query_result_error(
                 service_error,
                 &self.catalog,
                 &self.database,
                 query_id,
-            ));
+            );
+            record_retries_in_error_data(error.get_error_data_mut(), retries);
+            return Err(error);
         } 
             let res = res.unwrap();
             let result_set = res.result_set;
@@ -737,7 +928,20 @@ impl AthenaManager {
             if result_set.rows.is_none() {
                 return Ok(Value::Null);
             }
-            Ok(convert_to_json(&result_set, results_include_header_row))
+            let res = convert_to_json_with_options(
+                &result_set,
+                results_include_header_row,
+                &self.conversion_options,
+            );
+            if res.is_err() {
+                return Err(GlyphxGetQueryResultsError::from_result_set_conversion_error(
+                    res.err().unwrap(),
+                    &self.catalog,
+                    &self.database,
+                    query_id,
+                ));
+            }
+            Ok(res.unwrap())
         
     }
 
@@ -886,6 +1090,15 @@ impl AthenaManager {
                     Some(inner_error),
                 ))
             }
+            GlyphxGetQueryResultsError::ConversionError(e) => {
+                let inner_error = serde_json::to_value(&e).unwrap();
+                let inner_error = json!({ "ConversionError": inner_error });
+                RunQueryError::UnexpectedError(GlyphxErrorData::new(
+                    e.message,
+                    data,
+                    Some(inner_error),
+                ))
+            }
             GlyphxGetQueryResultsError::UnexpectedError(e) => {
                 let inner_error = serde_json::to_value(&e).unwrap();
                 let inner_error = json!({ "UnexpectedError": inner_error });
@@ -1003,23 +1216,15 @@ impl AthenaManager {
 
         let query_id = result.unwrap();
 
-        let time_out = time_out.unwrap_or(60); //Default to 60 seconds if no timeout is specified.
-        let start_time = Instant::now();
-        let desired_duration = Duration::from_secs(time_out as u64);
-        //Check our query status until results are ready or we hit our timeout.
+        let mut polling_policy = self.polling_policy.clone();
+        if let Some(time_out) = time_out {
+            polling_policy = polling_policy.with_max_wait(Duration::from_secs(time_out as u64));
+        }
+        let time_out = polling_policy.get_max_wait().as_secs();
+        let mut poller = polling_policy.start();
+        //Check our query status, backing off between checks, until results are ready or we hit
+        //our timeout.
         let query_result: Result<(), RunQueryError> = loop {
-            let elapsed = start_time.elapsed();
-
-            if elapsed >= desired_duration {
-                break Err(RunQueryError::QueryTimedOut(GlyphxErrorData::new(
-                    String::from("The query timed out."),
-                    Some(
-                        json!({"catalog": self.catalog, "database": self.database, "query": query, "query_id": query_id, "timeout": time_out}),
-                    ),
-                    None,
-                )));
-            }
-
             let result = aws_operations.get_query_status_impl(self, &query_id).await;
             if result.is_err() {
                 let err = result.err().unwrap();
@@ -1051,6 +1256,16 @@ impl AthenaManager {
                 }
                 _ => {}
             }
+
+            if !poller.wait().await {
+                break Err(RunQueryError::QueryTimedOut(GlyphxErrorData::new(
+                    String::from("The query timed out."),
+                    Some(
+                        json!({"catalog": self.catalog, "database": self.database, "query": query, "query_id": query_id, "timeout": time_out}),
+                    ),
+                    None,
+                )));
+            }
         };
 
         if query_result.is_err() {
@@ -1087,6 +1302,238 @@ impl AthenaManager {
         impl Stream<Item = Result<GetQueryResultsOutput, SdkError<GetQueryResultsError>>> + Unpin + Send,
         GetQueryPagerError,
     > {
+        let is_finished = self.check_query_is_finished_impl(query_id, aws_operations).await;
+        if is_finished.is_err() {
+            return Err(is_finished.err().unwrap());
+        }
+        Ok(aws_operations.get_query_results_paginator(&self.client, query_id, page_size))
+    }
+
+    ///The internal implementation of the get_output_location_query_results method.  This method
+    ///will make the actual calls to aws when AthenaManagerOpsImpl is passed as the aws_operations
+    ///parameter.  For unit tests, a mock of AthenaManagerOps can be passed in to simulate the aws
+    ///calls.
+    ///# Arguments
+    ///* `query_id` - The id of the query to get the results for.
+    ///* `object_store` - The ObjectStore for the bucket of the output location of the query.
+    ///* `page_size` - The number of results to return per page.  If None, 1000 will be used.
+    ///* `aws_operations` - The AthenaManagerOps implementation to use to make or mock the aws calls.
+    #[cfg(feature = "s3_manager")]
+    async fn get_output_location_query_results_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        object_store: &dyn ObjectStore,
+        page_size: Option<i32>,
+        aws_operations: &T,
+    ) -> Result<OutputLocationPages, GetQueryPagerError> {
+        let is_finished = self.check_query_is_finished_impl(query_id, aws_operations).await;
+        if is_finished.is_err() {
+            return Err(is_finished.err().unwrap());
+        }
+
+        let location = self.get_output_location_impl(query_id, aws_operations).await;
+        if location.is_err() {
+            return Err(location.err().unwrap());
+        }
+        let location = location.unwrap();
+        let data = json!({
+            "catalog": self.catalog,
+            "database": self.database,
+            "query_id": query_id,
+            "bucket": location.get_bucket(),
+            "key": location.get_key()
+        });
+        let bucket_name = object_store.get_bucket_name();
+        if location.get_bucket() != bucket_name {
+            let mut data = data;
+            data["object_store_bucket"] = json!(bucket_name);
+            return Err(GetQueryPagerError::OutputLocationError(GlyphxErrorData::new(
+                String::from(
+                    "The output location of the query is not in the bucket of the object store.",
+                ),
+                Some(data),
+                None,
+            )));
+        }
+
+        //The results file only names the columns, so their types come from get_query_results.
+        let metadata = self
+            .get_result_set_metadata_impl(query_id, aws_operations)
+            .await;
+        if metadata.is_err() {
+            return Err(metadata.err().unwrap());
+        }
+        let metadata = metadata.unwrap();
+
+        let body = object_store.get_object_stream(location.get_key()).await;
+        if body.is_err() {
+            let error = body.err().unwrap();
+            return Err(GetQueryPagerError::OutputLocationError(GlyphxErrorData::new(
+                String::from(
+                    "Unable to read the results file of the query. See the inner error for more \
+                     details.",
+                ),
+                Some(data),
+                Some(json!({ error.parse_error_type(): error.get_glyphx_error_data().to_json() })),
+            )));
+        }
+        Ok(OutputLocationPages::new(body.unwrap(), metadata, page_size, data))
+    }
+
+    ///Gets the bucket and key of the results file that Athena wrote for a query.
+    ///# Arguments
+    ///* `query_id` - The id of the query.
+    ///* `aws_operations` - The AthenaManagerOps implementation to use to make or mock the aws calls.
+    #[cfg(feature = "s3_manager")]
+    async fn get_output_location_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        aws_operations: &T,
+    ) -> Result<OutputLocation, GetQueryPagerError> {
+        let (res, retries) = self
+            .retry_policy
+            .retry(|| aws_operations.get_query_execution(&self.client, query_id))
+            .await;
+        let data = record_retries(
+            json!({"catalog": self.catalog, "database": self.database, "query_id": query_id}),
+            retries,
+        );
+        if res.is_err() {
+            let service_error = res.err().unwrap().into_service_error();
+            let message = service_error
+                .message()
+                .unwrap_or("An unexpected error occurred while getting the query execution.")
+                .to_string();
+            return Err(GetQueryPagerError::UnexpectedError(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )));
+        }
+        let output_location = res
+            .unwrap()
+            .query_execution
+            .and_then(|query_execution| query_execution.result_configuration)
+            .and_then(|result_configuration| result_configuration.output_location);
+        let location = output_location.as_deref().and_then(OutputLocation::parse);
+        if location.is_none() {
+            let mut data = data;
+            data["output_location"] = json!(output_location);
+            return Err(GetQueryPagerError::OutputLocationError(GlyphxErrorData::new(
+                String::from("The query does not have an S3 output location."),
+                Some(data),
+                None,
+            )));
+        }
+        Ok(location.unwrap())
+    }
+
+    ///Gets the column information of a query from the first page of its results, which we ask
+    ///to hold a single row so that we do not read rows that we are not going to use.
+    ///# Arguments
+    ///* `query_id` - The id of the query.
+    ///* `aws_operations` - The AthenaManagerOps implementation to use to make or mock the aws calls.
+    #[cfg(feature = "s3_manager")]
+    async fn get_result_set_metadata_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        aws_operations: &T,
+    ) -> Result<ResultSetMetadata, GetQueryPagerError> {
+        use tokio_stream::StreamExt;
+        let data =
+            Some(json!({"catalog": self.catalog, "database": self.database, "query_id": query_id}));
+        let mut pages = aws_operations.get_query_results_paginator(&self.client, query_id, Some(1));
+        let page = pages.next().await;
+        if let Some(Err(error)) = page {
+            let error = GlyphxGetQueryResultsError::from_aws_get_query_result_error(
+                error.into_service_error(),
+                &self.catalog,
+                &self.database,
+                query_id,
+            );
+            return Err(GetQueryPagerError::UnexpectedError(GlyphxErrorData::new(
+                String::from(
+                    "Unable to get the column information of the query. See the inner error for \
+                     more details.",
+                ),
+                data,
+                Some(serde_json::to_value(error).unwrap()),
+            )));
+        }
+        let metadata = page
+            .and_then(|page| page.ok())
+            .and_then(|page| page.result_set)
+            .and_then(|result_set| result_set.result_set_metadata);
+        if metadata.is_none() {
+            return Err(GetQueryPagerError::UnexpectedError(GlyphxErrorData::new(
+                String::from("The results of the query do not include its column information."),
+                data,
+                None,
+            )));
+        }
+        Ok(metadata.unwrap())
+    }
+
+    ///The internal implementation of the get_query_results_iterator method.
+    ///# Arguments
+    ///* `query_id` - The id of the query to get the results for.
+    ///* `page_size` - The number of results to read per page.  If None, 1000 will be used.
+    ///* `source` - Where the results are read from.
+    ///* `aws_operations` - The AthenaManagerOps implementation to use to make or mock the aws calls.
+    #[cfg(feature = "s3_manager")]
+    async fn get_query_results_iterator_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        page_size: Option<i32>,
+        source: QueryResultsSource<'_>,
+        aws_operations: &T,
+    ) -> Result<AthenaStreamIterator, GetQueryPagerError> {
+        let iterator = match source {
+            QueryResultsSource::GetQueryResults => {
+                let is_finished = self.check_query_is_finished_impl(query_id, aws_operations).await;
+                if is_finished.is_err() {
+                    return Err(is_finished.err().unwrap());
+                }
+                AthenaStreamIterator::new(
+                    aws_operations.get_query_results_paginator(&self.client, query_id, page_size),
+                    query_id,
+                    &self.catalog,
+                    &self.database,
+                )
+            }
+            QueryResultsSource::OutputLocation(object_store) => {
+                let pages = self
+                    .get_output_location_query_results_impl(
+                        query_id,
+                        object_store,
+                        page_size,
+                        aws_operations,
+                    )
+                    .await;
+                if pages.is_err() {
+                    return Err(pages.err().unwrap());
+                }
+                AthenaStreamIterator::from_pages(
+                    Box::new(pages.unwrap()),
+                    query_id,
+                    &self.catalog,
+                    &self.database,
+                )
+            }
+        };
+        Ok(iterator.with_conversion_options(self.conversion_options.clone()))
+    }
+
+    ///Checks that a query has succeeded so that its results can be read, returning the
+    ///GetQueryPagerError that describes why they can not be read if it has not.
+    ///# Arguments
+    ///* `query_id` - The id of the query to check.
+    ///* `aws_operations` - The AthenaManagerOps implementation to use to make or mock the aws calls.
+    async fn check_query_is_finished_impl<T: AthenaManagerOps>(
+        &self,
+        query_id: &str,
+        aws_operations: &T,
+    ) -> Result<(), GetQueryPagerError> {
         let status = aws_operations.get_query_status_impl(self, query_id).await;
         if status.is_err() {
             let err = status.err().unwrap();
@@ -1142,11 +1589,7 @@ impl AthenaManager {
                 }
             };
 
-            if is_finished.is_err() {
-                return Err(is_finished.err().unwrap());
-            }
-
-            Ok(aws_operations.get_query_results_paginator(&self.client, query_id, page_size))
+            is_finished
         }
     }
     
@@ -1288,7 +1731,7 @@ impl Default for AthenaManager {
     fn default() -> Self {
        let config = aws_config::SdkConfig::builder().build();
         let client = AthenaClient::new(&config);
-        AthenaManager { client, database: "mock".to_string(), catalog: "mock".to_string()}
+        AthenaManager { client, database: "mock".to_string(), catalog: "mock".to_string(), polling_policy: PollingPolicy::default(), retry_policy: RetryPolicy::default(), conversion_options: ConversionOptions::default()}
     }
 }
 
@@ -1500,6 +1943,7 @@ pub mod start_query {
     use aws_smithy_http::body::SdkBody;
     use aws_smithy_types::error::metadata::ErrorMetadata;
     use aws_smithy_types::error::Unhandled;
+    use std::sync::{Arc, Mutex};
 
     #[tokio::test]
     async fn is_ok() {
@@ -1518,7 +1962,7 @@ pub mod start_query {
         mocks
             .expect_start_query_execution()
             .times(1)
-            .returning(move |_, _, _, _, _| {
+            .returning(move |_, _, _, _, _, _| {
                 let output = StartQueryExecutionOutput::builder()
                     .query_execution_id(query_id_clone)
                     .build();
@@ -1551,7 +1995,7 @@ pub mod start_query {
         mocks
             .expect_start_query_execution()
             .times(1)
-            .returning(move |_, _, _, _, _| {
+            .returning(move |_, _, _, _, _, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
@@ -1598,8 +2042,8 @@ pub mod start_query {
 
         mocks
             .expect_start_query_execution()
-            .times(1)
-            .returning(move |_, _, _, _, _| {
+            .times(3)
+            .returning(move |_, _, _, _, _, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
@@ -1621,16 +2065,121 @@ pub mod start_query {
         let res = AthenaManager::new_impl(catalog, database, &mocks).await;
         assert!(res.is_ok());
 
-        let athena_manager = res.unwrap();
+        let athena_manager = res.unwrap().with_retry_policy(
+            RetryPolicy::new()
+                .with_max_attempts(3)
+                .with_initial_delay(Duration::ZERO),
+        );
         let res = athena_manager
             .start_query_impl("some query", None, &mocks)
             .await;
         assert!(res.is_err());
-        let is_throttled = match res.err().unwrap() {
-            StartQueryError::RequestWasThrottled(_) => true,
-            _ => false,
+        let retries = match res.err().unwrap() {
+            StartQueryError::RequestWasThrottled(e) => e.data.unwrap()["retries"].clone(),
+            _ => panic!("expected RequestWasThrottled"),
         };
-        assert!(is_throttled);
+        assert_eq!(retries, 2);
+    }
+
+    #[tokio::test]
+    async fn is_ok_after_being_throttled() {
+        let catalog = "catalog";
+        let database = "database";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        let mut sequence = Sequence::new();
+        mocks
+            .expect_start_query_execution()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(move |_, _, _, _, _, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("an error has occurred")
+                    .code("TooManyRequestsException")
+                    .build();
+                let too_many_request_exception = TooManyRequestsException::builder()
+                    .message("an error has occurred")
+                    .meta(meta)
+                    .build();
+                let err =
+                    StartQueryExecutionError::TooManyRequestsException(too_many_request_exception);
+                let inner = http::Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            });
+        mocks
+            .expect_start_query_execution()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(move |_, _, _, _, _, _| {
+                Ok(StartQueryExecutionOutput::builder()
+                    .query_execution_id("query_id")
+                    .build())
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_initial_delay(Duration::ZERO));
+        let res = athena_manager
+            .start_query_impl("some query", None, &mocks)
+            .await;
+        assert_eq!(res.unwrap(), "query_id");
+    }
+
+    #[tokio::test]
+    async fn same_token_on_each_retry() {
+        let catalog = "catalog";
+        let database = "database";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        let tokens = Arc::new(Mutex::new(Vec::<String>::new()));
+        let tokens_clone = tokens.clone();
+        mocks
+            .expect_start_query_execution()
+            .times(3)
+            .returning(move |_, _, _, _, _, client_request_token| {
+                let mut tokens = tokens_clone.lock().unwrap();
+                tokens.push(client_request_token.to_string());
+                //The first two attempts time out, though Athena may have started the query.
+                if tokens.len() < 3 {
+                    return Err(SdkError::timeout_error("timed out"));
+                }
+                Ok(StartQueryExecutionOutput::builder()
+                    .query_execution_id("query_id")
+                    .build())
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_initial_delay(Duration::ZERO));
+        let res = athena_manager
+            .start_query_impl("some query", None, &mocks)
+            .await;
+        assert_eq!(res.unwrap(), "query_id");
+
+        let tokens = tokens.lock().unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert!(tokens[0].len() >= 32);
+        assert!(tokens.iter().all(|token| token == &tokens[0]));
     }
 
     #[tokio::test]
@@ -1647,7 +2196,7 @@ pub mod start_query {
         mocks
             .expect_start_query_execution()
             .times(1)
-            .returning(move |_, _, _, _, _| {
+            .returning(move |_, _, _, _, _, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
@@ -1695,7 +2244,7 @@ pub mod start_query {
         mocks
             .expect_start_query_execution()
             .times(1)
-            .returning(move |_, _, _, _, _| {
+            .returning(move |_, _, _, _, _, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
@@ -1910,17 +2459,204 @@ pub mod get_query_status {
 
         let athena_manager = res.unwrap();
         let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
-        assert!(res.is_ok());
-        let status = res.unwrap();
-        let is_failed = match status {
-            AthenaQueryStatus::Failed(_) => true,
+        assert!(res.is_ok());
+        let status = res.unwrap();
+        let is_failed = match status {
+            AthenaQueryStatus::Failed(_) => true,
+            _ => false,
+        };
+        assert!(is_failed);
+    }
+
+    #[tokio::test]
+    async fn is_cancelled() {
+        let catalog = "catalog";
+        let database = "database";
+        let query_id = "query_id";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        mocks
+            .expect_get_query_execution()
+            .times(1)
+            .returning(move |_, _| {
+                let query_execution_status = QueryExecutionStatus::builder()
+                    .state(QueryExecutionState::Cancelled)
+                    .build();
+
+                let query_execution = QueryExecution::builder()
+                    .status(query_execution_status)
+                    .build();
+
+                let output = GetQueryExecutionOutput::builder()
+                    .query_execution(query_execution)
+                    .build();
+                Ok(output)
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res.unwrap();
+        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        assert!(res.is_ok());
+        let status = res.unwrap();
+        let is_cancelled = match status {
+            AthenaQueryStatus::Cancelled => true,
+            _ => false,
+        };
+        assert!(is_cancelled);
+    }
+
+    #[tokio::test]
+    async fn query_does_not_exist() {
+        let catalog = "catalog";
+        let database = "database";
+        let query_id = "query_id";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        mocks
+            .expect_get_query_execution()
+            .times(1)
+            .returning(|_, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("an error has occurred")
+                    .code("500")
+                    .build();
+                let invalid_request_exception = InvalidRequestException::builder()
+                    .message("an error has occurred")
+                    .meta(meta)
+                    .build();
+                let err =
+                    GetQueryExecutionError::InvalidRequestException(invalid_request_exception);
+                let inner = http::Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res.unwrap();
+        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        assert!(res.is_err());
+        let err = res.err().unwrap();
+        let is_query_does_not_exist = match err {
+            GetQueryStatusError::QueryDoesNotExist(_) => true,
+            _ => false,
+        };
+        assert!(is_query_does_not_exist);
+    }
+
+    #[tokio::test]
+    async fn internal_server_exception() {
+        let catalog = "catalog";
+        let database = "database";
+        let query_id = "query_id";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        mocks
+            .expect_get_query_execution()
+            .times(1)
+            .returning(|_, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("an error has occurred")
+                    .code("500")
+                    .build();
+                let internal_server_exception = InternalServerException::builder()
+                    .message("an error has occurred")
+                    .meta(meta)
+                    .build();
+                let err =
+                    GetQueryExecutionError::InternalServerException(internal_server_exception);
+                let inner = http::Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res.unwrap();
+        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        assert!(res.is_err());
+        let err = res.err().unwrap();
+        let is_unexpected = match err {
+            GetQueryStatusError::UnexpectedError(_) => true,
+            _ => false,
+        };
+        assert!(is_unexpected);
+    }
+
+    #[tokio::test]
+    async fn unhandled() {
+        let catalog = "catalog";
+        let database = "database";
+        let query_id = "query_id";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        mocks
+            .expect_get_query_execution()
+            .times(1)
+            .returning(|_, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("an error has occurred")
+                    .code("500")
+                    .build();
+                let unhandled_exception = Unhandled::builder()
+                    .source("an error has occurred")
+                    .meta(meta)
+                    .build();
+                let err = GetQueryExecutionError::Unhandled(unhandled_exception);
+                let inner = http::Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res.unwrap();
+        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        assert!(res.is_err());
+        let err = res.err().unwrap();
+        let is_unexpected = match err {
+            GetQueryStatusError::UnexpectedError(_) => true,
             _ => false,
         };
-        assert!(is_failed);
+        assert!(is_unexpected);
     }
 
     #[tokio::test]
-    async fn is_cancelled() {
+    async fn throttled() {
         let catalog = "catalog";
         let database = "database";
         let query_id = "query_id";
@@ -1933,38 +2669,52 @@ pub mod get_query_status {
 
         mocks
             .expect_get_query_execution()
-            .times(1)
-            .returning(move |_, _| {
-                let query_execution_status = QueryExecutionStatus::builder()
-                    .state(QueryExecutionState::Cancelled)
-                    .build();
-
-                let query_execution = QueryExecution::builder()
-                    .status(query_execution_status)
+            .times(2)
+            .returning(|_, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("Rate exceeded")
+                    .code("ThrottlingException")
                     .build();
-
-                let output = GetQueryExecutionOutput::builder()
-                    .query_execution(query_execution)
+                let unhandled_exception = Unhandled::builder()
+                    .source("Rate exceeded")
+                    .meta(meta)
                     .build();
-                Ok(output)
+                let err = GetQueryExecutionError::Unhandled(unhandled_exception);
+                let inner = http::Response::builder()
+                    .status(400)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
             });
 
         let res = AthenaManager::new_impl(catalog, database, &mocks).await;
         assert!(res.is_ok());
 
-        let athena_manager = res.unwrap();
+        let athena_manager = res.unwrap().with_retry_policy(
+            RetryPolicy::new()
+                .with_max_attempts(2)
+                .with_initial_delay(Duration::ZERO),
+        );
         let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
-        assert!(res.is_ok());
-        let status = res.unwrap();
-        let is_cancelled = match status {
-            AthenaQueryStatus::Cancelled => true,
-            _ => false,
+        assert!(res.is_err());
+        let retries = match res.err().unwrap() {
+            GetQueryStatusError::UnexpectedError(e) => e.data.unwrap()["retries"].clone(),
+            _ => panic!("expected UnexpectedError"),
         };
-        assert!(is_cancelled);
+        assert_eq!(retries, 1);
     }
+}
+
+#[cfg(test)]
+pub mod stop_query {
+    use super::*;
+    use aws_sdk_athena::types::error::{InternalServerException, InvalidRequestException};
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_types::error::metadata::ErrorMetadata;
 
     #[tokio::test]
-    async fn query_does_not_exist() {
+    async fn is_ok() {
         let catalog = "catalog";
         let database = "database";
         let query_id = "query_id";
@@ -1976,43 +2726,20 @@ pub mod get_query_status {
         });
 
         mocks
-            .expect_get_query_execution()
+            .expect_stop_query_execution()
             .times(1)
-            .returning(|_, _| {
-                let meta = ErrorMetadata::builder()
-                    .message("an error has occurred")
-                    .code("500")
-                    .build();
-                let invalid_request_exception = InvalidRequestException::builder()
-                    .message("an error has occurred")
-                    .meta(meta)
-                    .build();
-                let err =
-                    GetQueryExecutionError::InvalidRequestException(invalid_request_exception);
-                let inner = http::Response::builder()
-                    .status(200)
-                    .header("Content-Type", "application/json")
-                    .body(SdkBody::empty())
-                    .unwrap();
-                Err(SdkError::service_error(err, inner))
-            });
+            .returning(|_, _| Ok(StopQueryExecutionOutput::builder().build()));
 
         let res = AthenaManager::new_impl(catalog, database, &mocks).await;
         assert!(res.is_ok());
 
         let athena_manager = res.unwrap();
-        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
-        assert!(res.is_err());
-        let err = res.err().unwrap();
-        let is_query_does_not_exist = match err {
-            GetQueryStatusError::QueryDoesNotExist(_) => true,
-            _ => false,
-        };
-        assert!(is_query_does_not_exist);
+        let res = athena_manager.stop_query_impl(query_id, &mocks).await;
+        assert!(res.is_ok());
     }
 
     #[tokio::test]
-    async fn internal_server_exception() {
+    async fn query_does_not_exist() {
         let catalog = "catalog";
         let database = "database";
         let query_id = "query_id";
@@ -2024,19 +2751,19 @@ pub mod get_query_status {
         });
 
         mocks
-            .expect_get_query_execution()
+            .expect_stop_query_execution()
             .times(1)
             .returning(|_, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
                     .build();
-                let internal_server_exception = InternalServerException::builder()
+                let invalid_request_exception = InvalidRequestException::builder()
                     .message("an error has occurred")
                     .meta(meta)
                     .build();
                 let err =
-                    GetQueryExecutionError::InternalServerException(internal_server_exception);
+                    StopQueryExecutionError::InvalidRequestException(invalid_request_exception);
                 let inner = http::Response::builder()
                     .status(200)
                     .header("Content-Type", "application/json")
@@ -2049,18 +2776,17 @@ pub mod get_query_status {
         assert!(res.is_ok());
 
         let athena_manager = res.unwrap();
-        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        let res = athena_manager.stop_query_impl(query_id, &mocks).await;
         assert!(res.is_err());
-        let err = res.err().unwrap();
-        let is_unexpected = match err {
-            GetQueryStatusError::UnexpectedError(_) => true,
+        let is_query_does_not_exist = match res.err().unwrap() {
+            StopQueryError::QueryDoesNotExist(_) => true,
             _ => false,
         };
-        assert!(is_unexpected);
+        assert!(is_query_does_not_exist);
     }
 
     #[tokio::test]
-    async fn unhandled() {
+    async fn internal_server_exception() {
         let catalog = "catalog";
         let database = "database";
         let query_id = "query_id";
@@ -2072,18 +2798,19 @@ pub mod get_query_status {
         });
 
         mocks
-            .expect_get_query_execution()
+            .expect_stop_query_execution()
             .times(1)
             .returning(|_, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
                     .code("500")
                     .build();
-                let unhandled_exception = Unhandled::builder()
-                    .source("an error has occurred")
+                let internal_server_exception = InternalServerException::builder()
+                    .message("an error has occurred")
                     .meta(meta)
                     .build();
-                let err = GetQueryExecutionError::Unhandled(unhandled_exception);
+                let err =
+                    StopQueryExecutionError::InternalServerException(internal_server_exception);
                 let inner = http::Response::builder()
                     .status(200)
                     .header("Content-Type", "application/json")
@@ -2096,11 +2823,10 @@ pub mod get_query_status {
         assert!(res.is_ok());
 
         let athena_manager = res.unwrap();
-        let res = athena_manager.get_query_status_impl(query_id, &mocks).await;
+        let res = athena_manager.stop_query_impl(query_id, &mocks).await;
         assert!(res.is_err());
-        let err = res.err().unwrap();
-        let is_unexpected = match err {
-            GetQueryStatusError::UnexpectedError(_) => true,
+        let is_unexpected = match res.err().unwrap() {
+            StopQueryError::UnexpectedError(_) => true,
             _ => false,
         };
         assert!(is_unexpected);
@@ -2410,7 +3136,7 @@ pub mod get_query_results {
 
         mocks
             .expect_get_query_results()
-            .times(1)
+            .times(3)
             .returning(move |_, _| {
                 let meta = ErrorMetadata::builder()
                     .message("an error has occurred")
@@ -2433,16 +3159,20 @@ pub mod get_query_results {
         let res = AthenaManager::new_impl(catalog, database, &mocks).await;
         assert!(res.is_ok());
 
-        let athena_manager = res.unwrap();
+        let athena_manager = res.unwrap().with_retry_policy(
+            RetryPolicy::new()
+                .with_max_attempts(3)
+                .with_initial_delay(Duration::ZERO),
+        );
         let res = athena_manager
             .get_query_results_impl(query_id, None, &mocks)
             .await;
         assert!(res.is_err());
-        let is_throttled = match res.err().unwrap() {
-            GlyphxGetQueryResultsError::RequestWasThrottled(_) => true,
-            _ => false,
+        let retries = match res.err().unwrap() {
+            GlyphxGetQueryResultsError::RequestWasThrottled(e) => e.data.unwrap()["retries"].clone(),
+            _ => panic!("expected RequestWasThrottled"),
         };
-        assert!(is_throttled);
+        assert_eq!(retries, 2);
     }
 
     #[tokio::test]
@@ -2540,6 +3270,61 @@ pub mod get_query_results {
         };
         assert!(is_unexpected);
     }
+
+    #[tokio::test]
+    async fn is_conversion_error() {
+        let catalog = "catalog";
+        let database = "database";
+        let query_id = "query_id";
+
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+
+        mocks
+            .expect_get_query_results()
+            .times(1)
+            .returning(move |_, _| {
+                let row = Row::builder()
+                    .set_data(Some(vec![
+                        Datum::builder()
+                            .set_var_char_value(Some("abc".to_string()))
+                            .build(),
+                        Datum::builder()
+                            .set_var_char_value(Some("not a number".to_string()))
+                            .build(),
+                    ]))
+                    .build();
+                let result_set = ResultSet::builder()
+                    .result_set_metadata(get_result_set_metadata())
+                    .rows(row)
+                    .build();
+                let output = GetQueryResultsOutput::builder()
+                    .result_set(result_set)
+                    .build();
+                Ok(output)
+            });
+
+        let res = AthenaManager::new_impl(catalog, database, &mocks).await;
+        assert!(res.is_ok());
+
+        let athena_manager = res.unwrap().with_conversion_options(
+            ConversionOptions::new().with_report_conversion_failures(true),
+        );
+        let res = athena_manager
+            .get_query_results_impl(query_id, None, &mocks)
+            .await;
+        assert!(res.is_err());
+        match res.err().unwrap() {
+            GlyphxGetQueryResultsError::ConversionError(e) => {
+                assert_eq!(e.data.unwrap()["query_id"], query_id);
+                assert!(e.inner_error.unwrap()["UnableToConvertValue"].is_object());
+            }
+            _ => panic!("Expected a ConversionError"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2867,6 +3652,7 @@ mod convert_get_query_results_error_to_run_query_error {
 #[cfg(test)]
 mod run_query {
     use super::*;
+    use crate::aws::result_set_converter::convert_to_json;
 
     use aws_sdk_athena::types::{
         AthenaError, ColumnInfo, ColumnNullable, Datum, ResultSet, ResultSetMetadata, Row,
@@ -3808,6 +4594,286 @@ pub mod get_paged_query_results {
     }
 }
 
+#[cfg(test)]
+mod get_output_location_query_results {
+    use super::*;
+    use crate::aws::athena_stream_iterator::{ColumnInfo, ColumnNullable, ResultSet};
+    use crate::aws::object_store::MockObjectStore;
+    use crate::aws::s3_manager::GetObjectStreamError;
+    use aws_sdk_athena::types::{QueryExecution, ResultConfiguration};
+    use aws_sdk_s3::primitives::ByteStream;
+    use tokio_stream::StreamExt;
+
+    const FILE: &str = "\"id\",\"name\"\n\"1\",\"one\"\n\"2\",\n";
+
+    fn get_metadata_page() -> GetQueryResultsOutput {
+        let column = |name: &str, data_type: &str| {
+            ColumnInfo::builder()
+                .name(name)
+                .r#type(data_type)
+                .nullable(ColumnNullable::Nullable)
+                .build()
+        };
+        let metadata = ResultSetMetadata::builder()
+            .column_info(column("id", "integer"))
+            .column_info(column("name", "varchar"))
+            .build();
+        let result_set = ResultSet::builder().result_set_metadata(metadata).build();
+        GetQueryResultsOutput::builder().result_set(result_set).build()
+    }
+
+    fn get_mocks(output_location: Option<&'static str>) -> MockAthenaManagerOps {
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+        mocks
+            .expect_get_query_status_impl()
+            .returning(|_, _| Ok(AthenaQueryStatus::Succeeded));
+        mocks
+            .expect_get_query_execution()
+            .returning(move |_, _| {
+                let mut result_configuration = ResultConfiguration::builder();
+                if let Some(output_location) = output_location {
+                    result_configuration = result_configuration.output_location(output_location);
+                }
+                let query_execution = QueryExecution::builder()
+                    .result_configuration(result_configuration.build())
+                    .build();
+                Ok(GetQueryExecutionOutput::builder()
+                    .query_execution(query_execution)
+                    .build())
+            });
+        mocks
+            .expect_get_query_results_paginator()
+            .returning(|_, _, page_size| {
+                //We only need the column information from get_query_results.
+                assert_eq!(page_size, Some(1));
+                Box::new(tokio_stream::iter(vec![Ok(get_metadata_page())]))
+            });
+        mocks
+    }
+
+    fn get_object_store(bucket: &'static str) -> MockObjectStore {
+        let mut object_store = MockObjectStore::new();
+        object_store
+            .expect_get_bucket_name()
+            .returning(move || bucket.to_string());
+        object_store
+    }
+
+    #[tokio::test]
+    async fn is_ok() {
+        let mocks = get_mocks(Some("s3://bucket/results/query_id.csv"));
+        let mut object_store = get_object_store("bucket");
+        object_store
+            .expect_get_object_stream()
+            .times(1)
+            .returning(|key| {
+                assert_eq!(key, "results/query_id.csv");
+                Ok(ByteStream::from_static(FILE.as_bytes()))
+            });
+
+        let athena_manager = AthenaManager::new_impl("catalog", "database", &mocks)
+            .await
+            .unwrap();
+        let result = athena_manager
+            .get_output_location_query_results_impl("query_id", &object_store, None, &mocks)
+            .await;
+        assert!(result.is_ok());
+        let mut pages = result.unwrap();
+        let page = pages.next().await.unwrap().unwrap();
+        let result_set = page.result_set.unwrap();
+        assert_eq!(result_set.rows.unwrap().len(), 3);
+        assert_eq!(
+            result_set.result_set_metadata,
+            get_metadata_page().result_set.unwrap().result_set_metadata
+        );
+        assert!(pages.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn query_not_finished() {
+        let mut mocks = MockAthenaManagerOps::new();
+        mocks.expect_get_database().times(1).returning(|_, _, _| {
+            let output = GetDatabaseOutput::builder().build();
+            Ok(output)
+        });
+        mocks
+            .expect_get_query_status_impl()
+            .times(1)
+            .returning(|_, _| Ok(AthenaQueryStatus::Running));
+        mocks.expect_get_query_execution().times(0);
+        let object_store = MockObjectStore::new();
+
+        let athena_manager = AthenaManager::new_impl("catalog", "database", &mocks)
+            .await
+            .unwrap();
+        let result = athena_manager
+            .get_output_location_query_results_impl("query_id", &object_store, None, &mocks)
+            .await;
+        assert!(result.is_err());
+        let is_not_finished = match result.err().unwrap() {
+            GetQueryPagerError::QueryNotFinished(_) => true,
+            _ => false,
+        };
+        assert!(is_not_finished);
+    }
+
+    #[tokio::test]
+    async fn no_output_location() {
+        let mocks = get_mocks(None);
+        let object_store = get_object_store("bucket");
+
+        let athena_manager = AthenaManager::new_impl("catalog", "database", &mocks)
+            .await
+            .unwrap();
+        let result = athena_manager
+            .get_output_location_query_results_impl("query_id", &object_store, None, &mocks)
+            .await;
+        assert!(result.is_err());
+        let is_output_location_error = match result.err().unwrap() {
+            GetQueryPagerError::OutputLocationError(_) => true,
+            _ => false,
+        };
+        assert!(is_output_location_error);
+    }
+
+    #[tokio::test]
+    async fn wrong_bucket() {
+        let mocks = get_mocks(Some("s3://other-bucket/results/query_id.csv"));
+        let mut object_store = get_object_store("bucket");
+        object_store.expect_get_object_stream().times(0);
+
+        let athena_manager = AthenaManager::new_impl("catalog", "database", &mocks)
+            .await
+            .unwrap();
+        let result = athena_manager
+            .get_output_location_query_results_impl("query_id", &object_store, None, &mocks)
+            .await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            GetQueryPagerError::OutputLocationError(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["bucket"], "other-bucket");
+                assert_eq!(data["object_store_bucket"], "bucket");
+            }
+            _ => panic!("Expected an OutputLocationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn file_does_not_exist() {
+        let mocks = get_mocks(Some("s3://bucket/results/query_id.csv"));
+        let mut object_store = get_object_store("bucket");
+        object_store
+            .expect_get_object_stream()
+            .times(1)
+            .returning(|_| {
+                Err(GetObjectStreamError::KeyDoesNotExist(GlyphxErrorData::new(
+                    String::from("The key does not exist"),
+                    None,
+                    None,
+                )))
+            });
+
+        let athena_manager = AthenaManager::new_impl("catalog", "database", &mocks)
+            .await
+            .unwrap();
+        let result = athena_manager
+            .get_output_location_query_results_impl("query_id", &object_store, None, &mocks)
+            .await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            GetQueryPagerError::OutputLocationError(error_data) => {
+                assert!(error_data.inner_error.unwrap()["KeyDoesNotExist"].is_object());
+            }
+            _ => panic!("Expected an OutputLocationError"),
+        }
+    }
+
+    mod get_query_results_iterator {
+        use super::*;
+        use crate::aws::athena_stream_iterator::{Datum, Row};
+
+        async fn get_rows(
+            source: QueryResultsSource<'_>,
+            mocks: &MockAthenaManagerOps,
+        ) -> Vec<Value> {
+            let conversion_options = ConversionOptions::new().with_report_conversion_failures(true);
+            let athena_manager = AthenaManager::new_impl("catalog", "database", mocks)
+                .await
+                .unwrap()
+                .with_conversion_options(conversion_options);
+            let iterator = athena_manager
+                .get_query_results_iterator_impl("query_id", None, source, mocks)
+                .await;
+            assert!(iterator.is_ok());
+            let mut iterator = iterator.unwrap();
+            assert!(iterator.get_conversion_options().get_report_conversion_failures());
+            let mut rows = Vec::new();
+            while let Some(row) = iterator.next().await.unwrap() {
+                rows.push(row);
+            }
+            rows
+        }
+
+        #[tokio::test]
+        async fn output_location() {
+            let mocks = get_mocks(Some("s3://bucket/results/query_id.csv"));
+            let mut object_store = get_object_store("bucket");
+            object_store
+                .expect_get_object_stream()
+                .times(1)
+                .returning(|_| Ok(ByteStream::from_static(FILE.as_bytes())));
+
+            let rows = get_rows(QueryResultsSource::OutputLocation(&object_store), &mocks).await;
+            assert_eq!(
+                rows,
+                vec![json!({"id": 1, "name": "one"}), json!({"id": 2, "name": null})]
+            );
+        }
+
+        #[tokio::test]
+        async fn get_query_results() {
+            let mut mocks = MockAthenaManagerOps::new();
+            mocks.expect_get_database().times(1).returning(|_, _, _| {
+                let output = GetDatabaseOutput::builder().build();
+                Ok(output)
+            });
+            mocks
+                .expect_get_query_status_impl()
+                .times(1)
+                .returning(|_, _| Ok(AthenaQueryStatus::Succeeded));
+            mocks.expect_get_query_execution().times(0);
+            mocks
+                .expect_get_query_results_paginator()
+                .times(1)
+                .returning(|_, _, _| {
+                    let row = |values: [&str; 2]| {
+                        Row::builder()
+                            .data(Datum::builder().var_char_value(values[0]).build())
+                            .data(Datum::builder().var_char_value(values[1]).build())
+                            .build()
+                    };
+                    let mut page = get_metadata_page();
+                    let result_set = page.result_set.take().unwrap();
+                    let result_set = ResultSet::builder()
+                        .set_result_set_metadata(result_set.result_set_metadata)
+                        .rows(row(["id", "name"]))
+                        .rows(row(["1", "one"]))
+                        .build();
+                    page.result_set = Some(result_set);
+                    Box::new(tokio_stream::iter(vec![Ok(page)]))
+                });
+
+            let rows = get_rows(QueryResultsSource::GetQueryResults, &mocks).await;
+            assert_eq!(rows, vec![json!({"id": 1, "name": "one"})]);
+        }
+    }
+}
+
 #[cfg(test)]
 mod table_exists {
     use super::*;
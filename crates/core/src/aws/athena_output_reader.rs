@@ -0,0 +1,520 @@
+//! Reads the results of an Athena query from the CSV file that Athena writes to the output
+//! location of the query.  Streaming that one file is much faster than paging through
+//! get_query_results 1000 rows at a time.  The records of the file are grouped into the same
+//! pages of rows that get_query_results returns, with the column information of the query
+//! attached, so that they are typed exactly as the paged results are.
+use super::athena_manager::AthenaStreamIteratorError;
+use super::athena_stream_iterator::{
+    Datum, GetQueryResultsOutput, ResultSet, ResultSetMetadata, Row,
+};
+use crate::types::error::GlyphxErrorData;
+
+use aws_sdk_s3::primitives::ByteStream;
+use bytes::Bytes;
+use csv_core::ReadFieldResult;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio_stream::Stream;
+
+///By default we return 1000 rows per page, which is what get_query_results returns.
+const DEFAULT_PAGE_SIZE: usize = 1000;
+
+///The values of one record of the results file, None for a null.
+type Record = Vec<Option<String>>;
+
+///The bucket and key of the results file that Athena wrote for a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputLocation {
+    bucket: String,
+    key: String,
+}
+
+impl OutputLocation {
+    ///Parses an s3://bucket/key uri, returning None if it is not one.
+    pub fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix("s3://")?;
+        let (bucket, key) = path.split_once('/')?;
+        if bucket.is_empty() || key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    pub fn get_bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+///The number of bytes of a value that csv_core writes out at a time.
+const OUTPUT_SIZE: usize = 4096;
+
+///Reads the records of the results file as its bytes arrive, using csv_core to handle quotes,
+///embedded newlines and CRLF line endings.  Athena quotes every value that it writes and leaves
+///nulls empty, so we track whether each value was quoted, and an empty value that was not
+///quoted is None.
+struct RecordReader {
+    reader: csv_core::Reader,
+    output: Box<[u8]>,
+    field: Vec<u8>,
+    quoted: Option<bool>,
+    record: Record,
+}
+
+impl RecordReader {
+    fn new() -> Self {
+        Self {
+            reader: csv_core::Reader::new(),
+            output: vec![0; OUTPUT_SIZE].into_boxed_slice(),
+            field: Vec::new(),
+            quoted: None,
+            record: Vec::new(),
+        }
+    }
+
+    ///Reads input until a record is complete.  Returns the record, or None if input ran out
+    ///first, and the number of bytes of input that were used.  An empty input is the end of
+    ///the file, after which any partial record is returned.
+    fn read_record(&mut self, input: &[u8]) -> Result<(Option<Record>, usize), String> {
+        let mut position = 0;
+        loop {
+            //csv_core takes an empty input as the end of the file, so we stop at the end of ours.
+            if position == input.len() && position > 0 {
+                return Ok((None, position));
+            }
+            let (result, read, written) =
+                self.reader.read_field(&input[position..], &mut self.output);
+            //The line ending of the last record may be read along with the next value.
+            if self.quoted.is_none() {
+                self.quoted = input[position..position + read]
+                    .iter()
+                    .find(|byte| **byte != b'\r' && **byte != b'\n')
+                    .map(|byte| *byte == b'"');
+            }
+            position += read;
+            self.field.extend_from_slice(&self.output[..written]);
+            match result {
+                ReadFieldResult::InputEmpty | ReadFieldResult::End => return Ok((None, position)),
+                ReadFieldResult::OutputFull => {}
+                ReadFieldResult::Field { record_end } => {
+                    let field = self.finish_field()?;
+                    self.record.push(field);
+                    if record_end {
+                        return Ok((Some(std::mem::take(&mut self.record)), position));
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish_field(&mut self) -> Result<Option<String>, String> {
+        let value = std::mem::take(&mut self.field);
+        let quoted = self.quoted.take().unwrap_or(false);
+        if value.is_empty() && !quoted {
+            return Ok(None);
+        }
+        String::from_utf8(value)
+            .map(Some)
+            .map_err(|e| format!("A value in the file is not valid UTF-8 : {}", e))
+    }
+}
+
+fn get_row(fields: Record) -> Row {
+    let data = fields
+        .into_iter()
+        .map(|field| match field {
+            Some(value) => Datum::builder().var_char_value(value).build(),
+            None => Datum::builder().build(),
+        })
+        .collect();
+    Row::builder().set_data(Some(data)).build()
+}
+
+///A Stream over the results file of a query that returns the same pages as the
+///get_query_results paginator, so it can be read with AthenaStreamIterator::from_pages.  The
+///first row of the first page is the header row of the file.
+pub struct OutputLocationPages {
+    body: Pin<Box<ByteStream>>,
+    metadata: ResultSetMetadata,
+    page_size: usize,
+    reader: RecordReader,
+    chunk: Bytes,
+    position: usize,
+    rows: Vec<Row>,
+    records_read: usize,
+    body_finished: bool,
+    exhausted: bool,
+    error_data: Value,
+}
+
+impl OutputLocationPages {
+    ///# Arguments
+    ///* `body` - The contents of the results file.
+    ///* `metadata` - The column information of the query, which the results file does not hold.
+    ///* `page_size` - The number of rows to return per page.  The default is 1000.
+    ///* `error_data` - The data that is included in any errors, i.e. the query id and location.
+    pub fn new(
+        body: ByteStream,
+        metadata: ResultSetMetadata,
+        page_size: Option<i32>,
+        error_data: Value,
+    ) -> Self {
+        let page_size = page_size
+            .filter(|page_size| *page_size > 0)
+            .map(|page_size| page_size as usize)
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+        Self {
+            body: Box::pin(body),
+            metadata,
+            page_size,
+            reader: RecordReader::new(),
+            chunk: Bytes::new(),
+            position: 0,
+            rows: Vec::new(),
+            records_read: 0,
+            body_finished: false,
+            exhausted: false,
+            error_data,
+        }
+    }
+
+    pub fn get_page_size(&self) -> usize {
+        self.page_size
+    }
+
+    ///Reads records from the current chunk of the body into rows until we have a full page or
+    ///need the next chunk.
+    fn read_records(&mut self) -> Result<(), AthenaStreamIteratorError> {
+        let column_count = self
+            .metadata
+            .column_info
+            .as_ref()
+            .map(|columns| columns.len())
+            .unwrap_or(0);
+        while self.rows.len() < self.page_size {
+            let input = &self.chunk[self.position..];
+            if input.is_empty() && !self.body_finished {
+                break;
+            }
+            let record = self.reader.read_record(input);
+            if record.is_err() {
+                return Err(self.get_read_error(record.err().unwrap()));
+            }
+            let (fields, length) = record.unwrap();
+            self.position += length;
+            let fields = match fields {
+                Some(fields) => fields,
+                None => break,
+            };
+            self.records_read += 1;
+            if fields.len() != column_count {
+                return Err(self.get_read_error(format!(
+                    "Record {} has {} values but the query returned {} columns",
+                    self.records_read,
+                    fields.len(),
+                    column_count
+                )));
+            }
+            self.rows.push(get_row(fields));
+        }
+        Ok(())
+    }
+
+    fn take_page(&mut self) -> GetQueryResultsOutput {
+        let rows = std::mem::take(&mut self.rows);
+        let result_set = ResultSet::builder()
+            .result_set_metadata(self.metadata.clone())
+            .set_rows(Some(rows))
+            .build();
+        GetQueryResultsOutput::builder().result_set(result_set).build()
+    }
+
+    fn get_read_error(&self, message: String) -> AthenaStreamIteratorError {
+        let mut data = self.error_data.clone();
+        data["record"] = json!(self.records_read);
+        AthenaStreamIteratorError::ReadError(GlyphxErrorData::new(message, Some(data), None))
+    }
+
+    fn fail(&mut self, error: AthenaStreamIteratorError) -> Poll<Option<<Self as Stream>::Item>> {
+        self.exhausted = true;
+        self.chunk.clear();
+        self.rows.clear();
+        Poll::Ready(Some(Err(error)))
+    }
+}
+
+impl Stream for OutputLocationPages {
+    type Item = Result<GetQueryResultsOutput, AthenaStreamIteratorError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.exhausted {
+                return Poll::Ready(None);
+            }
+            if let Err(error) = this.read_records() {
+                return this.fail(error);
+            }
+            if this.rows.len() >= this.page_size || (this.body_finished && !this.rows.is_empty())
+            {
+                return Poll::Ready(Some(Ok(this.take_page())));
+            }
+            if this.body_finished {
+                this.exhausted = true;
+                return Poll::Ready(None);
+            }
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(bytes)) => {
+                    this.chunk = bytes;
+                    this.position = 0;
+                }
+                Some(Err(error)) => {
+                    let message = format!("Unable to read the results file : {}", error);
+                    let error = this.get_read_error(message);
+                    return this.fail(error);
+                }
+                None => this.body_finished = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod output_location {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let location = OutputLocation::parse("s3://my-bucket/results/query_id.csv").unwrap();
+        assert_eq!(location.get_bucket(), "my-bucket");
+        assert_eq!(location.get_key(), "results/query_id.csv");
+    }
+
+    #[test]
+    fn is_not_s3() {
+        assert!(OutputLocation::parse("https://my-bucket/query_id.csv").is_none());
+    }
+
+    #[test]
+    fn has_no_key() {
+        assert!(OutputLocation::parse("s3://my-bucket").is_none());
+        assert!(OutputLocation::parse("s3://my-bucket/").is_none());
+    }
+}
+
+#[cfg(test)]
+mod read_record {
+    use super::*;
+
+    fn values(record: &[&str]) -> Record {
+        record.iter().map(|value| Some(value.to_string())).collect()
+    }
+
+    ///Reads each input in turn and then the end of the file, returning every record read.  An
+    ///empty input is the end of the file, so we only pass one once the inputs are used up.
+    fn read_all(inputs: &[&[u8]]) -> Vec<Record> {
+        let mut reader = RecordReader::new();
+        let mut records = Vec::new();
+        for input in inputs {
+            let mut position = 0;
+            while position < input.len() {
+                let (record, length) = reader.read_record(&input[position..]).unwrap();
+                position += length;
+                records.extend(record);
+            }
+        }
+        while let (Some(record), _) = reader.read_record(b"").unwrap() {
+            records.push(record);
+        }
+        records
+    }
+
+    #[test]
+    fn is_ok() {
+        let mut reader = RecordReader::new();
+        let (record, length) = reader.read_record(b"\"a\",\"b\"\n\"c\"").unwrap();
+        assert_eq!(record, Some(values(&["a", "b"])));
+        assert_eq!(length, 8);
+    }
+
+    #[test]
+    fn empty_unquoted_value_is_null() {
+        let records = read_all(&[b"\"a\",,\"\"\n"]);
+        assert_eq!(records, vec![vec![Some("a".to_string()), None, Some(String::new())]]);
+    }
+
+    #[test]
+    fn doubled_quote_is_a_quote() {
+        let records = read_all(&[b"\"say \"\"hi\"\"\"\n"]);
+        assert_eq!(records, vec![values(&["say \"hi\""])]);
+    }
+
+    #[test]
+    fn newline_in_quotes_is_a_value() {
+        let records = read_all(&[b"\"a\nb\",\"c\"\r\n\"d\",\r\n"]);
+        assert_eq!(
+            records,
+            vec![values(&["a\nb", "c"]), vec![Some("d".to_string()), None]]
+        );
+    }
+
+    #[test]
+    fn record_across_inputs() {
+        //Split inside a doubled quote, a CRLF line ending and a null.
+        let records = read_all(&[b"\"a\"", b"\"b\",", b"\"c\"\r", b"\n", b",\"d\"\n"]);
+        assert_eq!(
+            records,
+            vec![values(&["a\"b", "c"]), vec![None, Some("d".to_string())]]
+        );
+    }
+
+    #[test]
+    fn incomplete_record_is_none() {
+        let mut reader = RecordReader::new();
+        let (record, length) = reader.read_record(b"\"a\",\"b").unwrap();
+        assert!(record.is_none());
+        assert_eq!(length, 6);
+    }
+
+    #[test]
+    fn last_record_without_newline() {
+        let records = read_all(&[b"\"a\","]);
+        assert_eq!(records, vec![vec![Some("a".to_string()), None]]);
+        assert!(read_all(&[b""]).is_empty());
+    }
+
+    #[test]
+    fn value_longer_than_the_output() {
+        let value = "a".repeat(OUTPUT_SIZE * 2 + 1);
+        let records = read_all(&[format!("\"{}\"\n", value).as_bytes()]);
+        assert_eq!(records, vec![values(&[&value])]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut reader = RecordReader::new();
+        assert!(reader.read_record(b"\"\xff\"\n").is_err());
+    }
+}
+
+#[cfg(test)]
+mod output_location_pages {
+    use super::*;
+    use crate::aws::athena_stream_iterator::{AthenaStreamIterator, ColumnInfo, ColumnNullable};
+    use std::path::PathBuf;
+    use tokio_stream::StreamExt;
+
+    const FILE: &str = concat!(
+        "\"id\",\"name\",\"amount\"\n",
+        "\"1\",\"one\",\"1.5\"\n",
+        "\"2\",,\n",
+        "\"3\",\"a \"\"b\"\"\",\"3.25\"\n"
+    );
+
+    fn get_metadata() -> ResultSetMetadata {
+        let column = |name: &str, data_type: &str| {
+            ColumnInfo::builder()
+                .name(name)
+                .r#type(data_type)
+                .nullable(ColumnNullable::Nullable)
+                .build()
+        };
+        ResultSetMetadata::builder()
+            .column_info(column("id", "integer"))
+            .column_info(column("name", "varchar"))
+            .column_info(column("amount", "double"))
+            .build()
+    }
+
+    ///Reads the file in chunks of chunk_size bytes, so that records span chunks.  The file is
+    ///not opened until the body is read, so the caller removes it once it is done.
+    async fn get_body(file: &str, chunk_size: usize) -> (ByteStream, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "athena_output_reader_{}_{}.csv",
+            std::process::id(),
+            chunk_size
+        ));
+        std::fs::write(&path, file).unwrap();
+        let body = ByteStream::read_from()
+            .path(&path)
+            .buffer_size(chunk_size)
+            .build()
+            .await
+            .unwrap();
+        (body, path)
+    }
+
+    fn get_row_count(page: &GetQueryResultsOutput) -> usize {
+        page.result_set.as_ref().unwrap().rows.as_ref().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn is_ok() {
+        let (body, path) = get_body(FILE, 5).await;
+        let mut pages = OutputLocationPages::new(body, get_metadata(), Some(3), json!({}));
+        let first_page = pages.next().await.unwrap().unwrap();
+        assert_eq!(get_row_count(&first_page), 3);
+        let result_set = first_page.result_set.as_ref().unwrap();
+        assert_eq!(result_set.result_set_metadata, Some(get_metadata()));
+        let second_page = pages.next().await.unwrap().unwrap();
+        assert_eq!(get_row_count(&second_page), 1);
+        assert!(pages.next().await.is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rows_are_typed_by_the_metadata() {
+        let (body, path) = get_body(FILE, 7).await;
+        let pages = OutputLocationPages::new(body, get_metadata(), Some(2), json!({}));
+        let mut iterator =
+            AthenaStreamIterator::from_pages(Box::new(pages), "query_id", "catalog", "database");
+        let mut rows = Vec::new();
+        while let Some(row) = iterator.next().await.unwrap() {
+            rows.push(row);
+        }
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                json!({"id": 1, "name": "one", "amount": 1.5}),
+                json!({"id": 2, "name": null, "amount": null}),
+                json!({"id": 3, "name": "a \"b\"", "amount": 3.25}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_number_of_values() {
+        let file = "\"id\",\"name\",\"amount\"\n\"1\",\"one\"\n";
+        let mut pages = OutputLocationPages::new(
+            ByteStream::from(file.as_bytes().to_vec()),
+            get_metadata(),
+            None,
+            json!({"query_id": "query_id"}),
+        );
+        let result = pages.next().await.unwrap();
+        match result {
+            Err(AthenaStreamIteratorError::ReadError(error_data)) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["query_id"], "query_id");
+                assert_eq!(data["record"], 2);
+            }
+            _ => panic!("Expected a ReadError"),
+        }
+        assert!(pages.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn empty_file() {
+        let mut pages =
+            OutputLocationPages::new(ByteStream::from_static(b""), get_metadata(), None, json!({}));
+        assert!(pages.next().await.is_none());
+    }
+}
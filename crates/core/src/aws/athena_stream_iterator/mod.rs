@@ -10,8 +10,11 @@ pub use aws_sdk_athena::operation::get_query_results::GetQueryResultsError as Aw
 use tokio_stream::{Stream, StreamExt};
 
 use super::{
-    athena_manager::{AthenaStreamIteratorError, GlyphxGetQueryResultsError},
-    result_set_converter::convert_to_json,
+    athena_manager::{
+        AthenaStreamIteratorError, GlyphxGetQueryResultsError, ResultSetConversionError,
+    },
+    record_batch_converter::convert_to_record_batch,
+    result_set_converter::{convert_to_json_with_options, ConversionOptions},
 };
 //We are re-exporting the types from the aws_sdk_athena crate so that we can use them in the other
 //crates to avoid having to import them in each file.
@@ -19,15 +22,34 @@ pub use aws_sdk_athena::types::error::InternalServerException;
 pub use aws_smithy_http::body::SdkBody;
 pub use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
 pub use aws_smithy_types::error::ErrorMetadata;
-use serde_json::Value;
+use crate::types::error::GlyphxErrorData;
+use arrow_array::RecordBatch;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
+type QueryResultsPage = Result<GetQueryResultsOutput, AthenaStreamIteratorError>;
+type QueryResultsStream = Box<dyn Stream<Item = QueryResultsPage> + Unpin + Send>;
+
+///By default we fetch one page ahead of the page that is being read.
+const DEFAULT_PREFETCH_PAGES: usize = 1;
+
+///Streams the rows of an Athena query, one JSON object per row, from the pages returned by the
+///get_query_results paginator.  While the rows of one page are being read, the following pages
+///are fetched in the background so that the consumer does not have to wait on each page.
 pub struct AthenaStreamIterator {
-    results: Box<
-        dyn Stream<Item = Result<GetQueryResultsOutput, SdkError<GetQueryResultsError>>> + Unpin + Send,
-    >,
-    query_output_results: Option<Vec<Value>>,
-    query_output_size: usize,
-    current_row: usize,
+    results: Option<QueryResultsStream>,
+    prefetched_results: Option<mpsc::Receiver<QueryResultsPage>>,
+    prefetch_task: Option<JoinHandle<()>>,
+    prefetch_pages: usize,
+    conversion_options: ConversionOptions,
+    rows: VecDeque<Value>,
     query_id: String,
     catalog: String,
     database: String,
@@ -44,12 +66,39 @@ impl AthenaStreamIterator {
         query_id: &str,
         catalog: &str,
         database: &str,
+    ) -> Self {
+        let (error_catalog, error_database, error_query_id) =
+            (catalog.to_string(), database.to_string(), query_id.to_string());
+        let results = results.map(move |page| {
+            page.map_err(|error| {
+                GlyphxGetQueryResultsError::from_aws_get_query_result_error(
+                    error.into_service_error(),
+                    &error_catalog,
+                    &error_database,
+                    &error_query_id,
+                )
+                .into()
+            })
+        });
+        Self::from_pages(Box::new(results), query_id, catalog, database)
+    }
+
+    ///Creates an iterator over pages of results that were not read with the get_query_results
+    ///paginator, i.e. the results file that Athena wrote to the output location of the query.
+    ///Like the paginator, the first row of the first page must be the header row.
+    pub fn from_pages(
+        pages: QueryResultsStream,
+        query_id: &str,
+        catalog: &str,
+        database: &str,
     ) -> Self {
         Self {
-            results,
-            query_output_results: None,
-            query_output_size: 0,
-            current_row: 0,
+            results: Some(pages),
+            prefetched_results: None,
+            prefetch_task: None,
+            prefetch_pages: DEFAULT_PREFETCH_PAGES,
+            conversion_options: ConversionOptions::default(),
+            rows: VecDeque::new(),
             query_id: query_id.to_string(),
             catalog: catalog.to_string(),
             database: database.to_string(),
@@ -58,75 +107,311 @@ impl AthenaStreamIterator {
         }
     }
 
-    async fn reload(&mut self) -> Result<Option<()>, GlyphxGetQueryResultsError> {
-        self.current_row = 0;
-        //Get the next page of results from the stream.
-        let next_result = self.results.next().await;
+    ///The number of pages that may be fetched ahead of the page that is being read.  0 turns
+    ///prefetching off so that each page is only fetched once the previous one has been read.
+    ///This has no effect once the first row has been read.
+    pub fn with_prefetch_pages(mut self, prefetch_pages: usize) -> Self {
+        self.prefetch_pages = prefetch_pages;
+        self
+    }
+
+    pub fn get_prefetch_pages(&self) -> usize {
+        self.prefetch_pages
+    }
+
+    ///Sets how the values of each page are converted to JSON.  When conversion failures are
+    ///reported, a value that can not be converted ends the stream with a GetQueryResultsError.
+    pub fn with_conversion_options(mut self, conversion_options: ConversionOptions) -> Self {
+        self.conversion_options = conversion_options;
+        self
+    }
+
+    pub fn get_conversion_options(&self) -> &ConversionOptions {
+        &self.conversion_options
+    }
 
-        if next_result.is_none() {
-            //The stream has ended, we have exhausted the results.
-            self.exhausted = true;
-            return Ok(None);
+    ///Returns the next row of the results, or None once the results have been exhausted.
+    pub async fn next(&mut self) -> Result<Option<Value>, AthenaStreamIteratorError> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .transpose()
+    }
+
+    ///Returns the next row of the results deserialized into T, or None once the results have
+    ///been exhausted.  Each column of the row is a field of T, named after the column.
+    pub async fn next_as<T: DeserializeOwned>(
+        &mut self,
+    ) -> Result<Option<T>, AthenaStreamIteratorError> {
+        match self.next().await? {
+            Some(row) => self.deserialize_row(row).map(Some),
+            None => Ok(None),
         }
+    }
 
-        let next_result = next_result.unwrap();
-        if next_result.is_err() {
-            let error = next_result.err().unwrap().into_service_error();
-            let error = GlyphxGetQueryResultsError::from_aws_get_query_result_error(
-                error,
-                &self.catalog,
-                &self.database,
-                &self.query_id,
-            );
-            return Err(error);
+    ///Turns this iterator into a Stream of Arrow RecordBatches, one for each page of the results,
+    ///which are converted without building a JSON object for each row.  This should be called
+    ///before any rows are read, the rows of a page that has already been loaded are not included.
+    pub fn record_batches(self) -> AthenaRecordBatchStream {
+        AthenaRecordBatchStream { inner: self }
+    }
+
+    ///Turns this iterator into a Stream of rows that are deserialized into T.
+    pub fn typed<T: DeserializeOwned>(self) -> TypedAthenaStream<T> {
+        TypedAthenaStream {
+            inner: self,
+            row_type: PhantomData,
+        }
+    }
+
+    fn deserialize_row<T: DeserializeOwned>(
+        &self,
+        row: Value,
+    ) -> Result<T, AthenaStreamIteratorError> {
+        serde::Deserialize::deserialize(&row).map_err(|e| {
+            let message = format!("Unable to deserialize the row : {}", e);
+            let data = json!({
+                "query_id": self.query_id,
+                "catalog": self.catalog,
+                "database": self.database,
+                "type": std::any::type_name::<T>(),
+                "row": row,
+            });
+            AthenaStreamIteratorError::DeserializationError(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            ))
+        })
+    }
+
+    ///Moves the paginator into a task that pushes its pages into a channel that only holds
+    ///prefetch_pages pages, so that the task waits for us to catch up once it is full.  If we
+    ///are not running in a tokio runtime the pages are read directly from the paginator.
+    fn start_prefetch(&mut self) {
+        if self.prefetch_pages == 0 || tokio::runtime::Handle::try_current().is_err() {
+            return;
         }
-        let query_output_results = next_result.unwrap();
+        let mut results = match self.results.take() {
+            Some(results) => results,
+            None => return,
+        };
+        let (sender, receiver) = mpsc::channel(self.prefetch_pages);
+        self.prefetch_task = Some(tokio::spawn(async move {
+            while let Some(page) = results.next().await {
+                let is_err = page.is_err();
+                if sender.send(page).await.is_err() || is_err {
+                    break;
+                }
+            }
+        }));
+        self.prefetched_results = Some(receiver);
+    }
+
+    fn poll_page(&mut self, cx: &mut Context<'_>) -> Poll<Option<QueryResultsPage>> {
+        if self.prefetched_results.is_none() {
+            self.start_prefetch();
+        }
+        if let Some(receiver) = self.prefetched_results.as_mut() {
+            return receiver.poll_recv(cx);
+        }
+        match self.results.as_mut() {
+            Some(results) => Pin::new(results).poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
+
+    ///Converts a page of results into rows.  Returns false if the page was empty, which is the
+    ///only reliable way to tell that we have reached the end of the results.
+    fn load_page(
+        &mut self,
+        page: GetQueryResultsOutput,
+    ) -> Result<bool, AthenaStreamIteratorError> {
         //There is always a result set in the output, so we should never get a None.
-        let result_set = query_output_results.result_set.as_ref().unwrap();
+        let result_set = page.result_set.as_ref().unwrap();
         //there are always rows, but they will be emptry if we have exhausted the result set.
-        //this is the only to reliably check that we are at the end of the results.
         let rows = result_set.rows.as_ref().unwrap();
         if rows.is_empty() {
-            self.exhausted = true;
-            return Ok(None);
+            return Ok(false);
+        }
+
+        //If this is the first page, we need to skip the first row, as it is the header row.
+        let includes_header_row = self.first_page;
+        self.first_page = false;
+        let converted_result_set = convert_to_json_with_options(
+            result_set,
+            Some(includes_header_row),
+            &self.conversion_options,
+        );
+        if converted_result_set.is_err() {
+            return Err(self.convert_conversion_error(converted_result_set.err().unwrap()));
         }
+        let converted_result_set = converted_result_set.unwrap();
+        self.rows
+            .extend(converted_result_set.as_array().unwrap().to_vec());
+        Ok(true)
+    }
 
-        let converted_result_set = convert_to_json(result_set, Some(false));
-        let mut converted_result_set = converted_result_set.as_array().unwrap().to_vec();
-        //If this is the first page, we need to remove the first row, as it is the header row.
-        if self.first_page {
+    ///Polls for the next page of the results and converts it into a RecordBatch.
+    fn poll_next_batch(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<RecordBatch, AthenaStreamIteratorError>>> {
+        loop {
+            if self.exhausted {
+                return Poll::Ready(None);
+            }
+            let page = match ready!(self.poll_page(cx)) {
+                None => {
+                    self.finish();
+                    return Poll::Ready(None);
+                }
+                Some(Err(error)) => return Poll::Ready(Some(Err(error))),
+                Some(Ok(page)) => page,
+            };
+            let result_set = page.result_set.as_ref().unwrap();
+            if result_set.rows.as_ref().unwrap().is_empty() {
+                self.finish();
+                return Poll::Ready(None);
+            }
+            let includes_header_row = self.first_page;
             self.first_page = false;
-            converted_result_set.remove(0);
+            let batch = convert_to_record_batch(
+                result_set,
+                Some(includes_header_row),
+                &self.conversion_options,
+            );
+            if batch.is_err() {
+                //The rows that follow a value we could not convert can not be trusted.
+                self.finish();
+                return Poll::Ready(Some(Err(self.convert_conversion_error(batch.err().unwrap()))));
+            }
+            let batch = batch.unwrap();
+            //A first page that only holds the header row has nothing for us to return.
+            if batch.num_rows() > 0 {
+                return Poll::Ready(Some(Ok(batch)));
+            }
         }
-        let result_set_size = converted_result_set.len();
+    }
 
-        self.query_output_results = Some(converted_result_set.to_vec());
-        self.query_output_size = result_set_size;
-        //There is data in the result set
-        return Ok(Some(()));
+    fn convert_conversion_error(
+        &self,
+        error: ResultSetConversionError,
+    ) -> AthenaStreamIteratorError {
+        GlyphxGetQueryResultsError::from_result_set_conversion_error(
+            error,
+            &self.catalog,
+            &self.database,
+            &self.query_id,
+        )
+        .into()
     }
-    pub async fn next(&mut self) -> Result<Option<Value>, AthenaStreamIteratorError> {
-        if self.exhausted {
-            //You have hit the end of the result set, there is no more data to return,
-            return Ok(None);
+
+    ///Marks the results as exhausted and stops fetching pages.
+    fn finish(&mut self) {
+        self.exhausted = true;
+        self.rows.clear();
+        self.prefetched_results = None;
+        self.results = None;
+        if let Some(prefetch_task) = self.prefetch_task.take() {
+            prefetch_task.abort();
         }
-        if self.query_output_results.is_none() {
-            let reloaded = self.reload().await?;
-            if reloaded.is_none() {
-                return Ok(None);
+    }
+}
+
+impl Stream for AthenaStreamIterator {
+    type Item = Result<Value, AthenaStreamIteratorError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.exhausted {
+                //You have hit the end of the result set, there is no more data to return,
+                return Poll::Ready(None);
+            }
+            if let Some(row) = this.rows.pop_front() {
+                return Poll::Ready(Some(Ok(row)));
+            }
+            match ready!(this.poll_page(cx)) {
+                None => {
+                    //The stream has ended, we have exhausted the results.
+                    this.finish();
+                    return Poll::Ready(None);
+                }
+                Some(Err(error)) => return Poll::Ready(Some(Err(error))),
+                Some(Ok(page)) => match this.load_page(page) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        this.finish();
+                        return Poll::Ready(None);
+                    }
+                    Err(error) => {
+                        //The rows that follow a value we could not convert can not be trusted.
+                        this.finish();
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                },
             }
         }
+    }
+}
 
-        if self.current_row >= self.query_output_size {
-            let reloaded = self.reload().await?;
-            if reloaded.is_none() {
-                return Ok(None);
-            }
+impl Drop for AthenaStreamIterator {
+    fn drop(&mut self) {
+        if let Some(prefetch_task) = self.prefetch_task.take() {
+            prefetch_task.abort();
         }
-        let result_set = self.query_output_results.as_ref().unwrap();
-        let row = result_set[self.current_row].clone();
-        self.current_row += 1;
-        Ok(Some(row))
+    }
+}
+
+///A Stream over the pages of an AthenaStreamIterator that converts each page into an Arrow
+///RecordBatch.
+pub struct AthenaRecordBatchStream {
+    inner: AthenaStreamIterator,
+}
+
+impl AthenaRecordBatchStream {
+    ///Returns the next batch of rows, or None once the results have been exhausted.
+    pub async fn next(&mut self) -> Result<Option<RecordBatch>, AthenaStreamIteratorError> {
+        poll_fn(|cx| self.inner.poll_next_batch(cx)).await.transpose()
+    }
+
+    pub fn into_inner(self) -> AthenaStreamIterator {
+        self.inner
+    }
+}
+
+impl Stream for AthenaRecordBatchStream {
+    type Item = Result<RecordBatch, AthenaStreamIteratorError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().inner.poll_next_batch(cx)
+    }
+}
+
+///A Stream over the rows of an AthenaStreamIterator that deserializes each row into T.
+pub struct TypedAthenaStream<T> {
+    inner: AthenaStreamIterator,
+    row_type: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> TypedAthenaStream<T> {
+    ///Returns the next row of the results, or None once the results have been exhausted.
+    pub async fn next(&mut self) -> Result<Option<T>, AthenaStreamIteratorError> {
+        self.inner.next_as::<T>().await
+    }
+
+    pub fn into_inner(self) -> AthenaStreamIterator {
+        self.inner
+    }
+}
+
+impl<T: DeserializeOwned> Stream for TypedAthenaStream<T> {
+    type Item = Result<T, AthenaStreamIteratorError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let row = ready!(Pin::new(&mut this.inner).poll_next(cx));
+        Poll::Ready(row.map(|row| row.and_then(|row| this.inner.deserialize_row(row))))
     }
 }
 
@@ -150,9 +435,9 @@ mod test {
             assert_eq!(iter.query_id, "test_query_id");
             assert_eq!(iter.catalog, "test_catalog");
             assert_eq!(iter.database, "test_database");
-            assert_eq!(iter.query_output_results, None);
-            assert_eq!(iter.query_output_size, 0);
-            assert_eq!(iter.current_row, 0);
+            assert!(iter.rows.is_empty());
+            assert_eq!(iter.prefetch_pages, 1);
+            assert!(iter.prefetched_results.is_none());
             assert_eq!(iter.exhausted, false);
             assert_eq!(iter.first_page, true);
         }
@@ -339,4 +624,243 @@ mod test {
             assert!(result.is_none());
         }
     }
+
+    fn get_iterator(
+        pages: usize,
+        fetched: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    ) -> AthenaStreamIterator {
+        let mut mock_stream =
+            test_objects::MockStream::new("test_field1", "test_field2", "test_field3", 10);
+        mock_stream.with_closure(Box::new(move |stream| {
+            fetched.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if stream.state.counter < pages {
+                Some(Ok(stream.get_query_results_set(None)))
+            } else {
+                Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
+            }
+        }));
+        AthenaStreamIterator::new(
+            Box::new(mock_stream),
+            "test_query_id",
+            "test_catalog",
+            "test_database",
+        )
+    }
+
+    mod stream {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[tokio::test]
+        async fn collects_all_rows() {
+            let iter = get_iterator(3, Arc::new(AtomicUsize::new(0)));
+            let rows: Vec<_> = StreamExt::collect(iter).await;
+            assert_eq!(rows.len(), 30);
+            assert!(rows.iter().all(|row| row.is_ok()));
+        }
+
+        #[tokio::test]
+        async fn prefetches_the_next_page() {
+            let fetched = Arc::new(AtomicUsize::new(0));
+            let mut iter = get_iterator(3, fetched.clone());
+            let row = iter.next().await;
+            assert!(row.unwrap().is_some());
+            //Give the prefetch task a chance to run.
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert!(fetched.load(Ordering::SeqCst) >= 2);
+        }
+
+        #[tokio::test]
+        async fn without_prefetch() {
+            let fetched = Arc::new(AtomicUsize::new(0));
+            let mut iter = get_iterator(3, fetched.clone()).with_prefetch_pages(0);
+            let row = iter.next().await;
+            assert!(row.unwrap().is_some());
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert_eq!(fetched.load(Ordering::SeqCst), 1);
+            let mut count = 1;
+            while iter.next().await.unwrap().is_some() {
+                count += 1;
+            }
+            assert_eq!(count, 30);
+        }
+    }
+
+    mod next_as {
+        use super::*;
+        use serde::Deserialize;
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        #[derive(Debug, Deserialize)]
+        struct TestRow {
+            glyphx_id__: String,
+            test_field1: String,
+            test_field2: String,
+            test_field3: String,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct BadRow {
+            #[allow(dead_code)]
+            test_field1: u64,
+        }
+
+        #[tokio::test]
+        async fn is_ok() {
+            let mut iter = get_iterator(1, Arc::new(AtomicUsize::new(0)));
+            let mut count = 0;
+            while let Some(row) = iter.next_as::<TestRow>().await.unwrap() {
+                assert!(!row.glyphx_id__.is_empty());
+                assert_eq!(
+                    row.test_field2.parse::<usize>().unwrap(),
+                    row.test_field1.parse::<usize>().unwrap() + 1
+                );
+                assert_eq!(
+                    row.test_field3.parse::<usize>().unwrap(),
+                    row.test_field1.parse::<usize>().unwrap() + 2
+                );
+                count += 1;
+            }
+            assert_eq!(count, 10);
+        }
+
+        #[tokio::test]
+        async fn is_deserialization_error() {
+            let mut iter = get_iterator(1, Arc::new(AtomicUsize::new(0)));
+            let result = iter.next_as::<BadRow>().await;
+            match result {
+                Err(AthenaStreamIteratorError::DeserializationError(error_data)) => {
+                    let data = error_data.data.unwrap();
+                    assert_eq!(data["query_id"], "test_query_id");
+                    assert!(data["row"].is_object());
+                }
+                _ => panic!("Expected DeserializationError"),
+            }
+        }
+
+        #[tokio::test]
+        async fn typed() {
+            let iter = get_iterator(2, Arc::new(AtomicUsize::new(0)));
+            let rows: Vec<_> = StreamExt::collect(iter.typed::<TestRow>()).await;
+            assert_eq!(rows.len(), 20);
+            assert!(rows.iter().all(|row| row.is_ok()));
+        }
+    }
+    mod record_batches {
+        use super::*;
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        #[tokio::test]
+        async fn collects_all_batches() {
+            let iter = get_iterator(3, Arc::new(AtomicUsize::new(0)));
+            let batches: Vec<_> = StreamExt::collect(iter.record_batches()).await;
+            assert_eq!(batches.len(), 3);
+            assert!(batches.iter().all(|batch| batch.is_ok()));
+            let rows: usize = batches
+                .iter()
+                .map(|batch| batch.as_ref().unwrap().num_rows())
+                .sum();
+            assert_eq!(rows, 30);
+        }
+
+        #[tokio::test]
+        async fn skips_the_header_row() {
+            let mut batches = get_iterator(1, Arc::new(AtomicUsize::new(0))).record_batches();
+            let batch = batches.next().await.unwrap().unwrap();
+            assert_eq!(batch.num_columns(), 4);
+            let ids = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<arrow_array::StringArray>()
+                .unwrap();
+            assert_ne!(ids.value(0), "glyphx_id__");
+            assert!(batches.next().await.unwrap().is_none());
+        }
+    }
+
+    mod conversion_options {
+        use super::*;
+        use crate::aws::result_set_converter::ConversionOptions;
+
+        fn get_iterator(values: &[&str]) -> AthenaStreamIterator {
+            let metadata = ResultSetMetadata::builder()
+                .column_info(
+                    ColumnInfo::builder()
+                        .name("units")
+                        .r#type("bigint")
+                        .nullable(ColumnNullable::NotNull)
+                        .build(),
+                )
+                .build();
+            let mut result_set = ResultSet::builder().result_set_metadata(metadata);
+            //The first row of the first page is the header row.
+            for value in ["units"].iter().chain(values.iter()) {
+                result_set = result_set.rows(
+                    Row::builder()
+                        .data(Datum::builder().var_char_value(value.to_string()).build())
+                        .build(),
+                );
+            }
+            let page = GetQueryResultsOutput::builder()
+                .result_set(result_set.build())
+                .build();
+            AthenaStreamIterator::new(
+                Box::new(tokio_stream::iter(vec![Ok(page)])),
+                "test_query_id",
+                "test_catalog",
+                "test_database",
+            )
+        }
+
+        #[tokio::test]
+        async fn replaces_conversion_failures() {
+            let mut iter = get_iterator(&["3", "abc"]);
+            assert_eq!(iter.next().await.unwrap().unwrap(), json!({"units": 3}));
+            assert_eq!(iter.next().await.unwrap().unwrap(), json!({"units": 0}));
+            assert!(iter.next().await.unwrap().is_none());
+        }
+
+        #[tokio::test]
+        async fn reports_conversion_failures() {
+            let mut iter = get_iterator(&["3", "abc"]).with_conversion_options(
+                ConversionOptions::new().with_report_conversion_failures(true),
+            );
+            let row = iter.next().await;
+            assert!(row.is_err());
+            match row.err().unwrap() {
+                AthenaStreamIteratorError::GetQueryResultsError(error_data) => {
+                    let inner_error = error_data.inner_error.unwrap();
+                    assert!(inner_error["ConversionError"].is_object());
+                }
+                _ => panic!("Expected a GetQueryResultsError"),
+            }
+            assert!(iter.next().await.unwrap().is_none());
+        }
+
+        #[tokio::test]
+        async fn record_batches_report_conversion_failures() {
+            let mut batches = get_iterator(&["3", "abc"])
+                .with_conversion_options(
+                    ConversionOptions::new().with_report_conversion_failures(true),
+                )
+                .record_batches();
+            let batch = batches.next().await;
+            assert!(batch.is_err());
+            match batch.err().unwrap() {
+                AthenaStreamIteratorError::GetQueryResultsError(error_data) => {
+                    let inner_error = error_data.inner_error.unwrap();
+                    assert!(inner_error["ConversionError"].is_object());
+                }
+                _ => panic!("Expected a GetQueryResultsError"),
+            }
+            assert!(batches.next().await.unwrap().is_none());
+        }
+    }
 }
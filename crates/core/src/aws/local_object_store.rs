@@ -0,0 +1,723 @@
+//! This module holds the LocalObjectStore, an ObjectStore that keeps its files in a directory on
+//! the local file system.  It allows us to run the whole pipeline on a laptop or in a CI
+//! environment that has no access to S3.
+use crate::aws::object_store::{ObjectStore, ObjectUploadStream};
+use crate::aws::upload_stream::UploadStreamState;
+pub use crate::types::aws::s3_manager::*;
+pub use crate::types::aws::upload_stream::*;
+pub use crate::types::error::GlyphxErrorData;
+
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::{ByteStream, DateTime};
+use serde_json::json;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+
+/// Files that are being written by a LocalUploadStream are written to their key with this suffix
+/// and renamed when the stream is finished.  Like an unfinished S3 multipart upload, they are not
+/// returned by list_objects.
+const UPLOAD_SUFFIX: &str = ".glyphx_upload";
+
+/// An ObjectStore that maps each key to a file under its directory.  The directory plays the
+/// part of the bucket.
+#[derive(Clone, Debug)]
+pub struct LocalObjectStore {
+    directory: PathBuf,
+    bucket: String,
+}
+
+impl LocalObjectStore {
+    /// Builds a LocalObjectStore over the supplied directory.  Like the S3Manager does with its
+    /// bucket, this will return a BucketDoesNotExist error if the directory does not exist.
+    /// # Arguments
+    /// * `directory` - The directory that holds our files.
+    pub async fn new(directory: &str) -> Result<Self, ConstructorError> {
+        let local_object_store = Self {
+            directory: PathBuf::from(directory),
+            bucket: directory.to_string(),
+        };
+        let result = local_object_store.bucket_exists().await;
+        if result.is_err() {
+            let error = match result.err().unwrap() {
+                BucketExistsError::BucketDoesNotExist(e) => ConstructorError::BucketDoesNotExist(e),
+                BucketExistsError::UnexpectedError(e) => ConstructorError::UnexpectedError(e),
+            };
+            return Err(error);
+        }
+        Ok(local_object_store)
+    }
+
+    /// Returns the directory that this store reads and writes.
+    pub fn get_directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Maps a key to its path.  Keys that are empty, absolute or that would escape our
+    /// directory, i.e. contain .., are rejected.
+    fn get_path(&self, key: &str) -> Result<PathBuf, GlyphxErrorData> {
+        let relative_path = Path::new(key);
+        let is_valid = !key.is_empty()
+            && relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if !is_valid {
+            return Err(GlyphxErrorData::new(
+                format!(
+                    "The key {} is not a valid key for a local object store",
+                    key
+                ),
+                Some(json!({ "bucket_name": self.bucket, "key": key })),
+                None,
+            ));
+        }
+        Ok(self.directory.join(relative_path))
+    }
+
+    fn build_error_data(&self, message: String, key: &str) -> GlyphxErrorData {
+        GlyphxErrorData::new(
+            message,
+            Some(json!({ "bucket_name": self.bucket, "key": key })),
+            None,
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalObjectStore {
+    fn get_bucket_name(&self) -> String {
+        self.bucket.clone()
+    }
+
+    async fn bucket_exists(&self) -> Result<(), BucketExistsError> {
+        let metadata = fs::metadata(&self.directory).await;
+        match metadata {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(BucketExistsError::BucketDoesNotExist(GlyphxErrorData::new(
+                format!("{} is not a directory", self.bucket),
+                Some(json!({ "bucket_name": self.bucket })),
+                None,
+            ))),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(BucketExistsError::BucketDoesNotExist(GlyphxErrorData::new(
+                    format!("Bucket {} does not exist", self.bucket),
+                    Some(json!({ "bucket_name": self.bucket })),
+                    None,
+                )))
+            }
+            Err(e) => Err(BucketExistsError::UnexpectedError(GlyphxErrorData::new(
+                e.to_string(),
+                Some(json!({ "bucket_name": self.bucket })),
+                None,
+            ))),
+        }
+    }
+
+    async fn file_exists(&self, key: &str) -> Result<(), FileExistsError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(FileExistsError::UnexpectedError(path.err().unwrap()));
+        }
+        let metadata = fs::metadata(path.unwrap()).await;
+        match metadata {
+            Ok(metadata) if metadata.is_file() => Ok(()),
+            Ok(_) => Err(FileExistsError::FileDoesNotExist(
+                self.build_error_data(format!("{} is not a file", key), key),
+            )),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(FileExistsError::FileDoesNotExist(
+                self.build_error_data(format!("The file {} does not exist", key), key),
+            )),
+            Err(e) => Err(FileExistsError::UnexpectedError(
+                self.build_error_data(e.to_string(), key),
+            )),
+        }
+    }
+
+    async fn list_objects(&self, filter: Option<String>) -> Result<Vec<String>, ListObjectsError> {
+        let mut keys = Vec::new();
+        let mut directories = vec![self.directory.clone()];
+        while let Some(directory) = directories.pop() {
+            let entries = fs::read_dir(&directory).await;
+            if entries.is_err() {
+                let e = entries.err().unwrap();
+                let data = json!({ "bucket_name": self.bucket });
+                if e.kind() == ErrorKind::NotFound && directory == self.directory {
+                    return Err(ListObjectsError::BucketDoesNotExist(GlyphxErrorData::new(
+                        format!("Bucket {} does not exist", self.bucket),
+                        Some(data),
+                        None,
+                    )));
+                }
+                return Err(ListObjectsError::UnexpectedError(GlyphxErrorData::new(
+                    e.to_string(),
+                    Some(data),
+                    None,
+                )));
+            }
+            let mut entries = entries.unwrap();
+            loop {
+                let entry = entries.next_entry().await;
+                if entry.is_err() {
+                    return Err(ListObjectsError::UnexpectedError(GlyphxErrorData::new(
+                        entry.err().unwrap().to_string(),
+                        Some(json!({ "bucket_name": self.bucket })),
+                        None,
+                    )));
+                }
+                let entry = match entry.unwrap() {
+                    Some(entry) => entry,
+                    None => break,
+                };
+                let path = entry.path();
+                if path.is_dir() {
+                    directories.push(path);
+                    continue;
+                }
+                //Keys are always / separated so that they match what we would see in S3.
+                let key = path
+                    .strip_prefix(&self.directory)
+                    .unwrap()
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if key.ends_with(UPLOAD_SUFFIX) {
+                    continue;
+                }
+                if let Some(filter) = &filter {
+                    if !key.starts_with(filter.as_str()) {
+                        continue;
+                    }
+                }
+                keys.push(key);
+            }
+        }
+        //S3 returns keys in lexical order, so we will too.
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn get_file_information(&self, key: &str) -> Result<S3FileInfo, GetFileInformationError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(GetFileInformationError::UnexpectedError(
+                path.err().unwrap(),
+            ));
+        }
+        let metadata = fs::metadata(path.unwrap()).await;
+        match metadata {
+            Ok(metadata) if metadata.is_file() => {
+                let last_modified = metadata.modified();
+                if last_modified.is_err() {
+                    return Err(GetFileInformationError::UnexpectedError(
+                        self.build_error_data(last_modified.err().unwrap().to_string(), key),
+                    ));
+                }
+                Ok(S3FileInfo {
+                    file_name: key.to_string(),
+                    file_size: metadata.len() as i64,
+                    last_modified: DateTime::from(last_modified.unwrap()),
+                })
+            }
+            Ok(_) => Err(GetFileInformationError::KeyDoesNotExist(
+                self.build_error_data(format!("{} is not a file", key), key),
+            )),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(
+                GetFileInformationError::KeyDoesNotExist(self.build_error_data(
+                    format!(
+                        "The file {} does not exist on the bucket {}",
+                        key, self.bucket
+                    ),
+                    key,
+                )),
+            ),
+            Err(e) => Err(GetFileInformationError::UnexpectedError(
+                self.build_error_data(e.to_string(), key),
+            )),
+        }
+    }
+
+    async fn get_object_stream(&self, key: &str) -> Result<ByteStream, GetObjectStreamError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(GetObjectStreamError::UnexpectedError(path.err().unwrap()));
+        }
+        let path = path.unwrap();
+        if !path.is_file() {
+            return Err(GetObjectStreamError::KeyDoesNotExist(
+                self.build_error_data(
+                    format!(
+                        "The object {} does not exist on the bucket {}",
+                        key, self.bucket
+                    ),
+                    key,
+                ),
+            ));
+        }
+        let stream = ByteStream::from_path(&path).await;
+        if stream.is_err() {
+            return Err(GetObjectStreamError::UnexpectedError(
+                self.build_error_data(stream.err().unwrap().to_string(), key),
+            ));
+        }
+        Ok(stream.unwrap())
+    }
+
+    async fn get_upload_stream(
+        &self,
+        key: &str,
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(GetUploadStreamError::UnexpectedError(path.err().unwrap()));
+        }
+        let upload_stream = LocalUploadStream::new(&self.bucket, key, path.unwrap()).await;
+        if upload_stream.is_err() {
+            return Err(upload_stream.err().unwrap());
+        }
+        Ok(Box::new(upload_stream.unwrap()))
+    }
+
+    async fn remove_object(&self, key: &str) -> Result<(), RemoveObjectError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(RemoveObjectError::UnexpectedError(path.err().unwrap()));
+        }
+        let result = fs::remove_file(path.unwrap()).await;
+        match result {
+            Ok(_) => Ok(()),
+            //S3 does not complain about deleting a key that does not exist.
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(RemoveObjectError::UnexpectedError(
+                self.build_error_data(e.to_string(), key),
+            )),
+        }
+    }
+
+    async fn upload_object(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        _content_type: Option<String>,
+    ) -> Result<(), UploadObjectError> {
+        let path = self.get_path(key);
+        if path.is_err() {
+            return Err(UploadObjectError::UnexpectedError(path.err().unwrap()));
+        }
+        let path = path.unwrap();
+        let result = fs::create_dir_all(path.parent().unwrap()).await;
+        if result.is_err() {
+            return Err(UploadObjectError::UnexpectedError(
+                self.build_error_data(result.err().unwrap().to_string(), key),
+            ));
+        }
+        let result = fs::write(&path, data).await;
+        if result.is_err() {
+            return Err(UploadObjectError::UnexpectedError(
+                self.build_error_data(result.err().unwrap().to_string(), key),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The local equivalent of our UploadStream.  Bytes are written to a temporary file next to the
+/// target which is renamed into place when the stream is finished, so readers never see a
+/// partially written file.
+#[derive(Debug)]
+pub struct LocalUploadStream {
+    file_name: String,
+    file_size: i64,
+    bucket_name: String,
+    path: PathBuf,
+    upload_path: PathBuf,
+    file: Option<File>,
+    state: UploadStreamState,
+}
+
+impl LocalUploadStream {
+    async fn new(
+        bucket_name: &str,
+        file_name: &str,
+        path: PathBuf,
+    ) -> Result<Self, GetUploadStreamError> {
+        let mut upload_path = path.clone().into_os_string();
+        upload_path.push(UPLOAD_SUFFIX);
+        let upload_path = PathBuf::from(upload_path);
+        let data = json!({"bucket_name": bucket_name, "file_name": file_name});
+
+        let result = fs::create_dir_all(path.parent().unwrap()).await;
+        if result.is_err() {
+            return Err(GetUploadStreamError::UnexpectedError(GlyphxErrorData::new(
+                result.err().unwrap().to_string(),
+                Some(data),
+                None,
+            )));
+        }
+        let file = File::create(&upload_path).await;
+        if file.is_err() {
+            return Err(GetUploadStreamError::UnexpectedError(GlyphxErrorData::new(
+                file.err().unwrap().to_string(),
+                Some(data),
+                None,
+            )));
+        }
+        Ok(Self {
+            file_name: file_name.to_string(),
+            file_size: 0,
+            bucket_name: bucket_name.to_string(),
+            path,
+            upload_path,
+            file: Some(file.unwrap()),
+            state: UploadStreamState::Ok,
+        })
+    }
+
+    fn build_error_data(&self, message: &str) -> GlyphxErrorData {
+        GlyphxErrorData::new(
+            message.to_string(),
+            Some(json!({"bucket_name": self.bucket_name, "file_name": self.file_name})),
+            None,
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectUploadStream for LocalUploadStream {
+    fn get_file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn get_file_size(&self) -> i64 {
+        self.file_size
+    }
+
+    fn get_state(&self) -> &UploadStreamState {
+        &self.state
+    }
+
+    async fn write(&mut self, bytes: Option<Vec<u8>>) -> Result<(), UploadStreamWriteError> {
+        match self.state {
+            UploadStreamState::Aborted => Err(UploadStreamWriteError::Aborted(
+                self.build_error_data("Stream has been previously aborted"),
+            )),
+            UploadStreamState::Finished => Err(UploadStreamWriteError::Finished(
+                self.build_error_data("Stream has been previously finished"),
+            )),
+            UploadStreamState::Ok => {
+                if bytes.is_none() {
+                    let res = self.finish().await;
+                    if res.is_err() {
+                        let err = match res.err().unwrap() {
+                            UploadStreamFinishError::Aborted(err) => {
+                                UploadStreamWriteError::Aborted(err)
+                            }
+                            UploadStreamFinishError::Finished(err) => {
+                                UploadStreamWriteError::Finished(err)
+                            }
+                            UploadStreamFinishError::UnexpectedError(err) => {
+                                UploadStreamWriteError::UnexpectedError(err)
+                            }
+                            UploadStreamFinishError::NoDataToWrite(err) => {
+                                UploadStreamWriteError::UnexpectedError(err)
+                            }
+                        };
+                        return Err(err);
+                    }
+                    return Ok(());
+                }
+                let bytes = bytes.unwrap();
+                let result = self.file.as_mut().unwrap().write_all(&bytes).await;
+                if result.is_err() {
+                    let error = self.build_error_data(&result.err().unwrap().to_string());
+                    self.abort().await;
+                    return Err(UploadStreamWriteError::UnexpectedError(error));
+                }
+                self.file_size += bytes.len() as i64;
+                Ok(())
+            }
+        }
+    }
+
+    async fn finish(&mut self) -> Result<(), UploadStreamFinishError> {
+        match self.state {
+            UploadStreamState::Aborted => Err(UploadStreamFinishError::Aborted(
+                self.build_error_data("The upload has been previously aborted"),
+            )),
+            UploadStreamState::Finished => Err(UploadStreamFinishError::Finished(
+                self.build_error_data("The upload has already been finished"),
+            )),
+            UploadStreamState::Ok => {
+                //Just like S3, you can't finish an empty stream.
+                if self.file_size == 0 {
+                    self.abort().await;
+                    return Err(UploadStreamFinishError::NoDataToWrite(
+                        self.build_error_data("There is no data to write to the stream"),
+                    ));
+                }
+                let mut file = self.file.take().unwrap();
+                let result = file.flush().await;
+                drop(file);
+                if result.is_err() {
+                    let error = self.build_error_data(&result.err().unwrap().to_string());
+                    self.abort().await;
+                    return Err(UploadStreamFinishError::UnexpectedError(error));
+                }
+                let result = fs::rename(&self.upload_path, &self.path).await;
+                if result.is_err() {
+                    let error = self.build_error_data(&result.err().unwrap().to_string());
+                    self.abort().await;
+                    return Err(UploadStreamFinishError::UnexpectedError(error));
+                }
+                self.state = UploadStreamState::Finished;
+                Ok(())
+            }
+        }
+    }
+
+    ///Removes our temporary file.  Like the UploadStream, we don't report errors that occur
+    ///while aborting since we are already handling an error.
+    async fn abort(&mut self) {
+        if let UploadStreamState::Ok = self.state {
+            self.file = None;
+            let _ = fs::remove_file(&self.upload_path).await;
+            self.state = UploadStreamState::Aborted;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use std::path::PathBuf;
+
+    ///Returns an empty directory under the temp dir for a test to use as its bucket.
+    pub fn get_test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("glyphx_local_object_store")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+}
+
+#[cfg(test)]
+mod constructor {
+    use super::test_helpers::*;
+    use super::*;
+
+    #[tokio::test]
+    async fn is_ok() {
+        let directory = get_test_directory("constructor_is_ok");
+        let result = LocalObjectStore::new(directory.to_str().unwrap()).await;
+        assert!(result.is_ok());
+        let local_object_store = result.unwrap();
+        assert_eq!(local_object_store.get_directory(), directory.as_path());
+        assert_eq!(
+            ObjectStore::get_bucket_name(&local_object_store),
+            directory.to_str().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_exist() {
+        let directory = get_test_directory("constructor_does_not_exist").join("missing");
+        let result = LocalObjectStore::new(directory.to_str().unwrap()).await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ConstructorError::BucketDoesNotExist(_) => {}
+            _ => panic!("Expected BucketDoesNotExist"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod objects {
+    use super::test_helpers::*;
+    use super::*;
+
+    async fn get_object_store(name: &str) -> LocalObjectStore {
+        let directory = get_test_directory(name);
+        LocalObjectStore::new(directory.to_str().unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn upload_and_read() {
+        let object_store = get_object_store("upload_and_read").await;
+        let result = object_store
+            .upload_object("a/b/file.txt", b"hello".to_vec(), None)
+            .await;
+        assert!(result.is_ok());
+        assert!(object_store.file_exists("a/b/file.txt").await.is_ok());
+
+        let info = object_store.get_file_information("a/b/file.txt").await;
+        assert!(info.is_ok());
+        assert_eq!(info.unwrap().file_size, 5);
+
+        let stream = object_store.get_object_stream("a/b/file.txt").await;
+        assert!(stream.is_ok());
+        let bytes = stream.unwrap().collect().await.unwrap().into_bytes();
+        assert_eq!(bytes.as_ref(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn missing_file() {
+        let object_store = get_object_store("missing_file").await;
+        match object_store.file_exists("missing.txt").await.err().unwrap() {
+            FileExistsError::FileDoesNotExist(_) => {}
+            _ => panic!("Expected FileDoesNotExist"),
+        }
+        match object_store
+            .get_object_stream("missing.txt")
+            .await
+            .err()
+            .unwrap()
+        {
+            GetObjectStreamError::KeyDoesNotExist(_) => {}
+            _ => panic!("Expected KeyDoesNotExist"),
+        }
+        //Removing a file that does not exist is not an error in S3.
+        assert!(object_store.remove_object("missing.txt").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn invalid_key() {
+        let object_store = get_object_store("invalid_key").await;
+        let result = object_store
+            .upload_object("../escaped.txt", b"hello".to_vec(), None)
+            .await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            UploadObjectError::UnexpectedError(_) => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn list_and_remove() {
+        let object_store = get_object_store("list_and_remove").await;
+        for key in ["b/2.txt", "a/1.txt", "b/1.txt"] {
+            object_store
+                .upload_object(key, b"data".to_vec(), None)
+                .await
+                .unwrap();
+        }
+        let keys = object_store.list_objects(None).await.unwrap();
+        assert_eq!(keys, vec!["a/1.txt", "b/1.txt", "b/2.txt"]);
+
+        let keys = object_store
+            .list_objects(Some("b/".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["b/1.txt", "b/2.txt"]);
+
+        assert!(object_store.remove_object("b/1.txt").await.is_ok());
+        let keys = object_store.list_objects(None).await.unwrap();
+        assert_eq!(keys, vec!["a/1.txt", "b/2.txt"]);
+    }
+}
+
+#[cfg(test)]
+mod upload_stream {
+    use super::test_helpers::*;
+    use super::*;
+
+    async fn get_object_store(name: &str) -> LocalObjectStore {
+        let directory = get_test_directory(name);
+        LocalObjectStore::new(directory.to_str().unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn write_and_finish() {
+        let object_store = get_object_store("write_and_finish").await;
+        let mut stream = object_store
+            .get_upload_stream("out/file.bin")
+            .await
+            .unwrap();
+        assert!(stream.write(Some(vec![1, 2, 3])).await.is_ok());
+        assert!(stream.write(Some(vec![4, 5])).await.is_ok());
+
+        //Unfinished files are not visible.
+        assert!(object_store.file_exists("out/file.bin").await.is_err());
+        assert!(object_store.list_objects(None).await.unwrap().is_empty());
+
+        assert!(stream.finish().await.is_ok());
+        assert_eq!(stream.get_file_size(), 5);
+        assert_eq!(stream.get_file_name(), "out/file.bin");
+        match stream.get_state() {
+            UploadStreamState::Finished => {}
+            _ => panic!("Expected Finished"),
+        }
+        let keys = object_store.list_objects(None).await.unwrap();
+        assert_eq!(keys, vec!["out/file.bin"]);
+        let directory = object_store.get_directory().join("out").join("file.bin");
+        assert_eq!(std::fs::read(directory).unwrap(), vec![1, 2, 3, 4, 5]);
+
+        match stream.write(Some(vec![6])).await.err().unwrap() {
+            UploadStreamWriteError::Finished(_) => {}
+            _ => panic!("Expected Finished"),
+        }
+    }
+
+    #[tokio::test]
+    async fn write_none_finishes() {
+        let object_store = get_object_store("write_none_finishes").await;
+        let mut stream = object_store.get_upload_stream("file.bin").await.unwrap();
+        assert!(stream.write(Some(vec![1])).await.is_ok());
+        assert!(stream.write(None).await.is_ok());
+        assert!(object_store.file_exists("file.bin").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn finish_empty_stream() {
+        let object_store = get_object_store("finish_empty_stream").await;
+        let mut stream = object_store.get_upload_stream("file.bin").await.unwrap();
+        match stream.finish().await.err().unwrap() {
+            UploadStreamFinishError::NoDataToWrite(_) => {}
+            _ => panic!("Expected NoDataToWrite"),
+        }
+        match stream.get_state() {
+            UploadStreamState::Aborted => {}
+            _ => panic!("Expected Aborted"),
+        }
+        //The temporary file is cleaned up.
+        let entries = std::fs::read_dir(object_store.get_directory()).unwrap();
+        assert_eq!(entries.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn abort_discards_stream() {
+        let object_store = get_object_store("abort_discards_stream").await;
+        let mut stream = object_store.get_upload_stream("file.bin").await.unwrap();
+        assert!(stream.write(Some(vec![1, 2, 3])).await.is_ok());
+        stream.abort().await;
+        match stream.get_state() {
+            UploadStreamState::Aborted => {}
+            _ => panic!("Expected Aborted"),
+        }
+        match stream.write(Some(vec![4])).await.err().unwrap() {
+            UploadStreamWriteError::Aborted(_) => {}
+            _ => panic!("Expected Aborted"),
+        }
+        let entries = std::fs::read_dir(object_store.get_directory()).unwrap();
+        assert_eq!(entries.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn abort_keeps_finished_file() {
+        let object_store = get_object_store("abort_keeps_finished_file").await;
+        let mut stream = object_store.get_upload_stream("file.bin").await.unwrap();
+        assert!(stream.write(Some(vec![1])).await.is_ok());
+        assert!(stream.finish().await.is_ok());
+        stream.abort().await;
+        match stream.get_state() {
+            UploadStreamState::Finished => {}
+            _ => panic!("Expected Finished"),
+        }
+        assert!(object_store.file_exists("file.bin").await.is_ok());
+    }
+}
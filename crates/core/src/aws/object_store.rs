@@ -0,0 +1,78 @@
+//! This module holds the traits that abstract the storage of our files away from S3.  The
+//! S3Manager and UploadStream implement these traits for S3 and the LocalObjectStore and
+//! LocalUploadStream implement them over a directory on the local file system.
+use crate::aws::upload_stream::UploadStreamState;
+pub use crate::types::aws::s3_manager::*;
+pub use crate::types::aws::upload_stream::*;
+
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use mockall::automock;
+use std::fmt::Debug;
+
+/// The operations that we need from a bucket of files.  Keys are always '/' separated
+/// regardless of the backing store.
+#[automock]
+#[async_trait]
+pub trait ObjectStore: Send + Sync + Debug {
+    /// Returns the name of the bucket (or directory) that this store operates on.
+    fn get_bucket_name(&self) -> String;
+
+    /// Returns Ok(()) if the bucket exists.
+    async fn bucket_exists(&self) -> Result<(), BucketExistsError>;
+
+    /// Returns Ok(()) if the file identified by key exists.
+    async fn file_exists(&self, key: &str) -> Result<(), FileExistsError>;
+
+    /// Returns all of the keys in the bucket, optionally filtered to those that start with
+    /// filter.
+    async fn list_objects(&self, filter: Option<String>) -> Result<Vec<String>, ListObjectsError>;
+
+    /// Returns the name, size and last modified date of the file identified by key.
+    async fn get_file_information(&self, key: &str) -> Result<S3FileInfo, GetFileInformationError>;
+
+    /// Returns a ByteStream that can be used to read the file identified by key.
+    async fn get_object_stream(&self, key: &str) -> Result<ByteStream, GetObjectStreamError>;
+
+    /// Returns a stream that will write a file to key as bytes are written to it.  The file
+    /// is not visible until the stream is finished.
+    async fn get_upload_stream(
+        &self,
+        key: &str,
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError>;
+
+    /// Removes the file identified by key.
+    async fn remove_object(&self, key: &str) -> Result<(), RemoveObjectError>;
+
+    /// Writes data to key in a single operation.
+    async fn upload_object(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        content_type: Option<String>,
+    ) -> Result<(), UploadObjectError>;
+}
+
+/// A stream that we can write an arbitrary number of bytes to before finishing it.  Passing None
+/// to write will finish the stream.
+#[async_trait]
+pub trait ObjectUploadStream: Send + Sync + Debug {
+    /// Returns the key of the file that is being written.
+    fn get_file_name(&self) -> &str;
+
+    /// Returns the number of bytes that have been committed to the file.
+    fn get_file_size(&self) -> i64;
+
+    /// Returns the current state of the stream.
+    fn get_state(&self) -> &UploadStreamState;
+
+    /// Writes bytes to the stream, or finishes it if bytes is None.
+    async fn write(&mut self, bytes: Option<Vec<u8>>) -> Result<(), UploadStreamWriteError>;
+
+    /// Completes the file.
+    async fn finish(&mut self) -> Result<(), UploadStreamFinishError>;
+
+    /// Abandons an open stream, discarding anything written so far.  Streams that have
+    /// already been finished or aborted are left as they are.
+    async fn abort(&mut self);
+}
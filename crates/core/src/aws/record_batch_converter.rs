@@ -0,0 +1,573 @@
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, NullArray,
+    RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use aws_sdk_athena::types::{ResultSet, ResultSetMetadata};
+use chrono::NaiveDate;
+
+use super::result_set_converter::{
+    convert_str_to_json_array, convert_str_to_json_bool, convert_str_to_json_decimal,
+    convert_str_to_json_map, get_column_info, get_conversion_error, is_non_finite, parse_date,
+    parse_timestamp, parse_timestamp_with_time_zone, ColumnInformation, ConversionOptions,
+    LegalDataTypes,
+};
+use crate::types::aws::athena_manager::athena_manager_errors::ResultSetConversionError;
+
+///The largest precision that fits in a Decimal128.
+const MAX_DECIMAL_PRECISION: u8 = 38;
+///Athena writes timestamps with a time zone in UTC once they have been converted.
+const UTC: &str = "+00:00";
+
+///Returns the precision and scale to use for a decimal column.  Athena will report a precision
+///of 0 if it does not know it, in which case we use the largest precision that we can hold.
+fn get_decimal_precision_and_scale(column: &ColumnInformation) -> (u8, i8) {
+    let precision = if (1..=MAX_DECIMAL_PRECISION as i32).contains(&column.precision) {
+        column.precision as u8
+    } else {
+        MAX_DECIMAL_PRECISION
+    };
+    let scale = column.scale.clamp(0, precision as i32) as i8;
+    (precision, scale)
+}
+
+///Returns true if a null in the column is replaced with a default value when the column is not
+///nullable, in the same way that the JSON conversion does.  Dates, timestamps and unsupported
+///types do not have a default, so they are always nullable.
+fn has_default(data_type: &LegalDataTypes) -> bool {
+    !matches!(
+        data_type,
+        LegalDataTypes::Date(_)
+            | LegalDataTypes::Timestamp(_)
+            | LegalDataTypes::TimestampWithTimeZone(_)
+            | LegalDataTypes::Illegal(_)
+    )
+}
+
+///Returns true for the Number types that hold floating point values.
+fn is_floating_point(type_name: &str) -> bool {
+    matches!(type_name, "double" | "float" | "real")
+}
+
+///Returns the Arrow field that the column is converted to.  Integers are widened to Int64,
+///arrays, maps and rows are held as JSON text, since Athena does not tell us the types of their
+///elements, and unsupported types are always null.
+fn get_field(column: &ColumnInformation) -> Field {
+    let data_type = match &column.data_type {
+        LegalDataTypes::Bool(_) => DataType::Boolean,
+        LegalDataTypes::Number(name) if is_floating_point(name) => DataType::Float64,
+        LegalDataTypes::Number(_) => DataType::Int64,
+        LegalDataTypes::Decimal(_) => {
+            let (precision, scale) = get_decimal_precision_and_scale(column);
+            DataType::Decimal128(precision, scale)
+        }
+        LegalDataTypes::String(_) => DataType::Utf8,
+        LegalDataTypes::Date(_) => DataType::Date32,
+        LegalDataTypes::Timestamp(_) => DataType::Timestamp(TimeUnit::Millisecond, None),
+        LegalDataTypes::TimestampWithTimeZone(_) => {
+            DataType::Timestamp(TimeUnit::Millisecond, Some(UTC.into()))
+        }
+        LegalDataTypes::Array(_) => DataType::Utf8,
+        LegalDataTypes::Map(_) => DataType::Utf8,
+        LegalDataTypes::Illegal(_) => DataType::Null,
+    };
+    let nullable = column.nullable || !has_default(&column.data_type);
+    Field::new(&column.name, data_type, nullable)
+}
+
+///Converts the ResultSetMetadata into the Arrow Schema of the RecordBatches that
+///convert_to_record_batch returns.
+///# Arguments
+///`metadata` - A ResultSetMetadata struct that contains information about the columns returned from Athena.
+pub fn get_schema(metadata: &ResultSetMetadata) -> Schema {
+    let fields: Vec<Field> = get_column_info(metadata).iter().map(get_field).collect();
+    Schema::new(fields)
+}
+
+///Parses a decimal into an i128 with the given scale.  Returns None if the input is not a
+///decimal, or if it does not fit the precision and scale without losing digits.
+fn parse_decimal(input: &str, precision: u8, scale: i8) -> Option<i128> {
+    if convert_str_to_json_decimal(input).is_null() {
+        return None;
+    }
+    let (negative, digits) = match input.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let scale = scale as usize;
+    if fraction.len() > scale && fraction[scale..].chars().any(|c| c != '0') {
+        return None;
+    }
+    let fraction: String = fraction
+        .chars()
+        .chain(std::iter::repeat('0'))
+        .take(scale)
+        .collect();
+    let value = format!("0{}{}", whole, fraction).parse::<i128>().ok()?;
+    if value >= 10i128.checked_pow(precision as u32)? {
+        return None;
+    }
+    Some(if negative { -value } else { value })
+}
+
+///Applies the same null processing as the JSON conversion to the values of a column.  A missing
+///or empty value is null, which is replaced with the default when the column is not nullable.
+///A value that can not be parsed is reported, or treated as null, depending on the options.
+///# Arguments
+///`inputs` - The values of the column.
+///`column` - The information about the column.
+///`default` - The value to use for null when the column is not nullable.
+///`options` - The options that control the conversion.
+///`first_row` - The index in the result set of the first value, used when reporting failures.
+///`parse` - The function that parses a value, returning None if it can not.
+fn convert_values<'a, T: Clone, F: Fn(&'a str) -> Option<T>>(
+    inputs: &[Option<&'a str>],
+    column: &ColumnInformation,
+    default: Option<T>,
+    options: &ConversionOptions,
+    first_row: usize,
+    parse: F,
+) -> Result<Vec<Option<T>>, ResultSetConversionError> {
+    let mut values = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        let input = input.map(str::trim).filter(|input| !input.is_empty());
+        let value = match input {
+            Some(input) => {
+                let value = parse(input);
+                if value.is_none() && options.get_report_conversion_failures() {
+                    return Err(get_conversion_error(column, input, first_row + index));
+                }
+                value
+            }
+            None => None,
+        };
+        if value.is_none() && !column.nullable {
+            values.push(default.clone());
+        } else {
+            values.push(value);
+        }
+    }
+    Ok(values)
+}
+
+///Converts the values of a column into the Arrow array for its field.
+fn convert_column(
+    inputs: &[Option<&str>],
+    column: &ColumnInformation,
+    options: &ConversionOptions,
+    first_row: usize,
+) -> Result<ArrayRef, ResultSetConversionError> {
+    let array: ArrayRef = match &column.data_type {
+        LegalDataTypes::Bool(_) => {
+            let values = convert_values(inputs, column, Some(false), options, first_row, |input| {
+                convert_str_to_json_bool(input).as_bool()
+            })?;
+            Arc::new(BooleanArray::from(values))
+        }
+        LegalDataTypes::Number(name) if is_floating_point(name) => {
+            //Arrow could hold NaN and Infinity, but we keep to the JSON conversion, which can not.
+            let inputs: Vec<Option<&str>> = inputs
+                .iter()
+                .map(|input| input.filter(|input| !is_non_finite(input)))
+                .collect();
+            let values = convert_values(&inputs, column, Some(0.0), options, first_row, |input| {
+                input.parse::<f64>().ok()
+            })?;
+            Arc::new(Float64Array::from(values))
+        }
+        LegalDataTypes::Number(_) => {
+            let values = convert_values(inputs, column, Some(0), options, first_row, |input| {
+                input.parse::<i64>().ok()
+            })?;
+            Arc::new(Int64Array::from(values))
+        }
+        LegalDataTypes::Decimal(_) => {
+            let (precision, scale) = get_decimal_precision_and_scale(column);
+            let values = convert_values(inputs, column, Some(0), options, first_row, |input| {
+                parse_decimal(input, precision, scale)
+            })?;
+            //The precision and scale have already been checked to be in range.
+            Arc::new(
+                Decimal128Array::from(values)
+                    .with_precision_and_scale(precision, scale)
+                    .unwrap(),
+            )
+        }
+        LegalDataTypes::String(_) => {
+            let values: Vec<Option<&str>> = inputs
+                .iter()
+                .map(|input| match input {
+                    Some(input) => Some(input.trim()),
+                    None if !column.nullable => Some(""),
+                    None => None,
+                })
+                .collect();
+            Arc::new(StringArray::from(values))
+        }
+        LegalDataTypes::Date(_) => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let values = convert_values(inputs, column, None, options, first_row, |input| {
+                parse_date(input).map(|date| date.signed_duration_since(epoch).num_days() as i32)
+            })?;
+            Arc::new(Date32Array::from(values))
+        }
+        LegalDataTypes::Timestamp(_) => {
+            let values = convert_values(inputs, column, None, options, first_row, |input| {
+                parse_timestamp(input).map(|timestamp| timestamp.and_utc().timestamp_millis())
+            })?;
+            Arc::new(TimestampMillisecondArray::from(values))
+        }
+        LegalDataTypes::TimestampWithTimeZone(_) => {
+            let values = convert_values(inputs, column, None, options, first_row, |input| {
+                parse_timestamp_with_time_zone(input).map(|timestamp| timestamp.timestamp_millis())
+            })?;
+            Arc::new(TimestampMillisecondArray::from(values).with_timezone(UTC))
+        }
+        LegalDataTypes::Array(_) => {
+            let default = Some(String::from("[]"));
+            let values = convert_values(inputs, column, default, options, first_row, |input| {
+                let value = convert_str_to_json_array(input);
+                (!value.is_null()).then(|| value.to_string())
+            })?;
+            Arc::new(StringArray::from(values))
+        }
+        LegalDataTypes::Map(_) => {
+            let default = Some(String::from("{}"));
+            let values = convert_values(inputs, column, default, options, first_row, |input| {
+                let value = convert_str_to_json_map(input);
+                (!value.is_null()).then(|| value.to_string())
+            })?;
+            Arc::new(StringArray::from(values))
+        }
+        LegalDataTypes::Illegal(_) => {
+            //Illegal data types will never be included, but are reported if asked.
+            convert_values(inputs, column, None::<()>, options, first_row, |_| None)?;
+            Arc::new(NullArray::new(inputs.len()))
+        }
+    };
+    Ok(array)
+}
+
+///Converts the ResultSet into an Arrow RecordBatch, with one column per column of the result
+///set.  This avoids building a JSON object for each row, and converts the values with the same
+///rules, and the same options, as convert_to_json_with_options.  Dates are held as Date32 and
+///timestamps as milliseconds, so the temporal_format of the options is not used.
+///# Arguments
+///`result_set` - A ResultSet from the athena client.
+///`includes_header_row` - An optional boolean that indicates if the first row should be skipped.
+///`options` - The options that control the conversion.
+pub fn convert_to_record_batch(
+    result_set: &ResultSet,
+    includes_header_row: Option<bool>,
+    options: &ConversionOptions,
+) -> Result<RecordBatch, ResultSetConversionError> {
+    let metadata = result_set.result_set_metadata().unwrap();
+    let column_information = get_column_info(metadata);
+    let schema = Arc::new(Schema::new(
+        column_information.iter().map(get_field).collect::<Vec<Field>>(),
+    ));
+    let rows = match result_set.rows() {
+        Some(rows) => rows,
+        None => return Ok(RecordBatch::new_empty(schema)),
+    };
+    let first_row = if includes_header_row.unwrap_or(false) { 1 } else { 0 };
+    let rows = rows.get(first_row..).unwrap_or_default();
+
+    let mut columns: Vec<Vec<Option<&str>>> =
+        vec![Vec::with_capacity(rows.len()); column_information.len()];
+    for row in rows {
+        let data = row.data().unwrap_or_default();
+        for (column_num, values) in columns.iter_mut().enumerate() {
+            values.push(data.get(column_num).and_then(|datum| datum.var_char_value()));
+        }
+    }
+
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+    for (inputs, column) in columns.iter().zip(column_information.iter()) {
+        arrays.push(convert_column(inputs, column, options, first_row)?);
+    }
+    //The arrays were built from the same column information as the schema, so they will match.
+    Ok(RecordBatch::try_new(schema, arrays).unwrap())
+}
+
+#[cfg(test)]
+mod get_schema {
+    use super::*;
+    use aws_sdk_athena::types::{ColumnInfo, ColumnNullable};
+
+    #[test]
+    fn is_ok() {
+        let metadata = ResultSetMetadata::builder()
+            .set_column_info(Some(vec![
+                ColumnInfo::builder()
+                    .name("units")
+                    .r#type("integer")
+                    .nullable(ColumnNullable::NotNull)
+                    .build(),
+                ColumnInfo::builder()
+                    .name("price")
+                    .r#type("decimal")
+                    .precision(10)
+                    .scale(2)
+                    .nullable(ColumnNullable::Nullable)
+                    .build(),
+                ColumnInfo::builder()
+                    .name("sold_on")
+                    .r#type("date")
+                    .nullable(ColumnNullable::NotNull)
+                    .build(),
+            ]))
+            .build();
+
+        let schema = get_schema(&metadata);
+        assert_eq!(schema.fields().len(), 3);
+
+        let units = schema.field(0);
+        assert_eq!(units.name(), "units");
+        assert_eq!(units.data_type(), &DataType::Int64);
+        assert!(!units.is_nullable());
+
+        let price = schema.field(1);
+        assert_eq!(price.data_type(), &DataType::Decimal128(10, 2));
+        assert!(price.is_nullable());
+
+        //Dates do not have a default, so they are nullable even when the column is not.
+        let sold_on = schema.field(2);
+        assert_eq!(sold_on.data_type(), &DataType::Date32);
+        assert!(sold_on.is_nullable());
+    }
+}
+
+#[cfg(test)]
+mod parse_decimal {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(parse_decimal("123.45", 10, 2), Some(12345));
+        assert_eq!(parse_decimal("-0.5", 10, 2), Some(-50));
+        assert_eq!(parse_decimal("7", 10, 2), Some(700));
+        assert_eq!(parse_decimal(".25", 10, 2), Some(25));
+    }
+
+    #[test]
+    fn keeps_precision() {
+        let input = "12345678901234567890.123456789";
+        assert_eq!(
+            parse_decimal(input, 38, 9),
+            Some(12345678901234567890123456789)
+        );
+    }
+
+    #[test]
+    fn trailing_zeros_are_dropped() {
+        assert_eq!(parse_decimal("1.5000", 10, 2), Some(150));
+    }
+
+    #[test]
+    fn is_err() {
+        assert_eq!(parse_decimal("abc", 10, 2), None);
+        //This would lose a digit.
+        assert_eq!(parse_decimal("1.005", 10, 2), None);
+        //This does not fit the precision.
+        assert_eq!(parse_decimal("123456.00", 5, 2), None);
+    }
+}
+
+#[cfg(test)]
+mod convert_to_record_batch {
+    use super::*;
+    use crate::aws::result_set_converter::convert_to_json_with_options;
+    use arrow_array::Array;
+    use aws_sdk_athena::types::{ColumnInfo, ColumnNullable, Datum, Row};
+    use serde_json::json;
+
+    fn get_result_set(columns: &[(&str, &str)], rows: Vec<Vec<Option<&str>>>) -> ResultSet {
+        let column_info = columns
+            .iter()
+            .map(|(name, data_type)| {
+                ColumnInfo::builder()
+                    .name(name.to_string())
+                    .r#type(data_type.to_string())
+                    .precision(12)
+                    .scale(2)
+                    .nullable(ColumnNullable::NotNull)
+                    .build()
+            })
+            .collect();
+        let metadata = ResultSetMetadata::builder()
+            .set_column_info(Some(column_info))
+            .build();
+        let mut result_set = ResultSet::builder().result_set_metadata(metadata);
+        for row in rows {
+            let data = row
+                .iter()
+                .map(|value| {
+                    Datum::builder()
+                        .set_var_char_value(value.map(|value| value.to_string()))
+                        .build()
+                })
+                .collect();
+            result_set = result_set.rows(Row::builder().set_data(Some(data)).build());
+        }
+        result_set.build()
+    }
+
+    #[test]
+    fn is_ok() {
+        let result_set = get_result_set(
+            &[
+                ("region", "varchar"),
+                ("units", "bigint"),
+                ("price", "double"),
+                ("total", "decimal"),
+                ("returned", "boolean"),
+                ("sold_on", "date"),
+                ("sold_at", "timestamp with time zone"),
+                ("tags", "array"),
+            ],
+            vec![
+                vec![
+                    Some("east"),
+                    Some("3"),
+                    Some("1.5"),
+                    Some("4.50"),
+                    Some("false"),
+                    Some("2023-06-01"),
+                    Some("2023-06-01 12:30:00.000 +02:00"),
+                    Some("[fresh, local]"),
+                ],
+                vec![
+                    Some("west"),
+                    Some("5"),
+                    Some("2.25"),
+                    Some("11.25"),
+                    Some("true"),
+                    None,
+                    None,
+                    None,
+                ],
+            ],
+        );
+
+        let result = convert_to_record_batch(&result_set, None, &ConversionOptions::new());
+        assert!(result.is_ok());
+        let batch = result.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 8);
+
+        let region = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(region.value(1), "west");
+
+        let units = batch.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(units.values().to_vec(), vec![3, 5]);
+
+        let price = batch.column(2).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(price.value(1), 2.25);
+
+        let total = batch.column(3).as_any().downcast_ref::<Decimal128Array>().unwrap();
+        assert_eq!(total.value(1), 1125);
+        assert_eq!(total.value_as_string(0), "4.50");
+
+        let returned = batch.column(4).as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert!(returned.value(1));
+
+        let sold_on = batch.column(5).as_any().downcast_ref::<Date32Array>().unwrap();
+        assert_eq!(sold_on.value(0), 19509);
+        assert!(sold_on.is_null(1));
+
+        let sold_at = batch
+            .column(6)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(sold_at.value(0), 1685615400000);
+
+        let tags = batch.column(7).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(tags.value(0), r#"["fresh","local"]"#);
+        //Arrays are not nullable when the column is not, so a null becomes an empty array.
+        assert_eq!(tags.value(1), "[]");
+    }
+
+    #[test]
+    fn skips_the_header_row() {
+        let result_set = get_result_set(
+            &[("units", "bigint")],
+            vec![vec![Some("units")], vec![Some("3")]],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_record_batch(&result_set, Some(true), &options);
+        assert!(result.is_ok());
+        let batch = result.unwrap();
+        assert_eq!(batch.num_rows(), 1);
+    }
+
+    #[test]
+    fn replaces_conversion_failures() {
+        let result_set = get_result_set(&[("units", "bigint")], vec![vec![Some("abc")]]);
+
+        let result = convert_to_record_batch(&result_set, None, &ConversionOptions::new());
+        assert!(result.is_ok());
+        let batch = result.unwrap();
+        let units = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(units.value(0), 0);
+    }
+
+    #[test]
+    fn reports_conversion_failures() {
+        let result_set = get_result_set(
+            &[("units", "bigint")],
+            vec![vec![Some("units")], vec![Some("3")], vec![Some("abc")]],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_record_batch(&result_set, Some(true), &options);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ResultSetConversionError::UnableToConvertValue(error) => {
+                let data = error.data.unwrap();
+                assert_eq!(data["value"], "abc");
+                assert_eq!(data["row"], 2);
+            }
+            _ => panic!("Expected UnableToConvertValue"),
+        }
+    }
+
+    #[test]
+    fn non_finite_doubles_are_null() {
+        let mut result_set = get_result_set(
+            &[("ratio", "double")],
+            vec![vec![Some("NaN")], vec![Some("Infinity")], vec![Some("0.5")]],
+        );
+        let column_info = result_set.result_set_metadata.as_mut().unwrap().column_info.as_mut();
+        column_info.unwrap()[0].nullable = Some(ColumnNullable::Nullable);
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_record_batch(&result_set, None, &options);
+        assert!(result.is_ok());
+        let batch = result.unwrap();
+        let ratio = batch.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert!(ratio.is_null(0));
+        assert!(ratio.is_null(1));
+        assert_eq!(ratio.value(2), 0.5);
+
+        //The JSON conversion agrees.
+        let rows = convert_to_json_with_options(&result_set, None, &options).unwrap();
+        assert_eq!(rows, json!([{"ratio": null}, {"ratio": null}, {"ratio": 0.5}]));
+    }
+
+    #[test]
+    fn no_rows() {
+        let result_set = get_result_set(&[("units", "bigint")], vec![]);
+
+        let result = convert_to_record_batch(&result_set, None, &ConversionOptions::new());
+        assert!(result.is_ok());
+        let batch = result.unwrap();
+        assert_eq!(batch.num_rows(), 0);
+        assert_eq!(batch.num_columns(), 1);
+    }
+}
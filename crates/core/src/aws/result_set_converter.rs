@@ -1,23 +1,112 @@
 use std::str::FromStr;
 
 use aws_sdk_athena::types::{ColumnNullable, ResultSet, ResultSetMetadata};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat};
 use serde_json::{json, Map, Value};
 
+use crate::types::aws::athena_manager::athena_manager_errors::ResultSetConversionError;
+use crate::types::error::GlyphxErrorData;
+
 ///This enum holds the legal datatypes that can be returned from Athena and converted to JSON.
 #[derive(Debug)]
-enum LegalDataTypes {
+pub(crate) enum LegalDataTypes {
     Bool(String),
     Number(String),
+    Decimal(String),
     String(String),
+    Date(String),
+    Timestamp(String),
+    TimestampWithTimeZone(String),
+    Array(String),
+    Map(String),
     Illegal(String),
 }
+
+impl LegalDataTypes {
+    ///The name of the Athena data type that this was converted from.
+    pub(crate) fn get_type_name(&self) -> &str {
+        match self {
+            LegalDataTypes::Bool(name) => name,
+            LegalDataTypes::Number(name) => name,
+            LegalDataTypes::Decimal(name) => name,
+            LegalDataTypes::String(name) => name,
+            LegalDataTypes::Date(name) => name,
+            LegalDataTypes::Timestamp(name) => name,
+            LegalDataTypes::TimestampWithTimeZone(name) => name,
+            LegalDataTypes::Array(name) => name,
+            LegalDataTypes::Map(name) => name,
+            LegalDataTypes::Illegal(name) => name,
+        }
+    }
+}
+
+///How dates and timestamps are written to JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TemporalFormat {
+    ///The number of milliseconds since the unix epoch.  Timestamps without a time zone are
+    ///treated as UTC.
+    #[default]
+    EpochMillis,
+    ///An ISO 8601 string, i.e. 2023-06-01 or 2023-06-01T12:30:00.000.
+    Iso8601,
+}
+
+///Controls how the values of a result set are converted to JSON.  By default, decimals are
+///written as exact JSON strings and a value that can not be converted to the data type of its
+///column is replaced with null, or the default value of the column when it is not nullable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversionOptions {
+    temporal_format: TemporalFormat,
+    report_conversion_failures: bool,
+    lossy_decimals: bool,
+}
+
+impl ConversionOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_temporal_format(mut self, temporal_format: TemporalFormat) -> Self {
+        self.temporal_format = temporal_format;
+        self
+    }
+
+    ///When true, a value that can not be converted is returned as an error instead of being
+    ///replaced.
+    pub fn with_report_conversion_failures(mut self, report_conversion_failures: bool) -> Self {
+        self.report_conversion_failures = report_conversion_failures;
+        self
+    }
+
+    ///When true, decimals are written as JSON numbers, which are floating point and may lose
+    ///precision.  By default they are written as JSON strings that hold the exact value.
+    pub fn with_lossy_decimals(mut self, lossy_decimals: bool) -> Self {
+        self.lossy_decimals = lossy_decimals;
+        self
+    }
+
+    pub fn get_temporal_format(&self) -> TemporalFormat {
+        self.temporal_format
+    }
+
+    pub fn get_report_conversion_failures(&self) -> bool {
+        self.report_conversion_failures
+    }
+
+    pub fn get_lossy_decimals(&self) -> bool {
+        self.lossy_decimals
+    }
+}
+
 ///This internal struct holds information about the columns that were returned in the ResultSet from Athena.
 ///This repesents a conversion of the metadata information from the ResultSetMetadata struct into
 ///something useful for this task.
-struct ColumnInformation {
-    name: String,
-    data_type: LegalDataTypes,
-    nullable: bool,
+pub(crate) struct ColumnInformation {
+    pub(crate) name: String,
+    pub(crate) data_type: LegalDataTypes,
+    pub(crate) nullable: bool,
+    pub(crate) precision: i32,
+    pub(crate) scale: i32,
 }
 
 ///This function will take the input Athena type and convert it into the appropriate LegalDataTypes enum.
@@ -34,10 +123,21 @@ fn convert_data_type_to_legal_type(input: &str) -> LegalDataTypes {
         "bigint" => LegalDataTypes::Number(clone),
         "double" => LegalDataTypes::Number(clone),
         "float" => LegalDataTypes::Number(clone),
-        "decimal" => LegalDataTypes::Number(clone),
+        "real" => LegalDataTypes::Number(clone),
         "string" => LegalDataTypes::String(clone),
+        "date" => LegalDataTypes::Date(clone),
+        value if value.starts_with("decimal") => LegalDataTypes::Decimal(String::from("decimal")),
         value if value.starts_with("char") => LegalDataTypes::String(String::from("char")),
         value if value.starts_with("varchar") => LegalDataTypes::String(String::from("varchar")),
+        value if value.starts_with("timestamp") && value.ends_with("with time zone") => {
+            LegalDataTypes::TimestampWithTimeZone(String::from("timestamp with time zone"))
+        }
+        value if value.starts_with("timestamp") => {
+            LegalDataTypes::Timestamp(String::from("timestamp"))
+        }
+        value if value.starts_with("array") => LegalDataTypes::Array(String::from("array")),
+        value if value.starts_with("map") => LegalDataTypes::Map(String::from("map")),
+        value if value.starts_with("row") => LegalDataTypes::Map(String::from("row")),
         _ => LegalDataTypes::Illegal(clone),
     }
 }
@@ -45,7 +145,7 @@ fn convert_data_type_to_legal_type(input: &str) -> LegalDataTypes {
 ///This function will take in a ResultSetMetadata struct from a ResultSet and convert it into a Vec of ColumnInformation structs.
 ///# Arguments
 ///`metadata` - A ResultSetMetadata struct that contains information about the columns returned from Athena.
-fn get_column_info(metadata: &ResultSetMetadata) -> Vec<ColumnInformation> {
+pub(crate) fn get_column_info(metadata: &ResultSetMetadata) -> Vec<ColumnInformation> {
     let mut column_information: Vec<ColumnInformation> = Vec::new();
     for column in metadata.column_info().unwrap() {
         let name = column.name().unwrap();
@@ -58,6 +158,8 @@ fn get_column_info(metadata: &ResultSetMetadata) -> Vec<ColumnInformation> {
             name: name.to_string(),
             data_type: convert_data_type_to_legal_type(data_type),
             nullable,
+            precision: column.precision(),
+            scale: column.scale(),
         });
     }
     column_information
@@ -67,7 +169,7 @@ fn get_column_info(metadata: &ResultSetMetadata) -> Vec<ColumnInformation> {
 ///JSON Boolean value. 
 ///# Arguments
 ///`input` - A &str that represents a value from a result set.
-fn convert_str_to_json_bool(input: &str) -> Value {
+pub(crate) fn convert_str_to_json_bool(input: &str) -> Value {
     let clean_input = input.trim().to_lowercase();
     match clean_input.as_str() {
         "true" => Value::Bool(true),
@@ -98,6 +200,275 @@ fn convert_str_to_json_number(input: &str) -> Value {
     }
 }
 
+///Athena returns NaN, Infinity and -Infinity for doubles that are not finite.  JSON can not hold
+///them, so both the JSON and the Arrow conversions treat them as null rather than as values that
+///could not be converted.
+pub(crate) fn is_non_finite(input: &str) -> bool {
+    input
+        .trim()
+        .parse::<f64>()
+        .is_ok_and(|value| !value.is_finite())
+}
+
+///This conversion function will take in a &str from a result set and attempt to convert it to a
+///JSON String that holds the exact decimal, so that no precision is lost to floating point.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+pub(crate) fn convert_str_to_json_decimal(input: &str) -> Value {
+    let clean_input = input.trim();
+    let digits = clean_input.strip_prefix(['-', '+']).unwrap_or(clean_input);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Value::Null;
+    }
+    Value::String(clean_input.to_string())
+}
+
+///Parses a date from a result set, i.e. 2023-06-01.
+pub(crate) fn parse_date(input: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d").ok()
+}
+
+///Parses a timestamp without a time zone from a result set, i.e. 2023-06-01 12:30:00.000.
+pub(crate) fn parse_timestamp(input: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(input.trim(), "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+///Parses a timestamp with a time zone from a result set, i.e. 2023-06-01 12:30:00.000 UTC.  Only
+///UTC and offsets, i.e. +05:00, are understood as time zones.
+pub(crate) fn parse_timestamp_with_time_zone(input: &str) -> Option<DateTime<FixedOffset>> {
+    let (timestamp, time_zone) = input.trim().rsplit_once(' ')?;
+    let offset = match time_zone {
+        "UTC" | "Z" | "GMT" => "+00:00",
+        offset => offset,
+    };
+    DateTime::parse_from_str(&format!("{} {}", timestamp, offset), "%Y-%m-%d %H:%M:%S%.f %z").ok()
+}
+
+///This conversion function will take in a &str from a result set, i.e. 2023-06-01, and attempt
+///to convert it to a JSON date in the given format.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+///`temporal_format` - The format to write the date in.
+fn convert_str_to_json_date(input: &str, temporal_format: TemporalFormat) -> Value {
+    let date = parse_date(input);
+    if date.is_none() {
+        return Value::Null;
+    }
+    let date = date.unwrap();
+    match temporal_format {
+        TemporalFormat::EpochMillis => {
+            json!(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis())
+        }
+        TemporalFormat::Iso8601 => json!(date.format("%Y-%m-%d").to_string()),
+    }
+}
+
+///This conversion function will take in a &str from a result set, i.e. 2023-06-01 12:30:00.000,
+///and attempt to convert it to a JSON timestamp in the given format.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+///`temporal_format` - The format to write the timestamp in.
+fn convert_str_to_json_timestamp(input: &str, temporal_format: TemporalFormat) -> Value {
+    let timestamp = parse_timestamp(input);
+    if timestamp.is_none() {
+        return Value::Null;
+    }
+    let timestamp = timestamp.unwrap();
+    match temporal_format {
+        TemporalFormat::EpochMillis => json!(timestamp.and_utc().timestamp_millis()),
+        TemporalFormat::Iso8601 => json!(timestamp.format("%Y-%m-%dT%H:%M:%S%.3f").to_string()),
+    }
+}
+
+///This conversion function will take in a &str from a result set, i.e.
+///2023-06-01 12:30:00.000 UTC, and attempt to convert it to a JSON timestamp in the given format.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+///`temporal_format` - The format to write the timestamp in.
+fn convert_str_to_json_timestamp_with_time_zone(
+    input: &str,
+    temporal_format: TemporalFormat,
+) -> Value {
+    let timestamp = parse_timestamp_with_time_zone(input);
+    if timestamp.is_none() {
+        return Value::Null;
+    }
+    let timestamp = timestamp.unwrap();
+    match temporal_format {
+        TemporalFormat::EpochMillis => json!(timestamp.timestamp_millis()),
+        TemporalFormat::Iso8601 => json!(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)),
+    }
+}
+
+///Reads the text that Athena returns for arrays, maps and rows, i.e. [1, 2, 3] or {a=1, b=2}.
+///Athena does not quote the strings inside of these values, so each element is read up to the
+///next , ] or } and is converted to a number, boolean or null when it looks like one.
+struct ComplexValueParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> ComplexValueParser<'a> {
+    ///Parses the input into JSON, returning None if it is not well formed.
+    fn parse(input: &'a str) -> Option<Value> {
+        let mut parser = ComplexValueParser { input, position: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.position != input.len() {
+            return None;
+        }
+        Some(value)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    ///Reads up to, but not including, the next delimiter or the end of the input.
+    fn read_until(&mut self, delimiters: &[u8]) -> &'a str {
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if !delimiters.contains(&c)) {
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn parse_value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_map(),
+            _ => Some(convert_str_to_json_scalar(self.read_until(b",]}").trim())),
+        }
+    }
+
+    fn parse_array(&mut self) -> Option<Value> {
+        self.position += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Some(Value::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Some(Value::Array(values));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_map(&mut self) -> Option<Value> {
+        self.position += 1;
+        let mut values = Map::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Some(Value::Object(values));
+        }
+        loop {
+            let key = self.read_until(b"=,}").trim();
+            if self.peek() != Some(b'=') {
+                return None;
+            }
+            self.position += 1;
+            let value = self.parse_value()?;
+            values.insert(key.to_string(), value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Some(Value::Object(values));
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+///Converts an element of an array, map or row to a JSON value.
+fn convert_str_to_json_scalar(input: &str) -> Value {
+    match input {
+        "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match serde_json::Number::from_str(input) {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(input.to_string()),
+        },
+    }
+}
+
+///This conversion function will take in a &str from a result set, i.e. [1, 2, 3], and attempt to
+///convert it to a JSON Array.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+pub(crate) fn convert_str_to_json_array(input: &str) -> Value {
+    match ComplexValueParser::parse(input) {
+        Some(value) if value.is_array() => value,
+        _ => Value::Null,
+    }
+}
+
+///This conversion function will take in a &str from a result set, i.e. {a=1, b=2}, and attempt
+///to convert it to a JSON Object.  This is used for both maps and rows.
+///# Arguments
+///`input` - A &str that represents a value from a result set.
+pub(crate) fn convert_str_to_json_map(input: &str) -> Value {
+    match ComplexValueParser::parse(input) {
+        Some(value) if value.is_object() => value,
+        _ => Value::Null,
+    }
+}
+
+///Converts the input with the convert function and handles null processing.  A missing or empty
+///input is null, which is replaced with the default when the field is not nullable.  If the input
+///can not be converted an Err is returned that holds the value to fall back to, so that the caller
+///can decide whether to report the failure.
+///# Arguments
+///`input` - An Option<&str> that represents a value from a result set.
+///`nullable` - A boolean that indicates if the field is nullable.
+///`default` - The value to use for null when the field is not nullable.
+///`convert` - The function that converts the input, returning a JSON Null if it can not.
+fn convert_field<F: Fn(&str) -> Value>(
+    input: &Option<&str>,
+    nullable: bool,
+    default: Value,
+    convert: F,
+) -> Result<Value, Value> {
+    let input = input.map(|input| input.trim()).filter(|input| !input.is_empty());
+    let value = match input {
+        Some(input) => convert(input),
+        None => Value::Null,
+    };
+    let is_converted = input.is_none() || !value.is_null();
+    let value = if value.is_null() && !nullable {
+        default
+    } else {
+        value
+    };
+    if is_converted {
+        Ok(value)
+    } else {
+        Err(value)
+    }
+}
+
 ///This conversion function will convert the input into a JSON Boolean.  It also handles null
 ///processing.
 ///# Arguments
@@ -105,22 +476,8 @@ fn convert_str_to_json_number(input: &str) -> Value {
 ///`nullable` - A boolean that indicates if the field is nullable. If the field is nullable and the
 ///input is None or cannot be converted to a JSON Boolean, then a JSON Null will be returned.
 ///If the field is not nullable Null will be converted to false.
-fn convert_boolean_field(input: &Option<&str>, nullable: bool) -> Value {
-    if input.is_some() {
-        let input = input.unwrap();
-        let value = convert_str_to_json_bool(input);
-        if value.is_null() && !nullable {
-            return Value::Bool(false);
-        } else {
-            return value;
-        }
-    } else {
-        if nullable {
-            return Value::Null;
-        } else {
-            return Value::Bool(false);
-        }
-    }
+fn convert_boolean_field(input: &Option<&str>, nullable: bool) -> Result<Value, Value> {
+    convert_field(input, nullable, Value::Bool(false), convert_str_to_json_bool)
 }
 
 ///This conversion function will convert the input into a JSON Number.  It also handles null
@@ -129,23 +486,16 @@ fn convert_boolean_field(input: &Option<&str>, nullable: bool) -> Value {
 ///`input` - An Option<&str> that represents a value from a result set.
 ///`nullable` - A boolean that indicates if the field is nullable. If the field is nullable and the
 ///input is None or cannot be converted to a JSON Number, then a JSON Null will be returned.
-///If the field is not nullable Null will be converted to 0.
-fn convert_number_field(input: &Option<&str>, nullable: bool) -> Value {
-    if input.is_some() {
-        let input = input.unwrap();
-        let value = convert_str_to_json_number(input);
-        if value.is_null() && !nullable {
-            return Value::Number(serde_json::Number::from(0));
-        } else {
-            return value;
-        }
-    } else {
-        if nullable {
-            return Value::Null;
-        } else {
-            return Value::Number(serde_json::Number::from(0));
-        }
-    }
+///If the field is not nullable Null will be converted to 0.  Doubles that are not finite are
+///null.
+fn convert_number_field(input: &Option<&str>, nullable: bool) -> Result<Value, Value> {
+    let input = input.filter(|input| !is_non_finite(input));
+    convert_field(
+        &input,
+        nullable,
+        Value::Number(serde_json::Number::from(0)),
+        convert_str_to_json_number,
+    )
 }
 ///This conversion function will convert the input into a JSON String.  It also handles null
 ///processing.
@@ -165,50 +515,148 @@ fn convert_string_field(input: &Option<&str>, nullable: bool) -> Value {
     }
 }
 
+///Converts the input into the JSON value for the data type of its column.  Returns an Err that
+///holds the value to fall back to if the input could not be converted.
+///# Arguments
+///`input` - An Option<&str> that represents a value from a result set.
+///`column` - The information about the column that the value is from.
+///`options` - The options that control the conversion.
+fn convert_column_value(
+    input: &Option<&str>,
+    column: &ColumnInformation,
+    options: &ConversionOptions,
+) -> Result<Value, Value> {
+    let nullable = column.nullable;
+    let temporal_format = options.temporal_format;
+    match &column.data_type {
+        LegalDataTypes::Bool(_) => convert_boolean_field(input, nullable),
+        LegalDataTypes::Number(_) => convert_number_field(input, nullable),
+        LegalDataTypes::Decimal(_) if options.lossy_decimals => {
+            convert_number_field(input, nullable)
+        }
+        LegalDataTypes::Decimal(_) => {
+            convert_field(input, nullable, json!("0"), convert_str_to_json_decimal)
+        }
+        LegalDataTypes::String(_) => Ok(convert_string_field(input, nullable)),
+        LegalDataTypes::Date(_) => convert_field(input, nullable, Value::Null, |input| {
+            convert_str_to_json_date(input, temporal_format)
+        }),
+        LegalDataTypes::Timestamp(_) => convert_field(input, nullable, Value::Null, |input| {
+            convert_str_to_json_timestamp(input, temporal_format)
+        }),
+        LegalDataTypes::TimestampWithTimeZone(_) => {
+            convert_field(input, nullable, Value::Null, |input| {
+                convert_str_to_json_timestamp_with_time_zone(input, temporal_format)
+            })
+        }
+        LegalDataTypes::Array(_) => {
+            convert_field(input, nullable, json!([]), convert_str_to_json_array)
+        }
+        LegalDataTypes::Map(_) => {
+            convert_field(input, nullable, json!({}), convert_str_to_json_map)
+        }
+        // Illeagal data types will never be included.
+        LegalDataTypes::Illegal(_) => convert_field(input, true, Value::Null, |_| Value::Null),
+    }
+}
+
+///Builds the error that is returned when a value could not be converted.
+///# Arguments
+///`column` - The information about the column that the value is from.
+///`value` - The value that could not be converted.
+///`row` - The index of the row in the result set.
+pub(crate) fn get_conversion_error(
+    column: &ColumnInformation,
+    value: &str,
+    row: usize,
+) -> ResultSetConversionError {
+    let data_type = column.data_type.get_type_name();
+    let data = json!({"column": column.name, "data_type": data_type, "value": value, "row": row});
+    match column.data_type {
+        LegalDataTypes::Illegal(_) => {
+            let message = format!(
+                "The column {} has the data type {}, which can not be converted to JSON",
+                column.name, data_type
+            );
+            ResultSetConversionError::UnsupportedDataType(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            ))
+        }
+        _ => {
+            let message = format!(
+                "Unable to convert the value {} of the column {} to a {}",
+                value, column.name, data_type
+            );
+            ResultSetConversionError::UnableToConvertValue(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            ))
+        }
+    }
+}
+
 ///This conversion function will convert the ResultSet into a JSON String. There is also an
 ///optional paramter to exlcude the first row of the result set.  This is useful, because some 
 ///queries/commands in athena will include a header row in the data as well as the information in
 ///the resultMetadata.  The includes_header_row will tell the function to skip the first row.
+///Values that can not be converted are replaced, see convert_to_json_with_options to have them
+///reported instead.
 ///# Arguments
 ///`result_set` - A ResultSet from the athena client.
 ///`includes_header_row` - An optional boolean that indicates if the first row should be skipped.
 pub fn convert_to_json(result_set: &ResultSet, includes_header_row: Option<bool>) -> Value {
+    //The default options do not report conversion failures, so this can not fail.
+    convert_to_json_with_options(result_set, includes_header_row, &ConversionOptions::default())
+        .unwrap()
+}
+
+///Converts the ResultSet into JSON in the same way as convert_to_json, using the given options.
+///# Arguments
+///`result_set` - A ResultSet from the athena client.
+///`includes_header_row` - An optional boolean that indicates if the first row should be skipped.
+///`options` - The options that control the conversion.
+pub fn convert_to_json_with_options(
+    result_set: &ResultSet,
+    includes_header_row: Option<bool>,
+    options: &ConversionOptions,
+) -> Result<Value, ResultSetConversionError> {
     let includes_header_row = includes_header_row.unwrap_or(false);
     let metadata = result_set.result_set_metadata().unwrap();
     let column_information = get_column_info(&metadata);
     let mut rows: Vec<Value> = Vec::new();
-    let mut first = true;
     if result_set.rows().is_none(){
-        return Value::Null;
+        return Ok(Value::Null);
     }
-    for row in result_set.rows().unwrap() {
-        if first && includes_header_row {
-            first = false;
+    for (row_number, row) in result_set.rows().unwrap().iter().enumerate() {
+        if row_number == 0 && includes_header_row {
             continue;
         }
         let mut key_values: Map<String, Value> = Map::new();
         let mut column_num = 0;
         for column in row.data().unwrap() {
-            let column_value = column.var_char_value();
+            let input = column.var_char_value();
             let column_information = &column_information[column_num];
             let column_name = &column_information.name;
-            let column_type = &column_information.data_type;
-            let column_nullable = &column_information.nullable;
-            let column_value = match column_type {
-                LegalDataTypes::Bool(_) => convert_boolean_field(&column_value, *column_nullable),
-                LegalDataTypes::Number(_) => convert_number_field(&column_value, *column_nullable),
-                LegalDataTypes::String(_) => convert_string_field(&column_value, *column_nullable),
-                // Illeagal data types will never be included.
-                LegalDataTypes::Illegal(_) => Value::Null,
+            let column_value = match convert_column_value(&input, column_information, options) {
+                Ok(value) => value,
+                Err(value) if !options.report_conversion_failures => value,
+                Err(_) => {
+                    return Err(get_conversion_error(
+                        column_information,
+                        input.unwrap_or_default(),
+                        row_number,
+                    ))
+                }
             };
             key_values.insert(column_name.to_string(), column_value);
             column_num += 1;
         }
         rows.push(Value::Object(key_values));
-
-        first = false;
     }
-    json!(rows)
+    Ok(json!(rows))
 }
 
 #[cfg(test)]
@@ -295,11 +743,11 @@ mod convert_data_type_to_legal_type {
     }
 
     #[test]
-    fn is_number_decimal() {
+    fn is_decimal() {
         let input = "decimal";
         let result = convert_data_type_to_legal_type(input);
         match result {
-            LegalDataTypes::Number(_) => assert!(true),
+            LegalDataTypes::Decimal(_) => assert!(true),
             _ => assert!(false),
         }
     }
@@ -347,6 +795,70 @@ mod convert_data_type_to_legal_type {
         }
     }
 
+    #[test]
+    fn is_decimal_with_precision() {
+        let input = "decimal(38,9)";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Decimal(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn is_date() {
+        let input = "date";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Date(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn is_timestamp() {
+        let input = "timestamp";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Timestamp(_) => assert!(true),
+            _ => assert!(false),
+        }
+
+        let input = "timestamp with time zone";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::TimestampWithTimeZone(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn is_array() {
+        let input = "array";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Array(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn is_map() {
+        let input = "map";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Map(_) => assert!(true),
+            _ => assert!(false),
+        }
+
+        let input = "row";
+        let result = convert_data_type_to_legal_type(input);
+        match result {
+            LegalDataTypes::Map(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
     #[test]
     fn is_illegal() {
         let input = "illegal";
@@ -504,6 +1016,200 @@ mod convert_str_to_json_number {
     }
 }
 
+#[cfg(test)]
+mod convert_str_to_json_decimal {
+    use super::*;
+
+    #[test]
+    fn keeps_precision() {
+        let input = "12345678901234567890.123456789";
+        let result = convert_str_to_json_decimal(input);
+        assert_eq!(result, "12345678901234567890.123456789");
+    }
+
+    #[test]
+    fn negative_decimal() {
+        let input = " -0.50 ";
+        let result = convert_str_to_json_decimal(input);
+        assert_eq!(result, "-0.50");
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "1.2.3";
+        let result = convert_str_to_json_decimal(input);
+        assert_eq!(result, Value::Null);
+
+        let input = ".";
+        let result = convert_str_to_json_decimal(input);
+        assert_eq!(result, Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod convert_str_to_json_date {
+    use super::*;
+
+    #[test]
+    fn epoch_millis() {
+        let input = "2023-06-01";
+        let result = convert_str_to_json_date(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, 1685577600000i64);
+    }
+
+    #[test]
+    fn iso_8601() {
+        let input = "2023-06-01";
+        let result = convert_str_to_json_date(input, TemporalFormat::Iso8601);
+        assert_eq!(result, "2023-06-01");
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "2023-13-01";
+        let result = convert_str_to_json_date(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod convert_str_to_json_timestamp {
+    use super::*;
+
+    #[test]
+    fn epoch_millis() {
+        let input = "2023-06-01 12:30:00.250";
+        let result = convert_str_to_json_timestamp(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, 1685622600250i64);
+    }
+
+    #[test]
+    fn iso_8601() {
+        let input = "2023-06-01 12:30:00.250";
+        let result = convert_str_to_json_timestamp(input, TemporalFormat::Iso8601);
+        assert_eq!(result, "2023-06-01T12:30:00.250");
+    }
+
+    #[test]
+    fn without_fraction() {
+        let input = "2023-06-01 12:30:00";
+        let result = convert_str_to_json_timestamp(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, 1685622600000i64);
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "June 1st";
+        let result = convert_str_to_json_timestamp(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod convert_str_to_json_timestamp_with_time_zone {
+    use super::*;
+
+    #[test]
+    fn utc() {
+        let input = "2023-06-01 12:30:00.250 UTC";
+        let result =
+            convert_str_to_json_timestamp_with_time_zone(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, 1685622600250i64);
+
+        let result = convert_str_to_json_timestamp_with_time_zone(input, TemporalFormat::Iso8601);
+        assert_eq!(result, "2023-06-01T12:30:00.250Z");
+    }
+
+    #[test]
+    fn offset() {
+        let input = "2023-06-01 12:30:00.000 +02:00";
+        let result =
+            convert_str_to_json_timestamp_with_time_zone(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, 1685615400000i64);
+
+        let result = convert_str_to_json_timestamp_with_time_zone(input, TemporalFormat::Iso8601);
+        assert_eq!(result, "2023-06-01T12:30:00.000+02:00");
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "2023-06-01 12:30:00.000 America/New_York";
+        let result =
+            convert_str_to_json_timestamp_with_time_zone(input, TemporalFormat::EpochMillis);
+        assert_eq!(result, Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod convert_str_to_json_array {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        let input = "[1, 2.5, -3]";
+        let result = convert_str_to_json_array(input);
+        assert_eq!(result, json!([1, 2.5, -3]));
+    }
+
+    #[test]
+    fn strings() {
+        let input = "[east, west, null, true]";
+        let result = convert_str_to_json_array(input);
+        assert_eq!(result, json!(["east", "west", null, true]));
+    }
+
+    #[test]
+    fn nested() {
+        let input = "[[1, 2], [], [{a=1}]]";
+        let result = convert_str_to_json_array(input);
+        assert_eq!(result, json!([[1, 2], [], [{"a": 1}]]));
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "[1, 2";
+        let result = convert_str_to_json_array(input);
+        assert_eq!(result, Value::Null);
+
+        let input = "{a=1}";
+        let result = convert_str_to_json_array(input);
+        assert_eq!(result, Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod convert_str_to_json_map {
+    use super::*;
+
+    #[test]
+    fn map() {
+        let input = "{east=1, west=2}";
+        let result = convert_str_to_json_map(input);
+        assert_eq!(result, json!({"east": 1, "west": 2}));
+    }
+
+    #[test]
+    fn row() {
+        let input = "{name=apple, prices=[1.5, 2], sold={east=3}}";
+        let result = convert_str_to_json_map(input);
+        assert_eq!(
+            result,
+            json!({"name": "apple", "prices": [1.5, 2], "sold": {"east": 3}})
+        );
+    }
+
+    #[test]
+    fn is_err() {
+        let input = "{east}";
+        let result = convert_str_to_json_map(input);
+        assert_eq!(result, Value::Null);
+
+        let input = "{a=1} trailing";
+        let result = convert_str_to_json_map(input);
+        assert_eq!(result, Value::Null);
+    }
+}
+
 #[cfg(test)]
 mod convert_boolean_field {
     use super::*;
@@ -511,40 +1217,40 @@ mod convert_boolean_field {
     #[test]
     fn convert_to_true() {
         let input = "true";
-        let result = convert_boolean_field(&Some(input), false);
+        let result = convert_boolean_field(&Some(input), false).unwrap();
         assert_eq!(result, true);
     }
 
     #[test]
     fn convert_to_false() {
         let input = "false";
-        let result = convert_boolean_field(&Some(input), false);
+        let result = convert_boolean_field(&Some(input), false).unwrap();
         assert_eq!(result, false);
     }
 
     #[test]
     fn convert_empty_is_nullable() {
         let input = "";
-        let result = convert_boolean_field(&Some(input), true);
+        let result = convert_boolean_field(&Some(input), true).unwrap();
         assert_eq!(result, Value::Null);
     }
 
     #[test]
     fn convert_empty_is_not_nullable() {
         let input = "";
-        let result = convert_boolean_field(&Some(input), false);
+        let result = convert_boolean_field(&Some(input), false).unwrap();
         assert_eq!(result, false);
     }
 
     #[test]
     fn convert_none_is_nullable() {
-        let result = convert_boolean_field(&None, true);
+        let result = convert_boolean_field(&None, true).unwrap();
         assert_eq!(result, Value::Null);
     }
 
     #[test]
     fn convert_none_is_not_nullable() {
-        let result = convert_boolean_field(&None, false);
+        let result = convert_boolean_field(&None, false).unwrap();
         assert_eq!(result, false);
     }
 }
@@ -556,35 +1262,55 @@ mod convert_number_field {
     #[test]
     fn convert_to_number() {
         let input = "123";
-        let result = convert_number_field(&Some(input), false);
+        let result = convert_number_field(&Some(input), false).unwrap();
         assert_eq!(result, 123);
     }
 
     #[test]
     fn convert_empty_is_nullable() {
         let input = "";
-        let result = convert_number_field(&Some(input), true);
+        let result = convert_number_field(&Some(input), true).unwrap();
         assert_eq!(result, Value::Null);
     }
 
+    #[test]
+    fn non_finite_is_null() {
+        for input in ["NaN", "Infinity", "-Infinity"] {
+            let result = convert_number_field(&Some(input), true);
+            assert_eq!(result, Ok(Value::Null));
+            let result = convert_number_field(&Some(input), false);
+            assert_eq!(result, Ok(json!(0)));
+        }
+    }
+
     #[test]
     fn convert_empty_is_not_nullable() {
         let input = "";
-        let result = convert_number_field(&Some(input), false);
+        let result = convert_number_field(&Some(input), false).unwrap();
         assert_eq!(result, 0);
     }
 
     #[test]
     fn convert_none_is_nullable() {
-        let result = convert_number_field(&None, true);
+        let result = convert_number_field(&None, true).unwrap();
         assert_eq!(result, Value::Null);
     }
 
     #[test]
     fn convert_none_is_not_nullable() {
-        let result = convert_number_field(&None, false);
+        let result = convert_number_field(&None, false).unwrap();
         assert_eq!(result, 0);
     }
+
+    #[test]
+    fn is_err() {
+        let input = "abc";
+        let result = convert_number_field(&Some(input), false);
+        assert_eq!(result.err().unwrap(), 0);
+
+        let result = convert_number_field(&Some(input), true);
+        assert_eq!(result.err().unwrap(), Value::Null);
+    }
 }
 
 #[cfg(test)]
@@ -765,3 +1491,204 @@ mod convert_to_json{
             assert!(result.is_null());
   }
 }
+
+#[cfg(test)]
+mod convert_to_json_with_options {
+    use super::*;
+    use aws_sdk_athena::types::{ColumnInfo, Datum, Row};
+
+    fn get_result_set(columns: &[(&str, &str)], rows: Vec<Vec<Option<&str>>>) -> ResultSet {
+        let column_info = columns
+            .iter()
+            .map(|(name, data_type)| {
+                ColumnInfo::builder()
+                    .name(name.to_string())
+                    .r#type(data_type.to_string())
+                    .nullable(ColumnNullable::NotNull)
+                    .build()
+            })
+            .collect();
+        let metadata = ResultSetMetadata::builder()
+            .set_column_info(Some(column_info))
+            .build();
+        let mut result_set = ResultSet::builder().result_set_metadata(metadata);
+        for row in rows {
+            let data = row
+                .iter()
+                .map(|value| {
+                    Datum::builder()
+                        .set_var_char_value(value.map(|value| value.to_string()))
+                        .build()
+                })
+                .collect();
+            result_set = result_set.rows(Row::builder().set_data(Some(data)).build());
+        }
+        result_set.build()
+    }
+
+    #[test]
+    fn converts_wider_types() {
+        let result_set = get_result_set(
+            &[
+                ("price", "decimal(38,9)"),
+                ("sold_on", "date"),
+                ("sold_at", "timestamp"),
+                ("regions", "array"),
+                ("units", "map"),
+            ],
+            vec![vec![
+                Some("12345678901234567890.123456789"),
+                Some("2023-06-01"),
+                Some("2023-06-01 12:30:00.000"),
+                Some("[east, west]"),
+                Some("{east=3, west=5}"),
+            ]],
+        );
+        let options = ConversionOptions::new().with_temporal_format(TemporalFormat::Iso8601);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            json!([{
+                "price": "12345678901234567890.123456789",
+                "sold_on": "2023-06-01",
+                "sold_at": "2023-06-01T12:30:00.000",
+                "regions": ["east", "west"],
+                "units": {"east": 3, "west": 5}
+            }])
+        );
+    }
+
+    #[test]
+    fn non_finite_doubles_are_not_conversion_failures() {
+        let result_set = get_result_set(
+            &[("ratio", "double")],
+            vec![vec![Some("NaN")], vec![Some("-Infinity")], vec![Some("0.5")]],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_ok());
+        //The column is not nullable, so the nulls are replaced with its default.
+        assert_eq!(
+            result.unwrap(),
+            json!([{"ratio": 0}, {"ratio": 0}, {"ratio": 0.5}])
+        );
+    }
+
+    #[test]
+    fn decimals_are_exact_by_default() {
+        let result_set = get_result_set(
+            &[("price", "decimal(10,2)"), ("total", "decimal(38,9)")],
+            vec![vec![Some("12.50"), None], vec![Some("-3"), Some("abc")]],
+        );
+
+        let result = convert_to_json_with_options(&result_set, None, &ConversionOptions::new());
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            json!([{"price": "12.50", "total": "0"}, {"price": "-3", "total": "0"}])
+        );
+
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decimals_can_be_lossy() {
+        let result_set = get_result_set(
+            &[("price", "decimal(10,2)"), ("total", "decimal(38,9)")],
+            vec![vec![Some("12.50"), None], vec![Some("-3"), Some("abc")]],
+        );
+        let options = ConversionOptions::new().with_lossy_decimals(true);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            json!([{"price": 12.5, "total": 0}, {"price": -3, "total": 0}])
+        );
+
+        let options = options.with_report_conversion_failures(true);
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn replaces_conversion_failures() {
+        let result_set = get_result_set(
+            &[("units", "bigint"), ("sold_on", "date")],
+            vec![vec![Some("abc"), Some("yesterday")]],
+        );
+
+        let result = convert_to_json_with_options(&result_set, None, &ConversionOptions::new());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), json!([{"units": 0, "sold_on": null}]));
+    }
+
+    #[test]
+    fn reports_conversion_failures() {
+        let result_set = get_result_set(
+            &[("region", "varchar"), ("units", "bigint")],
+            vec![
+                vec![Some("east"), Some("3")],
+                vec![Some("west"), Some("abc")],
+            ],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ResultSetConversionError::UnableToConvertValue(error) => {
+                let data = error.data.unwrap();
+                assert_eq!(data["column"], "units");
+                assert_eq!(data["data_type"], "bigint");
+                assert_eq!(data["value"], "abc");
+                assert_eq!(data["row"], 1);
+            }
+            _ => panic!("Expected UnableToConvertValue"),
+        }
+    }
+
+    #[test]
+    fn reports_unsupported_data_types() {
+        let result_set = get_result_set(&[("image", "varbinary")], vec![vec![Some("61 62")]]);
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ResultSetConversionError::UnsupportedDataType(_) => {}
+            _ => panic!("Expected UnsupportedDataType"),
+        }
+    }
+
+    #[test]
+    fn nulls_are_not_failures() {
+        let result_set = get_result_set(
+            &[("units", "bigint"), ("sold_on", "date")],
+            vec![vec![None, Some("")]],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_json_with_options(&result_set, None, &options);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), json!([{"units": 0, "sold_on": null}]));
+    }
+
+    #[test]
+    fn header_row_is_not_converted() {
+        let result_set = get_result_set(
+            &[("units", "bigint")],
+            vec![vec![Some("units")], vec![Some("3")]],
+        );
+        let options = ConversionOptions::new().with_report_conversion_failures(true);
+
+        let result = convert_to_json_with_options(&result_set, Some(true), &options);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), json!([{"units": 3}]));
+    }
+}
@@ -0,0 +1,427 @@
+///! This module holds the policy that our AWS managers use to retry calls that AWS has throttled.
+use crate::types::error::GlyphxErrorData;
+use aws_smithy_http::result::SdkError;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::Duration;
+
+///The error codes that AWS uses to tell us that we are making too many requests.
+const THROTTLING_ERROR_CODES: [&str; 8] = [
+    "TooManyRequestsException",
+    "ThrottlingException",
+    "Throttling",
+    "SlowDown",
+    "RequestLimitExceeded",
+    "RequestThrottled",
+    "RequestThrottledException",
+    "ProvisionedThroughputExceededException",
+];
+
+///Returns the backoff delay, before jitter, that follows the given attempt.  The first attempt is
+///attempt 0.
+pub(crate) fn get_backoff_delay(
+    initial_delay: Duration,
+    backoff_multiplier: f64,
+    max_delay: Duration,
+    attempt: u32,
+) -> Duration {
+    let delay =
+        initial_delay.as_secs_f64() * backoff_multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+    //Clamp before building the Duration, a large attempt will overflow to infinity.
+    let delay = delay.min(max_delay.as_secs_f64());
+    Duration::from_secs_f64(delay)
+}
+
+///Moves the delay by a random amount of up to jitter, a fraction of the delay.
+pub(crate) fn apply_jitter(delay: Duration, jitter: f64) -> Duration {
+    if jitter == 0.0 || delay.is_zero() {
+        return delay;
+    }
+    let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+    delay.mul_f64(factor)
+}
+
+///Controls how many times, and how quickly, we retry an AWS call that has failed with a
+///retryable error.  By default only throttling errors and failures to reach AWS are retried.
+///The delay between attempts starts at initial_delay and is multiplied by the backoff_multiplier
+///after each attempt, up to max_delay, and is moved by a random amount of up to jitter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    backoff_multiplier: f64,
+    max_delay: Duration,
+    jitter: f64,
+    retryable_error_codes: Vec<String>,
+    retry_transport_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(200),
+            backoff_multiplier: 2.0,
+            max_delay: Duration::from_secs(10),
+            jitter: 0.2,
+            retryable_error_codes: THROTTLING_ERROR_CODES
+                .iter()
+                .map(|code| code.to_string())
+                .collect(),
+            retry_transport_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///A policy that makes a single attempt and never retries.
+    pub fn no_retries() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    ///The number of times that we will make a call, including the first.  Values below 1 are
+    ///treated as 1.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    ///The delay before the first retry.
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    ///The factor that each delay is multiplied by.  Values below 1 are treated as 1, i.e. a
+    ///fixed delay.
+    pub fn with_backoff_multiplier(mut self, backoff_multiplier: f64) -> Self {
+        self.backoff_multiplier = backoff_multiplier.max(1.0);
+        self
+    }
+
+    ///The longest that we will wait between two attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    ///The fraction, between 0 and 1, of each delay that it may be moved by.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    ///Replaces the AWS error codes that will be retried.
+    pub fn with_retryable_error_codes(mut self, retryable_error_codes: Vec<String>) -> Self {
+        self.retryable_error_codes = retryable_error_codes;
+        self
+    }
+
+    ///Adds an AWS error code, i.e. InternalServerException, to the codes that will be retried.
+    pub fn with_retryable_error_code(mut self, error_code: &str) -> Self {
+        if !self.is_retryable_error_code(error_code) {
+            self.retryable_error_codes.push(error_code.to_string());
+        }
+        self
+    }
+
+    ///Whether or not timeouts and failures to dispatch the request to AWS will be retried.
+    pub fn with_retry_transport_errors(mut self, retry_transport_errors: bool) -> Self {
+        self.retry_transport_errors = retry_transport_errors;
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn get_initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    pub fn get_backoff_multiplier(&self) -> f64 {
+        self.backoff_multiplier
+    }
+
+    pub fn get_max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    pub fn get_jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    pub fn get_retryable_error_codes(&self) -> &[String] {
+        &self.retryable_error_codes
+    }
+
+    pub fn get_retry_transport_errors(&self) -> bool {
+        self.retry_transport_errors
+    }
+
+    pub fn is_retryable_error_code(&self, error_code: &str) -> bool {
+        self.retryable_error_codes
+            .iter()
+            .any(|code| code == error_code)
+    }
+
+    ///Returns true if the error is one that this policy will retry.
+    pub fn is_retryable<E: ProvideErrorMetadata, R>(&self, error: &SdkError<E, R>) -> bool {
+        match error {
+            SdkError::TimeoutError(_) => self.retry_transport_errors,
+            SdkError::DispatchFailure(e) => {
+                self.retry_transport_errors && (e.is_io() || e.is_timeout())
+            }
+            SdkError::ServiceError(e) => match e.err().code() {
+                Some(code) => self.is_retryable_error_code(code),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    ///Returns the delay, before jitter, that follows the given retry.  The first retry is retry 0.
+    pub fn get_delay(&self, retry: u32) -> Duration {
+        get_backoff_delay(
+            self.initial_delay,
+            self.backoff_multiplier,
+            self.max_delay,
+            retry,
+        )
+    }
+
+    ///Calls operation until it succeeds, fails with an error that is not retryable, or we have
+    ///made max_attempts calls.  Returns the last result along with the number of retries that
+    ///were made, so that the caller can record them in its error data.
+    ///# Arguments
+    ///* `operation` - A closure that makes the AWS call.  It is called once per attempt so any
+    ///  request body must be rebuilt inside of it.
+    pub async fn retry<T, E, R, F, Fut>(&self, operation: F) -> (Result<T, SdkError<E, R>>, u32)
+    where
+        E: ProvideErrorMetadata,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SdkError<E, R>>>,
+    {
+        self.retry_when(|_| false, operation).await
+    }
+
+    ///The same as retry, but service errors for which is_retryable_error returns true are also
+    ///retried.  This lets callers retry the typed error variants of an operation, i.e.
+    ///TooManyRequestsException, regardless of the error code that came with them.
+    ///# Arguments
+    ///* `is_retryable_error` - Returns true for the service errors that should be retried.
+    ///* `operation` - A closure that makes the AWS call.
+    pub async fn retry_when<T, E, R, P, F, Fut>(
+        &self,
+        is_retryable_error: P,
+        mut operation: F,
+    ) -> (Result<T, SdkError<E, R>>, u32)
+    where
+        E: ProvideErrorMetadata,
+        P: Fn(&E) -> bool,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SdkError<E, R>>>,
+    {
+        let mut retries = 0;
+        loop {
+            let res = operation().await;
+            match &res {
+                Err(e)
+                    if retries + 1 < self.max_attempts
+                        && (self.is_retryable(e)
+                            || matches!(e, SdkError::ServiceError(se) if is_retryable_error(se.err()))) =>
+                {
+                    let delay = apply_jitter(self.get_delay(retries), self.jitter);
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                _ => return (res, retries),
+            }
+        }
+    }
+}
+
+///Adds the number of retries that were made to the data of an error.  Nothing is added when
+///the call was not retried.
+pub fn record_retries(data: Value, retries: u32) -> Value {
+    if retries == 0 {
+        return data;
+    }
+    match data {
+        Value::Object(mut map) => {
+            map.insert("retries".to_string(), json!(retries));
+            Value::Object(map)
+        }
+        Value::Null => json!({ "retries": retries }),
+        data => json!({ "data": data, "retries": retries }),
+    }
+}
+
+///Adds the number of retries that were made to an existing GlyphxErrorData.
+pub fn record_retries_in_error_data(error_data: &mut GlyphxErrorData, retries: u32) {
+    if retries == 0 {
+        return;
+    }
+    let data = error_data.data.take().unwrap_or(Value::Null);
+    error_data.data = Some(record_retries(data, retries));
+}
+
+#[cfg(test)]
+mod is_retryable {
+    use super::*;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_http::result::ConnectorError;
+    use aws_smithy_types::error::ErrorMetadata;
+
+    fn service_error(code: &str) -> SdkError<ErrorMetadata, http::Response<SdkBody>> {
+        let err = ErrorMetadata::builder()
+            .code(code)
+            .message("an error has occurred")
+            .build();
+        let inner = http::Response::builder()
+            .status(400)
+            .body(SdkBody::empty())
+            .unwrap();
+        SdkError::service_error(err, inner)
+    }
+
+    #[test]
+    fn throttling_error() {
+        let policy = RetryPolicy::new();
+        assert!(policy.is_retryable(&service_error("TooManyRequestsException")));
+        assert!(policy.is_retryable(&service_error("SlowDown")));
+    }
+
+    #[test]
+    fn other_service_error() {
+        let policy = RetryPolicy::new();
+        assert!(!policy.is_retryable(&service_error("InternalServerException")));
+        let policy = policy.with_retryable_error_code("InternalServerException");
+        assert!(policy.is_retryable(&service_error("InternalServerException")));
+    }
+
+    #[test]
+    fn transport_error() {
+        let err: SdkError<ErrorMetadata, http::Response<SdkBody>> =
+            SdkError::dispatch_failure(ConnectorError::io("connection reset".into()));
+        assert!(RetryPolicy::new().is_retryable(&err));
+        assert!(!RetryPolicy::new()
+            .with_retry_transport_errors(false)
+            .is_retryable(&err));
+    }
+}
+
+#[cfg(test)]
+mod retry {
+    use super::*;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_types::error::ErrorMetadata;
+
+    fn throttled() -> Result<u32, SdkError<ErrorMetadata, http::Response<SdkBody>>> {
+        let err = ErrorMetadata::builder()
+            .code("ThrottlingException")
+            .message("slow down")
+            .build();
+        let inner = http::Response::builder()
+            .status(400)
+            .body(SdkBody::empty())
+            .unwrap();
+        Err(SdkError::service_error(err, inner))
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_initial_delay(Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn succeeds_after_throttle() {
+        let mut calls = 0;
+        let (res, retries) = policy()
+            .retry(|| {
+                calls += 1;
+                let call = calls;
+                async move {
+                    if call < 3 {
+                        throttled()
+                    } else {
+                        Ok(call)
+                    }
+                }
+            })
+            .await;
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(retries, 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up() {
+        let mut calls = 0;
+        let (res, retries) = policy()
+            .retry(|| {
+                calls += 1;
+                async { throttled() }
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(retries, 2);
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn no_retries() {
+        let mut calls = 0;
+        let (res, retries) = RetryPolicy::no_retries()
+            .retry(|| {
+                calls += 1;
+                async { throttled() }
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(retries, 0);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn retry_when() {
+        let mut calls = 0;
+        let (res, retries) = policy()
+            .retry_when(
+                |e: &ErrorMetadata| e.code() == Some("500"),
+                || {
+                    calls += 1;
+                    async {
+                        let err = ErrorMetadata::builder().code("500").build();
+                        let inner = http::Response::builder()
+                            .status(500)
+                            .body(SdkBody::empty())
+                            .unwrap();
+                        let res: Result<u32, _> = Err(SdkError::service_error(err, inner));
+                        res
+                    }
+                },
+            )
+            .await;
+        assert!(res.is_err());
+        assert_eq!(retries, 2);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn record_retries_in_data() {
+        let data = record_retries(json!({ "key": "value" }), 2);
+        assert_eq!(data, json!({ "key": "value", "retries": 2 }));
+        let data = record_retries(json!({ "key": "value" }), 0);
+        assert_eq!(data, json!({ "key": "value" }));
+        let mut error_data = GlyphxErrorData::new("error".to_string(), None, None);
+        record_retries_in_error_data(&mut error_data, 1);
+        assert_eq!(error_data.data, Some(json!({ "retries": 1 })));
+    }
+}
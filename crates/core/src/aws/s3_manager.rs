@@ -15,7 +15,10 @@ use aws_sdk_s3::operation::put_object::{PutObjectError, PutObjectOutput};
 
 pub use crate::types::aws::s3_manager::*;
 pub use crate::types::aws::upload_stream::*;
+use crate::aws::object_store::{ObjectStore, ObjectUploadStream};
 use crate::aws::upload_stream::UploadStream;
+pub use crate::aws::retry_policy::RetryPolicy;
+use crate::aws::retry_policy::record_retries;
 use http::Request;
 use log::warn;
 use serde_json::json;
@@ -26,6 +29,7 @@ use std::time::Duration;
 pub struct S3Manager {
     client: S3Client,
     bucket: String,
+    retry_policy: RetryPolicy,
 }
 
 /// this API wrapper uses our impl pattern.  As part of that pattern, we use dependency injection
@@ -343,6 +347,18 @@ impl S3Manager {
         self.client.clone()
     }
 
+    /// Sets the policy that is used to retry object operations, and the part uploads of any
+    /// UploadStreams that we create, when S3 throttles them.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// A Get accesor for the RetryPolicy.
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     /// Our public bucket_exists function that will return Ok(()) .  This uses our bucket_exists_impl function
     /// which uses dependency injection to inject the calls to S3.  In this manner, we can fully
     /// test the bucket_exists logic with no down stream effects.  This function just wraps our impl call.
@@ -445,7 +461,7 @@ impl S3Manager {
     ) -> Result<(), UploadObjectError> {
         self.upload_object_impl(
             key,
-            ByteStream::from(data),
+            data,
             content_type,
             &S3ManagerOpsImpl {},
         )
@@ -482,6 +498,7 @@ impl S3Manager {
         let s3_manager = Self {
             client,
             bucket: bucket.clone(),
+            retry_policy: RetryPolicy::default(),
         };
         Ok(s3_manager)
     }
@@ -609,8 +626,9 @@ impl S3Manager {
         key: &str,
         aws_operations: &T,
     ) -> Result<S3FileInfo, GetFileInformationError> {
-        let head_result = aws_operations
-            .get_file_information_operation(&self.client, &self.bucket, key)
+        let (head_result, retries) = self
+            .retry_policy
+            .retry(|| aws_operations.get_file_information_operation(&self.client, &self.bucket, key))
             .await;
         if head_result.is_err() {
             let e = head_result.err().unwrap();
@@ -628,7 +646,7 @@ impl S3Manager {
                 ));
             } else {
                 let msg = e.meta().message().unwrap().to_string();
-                let data = json!({ "bucket_name": self.bucket, "key" : key });
+                let data = record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries);
                 warn!(
                     "Error calling head_object for bucket {}, key {}, error : {} ",
                     self.bucket, key, e
@@ -697,8 +715,9 @@ impl S3Manager {
         key: &str,
         aws_operations: &T,
     ) -> Result<ByteStream, GetObjectStreamError> {
-        let res = aws_operations
-            .get_object_stream_operation(&self.client, &self.bucket, key)
+        let (res, retries) = self
+            .retry_policy
+            .retry(|| aws_operations.get_object_stream_operation(&self.client, &self.bucket, key))
             .await;
         match res {
             Ok(result) => {
@@ -710,7 +729,7 @@ impl S3Manager {
                     GetObjectError::InvalidObjectState(_) => {
                         return Err(GetObjectStreamError::ObjectUnavailable(GlyphxErrorData::new(
                             format!("The object {} exists on the bucket {}, but is archived and cannot be accessed", key, self.bucket),
-                            Some(json!({ "bucket_name": self.bucket, "key" : key })),
+                            Some(record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries)),
                             None,
                         )));
                     }
@@ -720,14 +739,14 @@ impl S3Manager {
                                 "The object {} does not exist on the bucket {}",
                                 key, self.bucket
                             ),
-                            Some(json!({ "bucket_name": self.bucket, "key" : key })),
+                            Some(record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries)),
                             None,
                         )));
                     }
 
                     GetObjectError::Unhandled(unhandled) => {
                         let msg = unhandled.to_string();
-                        let data = json!({ "bucket_name": self.bucket, "key" : key });
+                        let data = record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries);
                         return Err(GetObjectStreamError::UnexpectedError(GlyphxErrorData::new(
                             msg,
                             Some(data),
@@ -736,7 +755,7 @@ impl S3Manager {
                     }
                     _ => {
                         let msg = e.meta().message().unwrap().to_string();
-                        let data = json!({ "bucket_name": self.bucket, "key" : key });
+                        let data = record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries);
                         return Err(GetObjectStreamError::UnexpectedError(GlyphxErrorData::new(
                             msg,
                             Some(data),
@@ -776,7 +795,7 @@ impl S3Manager {
             return Err(GetUploadStreamError::UnexpectedError(GlyphxErrorData::new(String::from("An error occurred while getting the upload stream.  See the inner error for more information"), Some(json!({"bucket_name": self.bucket, "key": key})), Some(inner_err))));
         }
 
-        Ok(res.unwrap())
+        Ok(res.unwrap().with_retry_policy(self.retry_policy.clone()))
     }
 
     /// The implementation for our delete_object call.  This function will take a
@@ -789,8 +808,9 @@ impl S3Manager {
         key: &str,
         aws_operations: &T,
     ) -> Result<(), RemoveObjectError> {
-        let res = aws_operations
-            .remove_object_operation(&self.client, &self.bucket, key)
+        let (res, retries) = self
+            .retry_policy
+            .retry(|| aws_operations.remove_object_operation(&self.client, &self.bucket, key))
             .await;
         match res {
             Ok(_) => {
@@ -799,7 +819,7 @@ impl S3Manager {
             Err(e) => {
                 let e = e.into_service_error();
                 let msg = e.meta().to_string();
-                let data = json!({ "bucket_name": self.bucket, "key" : key });
+                let data = record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries);
                 return Err(RemoveObjectError::UnexpectedError(GlyphxErrorData::new(
                     msg,
                     Some(data),
@@ -813,18 +833,28 @@ impl S3Manager {
     /// S3ManagerOps trait to make the actual calls to AWS.
     /// # Arguments
     /// * `key` - A String that represents the filename to upload.
-    /// * `body` - A ByteStream that represents the body of the file to upload.
+    /// * `data` - A Vec<u8> that represents the body of the file to upload.  A new ByteStream is
+    ///   built from it for each attempt.
     /// * `content_type` - An optional String that represents the content type of the file to upload.
     /// * `aws_operations` - An implementation of the S3ManagerOps trait that will be used to make calls to S3.
     async fn upload_object_impl<T: S3ManagerOps>(
         &self,
         key: &str,
-        body: ByteStream,
+        data: Vec<u8>,
         content_type: Option<String>,
         aws_operations: &T,
     ) -> Result<(), UploadObjectError> {
-        let res = aws_operations
-            .put_object_operation(&self.client, &self.bucket, key, body, content_type)
+        let (res, retries) = self
+            .retry_policy
+            .retry(|| {
+                aws_operations.put_object_operation(
+                    &self.client,
+                    &self.bucket,
+                    key,
+                    ByteStream::from(data.clone()),
+                    content_type.clone(),
+                )
+            })
             .await;
         match res {
             Ok(_) => {
@@ -833,7 +863,7 @@ impl S3Manager {
             Err(e) => {
                 let e = e.into_service_error();
                 let msg = e.meta().to_string();
-                let data = json!({ "bucket_name": self.bucket, "key" : key });
+                let data = record_retries(json!({ "bucket_name": self.bucket, "key" : key }), retries);
                 return Err(UploadObjectError::UnexpectedError(GlyphxErrorData::new(
                     msg,
                     Some(data),
@@ -844,6 +874,62 @@ impl S3Manager {
     }
 }
 
+///Exposes our S3Manager as an ObjectStore so that consumers do not need to know whether their
+///files live in S3 or on the local file system.
+#[async_trait]
+impl ObjectStore for S3Manager {
+    fn get_bucket_name(&self) -> String {
+        S3Manager::get_bucket_name(self)
+    }
+
+    async fn bucket_exists(&self) -> Result<(), BucketExistsError> {
+        S3Manager::bucket_exists(self).await
+    }
+
+    async fn file_exists(&self, key: &str) -> Result<(), FileExistsError> {
+        S3Manager::file_exists(self, key).await
+    }
+
+    async fn list_objects(&self, filter: Option<String>) -> Result<Vec<String>, ListObjectsError> {
+        S3Manager::list_objects(self, filter).await
+    }
+
+    async fn get_file_information(
+        &self,
+        key: &str,
+    ) -> Result<S3FileInfo, GetFileInformationError> {
+        S3Manager::get_file_information(self, key).await
+    }
+
+    async fn get_object_stream(&self, key: &str) -> Result<ByteStream, GetObjectStreamError> {
+        S3Manager::get_object_stream(self, key).await
+    }
+
+    async fn get_upload_stream(
+        &self,
+        key: &str,
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
+        let upload_stream = S3Manager::get_upload_stream(self, key).await;
+        if upload_stream.is_err() {
+            return Err(upload_stream.err().unwrap());
+        }
+        Ok(Box::new(upload_stream.unwrap()))
+    }
+
+    async fn remove_object(&self, key: &str) -> Result<(), RemoveObjectError> {
+        S3Manager::remove_object(self, key).await
+    }
+
+    async fn upload_object(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        content_type: Option<String>,
+    ) -> Result<(), UploadObjectError> {
+        S3Manager::upload_object(self, key, data, content_type).await
+    }
+}
+
 //We will use our default trait to create a dummy S3Manager for testing.
 //This will allow us to create an S3 manager in structures that may hold 
 //an instance to an S3 manager, and allow us to use our impl patterns to write tests 
@@ -853,7 +939,7 @@ impl Default for S3Manager {
        //We just want an empty config as we are not going to actually call anything on it.
        let config = aws_config::SdkConfig::builder().build();
         let client = S3Client::new(&config);
-        S3Manager { client, bucket: "mock".to_string()  }
+        S3Manager { client, bucket: "mock".to_string(), retry_policy: RetryPolicy::default() }
    } 
 }
 #[cfg(test)]
@@ -1949,7 +2035,7 @@ mod upload_object {
         let s3_manager_result = S3Manager::new_impl(bucket.clone(), &mock_ops).await;
         let s3_manager = s3_manager_result.ok().unwrap();
 
-        let res = s3_manager.upload_object_impl(&key, ByteStream::from(bytes), None, &mock_ops).await;
+        let res = s3_manager.upload_object_impl(&key, bytes, None, &mock_ops).await;
         assert!(res.is_ok());
     }
 
@@ -1987,11 +2073,56 @@ mod upload_object {
         let s3_manager_result = S3Manager::new_impl(bucket.clone(), &mock_ops).await;
         let s3_manager = s3_manager_result.ok().unwrap();
 
-        let res = s3_manager.upload_object_impl(&key, ByteStream::from(bytes), None, &mock_ops).await;
+        let res = s3_manager.upload_object_impl(&key, bytes, None, &mock_ops).await;
         assert!(res.is_err());
         let is_unexpected = match res.as_ref().err().unwrap() {
             UploadObjectError::UnexpectedError(_) => true,
         };
         assert!(is_unexpected);
     }
+
+    #[tokio::test]
+    async fn is_throttled() {
+        let bucket = "jps-test-bucket".to_string();
+        let key = "jps-test-key".to_string();
+        let bytes = vec![0, 1, 2, 3, 4];
+        let mut mock_ops = MockS3ManagerOps::new();
+        mock_ops
+            .expect_bucket_exists_operation()
+            .returning(|_, _| Ok(true))
+            .times(1);
+
+        mock_ops
+            .expect_put_object_operation()
+            .returning(|_, _, _, _, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("Please reduce your request rate.")
+                    .code("SlowDown")
+                    .build();
+                let unhandled = Unhandled::builder()
+                    .meta(meta)
+                    .source("Please reduce your request rate.")
+                    .build();
+                let err = PutObjectError::Unhandled(unhandled);
+                let inner = http::Response::builder()
+                    .status(503)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            })
+            .times(3);
+        let s3_manager_result = S3Manager::new_impl(bucket.clone(), &mock_ops).await;
+        let s3_manager = s3_manager_result.ok().unwrap().with_retry_policy(
+            RetryPolicy::new()
+                .with_max_attempts(3)
+                .with_initial_delay(Duration::ZERO),
+        );
+
+        let res = s3_manager.upload_object_impl(&key, bytes, None, &mock_ops).await;
+        let retries = match res.err().unwrap() {
+            UploadObjectError::UnexpectedError(e) => e.data.unwrap()["retries"].clone(),
+        };
+        assert_eq!(retries, 2);
+    }
 }
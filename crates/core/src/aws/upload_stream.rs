@@ -19,6 +19,8 @@ use aws_smithy_http::byte_stream::ByteStream;
 use async_trait::async_trait;
 use mockall::*;
 pub use crate::types::aws::upload_stream::*;
+use crate::aws::object_store::ObjectUploadStream;
+use crate::aws::retry_policy::{record_retries, RetryPolicy};
 
 const BUFFER_LIMIT: usize = 1024 * 1024 * 5; // 5 MB
 
@@ -39,6 +41,7 @@ pub struct UploadStream {
     upload_id: String,
     upload_parts: Vec<CompletedPart>,
     state: UploadStreamState,
+    retry_policy: RetryPolicy,
 }
 
 ///This is our trait which wraps the AWS S3 Functions.  We pass this trait 
@@ -213,6 +216,17 @@ impl UploadStream {
         &self.state
     }
 
+    /// A Get accessor to get the policy used to retry throttled part uploads
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Sets the policy that is used to retry part uploads when S3 throttles them.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     ///Our contructor for UploadStream.  This function is used to create a new UploadStream.
     ///This function uses our impl dependency injection pattern and calls the write_impl
     ///function passing it the UploadStreamOpsImpl structure which implements the UploadStreamOps
@@ -238,7 +252,7 @@ impl UploadStream {
             part_number: 1,
             buffer_size: 0,
             upload_parts: Vec::new(),
-
+            retry_policy: RetryPolicy::default(),
         }
     }
     ///This function is used to submit bytes to the strcture to be uploaded.  This function uses
@@ -260,6 +274,15 @@ impl UploadStream {
         self.finish_impl(&UploadStreamImpl {}).await
     }
 
+    ///This function is used to abandon an open stream.  The multipart upload is aborted so that
+    ///S3 discards any parts that have already been uploaded.  Streams that have already been
+    ///finished or aborted are left untouched.
+    pub async fn abort(&mut self) {
+        if let UploadStreamState::Ok = self.state {
+            self.abort_impl(&UploadStreamImpl {}).await
+        }
+    }
+
     /// This is our private impl for new.  This function handles error handling and resolving
     /// the values retured by the aws_operation.  This pattern allows us to test our logic in
     /// our unit tests without hitting aws directly.
@@ -312,6 +335,7 @@ impl UploadStream {
             upload_id,
             upload_parts: Vec::new(),
             state: UploadStreamState::Ok,
+            retry_policy: RetryPolicy::default(),
         })
     }
 
@@ -505,16 +529,21 @@ where {
         aws_operations: &T,
     ) -> Result<(), UploadStreamWriteError> {
         if self.buffer_size > 0 {
-            let body = ByteStream::from(self.buffer[0..self.buffer_size as usize].to_vec());
-            let upload_part_response = aws_operations
-                .upload_part_operation(
-                    self.bucket_name.clone(),
-                    self.file_name.clone(),
-                    self.part_number.clone(),
-                    self.upload_id.clone(),
-                    body,
-                    self.client.clone(),
-                )
+            let (upload_part_response, retries) = self
+                .retry_policy
+                .retry(|| {
+                    //The body is consumed by each attempt so it is rebuilt from the buffer.
+                    let body =
+                        ByteStream::from(self.buffer[0..self.buffer_size as usize].to_vec());
+                    aws_operations.upload_part_operation(
+                        self.bucket_name.clone(),
+                        self.file_name.clone(),
+                        self.part_number,
+                        self.upload_id.clone(),
+                        body,
+                        self.client.clone(),
+                    )
+                })
                 .await;
             if upload_part_response.is_err() {
                 let err = upload_part_response.err().unwrap().into_service_error();
@@ -522,9 +551,10 @@ where {
                 return Err(UploadStreamWriteError::UnexpectedError(
                     GlyphxErrorData::new(
                         err.to_string(),
-                        Some(
+                        Some(record_retries(
                             json!({"Bucket" : self.get_bucket_name(), "FileName" : self.get_file_name()}),
-                        ),
+                            retries,
+                        )),
                         None,
                     ),
                 ));
@@ -569,6 +599,33 @@ where {
     }
 }
 
+#[async_trait]
+impl ObjectUploadStream for UploadStream {
+    fn get_file_name(&self) -> &str {
+        UploadStream::get_file_name(self)
+    }
+
+    fn get_file_size(&self) -> i64 {
+        UploadStream::get_file_size(self)
+    }
+
+    fn get_state(&self) -> &UploadStreamState {
+        UploadStream::get_state(self)
+    }
+
+    async fn write(&mut self, bytes: Option<Vec<u8>>) -> Result<(), UploadStreamWriteError> {
+        UploadStream::write(self, bytes).await
+    }
+
+    async fn finish(&mut self) -> Result<(), UploadStreamFinishError> {
+        UploadStream::finish(self).await
+    }
+
+    async fn abort(&mut self) {
+        UploadStream::abort(self).await
+    }
+}
+
 #[cfg(test)]
 mod constructor {
     use super::*;
@@ -652,6 +709,7 @@ mod constructor {
 mod flush {
     use super::*;
     use aws_smithy_http::body::SdkBody;
+    use std::time::Duration;
     use aws_smithy_types::error::metadata::ErrorMetadata;
     use http;
 
@@ -806,6 +864,60 @@ mod flush {
         assert_eq!(upload_manager.part_number, 1);
         assert!(!upload_manager.buffer.is_empty());
     }
+
+    #[tokio::test]
+    async fn is_ok_after_being_throttled() {
+        let config = ::aws_config::from_env().region("us-east-2").load().await;
+        let client = Client::new(&config);
+        let file_name = "test.txt";
+        let bucket_name = "glyphx-test";
+        let upload_id = "test_upload_id";
+        let mut mock_ops = MockUploadStreamOps::new();
+        mock_ops
+            .expect_start_multipart_upload_operation()
+            .returning(|_, _, _| {
+                Ok(CreateMultipartUploadOutput::builder()
+                    .bucket(bucket_name.to_string())
+                    .key(file_name.to_string())
+                    .upload_id(upload_id.to_string())
+                    .build())
+            })
+            .times(1);
+
+        let mut sequence = Sequence::new();
+        mock_ops
+            .expect_upload_part_operation()
+            .returning(|_, _, _, _, _, _| {
+                let meta = ErrorMetadata::builder()
+                    .message("Please reduce your request rate.")
+                    .code("SlowDown")
+                    .build();
+                let err = UploadPartError::generic(meta);
+                let inner = http::Response::builder()
+                    .status(503)
+                    .header("Content-Type", "application/json")
+                    .body(SdkBody::empty())
+                    .unwrap();
+                Err(SdkError::service_error(err, inner))
+            })
+            .times(2)
+            .in_sequence(&mut sequence);
+        mock_ops
+            .expect_upload_part_operation()
+            .returning(|_, _, _, _, _, _| Ok(UploadPartOutput::builder().build()))
+            .times(1)
+            .in_sequence(&mut sequence);
+        let mut upload_manager = UploadStream::new_impl(bucket_name, file_name, client, &mock_ops)
+            .await
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_initial_delay(Duration::ZERO));
+        upload_manager.buffer_size = 1;
+        upload_manager.buffer = vec![1];
+        let res = upload_manager.flush_impl(&mock_ops).await;
+        assert!(res.is_ok());
+        assert_eq!(upload_manager.part_number, 2);
+        assert!(upload_manager.buffer.is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,6 @@
 pub mod athena_manager_errors;
+pub mod polling_policy;
 pub mod query_status;
+#[cfg(feature = "s3_manager")]
+pub mod query_results_source;
 pub mod table_description;
@@ -7,6 +7,8 @@ mod run_query_error;
 mod get_query_pager_error;
 mod get_table_description_error;
 mod athena_stream_iterator_error;
+mod stop_query_error;
+mod result_set_conversion_error;
 
 pub use constructor_error::ConstructorError;
 pub use start_query_error::StartQueryError;
@@ -16,5 +18,7 @@ pub use run_query_error::RunQueryError;
 pub use get_query_pager_error::GetQueryPagerError;
 pub use get_table_description_error::GetTableDescriptionError;
 pub use athena_stream_iterator_error::AthenaStreamIteratorError;
+pub use stop_query_error::StopQueryError;
+pub use result_set_conversion_error::ResultSetConversionError;
 
 
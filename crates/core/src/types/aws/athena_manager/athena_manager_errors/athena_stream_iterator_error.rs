@@ -7,6 +7,10 @@ use crate as glyphx_core;
 #[error_definition("AthenaStreamIterator")]
 pub enum AthenaStreamIteratorError {
     GetQueryResultsError(GlyphxErrorData),
+    ///A row could not be deserialized into the type that was asked for.
+    DeserializationError(GlyphxErrorData),
+    ///The results file in the output location of the query could not be read or parsed.
+    ReadError(GlyphxErrorData),
 }
 
 impl From<GlyphxGetQueryResultsError> for AthenaStreamIteratorError {
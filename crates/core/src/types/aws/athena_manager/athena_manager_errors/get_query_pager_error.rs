@@ -22,6 +22,8 @@ pub enum GetQueryPagerError {
     QueryCancelled(GlyphxErrorData),
     ///If you are attempting to get a pager on a query which is still running this error is returned.
     QueryNotFinished(GlyphxErrorData),
+    ///If the results can not be read from the output location of the query this error is returned.
+    OutputLocationError(GlyphxErrorData),
     ///If any other error occurs while trying to get a query pager, this error will be returned.
     UnexpectedError(GlyphxErrorData),
 }
@@ -1,5 +1,6 @@
 use aws_sdk_athena::operation::get_query_results::GetQueryResultsError as AwsGetQueryResultsError;
 use aws_sdk_s3::error::ProvideErrorMetadata;
+use super::ResultSetConversionError;
 use crate::types::error::GlyphxErrorData;
 use crate::GlyphxError;
 //This is a bit hackey, but I built our GlyphxError macro to import any types that it needs are
@@ -18,6 +19,9 @@ pub enum GetQueryResultsError {
     QueryDoesNotExist(GlyphxErrorData),
     ///If AWS throttled our query do to execution limits, this error will be returned.
     RequestWasThrottled(GlyphxErrorData),
+    ///If the results could not be converted to JSON, and we were asked to report conversion
+    ///failures, this error will be returned.
+    ConversionError(GlyphxErrorData),
     ///If any other error occurs while trying to get the query results, this error will be returned.
     UnexpectedError(GlyphxErrorData),
 }
@@ -50,4 +54,22 @@ impl GetQueryResultsError {
                 }
             }
     }
+
+    pub fn from_result_set_conversion_error(error: ResultSetConversionError, catalog: &str, database: &str, query_id: &str) -> Self {
+        let message = error.get_glyphx_error_data().message.clone();
+        let data = json!({"catalog": catalog, "database": database, "query_id": query_id});
+        let inner_error = serde_json::to_value(error).unwrap();
+        Self::ConversionError(GlyphxErrorData::new(message, Some(data), Some(inner_error)))
+    }
+
+    ///Gives mutable access to the error data so that callers can add to it, i.e. the number of
+    ///retries that were made.
+    pub(crate) fn get_error_data_mut(&mut self) -> &mut GlyphxErrorData {
+        match self {
+            Self::QueryDoesNotExist(data) => data,
+            Self::RequestWasThrottled(data) => data,
+            Self::ConversionError(data) => data,
+            Self::UnexpectedError(data) => data,
+        }
+    }
 }
@@ -0,0 +1,20 @@
+use crate::types::error::GlyphxErrorData;
+use crate::GlyphxError;
+use serde::{Deserialize, Serialize};
+//This is a bit hackey, but I built our GlyphxError macro to import any types that it needs are
+//part of derived code, fully pathed to glyphx_core.  This allows errors defined in external
+//crates, i.e. common, to not have to worry about bringing structs and traits into scope.  This
+//however, breaks errors defined in the core crate.  To get past this, I am aliasing crate to
+//glyphx_core.
+use crate as glyphx_core;
+
+///Errors that are returned when converting a result set to JSON with conversion failures being
+///reported.
+#[derive(Debug, Clone, GlyphxError, Serialize, Deserialize)]
+#[error_definition("ResultSetConverter")]
+pub enum ResultSetConversionError {
+    ///A value could not be converted to the data type of its column.
+    UnableToConvertValue(GlyphxErrorData),
+    ///A column has a data type that we do not know how to convert.
+    UnsupportedDataType(GlyphxErrorData),
+}
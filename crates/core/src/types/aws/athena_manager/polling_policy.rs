@@ -0,0 +1,243 @@
+///! This module contains the policy that is used to poll Athena while a query is running.
+use crate::aws::retry_policy::{apply_jitter, get_backoff_delay};
+use std::time::{Duration, Instant};
+
+///Controls how often we ask Athena for the status of a running query and how long we are willing
+///to wait for it.  The delay between checks starts at initial_delay and is multiplied by the
+///backoff_multiplier after each check, up to max_delay.  Each delay is moved by a random amount of
+///up to jitter (a fraction of the delay) so that processes which started together spread out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollingPolicy {
+    initial_delay: Duration,
+    backoff_multiplier: f64,
+    max_delay: Duration,
+    jitter: f64,
+    max_wait: Duration,
+}
+
+impl Default for PollingPolicy {
+    ///Our defaults match the 60 second time out that run_query has always used.
+    fn default() -> Self {
+        PollingPolicy {
+            initial_delay: Duration::from_millis(250),
+            backoff_multiplier: 2.0,
+            max_delay: Duration::from_secs(5),
+            jitter: 0.1,
+            max_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+impl PollingPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///The delay before the second status check.
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    ///The factor that each delay is multiplied by.  Values below 1 are treated as 1, i.e. a
+    ///fixed delay.
+    pub fn with_backoff_multiplier(mut self, backoff_multiplier: f64) -> Self {
+        self.backoff_multiplier = backoff_multiplier.max(1.0);
+        self
+    }
+
+    ///The longest that we will wait between two status checks.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    ///The fraction, between 0 and 1, of each delay that it may be moved by.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    ///How long we will wait for the query before giving up on it.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    pub fn get_initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    pub fn get_backoff_multiplier(&self) -> f64 {
+        self.backoff_multiplier
+    }
+
+    pub fn get_max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    pub fn get_jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    pub fn get_max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    ///Returns the delay, before jitter, that follows the given status check.  The first check is
+    ///attempt 0.
+    pub fn get_delay(&self, attempt: u32) -> Duration {
+        get_backoff_delay(
+            self.initial_delay,
+            self.backoff_multiplier,
+            self.max_delay,
+            attempt,
+        )
+    }
+
+    fn apply_jitter(&self, delay: Duration) -> Duration {
+        apply_jitter(delay, self.jitter)
+    }
+
+    ///Starts the clock for a query that has just been started.
+    pub fn start(&self) -> QueryPoller {
+        QueryPoller {
+            policy: self.clone(),
+            start_time: Instant::now(),
+            attempt: 0,
+        }
+    }
+}
+
+///Tracks the status checks made for a single query against its PollingPolicy.
+#[derive(Debug, Clone)]
+pub struct QueryPoller {
+    policy: PollingPolicy,
+    start_time: Instant,
+    attempt: u32,
+}
+
+impl QueryPoller {
+    pub fn get_policy(&self) -> &PollingPolicy {
+        &self.policy
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
+    ///Returns how long to wait before the next status check, or None once the max wait has been
+    ///reached.  The delay never runs past the max wait so that the final check happens at the
+    ///deadline.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        let elapsed = self.start_time.elapsed();
+        if elapsed >= self.policy.max_wait {
+            return None;
+        }
+        let delay = self
+            .policy
+            .apply_jitter(self.policy.get_delay(self.attempt));
+        self.attempt = self.attempt.saturating_add(1);
+        Some(delay.min(self.policy.max_wait - elapsed))
+    }
+
+    ///Sleeps until the next status check.  Returns false, without sleeping, once the max wait has
+    ///been reached.
+    pub async fn wait(&mut self) -> bool {
+        match self.next_delay() {
+            Some(delay) => {
+                tokio::time::sleep(delay).await;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_delay {
+    use super::*;
+
+    #[test]
+    fn backs_off() {
+        let policy = PollingPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_backoff_multiplier(2.0)
+            .with_max_delay(Duration::from_secs(1));
+        assert_eq!(policy.get_delay(0), Duration::from_millis(100));
+        assert_eq!(policy.get_delay(1), Duration::from_millis(200));
+        assert_eq!(policy.get_delay(2), Duration::from_millis(400));
+        assert_eq!(policy.get_delay(3), Duration::from_millis(800));
+        assert_eq!(policy.get_delay(4), Duration::from_secs(1));
+        assert_eq!(policy.get_delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn multiplier_below_one() {
+        let policy = PollingPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_backoff_multiplier(0.5);
+        assert_eq!(policy.get_backoff_multiplier(), 1.0);
+        assert_eq!(policy.get_delay(5), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let policy = PollingPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.apply_jitter(policy.get_delay(0));
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(150));
+        }
+        assert_eq!(PollingPolicy::new().with_jitter(2.0).get_jitter(), 1.0);
+    }
+}
+
+#[cfg(test)]
+mod query_poller {
+    use super::*;
+
+    #[test]
+    fn next_delay() {
+        let mut poller = PollingPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_jitter(0.0)
+            .start();
+        assert_eq!(poller.next_delay(), Some(Duration::from_millis(100)));
+        assert_eq!(poller.next_delay(), Some(Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn does_not_pass_the_max_wait() {
+        let mut poller = PollingPolicy::new()
+            .with_initial_delay(Duration::from_secs(10))
+            .with_max_delay(Duration::from_secs(10))
+            .with_max_wait(Duration::from_secs(1))
+            .start();
+        let delay = poller.next_delay().unwrap();
+        assert!(delay <= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn times_out() {
+        let mut poller = PollingPolicy::new()
+            .with_initial_delay(Duration::from_millis(10))
+            .with_max_wait(Duration::from_millis(50))
+            .start();
+        let mut checks = 0;
+        while poller.wait().await {
+            checks += 1;
+        }
+        assert!(checks > 0);
+        assert!(poller.get_elapsed() >= Duration::from_millis(50));
+        assert!(poller.next_delay().is_none());
+    }
+
+    #[test]
+    fn no_max_wait() {
+        let mut poller = PollingPolicy::new().with_max_wait(Duration::ZERO).start();
+        assert!(poller.next_delay().is_none());
+    }
+}
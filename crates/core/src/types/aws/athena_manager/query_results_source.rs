@@ -0,0 +1,11 @@
+use crate::aws::object_store::ObjectStore;
+
+///Where the results of a finished Athena query are read from, which can be chosen for each query.
+#[derive(Debug, Clone, Copy)]
+pub enum QueryResultsSource<'a> {
+    ///Page through the results with get_query_results, at most 1000 rows per call.
+    GetQueryResults,
+    ///Stream the CSV file that Athena wrote to the output location of the query, which is much
+    ///faster for large results.  The ObjectStore must be for the bucket of the output location.
+    OutputLocation(&'a dyn ObjectStore),
+}
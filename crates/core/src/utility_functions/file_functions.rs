@@ -13,3 +13,8 @@ pub fn get_stats_file_name(workspace_id: &str, project_id: &str, model_hash: &st
         format!("{}/{}/output/{}.sts", workspace_id, project_id, model_hash)
 }
 
+
+pub fn get_supporting_file_name(workspace_id: &str, project_id: &str, model_hash: &str) -> String {
+
+    format!("{}/{}/output/{}.sup", workspace_id, project_id, model_hash)
+}
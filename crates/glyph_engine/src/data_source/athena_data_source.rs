@@ -0,0 +1,793 @@
+use super::{
+    BinEdgesQuery, DataSource, DataSourceError, GlyphQuery, RowStream, TopValuesQuery, VectorQuery,
+};
+use crate::types::vectorizer_parameters::{AccumulatorType, SortDirection, SortOrder};
+use crate::types::SupportingFieldAggregation;
+use glyphx_common::AthenaConnection;
+use glyphx_core::{
+    aws::{
+        athena_manager::{AthenaManager, AthenaQueryStatus, ColumnDescription, ConversionOptions},
+        athena_stream_iterator::AthenaStreamIterator,
+    },
+    GlyphxErrorData,
+};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+///Runs our queries against the Athena database that the AthenaConnection is configured for.
+pub struct AthenaDataSource {
+    athena_connection: &'static AthenaConnection,
+    athena_manager: AthenaManager,
+}
+
+impl AthenaDataSource {
+    ///Our queries are run with a copy of the connection's AthenaManager that converts decimals to
+    ///JSON numbers rather than exact strings, since the engine works with every value as an f64.
+    pub fn new(athena_connection: &'static AthenaConnection) -> Self {
+        let athena_manager = athena_connection.get_athena_manager();
+        let conversion_options = athena_manager
+            .get_conversion_options()
+            .clone()
+            .with_lossy_decimals(true);
+        Self {
+            athena_connection,
+            athena_manager: athena_manager
+                .clone()
+                .with_conversion_options(conversion_options),
+        }
+    }
+
+    pub fn build_vector_query(query: &VectorQuery) -> String {
+        let (field_name, field_value, raw_query) = query.field_definition.get_query_parts();
+        let mut columns = vec![field_value];
+        let mut conditions: Vec<String> = Vec::new();
+        if let Some(filter) = &query.filter {
+            conditions.push(format!("({})", filter.get_query()));
+        }
+        let mut order_by: Vec<String> = Vec::new();
+        //Bin labels do not sort in bin order, so binned fields are ordered by their bin number.
+        let mut value_order = format!(r#""{}""#, field_name);
+        if let Some(binned_field_definition) = query.field_definition.get_binned_field_definition()
+        {
+            columns.push(format!(
+                r#"{} as "glyphx_bin__""#,
+                binned_field_definition.get_bin_index_query()
+            ));
+            conditions.push(format!(
+                r#""{}" IS NOT NULL"#,
+                binned_field_definition.field_name
+            ));
+            value_order = r#""glyphx_bin__""#.to_string();
+        }
+        //The other bucket of a top N goes after the values that were kept.
+        let top_n = query
+            .field_definition
+            .get_standard_field_definition()
+            .and_then(|field_definition| field_definition.get_resolved_top_n());
+        if let Some(top_n) = top_n {
+            columns.push(format!(
+                r#"CASE WHEN {} = '{}' THEN 1 ELSE 0 END as "glyphx_other__""#,
+                raw_query,
+                top_n.other_label.replace('\'', "''")
+            ));
+            order_by.push(r#""glyphx_other__""#.to_string());
+        }
+        let filter = match conditions.is_empty() {
+            true => String::new(),
+            false => format!(" WHERE {}", conditions.join(" AND ")),
+        };
+        let mut is_grouped = false;
+        match query.field_definition.get_sort_order() {
+            SortOrder::Value { direction } => {
+                order_by.push(format!("{}{}", value_order, Self::get_direction(direction)));
+            }
+            SortOrder::Explicit { values } => {
+                columns.push(format!(
+                    r#"{} as "glyphx_sort__""#,
+                    Self::build_explicit_position_query(&raw_query, &values)
+                ));
+                order_by.push(r#""glyphx_sort__""#.to_string());
+                order_by.push(value_order);
+            }
+            SortOrder::Accumulator {
+                accumulator,
+                field_name,
+                direction,
+            } => {
+                columns.push(format!(
+                    r#"{} as "glyphx_sort__""#,
+                    Self::build_rank_query(accumulator, &field_name)
+                ));
+                order_by.push(format!(
+                    r#""glyphx_sort__"{} NULLS LAST"#,
+                    Self::get_direction(direction)
+                ));
+                order_by.push(value_order);
+                is_grouped = true;
+            }
+        }
+        if is_grouped {
+            //Every column but the accumulated sort column is grouped on.
+            let group_by = (1..columns.len())
+                .map(|column| column.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            return format!(
+                "SELECT {} FROM {}{} GROUP BY {} ORDER BY {}",
+                columns.join(", "),
+                query.table_name,
+                filter,
+                group_by,
+                order_by.join(", ")
+            );
+        }
+        format!(
+            "SELECT DISTINCT {} FROM {}{} ORDER BY {}",
+            columns.join(", "),
+            query.table_name,
+            filter,
+            order_by.join(", ")
+        )
+    }
+
+    fn get_direction(direction: SortDirection) -> &'static str {
+        match direction {
+            SortDirection::Ascending => "",
+            SortDirection::Descending => " DESC",
+        }
+    }
+
+    ///Accumulates rank_by_field over the rows of a group, or counts them when there is no
+    ///rank_by_field.
+    fn build_rank_query(accumulator: AccumulatorType, rank_by_field: &Option<String>) -> String {
+        let rank_by_field = match rank_by_field {
+            Some(rank_by_field) => format!(r#""{}""#, rank_by_field),
+            None => "*".to_string(),
+        };
+        let accumulator = match accumulator {
+            AccumulatorType::COUNT => "count",
+            AccumulatorType::AVG => "avg",
+            AccumulatorType::MIN => "min",
+            AccumulatorType::MAX => "max",
+            _ => "sum",
+        };
+        format!("{}({})", accumulator, rank_by_field)
+    }
+
+    ///Returns the position of the value of raw_query in values, or the number of values when it
+    ///is not listed.  Strings are compared to the text of the value.
+    fn build_explicit_position_query(raw_query: &str, values: &[Value]) -> String {
+        if values.is_empty() {
+            return "0".to_string();
+        }
+        let cases = values
+            .iter()
+            .enumerate()
+            .map(|(position, value)| match value {
+                Value::String(value) => format!(
+                    "WHEN CAST({} AS VARCHAR) = '{}' THEN {}",
+                    raw_query,
+                    value.replace('\'', "''"),
+                    position
+                ),
+                _ => format!("WHEN {} = {} THEN {}", raw_query, value, position),
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        format!("CASE {} ELSE {} END", cases, values.len())
+    }
+
+    ///Selects the values of the column, as "value", that rank highest by the field's top N.
+    pub fn build_top_values_query(query: &TopValuesQuery) -> String {
+        let field_name = &query.field_definition.field_name;
+        let top_n = query.field_definition.top_n.as_ref().unwrap();
+        format!(
+            r#"SELECT CAST("{}" AS VARCHAR) as "value" FROM {} WHERE "{}" IS NOT NULL GROUP BY "{}" ORDER BY {} DESC NULLS LAST, "{}" LIMIT {}"#,
+            field_name,
+            query.table_name,
+            field_name,
+            field_name,
+            Self::build_rank_query(top_n.accumulator, &top_n.rank_by_field),
+            field_name,
+            top_n.count
+        )
+    }
+
+    ///Selects the value of the binned column at each of the field's quantiles as edge0, edge1...
+    pub fn build_bin_edges_query(query: &BinEdgesQuery) -> String {
+        let field_name = &query.field_definition.field_name;
+        let columns = query
+            .field_definition
+            .get_quantiles()
+            .iter()
+            .enumerate()
+            .map(|(index, quantile)| {
+                let value = if *quantile <= 0.0 {
+                    format!(r#"min("{}")"#, field_name)
+                } else if *quantile >= 1.0 {
+                    format!(r#"max("{}")"#, field_name)
+                } else {
+                    format!(r#"approx_percentile("{}", {})"#, field_name, quantile)
+                };
+                format!(r#"CAST({} AS DOUBLE) as "edge{}""#, value, index)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!("SELECT {} FROM {}", columns, query.table_name)
+    }
+
+    ///Returns the SQL that aggregates a supporting field's column in the temp table over a group.
+    fn build_supporting_aggregate(aggregation: SupportingFieldAggregation, column: &str) -> String {
+        match aggregation {
+            SupportingFieldAggregation::First => format!("min_by({}, rowid)", column),
+            SupportingFieldAggregation::DistinctList => format!(
+                "json_format(CAST(array_sort(array_distinct(filter(array_agg({}), v -> v IS NOT NULL))) AS JSON))",
+                column
+            ),
+            SupportingFieldAggregation::Sum => format!("sum({})", column),
+            SupportingFieldAggregation::Avg => format!("avg({})", column),
+        }
+    }
+
+    pub fn build_glyph_query(&self, query: &GlyphQuery) -> String {
+        let (x_field_name, _, x_raw_query) = query.x_field_definition.get_query_parts();
+        let (y_field_name, _, y_raw_query) = query.y_field_definition.get_query_parts();
+        let (z_field_name, _, z_raw_query) = query.z_field_definition.get_query_parts();
+        //The z axis and the glyph channels are accumulated over the temp table, so it needs every
+        //column that their definitions read from, not just a column matching their display names.
+        let mut accumulated_columns: Vec<String> = Vec::new();
+        let accumulated_definitions = std::iter::once(&query.z_field_definition).chain(
+            query
+                .channel_field_definitions
+                .iter()
+                .map(|(_, field_definition)| field_definition),
+        );
+        for field_definition in accumulated_definitions {
+            for column in field_definition.get_column_names() {
+                if !accumulated_columns.contains(&column) {
+                    accumulated_columns.push(column);
+                }
+            }
+        }
+        let temp_columns = accumulated_columns
+            .iter()
+            .map(|column| format!(r#""{}""#, column))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let channel_columns = query
+            .channel_field_definitions
+            .iter()
+            .map(|(channel, field_definition)| {
+                let (field_name, _, raw_query) = field_definition.get_query_parts();
+                format!(
+                    r#",
+    {} as "{}_{}""#,
+                    raw_query,
+                    channel.get_axis_name(),
+                    field_name
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("");
+        //Supporting fields are evaluated per row in the temp table and then aggregated per group.
+        let mut supporting_temp_columns = String::new();
+        let mut supporting_columns = String::new();
+        for (index, supporting_field) in query.supporting_fields.iter().enumerate() {
+            let (_, _, raw_query) = supporting_field.field_definition.get_query_parts();
+            let temp_column = format!("supportingColumn{}", index);
+            supporting_temp_columns
+                .push_str(&format!(",\n        {} as {}", raw_query, temp_column));
+            supporting_columns.push_str(&format!(
+                r#",
+    {} as "{}""#,
+                Self::build_supporting_aggregate(supporting_field.aggregation, &temp_column),
+                supporting_field.get_column_name()
+            ));
+        }
+        let database_name = self.athena_connection.get_database_name();
+        let filter = match &query.filter {
+            Some(filter) => format!("WHERE {}", filter.get_query()),
+            None => "".to_string(),
+        };
+        format!(
+            r#"
+    WITH temp as (
+        SELECT glyphx_id__ as rowid,
+        {} as groupedXColumn,
+        {} as groupedYColumn,
+        {}{}
+        FROM "{}"."{}"
+        {}
+    )
+    SELECT array_join(array_agg(rowid), '|') as "rowids",
+    groupedXColumn as "x_{}",
+    groupedYColumn as "y_{}",
+    {} as "z_{}"{}{}
+    FROM temp
+    GROUP BY groupedXColumn, groupedYColumn;
+"#,
+            x_raw_query,
+            y_raw_query,
+            temp_columns,
+            supporting_temp_columns,
+            database_name,
+            query.table_name,
+            filter,
+            x_field_name,
+            y_field_name,
+            z_raw_query,
+            z_field_name,
+            channel_columns,
+            supporting_columns
+        )
+    }
+}
+
+#[async_trait]
+impl DataSource for AthenaDataSource {
+    async fn get_table_columns(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDescription>, DataSourceError> {
+        let result = self
+            .athena_manager
+            .get_table_description(table_name)
+            .await;
+        if result.is_err() {
+            return Err(DataSourceError::from_get_table_description_error(
+                result.err().unwrap(),
+                table_name,
+            ));
+        }
+        Ok(result.unwrap())
+    }
+
+    async fn get_vector_values(&self, query: &VectorQuery) -> Result<Value, DataSourceError> {
+        let query = Self::build_vector_query(query);
+        let result = self
+            .athena_manager
+            .run_query(&query, Some(300), Some(true))
+            .await;
+        if result.is_err() {
+            return Err(DataSourceError::from_run_query_error(
+                result.err().unwrap(),
+                &query,
+            ));
+        }
+        Ok(result.unwrap())
+    }
+
+    async fn get_bin_edges(&self, query: &BinEdgesQuery) -> Result<Vec<f64>, DataSourceError> {
+        let quantile_count = query.field_definition.get_quantiles().len();
+        if quantile_count == 0 {
+            return Ok(query.field_definition.edges.clone());
+        }
+        let sql = Self::build_bin_edges_query(query);
+        let result = self
+            .athena_manager
+            .run_query(&sql, Some(300), Some(true))
+            .await;
+        if result.is_err() {
+            return Err(DataSourceError::from_run_query_error(
+                result.err().unwrap(),
+                &sql,
+            ));
+        }
+        let result = result.unwrap();
+        let row = &result[0];
+        let mut values: Vec<f64> = Vec::with_capacity(quantile_count);
+        for index in 0..quantile_count {
+            let value = &row[format!("edge{}", index)];
+            let number = match value {
+                Value::String(value) => value.parse::<f64>().ok(),
+                _ => value.as_f64(),
+            };
+            if number.is_none() {
+                let message = format!(
+                    "The column {} has no values to bin",
+                    query.field_definition.field_name
+                );
+                let data = json!({ "field_name": query.field_definition.field_name, "query": sql });
+                return Err(DataSourceError::DataError(GlyphxErrorData::new(
+                    message,
+                    Some(data),
+                    None,
+                )));
+            }
+            values.push(number.unwrap());
+        }
+        Ok(query.field_definition.get_edges_from_quantiles(&values))
+    }
+
+    async fn get_top_values(&self, query: &TopValuesQuery) -> Result<Vec<String>, DataSourceError> {
+        if query.field_definition.top_n.is_none() {
+            return Ok(Vec::new());
+        }
+        let sql = Self::build_top_values_query(query);
+        let result = self
+            .athena_manager
+            .run_query(&sql, Some(300), Some(true))
+            .await;
+        if result.is_err() {
+            return Err(DataSourceError::from_run_query_error(
+                result.err().unwrap(),
+                &sql,
+            ));
+        }
+        let result = result.unwrap();
+        let values = result
+            .as_array()
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| match &row["value"] {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+        Ok(values)
+    }
+
+    async fn start_glyph_query(&self, query: &GlyphQuery) -> Result<String, DataSourceError> {
+        let query = self.build_glyph_query(query);
+        let query_id = self
+            .athena_manager
+            .start_query(&query, None)
+            .await;
+        if query_id.is_err() {
+            return Err(DataSourceError::from_start_query_error(
+                query_id.err().unwrap(),
+                &query,
+            ));
+        }
+        Ok(query_id.unwrap())
+    }
+
+    async fn get_glyph_query_status(
+        &self,
+        query_id: &str,
+    ) -> Result<AthenaQueryStatus, DataSourceError> {
+        let query_status = self
+            .athena_manager
+            .get_query_status(query_id)
+            .await;
+        if query_status.is_err() {
+            return Err(DataSourceError::from_get_query_status_error(
+                query_status.err().unwrap(),
+                query_id,
+            ));
+        }
+        Ok(query_status.unwrap())
+    }
+
+    async fn stop_glyph_query(&self, query_id: &str) -> Result<(), DataSourceError> {
+        let result = self
+            .athena_manager
+            .stop_query(query_id)
+            .await;
+        if result.is_err() {
+            return Err(DataSourceError::from_stop_query_error(
+                result.err().unwrap(),
+                query_id,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get_glyph_query_results(
+        &self,
+        query_id: &str,
+    ) -> Result<Box<dyn RowStream>, DataSourceError> {
+        let results = self
+            .athena_manager
+            .get_paged_query_results(query_id, Some(1000))
+            .await;
+        if results.is_err() {
+            return Err(DataSourceError::from_get_query_pager_error(
+                results.err().unwrap(),
+                query_id,
+            ));
+        }
+        let results = Box::new(results.unwrap());
+
+        let iterator = AthenaStreamIterator::new(
+            results,
+            query_id,
+            self.athena_connection.get_data_catalog_name(),
+            self.athena_connection.get_database_name(),
+        )
+        //A value that we can not read would silently become a default and misplace its glyph.
+        .with_conversion_options(
+            ConversionOptions::new()
+                .with_report_conversion_failures(true)
+                .with_lossy_decimals(true),
+        );
+        Ok(Box::new(iterator))
+    }
+}
+
+#[cfg(test)]
+mod build_vector_query {
+    use super::*;
+    use crate::types::vectorizer_parameters::{FieldDefinition, Filter, FilterExpression};
+    use serde_json::json;
+
+    #[test]
+    fn is_ok() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "field1",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "field1"
+            }
+        }))
+        .unwrap();
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition,
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            r#"SELECT DISTINCT "field1" as "field1" FROM my_table ORDER BY "field1""#
+        );
+    }
+
+    fn get_sorted_field_definition(sort_order: Value) -> FieldDefinition {
+        FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product"
+            },
+            "sortOrder": sort_order
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn descending() {
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition: get_sorted_field_definition(json!({"direction": "desc"})),
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            r#"SELECT DISTINCT "product" as "product" FROM my_table ORDER BY "product" DESC"#
+        );
+    }
+
+    #[test]
+    fn by_accumulator() {
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition: get_sorted_field_definition(json!({
+                "sortBy": "accumulator",
+                "accumulator": "sum",
+                "fieldName": "sales",
+                "direction": "desc"
+            })),
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            r#"SELECT "product" as "product", sum("sales") as "glyphx_sort__" FROM my_table GROUP BY 1 ORDER BY "glyphx_sort__" DESC NULLS LAST, "product""#
+        );
+    }
+
+    #[test]
+    fn explicit() {
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition: get_sorted_field_definition(json!({
+                "sortBy": "explicit",
+                "values": ["pear", "kiwi's", 3]
+            })),
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            r#"SELECT DISTINCT "product" as "product", CASE WHEN CAST("product" AS VARCHAR) = 'pear' THEN 0 WHEN CAST("product" AS VARCHAR) = 'kiwi''s' THEN 1 WHEN "product" = 3 THEN 2 ELSE 3 END as "glyphx_sort__" FROM my_table ORDER BY "glyphx_sort__", "product""#
+        );
+    }
+
+    #[test]
+    fn top_n() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "Product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": {"count": 1}
+            }
+        }))
+        .unwrap()
+        .with_top_values(vec!["apple".to_string()]);
+        let raw_query = field_definition.get_raw_query().to_string();
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition,
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            format!(
+                r#"SELECT DISTINCT {} as "Product", CASE WHEN {} = 'Other' THEN 1 ELSE 0 END as "glyphx_other__" FROM my_table ORDER BY "glyphx_other__", "Product""#,
+                raw_query, raw_query
+            )
+        );
+    }
+
+    #[test]
+    fn binned() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "Price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "explicit",
+                "edges": [0, 10]
+            }
+        }))
+        .unwrap();
+        let (field_query, _) = field_definition
+            .get_binned_field_definition()
+            .unwrap()
+            .get_query("Price");
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition,
+            filter: None,
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert_eq!(
+            result,
+            format!(
+                r#"SELECT DISTINCT {}, CASE WHEN "price" IS NULL THEN NULL WHEN "price" < 0 THEN 0 WHEN "price" <= 10 THEN 1 ELSE 2 END as "glyphx_bin__" FROM my_table WHERE "price" IS NOT NULL ORDER BY "glyphx_bin__""#,
+                field_query
+            )
+        );
+    }
+
+    #[test]
+    fn filtered() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "Price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "explicit",
+                "edges": [0, 10]
+            }
+        }))
+        .unwrap();
+        let filter = FilterExpression::from_json(&json!({"or": [
+            {"fieldName": "region", "operator": "=", "value": "east"},
+            {"fieldName": "units", "operator": ">", "value": 2}
+        ]}))
+        .unwrap();
+        let query = VectorQuery {
+            table_name: "my_table".to_string(),
+            field_definition,
+            filter: Some(Filter::Expression(filter)),
+        };
+        let result = AthenaDataSource::build_vector_query(&query);
+        assert!(result.contains(
+            r#" FROM my_table WHERE (("region" = 'east') OR ("units" > 2)) AND "price" IS NOT NULL ORDER BY"#
+        ));
+    }
+}
+
+#[cfg(test)]
+mod build_top_values_query {
+    use super::*;
+    use crate::types::vectorizer_parameters::StandardFieldDefinition;
+
+    fn get_query(top_n: Value) -> TopValuesQuery {
+        TopValuesQuery {
+            table_name: "my_table".to_string(),
+            field_definition: StandardFieldDefinition::from_json(&json!({
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": top_n
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn by_count() {
+        let result = AthenaDataSource::build_top_values_query(&get_query(json!({"count": 10})));
+        assert_eq!(
+            result,
+            r#"SELECT CAST("product" AS VARCHAR) as "value" FROM my_table WHERE "product" IS NOT NULL GROUP BY "product" ORDER BY count(*) DESC NULLS LAST, "product" LIMIT 10"#
+        );
+    }
+
+    #[test]
+    fn by_accumulator() {
+        let result = AthenaDataSource::build_top_values_query(&get_query(
+            json!({"count": 5, "accumulator": "avg", "rankByField": "revenue"}),
+        ));
+        assert_eq!(
+            result,
+            r#"SELECT CAST("product" AS VARCHAR) as "value" FROM my_table WHERE "product" IS NOT NULL GROUP BY "product" ORDER BY avg("revenue") DESC NULLS LAST, "product" LIMIT 5"#
+        );
+    }
+}
+
+#[cfg(test)]
+mod build_bin_edges_query {
+    use super::*;
+    use crate::types::vectorizer_parameters::BinnedFieldDefinition;
+
+    fn get_query(binning_method: &str) -> BinEdgesQuery {
+        BinEdgesQuery {
+            table_name: "my_table".to_string(),
+            field_definition: BinnedFieldDefinition::from_json(&json!({
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": binning_method,
+                "binCount": 4
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn equal_width() {
+        let result = AthenaDataSource::build_bin_edges_query(&get_query("equal_width"));
+        assert_eq!(
+            result,
+            r#"SELECT CAST(min("price") AS DOUBLE) as "edge0", CAST(max("price") AS DOUBLE) as "edge1" FROM my_table"#
+        );
+    }
+
+    #[test]
+    fn quantile() {
+        let result = AthenaDataSource::build_bin_edges_query(&get_query("quantile"));
+        assert_eq!(
+            result,
+            r#"SELECT CAST(min("price") AS DOUBLE) as "edge0", CAST(approx_percentile("price", 0.25) AS DOUBLE) as "edge1", CAST(approx_percentile("price", 0.5) AS DOUBLE) as "edge2", CAST(approx_percentile("price", 0.75) AS DOUBLE) as "edge3", CAST(max("price") AS DOUBLE) as "edge4" FROM my_table"#
+        );
+    }
+}
+
+#[cfg(test)]
+mod build_supporting_aggregate {
+    use super::*;
+
+    #[test]
+    fn first() {
+        let result = AthenaDataSource::build_supporting_aggregate(
+            SupportingFieldAggregation::First,
+            "supportingColumn0",
+        );
+        assert_eq!(result, "min_by(supportingColumn0, rowid)");
+    }
+
+    #[test]
+    fn distinct_list() {
+        let result = AthenaDataSource::build_supporting_aggregate(
+            SupportingFieldAggregation::DistinctList,
+            "supportingColumn1",
+        );
+        assert_eq!(
+            result,
+            "json_format(CAST(array_sort(array_distinct(filter(array_agg(supportingColumn1), v -> v IS NOT NULL))) AS JSON))"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use glyphx_core::{
+    aws::athena_manager::{
+        AthenaStreamIteratorError, GetQueryPagerError, GetQueryStatusError,
+        GetTableDescriptionError, RunQueryError, StartQueryError, StopQueryError,
+    },
+    GlyphxError, GlyphxErrorData,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, to_value};
+
+#[derive(Debug, Clone, GlyphxError, PartialEq, Serialize, Deserialize)]
+#[error_definition("DataSource")]
+pub enum DataSourceError {
+    AthenaQueryError(GlyphxErrorData),
+    TableNotFound(GlyphxErrorData),
+    FileReadError(GlyphxErrorData),
+    UnsupportedQuery(GlyphxErrorData),
+    QueryNotFound(GlyphxErrorData),
+    DataError(GlyphxErrorData),
+}
+
+impl DataSourceError {
+    pub fn from_run_query_error(error: RunQueryError, query: &str) -> Self {
+        let message = "An error occurred while running the query, see the inner error for additional information".to_string();
+        let data = json!({ "query": query });
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::AthenaQueryError(error_data)
+    }
+
+    pub fn from_start_query_error(error: StartQueryError, query: &str) -> Self {
+        let message = "An error occurred while starting the query, see the inner error for additional information".to_string();
+        let data = json!({ "query": query });
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::AthenaQueryError(error_data)
+    }
+
+    pub fn from_get_query_status_error(error: GetQueryStatusError, query_id: &str) -> Self {
+        let message = "An error occurred while checking the query status, see the inner error for additional information".to_string();
+        let data = json!({ "query_id": query_id });
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::AthenaQueryError(error_data)
+    }
+
+    pub fn from_stop_query_error(error: StopQueryError, query_id: &str) -> Self {
+        let message = "An error occurred while stopping the query, see the inner error for additional information".to_string();
+        let data = json!({ "query_id": query_id });
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::AthenaQueryError(error_data)
+    }
+
+    pub fn from_get_query_pager_error(error: GetQueryPagerError, query_id: &str) -> Self {
+        let message = "An error occurred while getting the query results, see the inner error for additional information".to_string();
+        let data = json!({ "query_id": query_id });
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::AthenaQueryError(error_data)
+    }
+
+    pub fn from_athena_stream_iterator_error(error: AthenaStreamIteratorError) -> Self {
+        let message = "An error occurred while iterating the query results, see the inner error for additional information".to_string();
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, None, Some(inner_error));
+        Self::DataError(error_data)
+    }
+
+    pub fn from_get_table_description_error(
+        error: GetTableDescriptionError,
+        table_name: &str,
+    ) -> Self {
+        let message = "An error occurred while describing the table, see the inner error for additional information".to_string();
+        let data = json!({ "table_name": table_name });
+        let inner_error = to_value(error.get_glyphx_error_data()).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        match error {
+            GetTableDescriptionError::TableDoesNotExist(_) => Self::TableNotFound(error_data),
+            _ => Self::AthenaQueryError(error_data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_start_query_error {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let inner = StartQueryError::RequestWasThrottled(GlyphxErrorData::new(
+            "throttled".to_string(),
+            None,
+            None,
+        ));
+        let result = DataSourceError::from_start_query_error(inner, "SELECT 1");
+        match result {
+            DataSourceError::AthenaQueryError(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["query"].as_str().unwrap(), "SELECT 1");
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected AthenaQueryError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_athena_stream_iterator_error {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let inner = AthenaStreamIteratorError::GetQueryResultsError(GlyphxErrorData::new(
+            "failed".to_string(),
+            None,
+            None,
+        ));
+        let result = DataSourceError::from_athena_stream_iterator_error(inner);
+        match result {
+            DataSourceError::DataError(error_data) => {
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected DataError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_get_table_description_error {
+    use super::*;
+
+    #[test]
+    fn table_not_found() {
+        let inner = GetTableDescriptionError::TableDoesNotExist(GlyphxErrorData::new(
+            "missing".to_string(),
+            None,
+            None,
+        ));
+        let result = DataSourceError::from_get_table_description_error(inner, "my_table");
+        match result {
+            DataSourceError::TableNotFound(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["table_name"].as_str().unwrap(), "my_table");
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected TableNotFound"),
+        }
+    }
+
+    #[test]
+    fn athena_query_error() {
+        let inner = GetTableDescriptionError::QueryFailed(GlyphxErrorData::new(
+            "failed".to_string(),
+            None,
+            None,
+        ));
+        let result = DataSourceError::from_get_table_description_error(inner, "my_table");
+        match result {
+            DataSourceError::AthenaQueryError(_) => {}
+            _ => panic!("Expected AthenaQueryError"),
+        }
+    }
+}
@@ -0,0 +1,1426 @@
+//! In-process equivalents of the SQL that our field definitions generate.  These are used by
+//! the LocalFileDataSource so that it produces the same values that Athena would for the same
+//! field definitions.
+use super::DataSourceError;
+use crate::types::vectorizer_parameters::{
+    parse_iso_date, parse_utc_offset, AccumulatedFieldDefinition, AccumulatorFieldDefinition,
+    AccumulatorType, BinnedFieldDefinition, ComparisonOperator, DateFieldDefinition, DateGrouping,
+    DateUnit, FieldDefinition, FilterExpression, FormulaExpression, FormulaFunction,
+    FormulaOperator, SortDirection, SortOrder, StandardFieldDefinition,
+};
+use crate::types::SupportingFieldAggregation;
+use glyphx_core::GlyphxErrorData;
+
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use time::{Duration, OffsetDateTime};
+
+pub type TableRow = Map<String, Value>;
+
+///Evaluates a non accumulated field definition against a single row.
+pub fn evaluate_field(
+    field_definition: &FieldDefinition,
+    row: &TableRow,
+) -> Result<Value, DataSourceError> {
+    match field_definition {
+        FieldDefinition::Standard {
+            field_definition, ..
+        } => {
+            let value = get_column_value(row, &field_definition.field_name);
+            match &field_definition.top_n {
+                Some(top_n) => Ok(top_n.apply(value)),
+                None => Ok(value),
+            }
+        }
+        FieldDefinition::Date {
+            field_definition, ..
+        } => evaluate_date(field_definition, row),
+        FieldDefinition::Formula {
+            field_definition, ..
+        } => evaluate_formula(&field_definition.expression, row),
+        FieldDefinition::Binned {
+            field_definition, ..
+        } => Ok(evaluate_bin_index(field_definition, row)?
+            .map(|index| json!(field_definition.get_bin_label(index)))
+            .unwrap_or(Value::Null)),
+        _ => {
+            let message = format!(
+                "The field {} cannot be evaluated one row at a time",
+                field_definition.get_field_display_name()
+            );
+            let data = json!({ "field_display_name": field_definition.get_field_display_name() });
+            Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )))
+        }
+    }
+}
+
+///Returns the number of the bin that a row falls into, or None when its value is null.
+pub fn evaluate_bin_index(
+    field_definition: &BinnedFieldDefinition,
+    row: &TableRow,
+) -> Result<Option<usize>, DataSourceError> {
+    let value = get_column_value(row, &field_definition.field_name);
+    if value.is_null() {
+        return Ok(None);
+    }
+    let value = value_as_f64(&value)?;
+    Ok(Some(field_definition.get_bin_index(value)))
+}
+
+///Resolves the edges of a binned field from the values of its column, using the same nearest
+///rank percentiles that the accumulators use.
+pub fn evaluate_bin_edges(
+    field_definition: &BinnedFieldDefinition,
+    rows: &[TableRow],
+) -> Result<Vec<f64>, DataSourceError> {
+    let values: Vec<Value> = rows
+        .iter()
+        .map(|row| get_column_value(row, &field_definition.field_name))
+        .filter(|value| !value.is_null())
+        .collect();
+    if values.is_empty() {
+        let message = format!(
+            "The column {} has no values to bin",
+            field_definition.field_name
+        );
+        let data = json!({ "field_name": field_definition.field_name });
+        return Err(DataSourceError::DataError(GlyphxErrorData::new(
+            message,
+            Some(data),
+            None,
+        )));
+    }
+    let mut quantile_values = Vec::new();
+    for quantile in field_definition.get_quantiles() {
+        let value = percentile_value(values.clone(), quantile)?;
+        quantile_values.push(value_as_f64(&value)?);
+    }
+    Ok(field_definition.get_edges_from_quantiles(&quantile_values))
+}
+
+///Returns the values of a standard field's column that rank highest by its top N, highest first.
+///Ties are broken by the value so that the result does not depend on the order of the rows.
+pub fn evaluate_top_values(
+    field_definition: &StandardFieldDefinition,
+    rows: &[TableRow],
+) -> Result<Vec<String>, DataSourceError> {
+    let top_n = match &field_definition.top_n {
+        Some(top_n) => top_n,
+        None => return Ok(Vec::new()),
+    };
+    //The value of each group and the values of its rank_by_field, or a placeholder for every
+    //row when we are just counting them.
+    let mut groups: Vec<(String, Vec<Value>)> = Vec::new();
+    for row in rows {
+        let key = match get_column_value(row, &field_definition.field_name) {
+            Value::Null => continue,
+            Value::String(string) => string,
+            value => value.to_string(),
+        };
+        let rank_value = match &top_n.rank_by_field {
+            Some(rank_by_field) => get_column_value(row, rank_by_field),
+            None => json!(1),
+        };
+        match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+            Some((_, values)) => values.push(rank_value),
+            None => groups.push((key, vec![rank_value])),
+        }
+    }
+    let mut ranked: Vec<(String, Option<f64>)> = Vec::with_capacity(groups.len());
+    for (key, values) in groups {
+        ranked.push((key, rank_values(top_n.accumulator, &values)?));
+    }
+    ranked.sort_by(|(left_key, left), (right_key, right)| {
+        compare_ranks(left, right, SortDirection::Descending).then_with(|| left_key.cmp(right_key))
+    });
+    Ok(ranked
+        .into_iter()
+        .take(top_n.count)
+        .map(|(key, _)| key)
+        .collect())
+}
+
+///Orders the distinct values of an axis, which are already in value order, by the field's sort
+///order.  Values that tie keep their value order.
+pub fn sort_vector_values(
+    field_definition: &FieldDefinition,
+    values: &mut [Value],
+    rows: &[TableRow],
+) -> Result<(), DataSourceError> {
+    let sort_order = field_definition.get_sort_order();
+    match &sort_order {
+        SortOrder::Value { direction } => {
+            if *direction == SortDirection::Descending {
+                values.reverse();
+            }
+        }
+        SortOrder::Explicit { .. } => {
+            values.sort_by_key(|value| sort_order.get_explicit_position(value));
+        }
+        SortOrder::Accumulator {
+            accumulator,
+            field_name,
+            direction,
+        } => {
+            //The values of field_name for the rows of each value, or a placeholder for every row
+            //when we are just counting them.
+            let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+            for row in rows {
+                let key = evaluate_field(field_definition, row)?;
+                let rank_value = match field_name {
+                    Some(field_name) => get_column_value(row, field_name),
+                    None => json!(1),
+                };
+                match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+                    Some((_, values)) => values.push(rank_value),
+                    None => groups.push((key, vec![rank_value])),
+                }
+            }
+            let mut ranks: Vec<Option<f64>> = Vec::with_capacity(values.len());
+            for value in values.iter() {
+                let rank = match groups.iter().find(|(group_key, _)| group_key == value) {
+                    Some((_, group_values)) => rank_values(*accumulator, group_values)?,
+                    None => None,
+                };
+                ranks.push(rank);
+            }
+            let mut ranked = values
+                .iter()
+                .cloned()
+                .zip(ranks)
+                .collect::<Vec<(Value, Option<f64>)>>();
+            ranked.sort_by(|(_, left), (_, right)| compare_ranks(left, right, *direction));
+            for (value, (ranked_value, _)) in values.iter_mut().zip(ranked) {
+                *value = ranked_value;
+            }
+        }
+    }
+    Ok(())
+}
+
+///Accumulates the non null values of a group for ranking it, like the count, sum, avg, min and
+///max aggregates do in SQL.
+fn rank_values(
+    accumulator: AccumulatorType,
+    values: &[Value],
+) -> Result<Option<f64>, DataSourceError> {
+    let mut numbers = Vec::with_capacity(values.len());
+    for value in values.iter().filter(|value| !value.is_null()) {
+        numbers.push(value_as_f64(value)?);
+    }
+    let rank = match accumulator {
+        AccumulatorType::COUNT => Some(numbers.len() as f64),
+        _ if numbers.is_empty() => None,
+        AccumulatorType::SUM => Some(numbers.iter().sum()),
+        AccumulatorType::AVG => Some(numbers.iter().sum::<f64>() / numbers.len() as f64),
+        AccumulatorType::MIN => numbers.iter().cloned().reduce(f64::min),
+        _ => numbers.iter().cloned().reduce(f64::max),
+    };
+    Ok(rank)
+}
+
+///Compares the ranks of two groups in the given direction, with groups that have nothing to rank
+///by last, like NULLS LAST.
+fn compare_ranks(left: &Option<f64>, right: &Option<f64>, direction: SortDirection) -> Ordering {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            let order = left.partial_cmp(right).unwrap_or(Ordering::Equal);
+            match direction {
+                SortDirection::Ascending => order,
+                SortDirection::Descending => order.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+///Evaluates the inner definition of an accumulated field against a single row.  The values
+///for each row in a group are then passed to accumulate.
+pub fn evaluate_accumulated_field(
+    field_definition: &AccumulatedFieldDefinition,
+    row: &TableRow,
+) -> Result<Value, DataSourceError> {
+    match field_definition {
+        AccumulatedFieldDefinition::Standard(field_definition) => {
+            Ok(get_column_value(row, &field_definition.field_name))
+        }
+        AccumulatedFieldDefinition::Date(field_definition) => evaluate_date(field_definition, row),
+        AccumulatedFieldDefinition::Formula(field_definition) => {
+            evaluate_formula(&field_definition.expression, row)
+        }
+        AccumulatedFieldDefinition::Unknown() => {
+            let message = "The accumulated field definition is unknown".to_string();
+            Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                message, None, None,
+            )))
+        }
+    }
+}
+
+///Evaluates the value that an accumulator takes from a single row.  FIRST and LAST also need
+///the value of the row's orderByField, so for them the value is paired with it as [order, value].
+pub fn evaluate_accumulator_input(
+    field_definition: &AccumulatorFieldDefinition,
+    row: &TableRow,
+) -> Result<Value, DataSourceError> {
+    let value = evaluate_accumulated_field(&field_definition.accumulated_field_definition, row)?;
+    match &field_definition.order_by_field {
+        Some(order_by_field) if field_definition.accumulator_type.requires_order_by_field() => {
+            Ok(json!([get_column_value(row, order_by_field), value]))
+        }
+        _ => Ok(value),
+    }
+}
+
+///Applies the accumulator to the values of a group, which come from evaluate_accumulator_input.
+///Like SQL, nulls are ignored and an empty group accumulates to null for everything except the
+///counts.
+pub fn accumulate(
+    field_definition: &AccumulatorFieldDefinition,
+    values: Vec<Value>,
+) -> Result<Value, DataSourceError> {
+    if field_definition.accumulator_type.requires_order_by_field() {
+        return Ok(accumulate_by_order(
+            field_definition.accumulator_type,
+            values,
+        ));
+    }
+    let values: Vec<Value> = values.into_iter().filter(|v| !v.is_null()).collect();
+    match field_definition.accumulator_type {
+        AccumulatorType::COUNT => Ok(json!(values.len() as u64)),
+        AccumulatorType::COUNT_DISTINCT => {
+            let mut distinct: Vec<&Value> = Vec::new();
+            for value in values.iter() {
+                if !distinct.contains(&value) {
+                    distinct.push(value);
+                }
+            }
+            Ok(json!(distinct.len() as u64))
+        }
+        AccumulatorType::MEDIAN => percentile_value(values, 0.5),
+        AccumulatorType::PERCENTILE => {
+            percentile_value(values, field_definition.percentile.unwrap_or(0.5))
+        }
+        AccumulatorType::STDDEV => Ok(sample_variance(&values)?
+            .map(|variance| json!(variance.sqrt()))
+            .unwrap_or(Value::Null)),
+        AccumulatorType::VARIANCE => Ok(sample_variance(&values)?
+            .map(|variance| json!(variance))
+            .unwrap_or(Value::Null)),
+        AccumulatorType::FIRST | AccumulatorType::LAST => unreachable!(),
+        AccumulatorType::MIN => Ok(values
+            .into_iter()
+            .min_by(compare_values)
+            .unwrap_or(Value::Null)),
+        AccumulatorType::MAX => Ok(values
+            .into_iter()
+            .max_by(compare_values)
+            .unwrap_or(Value::Null)),
+        AccumulatorType::SUM => sum_values(&values, false),
+        AccumulatorType::AVG => sum_values(&values, true),
+    }
+}
+
+///min_by and max_by: the value of the [order, value] pair with the lowest, or highest, order.
+///Rows without an order are ignored, but the value itself may be null.
+fn accumulate_by_order(accumulator_type: AccumulatorType, values: Vec<Value>) -> Value {
+    let pairs = values.into_iter().filter_map(|pair| match pair {
+        Value::Array(mut pair) if pair.len() == 2 && !pair[0].is_null() => {
+            let value = pair.pop().unwrap();
+            Some((pair.pop().unwrap(), value))
+        }
+        _ => None,
+    });
+    let pair = match accumulator_type {
+        AccumulatorType::LAST => pairs.max_by(|l, r| compare_values(&l.0, &r.0)),
+        _ => pairs.min_by(|l, r| compare_values(&l.0, &r.0)),
+    };
+    pair.map(|(_, value)| value).unwrap_or(Value::Null)
+}
+
+///Like approx_percentile, returns the value at the given percentile of the non null values,
+///using the nearest rank.
+fn percentile_value(values: Vec<Value>, percentile: f64) -> Result<Value, DataSourceError> {
+    if values.is_empty() {
+        return Ok(Value::Null);
+    }
+    for value in &values {
+        value_as_f64(value)?;
+    }
+    let mut values = values;
+    values.sort_by(compare_values);
+    let index = ((values.len() - 1) as f64 * percentile).round() as usize;
+    Ok(values.swap_remove(index))
+}
+
+///The sample variance, which is what Athena's variance and stddev use.  It needs at least two
+///values.
+fn sample_variance(values: &[Value]) -> Result<Option<f64>, DataSourceError> {
+    if values.len() < 2 {
+        return Ok(None);
+    }
+    let mut numbers = Vec::with_capacity(values.len());
+    for value in values {
+        numbers.push(value_as_f64(value)?);
+    }
+    let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+    let sum_of_squares: f64 = numbers.iter().map(|n| (n - mean) * (n - mean)).sum();
+    Ok(Some(sum_of_squares / (numbers.len() - 1) as f64))
+}
+
+///Sums, or averages, non null values.  Like SQL, the sum of integers is an integer and the sum of
+///nothing is null.
+fn sum_values(values: &[Value], average: bool) -> Result<Value, DataSourceError> {
+    if values.is_empty() {
+        return Ok(Value::Null);
+    }
+    let mut sum = 0.0;
+    let mut all_integers = true;
+    for value in values {
+        if !value.is_i64() && !value.is_u64() {
+            all_integers = false;
+        }
+        sum += value_as_f64(value)?;
+    }
+    if average {
+        Ok(json!(sum / values.len() as f64))
+    } else if all_integers {
+        Ok(json!(sum as i64))
+    } else {
+        Ok(json!(sum))
+    }
+}
+
+///Aggregates the values of a supporting field over the rows of a glyph, which are in row order.
+///A distinct list is returned as JSON text, which is what Athena's json_format gives us.
+pub fn aggregate_supporting_values(
+    aggregation: SupportingFieldAggregation,
+    values: Vec<Value>,
+) -> Result<Value, DataSourceError> {
+    match aggregation {
+        SupportingFieldAggregation::First => Ok(values.into_iter().next().unwrap_or(Value::Null)),
+        SupportingFieldAggregation::DistinctList => {
+            let mut distinct: Vec<Value> = Vec::new();
+            for value in values.into_iter().filter(|v| !v.is_null()) {
+                if !distinct.contains(&value) {
+                    distinct.push(value);
+                }
+            }
+            distinct.sort_by(compare_values);
+            Ok(json!(Value::Array(distinct).to_string()))
+        }
+        SupportingFieldAggregation::Sum | SupportingFieldAggregation::Avg => {
+            let values: Vec<Value> = values.into_iter().filter(|v| !v.is_null()).collect();
+            sum_values(&values, aggregation == SupportingFieldAggregation::Avg)
+        }
+    }
+}
+
+///Evaluates a filter against a single row the way a WHERE clause would.  Comparisons against a
+///null are unknown, None, and unknowns carry through AND, OR and NOT with SQL's three valued
+///logic, so a row is only kept when this returns Some(true).
+pub fn evaluate_filter(filter: &FilterExpression, row: &TableRow) -> Option<bool> {
+    match filter {
+        FilterExpression::Comparison {
+            field_name,
+            operator,
+            value,
+        } => {
+            let column_value = get_column_value(row, field_name);
+            if column_value.is_null() {
+                return None;
+            }
+            let ordering = compare_values(&column_value, value);
+            Some(match operator {
+                ComparisonOperator::Equal => ordering == Ordering::Equal,
+                ComparisonOperator::NotEqual => ordering != Ordering::Equal,
+                ComparisonOperator::LessThan => ordering == Ordering::Less,
+                ComparisonOperator::LessThanOrEqual => ordering != Ordering::Greater,
+                ComparisonOperator::GreaterThan => ordering == Ordering::Greater,
+                ComparisonOperator::GreaterThanOrEqual => ordering != Ordering::Less,
+            })
+        }
+        FilterExpression::In { field_name, values } => {
+            let column_value = get_column_value(row, field_name);
+            if column_value.is_null() {
+                return None;
+            }
+            Some(
+                values
+                    .iter()
+                    .any(|value| compare_values(&column_value, value) == Ordering::Equal),
+            )
+        }
+        FilterExpression::Range {
+            field_name,
+            min,
+            max,
+        } => {
+            let column_value = get_column_value(row, field_name);
+            if column_value.is_null() {
+                return None;
+            }
+            let above_min = min
+                .as_ref()
+                .is_none_or(|min| compare_values(&column_value, min) != Ordering::Less);
+            let below_max = max
+                .as_ref()
+                .is_none_or(|max| compare_values(&column_value, max) != Ordering::Greater);
+            Some(above_min && below_max)
+        }
+        FilterExpression::DateRange {
+            field_name,
+            from,
+            to,
+        } => {
+            let column_value = get_column_value(row, field_name).as_f64()?;
+            let after_from = from.is_none_or(|from| column_value >= from as f64);
+            let before_to = to.is_none_or(|to| column_value < to as f64);
+            Some(after_from && before_to)
+        }
+        FilterExpression::IsNull { field_name } => {
+            Some(get_column_value(row, field_name).is_null())
+        }
+        FilterExpression::IsNotNull { field_name } => {
+            Some(!get_column_value(row, field_name).is_null())
+        }
+        FilterExpression::And(filters) => {
+            let results: Vec<Option<bool>> = filters
+                .iter()
+                .map(|filter| evaluate_filter(filter, row))
+                .collect();
+            if results.contains(&Some(false)) {
+                Some(false)
+            } else if results.contains(&None) {
+                None
+            } else {
+                Some(true)
+            }
+        }
+        FilterExpression::Or(filters) => {
+            let results: Vec<Option<bool>> = filters
+                .iter()
+                .map(|filter| evaluate_filter(filter, row))
+                .collect();
+            if results.contains(&Some(true)) {
+                Some(true)
+            } else if results.contains(&None) {
+                None
+            } else {
+                Some(false)
+            }
+        }
+        FilterExpression::Not(filter) => evaluate_filter(filter, row).map(|result| !result),
+    }
+}
+
+///Orders values the way an ORDER BY would: numbers numerically, strings lexically, numbers
+///before strings and nulls last.
+pub fn compare_values(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        (Value::Number(l), Value::Number(r)) => {
+            let l = l.as_f64().unwrap_or(f64::NAN);
+            let r = r.as_f64().unwrap_or(f64::NAN);
+            l.partial_cmp(&r).unwrap_or(Ordering::Equal)
+        }
+        (Value::Number(_), _) => Ordering::Less,
+        (_, Value::Number(_)) => Ordering::Greater,
+        (Value::String(l), Value::String(r)) => l.cmp(r),
+        _ => left.to_string().cmp(&right.to_string()),
+    }
+}
+
+fn get_column_value(row: &TableRow, column_name: &str) -> Value {
+    row.get(column_name).cloned().unwrap_or(Value::Null)
+}
+
+fn value_as_f64(value: &Value) -> Result<f64, DataSourceError> {
+    if let Some(number) = value.as_f64() {
+        return Ok(number);
+    }
+    if let Some(number) = value.as_str().and_then(|s| s.trim().parse::<f64>().ok()) {
+        return Ok(number);
+    }
+    let message = format!("The value {} is not a number", value);
+    let data = json!({ "value": value });
+    Err(DataSourceError::DataError(GlyphxErrorData::new(
+        message,
+        Some(data),
+        None,
+    )))
+}
+
+fn evaluate_date(
+    field_definition: &DateFieldDefinition,
+    row: &TableRow,
+) -> Result<Value, DataSourceError> {
+    let value = get_column_value(row, &field_definition.field_name);
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    let invalid_timestamp = || {
+        let message = format!(
+            "The value {} in the field {} is not a valid timestamp",
+            value, field_definition.field_name
+        );
+        let data = json!({ "field_name": field_definition.field_name, "value": value });
+        DataSourceError::DataError(GlyphxErrorData::new(message, Some(data), None))
+    };
+    let milliseconds = match field_definition.date_unit {
+        DateUnit::Milliseconds => value_as_f64(&value)?,
+        DateUnit::Seconds => value_as_f64(&value)? * 1000.0,
+        //A local file has no timestamp type, so timestamps are either ISO strings or epoch
+        //milliseconds.
+        DateUnit::IsoString | DateUnit::Timestamp => match &value {
+            Value::String(text) => parse_iso_date(text).ok_or_else(invalid_timestamp)? as f64,
+            _ => value_as_f64(&value)?,
+        },
+    };
+    //Timestamps are already in the time zone of the field definition.
+    let offset_seconds = match (&field_definition.date_unit, &field_definition.time_zone) {
+        (DateUnit::Timestamp, _) | (_, None) => 0,
+        (_, Some(time_zone)) => {
+            let offset_seconds = parse_utc_offset(time_zone);
+            if offset_seconds.is_none() {
+                let message = format!(
+                    "The time zone {} is not supported for local files, only UTC and fixed offsets such as +05:30 are",
+                    time_zone
+                );
+                let data =
+                    json!({ "field_name": field_definition.field_name, "time_zone": time_zone });
+                return Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                    message,
+                    Some(data),
+                    None,
+                )));
+            }
+            offset_seconds.unwrap() as i64
+        }
+    };
+    let seconds = (milliseconds / 1000.0).floor() as i64 + offset_seconds;
+    let date_time = OffsetDateTime::from_unix_timestamp(seconds);
+    if date_time.is_err() {
+        return Err(invalid_timestamp());
+    }
+    let date_time = date_time.unwrap();
+    let year = date_time.year() as i64;
+    let month = u8::from(date_time.month()) as i64;
+    let day = date_time.day() as i64;
+    let hour = date_time.hour() as i64;
+    let minute = date_time.minute() as i64;
+    let day_of_year = date_time.ordinal() as i64;
+    let (week_year, week, weekday) = date_time.to_iso_week_date();
+    let week_year = week_year as i64;
+    let week = week as i64;
+    let day_of_week = weekday.number_from_monday() as i64;
+    let quarter = (month - 1) / 3 + 1;
+
+    let result = match field_definition.date_grouping {
+        DateGrouping::QualifiedDayOfYear => year * 1000 + day_of_year,
+        DateGrouping::DayOfYear => day_of_year,
+        DateGrouping::QualifiedDayOfMonth => year * 10000 + month * 100 + day,
+        DateGrouping::YearDayOfMonth => year * 100 + day,
+        DateGrouping::MonthDayOfMonth => month * 100 + day,
+        DateGrouping::DayOfMonth => day,
+        DateGrouping::QualifiedDayOfWeek => week_year * 1000 + week * 10 + day_of_week,
+        DateGrouping::DayOfWeek => day_of_week,
+        DateGrouping::QualifiedWeekOfYear => week_year * 100 + week,
+        DateGrouping::WeekOfYear => week,
+        DateGrouping::QualifiedMonth => year * 100 + month,
+        DateGrouping::MonthOfYear => month,
+        DateGrouping::Year => year,
+        DateGrouping::QualifiedQuarter => year * 10 + quarter,
+        DateGrouping::Quarter => quarter,
+        DateGrouping::YearOfWeek => week_year,
+        DateGrouping::HourOfDay => hour,
+        DateGrouping::QualifiedHour => year * 1000000 + month * 10000 + day * 100 + hour,
+        DateGrouping::MinuteBucket => {
+            let bucket_size = field_definition.minute_bucket as i64;
+            let bucket = (hour * 60 + minute) / bucket_size * bucket_size;
+            year * 100000000 + month * 1000000 + day * 10000 + bucket / 60 * 100 + bucket % 60
+        }
+        DateGrouping::WeekStarting => {
+            let days_into_week =
+                (day_of_week + 7 - field_definition.week_start.number_from_monday()) % 7;
+            let week_start = date_time.date() - Duration::days(days_into_week);
+            week_start.year() as i64 * 10000
+                + u8::from(week_start.month()) as i64 * 100
+                + week_start.day() as i64
+        }
+    };
+    Ok(json!(result))
+}
+
+fn evaluate_formula(
+    expression: &FormulaExpression,
+    row: &TableRow,
+) -> Result<Value, DataSourceError> {
+    let result = evaluate_expression(expression, row)?;
+    match result {
+        Some(value) if value.is_finite() => Ok(json!(value)),
+        _ => Ok(Value::Null),
+    }
+}
+
+fn evaluate_expression(
+    expression: &FormulaExpression,
+    row: &TableRow,
+) -> Result<Option<f64>, DataSourceError> {
+    let result = match expression {
+        FormulaExpression::Number(number) => number.parse::<f64>().ok(),
+        FormulaExpression::Column(column_name) => {
+            let value = get_column_value(row, column_name);
+            if value.is_null() {
+                None
+            } else {
+                Some(value_as_f64(&value)?)
+            }
+        }
+        FormulaExpression::Negate(operand) => evaluate_expression(operand, row)?.map(|v| -v),
+        FormulaExpression::Binary {
+            operator,
+            left,
+            right,
+        } => {
+            let left = evaluate_expression(left, row)?;
+            let right = evaluate_expression(right, row)?;
+            match (left, right) {
+                (Some(left), Some(right)) => apply_operator(operator, left, right),
+                _ => None,
+            }
+        }
+        FormulaExpression::Function {
+            function,
+            arguments,
+        } => {
+            let mut values = Vec::with_capacity(arguments.len());
+            for argument in arguments {
+                values.push(evaluate_expression(argument, row)?);
+            }
+            apply_function(function, &values)
+        }
+    };
+    Ok(result)
+}
+
+fn apply_operator(operator: &FormulaOperator, left: f64, right: f64) -> Option<f64> {
+    match operator {
+        FormulaOperator::Add => Some(left + right),
+        FormulaOperator::Subtract => Some(left - right),
+        FormulaOperator::Multiply => Some(left * right),
+        //A zero divisor has no value we can plot, so like a null it drops out of the result.
+        FormulaOperator::Divide | FormulaOperator::Modulo if right == 0.0 => None,
+        FormulaOperator::Divide => Some(left / right),
+        FormulaOperator::Modulo => Some(left % right),
+    }
+}
+
+fn apply_function(function: &FormulaFunction, values: &[Option<f64>]) -> Option<f64> {
+    match function {
+        FormulaFunction::Coalesce => return values.iter().find_map(|v| *v),
+        FormulaFunction::NullIf => {
+            return match (values[0], values[1]) {
+                (Some(left), Some(right)) if left == right => None,
+                (left, _) => left,
+            }
+        }
+        _ => {}
+    }
+    //Every other function returns null if any of its arguments are null
+    if values.iter().any(|v| v.is_none()) {
+        return None;
+    }
+    let values: Vec<f64> = values.iter().map(|v| v.unwrap()).collect();
+    let first = values[0];
+    match function {
+        FormulaFunction::Abs => Some(first.abs()),
+        FormulaFunction::Ceil => Some(first.ceil()),
+        FormulaFunction::Floor => Some(first.floor()),
+        FormulaFunction::Round => {
+            let digits = values.get(1).copied().unwrap_or(0.0);
+            let factor = 10f64.powi(digits as i32);
+            Some((first * factor).round() / factor)
+        }
+        FormulaFunction::Sqrt => Some(first.sqrt()),
+        FormulaFunction::Cbrt => Some(first.cbrt()),
+        FormulaFunction::Exp => Some(first.exp()),
+        FormulaFunction::Ln => Some(first.ln()),
+        FormulaFunction::Log2 => Some(first.log2()),
+        FormulaFunction::Log10 => Some(first.log10()),
+        FormulaFunction::Power => Some(first.powf(values[1])),
+        FormulaFunction::Mod => apply_operator(&FormulaOperator::Modulo, first, values[1]),
+        FormulaFunction::Sign => Some(if first == 0.0 { 0.0 } else { first.signum() }),
+        FormulaFunction::Greatest => values.into_iter().reduce(f64::max),
+        FormulaFunction::Least => values.into_iter().reduce(f64::min),
+        FormulaFunction::Coalesce | FormulaFunction::NullIf => None,
+    }
+}
+
+#[cfg(test)]
+mod evaluate_field {
+    use super::*;
+
+    fn get_field_definition(field_definition: Value) -> FieldDefinition {
+        FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "field",
+            "fieldDataType": 0,
+            "fieldDefinition": field_definition
+        }))
+        .unwrap()
+    }
+
+    fn get_row() -> TableRow {
+        json!({
+            "revenue": 10,
+            "cost": 4.5,
+            "name": "widget",
+            "missing": null,
+            //2023-03-15T12:00:00Z
+            "sold": 1678881600000u64
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn standard() {
+        let field_definition =
+            get_field_definition(json!({"fieldType": "standard", "fieldName": "name"}));
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert_eq!(result, json!("widget"));
+    }
+
+    #[test]
+    fn standard_missing_column_is_null() {
+        let field_definition =
+            get_field_definition(json!({"fieldType": "standard", "fieldName": "nope"}));
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn date_groupings() {
+        let cases = vec![
+            ("qualified_day_of_year", 2023074),
+            ("day_of_year", 74),
+            ("qualified_day_of_month", 20230315),
+            ("day_of_month", 15),
+            ("qualified_day_of_week", 2023113),
+            ("day_of_week", 3),
+            ("week_of_year", 11),
+            ("qualified_month", 202303),
+            ("month", 3),
+            ("year", 2023),
+            ("qualified_quarter", 20231),
+            ("quarter", 1),
+        ];
+        for (grouping, expected) in cases {
+            let field_definition = get_field_definition(
+                json!({"fieldType": "date", "fieldName": "sold", "dateGrouping": grouping}),
+            );
+            let result = evaluate_field(&field_definition, &get_row()).unwrap();
+            assert_eq!(result, json!(expected), "{}", grouping);
+        }
+    }
+
+    #[test]
+    fn sub_day_date_groupings() {
+        let cases = vec![
+            (json!({"dateGrouping": "hour_of_day"}), 12),
+            (json!({"dateGrouping": "qualified_hour"}), 2023031512),
+            (
+                json!({"dateGrouping": "minute_bucket", "minuteBucket": 50}),
+                202303151140u64,
+            ),
+            //2023-03-15 is a Wednesday
+            (json!({"dateGrouping": "week_starting"}), 20230313),
+            (
+                json!({"dateGrouping": "week_starting", "weekStart": "sunday"}),
+                20230312,
+            ),
+            (
+                json!({"dateGrouping": "week_starting", "weekStart": "wednesday"}),
+                20230315,
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut input = json!({"fieldType": "date", "fieldName": "sold"});
+            for (key, value) in options.as_object().unwrap() {
+                input[key] = value.clone();
+            }
+            let field_definition = get_field_definition(input);
+            let result = evaluate_field(&field_definition, &get_row()).unwrap();
+            assert_eq!(result, json!(expected), "{}", options);
+        }
+    }
+
+    #[test]
+    fn date_units_and_time_zones() {
+        let mut row = get_row();
+        row.insert("seconds".to_string(), json!(1678881600));
+        row.insert("iso".to_string(), json!("2023-03-15T12:00:00Z"));
+        row.insert("naive".to_string(), json!("2023-03-15 12:00:00"));
+        let cases = vec![
+            (json!({"fieldName": "sold", "timeZone": "-05:00"}), 7),
+            (json!({"fieldName": "sold", "timeZone": "UTC"}), 12),
+            (
+                json!({"fieldName": "seconds", "dateUnit": "seconds", "timeZone": "+13:00"}),
+                1,
+            ),
+            (
+                json!({"fieldName": "iso", "dateUnit": "iso", "timeZone": "+05:30"}),
+                17,
+            ),
+            (
+                json!({"fieldName": "naive", "dateUnit": "timestamp", "timeZone": "-05:00"}),
+                12,
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut input = json!({"fieldType": "date", "dateGrouping": "hour_of_day"});
+            for (key, value) in options.as_object().unwrap() {
+                input[key] = value.clone();
+            }
+            let field_definition = get_field_definition(input);
+            let result = evaluate_field(&field_definition, &row).unwrap();
+            assert_eq!(result, json!(expected), "{}", options);
+        }
+    }
+
+    #[test]
+    fn named_time_zone_is_unsupported() {
+        let field_definition = get_field_definition(json!({
+            "fieldType": "date",
+            "fieldName": "sold",
+            "dateGrouping": "hour_of_day",
+            "timeZone": "America/New_York"
+        }));
+        let result = evaluate_field(&field_definition, &get_row());
+        match result.err().unwrap() {
+            DataSourceError::UnsupportedQuery(_) => {}
+            _ => panic!("Expected UnsupportedQuery"),
+        }
+    }
+
+    #[test]
+    fn invalid_iso_date_is_an_error() {
+        let field_definition = get_field_definition(json!({
+            "fieldType": "date",
+            "fieldName": "name",
+            "dateGrouping": "year",
+            "dateUnit": "iso"
+        }));
+        let result = evaluate_field(&field_definition, &get_row());
+        match result.err().unwrap() {
+            DataSourceError::DataError(_) => {}
+            _ => panic!("Expected DataError"),
+        }
+    }
+
+    #[test]
+    fn formula() {
+        let field_definition = get_field_definition(
+            json!({"fieldType": "formula", "formula": "round((revenue - cost) / 2, 1)"}),
+        );
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert_eq!(result, json!(2.8));
+    }
+
+    #[test]
+    fn formula_with_null_is_null() {
+        let field_definition =
+            get_field_definition(json!({"fieldType": "formula", "formula": "revenue + missing"}));
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn formula_coalesce_skips_null() {
+        let field_definition = get_field_definition(
+            json!({"fieldType": "formula", "formula": "coalesce(missing, revenue)"}),
+        );
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert_eq!(result, json!(10.0));
+    }
+
+    #[test]
+    fn formula_divide_by_zero_is_null() {
+        let field_definition = get_field_definition(
+            json!({"fieldType": "formula", "formula": "revenue / (cost - cost)"}),
+        );
+        let result = evaluate_field(&field_definition, &get_row()).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn formula_over_a_string_is_an_error() {
+        let field_definition =
+            get_field_definition(json!({"fieldType": "formula", "formula": "name + 1"}));
+        let result = evaluate_field(&field_definition, &get_row());
+        match result.err().unwrap() {
+            DataSourceError::DataError(_) => {}
+            _ => panic!("Expected DataError"),
+        }
+    }
+
+    #[test]
+    fn accumulated_is_an_error() {
+        let field_definition = get_field_definition(json!({
+            "fieldType": "accumulated",
+            "accumulator": "sum",
+            "accumulatedFieldDefinition": {"fieldType": "standard", "fieldName": "revenue"}
+        }));
+        let result = evaluate_field(&field_definition, &get_row());
+        match result.err().unwrap() {
+            DataSourceError::UnsupportedQuery(_) => {}
+            _ => panic!("Expected UnsupportedQuery"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod accumulate {
+    use super::*;
+
+    fn get_accumulator(accumulator: &str) -> AccumulatorFieldDefinition {
+        AccumulatorFieldDefinition::from_json(&json!({
+            "fieldType": "accumulated",
+            "accumulator": accumulator,
+            "accumulatedFieldDefinition": {"fieldType": "standard", "fieldName": "revenue"}
+        }))
+        .unwrap()
+    }
+
+    fn get_values() -> Vec<Value> {
+        vec![json!(3), json!(1), Value::Null, json!(8)]
+    }
+
+    #[test]
+    fn sum() {
+        let result = accumulate(&get_accumulator("sum"), get_values()).unwrap();
+        assert_eq!(result, json!(12));
+    }
+
+    #[test]
+    fn avg() {
+        let result = accumulate(&get_accumulator("avg"), get_values()).unwrap();
+        assert_eq!(result, json!(4.0));
+    }
+
+    #[test]
+    fn min_and_max() {
+        let result = accumulate(&get_accumulator("min"), get_values()).unwrap();
+        assert_eq!(result, json!(1));
+        let result = accumulate(&get_accumulator("max"), get_values()).unwrap();
+        assert_eq!(result, json!(8));
+    }
+
+    #[test]
+    fn count_ignores_nulls() {
+        let result = accumulate(&get_accumulator("count"), get_values()).unwrap();
+        assert_eq!(result, json!(3));
+    }
+
+    #[test]
+    fn sum_of_nothing_is_null() {
+        let result = accumulate(&get_accumulator("sum"), vec![Value::Null]).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn sum_of_strings_is_an_error() {
+        let result = accumulate(&get_accumulator("sum"), vec![json!("abc")]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn count_distinct() {
+        let values = vec![json!(3), json!(1), Value::Null, json!(3)];
+        let result = accumulate(&get_accumulator("count_distinct"), values).unwrap();
+        assert_eq!(result, json!(2));
+    }
+
+    #[test]
+    fn median_and_percentile() {
+        let result = accumulate(&get_accumulator("median"), get_values()).unwrap();
+        assert_eq!(result, json!(3));
+        let mut accumulator = get_accumulator("median");
+        accumulator.accumulator_type = AccumulatorType::PERCENTILE;
+        accumulator.percentile = Some(0.9);
+        let result = accumulate(&accumulator, get_values()).unwrap();
+        assert_eq!(result, json!(8));
+    }
+
+    #[test]
+    fn stddev_and_variance() {
+        let result = accumulate(&get_accumulator("variance"), get_values()).unwrap();
+        assert_eq!(result, json!(13.0));
+        let result = accumulate(&get_accumulator("stddev"), get_values()).unwrap();
+        assert_eq!(result, json!(13.0_f64.sqrt()));
+        let result = accumulate(&get_accumulator("variance"), vec![json!(1)]).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn first_and_last() {
+        let accumulator = AccumulatorFieldDefinition::from_json(&json!({
+            "fieldType": "accumulated",
+            "accumulator": "first",
+            "orderByField": "order_date",
+            "accumulatedFieldDefinition": {"fieldType": "standard", "fieldName": "revenue"}
+        }))
+        .unwrap();
+        let rows = [
+            json!({"order_date": 20, "revenue": 5}),
+            json!({"order_date": 10, "revenue": 7}),
+            json!({"order_date": null, "revenue": 1}),
+            json!({"order_date": 30, "revenue": 9}),
+        ];
+        let values = rows
+            .iter()
+            .map(|row| evaluate_accumulator_input(&accumulator, row.as_object().unwrap()))
+            .collect::<Result<Vec<Value>, DataSourceError>>()
+            .unwrap();
+        let result = accumulate(&accumulator, values.clone()).unwrap();
+        assert_eq!(result, json!(7));
+
+        let mut accumulator = accumulator;
+        accumulator.accumulator_type = AccumulatorType::LAST;
+        let result = accumulate(&accumulator, values).unwrap();
+        assert_eq!(result, json!(9));
+    }
+}
+
+#[cfg(test)]
+mod aggregate_supporting_values {
+    use super::*;
+
+    fn get_values() -> Vec<Value> {
+        vec![Value::Null, json!("west"), json!("east"), json!("west")]
+    }
+
+    #[test]
+    fn first() {
+        let result =
+            aggregate_supporting_values(SupportingFieldAggregation::First, get_values()).unwrap();
+        assert!(result.is_null());
+        let result =
+            aggregate_supporting_values(SupportingFieldAggregation::First, vec![json!(2)]).unwrap();
+        assert_eq!(result, json!(2));
+    }
+
+    #[test]
+    fn distinct_list() {
+        let result =
+            aggregate_supporting_values(SupportingFieldAggregation::DistinctList, get_values())
+                .unwrap();
+        assert_eq!(result, json!(r#"["east","west"]"#));
+    }
+
+    #[test]
+    fn sum_and_avg() {
+        let values = vec![json!(3), Value::Null, json!(2)];
+        let result =
+            aggregate_supporting_values(SupportingFieldAggregation::Sum, values.clone()).unwrap();
+        assert_eq!(result, json!(5));
+        let result = aggregate_supporting_values(SupportingFieldAggregation::Avg, values).unwrap();
+        assert_eq!(result, json!(2.5));
+    }
+}
+
+#[cfg(test)]
+mod evaluate_bin_edges {
+    use super::*;
+
+    fn get_field_definition(binning_method: &str) -> BinnedFieldDefinition {
+        BinnedFieldDefinition::from_json(&json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": binning_method,
+            "binCount": 2
+        }))
+        .unwrap()
+    }
+
+    fn get_rows(values: Vec<Value>) -> Vec<TableRow> {
+        values
+            .into_iter()
+            .map(|value| json!({ "price": value }).as_object().unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn equal_width() {
+        let rows = get_rows(vec![json!(4), Value::Null, json!(0), json!(10)]);
+        let result = evaluate_bin_edges(&get_field_definition("equal_width"), &rows).unwrap();
+        assert_eq!(result, vec![0.0, 5.0, 10.0]);
+    }
+
+    #[test]
+    fn quantile() {
+        let rows = get_rows(vec![json!(1), json!(2), json!(3), json!(4), json!(100)]);
+        let result = evaluate_bin_edges(&get_field_definition("quantile"), &rows).unwrap();
+        assert_eq!(result, vec![1.0, 3.0, 100.0]);
+    }
+
+    #[test]
+    fn no_values() {
+        let rows = get_rows(vec![Value::Null]);
+        let result = evaluate_bin_edges(&get_field_definition("quantile"), &rows);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn evaluate_field() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "equal_width",
+                "binCount": 2
+            }
+        }))
+        .unwrap()
+        .with_bin_edges(vec![0.0, 5.0, 10.0]);
+        let rows = get_rows(vec![json!(4), Value::Null, json!(10)]);
+        let results = rows
+            .iter()
+            .map(|row| super::evaluate_field(&field_definition, row).unwrap())
+            .collect::<Vec<Value>>();
+        assert_eq!(
+            results,
+            vec![json!("[0, 5)"), Value::Null, json!("[5, 10]")]
+        );
+    }
+}
+
+#[cfg(test)]
+mod evaluate_top_values {
+    use super::*;
+
+    fn get_field_definition(top_n: Value) -> StandardFieldDefinition {
+        StandardFieldDefinition::from_json(&json!({
+            "fieldType": "standard",
+            "fieldName": "product",
+            "topN": top_n
+        }))
+        .unwrap()
+    }
+
+    fn get_rows() -> Vec<TableRow> {
+        vec![
+            json!({"product": "apple", "revenue": 1}),
+            json!({"product": "pear", "revenue": 10}),
+            json!({"product": "apple", "revenue": 2}),
+            json!({"product": "plum", "revenue": 4}),
+            json!({"product": null, "revenue": 100}),
+            json!({"product": "kiwi", "revenue": null}),
+        ]
+        .into_iter()
+        .map(|row| row.as_object().unwrap().clone())
+        .collect()
+    }
+
+    #[test]
+    fn by_count() {
+        let field_definition = get_field_definition(json!({"count": 2}));
+        let result = evaluate_top_values(&field_definition, &get_rows()).unwrap();
+        //apple occurs twice and kiwi wins the tie with the others on its name.
+        assert_eq!(result, vec!["apple", "kiwi"]);
+    }
+
+    #[test]
+    fn by_accumulator() {
+        let field_definition = get_field_definition(
+            json!({"count": 3, "accumulator": "sum", "rankByField": "revenue"}),
+        );
+        let result = evaluate_top_values(&field_definition, &get_rows()).unwrap();
+        assert_eq!(result, vec!["pear", "plum", "apple"]);
+    }
+
+    #[test]
+    fn evaluate_field() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": {"count": 1}
+            }
+        }))
+        .unwrap()
+        .with_top_values(vec!["apple".to_string()]);
+        let results = get_rows()
+            .iter()
+            .map(|row| super::evaluate_field(&field_definition, row).unwrap())
+            .collect::<Vec<Value>>();
+        assert_eq!(
+            results,
+            vec![
+                json!("apple"),
+                json!("Other"),
+                json!("apple"),
+                json!("Other"),
+                Value::Null,
+                json!("Other")
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod sort_vector_values {
+    use super::*;
+
+    fn get_field_definition(sort_order: Value) -> FieldDefinition {
+        FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product"
+            },
+            "sortOrder": sort_order
+        }))
+        .unwrap()
+    }
+
+    fn get_rows() -> Vec<TableRow> {
+        vec![
+            json!({"product": "apple", "revenue": 1}),
+            json!({"product": "pear", "revenue": 10}),
+            json!({"product": "apple", "revenue": 2}),
+            json!({"product": "plum", "revenue": 4}),
+            json!({"product": "kiwi", "revenue": null}),
+        ]
+        .into_iter()
+        .map(|row| row.as_object().unwrap().clone())
+        .collect()
+    }
+
+    fn sort(sort_order: Value) -> Vec<Value> {
+        let mut values = vec![json!("apple"), json!("kiwi"), json!("pear"), json!("plum")];
+        sort_vector_values(&get_field_definition(sort_order), &mut values, &get_rows()).unwrap();
+        values
+    }
+
+    #[test]
+    fn by_value() {
+        assert_eq!(
+            sort(Value::Null),
+            vec![json!("apple"), json!("kiwi"), json!("pear"), json!("plum")]
+        );
+        assert_eq!(
+            sort(json!({"direction": "desc"})),
+            vec![json!("plum"), json!("pear"), json!("kiwi"), json!("apple")]
+        );
+    }
+
+    #[test]
+    fn by_accumulator() {
+        //kiwi has no revenue so it goes last in either direction.
+        assert_eq!(
+            sort(json!({
+                "sortBy": "accumulator",
+                "accumulator": "sum",
+                "fieldName": "revenue",
+                "direction": "desc"
+            })),
+            vec![json!("pear"), json!("plum"), json!("apple"), json!("kiwi")]
+        );
+        assert_eq!(
+            sort(json!({"sortBy": "accumulator", "accumulator": "sum", "fieldName": "revenue"})),
+            vec![json!("apple"), json!("plum"), json!("pear"), json!("kiwi")]
+        );
+        //Ties keep their value order.
+        assert_eq!(
+            sort(json!({"sortBy": "accumulator", "direction": "desc"})),
+            vec![json!("apple"), json!("kiwi"), json!("pear"), json!("plum")]
+        );
+    }
+
+    #[test]
+    fn explicit() {
+        assert_eq!(
+            sort(json!({"sortBy": "explicit", "values": ["plum", "apple"]})),
+            vec![json!("plum"), json!("apple"), json!("kiwi"), json!("pear")]
+        );
+    }
+}
+
+#[cfg(test)]
+mod evaluate_filter {
+    use super::*;
+
+    fn get_row() -> TableRow {
+        json!({"region": "east", "units": 3, "created": 1000, "price": null})
+            .as_object()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn comparisons() {
+        let row = get_row();
+        let filter = FilterExpression::Comparison {
+            field_name: "units".to_string(),
+            operator: ComparisonOperator::GreaterThanOrEqual,
+            value: json!(3),
+        };
+        assert_eq!(evaluate_filter(&filter, &row), Some(true));
+        let filter = FilterExpression::In {
+            field_name: "region".to_string(),
+            values: vec![json!("west"), json!("north")],
+        };
+        assert_eq!(evaluate_filter(&filter, &row), Some(false));
+        let filter = FilterExpression::Range {
+            field_name: "units".to_string(),
+            min: Some(json!(1)),
+            max: Some(json!(3)),
+        };
+        assert_eq!(evaluate_filter(&filter, &row), Some(true));
+        let filter = FilterExpression::DateRange {
+            field_name: "created".to_string(),
+            from: Some(0),
+            to: Some(1000),
+        };
+        assert_eq!(evaluate_filter(&filter, &row), Some(false));
+    }
+
+    #[test]
+    fn nulls() {
+        let row = get_row();
+        let comparison = FilterExpression::Comparison {
+            field_name: "price".to_string(),
+            operator: ComparisonOperator::Equal,
+            value: json!(1),
+        };
+        assert_eq!(evaluate_filter(&comparison, &row), None);
+        let filter = FilterExpression::Not(Box::new(comparison.clone()));
+        assert_eq!(evaluate_filter(&filter, &row), None);
+        let is_null = FilterExpression::IsNull {
+            field_name: "price".to_string(),
+        };
+        assert_eq!(evaluate_filter(&is_null, &row), Some(true));
+        let filter = FilterExpression::Or(vec![comparison.clone(), is_null]);
+        assert_eq!(evaluate_filter(&filter, &row), Some(true));
+        let is_not_null = FilterExpression::IsNotNull {
+            field_name: "price".to_string(),
+        };
+        let filter = FilterExpression::And(vec![comparison, is_not_null]);
+        assert_eq!(evaluate_filter(&filter, &row), Some(false));
+    }
+}
@@ -0,0 +1,1091 @@
+use super::field_evaluator::{
+    accumulate, aggregate_supporting_values, compare_values, evaluate_accumulator_input,
+    evaluate_bin_edges, evaluate_bin_index, evaluate_field, evaluate_filter, evaluate_top_values,
+    sort_vector_values, TableRow,
+};
+use super::{
+    BinEdgesQuery, DataSource, DataSourceError, GlyphQuery, GlyphQueryRow, RowStream,
+    TopValuesQuery, VectorQuery,
+};
+use crate::types::vectorizer_parameters::{FieldDefinition, Filter, FilterExpression};
+use glyphx_core::{
+    aws::athena_manager::{AthenaQueryStatus, ColumnDataType, ColumnDescription},
+    GlyphxErrorData,
+};
+
+use async_trait::async_trait;
+use parquet::{
+    file::reader::{FileReader, SerializedFileReader},
+    record::Field,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+//The row id column that our ingestion process adds to every table.  If a local file does not
+//have one, the zero based position of the row in the file is used instead.
+const ROW_ID_COLUMN: &str = "glyphx_id__";
+
+struct LocalTable {
+    rows: Vec<TableRow>,
+}
+
+//The rows of a glyph query that share an x and y value, along with the values that will be
+//accumulated for z and each glyph channel and aggregated for each supporting field.
+struct GlyphGroup {
+    x_value: Value,
+    y_value: Value,
+    row_ids: Vec<String>,
+    z_values: Vec<Value>,
+    channel_values: Vec<Vec<Value>>,
+    supporting_values: Vec<Vec<Value>>,
+}
+
+///Reads tables from CSV or Parquet files in a local directory and runs our queries against
+///them in memory.  A table named my_table is read from my_table.csv or my_table.parquet.  This
+///is intended for small datasets, demos and tests where standing up Athena is not practical.
+pub struct LocalFileDataSource {
+    directory: PathBuf,
+    tables: Mutex<HashMap<String, Arc<LocalTable>>>,
+    query_results: Mutex<HashMap<String, Vec<Value>>>,
+    next_query_id: AtomicU64,
+}
+
+impl LocalFileDataSource {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            tables: Mutex::new(HashMap::new()),
+            query_results: Mutex::new(HashMap::new()),
+            next_query_id: AtomicU64::new(1),
+        }
+    }
+
+    fn get_table(&self, table_name: &str) -> Result<Arc<LocalTable>, DataSourceError> {
+        if let Some(table) = self.tables.lock().unwrap().get(table_name) {
+            return Ok(table.clone());
+        }
+        let csv_path = self.directory.join(format!("{}.csv", table_name));
+        let parquet_path = self.directory.join(format!("{}.parquet", table_name));
+        let rows = if csv_path.is_file() {
+            read_csv_file(&csv_path)?
+        } else if parquet_path.is_file() {
+            read_parquet_file(&parquet_path)?
+        } else {
+            let message = format!(
+                "Unable to find a .csv or .parquet file for the table {} in {}",
+                table_name,
+                self.directory.display()
+            );
+            let data = json!({ "table_name": table_name, "directory": self.directory.display().to_string() });
+            return Err(DataSourceError::TableNotFound(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )));
+        };
+        let table = Arc::new(LocalTable { rows });
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(table_name.to_string(), table.clone());
+        Ok(table)
+    }
+
+    ///Raw SQL cannot be evaluated in memory, so only filter expressions are supported.
+    fn get_filter_expression(
+        filter: &Option<Filter>,
+    ) -> Result<Option<&FilterExpression>, DataSourceError> {
+        match filter {
+            Some(Filter::Raw(_)) => {
+                let message =
+                    "Raw SQL filters cannot be applied to a local file data source".to_string();
+                let data = json!({ "filter": filter });
+                Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                    message,
+                    Some(data),
+                    None,
+                )))
+            }
+            Some(Filter::Expression(expression)) => Ok(Some(expression)),
+            None => Ok(None),
+        }
+    }
+
+    ///Returns the rows of the table that pass the filter, borrowing them when there is no
+    ///filter.
+    fn filter_rows<'a>(
+        rows: &'a [TableRow],
+        filter: &Option<Filter>,
+    ) -> Result<Cow<'a, [TableRow]>, DataSourceError> {
+        let filter = Self::get_filter_expression(filter)?;
+        match filter {
+            Some(filter) => Ok(Cow::Owned(
+                rows.iter()
+                    .filter(|row| evaluate_filter(filter, row) == Some(true))
+                    .cloned()
+                    .collect(),
+            )),
+            None => Ok(Cow::Borrowed(rows)),
+        }
+    }
+
+    fn run_glyph_query(&self, query: &GlyphQuery) -> Result<Vec<Value>, DataSourceError> {
+        let filter = Self::get_filter_expression(&query.filter)?;
+        let z_accumulator = match &query.z_field_definition {
+            FieldDefinition::Accumulated {
+                field_definition, ..
+            } => field_definition,
+            _ => {
+                let message = "The z axis must be an accumulated field".to_string();
+                let data = json!({ "field_display_name": query.z_field_definition.get_field_display_name() });
+                return Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                    message,
+                    Some(data),
+                    None,
+                )));
+            }
+        };
+        let mut channel_accumulators = Vec::with_capacity(query.channel_field_definitions.len());
+        for (channel, field_definition) in query.channel_field_definitions.iter() {
+            match field_definition {
+                FieldDefinition::Accumulated {
+                    field_definition, ..
+                } => channel_accumulators.push(field_definition),
+                _ => {
+                    let message = format!(
+                        "The glyph {} channel must be an accumulated field",
+                        channel.get_axis_name()
+                    );
+                    let data =
+                        json!({ "field_display_name": field_definition.get_field_display_name() });
+                    return Err(DataSourceError::UnsupportedQuery(GlyphxErrorData::new(
+                        message,
+                        Some(data),
+                        None,
+                    )));
+                }
+            }
+        }
+        let table = self.get_table(&query.table_name)?;
+
+        //Groups are kept in the order that they are first seen, the index lets us find a group
+        //from the JSON text of its x and y values.
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<GlyphGroup> = Vec::new();
+        for (position, row) in table.rows.iter().enumerate() {
+            if let Some(filter) = filter {
+                if evaluate_filter(filter, row) != Some(true) {
+                    continue;
+                }
+            }
+            let x_value = evaluate_field(&query.x_field_definition, row)?;
+            let y_value = evaluate_field(&query.y_field_definition, row)?;
+            //A null has no place in the vector tables, so the row could never be plotted.
+            if x_value.is_null() || y_value.is_null() {
+                continue;
+            }
+            let z_value = evaluate_accumulator_input(z_accumulator, row)?;
+            let mut channel_values = Vec::with_capacity(channel_accumulators.len());
+            for accumulator in channel_accumulators.iter() {
+                channel_values.push(evaluate_accumulator_input(accumulator, row)?);
+            }
+            let mut supporting_values = Vec::with_capacity(query.supporting_fields.len());
+            for supporting_field in query.supporting_fields.iter() {
+                supporting_values.push(evaluate_field(&supporting_field.field_definition, row)?);
+            }
+            let row_id = get_row_id(row, position);
+            let key = format!("{}|{}", x_value, y_value);
+            let index = match group_index.get(&key) {
+                Some(index) => *index,
+                None => {
+                    groups.push(GlyphGroup {
+                        x_value,
+                        y_value,
+                        row_ids: Vec::new(),
+                        z_values: Vec::new(),
+                        channel_values: vec![Vec::new(); channel_accumulators.len()],
+                        supporting_values: vec![Vec::new(); query.supporting_fields.len()],
+                    });
+                    group_index.insert(key, groups.len() - 1);
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.row_ids.push(row_id);
+            group.z_values.push(z_value);
+            for (values, value) in group.channel_values.iter_mut().zip(channel_values) {
+                values.push(value);
+            }
+            for (values, value) in group.supporting_values.iter_mut().zip(supporting_values) {
+                values.push(value);
+            }
+        }
+
+        let x_name = format!("x_{}", query.x_field_definition.get_field_display_name());
+        let y_name = format!("y_{}", query.y_field_definition.get_field_display_name());
+        let z_name = format!("z_{}", query.z_field_definition.get_field_display_name());
+        let channel_names = query
+            .channel_field_definitions
+            .iter()
+            .map(|(channel, field_definition)| {
+                format!(
+                    "{}_{}",
+                    channel.get_axis_name(),
+                    field_definition.get_field_display_name()
+                )
+            })
+            .collect::<Vec<String>>();
+        let mut results = Vec::with_capacity(groups.len());
+        for group in groups {
+            let z_value = accumulate(z_accumulator, group.z_values)?;
+            let mut result = serde_json::Map::new();
+            result.insert("rowids".to_string(), json!(group.row_ids.join("|")));
+            result.insert(x_name.clone(), group.x_value);
+            result.insert(y_name.clone(), group.y_value);
+            result.insert(z_name.clone(), z_value);
+            for ((name, accumulator), values) in channel_names
+                .iter()
+                .zip(channel_accumulators.iter())
+                .zip(group.channel_values)
+            {
+                result.insert(name.clone(), accumulate(accumulator, values)?);
+            }
+            for (supporting_field, values) in
+                query.supporting_fields.iter().zip(group.supporting_values)
+            {
+                result.insert(
+                    supporting_field.get_column_name(),
+                    aggregate_supporting_values(supporting_field.aggregation, values)?,
+                );
+            }
+            results.push(Value::Object(result));
+        }
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl DataSource for LocalFileDataSource {
+    ///Files do not carry a schema, so the type of each column is taken from its values.  A column
+    ///with no values, or with values of more than one type, is UNKNOWN.
+    async fn get_table_columns(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDescription>, DataSourceError> {
+        let table = self.get_table(table_name)?;
+        let mut column_names: Vec<String> = Vec::new();
+        for row in table.rows.iter() {
+            for column_name in row.keys() {
+                if !column_names.contains(column_name) {
+                    column_names.push(column_name.clone());
+                }
+            }
+        }
+        let columns = column_names
+            .into_iter()
+            .map(|name| {
+                let values: Vec<&Value> = table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(&name))
+                    .filter(|value| !value.is_null())
+                    .collect();
+                let data_type = if values.is_empty() {
+                    ColumnDataType::UNKNOWN
+                } else if values.iter().all(|value| value.is_i64() || value.is_u64()) {
+                    ColumnDataType::INTEGER
+                } else if values.iter().all(|value| value.is_number()) {
+                    ColumnDataType::NUMBER
+                } else if values.iter().all(|value| value.is_string()) {
+                    ColumnDataType::STRING
+                } else {
+                    ColumnDataType::UNKNOWN
+                };
+                ColumnDescription { name, data_type }
+            })
+            .collect();
+        Ok(columns)
+    }
+
+    async fn get_vector_values(&self, query: &VectorQuery) -> Result<Value, DataSourceError> {
+        let table = self.get_table(&query.table_name)?;
+        let rows = Self::filter_rows(&table.rows, &query.filter)?;
+        let field_name = query.field_definition.get_field_display_name();
+        let mut values: Vec<Value> = Vec::new();
+        match &query.field_definition {
+            //An accumulated vector is accumulated over the whole table, so it has one value.
+            FieldDefinition::Accumulated {
+                field_definition, ..
+            } => {
+                let mut accumulated_values = Vec::with_capacity(rows.len());
+                for row in rows.iter() {
+                    accumulated_values.push(evaluate_accumulator_input(field_definition, row)?);
+                }
+                values.push(accumulate(field_definition, accumulated_values)?);
+            }
+            //Bins are ordered by their number rather than by their labels.
+            FieldDefinition::Binned {
+                field_definition, ..
+            } => {
+                let mut bin_indexes: Vec<usize> = Vec::new();
+                for row in rows.iter() {
+                    let bin_index = evaluate_bin_index(field_definition, row)?;
+                    if let Some(bin_index) = bin_index {
+                        if !bin_indexes.contains(&bin_index) {
+                            bin_indexes.push(bin_index);
+                        }
+                    }
+                }
+                bin_indexes.sort();
+                values = bin_indexes
+                    .into_iter()
+                    .map(|bin_index| json!(field_definition.get_bin_label(bin_index)))
+                    .collect::<Vec<Value>>();
+            }
+            _ => {
+                let mut seen: HashMap<String, ()> = HashMap::new();
+                for row in rows.iter() {
+                    let value = evaluate_field(&query.field_definition, row)?;
+                    if value.is_null() {
+                        continue;
+                    }
+                    if seen.insert(value.to_string(), ()).is_none() {
+                        values.push(value);
+                    }
+                }
+                values.sort_by(compare_values);
+            }
+        }
+        sort_vector_values(&query.field_definition, &mut values, &rows)?;
+        //The other bucket of a top N goes after the values that were kept.
+        let top_n = query
+            .field_definition
+            .get_standard_field_definition()
+            .and_then(|field_definition| field_definition.get_resolved_top_n());
+        if let Some(top_n) = top_n {
+            values.sort_by_key(|value| value.as_str() == Some(top_n.other_label.as_str()));
+        }
+        let rows = values
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(|value| json!({ field_name: value }))
+            .collect::<Vec<Value>>();
+        Ok(Value::Array(rows))
+    }
+
+    async fn get_bin_edges(&self, query: &BinEdgesQuery) -> Result<Vec<f64>, DataSourceError> {
+        if query.field_definition.get_quantiles().is_empty() {
+            return Ok(query.field_definition.edges.clone());
+        }
+        let table = self.get_table(&query.table_name)?;
+        evaluate_bin_edges(&query.field_definition, &table.rows)
+    }
+
+    async fn get_top_values(&self, query: &TopValuesQuery) -> Result<Vec<String>, DataSourceError> {
+        if query.field_definition.top_n.is_none() {
+            return Ok(Vec::new());
+        }
+        let table = self.get_table(&query.table_name)?;
+        evaluate_top_values(&query.field_definition, &table.rows)
+    }
+
+    async fn start_glyph_query(&self, query: &GlyphQuery) -> Result<String, DataSourceError> {
+        //The query is run to completion here, so by the time anyone asks it has succeeded.
+        let results = self.run_glyph_query(query)?;
+        let query_id = format!(
+            "local-{}",
+            self.next_query_id.fetch_add(1, Ordering::SeqCst)
+        );
+        self.query_results
+            .lock()
+            .unwrap()
+            .insert(query_id.clone(), results);
+        Ok(query_id)
+    }
+
+    async fn get_glyph_query_status(
+        &self,
+        query_id: &str,
+    ) -> Result<AthenaQueryStatus, DataSourceError> {
+        if self.query_results.lock().unwrap().contains_key(query_id) {
+            Ok(AthenaQueryStatus::Succeeded)
+        } else {
+            Err(query_not_found_error(query_id))
+        }
+    }
+
+    ///Our queries run to completion when they are started, so stopping one just drops its
+    ///results.
+    async fn stop_glyph_query(&self, query_id: &str) -> Result<(), DataSourceError> {
+        self.query_results.lock().unwrap().remove(query_id);
+        Ok(())
+    }
+
+    async fn get_glyph_query_results(
+        &self,
+        query_id: &str,
+    ) -> Result<Box<dyn RowStream>, DataSourceError> {
+        let results = self.query_results.lock().unwrap().remove(query_id);
+        if results.is_none() {
+            return Err(query_not_found_error(query_id));
+        }
+        Ok(Box::new(LocalRowStream {
+            rows: results.unwrap().into_iter(),
+        }))
+    }
+}
+
+struct LocalRowStream {
+    rows: std::vec::IntoIter<Value>,
+}
+
+#[async_trait]
+impl RowStream for LocalRowStream {
+    async fn next(&mut self) -> Result<Option<GlyphQueryRow>, DataSourceError> {
+        let row = match self.rows.next() {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let result = GlyphQueryRow::deserialize(&row);
+        if result.is_err() {
+            let message = format!(
+                "Unable to read the row of the query results : {}",
+                result.err().unwrap()
+            );
+            let data = json!({ "row": row });
+            return Err(DataSourceError::DataError(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )));
+        }
+        Ok(Some(result.unwrap()))
+    }
+}
+
+fn query_not_found_error(query_id: &str) -> DataSourceError {
+    let message = format!("The query {} was not found", query_id);
+    let data = json!({ "query_id": query_id });
+    DataSourceError::QueryNotFound(GlyphxErrorData::new(message, Some(data), None))
+}
+
+fn get_row_id(row: &TableRow, position: usize) -> String {
+    match row.get(ROW_ID_COLUMN) {
+        Some(Value::Number(number)) => number.to_string(),
+        Some(Value::String(string)) => string.clone(),
+        _ => position.to_string(),
+    }
+}
+
+fn file_read_error(path: &Path, error: String) -> DataSourceError {
+    let message = format!(
+        "An error occurred while reading the file {}",
+        path.display()
+    );
+    let data = json!({ "file_name": path.display().to_string() });
+    DataSourceError::FileReadError(GlyphxErrorData::new(
+        message,
+        Some(data),
+        Some(json!(error)),
+    ))
+}
+
+///CSV files are untyped, so each value is typed the way a crawler would: integers, then
+///floating point numbers, then strings.  Empty values are null.  Values with a leading zero,
+///such as the id 007, stay strings so that the zeros are not lost.
+fn parse_csv_value(value: &str) -> Value {
+    if value.is_empty() {
+        return Value::Null;
+    }
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+        return json!(value);
+    }
+    if let Ok(number) = value.parse::<i64>() {
+        return json!(number);
+    }
+    if let Ok(number) = value.parse::<f64>() {
+        if number.is_finite() {
+            return json!(number);
+        }
+    }
+    json!(value)
+}
+
+fn read_csv_file(path: &Path) -> Result<Vec<TableRow>, DataSourceError> {
+    let reader = csv::Reader::from_path(path);
+    if reader.is_err() {
+        return Err(file_read_error(path, reader.err().unwrap().to_string()));
+    }
+    let mut reader = reader.unwrap();
+    let headers = reader.headers();
+    if headers.is_err() {
+        return Err(file_read_error(path, headers.err().unwrap().to_string()));
+    }
+    let headers = headers.unwrap().clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        if record.is_err() {
+            return Err(file_read_error(path, record.err().unwrap().to_string()));
+        }
+        let record = record.unwrap();
+        let mut row = TableRow::new();
+        for (header, value) in headers.iter().zip(record.iter()) {
+            row.insert(header.to_string(), parse_csv_value(value));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+///Dates and timestamps are converted to milliseconds since the epoch, which is how our
+///ingestion process stores them.
+fn convert_parquet_field(field: &Field) -> Value {
+    match field {
+        Field::Null => Value::Null,
+        Field::Bool(value) => json!(value),
+        Field::Byte(value) => json!(value),
+        Field::Short(value) => json!(value),
+        Field::Int(value) => json!(value),
+        Field::Long(value) => json!(value),
+        Field::UByte(value) => json!(value),
+        Field::UShort(value) => json!(value),
+        Field::UInt(value) => json!(value),
+        Field::ULong(value) => json!(value),
+        Field::Float16(value) => json!(f32::from(*value)),
+        Field::Float(value) => json!(value),
+        Field::Double(value) => json!(value),
+        Field::Decimal(_) => parse_csv_value(&field.to_string()),
+        Field::Str(value) => json!(value),
+        Field::Date(days) => json!(*days as i64 * 86_400_000),
+        Field::TimestampMillis(value) => json!(value),
+        Field::TimestampMicros(value) => json!(value / 1000),
+        _ => json!(field.to_string()),
+    }
+}
+
+fn read_parquet_file(path: &Path) -> Result<Vec<TableRow>, DataSourceError> {
+    let file = File::open(path);
+    if file.is_err() {
+        return Err(file_read_error(path, file.err().unwrap().to_string()));
+    }
+    let reader = SerializedFileReader::new(file.unwrap());
+    if reader.is_err() {
+        return Err(file_read_error(path, reader.err().unwrap().to_string()));
+    }
+    let reader = reader.unwrap();
+    let row_iter = reader.get_row_iter(None);
+    if row_iter.is_err() {
+        return Err(file_read_error(path, row_iter.err().unwrap().to_string()));
+    }
+    let mut rows = Vec::new();
+    for record in row_iter.unwrap() {
+        if record.is_err() {
+            return Err(file_read_error(path, record.err().unwrap().to_string()));
+        }
+        let record = record.unwrap();
+        let mut row = TableRow::new();
+        for (name, field) in record.get_column_iter() {
+            row.insert(name.clone(), convert_parquet_field(field));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use std::path::PathBuf;
+
+    ///Writes the csv text to <temp dir>/<directory>/<table_name>.csv and returns the directory
+    pub fn write_csv_table(directory: &str, table_name: &str, contents: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join(format!("{}.csv", table_name)), contents).unwrap();
+        directory
+    }
+
+    pub const SALES_CSV: &str = "glyphx_id__,region,product,units,price
+1,east,apple,3,1.5
+2,west,apple,5,1.5
+3,east,pear,2,2.25
+4,east,apple,4,1.75
+5,west,pear,,2.0
+";
+}
+
+#[cfg(test)]
+mod parse_csv_value {
+    use super::*;
+
+    #[test]
+    fn empty_is_null() {
+        assert_eq!(parse_csv_value(""), Value::Null);
+    }
+
+    #[test]
+    fn numbers_are_numbers() {
+        assert_eq!(parse_csv_value("0"), json!(0));
+        assert_eq!(parse_csv_value("42"), json!(42));
+        assert_eq!(parse_csv_value("-7"), json!(-7));
+        assert_eq!(parse_csv_value("0.5"), json!(0.5));
+        assert_eq!(parse_csv_value("-0.25"), json!(-0.25));
+    }
+
+    #[test]
+    fn leading_zeros_are_strings() {
+        assert_eq!(parse_csv_value("007"), json!("007"));
+        assert_eq!(parse_csv_value("-007"), json!("-007"));
+        assert_eq!(parse_csv_value("00.5"), json!("00.5"));
+        assert_eq!(parse_csv_value("01234"), json!("01234"));
+    }
+
+    #[test]
+    fn text_is_a_string() {
+        assert_eq!(parse_csv_value("east"), json!("east"));
+    }
+}
+
+#[cfg(test)]
+mod get_vector_values {
+    use super::test_helpers::*;
+    use super::*;
+    use crate::types::vectorizer_parameters::{AccumulatorType, SortDirection, SortOrder};
+
+    fn get_field_definition(field_name: &str) -> FieldDefinition {
+        FieldDefinition::from_json(&json!({
+            "fieldDisplayName": field_name,
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": field_name
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn is_ok() {
+        let directory = write_csv_table("glyph_engine_vector_values_is_ok", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition: get_field_definition("product"),
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        assert_eq!(result, json!([{"product": "apple"}, {"product": "pear"}]));
+    }
+
+    #[tokio::test]
+    async fn numbers_are_sorted_and_nulls_dropped() {
+        let directory = write_csv_table("glyph_engine_vector_values_numbers", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition: get_field_definition("units"),
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        assert_eq!(
+            result,
+            json!([{"units": 2}, {"units": 3}, {"units": 4}, {"units": 5}])
+        );
+    }
+
+    #[tokio::test]
+    async fn filtered() {
+        let directory = write_csv_table("glyph_engine_vector_values_filtered", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let filter = FilterExpression::from_json(
+            &json!({"fieldName": "region", "operator": "=", "value": "west"}),
+        )
+        .unwrap();
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition: get_field_definition("units"),
+            filter: Some(Filter::Expression(filter)),
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        assert_eq!(result, json!([{"units": 5}]));
+
+        let query = VectorQuery {
+            filter: Some(Filter::Raw("region = 'west'".to_string())),
+            ..query
+        };
+        match data_source.get_vector_values(&query).await.err().unwrap() {
+            DataSourceError::UnsupportedQuery(_) => {}
+            _ => panic!("Expected UnsupportedQuery"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sorted_by_accumulator() {
+        let directory = write_csv_table("glyph_engine_vector_values_sorted", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let field_definition =
+            get_field_definition("product").with_sort_order(SortOrder::Accumulator {
+                accumulator: AccumulatorType::SUM,
+                field_name: Some("units".to_string()),
+                direction: SortDirection::Ascending,
+            });
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition,
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        //pear sells 2 units and apple sells 12.
+        assert_eq!(result, json!([{"product": "pear"}, {"product": "apple"}]));
+    }
+
+    #[tokio::test]
+    async fn binned() {
+        let directory = write_csv_table("glyph_engine_vector_values_binned", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "equal_width",
+                "binCount": 3
+            }
+        }))
+        .unwrap();
+        let edges = data_source
+            .get_bin_edges(&BinEdgesQuery {
+                table_name: "sales".to_string(),
+                field_definition: field_definition
+                    .get_binned_field_definition()
+                    .unwrap()
+                    .clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(edges, vec![1.5, 1.75, 2.0, 2.25]);
+
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition: field_definition.with_bin_edges(vec![1.6, 2.1]),
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        assert_eq!(
+            result,
+            json!([{"price": "< 1.6"}, {"price": "[1.6, 2.1]"}, {"price": "> 2.1"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn top_n() {
+        let directory = write_csv_table("glyph_engine_vector_values_top_n", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": {"count": 1, "otherLabel": "All others"}
+            }
+        }))
+        .unwrap();
+        let top_values = data_source
+            .get_top_values(&TopValuesQuery {
+                table_name: "sales".to_string(),
+                field_definition: field_definition
+                    .get_standard_field_definition()
+                    .unwrap()
+                    .clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(top_values, vec!["apple".to_string()]);
+
+        let query = VectorQuery {
+            table_name: "sales".to_string(),
+            field_definition: field_definition.with_top_values(top_values),
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await.unwrap();
+        assert_eq!(
+            result,
+            json!([{"product": "apple"}, {"product": "All others"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn table_not_found() {
+        let directory = std::env::temp_dir().join("glyph_engine_vector_values_not_found");
+        let data_source = LocalFileDataSource::new(&directory);
+        let query = VectorQuery {
+            table_name: "nope".to_string(),
+            field_definition: get_field_definition("product"),
+            filter: None,
+        };
+        let result = data_source.get_vector_values(&query).await;
+        match result.err().unwrap() {
+            DataSourceError::TableNotFound(_) => {}
+            _ => panic!("Expected TableNotFound"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_table_columns {
+    use super::test_helpers::*;
+    use super::*;
+
+    #[tokio::test]
+    async fn is_ok() {
+        let directory = write_csv_table("glyph_engine_get_table_columns", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let columns = data_source.get_table_columns("sales").await.unwrap();
+        let get_type = |name: &str| {
+            let column = columns.iter().find(|column| column.name == name).unwrap();
+            format!("{:?}", column.data_type)
+        };
+        assert_eq!(columns.len(), 5);
+        assert_eq!(get_type("region"), "STRING");
+        assert_eq!(get_type("units"), "INTEGER");
+        assert_eq!(get_type("price"), "NUMBER");
+    }
+}
+
+#[cfg(test)]
+mod glyph_query {
+    use super::test_helpers::*;
+    use super::*;
+    use crate::types::{GlyphChannel, SupportingField, SupportingFieldAggregation};
+
+    fn get_glyph_row(value: Value) -> GlyphQueryRow {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn get_query(filter: Option<Filter>) -> GlyphQuery {
+        let x = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "region",
+            "fieldDataType": 1,
+            "fieldDefinition": { "fieldType": "standard", "fieldName": "region" }
+        }))
+        .unwrap();
+        let y = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": { "fieldType": "standard", "fieldName": "product" }
+        }))
+        .unwrap();
+        let z = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "revenue",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "sum",
+                "accumulatedFieldDefinition": { "fieldType": "formula", "formula": "units * price" }
+            }
+        }))
+        .unwrap();
+        GlyphQuery {
+            table_name: "sales".to_string(),
+            x_field_definition: x,
+            y_field_definition: y,
+            z_field_definition: z,
+            channel_field_definitions: vec![],
+            supporting_fields: vec![],
+            filter,
+        }
+    }
+
+    #[tokio::test]
+    async fn is_ok() {
+        let directory = write_csv_table("glyph_engine_glyph_query_is_ok", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let query_id = data_source
+            .start_glyph_query(&get_query(None))
+            .await
+            .unwrap();
+        let status = data_source.get_glyph_query_status(&query_id).await.unwrap();
+        assert_eq!(status, AthenaQueryStatus::Succeeded);
+
+        let mut stream = data_source
+            .get_glyph_query_results(&query_id)
+            .await
+            .unwrap();
+        let mut rows = Vec::new();
+        while let Some(row) = stream.next().await.unwrap() {
+            rows.push(row);
+        }
+        assert_eq!(
+            rows,
+            vec![
+                get_glyph_row(json!({"rowids": "1|4", "x_region": "east", "y_product": "apple", "z_revenue": 11.5})),
+                get_glyph_row(json!({"rowids": "2", "x_region": "west", "y_product": "apple", "z_revenue": 7.5})),
+                get_glyph_row(json!({"rowids": "3", "x_region": "east", "y_product": "pear", "z_revenue": 4.5})),
+                get_glyph_row(json!({"rowids": "5", "x_region": "west", "y_product": "pear", "z_revenue": null})),
+            ]
+        );
+
+        //The results can only be read once
+        let result = data_source.get_glyph_query_results(&query_id).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn with_channels() {
+        let directory = write_csv_table("glyph_engine_glyph_query_channels", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let color = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "units",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "max",
+                "accumulatedFieldDefinition": { "fieldType": "standard", "fieldName": "units" }
+            }
+        }))
+        .unwrap();
+        let mut query = get_query(None);
+        query.channel_field_definitions = vec![(GlyphChannel::Color, color)];
+        let query_id = data_source.start_glyph_query(&query).await.unwrap();
+        let mut stream = data_source
+            .get_glyph_query_results(&query_id)
+            .await
+            .unwrap();
+        let row = stream.next().await.unwrap().unwrap();
+        assert_eq!(row.rowids, vec![1, 4]);
+        assert_eq!(row.columns["z_revenue"], 11.5);
+        assert_eq!(row.columns["color_units"].as_f64(), Some(4.0));
+    }
+
+    #[tokio::test]
+    async fn with_supporting_fields() {
+        let directory = write_csv_table("glyph_engine_glyph_query_supporting", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let units = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "units",
+            "fieldDataType": 2,
+            "fieldDefinition": { "fieldType": "standard", "fieldName": "units" }
+        }))
+        .unwrap();
+        let mut query = get_query(None);
+        query.supporting_fields = vec![
+            SupportingField {
+                field_definition: units.clone(),
+                aggregation: SupportingFieldAggregation::First,
+            },
+            SupportingField {
+                field_definition: FieldDefinition::from_json(&json!({
+                    "fieldDisplayName": "price",
+                    "fieldDataType": 0,
+                    "fieldDefinition": { "fieldType": "standard", "fieldName": "price" }
+                }))
+                .unwrap(),
+                aggregation: SupportingFieldAggregation::DistinctList,
+            },
+        ];
+        let query_id = data_source.start_glyph_query(&query).await.unwrap();
+        let mut stream = data_source
+            .get_glyph_query_results(&query_id)
+            .await
+            .unwrap();
+        let row = stream.next().await.unwrap().unwrap();
+        assert_eq!(row.rowids, vec![1, 4]);
+        assert_eq!(row.columns["supporting_units"], 3);
+        assert_eq!(row.columns["supporting_price"], "[1.5,1.75]");
+    }
+
+    #[tokio::test]
+    async fn channel_is_not_accumulated() {
+        let directory = write_csv_table("glyph_engine_glyph_query_bad_channel", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let mut query = get_query(None);
+        query.channel_field_definitions =
+            vec![(GlyphChannel::Size, query.x_field_definition.clone())];
+        let result = data_source.start_glyph_query(&query).await;
+        match result.err().unwrap() {
+            DataSourceError::UnsupportedQuery(_) => {}
+            _ => panic!("Expected UnsupportedQuery"),
+        }
+    }
+
+    #[tokio::test]
+    async fn filter_is_unsupported() {
+        let directory = write_csv_table("glyph_engine_glyph_query_filter", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let result = data_source
+            .start_glyph_query(&get_query(Some(Filter::Raw("units > 2".to_string()))))
+            .await;
+        match result.err().unwrap() {
+            DataSourceError::UnsupportedQuery(_) => {}
+            _ => panic!("Expected UnsupportedQuery"),
+        }
+    }
+
+    #[tokio::test]
+    async fn filter_expression() {
+        let directory = write_csv_table(
+            "glyph_engine_glyph_query_filter_expression",
+            "sales",
+            SALES_CSV,
+        );
+        let data_source = LocalFileDataSource::new(&directory);
+        let filter = FilterExpression::from_json(&json!({"or": [
+            {"fieldName": "units", "operator": ">", "value": 3},
+            {"fieldName": "product", "operator": "=", "value": "pear"}
+        ]}))
+        .unwrap();
+        let query_id = data_source
+            .start_glyph_query(&get_query(Some(Filter::Expression(filter))))
+            .await
+            .unwrap();
+        let mut stream = data_source
+            .get_glyph_query_results(&query_id)
+            .await
+            .unwrap();
+        let mut rows = Vec::new();
+        while let Some(row) = stream.next().await.unwrap() {
+            rows.push(row);
+        }
+        //Row 5 has no units, so units > 3 is unknown, but product = 'pear' keeps it.
+        assert_eq!(
+            rows,
+            vec![
+                get_glyph_row(json!({"rowids": "2", "x_region": "west", "y_product": "apple", "z_revenue": 7.5})),
+                get_glyph_row(json!({"rowids": "3", "x_region": "east", "y_product": "pear", "z_revenue": 4.5})),
+                get_glyph_row(json!({"rowids": "4", "x_region": "east", "y_product": "apple", "z_revenue": 7.0})),
+                get_glyph_row(json!({"rowids": "5", "x_region": "west", "y_product": "pear", "z_revenue": null})),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stopped() {
+        let directory = write_csv_table("glyph_engine_glyph_query_stopped", "sales", SALES_CSV);
+        let data_source = LocalFileDataSource::new(&directory);
+        let query_id = data_source
+            .start_glyph_query(&get_query(None))
+            .await
+            .unwrap();
+        assert!(data_source.stop_glyph_query(&query_id).await.is_ok());
+        let result = data_source.get_glyph_query_status(&query_id).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn unknown_query_id() {
+        let data_source = LocalFileDataSource::new(std::env::temp_dir());
+        let result = data_source.get_glyph_query_status("local-42").await;
+        match result.err().unwrap() {
+            DataSourceError::QueryNotFound(_) => {}
+            _ => panic!("Expected QueryNotFound"),
+        }
+    }
+}
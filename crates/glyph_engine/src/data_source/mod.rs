@@ -0,0 +1,191 @@
+mod athena_data_source;
+mod errors;
+mod field_evaluator;
+mod local_file_data_source;
+
+pub use athena_data_source::*;
+pub use errors::*;
+pub use local_file_data_source::*;
+
+use crate::types::vectorizer_parameters::{
+    BinnedFieldDefinition, FieldDefinition, Filter, StandardFieldDefinition,
+};
+use crate::types::{GlyphChannel, SupportingField};
+use glyphx_core::aws::{
+    athena_manager::{AthenaQueryStatus, ColumnDescription},
+    athena_stream_iterator::AthenaStreamIterator,
+};
+
+use async_trait::async_trait;
+use mockall::automock;
+use serde::{de, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+///Describes the distinct values that make up the vector table for a single axis.  When there is
+///a filter, only the rows that pass it contribute values.
+#[derive(Debug, Clone)]
+pub struct VectorQuery {
+    pub table_name: String,
+    pub field_definition: FieldDefinition,
+    pub filter: Option<Filter>,
+}
+
+///Describes the values that the edges of an equal width or quantile binned field are resolved
+///from, which are the values of its column at each of the field's quantiles.
+#[derive(Debug, Clone)]
+pub struct BinEdgesQuery {
+    pub table_name: String,
+    pub field_definition: BinnedFieldDefinition,
+}
+
+///Describes the values of a standard field's column that are kept by its top N, which are the
+///count values that rank highest by its accumulator.
+#[derive(Debug, Clone)]
+pub struct TopValuesQuery {
+    pub table_name: String,
+    pub field_definition: StandardFieldDefinition,
+}
+
+///Describes the main glyph query, which groups the rows of the table by the x and y values
+///and accumulates the z value, and the value of each mapped glyph channel, over each group.
+///Each supporting field is aggregated over the group as well.
+#[derive(Debug, Clone)]
+pub struct GlyphQuery {
+    pub table_name: String,
+    pub x_field_definition: FieldDefinition,
+    pub y_field_definition: FieldDefinition,
+    pub z_field_definition: FieldDefinition,
+    pub channel_field_definitions: Vec<(GlyphChannel, FieldDefinition)>,
+    pub supporting_fields: Vec<SupportingField>,
+    pub filter: Option<Filter>,
+}
+
+///A row returned by a glyph query.  The rowids come back as pipe delimited text and are decoded
+///into the ids of the rows that were grouped into the glyph.  The remaining columns are named
+///after their fields, x_{name}, y_{name} and z_{name}, plus a {channel}_{name} column, i.e.
+///color_{name}, for each mapped glyph channel, and a supporting_{name} column for each supporting
+///field, so they are kept by name.  A distinct_list supporting field is the JSON text of its
+///array of values.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GlyphQueryRow {
+    #[serde(deserialize_with = "deserialize_row_ids")]
+    pub rowids: Vec<usize>,
+    #[serde(flatten)]
+    pub columns: Map<String, Value>,
+}
+
+impl GlyphQueryRow {
+    pub fn get(&self, column_name: &str) -> Option<&Value> {
+        self.columns.get(column_name)
+    }
+}
+
+fn deserialize_row_ids<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<usize>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    let value = value.as_str().ok_or_else(|| {
+        de::Error::custom("The field rowids was not a string in the query results")
+    })?;
+    value
+        .split('|')
+        .map(|row_id| {
+            row_id.parse::<usize>().map_err(|_| {
+                de::Error::custom(format!(
+                    "The field rowids was not a pipe delimited string of integers, found {}",
+                    value
+                ))
+            })
+        })
+        .collect()
+}
+
+///Iterates over the rows returned by a glyph query.
+#[async_trait]
+pub trait RowStream: Send {
+    async fn next(&mut self) -> Result<Option<GlyphQueryRow>, DataSourceError>;
+}
+
+#[async_trait]
+impl RowStream for AthenaStreamIterator {
+    async fn next(&mut self) -> Result<Option<GlyphQueryRow>, DataSourceError> {
+        let result = self.next_as::<GlyphQueryRow>().await;
+        if result.is_err() {
+            return Err(DataSourceError::from_athena_stream_iterator_error(
+                result.err().unwrap(),
+            ));
+        }
+        Ok(result.unwrap())
+    }
+}
+
+///The source of the data that the GlyphEngine is processing.  The vector queries are small
+///and are run to completion, while the glyph query is started and then polled so that the
+///vectors can be built while it is running.
+#[automock]
+#[async_trait]
+pub trait DataSource: Send + Sync {
+    async fn get_table_columns(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDescription>, DataSourceError>;
+    async fn get_vector_values(&self, query: &VectorQuery) -> Result<Value, DataSourceError>;
+    async fn get_bin_edges(&self, query: &BinEdgesQuery) -> Result<Vec<f64>, DataSourceError>;
+    async fn get_top_values(&self, query: &TopValuesQuery) -> Result<Vec<String>, DataSourceError>;
+    async fn start_glyph_query(&self, query: &GlyphQuery) -> Result<String, DataSourceError>;
+    async fn get_glyph_query_status(
+        &self,
+        query_id: &str,
+    ) -> Result<AthenaQueryStatus, DataSourceError>;
+    ///Stops a glyph query that is still running, i.e. when the GlyphEngine has been cancelled.
+    async fn stop_glyph_query(&self, query_id: &str) -> Result<(), DataSourceError>;
+    async fn get_glyph_query_results(
+        &self,
+        query_id: &str,
+    ) -> Result<Box<dyn RowStream>, DataSourceError>;
+}
+
+#[cfg(test)]
+mod glyph_query_row {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn is_ok() {
+        let row: GlyphQueryRow = serde_json::from_value(json!({
+            "rowids": "1|2|3",
+            "x_field1": 4.0,
+            "z_field3": 12.0
+        }))
+        .unwrap();
+        assert_eq!(row.rowids, vec![1, 2, 3]);
+        assert_eq!(row.get("x_field1"), Some(&json!(4.0)));
+        assert_eq!(row.get("z_field3"), Some(&json!(12.0)));
+        assert!(row.get("rowids").is_none());
+    }
+
+    #[test]
+    fn rowids_is_missing() {
+        let result = serde_json::from_value::<GlyphQueryRow>(json!({
+            "rowids_fail": "1|2|3",
+            "x_field1": 4.0
+        }));
+        assert!(result.err().unwrap().to_string().contains("rowids"));
+    }
+
+    #[test]
+    fn rowids_is_not_a_string() {
+        let result = serde_json::from_value::<GlyphQueryRow>(json!({ "rowids": 123 }));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "The field rowids was not a string in the query results"
+        );
+    }
+
+    #[test]
+    fn rowids_is_malformed() {
+        let result = serde_json::from_value::<GlyphQueryRow>(json!({ "rowids": "1|a|2|3" }));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "The field rowids was not a pipe delimited string of integers, found 1|a|2|3"
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{json, to_value};
+use std::time::Duration;
 
-use crate::types::vectorizer_parameters::GetFieldDefinitionError;
-use crate::vector_processer::TaskStatus;
+use crate::data_source::DataSourceError;
+use crate::types::vectorizer_parameters::{FilterValidateError, GetFieldDefinitionError};
+use crate::vector_processer::{TaskStatus, VectorCalculationError};
 
 use glyphx_core::{
     aws::{
@@ -21,9 +23,30 @@ pub enum GlyphEngineProcessError {
     VectorProcessingError(GlyphxErrorData),
     QueryProcessingError(GlyphxErrorData),
     DataProcessingError(GlyphxErrorData),
+    Cancelled(GlyphxErrorData),
+    QueryTimedOut(GlyphxErrorData),
 }
 
 impl GlyphEngineProcessError {
+    ///Returned by process when the GlyphEngine has been cancelled before it could finish.
+    pub fn cancelled() -> Self {
+        let message = "The GlyphEngine was cancelled before it could finish processing".to_string();
+        let error_data = GlyphxErrorData::new(message, None, None);
+        Self::Cancelled(error_data)
+    }
+
+    ///Returned by process when the glyph query has not finished within the max wait of our
+    ///polling policy.
+    pub fn query_timed_out(query_id: &str, max_wait: Duration) -> Self {
+        let message = format!(
+            "The query did not complete within {} seconds",
+            max_wait.as_secs()
+        );
+        let data = json!({ "query_id": query_id, "max_wait": max_wait.as_secs() });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::QueryTimedOut(error_data)
+    }
+
     pub fn from_get_field_definition_error(
         error: GetFieldDefinitionError,
         axis_name: &str,
@@ -36,12 +59,27 @@ impl GlyphEngineProcessError {
         Self::ConfigurationError(error_data)
     }
 
+    pub fn from_filter_validate_error(error: FilterValidateError, table_name: &str) -> Self {
+        let message = format!(
+            "The filter does not match the columns of the table {}, see the inner error for additional information",
+            table_name
+        );
+        let data = json!({ "table_name": table_name });
+        let inner_error = to_value(error.get_glyphx_error_data()).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::ConfigurationError(error_data)
+    }
+
     pub fn from_task_status_error(task_status: TaskStatus, axis_name: &str) -> Self {
         let inner_error = match task_status {
             TaskStatus::Errored(error) => error,
             _ => panic!("TaskStatus is not an error"),
         };
 
+        if let VectorCalculationError::Cancelled(_) = inner_error {
+            return Self::cancelled();
+        }
+
         let message = format!("An Error occurred while processing the vectors for axis :  {}.  See the inner error for additional information", axis_name);
         let data = json!({ "axis_name": axis_name });
 
@@ -82,6 +120,20 @@ impl GlyphEngineProcessError {
         Self::DataProcessingError(error_data)
     }
 
+    pub fn from_data_source_error(error: DataSourceError) -> Self {
+        let message = "An error occurred while querying the data source, see the inner error for additional information".to_string();
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, None, Some(inner_error));
+        Self::QueryProcessingError(error_data)
+    }
+
+    pub fn from_row_stream_error(error: DataSourceError) -> Self {
+        let message = "An error occurred while iterating the query results, see the inner error for additional information".to_string();
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new(message, None, Some(inner_error));
+        Self::DataProcessingError(error_data)
+    }
+
     pub fn from_get_upload_stream_error(error: GetUploadStreamError, file_name: &str) -> Self {
         let message = "An error occurred while getting the upload stream, see the inner error for additional information ".to_string();
         let data = Some(json!({ file_name: file_name }));
@@ -0,0 +1,328 @@
+//! The layout of the .vec, .gly, .sts and .sup files that the engine writes.  A file is:
+//!
+//! * MAGIC followed by the FORMAT_VERSION (a u32), the size of the header (a u64) and the bincode
+//!   encoded FileHeader.
+//! * Any number of records, where every record is its bincode encoded size (a u64) followed by the
+//!   bincode encoded record.
+//! * FOOTER_MARKER followed by the bincode encoded FileFooter, which holds the number of records
+//!   and a crc32 of every record byte (size prefixes included).
+//!
+//! The record count and checksum live in a footer, not in the header, because we stream records to
+//! S3 and do not know them until the last record has been written.  By then the UploadStream has
+//! pushed the start of a large file to S3, 5MB at a time, and an ObjectUploadStream can only be
+//! appended to, so the header can not be back-patched when the file is finished.
+//!
+//! Files written before the header was introduced are just the records, and MAGIC can never be
+//! mistaken for the size prefix of their first record, so the readers can still read them.
+use crate::types::vectorizer_parameters::FieldDefinition;
+use crate::types::{FieldType, Glyph, GlyphV1, Stats, SupportingValues};
+use crate::vector_processer::{Vector, VectorV2};
+
+use bincode::{serialize, Options};
+use crc32fast::Hasher;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+///The first 8 bytes of every file that has a header.  Read as a size prefix it is far larger than
+///any record that we write.
+pub const MAGIC: [u8; 8] = *b"GLYPHXBN";
+
+///The version of the header and record layout that this build writes.  Bump this whenever Glyph,
+///Stats, Vector or FileHeader change shape so that readers can reject files they do not
+///understand instead of mis-reading them.
+///
+///* 1 - The first version with a header.
+///* 2 - Glyphs carry the optional color, size and shape channel values.
+///* 3 - Vectors carry an optional display label.
+pub const FORMAT_VERSION: u32 = 3;
+
+///The version that we read legacy files, which have no header, as.
+pub const LEGACY_FORMAT_VERSION: u32 = 1;
+
+///Written in place of a size prefix to mark the start of the footer.
+pub const FOOTER_MARKER: u64 = u64::MAX;
+
+///Identifies which of our files a header belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FileType {
+    Vector,
+    Glyph,
+    Stats,
+    Supporting,
+}
+
+///Describes one of the fields that went into a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeaderField {
+    pub axis: String,
+    pub field_display_name: String,
+    pub field_type: FieldType,
+}
+
+impl HeaderField {
+    pub fn new(axis: &str, field_definition: &FieldDefinition) -> Self {
+        Self {
+            axis: axis.to_string(),
+            field_display_name: field_definition.get_field_display_name().to_string(),
+            field_type: field_definition.get_field_type(),
+        }
+    }
+}
+
+///Describes the records that follow it.  The record count and checksum are not known when the
+///header is written, so they are in the FileFooter instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHeader {
+    pub file_type: FileType,
+    pub model_hash: String,
+    pub fields: Vec<HeaderField>,
+}
+
+impl FileHeader {
+    pub fn new(file_type: FileType, model_hash: &str, fields: Vec<HeaderField>) -> Self {
+        Self {
+            file_type,
+            model_hash: model_hash.to_string(),
+            fields,
+        }
+    }
+
+    ///Returns the bytes that start a file: MAGIC, FORMAT_VERSION, the header size and the header.
+    pub fn encode(&self) -> Vec<u8> {
+        let header = serialize(self).unwrap();
+        let mut bytes: Vec<u8> = Vec::with_capacity(8 + 4 + 8 + header.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.append(serialize(&FORMAT_VERSION).unwrap().as_mut());
+        bytes.append(serialize(&(header.len() as u64)).unwrap().as_mut());
+        bytes.extend_from_slice(&header);
+        bytes
+    }
+}
+
+///Follows the last record of a file that has a header.  It holds what we only know once every
+///record has been written: the number of records and the crc32 of their bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileFooter {
+    pub record_count: u64,
+    pub checksum: u32,
+}
+
+impl FileFooter {
+    ///The number of bytes that follow FOOTER_MARKER.
+    pub const BINARY_SIZE: usize = 12;
+
+    ///Returns the bytes that end a file: FOOTER_MARKER followed by the footer.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(8 + Self::BINARY_SIZE);
+        bytes.append(serialize(&FOOTER_MARKER).unwrap().as_mut());
+        bytes.append(serialize(self).unwrap().as_mut());
+        bytes
+    }
+}
+
+///A record that can be written to and read from one of our binary files.  get_binary_size must
+///return the number of bytes that bincode encodes the record to, which is what we write as its
+///size prefix.
+pub trait BinaryRecord: Serialize + DeserializeOwned {
+    const FILE_TYPE: FileType;
+    fn get_binary_size(&self) -> usize;
+
+    ///Deserializes a record that was written by the given format version.  Records whose layout
+    ///has changed override this to read their older layouts.
+    fn deserialize_version(bytes: &[u8], _format_version: u32) -> bincode::Result<Self> {
+        deserialize_exact(bytes)
+    }
+}
+
+///Deserializes bytes into T, failing if T does not account for all of them.
+pub fn deserialize_exact<T: DeserializeOwned>(bytes: &[u8]) -> bincode::Result<T> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+}
+
+impl BinaryRecord for Vector {
+    const FILE_TYPE: FileType = FileType::Vector;
+    fn get_binary_size(&self) -> usize {
+        Vector::get_binary_size(self)
+    }
+
+    fn deserialize_version(bytes: &[u8], format_version: u32) -> bincode::Result<Self> {
+        if format_version < 3 {
+            let vector = deserialize_exact::<VectorV2>(bytes);
+            if vector.is_err() {
+                return Err(vector.err().unwrap());
+            }
+            return Ok(Vector::from(vector.unwrap()));
+        }
+        deserialize_exact(bytes)
+    }
+}
+
+impl BinaryRecord for Glyph {
+    const FILE_TYPE: FileType = FileType::Glyph;
+    fn get_binary_size(&self) -> usize {
+        Glyph::get_binary_size(self)
+    }
+
+    fn deserialize_version(bytes: &[u8], format_version: u32) -> bincode::Result<Self> {
+        if format_version < 2 {
+            let glyph = deserialize_exact::<GlyphV1>(bytes);
+            if glyph.is_err() {
+                return Err(glyph.err().unwrap());
+            }
+            return Ok(Glyph::from(glyph.unwrap()));
+        }
+        deserialize_exact(bytes)
+    }
+}
+
+impl BinaryRecord for Stats {
+    const FILE_TYPE: FileType = FileType::Stats;
+    fn get_binary_size(&self) -> usize {
+        Stats::get_binary_size(self)
+    }
+}
+
+impl BinaryRecord for SupportingValues {
+    const FILE_TYPE: FileType = FileType::Supporting;
+    fn get_binary_size(&self) -> usize {
+        SupportingValues::get_binary_size(self)
+    }
+}
+
+///Serializes records with their size prefix while keeping the count and checksum that go into
+///the footer.
+#[derive(Default)]
+pub struct RecordEncoder {
+    record_count: u64,
+    hasher: Hasher,
+}
+
+impl RecordEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encode_record<T: BinaryRecord>(&mut self, record: &T) -> Vec<u8> {
+        let binary_size = record.get_binary_size();
+        let mut bytes: Vec<u8> = Vec::with_capacity(8 + binary_size);
+        //We are including the size of the record so that when we read the file back in we
+        //will be able to determine the size of each record.
+        bytes.append(serialize(&binary_size).unwrap().as_mut());
+        bytes.append(serialize(record).unwrap().as_mut());
+        self.hasher.update(&bytes);
+        self.record_count += 1;
+        bytes
+    }
+
+    pub fn get_footer(&self) -> FileFooter {
+        FileFooter {
+            record_count: self.record_count,
+            checksum: self.hasher.clone().finalize(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod encode {
+    use super::*;
+    use bincode::deserialize;
+
+    #[test]
+    fn header() {
+        let header = FileHeader::new(FileType::Glyph, "hash", vec![]);
+        let bytes = header.encode();
+        assert_eq!(&bytes[0..8], &MAGIC);
+        assert_eq!(deserialize::<u32>(&bytes[8..12]).unwrap(), FORMAT_VERSION);
+        let size = deserialize::<u64>(&bytes[12..20]).unwrap();
+        assert_eq!(size as usize, bytes.len() - 20);
+        assert_eq!(deserialize::<FileHeader>(&bytes[20..]).unwrap(), header);
+    }
+
+    #[test]
+    fn magic_is_not_a_valid_size() {
+        let size: u64 = deserialize(&MAGIC).unwrap();
+        assert!(size > u32::MAX as u64);
+    }
+
+    #[test]
+    fn footer() {
+        let footer = FileFooter {
+            record_count: 3,
+            checksum: 42,
+        };
+        let bytes = footer.encode();
+        assert_eq!(bytes.len(), 8 + FileFooter::BINARY_SIZE);
+        assert_eq!(deserialize::<u64>(&bytes[0..8]).unwrap(), FOOTER_MARKER);
+        assert_eq!(deserialize::<FileFooter>(&bytes[8..]).unwrap(), footer);
+    }
+
+    #[test]
+    fn records() {
+        let mut encoder = RecordEncoder::new();
+        let first = encoder.encode_record(&Glyph::new(1.0, 2.0, 3.0, vec![1]));
+        let second = encoder.encode_record(&Glyph::new(4.0, 5.0, 6.0, vec![2, 3]));
+        assert_eq!(first.len(), 8 + 43);
+        assert_eq!(second.len(), 8 + 51);
+
+        let mut hasher = Hasher::new();
+        hasher.update(&first);
+        hasher.update(&second);
+        let footer = encoder.get_footer();
+        assert_eq!(footer.record_count, 2);
+        assert_eq!(footer.checksum, hasher.finalize());
+    }
+}
+
+#[cfg(test)]
+mod deserialize_version {
+    use super::*;
+    use crate::vector_processer::VectorOrigionalValue;
+
+    #[test]
+    fn reads_v1_glyph() {
+        let glyph = GlyphV1 {
+            x_value: 1.0,
+            y_value: 2.0,
+            z_value: 3.0,
+            row_ids: vec![1, 2],
+        };
+        let bytes = serialize(&glyph).unwrap();
+        let glyph = Glyph::deserialize_version(&bytes, 1).unwrap();
+        assert_eq!(glyph.row_ids, vec![1, 2]);
+        assert!(glyph.size_value.is_none());
+        assert!(Glyph::deserialize_version(&bytes, FORMAT_VERSION).is_err());
+    }
+
+    #[test]
+    fn reads_v2_vector() {
+        let vector = VectorV2 {
+            orig_value: VectorOrigionalValue::U64(20231),
+            vector: 1.0,
+            rank: 2,
+        };
+        let bytes = serialize(&vector).unwrap();
+        let vector = Vector::deserialize_version(&bytes, 2).unwrap();
+        assert_eq!(vector.orig_value, VectorOrigionalValue::U64(20231));
+        assert_eq!(vector.rank, 2);
+        assert!(vector.label.is_none());
+        assert!(Vector::deserialize_version(&bytes, FORMAT_VERSION).is_err());
+    }
+
+    #[test]
+    fn reads_labeled_vector() {
+        let vector = Vector::new(VectorOrigionalValue::U64(20231), 1.0, 2)
+            .with_label(Some("2023-Q1".to_string()));
+        let bytes = serialize(&vector).unwrap();
+        let vector = Vector::deserialize_version(&bytes, FORMAT_VERSION).unwrap();
+        assert_eq!(vector.label.unwrap(), "2023-Q1");
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = serialize(&Glyph::new(1.0, 2.0, 3.0, vec![1])).unwrap();
+        bytes.push(0);
+        assert!(Glyph::deserialize_version(&bytes, FORMAT_VERSION).is_err());
+    }
+}
@@ -1,22 +1,30 @@
 #[macro_use]
 pub mod macros;
+pub mod data_source;
 pub mod errors;
+pub mod file_format;
+pub mod readers;
 pub mod types;
 pub mod vector_processer;
 
 use crate::GlyphEngineResults;
+use data_source::{
+    AthenaDataSource, BinEdgesQuery, DataSource, GlyphQuery, GlyphQueryRow, RowStream,
+    TopValuesQuery,
+};
+use file_format::{FileHeader, FileType, HeaderField, RecordEncoder};
 use glyphx_common::{AthenaConnection, Heartbeat, S3Connection};
 
 use glyphx_core::{
     aws::{
-        athena_manager::AthenaQueryStatus,
-        athena_stream_iterator::AthenaStreamIterator,
+        athena_manager::{AthenaQueryStatus, ColumnDescription, PollingPolicy},
         s3_manager::GetUploadStreamError,
-        upload_stream::{UploadStream, UploadStreamFinishError, UploadStreamWriteError},
+        upload_stream::{UploadStreamFinishError, UploadStreamWriteError},
+        ObjectStore, ObjectUploadStream,
     },
     error,
     utility_functions::file_functions::{
-        get_glyph_file_name, get_stats_file_name, get_vector_file_name,
+        get_glyph_file_name, get_stats_file_name, get_supporting_file_name, get_vector_file_name,
     },
     ErrorTypeParser, GlyphxErrorData, Singleton,
 };
@@ -26,15 +34,19 @@ use glyphx_database::{
 };
 
 use async_trait::async_trait;
-use bincode::serialize;
 use bson::{doc, DateTime};
 use im::OrdSet;
 use mockall::automock;
 use serde_json::{to_value, Value};
 use statrs::statistics::*;
+use std::sync::Arc;
+use std::time::Duration;
+pub use tokio_util::sync::CancellationToken;
 
 pub use errors::*;
-use types::vectorizer_parameters::{FieldDefinition, VectorizerParameters};
+use types::vectorizer_parameters::{
+    FieldDefinition, Filter, ValidationProblem, VectorizerParameters,
+};
 pub use types::*;
 
 use vector_processer::{
@@ -85,39 +97,66 @@ trait GlyphEngineOperations {
         data_table_name: &str,
         field_definition: &FieldDefinition,
         output_file_name: &str,
+        model_hash: &str,
+        filter: &Option<Filter>,
+        data_source: Arc<dyn DataSource>,
+        object_store: Arc<dyn ObjectStore>,
+        cancellation_token: &CancellationToken,
     ) -> Box<dyn VectorValueProcesser>;
-    async fn start_athena_query(
+    async fn get_bin_edges(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &BinEdgesQuery,
+    ) -> Result<Vec<f64>, GlyphEngineProcessError>;
+    async fn get_top_values(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &TopValuesQuery,
+    ) -> Result<Vec<String>, GlyphEngineProcessError>;
+    async fn get_table_columns(
         &self,
-        athena_connection: &AthenaConnection,
-        query: &str,
+        data_source: Arc<dyn DataSource>,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDescription>, GlyphEngineProcessError>;
+    async fn start_glyph_query(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &GlyphQuery,
     ) -> Result<String, GlyphEngineProcessError>;
     async fn check_query_status(
         &self,
-        athena_connection: &AthenaConnection,
+        data_source: Arc<dyn DataSource>,
         query_id: &str,
     ) -> Result<AthenaQueryStatus, GlyphEngineProcessError>;
+    async fn stop_glyph_query(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query_id: &str,
+    ) -> Result<(), GlyphEngineProcessError>;
     async fn get_query_results(
         &self,
         query_id: &str,
-        athena_connection: &AthenaConnection,
-    ) -> Result<AthenaStreamIterator, GlyphEngineProcessError>;
+        data_source: Arc<dyn DataSource>,
+    ) -> Result<Box<dyn RowStream>, GlyphEngineProcessError>;
     async fn get_upload_stream(
         &self,
         file_name: &str,
-        s3_connection: &S3Connection,
-    ) -> Result<UploadStream, GetUploadStreamError>;
+        object_store: Arc<dyn ObjectStore>,
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError>;
 
     async fn write_to_upload_stream(
         &self,
-        upload_stream: &mut UploadStream,
+        upload_stream: &mut Box<dyn ObjectUploadStream>,
         bytes: Option<Vec<u8>>,
     ) -> Result<(), UploadStreamWriteError>;
 
     async fn finish_upload_stream(
         &self,
-        upload_stream: &mut UploadStream,
+        upload_stream: &mut Box<dyn ObjectUploadStream>,
     ) -> Result<(), UploadStreamFinishError>;
 
+    async fn abort_upload_stream(&self, upload_stream: &mut Box<dyn ObjectUploadStream>);
+
     async fn add_process_tracking_error(
         &self,
         process_id: &str,
@@ -137,6 +176,11 @@ struct GlyphEngineOperationsImpl;
 #[async_trait]
 impl GlyphEngineOperations for GlyphEngineOperationsImpl {
     async fn build_s3_connection(&self) -> Result<&'static S3Connection, GlyphEngineInitError> {
+        //If the caller has installed a local S3Connection via S3Connection::build_local_singleton,
+        //use it rather than binding a new connection to our S3 secret.
+        if let Some(s3_connection) = S3Connection::get_local_instance() {
+            return Ok(s3_connection);
+        }
         handle_error!(let s3_connection = S3Connection::build_singleton().await; GlyphEngineInitError);
         Ok(s3_connection)
     }
@@ -164,31 +208,71 @@ impl GlyphEngineOperations for GlyphEngineOperationsImpl {
         data_table_name: &str,
         field_definition: &FieldDefinition,
         output_file_name: &str,
+        model_hash: &str,
+        filter: &Option<Filter>,
+        data_source: Arc<dyn DataSource>,
+        object_store: Arc<dyn ObjectStore>,
+        cancellation_token: &CancellationToken,
     ) -> Box<dyn VectorValueProcesser> {
         let field_processor = VectorProcesser::new(
             axis,
             data_table_name,
             output_file_name,
+            model_hash,
             field_definition.clone(),
-        );
+            data_source,
+            object_store,
+        )
+        .with_filter(filter.clone())
+        .with_cancellation_token(cancellation_token.clone());
         Box::new(field_processor)
     }
 
-    async fn start_athena_query(
+    async fn get_bin_edges(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &BinEdgesQuery,
+    ) -> Result<Vec<f64>, GlyphEngineProcessError> {
+        handle_error!(let edges = data_source.get_bin_edges(query).await; GlyphEngineProcessError::from_data_source_error(), error);
+
+        Ok(edges)
+    }
+
+    async fn get_top_values(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &TopValuesQuery,
+    ) -> Result<Vec<String>, GlyphEngineProcessError> {
+        handle_error!(let values = data_source.get_top_values(query).await; GlyphEngineProcessError::from_data_source_error(), error);
+
+        Ok(values)
+    }
+
+    async fn get_table_columns(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDescription>, GlyphEngineProcessError> {
+        handle_error!(let columns = data_source.get_table_columns(table_name).await; GlyphEngineProcessError::from_data_source_error(), error);
+
+        Ok(columns)
+    }
+
+    async fn start_glyph_query(
         &self,
-        athena_connection: &AthenaConnection,
-        query: &str,
+        data_source: Arc<dyn DataSource>,
+        query: &GlyphQuery,
     ) -> Result<String, GlyphEngineProcessError> {
-        handle_error!(let query_id = athena_connection .get_athena_manager() .start_query(&query, None) .await; GlyphEngineProcessError::from_start_query_error(&query), error);
+        handle_error!(let query_id = data_source.start_glyph_query(query).await; GlyphEngineProcessError::from_data_source_error(), error);
 
         Ok(query_id)
     }
     async fn check_query_status(
         &self,
-        athena_connection: &AthenaConnection,
+        data_source: Arc<dyn DataSource>,
         query_id: &str,
     ) -> Result<AthenaQueryStatus, GlyphEngineProcessError> {
-        handle_error!(let query_status = athena_connection.get_athena_manager().get_query_status(query_id).await; GlyphEngineProcessError::from_get_query_status_error(&query_id), error);
+        handle_error!(let query_status = data_source.get_glyph_query_status(query_id).await; GlyphEngineProcessError::from_data_source_error(), error);
 
         match query_status {
             AthenaQueryStatus::Failed(error) => {
@@ -217,46 +301,42 @@ impl GlyphEngineOperations for GlyphEngineOperationsImpl {
         Ok(query_status)
     }
 
+    async fn stop_glyph_query(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query_id: &str,
+    ) -> Result<(), GlyphEngineProcessError> {
+        handle_error!(let _result = data_source.stop_glyph_query(query_id).await; GlyphEngineProcessError::from_data_source_error(), error);
+
+        Ok(())
+    }
+
     async fn get_query_results(
         &self,
         query_id: &str,
-        athena_connection: &AthenaConnection,
-    ) -> Result<AthenaStreamIterator, GlyphEngineProcessError> {
-        let results = athena_connection
-            .get_athena_manager()
-            .get_paged_query_results(query_id, Some(1000))
-            .await;
-        if results.is_err() {
-            let error = results.err().unwrap();
-            let error = GlyphEngineProcessError::from_get_query_pager_error(error, query_id);
+        data_source: Arc<dyn DataSource>,
+    ) -> Result<Box<dyn RowStream>, GlyphEngineProcessError> {
+        let iterator = data_source.get_glyph_query_results(query_id).await;
+        if iterator.is_err() {
+            let error = iterator.err().unwrap();
+            let error = GlyphEngineProcessError::from_data_source_error(error);
             error.error();
             return Err(error);
         }
-        let results = Box::new(results.unwrap());
-
-        let iterator = AthenaStreamIterator::new(
-            results,
-            query_id,
-            "foo",
-            athena_connection.get_database_name(),
-        );
-        Ok(iterator)
+        Ok(iterator.unwrap())
     }
 
     async fn get_upload_stream(
         &self,
         file_name: &str,
-        s3_connection: &S3Connection,
-    ) -> Result<UploadStream, GetUploadStreamError> {
-        s3_connection
-            .get_s3_manager()
-            .get_upload_stream(file_name)
-            .await
+        object_store: Arc<dyn ObjectStore>,
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
+        object_store.get_upload_stream(file_name).await
     }
 
     async fn write_to_upload_stream(
         &self,
-        upload_stream: &mut UploadStream,
+        upload_stream: &mut Box<dyn ObjectUploadStream>,
         bytes: Option<Vec<u8>>,
     ) -> Result<(), UploadStreamWriteError> {
         upload_stream.write(bytes).await
@@ -264,11 +344,15 @@ impl GlyphEngineOperations for GlyphEngineOperationsImpl {
 
     async fn finish_upload_stream(
         &self,
-        upload_stream: &mut UploadStream,
+        upload_stream: &mut Box<dyn ObjectUploadStream>,
     ) -> Result<(), UploadStreamFinishError> {
         upload_stream.finish().await
     }
 
+    async fn abort_upload_stream(&self, upload_stream: &mut Box<dyn ObjectUploadStream>) {
+        upload_stream.abort().await
+    }
+
     async fn add_process_tracking_error(
         &self,
         process_id: &str,
@@ -314,11 +398,17 @@ impl GlyphEngineOperations for GlyphEngineOperationsImpl {
     }
 }
 
+///Glyph queries read the whole table, so we wait for them as long as Athena will let a query run,
+///rather than the minute that AthenaManager::run_query waits by default.
+const GLYPH_QUERY_MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
 pub struct GlyphEngine {
     parameters: VectorizerParameters,
-    heartbeat: Heartbeat,
-    athena_connection: &'static AthenaConnection,
-    s3_connection: &'static S3Connection,
+    heartbeat: Option<Heartbeat>,
+    data_source: Arc<dyn DataSource>,
+    object_store: Arc<dyn ObjectStore>,
+    cancellation_token: CancellationToken,
+    polling_policy: PollingPolicy,
 }
 
 impl GlyphEngine {
@@ -329,18 +419,101 @@ impl GlyphEngine {
         Self::new_impl(parameters, &GlyphEngineOperationsImpl).await
     }
 
+    ///Builds a GlyphEngine that reads its data from the supplied data source instead of Athena,
+    ///i.e. a LocalFileDataSource, and writes its files to the supplied object store, i.e. a
+    ///LocalObjectStore.  This is a local run, so no AWS or MongoDb connections are created, there
+    ///is no heartbeat and process does not update a process tracking document.
+    pub async fn new_with_data_source(
+        parameters: &VectorizerParameters,
+        data_source: Arc<dyn DataSource>,
+        object_store: Arc<dyn ObjectStore>,
+    ) -> Result<GlyphEngine, GlyphEngineInitError> {
+        Ok(Self::build(parameters, None, data_source, object_store))
+    }
+
+    ///Validates a model definition before a GlyphEngine is built for it, see
+    ///VectorizerParameters::validate.  When a data source is supplied the columns of the data
+    ///table are read from it so that the columns that the model reads are checked as well.
+    pub async fn validate_parameters(
+        input: &Value,
+        data_source: Option<Arc<dyn DataSource>>,
+    ) -> Vec<ValidationProblem> {
+        Self::validate_parameters_impl(input, data_source, &GlyphEngineOperationsImpl).await
+    }
+
+    async fn validate_parameters_impl<T: GlyphEngineOperations>(
+        input: &Value,
+        data_source: Option<Arc<dyn DataSource>>,
+        operations: &T,
+    ) -> Vec<ValidationProblem> {
+        let (data_source, table_name) = match (data_source, input["data_table_name"].as_str()) {
+            (Some(data_source), Some(table_name)) => (data_source, table_name),
+            _ => return VectorizerParameters::validate(input, None),
+        };
+        let columns = operations.get_table_columns(data_source, table_name).await;
+        if columns.is_err() {
+            let err = columns.err().unwrap();
+            let error_data = err.get_glyphx_error_data();
+            let mut problems = VectorizerParameters::validate(input, None);
+            problems.push(ValidationProblem::new(
+                "data_table_name",
+                &error_data.message,
+                error_data.data.clone(),
+            ));
+            return problems;
+        }
+        VectorizerParameters::validate(input, Some(&columns.unwrap()))
+    }
+
     async fn new_impl<T: GlyphEngineOperations>(
         parameters: &VectorizerParameters,
         operations: &T,
     ) -> Result<GlyphEngine, GlyphEngineInitError> {
-        let (heartbeat, s3_connection, athena_connection, _mongo_connection) =
+        let (heartbeat, object_store, data_source, _mongo_connection) =
             Self::init(operations).await?;
-        Ok(GlyphEngine {
+        Ok(Self::build(
+            parameters,
+            Some(heartbeat),
+            data_source,
+            object_store,
+        ))
+    }
+
+    fn build(
+        parameters: &VectorizerParameters,
+        heartbeat: Option<Heartbeat>,
+        data_source: Arc<dyn DataSource>,
+        object_store: Arc<dyn ObjectStore>,
+    ) -> GlyphEngine {
+        GlyphEngine {
             parameters: parameters.clone(),
             heartbeat,
-            s3_connection,
-            athena_connection,
-        })
+            data_source,
+            object_store,
+            cancellation_token: CancellationToken::new(),
+            polling_policy: PollingPolicy::default().with_max_wait(GLYPH_QUERY_MAX_WAIT),
+        }
+    }
+
+    ///Returns a token that can be used to cancel process from another task.  Cancelling stops
+    ///the glyph query, aborts any files that are still being written and completes the process
+    ///tracking document with a status of Cancelled.
+    pub fn get_cancellation_token(&self) -> CancellationToken {
+        self.cancellation_token.clone()
+    }
+
+    ///Replaces our cancellation token, i.e. with one that was handed out before the GlyphEngine
+    ///was built.
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    ///Sets how often process checks on the glyph query and how long it waits for it.  Past the
+    ///max wait the query is stopped and process returns a QueryTimedOut error.
+    pub fn with_polling_policy(mut self, polling_policy: PollingPolicy) -> Self {
+        self.polling_policy = polling_policy;
+        self
     }
 
     async fn init<T: GlyphEngineOperations>(
@@ -348,20 +521,21 @@ impl GlyphEngine {
     ) -> Result<
         (
             Heartbeat,
-            &'static S3Connection,
-            &'static AthenaConnection,
+            Arc<dyn ObjectStore>,
+            Arc<dyn DataSource>,
             &'static MongoDbConnection,
         ),
         GlyphEngineInitError,
     > {
         pass_error!(let s3_connection = operations.build_s3_connection().await; error);
         pass_error!(let athena_connection = operations.build_athena_connection().await; error);
+        let data_source: Arc<dyn DataSource> = Arc::new(AthenaDataSource::new(athena_connection));
         pass_error!(let mongo_db_connection = operations.build_mongo_connection().await; error);
         pass_error!(let heartbeat = operations.build_heartbeat().await; error);
         Ok((
             heartbeat,
-            s3_connection,
-            athena_connection,
+            s3_connection.get_shared_object_store(),
+            data_source,
             mongo_db_connection,
         ))
     }
@@ -420,11 +594,17 @@ impl GlyphEngine {
         (Box<dyn VectorValueProcesser>, Box<dyn VectorValueProcesser>),
         GlyphEngineProcessError,
     > {
+        let vector_filter = self.parameters.get_vector_filter();
         let mut x_field_processor = operations.get_vector_processer(
             "x",
             &self.parameters.data_table_name,
             &x_field_definition,
             &x_file_name,
+            &self.parameters.model_hash,
+            &vector_filter,
+            self.data_source.clone(),
+            self.object_store.clone(),
+            &self.cancellation_token,
         );
 
         let mut y_field_processor = operations.get_vector_processer(
@@ -432,6 +612,11 @@ impl GlyphEngine {
             &self.parameters.data_table_name,
             &y_field_definition,
             &y_file_name,
+            &self.parameters.model_hash,
+            &vector_filter,
+            self.data_source.clone(),
+            self.object_store.clone(),
+            &self.cancellation_token,
         );
 
         let task_status = x_field_processor.run_sync().await;
@@ -457,60 +642,105 @@ impl GlyphEngine {
         Ok((x_field_processor, y_field_processor))
     }
 
+    ///Equal width and quantile bins depend on the data, so their edges are resolved once, up front,
+    ///and the resolved field is used for both the vector query and the glyph query so that the
+    ///two agree on the bins.
+    async fn resolve_bin_edges<T: GlyphEngineOperations>(
+        &self,
+        field_definition: FieldDefinition,
+        operations: &T,
+    ) -> Result<FieldDefinition, GlyphEngineProcessError> {
+        let binned_field_definition = match field_definition.get_binned_field_definition() {
+            Some(binned_field_definition) if !binned_field_definition.is_resolved() => {
+                binned_field_definition.clone()
+            }
+            _ => return Ok(field_definition),
+        };
+        let query = BinEdgesQuery {
+            table_name: self.parameters.data_table_name.clone(),
+            field_definition: binned_field_definition,
+        };
+        let edges = operations
+            .get_bin_edges(self.data_source.clone(), &query)
+            .await?;
+        Ok(field_definition.with_bin_edges(edges))
+    }
+
+    ///Like bin edges, the values that a top N keeps are resolved once, up front, so that the
+    ///vector query and the glyph query put the same values in the other bucket.
+    async fn resolve_top_values<T: GlyphEngineOperations>(
+        &self,
+        field_definition: FieldDefinition,
+        operations: &T,
+    ) -> Result<FieldDefinition, GlyphEngineProcessError> {
+        let standard_field_definition = match field_definition.get_standard_field_definition() {
+            Some(standard_field_definition) if standard_field_definition.needs_top_values() => {
+                standard_field_definition.clone()
+            }
+            _ => return Ok(field_definition),
+        };
+        let query = TopValuesQuery {
+            table_name: self.parameters.data_table_name.clone(),
+            field_definition: standard_field_definition,
+        };
+        let values = operations
+            .get_top_values(self.data_source.clone(), &query)
+            .await?;
+        Ok(field_definition.with_top_values(values))
+    }
+
+    ///A filter expression is checked against the columns of the table before anything is run so
+    ///that a bad column or value is reported as a configuration error, not a failed query.
+    async fn validate_filter<T: GlyphEngineOperations>(
+        &self,
+        operations: &T,
+    ) -> Result<(), GlyphEngineProcessError> {
+        let filter = match &self.parameters.filter {
+            Some(filter @ Filter::Expression(_)) => filter,
+            _ => return Ok(()),
+        };
+        let table_name = &self.parameters.data_table_name;
+        let columns = operations
+            .get_table_columns(self.data_source.clone(), table_name)
+            .await?;
+        let result = filter.validate(&columns);
+        if result.is_err() {
+            return Err(GlyphEngineProcessError::from_filter_validate_error(
+                result.err().unwrap(),
+                table_name,
+            ));
+        }
+        Ok(())
+    }
+
     async fn start_query<T: GlyphEngineOperations>(
         &self,
         x_axis_definition: &FieldDefinition,
         y_axis_definition: &FieldDefinition,
         z_axis_definition: &FieldDefinition,
+        channel_field_definitions: &[(GlyphChannel, FieldDefinition)],
+        supporting_fields: &[SupportingField],
         operations: &T,
     ) -> Result<String, GlyphEngineProcessError> {
-        let (x_field_name, _, x_raw_query) = x_axis_definition.get_query_parts();
-        let (y_field_name, _, y_raw_query) = y_axis_definition.get_query_parts();
-        let (z_field_name, _, z_raw_query) = z_axis_definition.get_query_parts();
-        let database_name = self.athena_connection.get_database_name();
-        let filter = &self.parameters.filter;
-        let filter = match filter {
-            Some(filter) => format!("WHERE {}", filter),
-            None => "".to_string(),
+        let query = GlyphQuery {
+            table_name: self.parameters.data_table_name.clone(),
+            x_field_definition: x_axis_definition.clone(),
+            y_field_definition: y_axis_definition.clone(),
+            z_field_definition: z_axis_definition.clone(),
+            channel_field_definitions: channel_field_definitions.to_vec(),
+            supporting_fields: supporting_fields.to_vec(),
+            filter: self.parameters.filter.clone(),
         };
-        let query = format!(
-            r#"
-    WITH temp as (
-        SELECT glyphx_id__ as rowid, 
-        {} as groupedXColumn, 
-        {} as groupedYColumn, 
-        "{}"
-        FROM "{}"."{}" 
-        {}
-    )  
-    SELECT array_join(array_agg(rowid), '|') as "rowids", 
-    groupedXColumn as "x_{}", 
-    groupedYColumn as "y_{}", 
-    {} as "z_{}"
-    FROM temp 
-    GROUP BY groupedXColumn, groupedYColumn;
-"#,
-            x_raw_query,
-            y_raw_query,
-            z_field_name,
-            database_name,
-            self.parameters.data_table_name,
-            filter,
-            x_field_name,
-            y_field_name,
-            z_raw_query,
-            z_field_name
-        );
 
         let query_id = operations
-            .start_athena_query(self.athena_connection, &query)
+            .start_glyph_query(self.data_source.clone(), &query)
             .await?;
         Ok(query_id)
     }
 
     fn get_vector(
         &self,
-        result: &Value,
+        result: &GlyphQueryRow,
         field_name: &str,
         vector_processor: &Box<dyn VectorValueProcesser>,
     ) -> Result<Vector, GlyphEngineProcessError> {
@@ -570,7 +800,7 @@ impl GlyphEngine {
 
     fn get_z_value(
         &self,
-        result: &Value,
+        result: &GlyphQueryRow,
         z_field_name: &str,
     ) -> Result<f64, GlyphEngineProcessError> {
         let value = result.get(z_field_name);
@@ -596,44 +826,13 @@ impl GlyphEngine {
         Ok(value.as_f64().unwrap())
     }
 
-    fn get_row_ids(&self, result: &Value) -> Result<Vec<usize>, GlyphEngineProcessError> {
-        let value = result.get("rowids");
-        if value.is_none() {
-            let message = format!("The field rowids was not found in the query results");
-            let data = serde_json::json!({});
-            let error_data = GlyphxErrorData::new(message, Some(data), None);
-            return Err(GlyphEngineProcessError::DataProcessingError(error_data));
-        }
-        let value = value.unwrap();
-        if !value.is_string() {
-            let message = format!("The field rowids was not a string in the query results");
-            let data = serde_json::json!({});
-            let error_data = GlyphxErrorData::new(message, Some(data), None);
-            return Err(GlyphEngineProcessError::DataProcessingError(error_data));
-        }
-        let value = value.as_str().unwrap();
-        let values: Vec<&str> = value.split("|").collect();
-        let mut return_value: Vec<usize> = Vec::new();
-        for v in values {
-            let result = v.parse::<usize>();
-            if result.is_err() {
-                let message =
-                    format!("The field rowids was not a pipe delimited string of integers");
-                let data = serde_json::json!({"value": value, "bad_value": v});
-                let error_data = GlyphxErrorData::new(message, Some(data), None);
-                return Err(GlyphEngineProcessError::DataProcessingError(error_data));
-            }
-            return_value.push(result.unwrap());
-        }
-        Ok(return_value)
-    }
-
     fn build_glyph(
         &self,
-        result: &Value,
+        result: &GlyphQueryRow,
         x_field_name: &str,
         y_field_name: &str,
         z_field_name: &str,
+        channel_field_definitions: &[(GlyphChannel, FieldDefinition)],
         x_vector_processer: &Box<dyn VectorValueProcesser>,
         y_vector_processer: &Box<dyn VectorValueProcesser>,
     ) -> Result<Glyph, GlyphEngineProcessError> {
@@ -644,69 +843,216 @@ impl GlyphEngine {
         let x_vector = self.get_vector(result, &x_field_name, x_vector_processer)?;
         let y_vector = self.get_vector(result, &y_field_name, y_vector_processer)?;
         let z_value = self.get_z_value(result, &z_field_name)?;
-        let rowids = self.get_row_ids(result)?;
-        let glyph = Glyph::new(x_vector.vector, y_vector.vector, z_value, rowids);
+        let rowids = result.rowids.clone();
+        let mut glyph = Glyph::new(x_vector.vector, y_vector.vector, z_value, rowids);
+        //The channels are accumulated the same way that z is.
+        for (channel, field_definition) in channel_field_definitions {
+            let channel_field_name = format!(
+                "{}_{}",
+                channel.get_axis_name(),
+                field_definition.get_field_display_name()
+            );
+            let channel_value = self.get_z_value(result, &channel_field_name)?;
+            glyph.set_channel_value(*channel, channel_value);
+        }
         Ok(glyph)
     }
 
-    fn serialize_glyph(&self, glyph: &Glyph) -> Vec<u8> {
-        let glyph_binary_size = glyph.get_binary_size();
-        let mut ser_glyph: Vec<u8> = Vec::with_capacity(8 + glyph_binary_size);
-        //We are including the size of the glyph so that when we write this to a file we
-        //will be able to determine the size of the glyph when we read it back in.
-        ser_glyph.append(serialize(&glyph_binary_size).unwrap().as_mut());
-        ser_glyph.append(serialize(glyph).unwrap().as_mut());
-        ser_glyph
+    ///Returns the aggregated supporting field values of a glyph as JSON text.  A distinct list
+    ///already comes back from the query as JSON text, so it is written as is.
+    fn build_supporting_values(
+        &self,
+        result: &GlyphQueryRow,
+        glyph_index: u64,
+        supporting_fields: &[SupportingField],
+    ) -> Result<SupportingValues, GlyphEngineProcessError> {
+        let mut values = Vec::with_capacity(supporting_fields.len());
+        for supporting_field in supporting_fields {
+            let column_name = supporting_field.get_column_name();
+            let value = result.get(&column_name);
+            if value.is_none() {
+                let message = format!(
+                    "The field {} was not found in the query results",
+                    column_name
+                );
+                let data = serde_json::json!({ "field_name": column_name });
+                let error_data = GlyphxErrorData::new(message, Some(data), None);
+                return Err(GlyphEngineProcessError::DataProcessingError(error_data));
+            }
+            let value = value.unwrap();
+            let value = match (supporting_field.aggregation, value) {
+                (SupportingFieldAggregation::DistinctList, Value::String(list)) => list.clone(),
+                _ => value.to_string(),
+            };
+            values.push(value);
+        }
+        Ok(SupportingValues {
+            glyph_index,
+            values,
+        })
+    }
+
+    ///Builds the header for one of our output files from the x, y and z field definitions and
+    ///the definitions of any mapped glyph channels.
+    fn build_file_header(
+        &self,
+        file_type: FileType,
+        channel_field_definitions: &[(GlyphChannel, FieldDefinition)],
+    ) -> FileHeader {
+        let mut fields: Vec<HeaderField> = [("x", "xaxis"), ("y", "yaxis"), ("z", "zaxis")]
+            .iter()
+            .map(|(axis, field_name)| {
+                let field_definition = self
+                    .parameters
+                    .get_field_definition(field_name)
+                    .unwrap_or(FieldDefinition::Unknown());
+                HeaderField::new(axis, &field_definition)
+            })
+            .collect();
+        for (channel, field_definition) in channel_field_definitions {
+            fields.push(HeaderField::new(channel.get_axis_name(), field_definition));
+        }
+        FileHeader::new(file_type, &self.parameters.model_hash, fields)
+    }
+
+    ///The .sup file header only describes the supporting fields, in the order of their values.
+    fn build_supporting_file_header(&self, supporting_fields: &[SupportingField]) -> FileHeader {
+        let fields = supporting_fields
+            .iter()
+            .map(|supporting_field| {
+                HeaderField::new("supporting", &supporting_field.field_definition)
+            })
+            .collect();
+        FileHeader::new(FileType::Supporting, &self.parameters.model_hash, fields)
     }
+    ///Writes the glyphs of the query results to the .gly file and, when supporting fields were
+    ///asked for, their values to the .sup file.
     async fn process_query_results<T: GlyphEngineOperations>(
         &self,
         file_name: &str,
-        results_iterator: &mut AthenaStreamIterator,
+        results_iterator: &mut dyn RowStream,
         x_field_name: &str,
         y_field_name: &str,
         z_field_name: &str,
+        channel_field_definitions: &[(GlyphChannel, FieldDefinition)],
+        supporting_file_name: Option<&str>,
+        supporting_fields: &[SupportingField],
         x_vector_processer: &Box<dyn VectorValueProcesser>,
         y_vector_processer: &Box<dyn VectorValueProcesser>,
         operations: &T,
-    ) -> Result<Vec<f64>, GlyphEngineProcessError> {
-        //Ok this is a bit of a hack, but I needed something which could hold our value that has to
-        //the Ord trait -- f64 does not hold this.  To keep things moving, I am just going
-        //to resuse VectorOrigionalValue, it is alread setup for OrdSet.
-        let mut unique_values = OrdSet::<VectorOrigionalValue>::new();
-        handle_error!(let upload_stream = operations.get_upload_stream(file_name, &self.s3_connection).await; GlyphEngineProcessError::from_get_upload_stream_error(file_name), error);
-        //our handle_error macro will not let us create a mutable reference
-        let mut upload_stream = upload_stream;
-        loop {
-            handle_error!(let result = results_iterator.next().await; GlyphEngineProcessError::from_athena_stream_iterator_error(), error);
-            if result.is_none() {
-                break;
+    ) -> Result<(Vec<f64>, Vec<(GlyphChannel, Vec<f64>)>), GlyphEngineProcessError> {
+        handle_error!(let upload_stream = operations.get_upload_stream(file_name, self.object_store.clone()).await; GlyphEngineProcessError::from_get_upload_stream_error(file_name), error);
+        //Each upload stream is set to None once it has been finished, so that if anything below
+        //fails we can abort the ones that are still open and S3 does not keep their parts.
+        let mut upload_stream = Some(upload_stream);
+        let mut supporting_upload_stream: Option<Box<dyn ObjectUploadStream>> = None;
+        let result = async {
+            //Ok this is a bit of a hack, but I needed something which could hold our value that
+            //has to the Ord trait -- f64 does not hold this.  To keep things moving, I am just
+            //going to resuse VectorOrigionalValue, it is alread setup for OrdSet.
+            let mut unique_values = OrdSet::<VectorOrigionalValue>::new();
+            let mut unique_channel_values =
+                vec![OrdSet::<VectorOrigionalValue>::new(); channel_field_definitions.len()];
+            let glyph_stream = upload_stream.as_mut().unwrap();
+            let header = self
+                .build_file_header(FileType::Glyph, channel_field_definitions)
+                .encode();
+            handle_error!(let _result = operations.write_to_upload_stream(glyph_stream, Some(header)).await; GlyphEngineProcessError::from_upload_stream_write_error(file_name), error);
+            let mut encoder = RecordEncoder::new();
+            //The supporting values are written to their own file, keyed by the index of the
+            //glyph in the .gly file, which is only opened when they have been asked for.
+            if let Some(supporting_file_name) = supporting_file_name {
+                handle_error!(let supporting_stream = operations.get_upload_stream(supporting_file_name, self.object_store.clone()).await; GlyphEngineProcessError::from_get_upload_stream_error(supporting_file_name), error);
+                let supporting_stream = supporting_upload_stream.insert(supporting_stream);
+                let header = self
+                    .build_supporting_file_header(supporting_fields)
+                    .encode();
+                handle_error!(let _result = operations.write_to_upload_stream(supporting_stream, Some(header)).await; GlyphEngineProcessError::from_upload_stream_write_error(supporting_file_name), error);
             }
-            let result = result.unwrap();
-            let glyph = self.build_glyph(
-                &result,
-                x_field_name,
-                y_field_name,
-                z_field_name,
-                x_vector_processer,
-                y_vector_processer,
-            )?;
-            let z_value = VectorOrigionalValue::F64(glyph.z_value.clone());
-            if !unique_values.contains(&z_value) {
-                unique_values.insert(z_value);
+            let mut supporting_encoder = RecordEncoder::new();
+            let mut glyph_index: u64 = 0;
+            loop {
+                if self.cancellation_token.is_cancelled() {
+                    return Err(GlyphEngineProcessError::cancelled());
+                }
+                handle_error!(let result = results_iterator.next().await; GlyphEngineProcessError::from_row_stream_error(), error);
+                if result.is_none() {
+                    break;
+                }
+                let result = result.unwrap();
+                let glyph = self.build_glyph(
+                    &result,
+                    x_field_name,
+                    y_field_name,
+                    z_field_name,
+                    channel_field_definitions,
+                    x_vector_processer,
+                    y_vector_processer,
+                )?;
+                let z_value = VectorOrigionalValue::F64(glyph.z_value);
+                if !unique_values.contains(&z_value) {
+                    unique_values.insert(z_value);
+                }
+                for ((channel, _), channel_values) in channel_field_definitions
+                    .iter()
+                    .zip(unique_channel_values.iter_mut())
+                {
+                    let channel_value =
+                        VectorOrigionalValue::F64(glyph.get_channel_value(*channel).unwrap());
+                    channel_values.insert(channel_value);
+                }
+                let ser_glyph = encoder.encode_record(&glyph);
+                handle_error!(let _result = operations.write_to_upload_stream(glyph_stream, Some(ser_glyph)).await; GlyphEngineProcessError::from_upload_stream_write_error(file_name), error);
+                if let (Some(supporting_file_name), Some(supporting_stream)) =
+                    (supporting_file_name, supporting_upload_stream.as_mut())
+                {
+                    let supporting_values =
+                        self.build_supporting_values(&result, glyph_index, supporting_fields)?;
+                    let ser_values = supporting_encoder.encode_record(&supporting_values);
+                    handle_error!(let _result = operations.write_to_upload_stream(supporting_stream, Some(ser_values)).await; GlyphEngineProcessError::from_upload_stream_write_error(supporting_file_name), error);
+                }
+                glyph_index += 1;
+            }
+            let footer = encoder.get_footer().encode();
+            handle_error!(let _result = operations.write_to_upload_stream(glyph_stream, Some(footer)).await; GlyphEngineProcessError::from_upload_stream_write_error(file_name), error);
+            handle_error!(let _result = operations.finish_upload_stream(glyph_stream).await; GlyphEngineProcessError::from_upload_stream_finish_error(file_name), error);
+            upload_stream = None;
+            if let (Some(supporting_file_name), Some(supporting_stream)) =
+                (supporting_file_name, supporting_upload_stream.as_mut())
+            {
+                let footer = supporting_encoder.get_footer().encode();
+                handle_error!(let _result = operations.write_to_upload_stream(supporting_stream, Some(footer)).await; GlyphEngineProcessError::from_upload_stream_write_error(supporting_file_name), error);
+                handle_error!(let _result = operations.finish_upload_stream(supporting_stream).await; GlyphEngineProcessError::from_upload_stream_finish_error(supporting_file_name), error);
+                supporting_upload_stream = None;
             }
-            let ser_glyph = self.serialize_glyph(&glyph);
-            handle_error!(let _result = operations.write_to_upload_stream(&mut upload_stream, Some(ser_glyph)).await; GlyphEngineProcessError::from_upload_stream_write_error(file_name), error);
-        }
-        handle_error!(let _result = operations.finish_upload_stream(&mut upload_stream).await; GlyphEngineProcessError::from_upload_stream_finish_error(file_name), error);
 
-        let vector_for_statistics = unique_values
-            .iter()
-            .map(|x| match x {
-                VectorOrigionalValue::F64(y) => *y,
-                _ => 0.0,
-            })
-            .collect();
-        Ok(vector_for_statistics)
+            let to_statistics_vector = |values: &OrdSet<VectorOrigionalValue>| -> Vec<f64> {
+                values
+                    .iter()
+                    .map(|x| match x {
+                        VectorOrigionalValue::F64(y) => *y,
+                        _ => 0.0,
+                    })
+                    .collect()
+            };
+            let vector_for_statistics = to_statistics_vector(&unique_values);
+            let channel_vectors_for_statistics = channel_field_definitions
+                .iter()
+                .zip(unique_channel_values.iter())
+                .map(|((channel, _), values)| (*channel, to_statistics_vector(values)))
+                .collect();
+            Ok((vector_for_statistics, channel_vectors_for_statistics))
+        }
+        .await;
+        if result.is_err() {
+            for upload_stream in [upload_stream.as_mut(), supporting_upload_stream.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                operations.abort_upload_stream(upload_stream).await;
+            }
+        }
+        result
     }
     fn get_stats_for_axis(&self, axis_name: &str, data: Vec<f64>) -> Stats {
         let mut stats_generator = statrs::statistics::Data::new(data);
@@ -749,15 +1095,11 @@ impl GlyphEngine {
     async fn write_stats<T: GlyphEngineOperations>(
         &self,
         stats: &Stats,
-        upload_stream: &mut UploadStream,
+        upload_stream: &mut Box<dyn ObjectUploadStream>,
+        encoder: &mut RecordEncoder,
         operations: &T,
     ) -> Result<(), GlyphEngineProcessError> {
-        let stats_binary_size = stats.get_binary_size();
-        let mut ser_stats: Vec<u8> = Vec::with_capacity(8 + stats_binary_size);
-        //We are including the size of the stats so that when we write this to a file we
-        //will be able to determine the size of the stats when we read it back in.
-        ser_stats.append(serialize(&stats_binary_size).unwrap().as_mut());
-        ser_stats.append(serialize(stats).unwrap().as_mut());
+        let ser_stats = encoder.encode_record(stats);
         handle_error!(let _result = operations.write_to_upload_stream(upload_stream, Some(ser_stats)).await; GlyphEngineProcessError::from_upload_stream_write_error("stats"), error);
         Ok(())
     }
@@ -767,11 +1109,16 @@ impl GlyphEngine {
         x_field_processor: &Box<dyn VectorValueProcesser>,
         y_field_processor: &Box<dyn VectorValueProcesser>,
         z_stats_vector: Vec<f64>,
+        channel_stats_vectors: Vec<(GlyphChannel, Vec<f64>)>,
         operations: &T,
     ) -> Result<String, GlyphEngineProcessError> {
         let x_stats = self.get_stats_for_axis("x", x_field_processor.get_statistics_vector());
         let y_stats = self.get_stats_for_axis("y", y_field_processor.get_statistics_vector());
         let z_stats = self.get_stats_for_axis("z", z_stats_vector);
+        let channel_field_definitions = self
+            .parameters
+            .get_channel_field_definitions()
+            .unwrap_or_default();
 
         let stats_file_name = format!(
             "{}/{}",
@@ -782,24 +1129,76 @@ impl GlyphEngine {
                 &self.parameters.model_hash
             )
         );
-        handle_error!(let upload_stream = operations.get_upload_stream(&stats_file_name, &self.s3_connection).await; GlyphEngineProcessError::from_get_upload_stream_error(&stats_file_name), error);
+        handle_error!(let upload_stream = operations.get_upload_stream(&stats_file_name, self.object_store.clone()).await; GlyphEngineProcessError::from_get_upload_stream_error(&stats_file_name), error);
         let mut upload_stream = upload_stream;
+        let header = self
+            .build_file_header(FileType::Stats, &channel_field_definitions)
+            .encode();
+        handle_error!(let _result = operations.write_to_upload_stream(&mut upload_stream, Some(header)).await; GlyphEngineProcessError::from_upload_stream_write_error(&stats_file_name), error);
+        let mut encoder = RecordEncoder::new();
 
-        self.write_stats(&x_stats, &mut upload_stream, operations)
+        self.write_stats(&x_stats, &mut upload_stream, &mut encoder, operations)
             .await?;
-        self.write_stats(&y_stats, &mut upload_stream, operations)
+        self.write_stats(&y_stats, &mut upload_stream, &mut encoder, operations)
             .await?;
-        self.write_stats(&z_stats, &mut upload_stream, operations)
+        self.write_stats(&z_stats, &mut upload_stream, &mut encoder, operations)
             .await?;
+        //The channel stats follow z in the order of the channels, each is named for its channel.
+        for (channel, channel_stats_vector) in channel_stats_vectors {
+            let channel_stats =
+                self.get_stats_for_axis(channel.get_axis_name(), channel_stats_vector);
+            self.write_stats(&channel_stats, &mut upload_stream, &mut encoder, operations)
+                .await?;
+        }
+        let footer = encoder.get_footer().encode();
+        handle_error!(let _result = operations.write_to_upload_stream(&mut upload_stream, Some(footer)).await; GlyphEngineProcessError::from_upload_stream_write_error(&stats_file_name), error);
         handle_error!(let _result = operations.finish_upload_stream(&mut upload_stream).await; GlyphEngineProcessError::from_upload_stream_finish_error(&stats_file_name), error);
         Ok(stats_file_name)
     }
+    ///Our cancellation checkpoints.  Cancelling is reported as an error so that process_error!
+    ///can unwind process_impl.
+    fn check_cancelled(&self) -> Result<(), GlyphEngineProcessError> {
+        if self.cancellation_token.is_cancelled() {
+            return Err(GlyphEngineProcessError::cancelled());
+        }
+        Ok(())
+    }
+
+    ///Once the glyph query has been started, cancelling has to stop it as well, otherwise it will
+    ///run to completion on Athena.
+    async fn stop_query<T: GlyphEngineOperations>(&self, query_id: &str, operations: &T) {
+        let result = operations
+            .stop_glyph_query(self.data_source.clone(), query_id)
+            .await;
+        if result.is_err() {
+            //We are already cancelling, so there is nothing left to do but log it.
+            error!("An error occurred while trying to stop the glyph query.  Here is the error: {:?}", result.err().unwrap());
+        }
+    }
+
     async fn process_error<T: GlyphEngineOperations>(
         &mut self,
         error: &GlyphEngineProcessError,
         operations: &T,
     ) {
-        let process_id = self.heartbeat.get_process_id();
+        //A local run has no heartbeat, and so no process tracking document to update.
+        if self.heartbeat.is_none() {
+            return;
+        }
+        let heartbeat = self.heartbeat.as_mut().unwrap();
+        let process_id = heartbeat.get_process_id();
+        //A cancelled process has not failed, so no error is added to the process tracking
+        //document.
+        if let GlyphEngineProcessError::Cancelled(_) = error {
+            let update_process_tracking_result = operations
+                .complete_process_tracking(&process_id, ProcessStatus::Cancelled, None)
+                .await;
+            if update_process_tracking_result.is_err() {
+                error!("An error occurred while trying to update the process tracking document.  Here is the error: {:?}", update_process_tracking_result.err().unwrap());
+            }
+            operations.stop_heartbeat(heartbeat);
+            return;
+        }
         let add_error_result = operations
             .add_process_tracking_error(&process_id, &error)
             .await;
@@ -820,36 +1219,76 @@ impl GlyphEngine {
             error!("An error occurred while trying to update the process tracking document.  Here is the error: {:?}", add_error_result.err().unwrap());
         }
         //No matter what, we need to stop the heartbeat
-        operations.stop_heartbeat(&mut self.heartbeat);
+        operations.stop_heartbeat(heartbeat);
     }
 
     async fn process_impl<T: GlyphEngineOperations>(
         &mut self,
         operations: &T,
     ) -> Result<GlyphEngineResults, GlyphEngineProcessError> {
+        process_error!(let _cancelled = self.check_cancelled(); operations; self);
         //Get our field definitions
         process_error!(let x_field_definition = self.parameters.get_field_definition("xaxis"); GlyphEngineProcessError::from_get_field_definition_error("xaxis"); operations; self);
         process_error!(let y_field_definition = self.parameters.get_field_definition("yaxis"); GlyphEngineProcessError::from_get_field_definition_error("yaxis");operations; self );
+        process_error!(let _filter = self.validate_filter(operations).await; operations; self);
+        process_error!(let x_field_definition = self.resolve_bin_edges(x_field_definition, operations).await; operations; self);
+        process_error!(let y_field_definition = self.resolve_bin_edges(y_field_definition, operations).await; operations; self);
+        process_error!(let x_field_definition = self.resolve_top_values(x_field_definition, operations).await; operations; self);
+        process_error!(let y_field_definition = self.resolve_top_values(y_field_definition, operations).await; operations; self);
         process_error!(let z_field_definition = self.parameters.get_field_definition("zaxis"); GlyphEngineProcessError::from_get_field_definition_error("zaxis"); operations; self);
+        process_error!(let channel_field_definitions = self.parameters.get_channel_field_definitions(); GlyphEngineProcessError::from_get_field_definition_error("channels"); operations; self);
+        //Supporting fields are only aggregated into the glyph query when they have been asked for.
+        let mut supporting_fields: Vec<SupportingField> = Vec::new();
+        if self.parameters.include_supporting_fields {
+            process_error!(let fields = self.parameters.get_supporting_fields(); GlyphEngineProcessError::from_get_field_definition_error("supportingFields"); operations; self);
+            supporting_fields = fields;
+        }
 
         //1. Kick off the main query.  This runs offline on AWS and we need it to finish before we
         //   can do anything else.  Here we can start the query, then go and get our vector tables
-        process_error!(let query_id = self.start_query( &x_field_definition, &y_field_definition, &z_field_definition, operations,).await;operations; self);
+        process_error!(let _cancelled = self.check_cancelled(); operations; self);
+        process_error!(let query_id = self.start_query( &x_field_definition, &y_field_definition, &z_field_definition, &channel_field_definitions, &supporting_fields, operations,).await;operations; self);
+        //The query runs while the vectors are built, so its clock starts now.
+        let mut poller = self.polling_policy.start();
         //1. Build the vector/rank tables tables and upload them to S3. -- 1 for each vertex (X and
         //   Y)
 
         let (x_file_name, y_file_name) = self.get_vector_file_names();
 
         let mut status: AthenaQueryStatus;
-        process_error!( let vectors = self.process_vectors(&x_field_definition, &x_file_name, &y_field_definition, &y_file_name, operations).await;operations;self);
+        let vectors = self.process_vectors(&x_field_definition, &x_file_name, &y_field_definition, &y_file_name, operations).await;
+        if let Err(GlyphEngineProcessError::Cancelled(_)) = vectors {
+            self.stop_query(&query_id, operations).await;
+        }
+        process_error!(let vectors = vectors; operations; self);
         let (x_field_processor, y_field_processor) = vectors;
 
         loop {
-            process_error!(let local_status = operations.check_query_status(self.athena_connection, &query_id).await;operations;self);
+            if self.cancellation_token.is_cancelled() {
+                self.stop_query(&query_id, operations).await;
+            }
+            process_error!(let _cancelled = self.check_cancelled(); operations; self);
+            process_error!(let local_status = operations.check_query_status(self.data_source.clone(), &query_id).await;operations;self);
             status = local_status;
             if status != AthenaQueryStatus::Queued && status != AthenaQueryStatus::Running {
                 break;
             }
+            //Back off before checking again, waking up early if we are cancelled.
+            let cancellation_token = self.cancellation_token.clone();
+            let timed_out = tokio::select! {
+                waited = poller.wait() => !waited,
+                _ = cancellation_token.cancelled() => false,
+            };
+            if timed_out {
+                self.stop_query(&query_id, operations).await;
+                let error = GlyphEngineProcessError::query_timed_out(
+                    &query_id,
+                    poller.get_policy().get_max_wait(),
+                );
+                error.error();
+                self.process_error(&error, operations).await;
+                return Err(error);
+            }
         }
 
         if status != AthenaQueryStatus::Succeeded {
@@ -881,33 +1320,53 @@ impl GlyphEngine {
             )
         );
 
-        process_error!(let iterator_results = operations.get_query_results(&query_id, &self.athena_connection).await; operations; self);
+        let supporting_file_name = if supporting_fields.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "{}/{}",
+                self.parameters.output_file_prefix,
+                get_supporting_file_name(
+                    &self.parameters.workspace_id,
+                    &self.parameters.project_id,
+                    &self.parameters.model_hash
+                )
+            ))
+        };
+
+        process_error!(let iterator_results = operations.get_query_results(&query_id, self.data_source.clone()).await; operations; self);
         let mut results_iterator = iterator_results;
 
-        process_error!(let z_stats_vector = self.process_query_results( &glyph_file_name, &mut results_iterator, &x_field_definition.get_field_display_name(), &y_field_definition.get_field_display_name(), &z_field_definition.get_field_display_name(), &x_field_processor, &y_field_processor, operations,).await; operations; self);
+        process_error!(let stats_vectors = self.process_query_results( &glyph_file_name, results_iterator.as_mut(), x_field_definition.get_field_display_name(), y_field_definition.get_field_display_name(), z_field_definition.get_field_display_name(), &channel_field_definitions, supporting_file_name.as_deref(), &supporting_fields, &x_field_processor, &y_field_processor, operations,).await; operations; self);
+        let (z_stats_vector, channel_stats_vectors) = stats_vectors;
 
-        process_error!(let stats_file_name = self.calculate_statistics(&x_field_processor, &y_field_processor, z_stats_vector, operations).await; operations; self);
+        process_error!(let _cancelled = self.check_cancelled(); operations; self);
+        process_error!(let stats_file_name = self.calculate_statistics(&x_field_processor, &y_field_processor, z_stats_vector, channel_stats_vectors, operations).await; operations; self);
 
         let results = GlyphEngineResults {
             x_axis_vectors_file_name: x_file_name,
             y_axis_vectors_file_name: y_file_name,
             glyphs_file_name: glyph_file_name,
             statistics_file_name: stats_file_name,
+            supporting_file_name,
         };
 
-        let json_results = to_value(&results).unwrap();
-        let complete_process_tracking_result = operations
-            .complete_process_tracking(
-                &self.heartbeat.get_process_id(),
-                ProcessStatus::Completed,
-                Some(json_results),
-            )
-            .await;
-        if complete_process_tracking_result.is_err() {
-            let error = complete_process_tracking_result.err().unwrap();
-            error!("An error occurred while trying to update the process tracking document. GlyphEngine has completed.  Here is the error: {:?}", error);
+        //A local run has no heartbeat, and so no process tracking document to complete.
+        if let Some(heartbeat) = self.heartbeat.as_mut() {
+            let json_results = to_value(&results).unwrap();
+            let complete_process_tracking_result = operations
+                .complete_process_tracking(
+                    &heartbeat.get_process_id(),
+                    ProcessStatus::Completed,
+                    Some(json_results),
+                )
+                .await;
+            if complete_process_tracking_result.is_err() {
+                let error = complete_process_tracking_result.err().unwrap();
+                error!("An error occurred while trying to update the process tracking document. GlyphEngine has completed.  Here is the error: {:?}", error);
+            }
+            operations.stop_heartbeat(heartbeat);
         }
-        operations.stop_heartbeat(&mut self.heartbeat);
         Ok(results)
     }
     pub async fn process(&mut self) -> Result<GlyphEngineResults, GlyphEngineProcessError> {
@@ -923,8 +1382,10 @@ pub mod glyph_engine {
     use once_cell::sync::Lazy;
     use serde_json::json;
 
+    use glyphx_core::aws::{upload_stream::UploadStream, S3Manager};
+    use glyphx_core::aws::athena_manager::ConversionOptions;
     use glyphx_core::aws::athena_stream_iterator::{
-        test_objects::MockStream, ColumnInfo, ColumnNullable, Datum, GetQueryResultsError,
+        test_objects::MockStream, AthenaStreamIterator, ColumnInfo, ColumnNullable, Datum, GetQueryResultsError,
         GetQueryResultsOutput, ResultSet, ResultSetMetadata, Row, SdkError,
     };
 
@@ -1156,6 +1617,10 @@ pub mod glyph_engine {
         mocks
     }
 
+    fn get_glyph_row(value: Value) -> GlyphQueryRow {
+        serde_json::from_value(value).unwrap()
+    }
+
     async fn get_glyph_engine() -> GlyphEngine {
         let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
         let mocks = get_setup_mocks();
@@ -1239,6 +1704,19 @@ pub mod glyph_engine {
             }
         }
 
+        #[tokio::test]
+        async fn with_data_source_is_local() {
+            let parameters = VectorizerParameters::default();
+            let data_source: Arc<dyn DataSource> = Arc::new(
+                data_source::LocalFileDataSource::new(std::env::temp_dir()),
+            );
+            let object_store: Arc<dyn ObjectStore> = Arc::new(S3Manager::default());
+            let result =
+                GlyphEngine::new_with_data_source(&parameters, data_source, object_store).await;
+            assert!(result.is_ok());
+            assert!(result.unwrap().heartbeat.is_none());
+        }
+
         #[tokio::test]
         async fn athena_connections_errors() {
             let parameters = VectorizerParameters::default();
@@ -1391,7 +1869,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_get_vector_processer()
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     CALL_NUMBER += 1;
                     if CALL_NUMBER == 1 {
                         let mut vector_processer_mock1 = MockVectorValueProcesser::new();
@@ -1475,7 +1953,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_get_vector_processer()
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     CALL_NUMBER += 1;
                     if CALL_NUMBER == 1 {
                         let mut vector_processer_mock1 = MockVectorValueProcesser::new();
@@ -1562,7 +2040,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_get_vector_processer()
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     CALL_NUMBER += 1;
                     if CALL_NUMBER == 1 {
                         let mut vector_processer_mock1 = MockVectorValueProcesser::new();
@@ -1649,7 +2127,7 @@ pub mod glyph_engine {
                 .returning(|| Ok(unsafe { HEARTBEAT_INSTANCE.as_ref().unwrap().clone() }));
 
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .returning(|_, _| Ok("1234".to_string()));
 
             let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
@@ -1663,6 +2141,8 @@ pub mod glyph_engine {
                     &x_field_definition,
                     &y_field_definition,
                     &z_field_definition,
+                    &[],
+                    &[],
                     &mocks,
                 )
                 .await;
@@ -1693,7 +2173,7 @@ pub mod glyph_engine {
                 .expect_build_heartbeat()
                 .returning(|| Ok(unsafe { HEARTBEAT_INSTANCE.as_ref().unwrap().clone() }));
 
-            mocks.expect_start_athena_query().returning(|_, _| {
+            mocks.expect_start_glyph_query().returning(|_, _| {
                 Err(GlyphEngineProcessError::QueryProcessingError(
                     GlyphxErrorData::new(
                         "An unexpected error occurred while running the vector query.  See the inner error for additional information".to_string(),
@@ -1715,6 +2195,8 @@ pub mod glyph_engine {
                     &x_field_definition,
                     &y_field_definition,
                     &z_field_definition,
+                    &[],
+                    &[],
                     &mocks,
                 )
                 .await;
@@ -1730,72 +2212,682 @@ pub mod glyph_engine {
         }
     }
 
-    mod process_query_results {
+    mod resolve_bin_edges {
         use super::*;
-        use glyphx_core::aws::athena_stream_iterator::{
-            ErrorMetadata, GetQueryResultsError, HttpResponse, InternalServerException, SdkBody,
-            SdkError,
-        };
-        use vector_processer::build_vector_processer_from_json;
+        use serde_json::json;
 
-        #[tokio::test]
-        async fn is_ok() {
-            //1. Mock out our GlyhEngineOperations
-            let mut mocks = get_setup_mocks();
+        fn get_mocks() -> MockGlyphEngineOperations {
+            let mut mocks = MockGlyphEngineOperations::new();
 
             mocks
-                .expect_get_upload_stream()
-                .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
-                });
+                .expect_build_s3_connection()
+                .returning(|| Ok(unsafe { &S3_CONNECTION_INSTANCE.as_ref().unwrap() }));
 
             mocks
-                .expect_write_to_upload_stream()
-                .times(10)
-                .returning(|_, _| Ok(()));
+                .expect_build_athena_connection()
+                .returning(|| Ok(unsafe { &ATHENA_CONNECTION_INSTANCE.as_ref().unwrap() }));
 
             mocks
-                .expect_finish_upload_stream()
-                .times(1)
-                .returning(|_| Ok(()));
+                .expect_build_mongo_connection()
+                .returning(|| Ok(unsafe { &MONGO_CONNECTION_INSTANCE.as_ref().unwrap() }));
 
-            //2. Get our glyph_engine
-            let glyph_engine = get_glyph_engine().await; //  GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+            mocks
+                .expect_build_heartbeat()
+                .returning(|| Ok(unsafe { HEARTBEAT_INSTANCE.as_ref().unwrap().clone() }));
+            mocks
+        }
 
-            //3. Build our field_definitions
-            let x_field_definition = glyph_engine
-                .parameters
-                .get_field_definition("xaxis")
-                .unwrap();
+        fn get_binned_field_definition() -> FieldDefinition {
+            FieldDefinition::from_json(&json!({
+                "fieldDisplayName": "price",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "binned",
+                    "fieldName": "price",
+                    "binningMethod": "equal_width",
+                    "binCount": 2
+                }
+            }))
+            .unwrap()
+        }
 
-            let y_field_definition = glyph_engine
-                .parameters
-                .get_field_definition("yaxis")
-                .unwrap();
+        #[tokio::test]
+        async fn is_ok() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_bin_edges().times(1).returning(|_, query| {
+                assert_eq!(query.table_name, "my_table");
+                assert_eq!(query.field_definition.field_name, "price");
+                Ok(vec![0.0, 5.0, 10.0])
+            });
 
-            //4. Mock out our vector processers
-            let x_vector_processor = build_vector_processer_from_json(
-                "x",
-                "test_table",
-                "test_file",
-                x_field_definition.clone(),
-                RESULT_SET.clone(),
-                X_FIELD_NAME.to_string(),
-            );
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
 
-            let y_vector_processor = build_vector_processer_from_json(
-                "y",
-                "test_table",
-                "test_file",
-                y_field_definition.clone(),
-                RESULT_SET.clone(),
-                Y_FIELD_NAME.to_string(),
-            );
+            let result = glyph_engine
+                .resolve_bin_edges(get_binned_field_definition(), &mocks)
+                .await
+                .unwrap();
+            let binned_field_definition = result.get_binned_field_definition().unwrap();
+            assert_eq!(binned_field_definition.edges, vec![0.0, 5.0, 10.0]);
+            assert!(result.get_raw_query().contains(r#""price" < 5"#));
+        }
 
-            //5. Mock out our AthenaStream
-            let mut athena_stream = get_mock_athena_stream_iterator(Box::new(move |stream| {
+        #[tokio::test]
+        async fn is_not_binned() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_bin_edges().never();
+
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let x_field_definition = parameters.get_field_definition("xaxis").unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine
+                .resolve_bin_edges(x_field_definition.clone(), &mocks)
+                .await
+                .unwrap();
+            assert_eq!(result.get_raw_query(), x_field_definition.get_raw_query());
+        }
+
+        #[tokio::test]
+        async fn get_bin_edges_fails() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_bin_edges().returning(|_, _| {
+                Err(GlyphEngineProcessError::QueryProcessingError(
+                    GlyphxErrorData::new("The edges could not be found".to_string(), None, None),
+                ))
+            });
+
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine
+                .resolve_bin_edges(get_binned_field_definition(), &mocks)
+                .await;
+            match result.err().unwrap() {
+                GlyphEngineProcessError::QueryProcessingError(_) => {}
+                _ => panic!("Expected QueryProcessingError"),
+            }
+        }
+    }
+
+    mod resolve_top_values {
+        use super::*;
+        use serde_json::json;
+
+        fn get_mocks() -> MockGlyphEngineOperations {
+            let mut mocks = MockGlyphEngineOperations::new();
+
+            mocks
+                .expect_build_s3_connection()
+                .returning(|| Ok(unsafe { &S3_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_athena_connection()
+                .returning(|| Ok(unsafe { &ATHENA_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_mongo_connection()
+                .returning(|| Ok(unsafe { &MONGO_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_heartbeat()
+                .returning(|| Ok(unsafe { HEARTBEAT_INSTANCE.as_ref().unwrap().clone() }));
+            mocks
+        }
+
+        fn get_top_n_field_definition() -> FieldDefinition {
+            FieldDefinition::from_json(&json!({
+                "fieldDisplayName": "product",
+                "fieldDataType": 1,
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "product",
+                    "topN": {"count": 2}
+                }
+            }))
+            .unwrap()
+        }
+
+        #[tokio::test]
+        async fn is_ok() {
+            let mut mocks = get_mocks();
+            mocks
+                .expect_get_top_values()
+                .times(1)
+                .returning(|_, query| {
+                    assert_eq!(query.table_name, "my_table");
+                    assert_eq!(query.field_definition.field_name, "product");
+                    Ok(vec!["apple".to_string(), "pear".to_string()])
+                });
+
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine
+                .resolve_top_values(get_top_n_field_definition(), &mocks)
+                .await
+                .unwrap();
+            let top_n = result
+                .get_standard_field_definition()
+                .unwrap()
+                .get_resolved_top_n()
+                .unwrap();
+            assert_eq!(
+                top_n.values,
+                Some(vec!["apple".to_string(), "pear".to_string()])
+            );
+            assert!(result.get_raw_query().contains("'Other'"));
+        }
+
+        #[tokio::test]
+        async fn has_no_top_n() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_top_values().never();
+
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let x_field_definition = parameters.get_field_definition("xaxis").unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine
+                .resolve_top_values(x_field_definition.clone(), &mocks)
+                .await
+                .unwrap();
+            assert_eq!(result.get_raw_query(), x_field_definition.get_raw_query());
+        }
+
+        #[tokio::test]
+        async fn get_top_values_fails() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_top_values().returning(|_, _| {
+                Err(GlyphEngineProcessError::QueryProcessingError(
+                    GlyphxErrorData::new(
+                        "The top values could not be found".to_string(),
+                        None,
+                        None,
+                    ),
+                ))
+            });
+
+            let parameters = VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine
+                .resolve_top_values(get_top_n_field_definition(), &mocks)
+                .await;
+            match result.err().unwrap() {
+                GlyphEngineProcessError::QueryProcessingError(_) => {}
+                _ => panic!("Expected QueryProcessingError"),
+            }
+        }
+    }
+
+    mod validate_filter {
+        use super::*;
+        use glyphx_core::aws::athena_manager::ColumnDataType;
+        use serde_json::json;
+
+        fn get_mocks() -> MockGlyphEngineOperations {
+            let mut mocks = MockGlyphEngineOperations::new();
+
+            mocks
+                .expect_build_s3_connection()
+                .returning(|| Ok(unsafe { &S3_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_athena_connection()
+                .returning(|| Ok(unsafe { &ATHENA_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_mongo_connection()
+                .returning(|| Ok(unsafe { &MONGO_CONNECTION_INSTANCE.as_ref().unwrap() }));
+
+            mocks
+                .expect_build_heartbeat()
+                .returning(|| Ok(unsafe { HEARTBEAT_INSTANCE.as_ref().unwrap().clone() }));
+            mocks
+        }
+
+        fn get_parameters(filter: Option<Filter>) -> VectorizerParameters {
+            let mut parameters =
+                VectorizerParameters::from_json_string(&INPUT.to_string()).unwrap();
+            parameters.filter = filter;
+            parameters
+        }
+
+        fn get_filter(field_name: &str, value: Value) -> Option<Filter> {
+            let expression = types::vectorizer_parameters::FilterExpression::from_json(
+                &json!({"fieldName": field_name, "operator": "=", "value": value}),
+            )
+            .unwrap();
+            Some(Filter::Expression(expression))
+        }
+
+        fn get_columns() -> Vec<ColumnDescription> {
+            vec![ColumnDescription {
+                name: "region".to_string(),
+                data_type: ColumnDataType::STRING,
+            }]
+        }
+
+        #[tokio::test]
+        async fn is_ok() {
+            let mut mocks = get_mocks();
+            mocks
+                .expect_get_table_columns()
+                .times(1)
+                .returning(|_, table_name| {
+                    assert_eq!(table_name, "my_table");
+                    Ok(get_columns())
+                });
+
+            let parameters = get_parameters(get_filter("region", json!("east")));
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine.validate_filter(&mocks).await;
+            assert!(result.is_ok());
+        }
+
+        #[tokio::test]
+        async fn has_no_expression() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_table_columns().never();
+
+            let parameters = get_parameters(Some(Filter::Raw("missing = 1".to_string())));
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+            assert!(glyph_engine.validate_filter(&mocks).await.is_ok());
+
+            let parameters = get_parameters(None);
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+            assert!(glyph_engine.validate_filter(&mocks).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn column_not_found() {
+            let mut mocks = get_mocks();
+            mocks
+                .expect_get_table_columns()
+                .returning(|_, _| Ok(get_columns()));
+
+            let parameters = get_parameters(get_filter("missing", json!("east")));
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine.validate_filter(&mocks).await;
+            match result.err().unwrap() {
+                GlyphEngineProcessError::ConfigurationError(error_data) => {
+                    let inner_error = error_data.inner_error.unwrap();
+                    assert_eq!(inner_error["data"]["fieldName"], "missing");
+                }
+                _ => panic!("Expected ConfigurationError"),
+            }
+        }
+
+        #[tokio::test]
+        async fn get_table_columns_fails() {
+            let mut mocks = get_mocks();
+            mocks.expect_get_table_columns().returning(|_, _| {
+                Err(GlyphEngineProcessError::QueryProcessingError(
+                    GlyphxErrorData::new(
+                        "The table could not be described".to_string(),
+                        None,
+                        None,
+                    ),
+                ))
+            });
+
+            let parameters = get_parameters(get_filter("region", json!("east")));
+            let glyph_engine = GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            let result = glyph_engine.validate_filter(&mocks).await;
+            match result.err().unwrap() {
+                GlyphEngineProcessError::QueryProcessingError(_) => {}
+                _ => panic!("Expected QueryProcessingError"),
+            }
+        }
+    }
+
+    mod validate_parameters {
+        use super::*;
+        use crate::data_source::MockDataSource;
+        use glyphx_core::aws::athena_manager::ColumnDataType;
+
+        fn get_input() -> Value {
+            let mut input = INPUT.clone();
+            input["zAxis"]["fieldDataType"] = json!(0);
+            input
+        }
+
+        fn get_columns() -> Vec<ColumnDescription> {
+            ["field1", "field2", "field3", "field4"]
+                .into_iter()
+                .map(|name| ColumnDescription {
+                    name: name.to_string(),
+                    data_type: ColumnDataType::NUMBER,
+                })
+                .collect()
+        }
+
+        fn get_data_source() -> Option<Arc<dyn DataSource>> {
+            Some(Arc::new(MockDataSource::new()))
+        }
+
+        #[tokio::test]
+        async fn is_ok() {
+            let mut mocks = MockGlyphEngineOperations::new();
+            mocks
+                .expect_get_table_columns()
+                .times(1)
+                .returning(|_, table_name| {
+                    assert_eq!(table_name, "my_table");
+                    Ok(get_columns())
+                });
+
+            let problems =
+                GlyphEngine::validate_parameters_impl(&get_input(), get_data_source(), &mocks)
+                    .await;
+            assert!(problems.is_empty());
+        }
+
+        #[tokio::test]
+        async fn missing_column() {
+            let mut mocks = MockGlyphEngineOperations::new();
+            mocks
+                .expect_get_table_columns()
+                .returning(|_, _| Ok(get_columns().into_iter().skip(1).collect()));
+
+            let problems =
+                GlyphEngine::validate_parameters_impl(&get_input(), get_data_source(), &mocks)
+                    .await;
+            assert_eq!(problems.len(), 1);
+            assert_eq!(problems[0].path, "xAxis.fieldDefinition");
+        }
+
+        #[tokio::test]
+        async fn has_no_data_source() {
+            let mut mocks = MockGlyphEngineOperations::new();
+            mocks.expect_get_table_columns().never();
+
+            let problems = GlyphEngine::validate_parameters_impl(&get_input(), None, &mocks).await;
+            assert!(problems.is_empty());
+        }
+
+        #[tokio::test]
+        async fn get_table_columns_fails() {
+            let mut mocks = MockGlyphEngineOperations::new();
+            mocks.expect_get_table_columns().returning(|_, _| {
+                Err(GlyphEngineProcessError::QueryProcessingError(
+                    GlyphxErrorData::new(
+                        "The table could not be described".to_string(),
+                        None,
+                        None,
+                    ),
+                ))
+            });
+
+            let problems =
+                GlyphEngine::validate_parameters_impl(&get_input(), get_data_source(), &mocks)
+                    .await;
+            assert_eq!(problems.len(), 1);
+            assert_eq!(problems[0].path, "data_table_name");
+            assert_eq!(problems[0].message, "The table could not be described");
+        }
+    }
+
+    mod process_query_results {
+        use super::*;
+        use glyphx_core::aws::athena_stream_iterator::{
+            ErrorMetadata, GetQueryResultsError, HttpResponse, InternalServerException, SdkBody,
+            SdkError,
+        };
+        use vector_processer::build_vector_processer_from_json;
+
+        #[tokio::test]
+        async fn is_ok() {
+            //1. Mock out our GlyhEngineOperations
+            let mut mocks = get_setup_mocks();
+
+            mocks
+                .expect_get_upload_stream()
+                .times(1)
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
+                });
+
+            mocks
+                .expect_write_to_upload_stream()
+                .times(12)
+                .returning(|_, _| Ok(()));
+
+            mocks
+                .expect_finish_upload_stream()
+                .times(1)
+                .returning(|_| Ok(()));
+
+            //2. Get our glyph_engine
+            let glyph_engine = get_glyph_engine().await; //  GlyphEngine::new_impl(&parameters, &mocks).await.unwrap();
+
+            //3. Build our field_definitions
+            let x_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("xaxis")
+                .unwrap();
+
+            let y_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("yaxis")
+                .unwrap();
+
+            //4. Mock out our vector processers
+            let x_vector_processor = build_vector_processer_from_json(
+                "x",
+                "test_table",
+                "test_file",
+                x_field_definition.clone(),
+                RESULT_SET.clone(),
+                X_FIELD_NAME.to_string(),
+            );
+
+            let y_vector_processor = build_vector_processer_from_json(
+                "y",
+                "test_table",
+                "test_file",
+                y_field_definition.clone(),
+                RESULT_SET.clone(),
+                Y_FIELD_NAME.to_string(),
+            );
+
+            //5. Mock out our AthenaStream
+            let mut athena_stream = get_mock_athena_stream_iterator(Box::new(move |stream| {
+                if stream.state.counter == 0 {
+                    Some(Ok(get_query_results_set()))
+                } else {
+                    Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
+                }
+            }));
+
+            let result = glyph_engine
+                .process_query_results(
+                    "test_file_name",
+                    &mut athena_stream,
+                    "field1",
+                    "field2",
+                    "field3",
+                    &[],
+                    None,
+                    &[],
+                    &x_vector_processor,
+                    &y_vector_processor,
+                    &mocks,
+                )
+                .await;
+
+            assert!(result.is_ok());
+            let (result, channel_results) = result.unwrap();
+            assert!(channel_results.is_empty());
+            for i in 0..10 {
+                assert_eq!(result[i], ((i + 1) * 3) as f64);
+            }
+        }
+
+        #[tokio::test]
+        async fn is_cancelled() {
+            //1. Mock out our GlyhEngineOperations, the glyph file is aborted after its header
+            let mut mocks = get_setup_mocks();
+
+            mocks
+                .expect_get_upload_stream()
+                .times(1)
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
+                });
+
+            mocks
+                .expect_write_to_upload_stream()
+                .times(1)
+                .returning(|_, _| Ok(()));
+
+            mocks.expect_abort_upload_stream().times(1).return_const(());
+
+            mocks.expect_finish_upload_stream().times(0);
+
+            //2. Get our glyph_engine and cancel it
+            let glyph_engine = get_glyph_engine().await;
+            glyph_engine.get_cancellation_token().cancel();
+
+            //3. Build our field_definitions
+            let x_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("xaxis")
+                .unwrap();
+
+            let y_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("yaxis")
+                .unwrap();
+
+            //4. Mock out our vector processers
+            let x_vector_processor = build_vector_processer_from_json(
+                "x",
+                "test_table",
+                "test_file",
+                x_field_definition.clone(),
+                RESULT_SET.clone(),
+                X_FIELD_NAME.to_string(),
+            );
+
+            let y_vector_processor = build_vector_processer_from_json(
+                "y",
+                "test_table",
+                "test_file",
+                y_field_definition.clone(),
+                RESULT_SET.clone(),
+                Y_FIELD_NAME.to_string(),
+            );
+
+            //5. Mock out our AthenaStream
+            let mut athena_stream = get_mock_athena_stream_iterator(Box::new(move |stream| {
+                if stream.state.counter == 0 {
+                    Some(Ok(get_query_results_set()))
+                } else {
+                    Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
+                }
+            }));
+
+            let result = glyph_engine
+                .process_query_results(
+                    "test_file_name",
+                    &mut athena_stream,
+                    "field1",
+                    "field2",
+                    "field3",
+                    &[],
+                    None,
+                    &[],
+                    &x_vector_processor,
+                    &y_vector_processor,
+                    &mocks,
+                )
+                .await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::Cancelled(_) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+        }
+
+        #[tokio::test]
+        async fn supporting_field_not_found() {
+            //1. Mock out our GlyhEngineOperations, the glyph and supporting files are both opened
+            let mut mocks = get_setup_mocks();
+            mocks
+                .expect_get_upload_stream()
+                .times(2)
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
+                });
+
+            //Both headers and the first glyph are written before the supporting values fail
+            mocks
+                .expect_write_to_upload_stream()
+                .times(3)
+                .returning(|_, _| Ok(()));
+
+            //Both uploads that were opened are aborted, so S3 does not keep their parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(2)
+                .return_const(());
+
+            mocks
+                .expect_finish_upload_stream()
+                .times(0)
+                .returning(|_| Ok(()));
+
+            //2. Get our glyph_engine
+            let glyph_engine = get_glyph_engine().await;
+
+            //3. Build our field_definitions
+            let x_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("xaxis")
+                .unwrap();
+
+            let y_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("yaxis")
+                .unwrap();
+
+            let supporting_fields = vec![SupportingField {
+                field_definition: x_field_definition.clone(),
+                aggregation: SupportingFieldAggregation::First,
+            }];
+
+            //4. Mock out our vector processers
+            let x_vector_processor = build_vector_processer_from_json(
+                "x",
+                "test_table",
+                "test_file",
+                x_field_definition.clone(),
+                RESULT_SET.clone(),
+                X_FIELD_NAME.to_string(),
+            );
+
+            let y_vector_processor = build_vector_processer_from_json(
+                "y",
+                "test_table",
+                "test_file",
+                y_field_definition.clone(),
+                RESULT_SET.clone(),
+                Y_FIELD_NAME.to_string(),
+            );
+
+            //5. Mock out our AthenaStream
+            let mut athena_stream = get_mock_athena_stream_iterator(Box::new(move |stream| {
                 if stream.state.counter == 0 {
                     Some(Ok(get_query_results_set()))
                 } else {
@@ -1810,16 +2902,126 @@ pub mod glyph_engine {
                     "field1",
                     "field2",
                     "field3",
+                    &[],
+                    Some("test_supporting_file_name"),
+                    &supporting_fields,
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
                 )
                 .await;
 
-            assert!(result.is_ok());
-            let result = result.unwrap();
-            for i in 0..10 {
-                assert_eq!(result[i], ((i + 1) * 3) as f64);
+            match result.err().unwrap() {
+                GlyphEngineProcessError::DataProcessingError(_) => {}
+                _ => panic!("Expected DataProcessingError"),
+            }
+        }
+
+        #[tokio::test]
+        async fn get_supporting_upload_stream_fails() {
+            //1. Mock out our GlyhEngineOperations, the glyph file is opened but the supporting
+            //file is not
+            let mut mocks = get_setup_mocks();
+            mocks
+                .expect_get_upload_stream()
+                .withf(|file_name, _| file_name == "test_file_name")
+                .times(1)
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
+                });
+            mocks
+                .expect_get_upload_stream()
+                .withf(|file_name, _| file_name == "test_supporting_file_name")
+                .times(1)
+                .returning(|_, _| {
+                    Err(GetUploadStreamError::UnexpectedError(GlyphxErrorData::new(
+                        "An unexpected error occurred while getting the upload stream".to_string(),
+                        None,
+                        None,
+                    )))
+                });
+
+            //Only the glyph file header is written
+            mocks
+                .expect_write_to_upload_stream()
+                .times(1)
+                .returning(|_, _| Ok(()));
+
+            //The glyph upload is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
+            mocks
+                .expect_finish_upload_stream()
+                .times(0)
+                .returning(|_| Ok(()));
+
+            //2. Get our glyph_engine
+            let glyph_engine = get_glyph_engine().await;
+
+            //3. Build our field_definitions
+            let x_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("xaxis")
+                .unwrap();
+
+            let y_field_definition = glyph_engine
+                .parameters
+                .get_field_definition("yaxis")
+                .unwrap();
+
+            let supporting_fields = vec![SupportingField {
+                field_definition: x_field_definition.clone(),
+                aggregation: SupportingFieldAggregation::First,
+            }];
+
+            //4. Mock out our vector processers
+            let x_vector_processor = build_vector_processer_from_json(
+                "x",
+                "test_table",
+                "test_file",
+                x_field_definition.clone(),
+                RESULT_SET.clone(),
+                X_FIELD_NAME.to_string(),
+            );
+
+            let y_vector_processor = build_vector_processer_from_json(
+                "y",
+                "test_table",
+                "test_file",
+                y_field_definition.clone(),
+                RESULT_SET.clone(),
+                Y_FIELD_NAME.to_string(),
+            );
+
+            //5. Mock out our AthenaStream
+            let mut athena_stream = get_mock_athena_stream_iterator(Box::new(move |stream| {
+                Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
+            }));
+
+            let result = glyph_engine
+                .process_query_results(
+                    "test_file_name",
+                    &mut athena_stream,
+                    "field1",
+                    "field2",
+                    "field3",
+                    &[],
+                    Some("test_supporting_file_name"),
+                    &supporting_fields,
+                    &x_vector_processor,
+                    &y_vector_processor,
+                    &mocks,
+                )
+                .await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::DataProcessingError(_) => {}
+                _ => panic!("Expected DataProcessingError"),
             }
         }
 
@@ -1895,6 +3097,9 @@ pub mod glyph_engine {
                     "field1",
                     "field2",
                     "field3",
+                    &[],
+                    None,
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
@@ -1919,16 +3124,23 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(10)
+                .times(11)
                 .returning(|_, _| Ok(()));
 
+            //The upload that was opened is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
             mocks
                 .expect_finish_upload_stream()
                 .times(0)
@@ -1998,6 +3210,9 @@ pub mod glyph_engine {
                     "field1",
                     "field2",
                     "field3",
+                    &[],
+                    None,
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
@@ -2022,9 +3237,10 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
@@ -2041,6 +3257,12 @@ pub mod glyph_engine {
                     ))
                 });
 
+            //The upload that was opened is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
             mocks
                 .expect_finish_upload_stream()
                 .times(0)
@@ -2095,6 +3317,9 @@ pub mod glyph_engine {
                     "field1",
                     "field2",
                     "field3",
+                    &[],
+                    None,
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
@@ -2119,16 +3344,23 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(10)
+                .times(12)
                 .returning(|_, _| Ok(()));
 
+            //The upload that was opened is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
             mocks.expect_finish_upload_stream().times(1).returning(|_| {
                 Err(UploadStreamFinishError::UnexpectedError(
                     GlyphxErrorData::new(
@@ -2189,6 +3421,9 @@ pub mod glyph_engine {
                     "field1",
                     "field2",
                     "field3",
+                    &[],
+                    None,
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
@@ -2213,16 +3448,23 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(0)
+                .times(1)
                 .returning(|_, _| Ok(()));
 
+            //The upload that was opened is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
             mocks
                 .expect_finish_upload_stream()
                 .times(0)
@@ -2281,6 +3523,9 @@ pub mod glyph_engine {
                     "field1_bad",
                     "field2",
                     "field3",
+                    &[],
+                    None,
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                     &mocks,
@@ -2333,18 +3578,19 @@ pub mod glyph_engine {
                     Y_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.build_glyph(
                     &value,
                     "field1",
                     "field2",
                     "field3",
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                 );
@@ -2355,12 +3601,13 @@ pub mod glyph_engine {
                 assert_eq!(result.z_value, 12.0);
                 assert_eq!(result.row_ids, vec![1, 2, 3]);
             }
+
             #[tokio::test]
-            async fn get_x_vector_fails() {
+            async fn with_channels() {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                //2. Build our field_definitions
+                //2. Build our field_definitions, the z field stands in for our channel
                 let x_field_definition = glyph_engine
                     .parameters
                     .get_field_definition("xaxis")
@@ -2369,6 +3616,10 @@ pub mod glyph_engine {
                     .parameters
                     .get_field_definition("yaxis")
                     .unwrap();
+                let channel_field_definition = glyph_engine
+                    .parameters
+                    .get_field_definition("zaxis")
+                    .unwrap();
                 //3. Mock out our vector processers
                 let x_vector_processor = build_vector_processer_from_json(
                     "x",
@@ -2388,36 +3639,31 @@ pub mod glyph_engine {
                     Y_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
-                    "x_field1_fail": 1.0,
-                    "y_field2": 2.0,
-                    "z_field3": 3.0,
-                    "rowids": "1"
-                });
+                let value = get_glyph_row(json!({
+                    "x_field1": 4.0,
+                    "y_field2": 8.0,
+                    "z_field3": 12.0,
+                    "size_field3": 16.0,
+                    "rowids": "1|2|3"
+                }));
 
                 let result = glyph_engine.build_glyph(
                     &value,
                     "field1",
                     "field2",
                     "field3",
+                    &[(GlyphChannel::Size, channel_field_definition)],
                     &x_vector_processor,
                     &y_vector_processor,
                 );
-                assert!(result.is_err());
-                let result = result.err().unwrap();
-                match result {
-                    GlyphEngineProcessError::DataProcessingError(error_data) => {
-                        assert_eq!(
-                            error_data.message,
-                            "The field x_field1 was not found in the query results"
-                        );
-                    }
-                    _ => panic!("Expected DataProcessingError"),
-                }
+                assert!(result.is_ok());
+                let result = result.unwrap();
+                assert_eq!(result.z_value, 12.0);
+                assert_eq!(result.size_value, Some(16.0));
+                assert!(result.color_value.is_none());
             }
-
             #[tokio::test]
-            async fn get_y_vector_fails() {
+            async fn get_x_vector_fails() {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
@@ -2449,18 +3695,19 @@ pub mod glyph_engine {
                     Y_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
-                    "x_field1": 1.0,
-                    "y_field2_fail": 2.0,
+                let value = get_glyph_row(json!({
+                    "x_field1_fail": 1.0,
+                    "y_field2": 2.0,
                     "z_field3": 3.0,
                     "rowids": "1"
-                });
+                }));
 
                 let result = glyph_engine.build_glyph(
                     &value,
                     "field1",
                     "field2",
                     "field3",
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                 );
@@ -2470,7 +3717,7 @@ pub mod glyph_engine {
                     GlyphEngineProcessError::DataProcessingError(error_data) => {
                         assert_eq!(
                             error_data.message,
-                            "The field y_field2 was not found in the query results"
+                            "The field x_field1 was not found in the query results"
                         );
                     }
                     _ => panic!("Expected DataProcessingError"),
@@ -2478,7 +3725,7 @@ pub mod glyph_engine {
             }
 
             #[tokio::test]
-            async fn get_z_value_fails() {
+            async fn get_y_vector_fails() {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
@@ -2510,18 +3757,19 @@ pub mod glyph_engine {
                     Y_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 1.0,
-                    "y_field2": 2.0,
-                    "z_field3_fail": 3.0,
+                    "y_field2_fail": 2.0,
+                    "z_field3": 3.0,
                     "rowids": "1"
-                });
+                }));
 
                 let result = glyph_engine.build_glyph(
                     &value,
                     "field1",
                     "field2",
                     "field3",
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                 );
@@ -2531,7 +3779,7 @@ pub mod glyph_engine {
                     GlyphEngineProcessError::DataProcessingError(error_data) => {
                         assert_eq!(
                             error_data.message,
-                            "The field z_field3 was not found in the query results"
+                            "The field y_field2 was not found in the query results"
                         );
                     }
                     _ => panic!("Expected DataProcessingError"),
@@ -2539,10 +3787,11 @@ pub mod glyph_engine {
             }
 
             #[tokio::test]
-            async fn get_row_ids_fails() {
+            async fn get_z_value_fails() {
+                //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                // Build our field_definitions
+                //2. Build our field_definitions
                 let x_field_definition = glyph_engine
                     .parameters
                     .get_field_definition("xaxis")
@@ -2551,7 +3800,7 @@ pub mod glyph_engine {
                     .parameters
                     .get_field_definition("yaxis")
                     .unwrap();
-                //Mock out our vector processers
+                //3. Mock out our vector processers
                 let x_vector_processor = build_vector_processer_from_json(
                     "x",
                     "test_table",
@@ -2570,28 +3819,99 @@ pub mod glyph_engine {
                     Y_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 1.0,
                     "y_field2": 2.0,
-                    "z_field3": 3.0,
-                    "rowids_fail": "1"
-                });
+                    "z_field3_fail": 3.0,
+                    "rowids": "1"
+                }));
 
                 let result = glyph_engine.build_glyph(
                     &value,
                     "field1",
                     "field2",
                     "field3",
+                    &[],
                     &x_vector_processor,
                     &y_vector_processor,
                 );
-                assert!(result.is_err());
-                let result = result.err().unwrap();
-                match result {
+                assert!(result.is_err());
+                let result = result.err().unwrap();
+                match result {
+                    GlyphEngineProcessError::DataProcessingError(error_data) => {
+                        assert_eq!(
+                            error_data.message,
+                            "The field z_field3 was not found in the query results"
+                        );
+                    }
+                    _ => panic!("Expected DataProcessingError"),
+                }
+            }
+        }
+
+        mod build_supporting_values {
+            use super::*;
+
+            fn get_supporting_field(
+                name: &str,
+                aggregation: SupportingFieldAggregation,
+            ) -> SupportingField {
+                let field_definition = FieldDefinition::from_json(&json!({
+                    "fieldDisplayName": name,
+                    "fieldDataType": 1,
+                    "fieldDefinition": { "fieldType": "standard", "fieldName": name }
+                }))
+                .unwrap();
+                SupportingField {
+                    field_definition,
+                    aggregation,
+                }
+            }
+
+            #[tokio::test]
+            async fn is_ok() {
+                let glyph_engine = get_glyph_engine().await;
+                let supporting_fields = vec![
+                    get_supporting_field("customer", SupportingFieldAggregation::First),
+                    get_supporting_field("region", SupportingFieldAggregation::DistinctList),
+                    get_supporting_field("units", SupportingFieldAggregation::Sum),
+                ];
+                let value = get_glyph_row(json!({
+                    "rowids": "1|2",
+                    "supporting_customer": "acme",
+                    "supporting_region": r#"["east","west"]"#,
+                    "supporting_units": 7
+                }));
+
+                let result = glyph_engine
+                    .build_supporting_values(&value, 3, &supporting_fields)
+                    .unwrap();
+                assert_eq!(result.glyph_index, 3);
+                assert_eq!(
+                    result.values,
+                    vec![
+                        r#""acme""#.to_string(),
+                        r#"["east","west"]"#.to_string(),
+                        "7".to_string()
+                    ]
+                );
+            }
+
+            #[tokio::test]
+            async fn field_not_found() {
+                let glyph_engine = get_glyph_engine().await;
+                let supporting_fields = vec![get_supporting_field(
+                    "customer",
+                    SupportingFieldAggregation::First,
+                )];
+                let value = get_glyph_row(json!({ "rowids": "1|2" }));
+
+                let result = glyph_engine.build_supporting_values(&value, 0, &supporting_fields);
+                match result.err().unwrap() {
                     GlyphEngineProcessError::DataProcessingError(error_data) => {
                         assert_eq!(
                             error_data.message,
-                            "The field rowids was not found in the query results"
+                            "The field supporting_customer was not found in the query results"
                         );
                     }
                     _ => panic!("Expected DataProcessingError"),
@@ -2623,12 +3943,12 @@ pub mod glyph_engine {
                     X_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_vector(&value, "x_field1", &vector_processor);
                 assert!(result.is_ok());
@@ -2659,12 +3979,12 @@ pub mod glyph_engine {
                     X_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_vector(&value, "x_field1_bad", &vector_processor);
                 assert!(result.is_err());
@@ -2702,12 +4022,12 @@ pub mod glyph_engine {
                     X_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": true,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_vector(&value, "x_field1", &vector_processor);
                 assert!(result.is_err());
@@ -2744,12 +4064,12 @@ pub mod glyph_engine {
                     X_FIELD_NAME.to_string(),
                 );
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 444.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_vector(&value, "x_field1", &vector_processor);
 
@@ -2775,12 +4095,12 @@ pub mod glyph_engine {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_z_value(&value, "z_field3");
                 assert!(result.is_ok());
@@ -2793,12 +4113,12 @@ pub mod glyph_engine {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
                     "z_field3": 12.0,
                     "rowids": "1|2|3"
-                });
+                }));
 
                 let result = glyph_engine.get_z_value(&value, "z_field3_bad");
                 assert!(result.is_err());
@@ -2815,131 +4135,84 @@ pub mod glyph_engine {
             }
 
             #[tokio::test]
-            async fn z_is_not_a_number() {
+            async fn decimal_z_column() {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                let value: Value = json!({
-                    "x_field1": 4.0,
-                    "y_field2": 8.0,
-                    "z_field3": "string",
-                    "rowids": "1|2|3"
-                });
-
-                let result = glyph_engine.get_z_value(&value, "z_field3");
-                assert!(result.is_err());
-                let result = result.err().unwrap();
-                match result {
-                    GlyphEngineProcessError::DataProcessingError(error_data) => {
-                        assert_eq!(
-                            error_data.message,
-                            "The field z_field3 was not a number in the query results"
-                        );
+                //2. Read a decimal column the way AthenaDataSource does, SUM and AVG over a
+                //decimal are decimals too.
+                let column = |name: &str, data_type: &str| {
+                    ColumnInfo::builder()
+                        .name(name)
+                        .r#type(data_type)
+                        .nullable(ColumnNullable::Nullable)
+                        .build()
+                };
+                let row = |values: [&str; 4]| {
+                    let mut row = Row::builder();
+                    for value in values {
+                        row = row.data(Datum::builder().var_char_value(value).build());
                     }
-                    _ => panic!("Expected DataProcessingError"),
-                }
-            }
-        }
-
-        mod get_row_ids {
-            use super::*;
-
-            #[tokio::test]
-            async fn is_ok() {
-                //1. Get our glyph_engine
-                let glyph_engine = get_glyph_engine().await;
-
-                let value: Value = json!({
-                    "x_field1": 4.0,
-                    "y_field2": 8.0,
-                    "z_field3": 12.0,
-                    "rowids": "1|2|3"
-                });
+                    row.build()
+                };
+                let metadata = ResultSetMetadata::builder()
+                    .column_info(column("rowids", "varchar"))
+                    .column_info(column("x_field1", "integer"))
+                    .column_info(column("y_field2", "integer"))
+                    .column_info(column("z_field3", "decimal(38,2)"))
+                    .build();
+                let result_set = ResultSet::builder()
+                    .result_set_metadata(metadata)
+                    .rows(row(["rowids", "x_field1", "y_field2", "z_field3"]))
+                    .rows(row(["1|2|3", "4", "8", "12345.67"]))
+                    .build();
+                let page: Result<GetQueryResultsOutput, SdkError<GetQueryResultsError>> =
+                    Ok(GetQueryResultsOutput::builder().result_set(result_set).build());
+                let mut iterator = AthenaStreamIterator::new(
+                    Box::new(tokio_stream::iter(vec![page])),
+                    "query_id",
+                    "catalog",
+                    "database",
+                )
+                .with_conversion_options(
+                    ConversionOptions::new()
+                        .with_report_conversion_failures(true)
+                        .with_lossy_decimals(true),
+                );
+                let value = iterator.next_as::<GlyphQueryRow>().await.unwrap().unwrap();
 
-                let result = glyph_engine.get_row_ids(&value);
+                let result = glyph_engine.get_z_value(&value, "z_field3");
                 assert!(result.is_ok());
-                let result = result.unwrap();
-                assert_eq!(result, vec![1, 2, 3]);
-            }
-
-            #[tokio::test]
-            async fn field_is_not_in_the_result() {
-                //1. Get our glyph_engine
-                let glyph_engine = get_glyph_engine().await;
-
-                let value: Value = json!({
-                    "x_field1": 4.0,
-                    "y_field2": 8.0,
-                    "z_field3": 12.0,
-                    "rowids_fail": "1|2|3"
-                });
-
-                let result = glyph_engine.get_row_ids(&value);
-                assert!(result.is_err());
-                let result = result.err().unwrap();
-                match result {
-                    GlyphEngineProcessError::DataProcessingError(error_data) => {
-                        assert_eq!(
-                            error_data.message,
-                            "The field rowids was not found in the query results"
-                        );
-                    }
-                    _ => panic!("Expected DataProcessingError"),
-                }
-            }
-
-            #[tokio::test]
-            async fn rowids_is_not_a_string() {
-                //1. Get our glyph_engine
-                let glyph_engine = get_glyph_engine().await;
-
-                let value: Value = json!({
-                    "x_field1": 4.0,
-                    "y_field2": 8.0,
-                    "z_field3": 12.0,
-                    "rowids": 123
-                });
-
-                let result = glyph_engine.get_row_ids(&value);
-                assert!(result.is_err());
-                let result = result.err().unwrap();
-                match result {
-                    GlyphEngineProcessError::DataProcessingError(error_data) => {
-                        assert_eq!(
-                            error_data.message,
-                            "The field rowids was not a string in the query results"
-                        );
-                    }
-                    _ => panic!("Expected DataProcessingError"),
-                }
+                assert_eq!(result.unwrap(), 12345.67);
             }
 
             #[tokio::test]
-            async fn rowids_is_malformed() {
+            async fn z_is_not_a_number() {
                 //1. Get our glyph_engine
                 let glyph_engine = get_glyph_engine().await;
 
-                let value: Value = json!({
+                let value = get_glyph_row(json!({
                     "x_field1": 4.0,
                     "y_field2": 8.0,
-                    "z_field3": 12.0,
-                    "rowids": "1|a|2|3"
-                });
+                    "z_field3": "string",
+                    "rowids": "1|2|3"
+                }));
 
-                let result = glyph_engine.get_row_ids(&value);
+                let result = glyph_engine.get_z_value(&value, "z_field3");
                 assert!(result.is_err());
                 let result = result.err().unwrap();
                 match result {
                     GlyphEngineProcessError::DataProcessingError(error_data) => {
                         assert_eq!(
                             error_data.message,
-                            "The field rowids was not a pipe delimited string of integers"
+                            "The field z_field3 was not a number in the query results"
                         );
                     }
                     _ => panic!("Expected DataProcessingError"),
                 }
             }
         }
+
     }
     mod process {
         use super::*;
@@ -2956,7 +4229,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
 
@@ -2965,7 +4238,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3040,21 +4313,22 @@ pub mod glyph_engine {
                 .returning(move |_, _| Ok(AthenaQueryStatus::Succeeded));
 
             mocks.expect_get_query_results().times(1).returning(|_, _| {
-                Ok(get_mock_athena_stream_iterator(Box::new(move |stream| {
+                Ok(Box::new(get_mock_athena_stream_iterator(Box::new(move |stream| {
                     if stream.state.counter == 0 {
                         Some(Ok(get_query_results_set()))
                     } else {
                         Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
                     }
-                })))
+                }))))
             });
 
             mocks
                 .expect_get_upload_stream()
                 .times(2)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
@@ -3064,7 +4338,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(13)
+                .times(17)
                 .returning(|_, _| Ok(()));
 
             mocks
@@ -3143,6 +4417,35 @@ pub mod glyph_engine {
             }
         }
 
+        #[tokio::test]
+        async fn no_x_axis_without_process_tracking() {
+            //1. A local run has no process tracking document, so none of it is touched
+            let mut mocks = MockGlyphEngineOperations::new();
+            mocks.expect_add_process_tracking_error().times(0);
+            mocks.expect_complete_process_tracking().times(0);
+            mocks.expect_stop_heartbeat().times(0);
+            //2. Get our local glyph_engine
+
+            let mut input = INPUT.clone();
+
+            input["xAxis"].take();
+
+            let parameters = VectorizerParameters::from_json_string(&input.to_string()).unwrap();
+            let data_source: Arc<dyn DataSource> =
+                Arc::new(data_source::MockDataSource::new());
+            let object_store: Arc<dyn ObjectStore> = Arc::new(S3Manager::default());
+            let mut glyph_engine =
+                GlyphEngine::new_with_data_source(&parameters, data_source, object_store)
+                    .await
+                    .unwrap();
+            let result = glyph_engine.process_impl(&mocks).await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::ConfigurationError(_) => {}
+                _ => panic!("Expected ConfigurationError"),
+            }
+        }
+
         #[tokio::test]
         async fn no_y_axis() {
             //1. Mock out our GlyhEngineOperations
@@ -3230,7 +4533,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| {
                     Err(GlyphEngineProcessError::QueryProcessingError(
@@ -3276,7 +4579,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
 
@@ -3285,7 +4588,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3370,6 +4673,205 @@ pub mod glyph_engine {
             }
         }
 
+        #[tokio::test]
+        async fn cancelled_before_start() {
+            //1. Mock out our GlyhEngineOperations, nothing is started once we have been cancelled
+            let mut mocks = get_setup_mocks();
+            mocks.expect_start_glyph_query().times(0);
+            mocks.expect_get_vector_processer().times(0);
+            mocks.expect_add_process_tracking_error().times(0);
+            mocks
+                .expect_complete_process_tracking()
+                .withf(|_, status, value| {
+                    matches!(status, ProcessStatus::Cancelled) && value.is_none()
+                })
+                .times(1)
+                .returning(|_, _, _| Ok(()));
+            mocks.expect_stop_heartbeat().times(1).return_const(());
+
+            //2. Get our glyph_engine and cancel it
+            let mut glyph_engine = get_glyph_engine().await;
+            glyph_engine.get_cancellation_token().cancel();
+            let result = glyph_engine.process_impl(&mocks).await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::Cancelled(_) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+        }
+
+        #[tokio::test]
+        async fn cancelled_while_processing_vectors() {
+            let query_id = "12345".to_string();
+
+            //1. Mock out our GlyhEngineOperations
+            let mut mocks = get_setup_mocks();
+            mocks
+                .expect_start_glyph_query()
+                .times(1)
+                .returning(move |_, _| Ok(query_id.clone()));
+
+            mocks
+                .expect_get_vector_processer()
+                .times(2)
+                .returning(|axis, _, _, _, _, _, _, _, _| {
+                    let mut vector_processer_mock = MockVectorValueProcesser::new();
+                    vector_processer_mock
+                        .expect_get_axis_name()
+                        .return_const(axis.to_string());
+                    vector_processer_mock.expect_run_sync().returning(|| {
+                        TaskStatus::Errored(VectorCalculationError::Cancelled(
+                            GlyphxErrorData::new("cancelled".to_string(), None, None),
+                        ))
+                    });
+                    Box::new(vector_processer_mock)
+                });
+
+            mocks
+                .expect_stop_glyph_query()
+                .withf(|_, query_id| query_id == "12345")
+                .times(1)
+                .returning(|_, _| Ok(()));
+            mocks.expect_check_query_status().times(0);
+            mocks.expect_add_process_tracking_error().times(0);
+            mocks
+                .expect_complete_process_tracking()
+                .withf(|_, status, _| matches!(status, ProcessStatus::Cancelled))
+                .times(1)
+                .returning(|_, _, _| Ok(()));
+            mocks.expect_stop_heartbeat().times(1).return_const(());
+
+            //2. Get our glyph_engine
+            let mut glyph_engine = get_glyph_engine().await;
+            let result = glyph_engine.process_impl(&mocks).await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::Cancelled(_) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+        }
+
+        #[tokio::test]
+        async fn cancelled_while_polling() {
+            let query_id = "12345".to_string();
+
+            //1. Get our glyph_engine so that the query status can cancel it
+            let mut glyph_engine = get_glyph_engine().await;
+            let cancellation_token = glyph_engine.get_cancellation_token();
+
+            //2. Mock out our GlyhEngineOperations
+            let mut mocks = get_setup_mocks();
+            mocks
+                .expect_start_glyph_query()
+                .times(1)
+                .returning(move |_, _| Ok(query_id.clone()));
+
+            mocks
+                .expect_get_vector_processer()
+                .times(2)
+                .returning(|axis, _, _, _, _, _, _, _, _| {
+                    let mut vector_processer_mock = MockVectorValueProcesser::new();
+                    vector_processer_mock
+                        .expect_get_axis_name()
+                        .return_const(axis.to_string());
+                    vector_processer_mock
+                        .expect_run_sync()
+                        .times(1)
+                        .return_const(TaskStatus::Complete);
+                    Box::new(vector_processer_mock)
+                });
+
+            mocks
+                .expect_check_query_status()
+                .times(1)
+                .returning(move |_, _| {
+                    cancellation_token.cancel();
+                    Ok(AthenaQueryStatus::Running)
+                });
+            mocks
+                .expect_stop_glyph_query()
+                .withf(|_, query_id| query_id == "12345")
+                .times(1)
+                .returning(|_, _| Ok(()));
+            mocks.expect_get_query_results().times(0);
+            mocks.expect_add_process_tracking_error().times(0);
+            mocks
+                .expect_complete_process_tracking()
+                .withf(|_, status, _| matches!(status, ProcessStatus::Cancelled))
+                .times(1)
+                .returning(|_, _, _| Ok(()));
+            mocks.expect_stop_heartbeat().times(1).return_const(());
+
+            let result = glyph_engine.process_impl(&mocks).await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::Cancelled(_) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+        }
+
+        #[tokio::test]
+        async fn query_times_out() {
+            let query_id = "12345".to_string();
+
+            //1. Get a glyph_engine that will not wait for the query
+            let mut glyph_engine = get_glyph_engine()
+                .await
+                .with_polling_policy(PollingPolicy::new().with_max_wait(Duration::ZERO));
+
+            //2. Mock out our GlyhEngineOperations
+            let mut mocks = get_setup_mocks();
+            mocks
+                .expect_start_glyph_query()
+                .times(1)
+                .returning(move |_, _| Ok(query_id.clone()));
+
+            mocks
+                .expect_get_vector_processer()
+                .times(2)
+                .returning(|axis, _, _, _, _, _, _, _, _| {
+                    let mut vector_processer_mock = MockVectorValueProcesser::new();
+                    vector_processer_mock
+                        .expect_get_axis_name()
+                        .return_const(axis.to_string());
+                    vector_processer_mock
+                        .expect_run_sync()
+                        .times(1)
+                        .return_const(TaskStatus::Complete);
+                    Box::new(vector_processer_mock)
+                });
+
+            mocks
+                .expect_check_query_status()
+                .times(1)
+                .returning(|_, _| Ok(AthenaQueryStatus::Running));
+            mocks
+                .expect_stop_glyph_query()
+                .withf(|_, query_id| query_id == "12345")
+                .times(1)
+                .returning(|_, _| Ok(()));
+            mocks.expect_get_query_results().times(0);
+            mocks
+                .expect_add_process_tracking_error()
+                .times(1)
+                .return_const(Ok(()));
+            mocks
+                .expect_complete_process_tracking()
+                .withf(|_, status, _| matches!(status, ProcessStatus::Failed))
+                .times(1)
+                .returning(|_, _, _| Ok(()));
+            mocks.expect_stop_heartbeat().times(1).return_const(());
+
+            let result = glyph_engine.process_impl(&mocks).await;
+
+            match result.err().unwrap() {
+                GlyphEngineProcessError::QueryTimedOut(data) => {
+                    assert_eq!(data.data.unwrap()["query_id"], "12345")
+                }
+                _ => panic!("Expected QueryTimedOut"),
+            }
+        }
+
         #[tokio::test]
         async fn check_query_status_fails() {
             let query_id = "12345".to_string();
@@ -3377,7 +4879,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
             static mut CALL_NUMBER: usize = 0;
@@ -3385,7 +4887,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3491,7 +4993,7 @@ pub mod glyph_engine {
             let mut mocks = get_setup_mocks();
 
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
 
@@ -3500,7 +5002,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3565,13 +5067,25 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
+            mocks
+                .expect_write_to_upload_stream()
+                .times(1)
+                .returning(|_, _| Ok(()));
+
+            //The upload that was opened is aborted, so S3 does not keep its parts.
+            mocks
+                .expect_abort_upload_stream()
+                .times(1)
+                .return_const(());
+
             mocks.expect_get_query_results().times(1).returning(|_, _| {
-                Ok(get_mock_athena_stream_iterator(Box::new(move |_| {
+                Ok(Box::new(get_mock_athena_stream_iterator(Box::new(move |_| {
                     let body = SdkBody::from("An error occurred".to_string());
                     let metadata = ErrorMetadata::builder()
                         .code("500")
@@ -3589,7 +5103,7 @@ pub mod glyph_engine {
                         SdkError::service_error(query_response_error, raw);
 
                     Some(Err(error))
-                })))
+                }))))
             });
 
             mocks
@@ -3630,7 +5144,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
             static mut CALL_NUMBER: usize = 0;
@@ -3638,7 +5152,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3713,21 +5227,22 @@ pub mod glyph_engine {
                 .returning(move |_, _| Ok(AthenaQueryStatus::Succeeded));
 
             mocks.expect_get_query_results().times(1).returning(|_, _| {
-                Ok(get_mock_athena_stream_iterator(Box::new(move |stream| {
+                Ok(Box::new(get_mock_athena_stream_iterator(Box::new(move |stream| {
                     if stream.state.counter == 0 {
                         Some(Ok(get_query_results_set()))
                     } else {
                         Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
                     }
-                })))
+                }))))
             });
 
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks.expect_get_upload_stream().times(1).returning(|_, _| {
@@ -3744,7 +5259,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(10)
+                .times(12)
                 .returning(|_, _| Ok(()));
 
             mocks
@@ -3777,7 +5292,7 @@ pub mod glyph_engine {
             //1. Mock out our GlyhEngineOperations
             let mut mocks = get_setup_mocks();
             mocks
-                .expect_start_athena_query()
+                .expect_start_glyph_query()
                 .times(1)
                 .returning(move |_, _| Ok(query_id.clone()));
             static mut CALL_NUMBER: usize = 0;
@@ -3785,7 +5300,7 @@ pub mod glyph_engine {
             mocks
                 .expect_get_vector_processer()
                 .times(2)
-                .returning(|_, _, _, _| unsafe {
+                .returning(|_, _, _, _, _, _, _, _, _| unsafe {
                     //This is where we setup our vector processer moocks.  Add a get_vector
                     //expectation here so we can build our glyphs
                     CALL_NUMBER += 1;
@@ -3860,21 +5375,22 @@ pub mod glyph_engine {
                 .returning(move |_, _| Ok(AthenaQueryStatus::Succeeded));
 
             mocks.expect_get_query_results().times(1).returning(|_, _| {
-                Ok(get_mock_athena_stream_iterator(Box::new(move |stream| {
+                Ok(Box::new(get_mock_athena_stream_iterator(Box::new(move |stream| {
                     if stream.state.counter == 0 {
                         Some(Ok(get_query_results_set()))
                     } else {
                         Some(Ok(stream.get_query_results_set(Some(())))) //End the stream
                     }
-                })))
+                }))))
             });
 
             mocks
                 .expect_get_upload_stream()
                 .times(2)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
@@ -3884,7 +5400,7 @@ pub mod glyph_engine {
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(13)
+                .times(17)
                 .returning(|_, _| Ok(()));
 
             mocks
@@ -3978,14 +5494,15 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(3)
+                .times(5)
                 .returning(|_, _| Ok(()));
 
             mocks
@@ -3994,7 +5511,7 @@ pub mod glyph_engine {
                 .returning(|_| Ok(()));
 
             let result = glyph_engine
-                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, &mocks)
+                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, vec![], &mocks)
                 .await;
 
             assert!(result.is_ok());
@@ -4052,7 +5569,7 @@ pub mod glyph_engine {
             });
 
             let result = glyph_engine
-                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, &mocks)
+                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, vec![], &mocks)
                 .await;
 
             assert!(result.is_err());
@@ -4109,9 +5626,10 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
@@ -4128,7 +5646,7 @@ pub mod glyph_engine {
                 });
 
             let result = glyph_engine
-                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, &mocks)
+                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, vec![], &mocks)
                 .await;
 
             assert!(result.is_err());
@@ -4185,14 +5703,15 @@ pub mod glyph_engine {
             mocks
                 .expect_get_upload_stream()
                 .times(1)
-                .returning(|_, _| unsafe {
-                    let client = S3_CONNECTION_INSTANCE.as_ref().unwrap().clone();
-                    Ok(UploadStream::empty(client.get_s3_manager().get_client()))
+                .returning(|_, _| {
+                    Ok(Box::new(UploadStream::empty(
+                        S3Manager::default().get_client(),
+                    )))
                 });
 
             mocks
                 .expect_write_to_upload_stream()
-                .times(3)
+                .times(5)
                 .returning(|_, _| Ok(()));
 
             mocks.expect_finish_upload_stream().times(1).returning(|_| {
@@ -4206,7 +5725,7 @@ pub mod glyph_engine {
             });
 
             let result = glyph_engine
-                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, &mocks)
+                .calculate_statistics(&x_vector_processor, &y_vector_processor, z_vectors, vec![], &mocks)
                 .await;
 
             assert!(result.is_err());
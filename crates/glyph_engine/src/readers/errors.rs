@@ -0,0 +1,79 @@
+use glyphx_core::{GlyphxError, GlyphxErrorData};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, GlyphxError, PartialEq, Serialize, Deserialize)]
+#[error_definition("Reader")]
+pub enum ReaderError {
+    ///The underlying reader returned an error.
+    ReadError(GlyphxErrorData),
+    ///The file ended part way through a size prefix or a record.
+    TruncatedRecord(GlyphxErrorData),
+    ///A record could not be deserialized or its size does not match its size prefix.
+    CorruptRecord(GlyphxErrorData),
+    ///The file starts with MAGIC but its header can not be read, is from a newer version of the
+    ///format or describes a different type of file.
+    InvalidHeader(GlyphxErrorData),
+    ///The footer is missing or does not agree with the records that were read.
+    InvalidFooter(GlyphxErrorData),
+}
+
+impl ReaderError {
+    pub fn from_io_error(error: std::io::Error, record_number: u64) -> Self {
+        let message = format!(
+            "An error occurred while reading record {}: {}",
+            record_number, error
+        );
+        let data = json!({ "record_number": record_number });
+        Self::ReadError(GlyphxErrorData::new(message, Some(data), None))
+    }
+
+    pub fn truncated(record_number: u64, expected_bytes: u64, read_bytes: u64) -> Self {
+        let message = format!(
+            "Record {} is truncated, expected {} bytes but only {} were available",
+            record_number, expected_bytes, read_bytes
+        );
+        let data = json!({
+            "record_number": record_number,
+            "expected_bytes": expected_bytes,
+            "read_bytes": read_bytes
+        });
+        Self::TruncatedRecord(GlyphxErrorData::new(message, Some(data), None))
+    }
+
+    pub fn corrupt(record_number: u64, reason: &str) -> Self {
+        let message = format!("Record {} is corrupt: {}", record_number, reason);
+        let data = json!({ "record_number": record_number });
+        Self::CorruptRecord(GlyphxErrorData::new(message, Some(data), None))
+    }
+
+    pub fn invalid_header(reason: &str) -> Self {
+        let message = format!("The file header is invalid: {}", reason);
+        Self::InvalidHeader(GlyphxErrorData::new(message, None, None))
+    }
+
+    pub fn invalid_footer(record_number: u64, reason: &str) -> Self {
+        let message = format!("The file footer is invalid: {}", reason);
+        let data = json!({ "record_number": record_number });
+        Self::InvalidFooter(GlyphxErrorData::new(message, Some(data), None))
+    }
+}
+
+#[cfg(test)]
+mod truncated {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let error = ReaderError::truncated(3, 28, 10);
+        match error {
+            ReaderError::TruncatedRecord(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["record_number"], 3);
+                assert_eq!(data["expected_bytes"], 28);
+                assert_eq!(data["read_bytes"], 10);
+            }
+            _ => panic!("Expected TruncatedRecord"),
+        }
+    }
+}
@@ -0,0 +1,576 @@
+//! Readers for the binary files that the engine writes.  The layout of our .vec, .gly, .sts and
+//! .sup files is described in crate::file_format.  The readers validate the header and footer of the
+//! files that have them and still read legacy files, which are just the records.  The record
+//! count and checksum are in the footer rather than the header, so a reader only knows how many
+//! records a file holds, and that they are intact, once it has read all of them.  The readers
+//! work over any AsyncRead, so a local file can be read directly and an S3 ByteStream can be read
+//! via ByteStream::into_async_read.
+mod errors;
+
+pub use crate::file_format::BinaryRecord;
+pub use errors::ReaderError;
+
+use crate::file_format::{
+    FileFooter, FileHeader, FOOTER_MARKER, FORMAT_VERSION, LEGACY_FORMAT_VERSION, MAGIC,
+};
+use crate::types::{Glyph, Stats, SupportingValues};
+use crate::vector_processer::Vector;
+
+use bincode::deserialize;
+use crc32fast::Hasher;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+///No record that we write comes close to this size, so a larger size prefix means that we are
+///not reading one of our files, or that it is corrupt.  Checking it keeps us from trying to
+///allocate an absurd buffer.
+const MAX_RECORD_SIZE: u64 = 1024 * 1024 * 1024;
+
+///Reads the records of type T from reader one at a time, so a file never has to be held in
+///memory.
+pub struct RecordReader<R: AsyncRead, T: BinaryRecord> {
+    reader: Pin<Box<R>>,
+    record_number: u64,
+    header: Option<FileHeader>,
+    header_read: bool,
+    format_version: u32,
+    //When a file does not start with MAGIC, the bytes we read looking for it are the start of
+    //the first size prefix.
+    pending_prefix: Option<([u8; 8], usize)>,
+    hasher: Hasher,
+    finished: bool,
+    record_type: PhantomData<T>,
+}
+
+///Reads the Vectors from a .vec file.
+pub type VectorFileReader<R> = RecordReader<R, Vector>;
+///Reads the Glyphs from a .gly file.
+pub type GlyphFileReader<R> = RecordReader<R, Glyph>;
+///Reads the x, y and z Stats from a .sts file.
+pub type StatsFileReader<R> = RecordReader<R, Stats>;
+///Reads the aggregated supporting field values of each glyph from a .sup file.
+pub type SupportingFileReader<R> = RecordReader<R, SupportingValues>;
+
+impl<R: AsyncRead, T: BinaryRecord> RecordReader<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: Box::pin(reader),
+            record_number: 0,
+            header: None,
+            header_read: false,
+            format_version: LEGACY_FORMAT_VERSION,
+            pending_prefix: None,
+            hasher: Hasher::new(),
+            finished: false,
+            record_type: PhantomData,
+        }
+    }
+
+    ///Returns the number of records that have been read so far.  This is the number of records in
+    ///the file once next has returned None, at which point it has been checked against the footer.
+    pub fn get_record_count(&self) -> u64 {
+        self.record_number
+    }
+
+    ///Returns the format version of the file, which is only known once the header has been read.
+    pub fn get_format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    ///Reads and validates the header if it has not been read yet.  Returns None for a legacy file
+    ///that was written without one.
+    pub async fn read_header(&mut self) -> Result<Option<&FileHeader>, ReaderError> {
+        if !self.header_read {
+            let mut magic = [0u8; 8];
+            let read_bytes = self.read_fully(&mut magic).await?;
+            if read_bytes == MAGIC.len() && magic == MAGIC {
+                let header = self.read_file_header().await?;
+                self.header = Some(header);
+            } else {
+                self.pending_prefix = Some((magic, read_bytes));
+            }
+            self.header_read = true;
+        }
+        Ok(self.header.as_ref())
+    }
+
+    ///Returns the next record, or None once the file has been read.  A file that ends part way
+    ///through a record returns a TruncatedRecord error, and a file with a header returns an
+    ///InvalidFooter error if its footer is missing or does not match the records.  The footer is
+    ///only checked after the last record, so the records before it have already been returned.
+    pub async fn next(&mut self) -> Result<Option<T>, ReaderError> {
+        if self.finished {
+            return Ok(None);
+        }
+        self.read_header().await?;
+
+        let mut size_buffer = [0u8; 8];
+        let read_bytes = match self.pending_prefix.take() {
+            Some((prefix, read_bytes)) => {
+                size_buffer = prefix;
+                read_bytes
+            }
+            None => self.read_fully(&mut size_buffer).await?,
+        };
+        if read_bytes == 0 {
+            if self.header.is_some() {
+                return Err(ReaderError::invalid_footer(
+                    self.record_number,
+                    "the file ends without a footer",
+                ));
+            }
+            self.finished = true;
+            return Ok(None);
+        }
+        if read_bytes < size_buffer.len() {
+            return Err(ReaderError::truncated(
+                self.record_number,
+                size_buffer.len() as u64,
+                read_bytes as u64,
+            ));
+        }
+        let record_size: u64 = deserialize(&size_buffer).unwrap();
+        if record_size == FOOTER_MARKER && self.header.is_some() {
+            self.read_footer().await?;
+            self.finished = true;
+            return Ok(None);
+        }
+        if record_size > MAX_RECORD_SIZE {
+            return Err(ReaderError::corrupt(
+                self.record_number,
+                &format!("the size prefix {} is too large", record_size),
+            ));
+        }
+
+        let mut record_buffer = vec![0u8; record_size as usize];
+        let read_bytes = self.read_fully(&mut record_buffer).await?;
+        if read_bytes < record_buffer.len() {
+            return Err(ReaderError::truncated(
+                self.record_number,
+                record_size,
+                read_bytes as u64,
+            ));
+        }
+        //The record must account for every byte of its size prefix, anything else means that
+        //the prefix or the record is corrupt.
+        let record = T::deserialize_version(&record_buffer, self.format_version);
+        if record.is_err() {
+            return Err(ReaderError::corrupt(
+                self.record_number,
+                &record.err().unwrap().to_string(),
+            ));
+        }
+        let record = record.unwrap();
+        self.hasher.update(&size_buffer);
+        self.hasher.update(&record_buffer);
+        self.record_number += 1;
+        Ok(Some(record))
+    }
+
+    ///Reads everything that follows MAGIC in the header.
+    async fn read_file_header(&mut self) -> Result<FileHeader, ReaderError> {
+        let mut version_buffer = [0u8; 4];
+        let read_bytes = self.read_fully(&mut version_buffer).await?;
+        if read_bytes < version_buffer.len() {
+            return Err(ReaderError::invalid_header(
+                "the file ends inside the header",
+            ));
+        }
+        let version: u32 = deserialize(&version_buffer).unwrap();
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(ReaderError::invalid_header(&format!(
+                "format version {} is not supported, the latest supported version is {}",
+                version, FORMAT_VERSION
+            )));
+        }
+
+        let mut size_buffer = [0u8; 8];
+        let read_bytes = self.read_fully(&mut size_buffer).await?;
+        if read_bytes < size_buffer.len() {
+            return Err(ReaderError::invalid_header(
+                "the file ends inside the header",
+            ));
+        }
+        let header_size: u64 = deserialize(&size_buffer).unwrap();
+        if header_size > MAX_RECORD_SIZE {
+            return Err(ReaderError::invalid_header(&format!(
+                "the header size {} is too large",
+                header_size
+            )));
+        }
+        let mut header_buffer = vec![0u8; header_size as usize];
+        let read_bytes = self.read_fully(&mut header_buffer).await?;
+        if read_bytes < header_buffer.len() {
+            return Err(ReaderError::invalid_header(
+                "the file ends inside the header",
+            ));
+        }
+        let header = deserialize::<FileHeader>(&header_buffer);
+        if header.is_err() {
+            return Err(ReaderError::invalid_header(
+                &header.err().unwrap().to_string(),
+            ));
+        }
+        let header = header.unwrap();
+        self.format_version = version;
+        if header.file_type != T::FILE_TYPE {
+            return Err(ReaderError::invalid_header(&format!(
+                "expected a {:?} file but the header is for a {:?} file",
+                T::FILE_TYPE,
+                header.file_type
+            )));
+        }
+        Ok(header)
+    }
+
+    ///Reads everything that follows FOOTER_MARKER and checks it against the records that we
+    ///have read.
+    async fn read_footer(&mut self) -> Result<(), ReaderError> {
+        let mut footer_buffer = [0u8; FileFooter::BINARY_SIZE];
+        let read_bytes = self.read_fully(&mut footer_buffer).await?;
+        if read_bytes < footer_buffer.len() {
+            return Err(ReaderError::invalid_footer(
+                self.record_number,
+                "the file ends inside the footer",
+            ));
+        }
+        let footer: FileFooter = deserialize(&footer_buffer).unwrap();
+        if footer.record_count != self.record_number {
+            return Err(ReaderError::invalid_footer(
+                self.record_number,
+                &format!(
+                    "the footer records {} records but the file holds {}",
+                    footer.record_count, self.record_number
+                ),
+            ));
+        }
+        let checksum = self.hasher.clone().finalize();
+        if footer.checksum != checksum {
+            return Err(ReaderError::invalid_footer(
+                self.record_number,
+                &format!(
+                    "the checksum of the records is {} but the footer records {}",
+                    checksum, footer.checksum
+                ),
+            ));
+        }
+        let mut trailing_buffer = [0u8; 1];
+        let read_bytes = self.read_fully(&mut trailing_buffer).await?;
+        if read_bytes != 0 {
+            return Err(ReaderError::invalid_footer(
+                self.record_number,
+                "there is data after the footer",
+            ));
+        }
+        Ok(())
+    }
+
+    ///Reads until buffer is full or we reach the end of the file and returns the number of
+    ///bytes read.
+    async fn read_fully(&mut self, buffer: &mut [u8]) -> Result<usize, ReaderError> {
+        let mut read_bytes = 0;
+        while read_bytes < buffer.len() {
+            let result = self.reader.read(&mut buffer[read_bytes..]).await;
+            if result.is_err() {
+                return Err(ReaderError::from_io_error(
+                    result.err().unwrap(),
+                    self.record_number,
+                ));
+            }
+            let count = result.unwrap();
+            if count == 0 {
+                break;
+            }
+            read_bytes += count;
+        }
+        Ok(read_bytes)
+    }
+}
+
+#[cfg(test)]
+mod next {
+    use super::*;
+    use crate::file_format::{FileType, HeaderField, RecordEncoder};
+    use crate::types::{FieldType, GlyphChannel, GlyphV1};
+    use crate::vector_processer::{VectorOrigionalValue, VectorV2};
+    use bincode::serialize;
+
+    //Legacy files hold the records without a header or footer.
+    fn write_record<T: serde::Serialize>(record: &T, buffer: &mut Vec<u8>) {
+        let mut record = serialize(record).unwrap();
+        buffer.append(&mut serialize(&record.len()).unwrap());
+        buffer.append(&mut record);
+    }
+
+    //Glyphs in legacy files use the version 1 layout.
+    fn get_legacy_glyph(z_value: f64, row_ids: Vec<usize>) -> GlyphV1 {
+        GlyphV1 {
+            x_value: 1.0,
+            y_value: 2.0,
+            z_value,
+            row_ids,
+        }
+    }
+
+    fn get_vectors() -> Vec<Vector> {
+        vec![
+            Vector::new(VectorOrigionalValue::String("a".to_string()), 0.0, 0),
+            Vector::new(VectorOrigionalValue::F64(1.5), 1.0, 1),
+            Vector::new(VectorOrigionalValue::U64(7), 2.0, 2),
+        ]
+    }
+
+    //Vectors in legacy files use the version 2 layout.
+    fn get_legacy_vectors() -> Vec<VectorV2> {
+        get_vectors()
+            .into_iter()
+            .map(|vector| VectorV2 {
+                orig_value: vector.orig_value,
+                vector: vector.vector,
+                rank: vector.rank,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reads_vectors() {
+        let mut buffer = Vec::new();
+        for vector in get_legacy_vectors() {
+            write_record(&vector, &mut buffer);
+        }
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        let mut vectors = Vec::new();
+        while let Some(vector) = reader.next().await.unwrap() {
+            vectors.push(vector);
+        }
+        assert_eq!(reader.get_record_count(), 3);
+        for (read, written) in vectors.iter().zip(get_vectors().iter()) {
+            assert_eq!(read.orig_value, written.orig_value);
+            assert_eq!(read.vector, written.vector);
+            assert_eq!(read.rank, written.rank);
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_glyphs() {
+        let mut buffer = Vec::new();
+        write_record(&get_legacy_glyph(3.0, vec![1, 2, 3]), &mut buffer);
+        write_record(&get_legacy_glyph(6.0, vec![4]), &mut buffer);
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        let glyph = reader.next().await.unwrap().unwrap();
+        assert_eq!(glyph.row_ids, vec![1, 2, 3]);
+        let glyph = reader.next().await.unwrap().unwrap();
+        assert_eq!(glyph.z_value, 6.0);
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn empty_file() {
+        let mut reader = StatsFileReader::new(&[][..]);
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn truncated_size_prefix() {
+        let mut buffer = Vec::new();
+        write_record(&get_legacy_vectors()[0], &mut buffer);
+        buffer.extend_from_slice(&[1, 0, 0]);
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        assert!(reader.next().await.unwrap().is_some());
+        let result = reader.next().await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ReaderError::TruncatedRecord(_) => {}
+            _ => panic!("Expected TruncatedRecord"),
+        }
+    }
+
+    #[tokio::test]
+    async fn truncated_record() {
+        let mut buffer = Vec::new();
+        write_record(&get_legacy_glyph(3.0, vec![1, 2, 3]), &mut buffer);
+        buffer.truncate(buffer.len() - 4);
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        let result = reader.next().await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ReaderError::TruncatedRecord(_) => {}
+            _ => panic!("Expected TruncatedRecord"),
+        }
+    }
+
+    #[tokio::test]
+    async fn size_mismatch() {
+        //A glyph with 2 row ids written under the prefix of one with 3 row ids leaves 8 bytes
+        //of the record that the glyph does not account for.
+        let glyph = get_legacy_glyph(3.0, vec![1, 2]);
+        let mut record = serialize(&glyph).unwrap();
+        let mut buffer = serialize(&(record.len() + 8)).unwrap();
+        buffer.append(&mut record);
+        buffer.extend_from_slice(&[0u8; 8]);
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        let result = reader.next().await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ReaderError::CorruptRecord(_) => {}
+            _ => panic!("Expected CorruptRecord"),
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_prefix() {
+        let buffer = serialize(&u64::MAX).unwrap();
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        let result = reader.next().await;
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            ReaderError::CorruptRecord(_) => {}
+            _ => panic!("Expected CorruptRecord"),
+        }
+    }
+
+    fn write_file<T: BinaryRecord>(records: &[T]) -> Vec<u8> {
+        let header = FileHeader::new(
+            T::FILE_TYPE,
+            "test_hash",
+            vec![HeaderField {
+                axis: "x".to_string(),
+                field_display_name: "field".to_string(),
+                field_type: FieldType::String,
+            }],
+        );
+        let mut encoder = RecordEncoder::new();
+        let mut buffer = header.encode();
+        for record in records {
+            buffer.append(&mut encoder.encode_record(record));
+        }
+        buffer.append(&mut encoder.get_footer().encode());
+        buffer
+    }
+
+    #[tokio::test]
+    async fn reads_file_with_header() {
+        let buffer = write_file(&get_vectors());
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        let header = reader.read_header().await.unwrap().unwrap();
+        assert_eq!(header.file_type, FileType::Vector);
+        assert_eq!(header.model_hash, "test_hash");
+        assert_eq!(header.fields[0].field_display_name, "field");
+        let mut count = 0;
+        while let Some(vector) = reader.next().await.unwrap() {
+            assert_eq!(vector.rank, count);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn legacy_file_has_no_header() {
+        let mut buffer = Vec::new();
+        write_record(&get_legacy_vectors()[0], &mut buffer);
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        assert!(reader.read_header().await.unwrap().is_none());
+        assert!(reader.next().await.unwrap().is_some());
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn wrong_file_type() {
+        let buffer = write_file(&get_vectors());
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        let result = reader.next().await;
+        match result.err().unwrap() {
+            ReaderError::InvalidHeader(_) => {}
+            _ => panic!("Expected InvalidHeader"),
+        }
+    }
+
+    #[tokio::test]
+    async fn newer_format_version() {
+        let mut buffer = write_file(&get_vectors());
+        buffer[8..12].copy_from_slice(&serialize(&(FORMAT_VERSION + 1)).unwrap());
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        let result = reader.read_header().await;
+        match result.err().unwrap() {
+            ReaderError::InvalidHeader(_) => {}
+            _ => panic!("Expected InvalidHeader"),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_footer() {
+        let mut buffer = write_file(&get_vectors());
+        buffer.truncate(buffer.len() - 8 - FileFooter::BINARY_SIZE);
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        for _ in 0..3 {
+            assert!(reader.next().await.unwrap().is_some());
+        }
+        let result = reader.next().await;
+        match result.err().unwrap() {
+            ReaderError::InvalidFooter(_) => {}
+            _ => panic!("Expected InvalidFooter"),
+        }
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch() {
+        let mut buffer = write_file(&[Glyph::new(1.0, 2.0, 3.0, vec![1, 2, 3])]);
+        //Flip a bit in the last row id, which leaves the glyph readable.  It is followed by the
+        //3 channel flags.
+        let index = buffer.len() - 8 - FileFooter::BINARY_SIZE - 3 - 1;
+        buffer[index] ^= 1;
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        assert!(reader.next().await.unwrap().is_some());
+        let result = reader.next().await;
+        match result.err().unwrap() {
+            ReaderError::InvalidFooter(_) => {}
+            _ => panic!("Expected InvalidFooter"),
+        }
+    }
+
+    #[tokio::test]
+    async fn record_count_mismatch() {
+        let glyph = Glyph::new(1.0, 2.0, 3.0, vec![1]);
+        let mut encoder = RecordEncoder::new();
+        let mut buffer = FileHeader::new(FileType::Glyph, "test_hash", vec![]).encode();
+        buffer.append(&mut encoder.encode_record(&glyph));
+        let footer = encoder.get_footer();
+        buffer.append(&mut encoder.encode_record(&glyph));
+        buffer.append(&mut footer.encode());
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        assert!(reader.next().await.unwrap().is_some());
+        assert!(reader.next().await.unwrap().is_some());
+        let result = reader.next().await;
+        match result.err().unwrap() {
+            ReaderError::InvalidFooter(_) => {}
+            _ => panic!("Expected InvalidFooter"),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_vector_labels() {
+        let vector = Vector::new(VectorOrigionalValue::U64(20231), 0.0, 0)
+            .with_label(Some("2023-Q1".to_string()));
+        let buffer = write_file(&[vector, get_vectors().remove(0)]);
+        let mut reader = VectorFileReader::new(buffer.as_slice());
+        let vector = reader.next().await.unwrap().unwrap();
+        assert_eq!(vector.label.unwrap(), "2023-Q1");
+        let vector = reader.next().await.unwrap().unwrap();
+        assert!(vector.label.is_none());
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn reads_glyph_channels() {
+        let mut glyph = Glyph::new(1.0, 2.0, 3.0, vec![1]);
+        glyph.set_channel_value(GlyphChannel::Size, 7.0);
+        let buffer = write_file(&[glyph]);
+        let mut reader = GlyphFileReader::new(buffer.as_slice());
+        let glyph = reader.next().await.unwrap().unwrap();
+        assert_eq!(reader.get_format_version(), FORMAT_VERSION);
+        assert_eq!(glyph.get_channel_value(GlyphChannel::Size), Some(7.0));
+        assert!(glyph.get_channel_value(GlyphChannel::Color).is_none());
+    }
+}
@@ -5,6 +5,7 @@ pub enum FieldDefinitionType {
     Date,
     Formula,
     ACCUMULATED,
+    Binned,
 }
 
 impl FieldDefinitionType {
@@ -17,6 +18,7 @@ impl FieldDefinitionType {
             "date" => Some(FieldDefinitionType::Date),
             "formula" => Some(FieldDefinitionType::Formula),
             "accumulated" => Some(FieldDefinitionType::ACCUMULATED),
+            "binned" => Some(FieldDefinitionType::Binned),
             _ => None,
         }
     }
@@ -77,6 +79,19 @@ mod from_string {
         }
     }
     #[test]
+    fn binned() {
+        let input = "binned";
+        let result = FieldDefinitionType::from_string(input);
+        assert!(result.is_some());
+        let result = result.unwrap();
+        match result {
+            FieldDefinitionType::Binned => {}
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+    #[test]
     fn invalid() {
         let input = "invalid";
         let result = FieldDefinitionType::from_string(input);
@@ -1,5 +1,7 @@
+use super::field_type_errors::TryFromNumericValueError;
 use serde::{Deserialize, Serialize};
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use serde_json::Value;
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FieldType {
     Number,
     String,
@@ -8,6 +10,10 @@ pub enum FieldType {
     Unknown,
 }
 
+///The numeric values of the fieldDataTypes that a model definition can use.
+pub(crate) const ACCEPTED_VALUES: [(usize, &str); 4] =
+    [(0, "number"), (1, "string"), (2, "integer"), (3, "date")];
+
 impl FieldType {
     pub fn from_numeric_value(input: usize) -> FieldType {
         match input {
@@ -18,6 +24,30 @@ impl FieldType {
             _ => FieldType::Unknown,
         }
     }
+
+    ///Like from_numeric_value, but returns an error rather than FieldType::Unknown.
+    pub fn try_from_numeric_value(input: usize) -> Result<FieldType, TryFromNumericValueError> {
+        match Self::from_numeric_value(input) {
+            FieldType::Unknown => Err(Self::unknown_field_type(&Value::from(input))),
+            field_type => Ok(field_type),
+        }
+    }
+
+    ///Parses the fieldDataType of a model definition, which must be one of the numeric values.
+    ///Older model definitions hold them as floats, i.e. 1.0, so whole floats are accepted too.
+    pub fn try_from_json(input: &Value) -> Result<FieldType, TryFromNumericValueError> {
+        match input.as_f64() {
+            Some(number) if number >= 0.0 && number.fract() == 0.0 => {
+                Self::try_from_numeric_value(number as usize)
+                    .map_err(|_| Self::unknown_field_type(input))
+            }
+            _ => Err(Self::unknown_field_type(input)),
+        }
+    }
+
+    fn unknown_field_type(value: &Value) -> TryFromNumericValueError {
+        TryFromNumericValueError::new(value, &ACCEPTED_VALUES)
+    }
 }
 
 #[cfg(test)]
@@ -68,3 +98,57 @@ mod from_numeric_value {
         }
     }
 }
+
+#[cfg(test)]
+mod try_from_numeric_value {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(FieldType::try_from_numeric_value(2).unwrap(), FieldType::Integer);
+    }
+
+    #[test]
+    fn is_error() {
+        let result = FieldType::try_from_numeric_value(4);
+        match result.err().unwrap() {
+            TryFromNumericValueError::UnknownFieldType(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], 4);
+                assert_eq!(d["acceptedValues"].as_array().unwrap().len(), 4);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod try_from_json {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(FieldType::try_from_json(&json!(3)).unwrap(), FieldType::Date);
+    }
+
+    #[test]
+    fn not_a_number() {
+        let result = FieldType::try_from_json(&json!("string"));
+        match result.err().unwrap() {
+            TryFromNumericValueError::UnknownFieldType(error_data) => {
+                assert_eq!(error_data.data.unwrap()["value"], "string");
+            }
+        }
+    }
+
+    #[test]
+    fn whole_float() {
+        assert_eq!(FieldType::try_from_json(&json!(1.0)).unwrap(), FieldType::String);
+        assert!(FieldType::try_from_json(&json!(1.5)).is_err());
+    }
+
+    #[test]
+    fn negative_number() {
+        assert!(FieldType::try_from_json(&json!(-1)).is_err());
+    }
+}
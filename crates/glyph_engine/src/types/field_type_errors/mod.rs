@@ -0,0 +1,3 @@
+mod try_from_numeric_value_error;
+
+pub use try_from_numeric_value_error::TryFromNumericValueError;
@@ -0,0 +1,45 @@
+use glyphx_core::GlyphxError;
+use glyphx_core::GlyphxErrorData;
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, GlyphxError)]
+#[error_definition("FieldType")]
+pub enum TryFromNumericValueError {
+    UnknownFieldType(GlyphxErrorData),
+}
+
+impl TryFromNumericValueError {
+    ///value is a Value rather than a number so that fieldDataTypes which are not numbers at all
+    ///can be reported the same way.
+    pub fn new(value: &Value, accepted_values: &[(usize, &str)]) -> Self {
+        let message = format!("The fieldDataType {} is not valid", value);
+        let accepted_values: Vec<Value> = accepted_values
+            .iter()
+            .map(|(number, name)| json!({ "value": number, "name": name }))
+            .collect();
+        let data = json!({ "value": value, "acceptedValues": accepted_values });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::UnknownFieldType(error_data)
+    }
+}
+
+#[cfg(test)]
+mod constructor {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let result = TryFromNumericValueError::new(&json!(7), &[(0, "number"), (1, "string")]);
+        match result {
+            TryFromNumericValueError::UnknownFieldType(error_data) => {
+                assert_eq!(error_data.message, "The fieldDataType 7 is not valid");
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], 7);
+                assert_eq!(d["acceptedValues"][1]["value"], 1);
+                assert_eq!(d["acceptedValues"][1]["name"], "string");
+                assert!(error_data.inner_error.is_none());
+            }
+        }
+    }
+}
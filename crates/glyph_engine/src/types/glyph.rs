@@ -1,3 +1,4 @@
+use crate::types::GlyphChannel;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -6,6 +7,24 @@ pub struct Glyph {
     pub y_value: f64,
     pub z_value: f64,
     pub row_ids: Vec<usize>,
+    pub color_value: Option<f64>,
+    pub size_value: Option<f64>,
+    pub shape_value: Option<f64>,
+}
+
+///The layout of a Glyph before the color, size and shape channels were added (format version 1).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GlyphV1 {
+    pub x_value: f64,
+    pub y_value: f64,
+    pub z_value: f64,
+    pub row_ids: Vec<usize>,
+}
+
+impl From<GlyphV1> for Glyph {
+    fn from(glyph: GlyphV1) -> Self {
+        Glyph::new(glyph.x_value, glyph.y_value, glyph.z_value, glyph.row_ids)
+    }
 }
 
 impl Glyph {
@@ -15,10 +34,36 @@ impl Glyph {
             y_value,
             z_value,
             row_ids,
+            color_value: None,
+            size_value: None,
+            shape_value: None,
+        }
+    }
+
+    pub fn get_channel_value(&self, channel: GlyphChannel) -> Option<f64> {
+        match channel {
+            GlyphChannel::Color => self.color_value,
+            GlyphChannel::Size => self.size_value,
+            GlyphChannel::Shape => self.shape_value,
         }
     }
+
+    pub fn set_channel_value(&mut self, channel: GlyphChannel, value: f64) {
+        match channel {
+            GlyphChannel::Color => self.color_value = Some(value),
+            GlyphChannel::Size => self.size_value = Some(value),
+            GlyphChannel::Shape => self.shape_value = Some(value),
+        }
+    }
+
     pub fn get_binary_size(&self) -> usize {
-        self.row_ids.len() * 8 + 32
+        //x, y and z, the length of row_ids and 8 bytes per row id, plus 1 byte for each channel
+        //flag and 8 bytes for each channel that has a value.
+        let channel_count = GlyphChannel::all()
+            .iter()
+            .filter(|channel| self.get_channel_value(**channel).is_some())
+            .count();
+        self.row_ids.len() * 8 + 32 + 3 + channel_count * 8
     }
 }
 
@@ -38,4 +83,30 @@ mod tests {
         assert_eq!(glyph.z_value, decoded.z_value);
         assert_eq!(glyph.row_ids, decoded.row_ids);
     }
+
+    #[test]
+    fn encode_glyph_with_channels() {
+        let mut glyph = Glyph::new(1.0, 2.0, 3.0, vec![1]);
+        glyph.set_channel_value(GlyphChannel::Color, 4.0);
+        glyph.set_channel_value(GlyphChannel::Shape, 5.0);
+        let encoded = serialize(&glyph).unwrap();
+        let decoded: Glyph = deserialize(&encoded).unwrap();
+        assert_eq!(glyph.get_binary_size(), encoded.len());
+        assert_eq!(decoded.get_channel_value(GlyphChannel::Color), Some(4.0));
+        assert_eq!(decoded.get_channel_value(GlyphChannel::Size), None);
+        assert_eq!(decoded.get_channel_value(GlyphChannel::Shape), Some(5.0));
+    }
+
+    #[test]
+    fn from_glyph_v1() {
+        let glyph = GlyphV1 {
+            x_value: 1.0,
+            y_value: 2.0,
+            z_value: 3.0,
+            row_ids: vec![4],
+        };
+        let glyph = Glyph::from(glyph);
+        assert_eq!(glyph.row_ids, vec![4]);
+        assert!(glyph.color_value.is_none());
+    }
 }
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+///The optional channels, beyond x, y and z, that a field can be mapped to.  Like the z axis, each
+///channel is an accumulated field that is calculated over the rows of a glyph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GlyphChannel {
+    Color,
+    Size,
+    Shape,
+}
+
+impl GlyphChannel {
+    pub fn all() -> [GlyphChannel; 3] {
+        [GlyphChannel::Color, GlyphChannel::Size, GlyphChannel::Shape]
+    }
+
+    ///The name of the channel's field definition in the vectorizer parameters.
+    pub fn get_parameter_name(&self) -> &'static str {
+        match self {
+            GlyphChannel::Color => "glyphColor",
+            GlyphChannel::Size => "glyphSize",
+            GlyphChannel::Shape => "glyphShape",
+        }
+    }
+
+    ///The name that we use for the channel in place of x, y or z.  It prefixes the channel's
+    ///column in the glyph query results and names its statistics.
+    pub fn get_axis_name(&self) -> &'static str {
+        match self {
+            GlyphChannel::Color => "color",
+            GlyphChannel::Size => "size",
+            GlyphChannel::Shape => "shape",
+        }
+    }
+
+    pub fn from_parameter_name(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        GlyphChannel::all()
+            .into_iter()
+            .find(|channel| channel.get_parameter_name().to_lowercase() == input)
+    }
+}
+
+#[cfg(test)]
+mod from_parameter_name {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        for channel in GlyphChannel::all() {
+            let result = GlyphChannel::from_parameter_name(channel.get_parameter_name());
+            assert_eq!(result, Some(channel));
+        }
+    }
+
+    #[test]
+    fn ignores_case() {
+        let result = GlyphChannel::from_parameter_name(" GLYPHSIZE ");
+        assert_eq!(result, Some(GlyphChannel::Size));
+    }
+
+    #[test]
+    fn is_none() {
+        assert!(GlyphChannel::from_parameter_name("zAxis").is_none());
+    }
+}
@@ -5,4 +5,6 @@ use serde::{Deserialize, Serialize};
      pub y_axis_vectors_file_name: String,
      pub glyphs_file_name: String,
      pub statistics_file_name: String,
+     #[serde(skip_serializing_if = "Option::is_none")]
+     pub supporting_file_name: Option<String>,
  }
@@ -1,11 +1,18 @@
 mod field_type;
+mod field_type_errors;
 mod glyph;
+mod glyph_channel;
 mod glyph_engine_results;
 mod stats;
+mod supporting_field;
 
 pub mod field_definition_type;
 pub mod vectorizer_parameters;
 pub use field_type::FieldType;
+pub use field_type_errors::TryFromNumericValueError as FieldTypeTryFromNumericValueError;
 pub use glyph::Glyph;
+pub(crate) use glyph::GlyphV1;
+pub use glyph_channel::GlyphChannel;
 pub use glyph_engine_results::GlyphEngineResults;
 pub use stats::Stats;
+pub use supporting_field::{SupportingField, SupportingFieldAggregation, SupportingValues};
@@ -0,0 +1,106 @@
+use crate::types::vectorizer_parameters::FieldDefinition;
+use serde::{Deserialize, Serialize};
+
+///How the values of a supporting field are combined over the rows of a glyph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SupportingFieldAggregation {
+    ///The value from the row with the lowest row id.
+    First,
+    ///The distinct, non null values, sorted, as a JSON array.
+    DistinctList,
+    Sum,
+    Avg,
+}
+
+impl SupportingFieldAggregation {
+    pub fn from_string(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "first" => Some(SupportingFieldAggregation::First),
+            "distinct_list" => Some(SupportingFieldAggregation::DistinctList),
+            "sum" => Some(SupportingFieldAggregation::Sum),
+            "avg" => Some(SupportingFieldAggregation::Avg),
+            _ => None,
+        }
+    }
+
+    ///Sum and Avg can only be applied to numeric fields.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            SupportingFieldAggregation::Sum | SupportingFieldAggregation::Avg
+        )
+    }
+}
+
+///A supporting field that is aggregated per glyph and written to the .sup file.
+#[derive(Debug, Clone)]
+pub struct SupportingField {
+    pub field_definition: FieldDefinition,
+    pub aggregation: SupportingFieldAggregation,
+}
+
+impl SupportingField {
+    ///The name of the supporting field's column in the glyph query results.
+    pub fn get_column_name(&self) -> String {
+        format!(
+            "supporting_{}",
+            self.field_definition.get_field_display_name()
+        )
+    }
+}
+
+///The aggregated supporting field values of a single glyph.  glyph_index is the position of the
+///glyph in the .gly file and the values are JSON text, in the order of the fields in the .sup
+///file header, so that values of any type can be written without a schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupportingValues {
+    pub glyph_index: u64,
+    pub values: Vec<String>,
+}
+
+impl SupportingValues {
+    pub fn get_binary_size(&self) -> usize {
+        //glyph_index, the length of values and the length and bytes of each value.
+        let values_size: usize = self.values.iter().map(|value| 8 + value.len()).sum();
+        16 + values_size
+    }
+}
+
+#[cfg(test)]
+mod from_string {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(
+            SupportingFieldAggregation::from_string(" Distinct_List "),
+            Some(SupportingFieldAggregation::DistinctList)
+        );
+        assert_eq!(
+            SupportingFieldAggregation::from_string("avg"),
+            Some(SupportingFieldAggregation::Avg)
+        );
+    }
+
+    #[test]
+    fn is_none() {
+        assert!(SupportingFieldAggregation::from_string("median").is_none());
+    }
+}
+
+#[cfg(test)]
+mod get_binary_size {
+    use super::*;
+    use bincode::serialize;
+
+    #[test]
+    fn is_ok() {
+        let values = SupportingValues {
+            glyph_index: 3,
+            values: vec![r#""east""#.to_string(), "12.5".to_string()],
+        };
+        let encoded = serialize(&values).unwrap();
+        assert_eq!(values.get_binary_size(), encoded.len());
+    }
+}
@@ -1,41 +1,109 @@
 use crate::types::field_definition_type::FieldDefinitionType;
-use crate::types::vectorizer_parameters::{DateFieldDefinition, StandardFieldDefinition};
+use crate::types::vectorizer_parameters::{
+    DateFieldDefinition, FormulaFieldDefinition, StandardFieldDefinition,
+};
 
+use super::accumulated_field_definition_errors::TryFromStrError as AccumulatorTypeTryFromStrError;
 use super::AccumulatedFieldDefinitionFromJsonError;
 use crate::types::vectorizer_parameters::helper_functions::json_has_field;
 use glyphx_core::GlyphxErrorData;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
 pub enum AccumulatorType {
     SUM,
     AVG,
     MIN,
     MAX,
     COUNT,
+    COUNT_DISTINCT,
+    MEDIAN,
+    ///Requires a percentile between 0 and 1.
+    PERCENTILE,
+    STDDEV,
+    VARIANCE,
+    ///The value from the row with the earliest orderByField.
+    FIRST,
+    ///The value from the row with the latest orderByField.
+    LAST,
 }
 
+///The accumulator values that a model definition can use.
+const ACCUMULATOR_TYPE_NAMES: [&str; 12] = [
+    "sum",
+    "avg",
+    "min",
+    "max",
+    "count",
+    "count_distinct",
+    "median",
+    "percentile",
+    "stddev",
+    "variance",
+    "first",
+    "last",
+];
+
 impl AccumulatorType {
+    ///Unknown values fall back to SUM, use try_from_str to reject them instead.
     pub fn from_str(input: &str) -> Self {
-        let input = input.trim();
-        let input = input.to_lowercase();
-        let input = input.as_str();
-        match input {
+        Self::try_from_str(input).unwrap_or(AccumulatorType::SUM)
+    }
+
+    pub fn try_from_str(input: &str) -> Result<Self, AccumulatorTypeTryFromStrError> {
+        let cleaned_input = input.trim().to_lowercase();
+        let cleaned_input = cleaned_input.as_str();
+        let accumulator_type = match cleaned_input {
             "sum" => AccumulatorType::SUM,
             "avg" => AccumulatorType::AVG,
             "min" => AccumulatorType::MIN,
             "max" => AccumulatorType::MAX,
             "count" => AccumulatorType::COUNT,
-            _ => AccumulatorType::SUM,
-        }
+            "count_distinct" => AccumulatorType::COUNT_DISTINCT,
+            "median" => AccumulatorType::MEDIAN,
+            "percentile" => AccumulatorType::PERCENTILE,
+            "stddev" => AccumulatorType::STDDEV,
+            "variance" => AccumulatorType::VARIANCE,
+            "first" => AccumulatorType::FIRST,
+            "last" => AccumulatorType::LAST,
+            _ => {
+                return Err(AccumulatorTypeTryFromStrError::new(
+                    input,
+                    &ACCUMULATOR_TYPE_NAMES,
+                ))
+            }
+        };
+        Ok(accumulator_type)
+    }
+
+    pub fn requires_percentile(&self) -> bool {
+        *self == AccumulatorType::PERCENTILE
+    }
+
+    pub fn requires_order_by_field(&self) -> bool {
+        matches!(self, AccumulatorType::FIRST | AccumulatorType::LAST)
+    }
+
+    ///These accumulators only make sense over numbers, the others can be applied to strings.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            AccumulatorType::SUM
+                | AccumulatorType::AVG
+                | AccumulatorType::MEDIAN
+                | AccumulatorType::PERCENTILE
+                | AccumulatorType::STDDEV
+                | AccumulatorType::VARIANCE
+        )
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AccumulatedFieldDefinition {
     Standard(StandardFieldDefinition),
-    //FormulaFieldDefinition(FormulaFieldDefinition),
+    Formula(FormulaFieldDefinition),
     Date(DateFieldDefinition),
     Unknown(),
 }
@@ -55,6 +123,7 @@ impl AccumulatedFieldDefinition {
         match field_definition_type {
             FieldDefinitionType::Standard => Self::build_standard_field(input),
             FieldDefinitionType::Date => Self::build_date_field(input),
+            FieldDefinitionType::Formula => Self::build_formula_field(input),
             _ => Ok(AccumulatedFieldDefinition::Unknown()),
         }
     }
@@ -74,6 +143,22 @@ impl AccumulatedFieldDefinition {
         ))
     }
 
+    fn build_formula_field(
+        field_definition: &Value,
+    ) -> Result<AccumulatedFieldDefinition, AccumulatedFieldDefinitionFromJsonError> {
+        let formula_field_definition = FormulaFieldDefinition::from_json(field_definition);
+        if formula_field_definition.is_err() {
+            let err = formula_field_definition.err().unwrap();
+            let err =
+                AccumulatedFieldDefinitionFromJsonError::from_formula_field_from_json_error(err);
+            return Err(err);
+        }
+
+        Ok(AccumulatedFieldDefinition::Formula(
+            formula_field_definition.unwrap(),
+        ))
+    }
+
     fn build_standard_field(
         field_definition: &Value,
     ) -> Result<AccumulatedFieldDefinition, AccumulatedFieldDefinitionFromJsonError> {
@@ -134,6 +219,10 @@ impl AccumulatedFieldDefinition {
         }
     }
 
+    pub fn is_formula(&self) -> bool {
+        matches!(self, AccumulatedFieldDefinition::Formula { .. })
+    }
+
     pub fn get_standard_field_definition(&self) -> Option<&StandardFieldDefinition> {
         match self {
             AccumulatedFieldDefinition::Standard(field_definition) => Some(field_definition),
@@ -148,6 +237,28 @@ impl AccumulatedFieldDefinition {
         }
     }
 
+    pub fn get_formula_field_definition(&self) -> Option<&FormulaFieldDefinition> {
+        match self {
+            AccumulatedFieldDefinition::Formula(field_definition) => Some(field_definition),
+            _ => None,
+        }
+    }
+
+    pub fn get_column_names(&self) -> Vec<String> {
+        match self {
+            AccumulatedFieldDefinition::Standard(field_definition) => {
+                vec![field_definition.field_name.clone()]
+            }
+            AccumulatedFieldDefinition::Date(field_definition) => {
+                vec![field_definition.field_name.clone()]
+            }
+            AccumulatedFieldDefinition::Formula(field_definition) => {
+                field_definition.get_column_names()
+            }
+            _ => Vec::new(),
+        }
+    }
+
     pub fn get_query(&self) -> String {
         let query = match self {
             AccumulatedFieldDefinition::Standard(field_definition) => {
@@ -158,7 +269,11 @@ impl AccumulatedFieldDefinition {
                 let (_, raw_query) = field_definition.get_query("foo");
                 raw_query
             }
-            _ => "Formula Field".to_string(),
+            AccumulatedFieldDefinition::Formula(field_definition) => {
+                let (_, raw_query) = field_definition.get_query("foo");
+                raw_query
+            }
+            _ => "Unknown Field".to_string(),
         };
 
         query
@@ -170,6 +285,10 @@ pub struct AccumulatorFieldDefinition {
     pub field_type: FieldDefinitionType,
     pub accumulator_type: AccumulatorType,
     pub accumulated_field_definition: AccumulatedFieldDefinition,
+    ///Only set for the PERCENTILE accumulator.
+    pub percentile: Option<f64>,
+    ///The column that orders the rows for the FIRST and LAST accumulators.
+    pub order_by_field: Option<String>,
 }
 
 impl AccumulatorFieldDefinition {
@@ -180,8 +299,13 @@ impl AccumulatorFieldDefinition {
         if validation_result.is_err() {
             return Err(validation_result.err().unwrap());
         }
-        let raw_accumulator_type = &json_value["accumulator"].as_str().unwrap();
-        let accumulator_type = AccumulatorType::from_str(raw_accumulator_type);
+        let raw_accumulator_type = json_value["accumulator"].as_str().unwrap_or("");
+        let accumulator_type = AccumulatorType::try_from_str(raw_accumulator_type);
+        if accumulator_type.is_err() {
+            let err = accumulator_type.err().unwrap();
+            return Err(AccumulatedFieldDefinitionFromJsonError::from_try_from_str_error(err));
+        }
+        let accumulator_type = accumulator_type.unwrap();
 
         let accumlated_field_definition = &json_value["accumulatedFieldDefinition"];
         let accumulated_field_definition = AccumulatedFieldDefinition::from_json(
@@ -195,10 +319,21 @@ impl AccumulatorFieldDefinition {
 
         let accumulated_field_definition = accumulated_field_definition.unwrap();
 
+        let percentile = match accumulator_type.requires_percentile() {
+            true => json_value["percentile"].as_f64(),
+            false => None,
+        };
+        let order_by_field = match accumulator_type.requires_order_by_field() {
+            true => json_value["orderByField"].as_str().map(|s| s.to_string()),
+            false => None,
+        };
+
         Ok(AccumulatorFieldDefinition {
             field_type: FieldDefinitionType::ACCUMULATED,
             accumulator_type,
             accumulated_field_definition,
+            percentile,
+            order_by_field,
         })
     }
 
@@ -219,16 +354,76 @@ impl AccumulatorFieldDefinition {
             return Err(err);
         }
 
+        let accumulator_type =
+            AccumulatorType::from_str(json_value["accumulator"].as_str().unwrap_or(""));
+        if accumulator_type.requires_percentile() {
+            let percentile = json_value["percentile"].as_f64();
+            if percentile.is_none() || percentile.unwrap() <= 0.0 || percentile.unwrap() >= 1.0 {
+                let description =
+                    "The percentile accumulator requires a percentile between 0 and 1".to_string();
+                let data =
+                    json!({ "accumulator": "percentile", "percentile": json_value["percentile"] });
+                return Err(
+                    AccumulatedFieldDefinitionFromJsonError::InvalidAccumulatorParameter(
+                        GlyphxErrorData::new(description, Some(data), None),
+                    ),
+                );
+            }
+        }
+        if accumulator_type.requires_order_by_field() {
+            let order_by_field = json_value["orderByField"].as_str();
+            if order_by_field.is_none() || order_by_field.unwrap().trim().is_empty() {
+                let description = format!(
+                    "The {:?} accumulator requires an orderByField",
+                    accumulator_type
+                );
+                let data = json!({ "accumulator": json_value["accumulator"], "orderByField": json_value["orderByField"] });
+                return Err(
+                    AccumulatedFieldDefinitionFromJsonError::InvalidAccumulatorParameter(
+                        GlyphxErrorData::new(description, Some(data), None),
+                    ),
+                );
+            }
+        }
+
         Ok(())
     }
 
+    pub fn get_column_names(&self) -> Vec<String> {
+        let mut column_names = self.accumulated_field_definition.get_column_names();
+        if let Some(order_by_field) = &self.order_by_field {
+            if !column_names.contains(order_by_field) {
+                column_names.push(order_by_field.clone());
+            }
+        }
+        column_names
+    }
+
     pub fn get_query(&self, display_name: &str) -> (String, String) {
         let base_query = self.accumulated_field_definition.get_query();
-        let query = format!(
-            r#"{:?}({}) as "{}""#,
-            self.accumulator_type, base_query, display_name
-        );
-        let raw_query = format!(r#"{:?}({})"#, self.accumulator_type, base_query);
+        let raw_query = match self.accumulator_type {
+            AccumulatorType::COUNT_DISTINCT => format!("COUNT(DISTINCT {})", base_query),
+            AccumulatorType::MEDIAN => format!("approx_percentile({}, 0.5)", base_query),
+            AccumulatorType::PERCENTILE => format!(
+                "approx_percentile({}, {})",
+                base_query,
+                self.percentile.unwrap_or(0.5)
+            ),
+            AccumulatorType::STDDEV => format!("stddev({})", base_query),
+            AccumulatorType::VARIANCE => format!("variance({})", base_query),
+            AccumulatorType::FIRST => format!(
+                r#"min_by({}, "{}")"#,
+                base_query,
+                self.order_by_field.as_deref().unwrap_or_default()
+            ),
+            AccumulatorType::LAST => format!(
+                r#"max_by({}, "{}")"#,
+                base_query,
+                self.order_by_field.as_deref().unwrap_or_default()
+            ),
+            _ => format!(r#"{:?}({})"#, self.accumulator_type, base_query),
+        };
+        let query = format!(r#"{} as "{}""#, raw_query, display_name);
         (query, raw_query)
     }
 }
@@ -281,6 +476,26 @@ mod AccumulatorType_from_str {
         }
     }
 
+    #[test]
+    fn distribution_accumulators() {
+        assert_eq!(
+            AccumulatorType::from_str("count_distinct"),
+            AccumulatorType::COUNT_DISTINCT
+        );
+        assert_eq!(AccumulatorType::from_str("Median"), AccumulatorType::MEDIAN);
+        assert_eq!(
+            AccumulatorType::from_str("percentile"),
+            AccumulatorType::PERCENTILE
+        );
+        assert_eq!(AccumulatorType::from_str("stddev"), AccumulatorType::STDDEV);
+        assert_eq!(
+            AccumulatorType::from_str("variance"),
+            AccumulatorType::VARIANCE
+        );
+        assert_eq!(AccumulatorType::from_str("first"), AccumulatorType::FIRST);
+        assert_eq!(AccumulatorType::from_str("last"), AccumulatorType::LAST);
+    }
+
     #[test]
     fn default() {
         let accumulator_type = AccumulatorType::from_str("default");
@@ -291,13 +506,70 @@ mod AccumulatorType_from_str {
     }
 }
 
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod AccumulatorType_is_numeric {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert!(AccumulatorType::SUM.is_numeric());
+        assert!(AccumulatorType::STDDEV.is_numeric());
+        assert!(!AccumulatorType::COUNT.is_numeric());
+        assert!(!AccumulatorType::MIN.is_numeric());
+        assert!(!AccumulatorType::FIRST.is_numeric());
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod AccumulatorType_try_from_str {
+    use super::*;
+
+    #[test]
+    fn accepts_every_name() {
+        for name in ACCUMULATOR_TYPE_NAMES {
+            assert!(AccumulatorType::try_from_str(name).is_ok(), "{}", name);
+        }
+        assert_eq!(
+            AccumulatorType::try_from_str(" Count_Distinct ").unwrap(),
+            AccumulatorType::COUNT_DISTINCT
+        );
+    }
+
+    #[test]
+    fn matches_the_model_definition() {
+        use crate::types::vectorizer_parameters::ModelAccumulator;
+        for name in ACCUMULATOR_TYPE_NAMES {
+            let model_accumulator: ModelAccumulator =
+                serde_json::from_value(json!(name)).unwrap();
+            assert_eq!(serde_json::to_value(model_accumulator).unwrap(), name);
+        }
+    }
+
+    #[test]
+    fn unknown() {
+        let result = AccumulatorType::try_from_str("summ");
+        match result.err().unwrap() {
+            AccumulatorTypeTryFromStrError::UnknownAccumulatorType(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["value"], "summ");
+                assert_eq!(
+                    data["acceptedValues"].as_array().unwrap().len(),
+                    ACCUMULATOR_TYPE_NAMES.len()
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod AccumulatedFieldDefinition_is_standard {
     use super::*;
     use crate::types::field_definition_type::FieldDefinitionType;
     use crate::types::vectorizer_parameters::{
-        DateFieldDefinition, DateGrouping, StandardFieldDefinition,
+        DateFieldDefinition, DateGrouping, DateUnit, StandardFieldDefinition, Weekday,
     };
 
     #[test]
@@ -306,6 +578,7 @@ mod AccumulatedFieldDefinition_is_standard {
             AccumulatedFieldDefinition::Standard(StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             });
 
         assert!(accumulated_field_definition.is_standard());
@@ -317,6 +590,10 @@ mod AccumulatedFieldDefinition_is_standard {
             field_type: FieldDefinitionType::Date,
             field_name: "test".to_string(),
             date_grouping: DateGrouping::DayOfMonth,
+            date_unit: DateUnit::Milliseconds,
+            time_zone: None,
+            week_start: Weekday::Monday,
+            minute_bucket: 15,
         });
         assert!(!accumulated_field_definition.is_standard());
     }
@@ -328,7 +605,7 @@ mod AccumulatedFieldDefinition_is_date {
     use super::*;
     use crate::types::field_definition_type::FieldDefinitionType;
     use crate::types::vectorizer_parameters::{
-        DateFieldDefinition, DateGrouping, StandardFieldDefinition,
+        DateFieldDefinition, DateGrouping, DateUnit, StandardFieldDefinition, Weekday,
     };
 
     #[test]
@@ -337,6 +614,10 @@ mod AccumulatedFieldDefinition_is_date {
             field_type: FieldDefinitionType::Date,
             field_name: "test".to_string(),
             date_grouping: DateGrouping::DayOfMonth,
+            date_unit: DateUnit::Milliseconds,
+            time_zone: None,
+            week_start: Weekday::Monday,
+            minute_bucket: 15,
         });
         assert!(accumulated_field_definition.is_date());
     }
@@ -347,6 +628,7 @@ mod AccumulatedFieldDefinition_is_date {
             AccumulatedFieldDefinition::Standard(StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             });
         assert!(!accumulated_field_definition.is_date());
     }
@@ -358,7 +640,7 @@ mod AccumulatedFieldDefinition_get_standard_field_definition {
     use super::*;
     use crate::types::field_definition_type::FieldDefinitionType;
     use crate::types::vectorizer_parameters::{
-        DateFieldDefinition, DateGrouping, StandardFieldDefinition,
+        DateFieldDefinition, DateGrouping, DateUnit, StandardFieldDefinition, Weekday,
     };
 
     #[test]
@@ -367,6 +649,7 @@ mod AccumulatedFieldDefinition_get_standard_field_definition {
             AccumulatedFieldDefinition::Standard(StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             });
         let standard_field_definition =
             accumulated_field_definition.get_standard_field_definition();
@@ -387,6 +670,10 @@ mod AccumulatedFieldDefinition_get_standard_field_definition {
             field_type: FieldDefinitionType::Date,
             field_name: "test".to_string(),
             date_grouping: DateGrouping::DayOfMonth,
+            date_unit: DateUnit::Milliseconds,
+            time_zone: None,
+            week_start: Weekday::Monday,
+            minute_bucket: 15,
         });
         let standard_field_definition =
             accumulated_field_definition.get_standard_field_definition();
@@ -408,7 +695,7 @@ mod AccumulatedFieldDefinition_get_date_field_definition {
     use super::*;
     use crate::types::field_definition_type::FieldDefinitionType;
     use crate::types::vectorizer_parameters::{
-        DateFieldDefinition, DateGrouping, StandardFieldDefinition,
+        DateFieldDefinition, DateGrouping, DateUnit, StandardFieldDefinition, Weekday,
     };
 
     #[test]
@@ -417,6 +704,7 @@ mod AccumulatedFieldDefinition_get_date_field_definition {
             AccumulatedFieldDefinition::Standard(StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             });
         let date_field_definition = accumulated_field_definition.get_date_field_definition();
         assert!(date_field_definition.is_none());
@@ -428,6 +716,10 @@ mod AccumulatedFieldDefinition_get_date_field_definition {
             field_type: FieldDefinitionType::Date,
             field_name: "test".to_string(),
             date_grouping: DateGrouping::DayOfMonth,
+            date_unit: DateUnit::Milliseconds,
+            time_zone: None,
+            week_start: Weekday::Monday,
+            minute_bucket: 15,
         });
         let date_field_definition = accumulated_field_definition.get_date_field_definition();
         assert!(date_field_definition.is_some());
@@ -528,7 +820,7 @@ mod AccumulatedFieldDefinition_from_json {
         let input = json!({
             "fieldType": "date",
             "fieldName": "test",
-            "dateGrouping": "dayOfMonth",
+            "dateGrouping": "day_of_month",
         });
 
         let accumulated_field_definition = AccumulatedFieldDefinition::from_json(&input, "test");
@@ -542,6 +834,52 @@ mod AccumulatedFieldDefinition_from_json {
         }
     }
 
+    #[test]
+    fn formula_is_ok() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "revenue - cost",
+        });
+
+        let accumulated_field_definition = AccumulatedFieldDefinition::from_json(&input, "test");
+        assert!(accumulated_field_definition.is_ok());
+        let accumulated_field_definition = accumulated_field_definition.unwrap();
+        match &accumulated_field_definition {
+            AccumulatedFieldDefinition::Formula(_) => {}
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+        assert_eq!(
+            accumulated_field_definition.get_query(),
+            r#"("revenue" - "cost")"#
+        );
+    }
+
+    #[test]
+    fn formula_is_err() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "revenue -",
+        });
+
+        let accumulated_field_definition = AccumulatedFieldDefinition::from_json(&input, "test");
+        assert!(accumulated_field_definition.is_err());
+        let accumulated_field_definition = accumulated_field_definition.err().unwrap();
+        match accumulated_field_definition {
+            AccumulatedFieldDefinitionFromJsonError::FormulaFieldDefinitionFromJsonError(
+                error_data,
+            ) => {
+                let data = error_data.data.unwrap();
+                let formula = data["formula"].as_str().unwrap();
+                assert_eq!(formula, "revenue -");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+
     #[test]
     fn standard_is_err() {
         let input = json!({
@@ -636,6 +974,48 @@ mod AccumulatorFieldDefinition_validate_json {
         }
     }
 
+    #[test]
+    fn percentile_is_invalid() {
+        for percentile in [json!(null), json!(0.0), json!(1.5), json!("0.9")] {
+            let input = json!({
+                "accumulator": "percentile",
+                "percentile": percentile,
+                "accumulatedFieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "test",
+                },
+            });
+
+            let result = AccumulatorFieldDefinition::validate_json(&input);
+            match result.err().unwrap() {
+                AccumulatedFieldDefinitionFromJsonError::InvalidAccumulatorParameter(_) => {}
+                _ => panic!("Unexpected result"),
+            }
+        }
+    }
+
+    #[test]
+    fn order_by_field_is_missing() {
+        let input = json!({
+            "accumulator": "last",
+            "accumulatedFieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "test",
+            },
+        });
+
+        let result = AccumulatorFieldDefinition::validate_json(&input);
+        match result.err().unwrap() {
+            AccumulatedFieldDefinitionFromJsonError::InvalidAccumulatorParameter(error_data) => {
+                assert_eq!(
+                    error_data.message,
+                    "The LAST accumulator requires an orderByField"
+                );
+            }
+            _ => panic!("Unexpected result"),
+        }
+    }
+
     #[test]
     fn accumulatedFieldDefinition_is_missing() {
         let input = json!({
@@ -748,4 +1128,102 @@ mod AccumulatorFieldDefinition_from_json {
             }
         }
     }
+
+    #[test]
+    fn unknown_accumulator() {
+        let input = json!({
+            "accumulator": "summ",
+            "accumulatedFieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "test",
+            },
+        });
+
+        let result = AccumulatorFieldDefinition::from_json(&input);
+        match result.err().unwrap() {
+            AccumulatedFieldDefinitionFromJsonError::InvalidAccumulatorType(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["value"], "summ");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod AccumulatorFieldDefinition_get_query {
+    use super::*;
+    use serde_json::json;
+
+    fn get_query(accumulator: Value) -> String {
+        let mut input = json!({
+            "accumulatedFieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "sales",
+            },
+        });
+        for (key, value) in accumulator.as_object().unwrap() {
+            input[key] = value.clone();
+        }
+        let field_definition = AccumulatorFieldDefinition::from_json(&input).unwrap();
+        let (query, raw_query) = field_definition.get_query("total");
+        assert_eq!(query, format!(r#"{} as "total""#, raw_query));
+        raw_query
+    }
+
+    #[test]
+    fn sum() {
+        let result = get_query(json!({"accumulator": "sum"}));
+        assert_eq!(result, r#"SUM("sales")"#);
+    }
+
+    #[test]
+    fn count_distinct() {
+        let result = get_query(json!({"accumulator": "count_distinct"}));
+        assert_eq!(result, r#"COUNT(DISTINCT "sales")"#);
+    }
+
+    #[test]
+    fn median_and_percentile() {
+        let result = get_query(json!({"accumulator": "median"}));
+        assert_eq!(result, r#"approx_percentile("sales", 0.5)"#);
+        let result = get_query(json!({"accumulator": "percentile", "percentile": 0.95}));
+        assert_eq!(result, r#"approx_percentile("sales", 0.95)"#);
+    }
+
+    #[test]
+    fn stddev_and_variance() {
+        let result = get_query(json!({"accumulator": "stddev"}));
+        assert_eq!(result, r#"stddev("sales")"#);
+        let result = get_query(json!({"accumulator": "variance"}));
+        assert_eq!(result, r#"variance("sales")"#);
+    }
+
+    #[test]
+    fn first_and_last() {
+        let result = get_query(json!({"accumulator": "first", "orderByField": "order_date"}));
+        assert_eq!(result, r#"min_by("sales", "order_date")"#);
+        let result = get_query(json!({"accumulator": "last", "orderByField": "order_date"}));
+        assert_eq!(result, r#"max_by("sales", "order_date")"#);
+    }
+
+    #[test]
+    fn order_by_field_is_a_column() {
+        let field_definition = AccumulatorFieldDefinition::from_json(&json!({
+            "accumulator": "first",
+            "orderByField": "order_date",
+            "accumulatedFieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "sales",
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            field_definition.get_column_names(),
+            vec!["sales".to_string(), "order_date".to_string()]
+        );
+    }
 }
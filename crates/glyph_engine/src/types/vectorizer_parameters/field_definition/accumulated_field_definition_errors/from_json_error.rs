@@ -1,6 +1,8 @@
 use crate::types::vectorizer_parameters::field_definition::{
-    DateFieldDefinitionFromJsonError, StandardFieldDefinitionFromJsonError,
+    DateFieldDefinitionFromJsonError, FormulaFieldDefinitionFromJsonError,
+    StandardFieldDefinitionFromJsonError,
 };
+use super::TryFromStrError;
 use crate::types::vectorizer_parameters::helper_functions::JsonHasFieldError;
 use glyphx_core::GlyphxError;
 use glyphx_core::GlyphxErrorData;
@@ -12,6 +14,9 @@ pub enum FromJsonError {
     InvalidFieldDefinitionType(GlyphxErrorData),
     StandardFieldDefinitionFromJsonError(GlyphxErrorData),
     DateFieldDefinitionFromJsonError(GlyphxErrorData),
+    FormulaFieldDefinitionFromJsonError(GlyphxErrorData),
+    InvalidAccumulatorParameter(GlyphxErrorData),
+    InvalidAccumulatorType(GlyphxErrorData),
 }
 
 impl FromJsonError {
@@ -21,6 +26,12 @@ impl FromJsonError {
         }
     }
 
+    pub fn from_try_from_str_error(input: TryFromStrError) -> Self {
+        match input {
+            TryFromStrError::UnknownAccumulatorType(data) => Self::InvalidAccumulatorType(data),
+        }
+    }
+
     pub fn from_standard_field_from_json_error(
         input: StandardFieldDefinitionFromJsonError,
     ) -> Self {
@@ -28,6 +39,9 @@ impl FromJsonError {
             StandardFieldDefinitionFromJsonError::FieldNotDefined(data) => {
                 Self::StandardFieldDefinitionFromJsonError(data)
             }
+            StandardFieldDefinitionFromJsonError::InvalidTopN(data) => {
+                Self::StandardFieldDefinitionFromJsonError(data)
+            }
         }
     }
 
@@ -36,6 +50,20 @@ impl FromJsonError {
             DateFieldDefinitionFromJsonError::FieldNotDefined(data) => {
                 Self::DateFieldDefinitionFromJsonError(data)
             }
+            DateFieldDefinitionFromJsonError::InvalidDateOption(data) => {
+                Self::DateFieldDefinitionFromJsonError(data)
+            }
+        }
+    }
+
+    pub fn from_formula_field_from_json_error(input: FormulaFieldDefinitionFromJsonError) -> Self {
+        match input {
+            FormulaFieldDefinitionFromJsonError::FieldNotDefined(data) => {
+                Self::FormulaFieldDefinitionFromJsonError(data)
+            }
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(data) => {
+                Self::FormulaFieldDefinitionFromJsonError(data)
+            }
         }
     }
 }
@@ -126,3 +154,74 @@ mod from_date_field_from_json_error {
         }
     }
 }
+
+#[cfg(test)]
+mod from_formula_field_from_json_error {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn field_not_defined() {
+        let message = "testMessage";
+        let data = json!({"field": "test"});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = FormulaFieldDefinitionFromJsonError::FieldNotDefined(data);
+
+        let result = FromJsonError::from_formula_field_from_json_error(input);
+        match result {
+            FromJsonError::FormulaFieldDefinitionFromJsonError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let field = d["field"].as_str().unwrap();
+                assert_eq!(field, "test");
+                assert!(error_data.inner_error.is_none());
+            }
+            _ => panic!("Expected FormulaFieldDefinitionFromJsonError"),
+        }
+    }
+
+    #[test]
+    fn invalid_formula() {
+        let message = "testMessage";
+        let data = json!({"formula": "a +", "position": 3});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = FormulaFieldDefinitionFromJsonError::InvalidFormula(data);
+
+        let result = FromJsonError::from_formula_field_from_json_error(input);
+        match result {
+            FromJsonError::FormulaFieldDefinitionFromJsonError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let formula = d["formula"].as_str().unwrap();
+                assert_eq!(formula, "a +");
+                assert!(error_data.inner_error.is_none());
+            }
+            _ => panic!("Expected FormulaFieldDefinitionFromJsonError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_try_from_str_error {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let input = TryFromStrError::new("summ", &["sum", "avg"]);
+        let result = FromJsonError::from_try_from_str_error(input);
+        match result {
+            FromJsonError::InvalidAccumulatorType(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], "summ");
+                assert_eq!(d["acceptedValues"][0], "sum");
+            }
+            _ => panic!("Expected InvalidAccumulatorType"),
+        }
+    }
+}
@@ -1,3 +1,5 @@
 mod from_json_error;
+mod try_from_str_error;
 
 pub use from_json_error::FromJsonError;
+pub use try_from_str_error::TryFromStrError;
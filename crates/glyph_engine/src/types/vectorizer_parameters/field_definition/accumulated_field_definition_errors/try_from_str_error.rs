@@ -0,0 +1,38 @@
+use glyphx_core::GlyphxError;
+use glyphx_core::GlyphxErrorData;
+
+use serde_json::json;
+
+#[derive(Debug, Clone, GlyphxError)]
+#[error_definition("AccumulatorType")]
+pub enum TryFromStrError {
+    UnknownAccumulatorType(GlyphxErrorData),
+}
+
+impl TryFromStrError {
+    pub fn new(value: &str, accepted_values: &[&str]) -> Self {
+        let message = format!("The accumulator '{}' is not valid", value);
+        let data = json!({ "value": value, "acceptedValues": accepted_values });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::UnknownAccumulatorType(error_data)
+    }
+}
+
+#[cfg(test)]
+mod constructor {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let result = TryFromStrError::new("summ", &["sum", "avg"]);
+        match result {
+            TryFromStrError::UnknownAccumulatorType(error_data) => {
+                assert_eq!(error_data.message, "The accumulator 'summ' is not valid");
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], "summ");
+                assert_eq!(d["acceptedValues"][1], "avg");
+                assert!(error_data.inner_error.is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use super::BinnedFieldDefinitionFromJsonError;
+use crate::types::field_definition_type::FieldDefinitionType;
+use crate::types::vectorizer_parameters::helper_functions::json_has_field;
+use glyphx_core::GlyphxErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BinningMethod {
+    ///bin_count bins of the same width between the minimum and maximum values.
+    EqualWidth,
+    ///bin_count bins holding roughly the same number of rows.
+    Quantile,
+    ///The bins between the edges that the user has supplied.
+    Explicit,
+}
+
+impl BinningMethod {
+    pub fn from_string(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "equal_width" => Some(BinningMethod::EqualWidth),
+            "quantile" | "equal_frequency" => Some(BinningMethod::Quantile),
+            "explicit" => Some(BinningMethod::Explicit),
+            _ => None,
+        }
+    }
+}
+
+///Groups a continuous numeric column into bins so that an axis gets one vector per bin instead of
+///one per distinct value.  Given the edges e0 < e1 < ... < en the bins are [e0, e1) ... [en-1, en],
+///plus a bin for the values below e0 and one for the values above en.  The bins are numbered in
+///that order and each is labeled, i.e. "[0, 10)", and the labels are what the queries return.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinnedFieldDefinition {
+    pub field_type: FieldDefinitionType,
+    pub field_name: String,
+    pub binning_method: BinningMethod,
+    pub bin_count: usize,
+    ///The edges of the bins in ascending order.  Equal width and quantile edges depend on the
+    ///data, so they are empty until they have been resolved with with_edges.
+    pub edges: Vec<f64>,
+}
+
+impl BinnedFieldDefinition {
+    pub fn from_json(input: &Value) -> Result<Self, BinnedFieldDefinitionFromJsonError> {
+        let validation_result = Self::validate_json(input);
+        if validation_result.is_err() {
+            return Err(validation_result.err().unwrap());
+        }
+        let field_name = input["fieldName"].as_str().unwrap().to_string();
+        let raw_binning_method = &input["binningMethod"];
+        let binning_method = match raw_binning_method.as_str() {
+            Some(raw_binning_method) => BinningMethod::from_string(raw_binning_method),
+            None => None,
+        };
+        if binning_method.is_none() {
+            let description = format!("Invalid binning method: {}", raw_binning_method);
+            let data = json!({ "field": field_name, "binningMethod": raw_binning_method });
+            return Err(BinnedFieldDefinitionFromJsonError::InvalidBinningMethod(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
+        let binning_method = binning_method.unwrap();
+
+        let mut edges: Vec<f64> = Vec::new();
+        let bin_count;
+        if binning_method == BinningMethod::Explicit {
+            let parsed_edges = Self::parse_edges(&field_name, &input["edges"]);
+            if parsed_edges.is_err() {
+                return Err(parsed_edges.err().unwrap());
+            }
+            edges = parsed_edges.unwrap();
+            bin_count = edges.len() - 1;
+        } else {
+            let raw_bin_count = &input["binCount"];
+            let parsed_bin_count = raw_bin_count.as_u64().unwrap_or(0);
+            if parsed_bin_count == 0 {
+                let description = "The binCount must be a positive integer".to_string();
+                let data = json!({ "field": field_name, "binCount": raw_bin_count });
+                return Err(BinnedFieldDefinitionFromJsonError::InvalidBinParameter(
+                    GlyphxErrorData::new(description, Some(data), None),
+                ));
+            }
+            bin_count = parsed_bin_count as usize;
+        }
+
+        Ok(Self {
+            field_type: FieldDefinitionType::Binned,
+            field_name,
+            binning_method,
+            bin_count,
+            edges,
+        })
+    }
+
+    fn validate_json(input: &Value) -> Result<(), BinnedFieldDefinitionFromJsonError> {
+        let has_field_name = json_has_field(input, "fieldName");
+        if has_field_name.is_err() {
+            let err = has_field_name.err().unwrap();
+            let err = BinnedFieldDefinitionFromJsonError::from_json_has_field_error(err);
+            return Err(err);
+        }
+
+        let has_binning_method = json_has_field(input, "binningMethod");
+        if has_binning_method.is_err() {
+            let err = has_binning_method.err().unwrap();
+            let err = BinnedFieldDefinitionFromJsonError::from_json_has_field_error(err);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    ///Explicit edges must be at least two numbers in strictly ascending order.
+    fn parse_edges(
+        field_name: &str,
+        raw_edges: &Value,
+    ) -> Result<Vec<f64>, BinnedFieldDefinitionFromJsonError> {
+        let edges = match raw_edges.as_array() {
+            Some(raw_edges) => raw_edges
+                .iter()
+                .map(|edge| edge.as_f64())
+                .collect::<Option<Vec<f64>>>(),
+            None => None,
+        };
+        let is_valid = match &edges {
+            Some(edges) => edges.len() >= 2 && edges.windows(2).all(|pair| pair[0] < pair[1]),
+            None => false,
+        };
+        if !is_valid {
+            let description =
+                "The edges must be at least two numbers in ascending order".to_string();
+            let data = json!({ "field": field_name, "edges": raw_edges });
+            return Err(BinnedFieldDefinitionFromJsonError::InvalidBinParameter(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
+        Ok(edges.unwrap())
+    }
+
+    ///Returns true once the edges are known and the bins can be queried.
+    pub fn is_resolved(&self) -> bool {
+        self.edges.len() >= 2
+    }
+
+    ///The fractions of the data, 0 being the minimum and 1 the maximum, whose values we need to
+    ///resolve the edges.  Explicit edges do not depend on the data so they need none.
+    pub fn get_quantiles(&self) -> Vec<f64> {
+        match self.binning_method {
+            BinningMethod::EqualWidth => vec![0.0, 1.0],
+            BinningMethod::Quantile => (0..=self.bin_count)
+                .map(|index| index as f64 / self.bin_count as f64)
+                .collect(),
+            BinningMethod::Explicit => Vec::new(),
+        }
+    }
+
+    ///Turns the values at get_quantiles into edges.  Quantiles that land on the same value would
+    ///produce empty bins so they are collapsed, and a column with a single value gets one bin.
+    pub fn get_edges_from_quantiles(&self, values: &[f64]) -> Vec<f64> {
+        let mut edges: Vec<f64> = match self.binning_method {
+            BinningMethod::EqualWidth => {
+                let min = values[0];
+                let max = values[values.len() - 1];
+                let width = (max - min) / self.bin_count as f64;
+                let mut edges = (0..self.bin_count)
+                    .map(|index| min + width * index as f64)
+                    .collect::<Vec<f64>>();
+                edges.push(max);
+                edges
+            }
+            BinningMethod::Quantile => values.to_vec(),
+            BinningMethod::Explicit => self.edges.clone(),
+        };
+        edges.dedup();
+        if edges.len() == 1 {
+            edges.push(edges[0]);
+        }
+        edges
+    }
+
+    pub fn with_edges(&self, edges: Vec<f64>) -> Self {
+        Self {
+            edges,
+            ..self.clone()
+        }
+    }
+
+    ///Returns the number of the bin that the value falls into.
+    pub fn get_bin_index(&self, value: f64) -> usize {
+        let last = self.edges.len() - 1;
+        for (index, edge) in self.edges.iter().enumerate() {
+            if index == last {
+                return if value <= *edge { index } else { index + 1 };
+            }
+            if value < *edge {
+                return index;
+            }
+        }
+        0
+    }
+
+    pub fn get_bin_label(&self, index: usize) -> String {
+        let last = self.edges.len() - 1;
+        if index == 0 {
+            format!("< {}", self.edges[0])
+        } else if index > last {
+            format!("> {}", self.edges[last])
+        } else if index == last {
+            format!("[{}, {}]", self.edges[index - 1], self.edges[index])
+        } else {
+            format!("[{}, {})", self.edges[index - 1], self.edges[index])
+        }
+    }
+
+    ///Returns the labels of every bin, in bin order.
+    pub fn get_bin_labels(&self) -> Vec<String> {
+        (0..=self.edges.len())
+            .map(|index| self.get_bin_label(index))
+            .collect()
+    }
+
+    ///Returns the SQL that numbers the bin of a row, matching get_bin_index.
+    pub fn get_bin_index_query(&self) -> String {
+        let last = self.edges.len() - 1;
+        let mut query = format!(r#"CASE WHEN "{}" IS NULL THEN NULL"#, self.field_name);
+        for (index, edge) in self.edges.iter().enumerate() {
+            let operator = if index == last { "<=" } else { "<" };
+            query.push_str(&format!(
+                r#" WHEN "{}" {} {} THEN {}"#,
+                self.field_name, operator, edge, index
+            ));
+        }
+        query.push_str(&format!(" ELSE {} END", last + 1));
+        query
+    }
+
+    pub fn get_query(&self, display_name: &str) -> (String, String) {
+        let raw_query = if self.is_resolved() {
+            let labels = self
+                .get_bin_labels()
+                .iter()
+                .map(|label| format!("'{}'", label))
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!(
+                "element_at(ARRAY[{}], {} + 1)",
+                labels,
+                self.get_bin_index_query()
+            )
+        } else {
+            "CAST(NULL AS VARCHAR)".to_string()
+        };
+        let query = format!(r#"{} as "{}""#, raw_query, display_name);
+        (query, raw_query)
+    }
+}
+
+#[cfg(test)]
+mod from_json {
+    use super::*;
+
+    #[test]
+    fn equal_width() {
+        let input = json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "equal_width",
+            "binCount": 4
+        });
+        let result = BinnedFieldDefinition::from_json(&input).unwrap();
+        assert_eq!(result.field_name, "price");
+        assert_eq!(result.binning_method, BinningMethod::EqualWidth);
+        assert_eq!(result.bin_count, 4);
+        assert!(!result.is_resolved());
+    }
+
+    #[test]
+    fn explicit() {
+        let input = json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "explicit",
+            "edges": [0, 10.5, 100]
+        });
+        let result = BinnedFieldDefinition::from_json(&input).unwrap();
+        assert_eq!(result.edges, vec![0.0, 10.5, 100.0]);
+        assert_eq!(result.bin_count, 2);
+        assert!(result.is_resolved());
+    }
+
+    #[test]
+    fn field_name_is_missing() {
+        let input = json!({
+            "fieldType": "binned",
+            "binningMethod": "quantile",
+            "binCount": 4
+        });
+        let result = BinnedFieldDefinition::from_json(&input);
+        match result {
+            Err(BinnedFieldDefinitionFromJsonError::FieldNotDefined(_)) => {}
+            _ => panic!("Expected FieldNotDefined"),
+        }
+    }
+
+    #[test]
+    fn invalid_binning_method() {
+        let input = json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "log",
+            "binCount": 4
+        });
+        let result = BinnedFieldDefinition::from_json(&input);
+        match result {
+            Err(BinnedFieldDefinitionFromJsonError::InvalidBinningMethod(_)) => {}
+            _ => panic!("Expected InvalidBinningMethod"),
+        }
+    }
+
+    #[test]
+    fn bin_count_is_invalid() {
+        let input = json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "quantile",
+            "binCount": 0
+        });
+        let result = BinnedFieldDefinition::from_json(&input);
+        match result {
+            Err(BinnedFieldDefinitionFromJsonError::InvalidBinParameter(_)) => {}
+            _ => panic!("Expected InvalidBinParameter"),
+        }
+    }
+
+    #[test]
+    fn edges_are_not_ascending() {
+        let input = json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "explicit",
+            "edges": [0, 10, 10]
+        });
+        let result = BinnedFieldDefinition::from_json(&input);
+        match result {
+            Err(BinnedFieldDefinitionFromJsonError::InvalidBinParameter(_)) => {}
+            _ => panic!("Expected InvalidBinParameter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_edges_from_quantiles {
+    use super::*;
+
+    fn get_definition(binning_method: &str) -> BinnedFieldDefinition {
+        BinnedFieldDefinition::from_json(&json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": binning_method,
+            "binCount": 4
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn equal_width() {
+        let definition = get_definition("equal_width");
+        assert_eq!(definition.get_quantiles(), vec![0.0, 1.0]);
+        let edges = definition.get_edges_from_quantiles(&[10.0, 50.0]);
+        assert_eq!(edges, vec![10.0, 20.0, 30.0, 40.0, 50.0]);
+    }
+
+    #[test]
+    fn quantile() {
+        let definition = get_definition("quantile");
+        assert_eq!(definition.get_quantiles(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        let edges = definition.get_edges_from_quantiles(&[1.0, 2.0, 2.0, 7.0, 9.0]);
+        assert_eq!(edges, vec![1.0, 2.0, 7.0, 9.0]);
+    }
+
+    #[test]
+    fn single_value() {
+        let definition = get_definition("equal_width");
+        let edges = definition.get_edges_from_quantiles(&[5.0, 5.0]);
+        assert_eq!(edges, vec![5.0, 5.0]);
+        let definition = definition.with_edges(edges);
+        assert_eq!(definition.get_bin_index(5.0), 1);
+    }
+}
+
+#[cfg(test)]
+mod get_bin_index {
+    use super::*;
+
+    fn get_definition() -> BinnedFieldDefinition {
+        BinnedFieldDefinition::from_json(&json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "explicit",
+            "edges": [0, 10, 20]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn is_ok() {
+        let definition = get_definition();
+        assert_eq!(definition.get_bin_index(-1.0), 0);
+        assert_eq!(definition.get_bin_index(0.0), 1);
+        assert_eq!(definition.get_bin_index(9.9), 1);
+        assert_eq!(definition.get_bin_index(10.0), 2);
+        assert_eq!(definition.get_bin_index(20.0), 2);
+        assert_eq!(definition.get_bin_index(20.1), 3);
+    }
+
+    #[test]
+    fn labels() {
+        let definition = get_definition();
+        assert_eq!(
+            definition.get_bin_labels(),
+            vec!["< 0", "[0, 10)", "[10, 20]", "> 20"]
+        );
+    }
+}
+
+#[cfg(test)]
+mod get_query {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let definition = BinnedFieldDefinition::from_json(&json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "explicit",
+            "edges": [0, 10]
+        }))
+        .unwrap();
+        let (query, raw_query) = definition.get_query("Price");
+        assert_eq!(
+            raw_query,
+            r#"element_at(ARRAY['< 0', '[0, 10]', '> 10'], CASE WHEN "price" IS NULL THEN NULL WHEN "price" < 0 THEN 0 WHEN "price" <= 10 THEN 1 ELSE 2 END + 1)"#
+        );
+        assert_eq!(query, format!(r#"{} as "Price""#, raw_query));
+    }
+
+    #[test]
+    fn is_not_resolved() {
+        let definition = BinnedFieldDefinition::from_json(&json!({
+            "fieldType": "binned",
+            "fieldName": "price",
+            "binningMethod": "quantile",
+            "binCount": 4
+        }))
+        .unwrap();
+        let (_, raw_query) = definition.get_query("Price");
+        assert_eq!(raw_query, "CAST(NULL AS VARCHAR)");
+    }
+}
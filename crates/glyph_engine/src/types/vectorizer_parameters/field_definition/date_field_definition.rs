@@ -1,8 +1,11 @@
+use super::date_field_definition_errors::TryFromStrError as DateGroupingTryFromStrError;
 use super::DateFieldDefinitionFromJsonError;
 use crate::types::field_definition_type::FieldDefinitionType;
-use crate::types::vectorizer_parameters::json_has_field;
+use crate::types::vectorizer_parameters::{json_has_field, quote_identifier, quote_literal};
+use glyphx_core::GlyphxErrorData;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use time::{Date, Month};
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum DateGrouping {
@@ -22,13 +25,52 @@ pub enum DateGrouping {
     QualifiedQuarter,
     Quarter,
     YearOfWeek,
+    ///0 - 23
+    HourOfDay,
+    ///yyyymmddhh
+    QualifiedHour,
+    ///yyyymmddhhmm, where the minute is the start of the minute_bucket that the time falls in.
+    MinuteBucket,
+    ///yyyymmdd of the first day of the week, where weeks start on the week_start day.
+    WeekStarting,
 }
 
+///The dateGrouping values that a model definition can use.
+const DATE_GROUPING_NAMES: [&str; 22] = [
+    "qualified_day_of_year",
+    "day_of_year",
+    "qualified_day_of_month",
+    "year_day_of_month",
+    "month_day_of_month",
+    "day_of_month",
+    "qualified_day_of_week",
+    "day_of_week",
+    "qualified_week_of_year",
+    "week_of_year",
+    "qualified_month",
+    "month_of_year",
+    "month",
+    "year",
+    "qualified_quarter",
+    "quarter",
+    "year_of_week",
+    "hour_of_day",
+    "hour",
+    "qualified_hour",
+    "minute_bucket",
+    "week_starting",
+];
+
 impl DateGrouping {
+    ///Unknown values fall back to DayOfYear, use try_from_str to reject them instead.
     pub fn from_str(input: &str) -> DateGrouping {
+        Self::try_from_str(input).unwrap_or(DateGrouping::DayOfYear)
+    }
+
+    pub fn try_from_str(input: &str) -> Result<DateGrouping, DateGroupingTryFromStrError> {
         let cleaned_input = input.trim().to_lowercase();
         let cleaned_input = cleaned_input.as_str();
-        match cleaned_input {
+        let date_grouping = match cleaned_input {
             "qualified_day_of_year" => DateGrouping::QualifiedDayOfYear,
             "day_of_year" => DateGrouping::DayOfYear,
             "qualified_day_of_month" => DateGrouping::QualifiedDayOfMonth,
@@ -45,15 +87,193 @@ impl DateGrouping {
             "qualified_quarter" => DateGrouping::QualifiedQuarter,
             "quarter" => DateGrouping::Quarter,
             "year_of_week" => DateGrouping::YearOfWeek,
-            _ => DateGrouping::DayOfYear,
+            "hour_of_day" | "hour" => DateGrouping::HourOfDay,
+            "qualified_hour" => DateGrouping::QualifiedHour,
+            "minute_bucket" => DateGrouping::MinuteBucket,
+            "week_starting" => DateGrouping::WeekStarting,
+            _ => {
+                return Err(DateGroupingTryFromStrError::new(
+                    input,
+                    &DATE_GROUPING_NAMES,
+                ))
+            }
+        };
+        Ok(date_grouping)
+    }
+
+    ///Returns a display label for a value that get_query computed for this grouping, i.e.
+    ///"2023-Q1" for the QualifiedQuarter 20231 or "Feb 14 2023" for the QualifiedDayOfYear
+    ///2023045.  Returns None if the value is not one that this grouping produces.
+    pub fn get_label(&self, value: i64) -> Option<String> {
+        if value < 0 {
+            return None;
+        }
+        let label = match self {
+            DateGrouping::QualifiedDayOfYear => {
+                let date = Date::from_ordinal_date((value / 1000) as i32, (value % 1000) as u16);
+                format_date(date.ok()?)
+            }
+            DateGrouping::DayOfYear if (1..=366).contains(&value) => format!("Day {}", value),
+            DateGrouping::QualifiedDayOfMonth => format_date(get_date(value)?),
+            DateGrouping::YearDayOfMonth if (1..=31).contains(&(value % 100)) => {
+                format!("Day {} {}", value % 100, value / 100)
+            }
+            DateGrouping::MonthDayOfMonth if (1..=31).contains(&(value % 100)) => {
+                format!("{} {}", get_month_name(value / 100)?, value % 100)
+            }
+            DateGrouping::DayOfMonth if (1..=31).contains(&value) => format!("Day {}", value),
+            DateGrouping::QualifiedDayOfWeek if (1..=53).contains(&(value / 10 % 100)) => {
+                format!(
+                    "{} Week {} {}",
+                    get_day_name(value % 10)?,
+                    value / 10 % 100,
+                    value / 1000
+                )
+            }
+            DateGrouping::DayOfWeek => get_day_name(value)?.to_string(),
+            DateGrouping::QualifiedWeekOfYear if (1..=53).contains(&(value % 100)) => {
+                format!("Week {} {}", value % 100, value / 100)
+            }
+            DateGrouping::WeekOfYear if (1..=53).contains(&value) => format!("Week {}", value),
+            DateGrouping::QualifiedMonth => {
+                format!("{} {}", get_month_name(value % 100)?, value / 100)
+            }
+            DateGrouping::MonthOfYear => get_month_name(value)?.to_string(),
+            DateGrouping::Year | DateGrouping::YearOfWeek => value.to_string(),
+            DateGrouping::QualifiedQuarter if (1..=4).contains(&(value % 10)) => {
+                format!("{}-Q{}", value / 10, value % 10)
+            }
+            DateGrouping::Quarter if (1..=4).contains(&value) => format!("Q{}", value),
+            DateGrouping::HourOfDay if value < 24 => format!("{:02}:00", value),
+            DateGrouping::QualifiedHour if value % 100 < 24 => {
+                format!(
+                    "{} {:02}:00",
+                    format_date(get_date(value / 100)?),
+                    value % 100
+                )
+            }
+            DateGrouping::MinuteBucket if value / 100 % 100 < 24 && value % 100 < 60 => {
+                format!(
+                    "{} {:02}:{:02}",
+                    format_date(get_date(value / 10000)?),
+                    value / 100 % 100,
+                    value % 100
+                )
+            }
+            DateGrouping::WeekStarting => format!("Week of {}", format_date(get_date(value)?)),
+            _ => return None,
+        };
+        Some(label)
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+fn get_month_name(month: i64) -> Option<&'static str> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    Some(MONTH_NAMES[month as usize - 1])
+}
+
+///day_of_week runs from 1 for Monday to 7 for Sunday.
+fn get_day_name(day_of_week: i64) -> Option<&'static str> {
+    if !(1..=7).contains(&day_of_week) {
+        return None;
+    }
+    Some(DAY_NAMES[day_of_week as usize - 1])
+}
+
+///Parses a yyyymmdd value.
+fn get_date(value: i64) -> Option<Date> {
+    let month = Month::try_from((value / 100 % 100) as u8).ok()?;
+    Date::from_calendar_date((value / 10000) as i32, month, (value % 100) as u8).ok()
+}
+
+fn format_date(date: Date) -> String {
+    format!(
+        "{} {} {}",
+        MONTH_NAMES[u8::from(date.month()) as usize - 1],
+        date.day(),
+        date.year()
+    )
+}
+
+///How the values of a date column are stored.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DateUnit {
+    ///Milliseconds since the epoch.
+    Milliseconds,
+    ///Seconds since the epoch.
+    Seconds,
+    ///ISO 8601 strings, i.e. 2023-01-31T12:30:00Z.  Strings without an offset are taken to be UTC.
+    IsoString,
+    ///An Athena timestamp.  Timestamps have no time zone so they are taken to already be in the
+    ///time zone of the field definition.
+    Timestamp,
+}
+
+impl DateUnit {
+    pub fn from_string(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "milliseconds" | "millis" | "ms" => Some(DateUnit::Milliseconds),
+            "seconds" | "s" => Some(DateUnit::Seconds),
+            "iso" | "iso_string" | "iso8601" => Some(DateUnit::IsoString),
+            "timestamp" => Some(DateUnit::Timestamp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub fn from_string(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "monday" | "mon" => Some(Weekday::Monday),
+            "tuesday" | "tue" => Some(Weekday::Tuesday),
+            "wednesday" | "wed" => Some(Weekday::Wednesday),
+            "thursday" | "thu" => Some(Weekday::Thursday),
+            "friday" | "fri" => Some(Weekday::Friday),
+            "saturday" | "sat" => Some(Weekday::Saturday),
+            "sunday" | "sun" => Some(Weekday::Sunday),
+            _ => None,
         }
     }
+
+    ///1 for Monday through 7 for Sunday, which is what Athena's day_of_week returns.
+    pub fn number_from_monday(&self) -> i64 {
+        *self as i64 + 1
+    }
 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateFieldDefinition {
     pub field_type: FieldDefinitionType,
     pub field_name: String,
     pub date_grouping: DateGrouping,
+    pub date_unit: DateUnit,
+    ///The time zone that the dates are grouped in, either a name such as America/New_York or a
+    ///fixed offset such as +05:30.  None groups the dates in UTC.
+    pub time_zone: Option<String>,
+    pub week_start: Weekday,
+    ///The width, in minutes, of the MinuteBucket grouping.
+    pub minute_bucket: u32,
 }
 
 impl DateFieldDefinition {
@@ -65,12 +285,79 @@ impl DateFieldDefinition {
         }
         let field_name = input["fieldName"].as_str().unwrap().to_string();
         let field_type = FieldDefinitionType::Date;
-        let raw_date_grouping = input["dateGrouping"].as_str().unwrap().to_string();
-        let date_grouping = DateGrouping::from_str(&raw_date_grouping);
+        let raw_date_grouping = input["dateGrouping"].as_str().unwrap_or("").to_string();
+        let date_grouping = DateGrouping::try_from_str(&raw_date_grouping);
+        if date_grouping.is_err() {
+            let err = date_grouping.err().unwrap();
+            return Err(DateFieldDefinitionFromJsonError::from_try_from_str_error(err));
+        }
+        let date_grouping = date_grouping.unwrap();
+
+        let raw_date_unit = &input["dateUnit"];
+        let date_unit = match raw_date_unit {
+            Value::Null => Some(DateUnit::Milliseconds),
+            _ => raw_date_unit.as_str().and_then(DateUnit::from_string),
+        };
+        if date_unit.is_none() {
+            return Err(Self::invalid_date_option(
+                &field_name,
+                "dateUnit",
+                raw_date_unit,
+            ));
+        }
+        let date_unit = date_unit.unwrap();
+
+        let raw_time_zone = &input["timeZone"];
+        let time_zone = match raw_time_zone {
+            Value::Null => None,
+            _ => {
+                let time_zone = raw_time_zone.as_str().unwrap_or("").trim();
+                if !Self::is_valid_time_zone(time_zone) {
+                    return Err(Self::invalid_date_option(
+                        &field_name,
+                        "timeZone",
+                        raw_time_zone,
+                    ));
+                }
+                Some(time_zone.to_string())
+            }
+        };
+
+        let raw_week_start = &input["weekStart"];
+        let week_start = match raw_week_start {
+            Value::Null => Some(Weekday::Monday),
+            _ => raw_week_start.as_str().and_then(Weekday::from_string),
+        };
+        if week_start.is_none() {
+            return Err(Self::invalid_date_option(
+                &field_name,
+                "weekStart",
+                raw_week_start,
+            ));
+        }
+        let week_start = week_start.unwrap();
+
+        let raw_minute_bucket = &input["minuteBucket"];
+        let minute_bucket = match raw_minute_bucket {
+            Value::Null => 15,
+            _ => raw_minute_bucket.as_u64().unwrap_or(0),
+        };
+        if minute_bucket == 0 || minute_bucket > 1440 {
+            return Err(Self::invalid_date_option(
+                &field_name,
+                "minuteBucket",
+                raw_minute_bucket,
+            ));
+        }
+
         Ok(Self {
             field_type,
             field_name,
             date_grouping,
+            date_unit,
+            time_zone,
+            week_start,
+            minute_bucket: minute_bucket as u32,
         })
     }
     fn validate_json(input: &Value) -> Result<(), DateFieldDefinitionFromJsonError> {
@@ -89,94 +376,142 @@ impl DateFieldDefinition {
         }
         Ok(())
     }
-    pub fn get_query(&self, display_name: &str) -> (String, String) {
-        let raw_query = match &self.date_grouping {
-            DateGrouping::QualifiedDayOfYear => {
+
+    fn invalid_date_option(
+        field_name: &str,
+        option: &str,
+        value: &Value,
+    ) -> DateFieldDefinitionFromJsonError {
+        let description = format!(
+            "The {} {} of the date field {} is not valid",
+            option, value, field_name
+        );
+        let data = json!({ "field": field_name, option: value });
+        DateFieldDefinitionFromJsonError::InvalidDateOption(GlyphxErrorData::new(
+            description,
+            Some(data),
+            None,
+        ))
+    }
+
+    //Athena will reject a zone that it does not know about when the query runs, this just keeps
+    //anything other than a zone name or an offset out of the SQL.
+    fn is_valid_time_zone(time_zone: &str) -> bool {
+        !time_zone.is_empty()
+            && time_zone
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_+-/:".contains(c))
+    }
+
+    ///The SQL for the column as a timestamp in the time zone of the field definition.
+    fn get_timestamp_query(&self) -> String {
+        let field_name = quote_identifier(&self.field_name);
+        let time_zone = self
+            .time_zone
+            .as_ref()
+            .map(|time_zone| quote_literal(&json!(time_zone)));
+        match (&self.date_unit, time_zone) {
+            (DateUnit::Milliseconds, None) => format!("from_unixtime({}/1000)", field_name),
+            (DateUnit::Milliseconds, Some(time_zone)) => {
+                format!("from_unixtime({}/1000, {})", field_name, time_zone)
+            }
+            (DateUnit::Seconds, None) => format!("from_unixtime({})", field_name),
+            (DateUnit::Seconds, Some(time_zone)) => {
+                format!("from_unixtime({}, {})", field_name, time_zone)
+            }
+            (DateUnit::IsoString, None) => format!("from_iso8601_timestamp({})", field_name),
+            (DateUnit::IsoString, Some(time_zone)) => {
                 format!(
-                    r#"(year(from_unixtime("{}"/1000)) * 1000) + day_of_year(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name
+                    "(from_iso8601_timestamp({}) AT TIME ZONE {})",
+                    field_name, time_zone
                 )
             }
+            (DateUnit::Timestamp, _) => field_name,
+        }
+    }
 
-            DateGrouping::DayOfYear => {
-                format!(r#"day_of_year(from_unixtime("{}"/1000))"#, self.field_name)
+    ///Returns the display label of a value that the query for this field returned.
+    pub fn get_label(&self, value: &Value) -> Option<String> {
+        let value = match value {
+            Value::String(text) => text.trim().parse::<i64>().ok(),
+            _ => value.as_i64().or_else(|| {
+                value
+                    .as_f64()
+                    .filter(|v| v.fract() == 0.0)
+                    .map(|v| v as i64)
+            }),
+        };
+        self.date_grouping.get_label(value?)
+    }
+
+    pub fn get_query(&self, display_name: &str) -> (String, String) {
+        let t = self.get_timestamp_query();
+        let raw_query = match &self.date_grouping {
+            DateGrouping::QualifiedDayOfYear => {
+                format!("(year({t}) * 1000) + day_of_year({t})")
             }
 
+            DateGrouping::DayOfYear => format!("day_of_year({t})"),
+
             DateGrouping::QualifiedDayOfMonth => {
-                format!(
-                    r#"(year(from_unixtime("{}"/1000)) * 10000) + (month(from_unixtime("{}"/1000)) * 100) + day_of_month(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name, self.field_name
-                )
+                format!("(year({t}) * 10000) + (month({t}) * 100) + day_of_month({t})")
             }
 
-            DateGrouping::YearDayOfMonth => {
-                format!(
-                    r#"(year(from_unixtime("{}"/1000)) * 100) + day_of_month(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name
-                )
-            }
+            DateGrouping::YearDayOfMonth => format!("(year({t}) * 100) + day_of_month({t})"),
 
-            DateGrouping::MonthDayOfMonth => {
-                format!(
-                    r#"(month(from_unixtime("{}"/1000)) * 100) + day_of_month(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name
-                )
-            }
+            DateGrouping::MonthDayOfMonth => format!("(month({t}) * 100) + day_of_month({t})"),
 
-            DateGrouping::DayOfMonth => {
-                format!(r#"day(from_unixtime("{}"/1000))"#, self.field_name)
-            }
+            DateGrouping::DayOfMonth => format!("day({t})"),
 
             DateGrouping::QualifiedDayOfWeek => {
-                format!(
-                    r#"(year_of_week(from_unixtime("{}"/1000)) * 1000) + (week_of_year(from_unixtime("{}"/1000)) * 10) + day_of_week(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name, self.field_name
-                )
+                format!("(year_of_week({t}) * 1000) + (week_of_year({t}) * 10) + day_of_week({t})")
             }
 
-            DateGrouping::DayOfWeek => {
-                format!(r#"day_of_week(from_unixtime("{}"/1000))"#, self.field_name)
-            }
+            DateGrouping::DayOfWeek => format!("day_of_week({t})"),
 
             DateGrouping::QualifiedWeekOfYear => {
-                format!(
-                    r#" (year_of_week(from_unixtime({}/1000)) * 100) + (week_of_year(from_unixtime({}/1000)))"#,
-                    self.field_name, self.field_name
-                )
+                format!("(year_of_week({t}) * 100) + week_of_year({t})")
             }
 
-            DateGrouping::WeekOfYear => {
-                format!(r#"week_of_year(from_unixtime("{}"/1000))"#, self.field_name)
-            }
+            DateGrouping::WeekOfYear => format!("week_of_year({t})"),
 
-            DateGrouping::QualifiedMonth => {
-                format!(
-                    r#"(year(from_unixtime("{}"/1000)) * 100) + month(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name
-                )
-            }
+            DateGrouping::QualifiedMonth => format!("(year({t}) * 100) + month({t})"),
 
-            DateGrouping::MonthOfYear => {
-                format!(r#"month(from_unixtime("{}"/1000))"#, self.field_name)
-            }
+            DateGrouping::MonthOfYear => format!("month({t})"),
 
-            DateGrouping::Year => {
-                format!(r#"year(from_unixtime("{}"/1000))"#, self.field_name)
-            }
+            DateGrouping::Year => format!("year({t})"),
+
+            DateGrouping::QualifiedQuarter => format!("(year({t}) * 10) + quarter({t})"),
+
+            DateGrouping::Quarter => format!("quarter({t})"),
+
+            DateGrouping::YearOfWeek => format!("year_of_week({t})"),
+
+            DateGrouping::HourOfDay => format!("hour({t})"),
 
-            DateGrouping::QualifiedQuarter => {
+            DateGrouping::QualifiedHour => {
                 format!(
-                    r#"(year(from_unixtime("{}"/1000)) * 10) + quarter(from_unixtime("{}"/1000))"#,
-                    self.field_name, self.field_name
+                    "(year({t}) * 1000000) + (month({t}) * 10000) + (day_of_month({t}) * 100) + hour({t})"
                 )
             }
 
-            DateGrouping::Quarter => {
-                format!(r#"quarter(from_unixtime("{}"/1000))"#, self.field_name)
+            DateGrouping::MinuteBucket => {
+                //The minute of the day, rounded down to the start of its bucket.
+                let m = format!(
+                    "(((hour({t}) * 60) + minute({t})) / {n} * {n})",
+                    n = self.minute_bucket
+                );
+                format!(
+                    "(year({t}) * 100000000) + (month({t}) * 1000000) + (day_of_month({t}) * 10000) + ({m} / 60 * 100) + ({m} % 60)"
+                )
             }
 
-            DateGrouping::YearOfWeek => {
-                format!(r#"year_of_week(from_unixtime("{}"/1000))"#, self.field_name)
+            DateGrouping::WeekStarting => {
+                let w = format!(
+                    "date_add('day', -((day_of_week({t}) + {}) % 7), {t})",
+                    7 - self.week_start.number_from_monday()
+                );
+                format!("(year({w}) * 10000) + (month({w}) * 100) + day_of_month({w})")
             }
         };
         let query = format!(r#"{} as "{}""#, raw_query, display_name);
@@ -340,6 +675,42 @@ mod DateGrouping_from_str {
         }
     }
 
+    #[test]
+    fn hour_of_day() {
+        let date_grouping = DateGrouping::from_str("hour_of_day");
+        match date_grouping {
+            DateGrouping::HourOfDay => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn qualified_hour() {
+        let date_grouping = DateGrouping::from_str("qualified_hour");
+        match date_grouping {
+            DateGrouping::QualifiedHour => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn minute_bucket() {
+        let date_grouping = DateGrouping::from_str("minute_bucket");
+        match date_grouping {
+            DateGrouping::MinuteBucket => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn week_starting() {
+        let date_grouping = DateGrouping::from_str("week_starting");
+        match date_grouping {
+            DateGrouping::WeekStarting => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
     #[test]
     fn unknown() {
         let date_grouping = DateGrouping::from_str("unknown");
@@ -350,6 +721,49 @@ mod DateGrouping_from_str {
     }
 }
 
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod DateGrouping_try_from_str {
+    use super::*;
+
+    #[test]
+    fn accepts_every_name() {
+        for name in DATE_GROUPING_NAMES {
+            assert!(DateGrouping::try_from_str(name).is_ok(), "{}", name);
+        }
+        assert!(matches!(
+            DateGrouping::try_from_str(" Qualified_Quarter "),
+            Ok(DateGrouping::QualifiedQuarter)
+        ));
+    }
+
+    #[test]
+    fn matches_the_model_definition() {
+        use crate::types::vectorizer_parameters::ModelDateGrouping;
+        for name in DATE_GROUPING_NAMES {
+            let model_date_grouping: ModelDateGrouping =
+                serde_json::from_value(json!(name)).unwrap();
+            let name = serde_json::to_value(model_date_grouping).unwrap();
+            assert!(DateGrouping::try_from_str(name.as_str().unwrap()).is_ok());
+        }
+    }
+
+    #[test]
+    fn unknown() {
+        let result = DateGrouping::try_from_str("qualified_day");
+        match result.err().unwrap() {
+            DateGroupingTryFromStrError::UnknownDateGrouping(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["value"], "qualified_day");
+                assert_eq!(
+                    data["acceptedValues"].as_array().unwrap().len(),
+                    DATE_GROUPING_NAMES.len()
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod validate_json {
     use super::*;
@@ -462,4 +876,318 @@ mod from_json {
             }
         }
     }
+
+    #[test]
+    fn defaults() {
+        let input = json!({
+            "fieldType": "date",
+            "fieldName": "test",
+            "dateGrouping": "day_of_year"
+        });
+        let result = DateFieldDefinition::from_json(&input).unwrap();
+        assert_eq!(result.date_unit, DateUnit::Milliseconds);
+        assert!(result.time_zone.is_none());
+        assert_eq!(result.week_start, Weekday::Monday);
+        assert_eq!(result.minute_bucket, 15);
+    }
+
+    #[test]
+    fn with_options() {
+        let input = json!({
+            "fieldType": "date",
+            "fieldName": "test",
+            "dateGrouping": "minute_bucket",
+            "dateUnit": "seconds",
+            "timeZone": "America/New_York",
+            "weekStart": "sunday",
+            "minuteBucket": 30
+        });
+        let result = DateFieldDefinition::from_json(&input).unwrap();
+        assert_eq!(result.date_unit, DateUnit::Seconds);
+        assert_eq!(result.time_zone.unwrap(), "America/New_York");
+        assert_eq!(result.week_start, Weekday::Sunday);
+        assert_eq!(result.minute_bucket, 30);
+    }
+
+    #[test]
+    fn invalid_options() {
+        let cases = vec![
+            ("dateUnit", json!("fortnights")),
+            ("timeZone", json!("UTC'; DROP TABLE x")),
+            ("timeZone", json!("")),
+            ("weekStart", json!("someday")),
+            ("minuteBucket", json!(0)),
+            ("minuteBucket", json!(1441)),
+        ];
+        for (option, value) in cases {
+            let mut input = json!({
+                "fieldType": "date",
+                "fieldName": "test",
+                "dateGrouping": "day_of_year"
+            });
+            input[option] = value.clone();
+            let result = DateFieldDefinition::from_json(&input);
+            match result.err().unwrap() {
+                DateFieldDefinitionFromJsonError::InvalidDateOption(error_data) => {
+                    let data = error_data.data.unwrap();
+                    assert_eq!(data["field"], "test");
+                    assert_eq!(data[option], value);
+                }
+                _ => panic!("Expected InvalidDateOption for {}", option),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_date_grouping() {
+        let input = json!({
+            "fieldType": "date",
+            "fieldName": "test",
+            "dateGrouping": "qualified_day"
+        });
+        let result = DateFieldDefinition::from_json(&input);
+        match result.err().unwrap() {
+            DateFieldDefinitionFromJsonError::InvalidDateOption(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["value"], "qualified_day");
+                assert!(data["acceptedValues"].is_array());
+            }
+            _ => panic!("Expected InvalidDateOption"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_query {
+    use super::*;
+    use serde_json::json;
+
+    fn get_field_definition(options: Value) -> DateFieldDefinition {
+        let mut input = json!({
+            "fieldType": "date",
+            "fieldName": "sold",
+        });
+        for (key, value) in options.as_object().unwrap() {
+            input[key] = value.clone();
+        }
+        DateFieldDefinition::from_json(&input).unwrap()
+    }
+
+    #[test]
+    fn default_is_epoch_milliseconds_in_utc() {
+        let field_definition = get_field_definition(json!({"dateGrouping": "day_of_month"}));
+        let (query, raw_query) = field_definition.get_query("x");
+        assert_eq!(raw_query, r#"day(from_unixtime("sold"/1000))"#);
+        assert_eq!(query, r#"day(from_unixtime("sold"/1000)) as "x""#);
+    }
+
+    #[test]
+    fn date_units() {
+        let cases = vec![
+            (
+                json!({}),
+                r#"hour(from_unixtime("sold"/1000, 'America/New_York'))"#,
+            ),
+            (
+                json!({"dateUnit": "seconds"}),
+                r#"hour(from_unixtime("sold", 'America/New_York'))"#,
+            ),
+            (
+                json!({"dateUnit": "iso"}),
+                r#"hour((from_iso8601_timestamp("sold") AT TIME ZONE 'America/New_York'))"#,
+            ),
+            (json!({"dateUnit": "timestamp"}), r#"hour("sold")"#),
+        ];
+        for (options, expected) in cases {
+            let mut options = options;
+            options["dateGrouping"] = json!("hour_of_day");
+            options["timeZone"] = json!("America/New_York");
+            let field_definition = get_field_definition(options);
+            assert_eq!(field_definition.get_query("x").1, expected);
+        }
+    }
+
+    #[test]
+    fn seconds_without_a_time_zone() {
+        let field_definition =
+            get_field_definition(json!({"dateGrouping": "year", "dateUnit": "s"}));
+        assert_eq!(
+            field_definition.get_query("x").1,
+            r#"year(from_unixtime("sold"))"#
+        );
+    }
+
+    #[test]
+    fn qualified_week_of_year() {
+        let field_definition =
+            get_field_definition(json!({"dateGrouping": "qualified_week_of_year"}));
+        assert_eq!(
+            field_definition.get_query("x").1,
+            r#"(year_of_week(from_unixtime("sold"/1000)) * 100) + week_of_year(from_unixtime("sold"/1000))"#
+        );
+    }
+
+    #[test]
+    fn qualified_hour() {
+        let field_definition = get_field_definition(
+            json!({"dateGrouping": "qualified_hour", "dateUnit": "timestamp"}),
+        );
+        assert_eq!(
+            field_definition.get_query("x").1,
+            r#"(year("sold") * 1000000) + (month("sold") * 10000) + (day_of_month("sold") * 100) + hour("sold")"#
+        );
+    }
+
+    #[test]
+    fn minute_bucket() {
+        let field_definition = get_field_definition(json!({
+            "dateGrouping": "minute_bucket",
+            "dateUnit": "timestamp",
+            "minuteBucket": 30
+        }));
+        let m = r#"(((hour("sold") * 60) + minute("sold")) / 30 * 30)"#;
+        assert_eq!(
+            field_definition.get_query("x").1,
+            format!(
+                r#"(year("sold") * 100000000) + (month("sold") * 1000000) + (day_of_month("sold") * 10000) + ({m} / 60 * 100) + ({m} % 60)"#
+            )
+        );
+    }
+
+    #[test]
+    fn week_starting() {
+        let field_definition = get_field_definition(json!({
+            "dateGrouping": "week_starting",
+            "dateUnit": "timestamp",
+            "weekStart": "sunday"
+        }));
+        let w = r#"date_add('day', -((day_of_week("sold") + 0) % 7), "sold")"#;
+        assert_eq!(
+            field_definition.get_query("x").1,
+            format!("(year({w}) * 10000) + (month({w}) * 100) + day_of_month({w})")
+        );
+    }
+
+    #[test]
+    fn quotes_the_field_name() {
+        let field_definition = get_field_definition(json!({"dateGrouping": "year"}));
+        let field_definition = DateFieldDefinition {
+            field_name: r#"so"ld"#.to_string(),
+            ..field_definition
+        };
+        assert_eq!(
+            field_definition.get_query("x").1,
+            r#"year(from_unixtime("so""ld"/1000))"#
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod DateGrouping_get_label {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let cases = vec![
+            (DateGrouping::QualifiedDayOfYear, 2023045, "Feb 14 2023"),
+            (DateGrouping::DayOfYear, 45, "Day 45"),
+            (DateGrouping::QualifiedDayOfMonth, 20230214, "Feb 14 2023"),
+            (DateGrouping::YearDayOfMonth, 202314, "Day 14 2023"),
+            (DateGrouping::MonthDayOfMonth, 214, "Feb 14"),
+            (DateGrouping::DayOfMonth, 14, "Day 14"),
+            (DateGrouping::QualifiedDayOfWeek, 2023072, "Tue Week 7 2023"),
+            (DateGrouping::DayOfWeek, 7, "Sun"),
+            (DateGrouping::QualifiedWeekOfYear, 202307, "Week 7 2023"),
+            (DateGrouping::WeekOfYear, 7, "Week 7"),
+            (DateGrouping::QualifiedMonth, 202302, "Feb 2023"),
+            (DateGrouping::MonthOfYear, 12, "Dec"),
+            (DateGrouping::Year, 2023, "2023"),
+            (DateGrouping::QualifiedQuarter, 20231, "2023-Q1"),
+            (DateGrouping::Quarter, 4, "Q4"),
+            (DateGrouping::YearOfWeek, 2022, "2022"),
+            (DateGrouping::HourOfDay, 9, "09:00"),
+            (DateGrouping::QualifiedHour, 2023021413, "Feb 14 2023 13:00"),
+            (
+                DateGrouping::MinuteBucket,
+                202302141330,
+                "Feb 14 2023 13:30",
+            ),
+            (DateGrouping::WeekStarting, 20230213, "Week of Feb 13 2023"),
+        ];
+        for (date_grouping, value, expected) in cases {
+            assert_eq!(
+                date_grouping.get_label(value),
+                Some(expected.to_string()),
+                "{:?}",
+                date_grouping
+            );
+        }
+    }
+
+    #[test]
+    fn is_none() {
+        let cases = vec![
+            (DateGrouping::QualifiedDayOfYear, 2023367),
+            (DateGrouping::QualifiedDayOfMonth, 20230230),
+            (DateGrouping::DayOfWeek, 0),
+            (DateGrouping::MonthOfYear, 13),
+            (DateGrouping::QualifiedQuarter, 20235),
+            (DateGrouping::HourOfDay, 24),
+            (DateGrouping::MinuteBucket, 202302141360),
+            (DateGrouping::Year, -1),
+        ];
+        for (date_grouping, value) in cases {
+            assert!(
+                date_grouping.get_label(value).is_none(),
+                "{:?}",
+                date_grouping
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_label {
+    use super::*;
+    use serde_json::json;
+
+    fn get_field_definition() -> DateFieldDefinition {
+        DateFieldDefinition::from_json(&json!({
+            "fieldType": "date",
+            "fieldName": "sold",
+            "dateGrouping": "qualified_quarter"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn number_values() {
+        let field_definition = get_field_definition();
+        assert_eq!(
+            field_definition.get_label(&json!(20231)).unwrap(),
+            "2023-Q1"
+        );
+        assert_eq!(
+            field_definition.get_label(&json!(20232.0)).unwrap(),
+            "2023-Q2"
+        );
+    }
+
+    #[test]
+    fn string_values() {
+        let field_definition = get_field_definition();
+        assert_eq!(
+            field_definition.get_label(&json!("20233")).unwrap(),
+            "2023-Q3"
+        );
+    }
+
+    #[test]
+    fn other_values() {
+        let field_definition = get_field_definition();
+        assert!(field_definition.get_label(&json!(20231.5)).is_none());
+        assert!(field_definition.get_label(&json!("abc")).is_none());
+        assert!(field_definition.get_label(&Value::Null).is_none());
+    }
 }
@@ -1,3 +1,4 @@
+use super::TryFromStrError;
 use crate::types::vectorizer_parameters::helper_functions::JsonHasFieldError;
 use glyphx_core::GlyphxError;
 use glyphx_core::GlyphxErrorData;
@@ -5,6 +6,7 @@ use glyphx_core::GlyphxErrorData;
 #[error_definition("DateFieldDefinition")]
 pub enum FromJsonError {
     FieldNotDefined(GlyphxErrorData),
+    InvalidDateOption(GlyphxErrorData),
 }
 
 impl FromJsonError {
@@ -13,6 +15,12 @@ impl FromJsonError {
             JsonHasFieldError::JsonValidationError(data) => Self::FieldNotDefined(data),
         }
     }
+
+    pub fn from_try_from_str_error(input: TryFromStrError) -> Self {
+        match input {
+            TryFromStrError::UnknownDateGrouping(data) => Self::InvalidDateOption(data),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -44,3 +52,22 @@ mod from_json_has_field_error {
         }
     }
 }
+
+#[cfg(test)]
+mod from_try_from_str_error {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let input = TryFromStrError::new("qualified_day", &["day_of_year"]);
+        let result = FromJsonError::from_try_from_str_error(input);
+        match result {
+            FromJsonError::InvalidDateOption(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], "qualified_day");
+                assert_eq!(d["acceptedValues"][0], "day_of_year");
+            }
+            _ => panic!("Expected InvalidDateOption"),
+        }
+    }
+}
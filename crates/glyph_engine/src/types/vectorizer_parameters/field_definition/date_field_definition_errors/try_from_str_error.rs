@@ -0,0 +1,38 @@
+use glyphx_core::GlyphxError;
+use glyphx_core::GlyphxErrorData;
+
+use serde_json::json;
+
+#[derive(Debug, Clone, GlyphxError)]
+#[error_definition("DateGrouping")]
+pub enum TryFromStrError {
+    UnknownDateGrouping(GlyphxErrorData),
+}
+
+impl TryFromStrError {
+    pub fn new(value: &str, accepted_values: &[&str]) -> Self {
+        let message = format!("The dateGrouping '{}' is not valid", value);
+        let data = json!({ "value": value, "acceptedValues": accepted_values });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::UnknownDateGrouping(error_data)
+    }
+}
+
+#[cfg(test)]
+mod constructor {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let result = TryFromStrError::new("qualified_day", &["day_of_year", "month"]);
+        match result {
+            TryFromStrError::UnknownDateGrouping(error_data) => {
+                assert_eq!(error_data.message, "The dateGrouping 'qualified_day' is not valid");
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], "qualified_day");
+                assert_eq!(d["acceptedValues"][1], "month");
+                assert!(error_data.inner_error.is_none());
+            }
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use crate::types::vectorizer_parameters::field_definition::accumulated_field_definition_errors::FromJsonError as AccumulatorFieldDefinitionFromJsonError;
+use crate::types::vectorizer_parameters::field_definition::binned_field_definition_errors::FromJsonError as BinnedFieldDefinitionFromJsonError;
 use crate::types::vectorizer_parameters::field_definition::date_field_definition_errors::FromJsonError as DateFieldDefinitionFromJsonError;
+use crate::types::vectorizer_parameters::field_definition::formula_field_definition_errors::FromJsonError as FormulaFieldDefinitionFromJsonError;
 use crate::types::vectorizer_parameters::field_definition::standard_field_definition_errors::FromJsonError as StandardFieldDefinitionFromJsonError;
 use crate::types::vectorizer_parameters::helper_functions::JsonHasFieldError;
+use crate::types::FieldTypeTryFromNumericValueError;
 use glyphx_core::GlyphxError;
 use glyphx_core::GlyphxErrorData;
 
@@ -14,7 +17,10 @@ pub enum FromJsonError {
     InvalidFieldDefinitionType(GlyphxErrorData),
     StandardFieldDefinitionError(GlyphxErrorData),
     DateFieldDefinitionError(GlyphxErrorData),
+    FormulaFieldDefinitionError(GlyphxErrorData),
     AccumulatorFieldDefinitionError(GlyphxErrorData),
+    BinnedFieldDefinitionError(GlyphxErrorData),
+    InvalidSortOrder(GlyphxErrorData),
 }
 
 impl FromJsonError {
@@ -24,6 +30,20 @@ impl FromJsonError {
         }
     }
 
+    pub fn from_try_from_numeric_value_error(
+        input: FieldTypeTryFromNumericValueError,
+        field_name: &str,
+    ) -> Self {
+        match input {
+            FieldTypeTryFromNumericValueError::UnknownFieldType(data) => {
+                let mut d = data.data.unwrap();
+                d["field"] = field_name.into();
+                d["field_type"] = d["value"].clone();
+                Self::InvalidFieldType(GlyphxErrorData::new(data.message, Some(d), None))
+            }
+        }
+    }
+
     pub fn from_standard_field_from_json_error(
         input: StandardFieldDefinitionFromJsonError,
     ) -> Self {
@@ -31,6 +51,9 @@ impl FromJsonError {
             StandardFieldDefinitionFromJsonError::FieldNotDefined(data) => {
                 FromJsonError::StandardFieldDefinitionError(data)
             }
+            StandardFieldDefinitionFromJsonError::InvalidTopN(data) => {
+                FromJsonError::StandardFieldDefinitionError(data)
+            }
         }
     }
 
@@ -39,6 +62,34 @@ impl FromJsonError {
             DateFieldDefinitionFromJsonError::FieldNotDefined(data) => {
                 FromJsonError::DateFieldDefinitionError(data)
             }
+            DateFieldDefinitionFromJsonError::InvalidDateOption(data) => {
+                FromJsonError::DateFieldDefinitionError(data)
+            }
+        }
+    }
+
+    pub fn from_formula_field_from_json_error(input: FormulaFieldDefinitionFromJsonError) -> Self {
+        match input {
+            FormulaFieldDefinitionFromJsonError::FieldNotDefined(data) => {
+                FromJsonError::FormulaFieldDefinitionError(data)
+            }
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(data) => {
+                FromJsonError::FormulaFieldDefinitionError(data)
+            }
+        }
+    }
+
+    pub fn from_binned_field_from_json_error(input: BinnedFieldDefinitionFromJsonError) -> Self {
+        match input {
+            BinnedFieldDefinitionFromJsonError::FieldNotDefined(data) => {
+                FromJsonError::BinnedFieldDefinitionError(data)
+            }
+            BinnedFieldDefinitionFromJsonError::InvalidBinningMethod(data) => {
+                FromJsonError::BinnedFieldDefinitionError(data)
+            }
+            BinnedFieldDefinitionFromJsonError::InvalidBinParameter(data) => {
+                FromJsonError::BinnedFieldDefinitionError(data)
+            }
         }
     }
 
@@ -62,6 +113,15 @@ impl FromJsonError {
             AccumulatorFieldDefinitionFromJsonError::DateFieldDefinitionFromJsonError(data) => {
                 Self::reformat_accumlator_error(&input, data, "DateFieldDefinitionFromJsonError")
             }
+            AccumulatorFieldDefinitionFromJsonError::FormulaFieldDefinitionFromJsonError(data) => {
+                Self::reformat_accumlator_error(&input, data, "FormulaFieldDefinitionFromJsonError")
+            }
+            AccumulatorFieldDefinitionFromJsonError::InvalidAccumulatorParameter(data) => {
+                Self::reformat_accumlator_error(&input, data, "InvalidAccumulatorParameter")
+            }
+            AccumulatorFieldDefinitionFromJsonError::InvalidAccumulatorType(data) => {
+                Self::reformat_accumlator_error(&input, data, "InvalidAccumulatorType")
+            }
         }
     }
 
@@ -108,6 +168,27 @@ mod from_json_has_field_error {
     }
 }
 
+#[cfg(test)]
+mod from_try_from_numeric_value_error {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn is_ok() {
+        let input = FieldTypeTryFromNumericValueError::new(&json!(9), &[(0, "number")]);
+        let result = FromJsonError::from_try_from_numeric_value_error(input, "test");
+        match result {
+            FromJsonError::InvalidFieldType(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["field"], "test");
+                assert_eq!(d["field_type"], 9);
+                assert_eq!(d["acceptedValues"][0]["name"], "number");
+            }
+            _ => panic!("Expected InvalidFieldType"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod from_standard_field_from_json_error {
     use super::*;
@@ -135,6 +216,27 @@ mod from_standard_field_from_json_error {
             _ => panic!("Expected FieldNotDefined"),
         }
     }
+
+    #[test]
+    fn invalid_top_n() {
+        let message = "testMessage";
+        let data = json!({"field": "topN", "count": 0});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = StandardFieldDefinitionFromJsonError::InvalidTopN(data);
+
+        let result = FromJsonError::from_standard_field_from_json_error(input);
+        match result {
+            FromJsonError::StandardFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                assert_eq!(d["field"].as_str().unwrap(), "topN");
+            }
+            _ => panic!("Expected StandardFieldDefinitionError"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -164,6 +266,107 @@ mod from_date_field_from_json_error {
             _ => panic!("Expected FieldNotDefined"),
         }
     }
+
+    #[test]
+    fn invalid_date_option() {
+        let message = "testMessage";
+        let data = json!({"field": "test", "timeZone": "bad zone"});
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), None);
+
+        let input = DateFieldDefinitionFromJsonError::InvalidDateOption(data);
+
+        let result = FromJsonError::from_date_field_from_json_error(input);
+        match result {
+            FromJsonError::DateFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                assert_eq!(d["timeZone"].as_str().unwrap(), "bad zone");
+            }
+            _ => panic!("Expected DateFieldDefinitionError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_formula_field_from_json_error {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn field_not_defined() {
+        let message = "testMessage";
+        let data = json!({"field": "test"});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = FormulaFieldDefinitionFromJsonError::FieldNotDefined(data);
+
+        let result = FromJsonError::from_formula_field_from_json_error(input);
+        match result {
+            FromJsonError::FormulaFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let field = d["field"].as_str().unwrap();
+                assert_eq!(field, "test");
+                assert!(error_data.inner_error.is_none());
+            }
+            _ => panic!("Expected FormulaFieldDefinitionError"),
+        }
+    }
+
+    #[test]
+    fn invalid_formula() {
+        let message = "testMessage";
+        let data = json!({"formula": "a +", "position": 3});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = FormulaFieldDefinitionFromJsonError::InvalidFormula(data);
+
+        let result = FromJsonError::from_formula_field_from_json_error(input);
+        match result {
+            FromJsonError::FormulaFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let formula = d["formula"].as_str().unwrap();
+                assert_eq!(formula, "a +");
+                assert!(error_data.inner_error.is_none());
+            }
+            _ => panic!("Expected FormulaFieldDefinitionError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_binned_field_from_json_error {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn invalid_bin_parameter() {
+        let message = "testMessage";
+        let data = json!({"field": "price", "binCount": 0});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = BinnedFieldDefinitionFromJsonError::InvalidBinParameter(data);
+
+        let result = FromJsonError::from_binned_field_from_json_error(input);
+        match result {
+            FromJsonError::BinnedFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let field = d["field"].as_str().unwrap();
+                assert_eq!(field, "price");
+                assert!(error_data.inner_error.is_none());
+            }
+            _ => panic!("Expected BinnedFieldDefinitionError"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -271,4 +474,74 @@ mod from_accumulated_field_from_json_error {
             _ => panic!("Expected FieldNotDefined"),
         }
     }
+
+    #[test]
+    fn formula_field_definition_from_json_error() {
+        let message = "testMessage";
+        let data = json!({"formula": "a +"});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input =
+            AccumulatorFieldDefinitionFromJsonError::FormulaFieldDefinitionFromJsonError(data);
+
+        let result = FromJsonError::from_accumulated_field_from_json_error(input);
+        match result {
+            FromJsonError::AccumulatorFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let formula = d["formula"].as_str().unwrap();
+                assert_eq!(formula, "a +");
+                let error_type = d["errorType"].as_str().unwrap();
+                assert_eq!(error_type, "FormulaFieldDefinitionFromJsonError");
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected AccumulatorFieldDefinitionError"),
+        }
+    }
+
+    #[test]
+    fn invalid_accumulator_parameter() {
+        let message = "testMessage";
+        let data = json!({"accumulator": "percentile"});
+        let inner_error = None;
+
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), inner_error);
+
+        let input = AccumulatorFieldDefinitionFromJsonError::InvalidAccumulatorParameter(data);
+
+        let result = FromJsonError::from_accumulated_field_from_json_error(input);
+        match result {
+            FromJsonError::AccumulatorFieldDefinitionError(error_data) => {
+                assert_eq!(error_data.message, message);
+                let d = error_data.data.unwrap();
+                let accumulator = d["accumulator"].as_str().unwrap();
+                assert_eq!(accumulator, "percentile");
+                let error_type = d["errorType"].as_str().unwrap();
+                assert_eq!(error_type, "InvalidAccumulatorParameter");
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected AccumulatorFieldDefinitionError"),
+        }
+    }
+
+    #[test]
+    fn invalid_accumulator_type() {
+        let message = "testMessage";
+        let data = json!({"value": "summ"});
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), None);
+
+        let input = AccumulatorFieldDefinitionFromJsonError::InvalidAccumulatorType(data);
+
+        let result = FromJsonError::from_accumulated_field_from_json_error(input);
+        match result {
+            FromJsonError::AccumulatorFieldDefinitionError(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["value"], "summ");
+                assert_eq!(d["errorType"], "InvalidAccumulatorType");
+            }
+            _ => panic!("Expected AccumulatorFieldDefinitionError"),
+        }
+    }
 }
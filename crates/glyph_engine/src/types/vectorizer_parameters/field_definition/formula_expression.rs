@@ -0,0 +1,752 @@
+use super::FormulaFieldDefinitionFromJsonError;
+use glyphx_core::GlyphxErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::str::FromStr;
+
+//Formulas are user supplied, so we cap how deeply they can nest to keep a pathological input
+//from blowing the stack while we recurse through it.
+const MAX_NESTING_DEPTH: usize = 64;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FormulaOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+impl FormulaOperator {
+    fn to_sql(self, left: &str, right: &str) -> String {
+        match self {
+            FormulaOperator::Add => format!("({} + {})", left, right),
+            FormulaOperator::Subtract => format!("({} - {})", left, right),
+            FormulaOperator::Multiply => format!("({} * {})", left, right),
+            //Athena truncates when both sides of a division are integers, which is never what
+            //someone writing revenue / units expects, so we always divide as doubles.
+            FormulaOperator::Divide => format!("(CAST({} AS DOUBLE) / {})", left, right),
+            FormulaOperator::Modulo => format!("mod({}, {})", left, right),
+        }
+    }
+}
+
+///The functions which may be called from a formula.  Anything not in this list is rejected
+///when the formula is parsed so that we never pass arbitrary SQL through to Athena.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FormulaFunction {
+    Abs,
+    Ceil,
+    Floor,
+    Round,
+    Sqrt,
+    Cbrt,
+    Exp,
+    Ln,
+    Log2,
+    Log10,
+    Power,
+    Mod,
+    Sign,
+    Greatest,
+    Least,
+    Coalesce,
+    NullIf,
+}
+
+impl FromStr for FormulaFunction {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "abs" => Ok(FormulaFunction::Abs),
+            "ceil" | "ceiling" => Ok(FormulaFunction::Ceil),
+            "floor" => Ok(FormulaFunction::Floor),
+            "round" => Ok(FormulaFunction::Round),
+            "sqrt" => Ok(FormulaFunction::Sqrt),
+            "cbrt" => Ok(FormulaFunction::Cbrt),
+            "exp" => Ok(FormulaFunction::Exp),
+            "ln" => Ok(FormulaFunction::Ln),
+            "log2" => Ok(FormulaFunction::Log2),
+            "log10" => Ok(FormulaFunction::Log10),
+            "power" | "pow" => Ok(FormulaFunction::Power),
+            "mod" => Ok(FormulaFunction::Mod),
+            "sign" => Ok(FormulaFunction::Sign),
+            "greatest" => Ok(FormulaFunction::Greatest),
+            "least" => Ok(FormulaFunction::Least),
+            "coalesce" => Ok(FormulaFunction::Coalesce),
+            "nullif" => Ok(FormulaFunction::NullIf),
+            _ => Err(format!("Unknown function {}", input)),
+        }
+    }
+}
+
+impl FormulaFunction {
+    pub fn get_sql_name(&self) -> &'static str {
+        match self {
+            FormulaFunction::Abs => "abs",
+            FormulaFunction::Ceil => "ceil",
+            FormulaFunction::Floor => "floor",
+            FormulaFunction::Round => "round",
+            FormulaFunction::Sqrt => "sqrt",
+            FormulaFunction::Cbrt => "cbrt",
+            FormulaFunction::Exp => "exp",
+            FormulaFunction::Ln => "ln",
+            FormulaFunction::Log2 => "log2",
+            FormulaFunction::Log10 => "log10",
+            FormulaFunction::Power => "power",
+            FormulaFunction::Mod => "mod",
+            FormulaFunction::Sign => "sign",
+            FormulaFunction::Greatest => "greatest",
+            FormulaFunction::Least => "least",
+            FormulaFunction::Coalesce => "coalesce",
+            FormulaFunction::NullIf => "nullif",
+        }
+    }
+
+    ///Returns the minimum and maximum number of arguments the function accepts.  A maximum of
+    ///None means the function is variadic.
+    pub fn get_arity(&self) -> (usize, Option<usize>) {
+        match self {
+            FormulaFunction::Round => (1, Some(2)),
+            FormulaFunction::Power | FormulaFunction::Mod | FormulaFunction::NullIf => (2, Some(2)),
+            FormulaFunction::Greatest | FormulaFunction::Least | FormulaFunction::Coalesce => {
+                (2, None)
+            }
+            _ => (1, Some(1)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FormulaExpression {
+    //We keep the literal as it was written so that it reaches Athena without any float
+    //formatting surprises.
+    Number(String),
+    Column(String),
+    Negate(Box<FormulaExpression>),
+    Binary {
+        operator: FormulaOperator,
+        left: Box<FormulaExpression>,
+        right: Box<FormulaExpression>,
+    },
+    Function {
+        function: FormulaFunction,
+        arguments: Vec<FormulaExpression>,
+    },
+}
+
+impl FormulaExpression {
+    pub fn parse(formula: &str) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        let tokens = tokenize(formula)?;
+        let mut parser = FormulaParser {
+            formula,
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.parse_expression()?;
+        if parser.position < parser.tokens.len() {
+            let (token, position) = &parser.tokens[parser.position];
+            return Err(build_error(
+                formula,
+                *position,
+                format!(
+                    "Unexpected {} after the end of the expression",
+                    token.describe()
+                ),
+            ));
+        }
+        Ok(expression)
+    }
+
+    pub fn to_sql(&self) -> String {
+        match self {
+            FormulaExpression::Number(value) => value.clone(),
+            FormulaExpression::Column(name) => format!(r#""{}""#, name),
+            FormulaExpression::Negate(expression) => format!("(-{})", expression.to_sql()),
+            FormulaExpression::Binary {
+                operator,
+                left,
+                right,
+            } => operator.to_sql(&left.to_sql(), &right.to_sql()),
+            FormulaExpression::Function {
+                function,
+                arguments,
+            } => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_sql()).collect();
+                format!("{}({})", function.get_sql_name(), arguments.join(", "))
+            }
+        }
+    }
+
+    ///Returns the distinct column names referenced by the expression in the order that they
+    ///first appear.
+    pub fn get_column_names(&self) -> Vec<String> {
+        let mut column_names = Vec::new();
+        self.collect_column_names(&mut column_names);
+        column_names
+    }
+
+    fn collect_column_names(&self, column_names: &mut Vec<String>) {
+        match self {
+            FormulaExpression::Number(_) => {}
+            FormulaExpression::Column(name) => {
+                if !column_names.contains(name) {
+                    column_names.push(name.clone());
+                }
+            }
+            FormulaExpression::Negate(expression) => expression.collect_column_names(column_names),
+            FormulaExpression::Binary { left, right, .. } => {
+                left.collect_column_names(column_names);
+                right.collect_column_names(column_names);
+            }
+            FormulaExpression::Function { arguments, .. } => {
+                for argument in arguments {
+                    argument.collect_column_names(column_names);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Identifier(String),
+    QuotedIdentifier(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(value) => format!("number {}", value),
+            Token::Identifier(value) => format!("identifier {}", value),
+            Token::QuotedIdentifier(value) => format!(r#"column "{}""#, value),
+            Token::Plus => "'+'".to_string(),
+            Token::Minus => "'-'".to_string(),
+            Token::Star => "'*'".to_string(),
+            Token::Slash => "'/'".to_string(),
+            Token::Percent => "'%'".to_string(),
+            Token::LeftParen => "'('".to_string(),
+            Token::RightParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+}
+
+fn build_error(
+    formula: &str,
+    position: usize,
+    message: String,
+) -> FormulaFieldDefinitionFromJsonError {
+    let data = json!({ "formula": formula, "position": position });
+    FormulaFieldDefinitionFromJsonError::InvalidFormula(GlyphxErrorData::new(
+        message,
+        Some(data),
+        None,
+    ))
+}
+
+fn tokenize(formula: &str) -> Result<Vec<(Token, usize)>, FormulaFieldDefinitionFromJsonError> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '%' => Token::Percent,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '"' => {
+                let mut name = String::new();
+                let mut terminated = false;
+                for (_, c) in chars.by_ref() {
+                    if c == '"' {
+                        terminated = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if !terminated {
+                    return Err(build_error(
+                        formula,
+                        position,
+                        "Unterminated quoted column name".to_string(),
+                    ));
+                }
+                if name.trim().is_empty() {
+                    return Err(build_error(
+                        formula,
+                        position,
+                        "Quoted column names cannot be empty".to_string(),
+                    ));
+                }
+                Token::QuotedIdentifier(name)
+            }
+            c if c.is_ascii_digit() => {
+                let mut value = c.to_string();
+                let mut has_decimal = false;
+                while let Some((_, next)) = chars.peek() {
+                    let next = *next;
+                    if next.is_ascii_digit() {
+                        value.push(next);
+                        chars.next();
+                    } else if next == '.' && !has_decimal {
+                        has_decimal = true;
+                        value.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if value.ends_with('.') {
+                    return Err(build_error(
+                        formula,
+                        position,
+                        format!("Invalid number {}", value),
+                    ));
+                }
+                Token::Number(value)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut value = c.to_string();
+                while let Some((_, next)) = chars.peek() {
+                    let next = *next;
+                    if next.is_ascii_alphanumeric() || next == '_' {
+                        value.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Identifier(value)
+            }
+            _ => {
+                return Err(build_error(
+                    formula,
+                    position,
+                    format!("Unexpected character '{}'", c),
+                ));
+            }
+        };
+        tokens.push((token, position));
+    }
+    if tokens.is_empty() {
+        return Err(build_error(formula, 0, "The formula is empty".to_string()));
+    }
+    Ok(tokens)
+}
+
+///A recursive descent parser for the grammar:
+///
+///expression := term (('+' | '-') term)*
+///term       := unary (('*' | '/' | '%') unary)*
+///unary      := ('-' | '+') unary | primary
+///primary    := number | column | function '(' arguments ')' | '(' expression ')'
+struct FormulaParser<'a> {
+    formula: &'a str,
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> FormulaParser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn end_position(&self) -> usize {
+        self.formula.len()
+    }
+
+    fn enter(&mut self, position: usize) -> Result<(), FormulaFieldDefinitionFromJsonError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(build_error(
+                self.formula,
+                position,
+                format!(
+                    "The formula is nested more than {} levels deep",
+                    MAX_NESTING_DEPTH
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_expression(
+        &mut self,
+    ) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        let mut left = self.parse_term()?;
+        let mut operator_count = 0;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Plus) => FormulaOperator::Add,
+                Some(Token::Minus) => FormulaOperator::Subtract,
+                _ => break,
+            };
+            let (_, position) = self.next().unwrap();
+            //Each operator wraps everything to its left in another Binary, so a long chain
+            //nests just as deeply as parentheses do.
+            self.enter(position)?;
+            operator_count += 1;
+            let right = self.parse_term()?;
+            left = FormulaExpression::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        self.depth -= operator_count;
+        Ok(left)
+    }
+
+    fn parse_term(&mut self) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        let mut left = self.parse_unary()?;
+        let mut operator_count = 0;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Star) => FormulaOperator::Multiply,
+                Some(Token::Slash) => FormulaOperator::Divide,
+                Some(Token::Percent) => FormulaOperator::Modulo,
+                _ => break,
+            };
+            let (_, position) = self.next().unwrap();
+            self.enter(position)?;
+            operator_count += 1;
+            let right = self.parse_unary()?;
+            left = FormulaExpression::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        self.depth -= operator_count;
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                let (_, position) = self.next().unwrap();
+                self.enter(position)?;
+                let expression = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(FormulaExpression::Negate(Box::new(expression)))
+            }
+            Some(Token::Plus) => {
+                let (_, position) = self.next().unwrap();
+                self.enter(position)?;
+                let expression = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(expression)
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        let next = self.next();
+        if next.is_none() {
+            return Err(build_error(
+                self.formula,
+                self.end_position(),
+                "Unexpected end of formula".to_string(),
+            ));
+        }
+        let (token, position) = next.unwrap();
+        match token {
+            Token::Number(value) => Ok(FormulaExpression::Number(value)),
+            Token::QuotedIdentifier(name) => Ok(FormulaExpression::Column(name)),
+            Token::Identifier(name) => {
+                if self.peek() != Some(&Token::LeftParen) {
+                    return Ok(FormulaExpression::Column(name));
+                }
+                self.parse_function(&name, position)
+            }
+            Token::LeftParen => {
+                self.enter(position)?;
+                let expression = self.parse_expression()?;
+                self.expect_right_paren(position)?;
+                self.depth -= 1;
+                Ok(expression)
+            }
+            _ => Err(build_error(
+                self.formula,
+                position,
+                format!("Unexpected {}", token.describe()),
+            )),
+        }
+    }
+
+    fn parse_function(
+        &mut self,
+        name: &str,
+        position: usize,
+    ) -> Result<FormulaExpression, FormulaFieldDefinitionFromJsonError> {
+        let function = name.parse::<FormulaFunction>();
+        if function.is_err() {
+            return Err(build_error(self.formula, position, function.err().unwrap()));
+        }
+        let function = function.unwrap();
+        self.enter(position)?;
+        //consume the '('
+        self.next();
+        let mut arguments = Vec::new();
+        if self.peek() == Some(&Token::RightParen) {
+            self.next();
+        } else {
+            loop {
+                arguments.push(self.parse_expression()?);
+                match self.next() {
+                    Some((Token::Comma, _)) => continue,
+                    Some((Token::RightParen, _)) => break,
+                    Some((token, token_position)) => {
+                        return Err(build_error(
+                            self.formula,
+                            token_position,
+                            format!("Expected ',' or ')' but found {}", token.describe()),
+                        ));
+                    }
+                    None => {
+                        return Err(build_error(
+                            self.formula,
+                            self.end_position(),
+                            format!("Missing ')' for the call to {}", name),
+                        ));
+                    }
+                }
+            }
+        }
+        self.depth -= 1;
+
+        let (min_arguments, max_arguments) = function.get_arity();
+        let too_many = max_arguments.is_some() && arguments.len() > max_arguments.unwrap();
+        if arguments.len() < min_arguments || too_many {
+            let expected = match max_arguments {
+                Some(max) if max == min_arguments => format!("{}", min_arguments),
+                Some(max) => format!("{} to {}", min_arguments, max),
+                None => format!("at least {}", min_arguments),
+            };
+            return Err(build_error(
+                self.formula,
+                position,
+                format!(
+                    "The function {} expects {} argument(s) but was given {}",
+                    function.get_sql_name(),
+                    expected,
+                    arguments.len()
+                ),
+            ));
+        }
+        Ok(FormulaExpression::Function {
+            function,
+            arguments,
+        })
+    }
+
+    fn expect_right_paren(
+        &mut self,
+        open_position: usize,
+    ) -> Result<(), FormulaFieldDefinitionFromJsonError> {
+        match self.next() {
+            Some((Token::RightParen, _)) => Ok(()),
+            Some((token, position)) => Err(build_error(
+                self.formula,
+                position,
+                format!("Expected ')' but found {}", token.describe()),
+            )),
+            None => Err(build_error(
+                self.formula,
+                open_position,
+                "Unbalanced '(' in formula".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse {
+    use super::*;
+
+    fn get_error_position(formula: &str) -> usize {
+        let result = FormulaExpression::parse(formula);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["formula"].as_str().unwrap(), formula);
+                data["position"].as_u64().unwrap() as usize
+            }
+            _ => panic!("Expected InvalidFormula"),
+        }
+    }
+
+    #[test]
+    fn column_difference() {
+        let result = FormulaExpression::parse("revenue - cost").unwrap();
+        assert_eq!(
+            result,
+            FormulaExpression::Binary {
+                operator: FormulaOperator::Subtract,
+                left: Box::new(FormulaExpression::Column("revenue".to_string())),
+                right: Box::new(FormulaExpression::Column("cost".to_string())),
+            }
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        let result = FormulaExpression::parse("a + b * 2").unwrap();
+        assert_eq!(result.to_sql(), r#"("a" + ("b" * 2))"#);
+    }
+
+    #[test]
+    fn parentheses() {
+        let result = FormulaExpression::parse("(a + b) * 2").unwrap();
+        assert_eq!(result.to_sql(), r#"(("a" + "b") * 2)"#);
+    }
+
+    #[test]
+    fn quoted_column() {
+        let result = FormulaExpression::parse(r#""unit price" * quantity"#).unwrap();
+        assert_eq!(result.to_sql(), r#"("unit price" * "quantity")"#);
+    }
+
+    #[test]
+    fn negation() {
+        let result = FormulaExpression::parse("-a + 1.5").unwrap();
+        assert_eq!(result.to_sql(), r#"((-"a") + 1.5)"#);
+    }
+
+    #[test]
+    fn function_call() {
+        let result = FormulaExpression::parse("ROUND(revenue / units, 2)").unwrap();
+        assert_eq!(
+            result.to_sql(),
+            r#"round((CAST("revenue" AS DOUBLE) / "units"), 2)"#
+        );
+    }
+
+    #[test]
+    fn variadic_function() {
+        let result = FormulaExpression::parse("greatest(a, b, c)").unwrap();
+        assert_eq!(result.to_sql(), r#"greatest("a", "b", "c")"#);
+    }
+
+    #[test]
+    fn unknown_function() {
+        let position = get_error_position("drop_table(a)");
+        assert_eq!(position, 0);
+    }
+
+    #[test]
+    fn wrong_arity() {
+        let position = get_error_position("a + power(b)");
+        assert_eq!(position, 4);
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let position = get_error_position("a; b");
+        assert_eq!(position, 1);
+    }
+
+    #[test]
+    fn unbalanced_parentheses() {
+        let position = get_error_position("(a + b");
+        assert_eq!(position, 0);
+    }
+
+    #[test]
+    fn trailing_operator() {
+        let position = get_error_position("a +");
+        assert_eq!(position, 3);
+    }
+
+    #[test]
+    fn trailing_tokens() {
+        let position = get_error_position("a b");
+        assert_eq!(position, 2);
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let position = get_error_position(r#"a + "b"#);
+        assert_eq!(position, 4);
+    }
+
+    #[test]
+    fn empty() {
+        let position = get_error_position("   ");
+        assert_eq!(position, 0);
+    }
+
+    #[test]
+    fn too_deep() {
+        let formula = format!("{}a{}", "(".repeat(100), ")".repeat(100));
+        let position = get_error_position(&formula);
+        assert_eq!(position, MAX_NESTING_DEPTH);
+    }
+
+    #[test]
+    fn long_chain_is_too_deep() {
+        //The 65th '+' is the first operator past the limit, at 2 * 65 - 1.
+        let formula = vec!["a"; 100_000].join("+");
+        let position = get_error_position(&formula);
+        assert_eq!(position, 2 * (MAX_NESTING_DEPTH + 1) - 1);
+        let formula = vec!["a"; 100_000].join("*");
+        let position = get_error_position(&formula);
+        assert_eq!(position, 2 * (MAX_NESTING_DEPTH + 1) - 1);
+    }
+
+    #[test]
+    fn chain_at_the_limit() {
+        let formula = vec!["a"; MAX_NESTING_DEPTH + 1].join(" + ");
+        let result = FormulaExpression::parse(&formula);
+        assert!(result.is_ok());
+        let sql = result.unwrap().to_sql();
+        assert_eq!(sql.matches('+').count(), MAX_NESTING_DEPTH);
+    }
+}
+
+#[cfg(test)]
+mod get_column_names {
+    use super::*;
+
+    #[test]
+    fn distinct_in_order() {
+        let result = FormulaExpression::parse("abs(b - a) / (b + c)").unwrap();
+        assert_eq!(
+            result.get_column_names(),
+            vec!["b".to_string(), "a".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn no_columns() {
+        let result = FormulaExpression::parse("1 + 2").unwrap();
+        assert!(result.get_column_names().is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+use super::FormulaExpression;
+use super::FormulaFieldDefinitionFromJsonError;
+use crate::types::field_definition_type::FieldDefinitionType;
+use crate::types::vectorizer_parameters::helper_functions::json_has_field;
+use glyphx_core::GlyphxErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormulaFieldDefinition {
+    pub field_type: FieldDefinitionType,
+    pub formula: String,
+    pub expression: FormulaExpression,
+}
+
+impl FormulaFieldDefinition {
+    pub fn from_json(input: &Value) -> Result<Self, FormulaFieldDefinitionFromJsonError> {
+        let validation_result = Self::validate_json(input);
+        if validation_result.is_err() {
+            return Err(validation_result.err().unwrap());
+        }
+        let formula = &input["formula"];
+        if !formula.is_string() {
+            let description = "The formula must be a string".to_string();
+            let data = json!({ "field": "formula", "formula": formula });
+            return Err(FormulaFieldDefinitionFromJsonError::InvalidFormula(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
+        let formula = formula.as_str().unwrap().to_string();
+        let expression = FormulaExpression::parse(&formula);
+        if expression.is_err() {
+            return Err(expression.err().unwrap());
+        }
+        let expression = expression.unwrap();
+        //A formula without any columns would plot every row at the same point, which is
+        //almost certainly a mistake on the part of the user.
+        if expression.get_column_names().is_empty() {
+            let description = "The formula must reference at least one column".to_string();
+            let data = json!({ "formula": formula });
+            return Err(FormulaFieldDefinitionFromJsonError::InvalidFormula(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
+        Ok(Self {
+            field_type: FieldDefinitionType::Formula,
+            formula,
+            expression,
+        })
+    }
+
+    fn validate_json(input: &Value) -> Result<(), FormulaFieldDefinitionFromJsonError> {
+        let has_formula = json_has_field(input, "formula");
+        if has_formula.is_err() {
+            let err = has_formula.err().unwrap();
+            let err = FormulaFieldDefinitionFromJsonError::from_json_has_field_error(err);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    pub fn get_column_names(&self) -> Vec<String> {
+        self.expression.get_column_names()
+    }
+
+    pub fn get_query(&self, display_name: &str) -> (String, String) {
+        let raw_query = self.expression.to_sql();
+        let query = format!(r#"{} as "{}""#, raw_query, display_name);
+        (query, raw_query)
+    }
+}
+
+#[cfg(test)]
+mod from_json {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "revenue - cost"
+        });
+        let result = FormulaFieldDefinition::from_json(&input);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.formula, "revenue - cost");
+        match result.field_type {
+            FieldDefinitionType::Formula => {}
+            _ => panic!("Unexpected field type"),
+        }
+        assert_eq!(
+            result.get_column_names(),
+            vec!["revenue".to_string(), "cost".to_string()]
+        );
+    }
+
+    #[test]
+    fn missing_formula() {
+        let input = json!({
+            "fieldType": "formula"
+        });
+        let result = FormulaFieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FormulaFieldDefinitionFromJsonError::FieldNotDefined(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["field"].as_str().unwrap(), "formula");
+            }
+            _ => panic!("Expected FieldNotDefined"),
+        }
+    }
+
+    #[test]
+    fn formula_is_not_a_string() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": 42
+        });
+        let result = FormulaFieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(_) => {}
+            _ => panic!("Expected InvalidFormula"),
+        }
+    }
+
+    #[test]
+    fn formula_does_not_parse() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "revenue - "
+        });
+        let result = FormulaFieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["formula"].as_str().unwrap(), "revenue - ");
+            }
+            _ => panic!("Expected InvalidFormula"),
+        }
+    }
+
+    #[test]
+    fn formula_has_no_columns() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "1 + 2"
+        });
+        let result = FormulaFieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FormulaFieldDefinitionFromJsonError::InvalidFormula(_) => {}
+            _ => panic!("Expected InvalidFormula"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_query {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let input = json!({
+            "fieldType": "formula",
+            "formula": "revenue - cost"
+        });
+        let field_definition = FormulaFieldDefinition::from_json(&input).unwrap();
+        let (query, raw_query) = field_definition.get_query("profit");
+        assert_eq!(raw_query, r#"("revenue" - "cost")"#);
+        assert_eq!(query, r#"("revenue" - "cost") as "profit""#);
+    }
+}
@@ -0,0 +1,2 @@
+mod from_json_error;
+pub use from_json_error::*;
@@ -1,9 +1,15 @@
 mod accumulated_field_definition;
 mod accumulated_field_definition_errors;
+mod binned_field_definition;
+mod binned_field_definition_errors;
 mod date_field_definition;
 mod date_field_definition_errors;
 mod field_definition_collection;
 mod field_definition_errors;
+mod formula_expression;
+mod formula_field_definition;
+mod formula_field_definition_errors;
+mod sort_order;
 mod standard_field_definition;
 mod standard_field_definition_errors;
 
@@ -11,11 +17,17 @@ pub use accumulated_field_definition::{
     AccumulatedFieldDefinition, AccumulatorFieldDefinition, AccumulatorType,
 };
 pub use accumulated_field_definition_errors::FromJsonError as AccumulatedFieldDefinitionFromJsonError;
-pub use date_field_definition::{DateFieldDefinition, DateGrouping};
+pub use binned_field_definition::{BinnedFieldDefinition, BinningMethod};
+pub use binned_field_definition_errors::FromJsonError as BinnedFieldDefinitionFromJsonError;
+pub use date_field_definition::{DateFieldDefinition, DateGrouping, DateUnit, Weekday};
 pub use date_field_definition_errors::FromJsonError as DateFieldDefinitionFromJsonError;
 pub use field_definition_collection::FieldDefinitionCollection;
 pub use field_definition_errors::*;
-pub use standard_field_definition::StandardFieldDefinition;
+pub use formula_expression::{FormulaExpression, FormulaFunction, FormulaOperator};
+pub use formula_field_definition::FormulaFieldDefinition;
+pub use formula_field_definition_errors::FromJsonError as FormulaFieldDefinitionFromJsonError;
+pub use sort_order::{SortDirection, SortOrder};
+pub use standard_field_definition::{StandardFieldDefinition, TopNDefinition};
 pub use standard_field_definition_errors::FromJsonError as StandardFieldDefinitionFromJsonError;
 
 use crate::types::vectorizer_parameters::helper_functions::json_has_field;
@@ -34,14 +46,23 @@ pub enum FieldDefinition {
         field_definition: StandardFieldDefinition,
         field_query: String,
         raw_query: String,
+        sort_order: SortOrder,
+    },
+    Formula {
+        field_display_name: String,
+        field_data_type: FieldType,
+        field_definition: FormulaFieldDefinition,
+        field_query: String,
+        raw_query: String,
+        sort_order: SortOrder,
     },
-    //    Formula(FormulaFieldDefinition),
     Date {
         field_display_name: String,
         field_data_type: FieldType,
         field_definition: DateFieldDefinition,
         field_query: String,
         raw_query: String,
+        sort_order: SortOrder,
     },
     Accumulated {
         field_display_name: String,
@@ -50,6 +71,14 @@ pub enum FieldDefinition {
         field_query: String,
         raw_query: String,
     },
+    Binned {
+        field_display_name: String,
+        field_data_type: FieldType,
+        field_definition: BinnedFieldDefinition,
+        field_query: String,
+        raw_query: String,
+        sort_order: SortOrder,
+    },
     Unknown(),
 }
 
@@ -73,19 +102,108 @@ impl FieldDefinition {
             return Err(field_definition_type.err().unwrap());
         }
         let field_definition_type = field_definition_type.unwrap();
+        let sort_order = SortOrder::from_json(&input["sortOrder"]);
+        if sort_order.is_err() {
+            return Err(sort_order.err().unwrap());
+        }
+        let sort_order = sort_order.unwrap();
+        //Accumulated fields are a single value per glyph, they are never an axis to be sorted.
+        if !sort_order.is_default()
+            && matches!(field_definition_type, FieldDefinitionType::ACCUMULATED)
+        {
+            let description = format!(
+                "The accumulated field {} can not have a sortOrder",
+                field_display_name
+            );
+            let data = json!({ "field": field_display_name, "sortOrder": input["sortOrder"] });
+            return Err(FromJsonError::InvalidSortOrder(GlyphxErrorData::new(
+                description,
+                Some(data),
+                None,
+            )));
+        }
 
-        match field_definition_type {
+        let field_definition = match field_definition_type {
             FieldDefinitionType::Standard => {
                 Self::build_standard_field(field_display_name, field_data_type, field_definition)
             }
             FieldDefinitionType::Date => {
                 Self::build_date_field(field_display_name, field_data_type, field_definition)
             }
+            FieldDefinitionType::Formula => {
+                Self::build_formula_field(field_display_name, field_data_type, field_definition)
+            }
             FieldDefinitionType::ACCUMULATED => {
                 Self::build_accumulated_field(field_display_name, field_data_type, field_definition)
             }
-            _ => Ok(FieldDefinition::Unknown()),
+            FieldDefinitionType::Binned => {
+                Self::build_binned_field(field_display_name, field_data_type, field_definition)
+            }
+        };
+        if field_definition.is_err() {
+            return Err(field_definition.err().unwrap());
+        }
+        Ok(field_definition.unwrap().with_sort_order(sort_order))
+    }
+
+    fn build_binned_field(
+        field_display_name: String,
+        field_data_type: FieldType,
+        field_definition: &Value,
+    ) -> Result<FieldDefinition, FromJsonError> {
+        //Only numbers have an order that we can cut into bins.
+        if field_data_type != FieldType::Number && field_data_type != FieldType::Integer {
+            let description = format!(
+                "The binned field {} must be a number or an integer",
+                field_display_name
+            );
+            let data = json!({
+                "field": field_display_name,
+                "field_type": format!("{:?}", field_data_type)
+            });
+            return Err(FromJsonError::BinnedFieldDefinitionError(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
+        let binned_field_definition = BinnedFieldDefinition::from_json(field_definition);
+        if binned_field_definition.is_err() {
+            let err = binned_field_definition.err().unwrap();
+            let err = FromJsonError::from_binned_field_from_json_error(err);
+            return Err(err);
+        }
+        let binned_field_definition = binned_field_definition.unwrap();
+        let (field_query, raw_query) = binned_field_definition.get_query(&field_display_name);
+        Ok(FieldDefinition::Binned {
+            field_display_name,
+            field_data_type,
+            field_definition: binned_field_definition,
+            field_query,
+            raw_query,
+            sort_order: SortOrder::default(),
+        })
+    }
+
+    fn build_formula_field(
+        field_display_name: String,
+        field_data_type: FieldType,
+        field_definition: &Value,
+    ) -> Result<FieldDefinition, FromJsonError> {
+        let formula_field_definition = FormulaFieldDefinition::from_json(field_definition);
+        if formula_field_definition.is_err() {
+            let err = formula_field_definition.err().unwrap();
+            let err = FromJsonError::from_formula_field_from_json_error(err);
+            return Err(err);
         }
+        let formula_field_definition = formula_field_definition.unwrap();
+        let (field_query, raw_query) = formula_field_definition.get_query(&field_display_name);
+        Ok(FieldDefinition::Formula {
+            field_display_name,
+            field_data_type,
+            field_definition: formula_field_definition,
+            field_query,
+            raw_query,
+            sort_order: SortOrder::default(),
+        })
     }
 
     fn build_accumulated_field(
@@ -129,6 +247,7 @@ impl FieldDefinition {
             field_definition: date_field_definition,
             field_query,
             raw_query,
+            sort_order: SortOrder::default(),
         })
     }
 
@@ -146,6 +265,20 @@ impl FieldDefinition {
         }
 
         let standard_field_definition = standard_field_definition.unwrap();
+        //The other bucket is a string, so only string columns can be collapsed into it.
+        if standard_field_definition.top_n.is_some() && field_data_type != FieldType::String {
+            let description = format!(
+                "The field {} must be a string to use topN",
+                field_display_name
+            );
+            let data = json!({
+                "field": field_display_name,
+                "field_type": format!("{:?}", field_data_type)
+            });
+            return Err(FromJsonError::StandardFieldDefinitionError(
+                GlyphxErrorData::new(description, Some(data), None),
+            ));
+        }
         let (field_query, raw_query) = standard_field_definition.get_query(&field_display_name);
         Ok(FieldDefinition::Standard {
             field_display_name,
@@ -153,38 +286,22 @@ impl FieldDefinition {
             field_definition: standard_field_definition,
             field_query,
             raw_query,
+            sort_order: SortOrder::default(),
         })
     }
 
     fn get_field_data_type(input: &Value) -> Result<FieldType, FromJsonError> {
         let field_name = input["fieldDisplayName"].as_str().unwrap().to_string();
-        
-        let raw_field_data_type = &input["fieldDataType"];
-        let raw_field_data_type = if raw_field_data_type.is_i64() {
-            raw_field_data_type.as_i64().unwrap() as usize
-        } else if raw_field_data_type.is_f64() {
-            raw_field_data_type.as_f64().unwrap() as usize
-        } else {
-            raw_field_data_type.as_u64().unwrap() as usize
-        };
-        let field_data_type = FieldType::from_numeric_value(raw_field_data_type);
-        match field_data_type {
-            FieldType::Unknown => {
-                let description = format!("Invalid field data type: {}", raw_field_data_type);
-                let data = json!( {
-                "field": field_name,
-                "field_type": raw_field_data_type
-                });
-                let err = FromJsonError::InvalidFieldType(GlyphxErrorData::new(
-                    description,
-                    Some(data),
-                    None,
-                ));
-                return Err(err);
-            }
-
-            _ => Ok(field_data_type),
+
+        let field_data_type = FieldType::try_from_json(&input["fieldDataType"]);
+        if field_data_type.is_err() {
+            let err = field_data_type.err().unwrap();
+            return Err(FromJsonError::from_try_from_numeric_value_error(
+                err,
+                &field_name,
+            ));
         }
+        Ok(field_data_type.unwrap())
     }
 
     fn get_field_definition_type(input: &Value) -> Result<FieldDefinitionType, FromJsonError> {
@@ -250,6 +367,10 @@ impl FieldDefinition {
         }
     }
 
+    pub fn is_formula(&self) -> bool {
+        matches!(self, FieldDefinition::Formula { .. })
+    }
+
     pub fn is_accumulated(&self) -> bool {
         match self {
             FieldDefinition::Accumulated { .. } => true,
@@ -257,6 +378,10 @@ impl FieldDefinition {
         }
     }
 
+    pub fn is_binned(&self) -> bool {
+        matches!(self, FieldDefinition::Binned { .. })
+    }
+
     pub fn get_field_display_name(&self) -> &str {
         match self {
             FieldDefinition::Standard {
@@ -265,17 +390,45 @@ impl FieldDefinition {
             FieldDefinition::Date {
                 field_display_name, ..
             } => field_display_name.as_str(),
+            FieldDefinition::Formula {
+                field_display_name, ..
+            } => field_display_name.as_str(),
             FieldDefinition::Accumulated {
                 field_display_name, ..
             } => field_display_name.as_str(),
+            FieldDefinition::Binned {
+                field_display_name, ..
+            } => field_display_name.as_str(),
             _ => "",
         }
     }
+    ///Returns the type of the data that this field produces.
+    pub fn get_field_type(&self) -> FieldType {
+        match self {
+            FieldDefinition::Standard {
+                field_data_type, ..
+            } => field_data_type.clone(),
+            FieldDefinition::Date {
+                field_data_type, ..
+            } => field_data_type.clone(),
+            FieldDefinition::Formula {
+                field_data_type, ..
+            } => field_data_type.clone(),
+            FieldDefinition::Accumulated {
+                field_data_type, ..
+            } => field_data_type.clone(),
+            //The column is numeric but the bins are returned as their labels.
+            FieldDefinition::Binned { .. } => FieldType::String,
+            _ => FieldType::Unknown,
+        }
+    }
     pub fn get_field_query(&self) -> &str {
         match self {
             FieldDefinition::Standard { field_query, .. } => field_query.as_str(),
             FieldDefinition::Date { field_query, .. } => field_query.as_str(),
+            FieldDefinition::Formula { field_query, .. } => field_query.as_str(),
             FieldDefinition::Accumulated { field_query, .. } => field_query.as_str(),
+            FieldDefinition::Binned { field_query, .. } => field_query.as_str(),
             _ => "",
         }
     }
@@ -284,7 +437,9 @@ impl FieldDefinition {
         match self {
             FieldDefinition::Standard { raw_query, .. } => raw_query.as_str(),
             FieldDefinition::Date { raw_query, .. } => raw_query.as_str(),
+            FieldDefinition::Formula { raw_query, .. } => raw_query.as_str(),
             FieldDefinition::Accumulated { raw_query, .. } => raw_query.as_str(),
+            FieldDefinition::Binned { raw_query, .. } => raw_query.as_str(),
             _ => "",
         }
     }
@@ -306,6 +461,15 @@ impl FieldDefinition {
         }
     }
 
+    pub fn get_formula_field_definition(&self) -> Option<&FormulaFieldDefinition> {
+        match self {
+            FieldDefinition::Formula {
+                field_definition, ..
+            } => Some(field_definition),
+            _ => None,
+        }
+    }
+
     pub fn get_accumulator_field_definition(&self) -> Option<&AccumulatorFieldDefinition> {
         match self {
             FieldDefinition::Accumulated {
@@ -315,6 +479,126 @@ impl FieldDefinition {
         }
     }
 
+    pub fn get_binned_field_definition(&self) -> Option<&BinnedFieldDefinition> {
+        match self {
+            FieldDefinition::Binned {
+                field_definition, ..
+            } => Some(field_definition),
+            _ => None,
+        }
+    }
+
+    ///The order of the values of the field when it is an axis.
+    pub fn get_sort_order(&self) -> SortOrder {
+        match self {
+            FieldDefinition::Standard { sort_order, .. }
+            | FieldDefinition::Date { sort_order, .. }
+            | FieldDefinition::Formula { sort_order, .. }
+            | FieldDefinition::Binned { sort_order, .. } => sort_order.clone(),
+            _ => SortOrder::default(),
+        }
+    }
+
+    ///Returns a copy of the field that is sorted by sort_order.  Accumulated fields are not
+    ///sorted, so they are returned as is.
+    pub fn with_sort_order(&self, sort_order: SortOrder) -> FieldDefinition {
+        let mut field_definition = self.clone();
+        match &mut field_definition {
+            FieldDefinition::Standard {
+                sort_order: field_sort_order,
+                ..
+            }
+            | FieldDefinition::Date {
+                sort_order: field_sort_order,
+                ..
+            }
+            | FieldDefinition::Formula {
+                sort_order: field_sort_order,
+                ..
+            }
+            | FieldDefinition::Binned {
+                sort_order: field_sort_order,
+                ..
+            } => *field_sort_order = sort_order,
+            _ => {}
+        }
+        field_definition
+    }
+
+    ///Returns a copy of a standard field with the values of its top N set and its queries rebuilt
+    ///to match.  Any other field is returned as is.
+    pub fn with_top_values(&self, values: Vec<String>) -> FieldDefinition {
+        match self {
+            FieldDefinition::Standard {
+                field_display_name,
+                field_data_type,
+                field_definition,
+                sort_order,
+                ..
+            } => {
+                let field_definition = field_definition.with_top_values(values);
+                let (field_query, raw_query) = field_definition.get_query(field_display_name);
+                FieldDefinition::Standard {
+                    field_display_name: field_display_name.clone(),
+                    field_data_type: field_data_type.clone(),
+                    field_definition,
+                    field_query,
+                    raw_query,
+                    sort_order: sort_order.clone(),
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+
+    ///Returns a copy of a binned field with its edges set and its queries rebuilt to match.  Any
+    ///other field is returned as is.
+    pub fn with_bin_edges(&self, edges: Vec<f64>) -> FieldDefinition {
+        match self {
+            FieldDefinition::Binned {
+                field_display_name,
+                field_data_type,
+                field_definition,
+                sort_order,
+                ..
+            } => {
+                let field_definition = field_definition.with_edges(edges);
+                let (field_query, raw_query) = field_definition.get_query(field_display_name);
+                FieldDefinition::Binned {
+                    field_display_name: field_display_name.clone(),
+                    field_data_type: field_data_type.clone(),
+                    field_definition,
+                    field_query,
+                    raw_query,
+                    sort_order: sort_order.clone(),
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+
+    ///Returns the names of the underlying table columns that this field reads from.
+    pub fn get_column_names(&self) -> Vec<String> {
+        match self {
+            FieldDefinition::Standard {
+                field_definition, ..
+            } => vec![field_definition.field_name.clone()],
+            FieldDefinition::Date {
+                field_definition, ..
+            } => vec![field_definition.field_name.clone()],
+            FieldDefinition::Formula {
+                field_definition, ..
+            } => field_definition.get_column_names(),
+            FieldDefinition::Accumulated {
+                field_definition, ..
+            } => field_definition.get_column_names(),
+            FieldDefinition::Binned {
+                field_definition, ..
+            } => vec![field_definition.field_name.clone()],
+            _ => Vec::new(),
+        }
+    }
+
     pub fn get_query_parts(&self) -> (String, String, String) {
         let field_value;
         let field_name;
@@ -336,6 +620,14 @@ impl FieldDefinition {
                 field_name = field_display_name.clone();
                 (field_value, raw_field_query) = field_definition.get_query(&field_name);
             }
+            FieldDefinition::Formula {
+                field_definition,
+                field_display_name,
+                ..
+            } => {
+                field_name = field_display_name.clone();
+                (field_value, raw_field_query) = field_definition.get_query(&field_name);
+            }
             FieldDefinition::Accumulated {
                 field_definition,
                 field_display_name,
@@ -344,6 +636,14 @@ impl FieldDefinition {
                 field_name = field_display_name.clone();
                 (field_value, raw_field_query) = field_definition.get_query(&field_name);
             }
+            FieldDefinition::Binned {
+                field_definition,
+                field_display_name,
+                ..
+            } => {
+                field_name = field_display_name.clone();
+                (field_value, raw_field_query) = field_definition.get_query(&field_name);
+            }
 
             _ => {
                 panic!("Unexpected field definition");
@@ -365,9 +665,11 @@ mod is_standard {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert!(field_definition.is_standard());
     }
@@ -381,9 +683,14 @@ mod is_standard {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert!(!field_definition.is_standard());
     }
@@ -402,9 +709,14 @@ mod is_date {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert!(field_definition.is_date());
     }
@@ -417,9 +729,11 @@ mod is_date {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert!(!field_definition.is_date());
     }
@@ -441,8 +755,11 @@ mod is_accumulated {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
@@ -458,9 +775,11 @@ mod is_accumulated {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert!(!field_definition.is_accumulated());
     }
@@ -478,9 +797,11 @@ mod get_field_display_name {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_field_display_name(), "test");
     }
@@ -494,9 +815,14 @@ mod get_field_display_name {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_field_display_name(), "test");
     }
@@ -513,8 +839,11 @@ mod get_field_display_name {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
@@ -529,6 +858,55 @@ mod get_field_display_name {
     }
 }
 
+#[cfg(test)]
+mod get_field_type {
+    use super::*;
+    use crate::types::field_definition_type::FieldDefinitionType;
+    #[test]
+    fn standard() {
+        let field_definition = FieldDefinition::Standard {
+            field_display_name: "test".to_string(),
+            field_data_type: FieldType::Integer,
+            field_definition: StandardFieldDefinition {
+                field_type: FieldDefinitionType::Standard,
+                field_name: "test".to_string(),
+                top_n: None,
+            },
+            field_query: String::from(r#""test" as "test""#),
+            raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
+        };
+        assert_eq!(field_definition.get_field_type(), FieldType::Integer);
+    }
+
+    #[test]
+    fn date() {
+        let field_definition = FieldDefinition::Date {
+            field_display_name: "test".to_string(),
+            field_data_type: FieldType::Date,
+            field_definition: DateFieldDefinition {
+                field_type: FieldDefinitionType::Date,
+                field_name: "test".to_string(),
+                date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
+            },
+            field_query: String::from(r#""test" as "test""#),
+            raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
+        };
+        assert_eq!(field_definition.get_field_type(), FieldType::Date);
+    }
+
+    #[test]
+    fn unknown() {
+        let field_definition = FieldDefinition::Unknown();
+        assert_eq!(field_definition.get_field_type(), FieldType::Unknown);
+    }
+}
+
 #[cfg(test)]
 mod get_field_query {
     use super::*;
@@ -542,9 +920,11 @@ mod get_field_query {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: field_query.clone(),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_field_query(), field_query);
     }
@@ -559,9 +939,14 @@ mod get_field_query {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: field_query.clone(),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_field_query(), field_query);
     }
@@ -579,8 +964,11 @@ mod get_field_query {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: field_query.clone(),
             raw_query: String::from(r#""test""#),
@@ -608,9 +996,11 @@ mod get_raw_query {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: raw_query.clone(),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_raw_query(), raw_query);
     }
@@ -625,9 +1015,14 @@ mod get_raw_query {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: raw_query.clone(),
+            sort_order: SortOrder::default(),
         };
         assert_eq!(field_definition.get_raw_query(), raw_query);
     }
@@ -645,8 +1040,11 @@ mod get_raw_query {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: raw_query.clone(),
@@ -673,9 +1071,11 @@ mod get_standard_field_definition {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_standard_field_definition();
         assert!(result.is_some());
@@ -692,9 +1092,14 @@ mod get_standard_field_definition {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_standard_field_definition();
         assert!(result.is_none());
@@ -712,8 +1117,11 @@ mod get_standard_field_definition {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
@@ -743,9 +1151,14 @@ mod get_date_field_definition {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_date_field_definition();
         assert!(result.is_some());
@@ -761,9 +1174,11 @@ mod get_date_field_definition {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_date_field_definition();
         assert!(result.is_none());
@@ -781,8 +1196,11 @@ mod get_date_field_definition {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
@@ -816,8 +1234,11 @@ mod get_accumulator_field_definition {
                     StandardFieldDefinition {
                         field_type: FieldDefinitionType::Standard,
                         field_name: "test".to_string(),
+                        top_n: None,
                     },
                 ),
+                percentile: None,
+                order_by_field: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
@@ -839,9 +1260,14 @@ mod get_accumulator_field_definition {
                 field_type: FieldDefinitionType::Date,
                 field_name: "test".to_string(),
                 date_grouping: DateGrouping::DayOfMonth,
+                date_unit: DateUnit::Milliseconds,
+                time_zone: None,
+                week_start: Weekday::Monday,
+                minute_bucket: 15,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_accumulator_field_definition();
         assert!(result.is_none());
@@ -855,9 +1281,11 @@ mod get_accumulator_field_definition {
             field_definition: StandardFieldDefinition {
                 field_type: FieldDefinitionType::Standard,
                 field_name: "test".to_string(),
+                top_n: None,
             },
             field_query: String::from(r#""test" as "test""#),
             raw_query: String::from(r#""test""#),
+            sort_order: SortOrder::default(),
         };
         let result = field_definition.get_accumulator_field_definition();
         assert!(result.is_none());
@@ -871,6 +1299,56 @@ mod get_accumulator_field_definition {
     }
 }
 
+#[cfg(test)]
+mod with_bin_edges {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "equal_width",
+                "binCount": 2
+            }
+        }))
+        .unwrap();
+        assert!(!field_definition
+            .get_binned_field_definition()
+            .unwrap()
+            .is_resolved());
+
+        let result = field_definition.with_bin_edges(vec![0.0, 5.0, 10.0]);
+        let binned_field_definition = result.get_binned_field_definition().unwrap();
+        assert_eq!(binned_field_definition.edges, vec![0.0, 5.0, 10.0]);
+        let (_, raw_query) = binned_field_definition.get_query("price");
+        assert_eq!(result.get_raw_query(), raw_query);
+        assert_eq!(
+            result.get_field_query(),
+            format!(r#"{} as "price""#, raw_query)
+        );
+    }
+
+    #[test]
+    fn not_binned() {
+        let field_definition = FieldDefinition::from_json(&json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "price"
+            }
+        }))
+        .unwrap();
+        let result = field_definition.with_bin_edges(vec![0.0, 5.0]);
+        assert!(result.is_standard());
+        assert_eq!(result.get_raw_query(), field_definition.get_raw_query());
+    }
+}
+
 #[cfg(test)]
 mod validate_outer_json {
     use super::*;
@@ -983,6 +1461,7 @@ mod from_json {
                 field_definition,
                 field_query: _,
                 raw_query : _,
+                sort_order: _,
             } => {
                 assert_eq!(field_display_name, "test");
                 match field_data_type {
@@ -1001,6 +1480,82 @@ mod from_json {
         }
     }
 
+    #[test]
+    fn formula_field() {
+        let input = json!({
+            "fieldDisplayName": "profit",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "formula",
+                "formula": "revenue - cost"
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_formula());
+        assert_eq!(result.get_field_display_name(), "profit");
+        assert_eq!(result.get_raw_query(), r#"("revenue" - "cost")"#);
+        assert_eq!(
+            result.get_field_query(),
+            r#"("revenue" - "cost") as "profit""#
+        );
+        assert_eq!(
+            result.get_column_names(),
+            vec!["revenue".to_string(), "cost".to_string()]
+        );
+        let field_definition = result.get_formula_field_definition().unwrap();
+        assert_eq!(field_definition.formula, "revenue - cost");
+    }
+
+    #[test]
+    fn formula_field_is_error() {
+        let input = json!({
+            "fieldDisplayName": "profit",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "formula",
+                "formula": "revenue - drop(cost)"
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FromJsonError::FormulaFieldDefinitionError(data) => {
+                let data = data.data.unwrap();
+                assert_eq!(data["position"].as_u64().unwrap(), 10);
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+
+    #[test]
+    fn accumulated_formula_field() {
+        let input = json!({
+            "fieldDisplayName": "profit",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "sum",
+                "accumulatedFieldDefinition": {
+                    "fieldType": "formula",
+                    "formula": "revenue - cost"
+                }
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_accumulated());
+        assert_eq!(result.get_raw_query(), r#"SUM(("revenue" - "cost"))"#);
+        assert_eq!(
+            result.get_column_names(),
+            vec!["revenue".to_string(), "cost".to_string()]
+        );
+    }
+
     #[test]
     fn standard_field_is_error() {
         let input = json!({
@@ -1045,6 +1600,7 @@ mod from_json {
                 field_definition,
                 field_query: _,
                 raw_query : _,
+                sort_order: _,
             } => {
                 assert_eq!(field_display_name, "test");
                 match field_data_type {
@@ -1172,6 +1728,126 @@ mod from_json {
         }
     }
 
+    #[test]
+    fn top_n_is_ok() {
+        let input = json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": {"count": 10}
+            }
+        });
+        let result = FieldDefinition::from_json(&input).unwrap();
+        let standard_field_definition = result.get_standard_field_definition().unwrap();
+        assert!(standard_field_definition.needs_top_values());
+
+        let result = result.with_top_values(vec!["apple".to_string()]);
+        let standard_field_definition = result.get_standard_field_definition().unwrap();
+        assert!(!standard_field_definition.needs_top_values());
+        assert!(result.get_raw_query().contains("ELSE 'Other'"));
+        assert_eq!(
+            result.get_field_query(),
+            format!(r#"{} as "product""#, result.get_raw_query())
+        );
+    }
+
+    #[test]
+    fn top_n_is_not_a_string() {
+        let input = json!({
+            "fieldDisplayName": "units",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "units",
+                "topN": {"count": 10}
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        match result.err().unwrap() {
+            FromJsonError::StandardFieldDefinitionError(data) => {
+                let d = data.data.unwrap();
+                assert_eq!(d["field"], "units");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+
+    #[test]
+    fn binned_field_is_ok() {
+        let input = json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "equal_width",
+                "binCount": 10
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_binned());
+        assert_eq!(result.get_field_type(), FieldType::String);
+        assert_eq!(result.get_column_names(), vec!["price".to_string()]);
+        let binned_field_definition = result.get_binned_field_definition().unwrap();
+        assert_eq!(binned_field_definition.bin_count, 10);
+    }
+
+    #[test]
+    fn binned_field_is_not_numeric() {
+        let input = json!({
+            "fieldDisplayName": "region",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "region",
+                "binningMethod": "equal_width",
+                "binCount": 10
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FromJsonError::BinnedFieldDefinitionError(data) => {
+                let d = data.data.unwrap();
+                assert_eq!(d["field"], "region");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+
+    #[test]
+    fn binned_field_is_error() {
+        let input = json!({
+            "fieldDisplayName": "price",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "price",
+                "binningMethod": "explicit",
+                "edges": [10]
+            }
+        });
+        let result = FieldDefinition::from_json(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FromJsonError::BinnedFieldDefinitionError(data) => {
+                let d = data.data.unwrap();
+                assert_eq!(d["field"], "price");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
+
     #[test]
     fn outer_validation_error() {
         let input = json!({
@@ -1341,6 +2017,29 @@ mod get_field_data_type {
             }
         }
     }
+
+    #[test]
+    fn not_a_number() {
+        let input = json!({
+            "fieldDisplayName": "test",
+            "fieldDataType": "string",
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "test"
+            }
+        });
+        let result = FieldDefinition::get_field_data_type(&input);
+        match result.err().unwrap() {
+            FromJsonError::InvalidFieldType(data) => {
+                let d = data.data.unwrap();
+                assert_eq!(d["field"], "test");
+                assert_eq!(d["field_type"], "string");
+            }
+            _ => {
+                panic!("Unexpected result");
+            }
+        }
+    }
 }
 #[cfg(test)]
 mod get_query_parts {
@@ -1460,3 +2159,84 @@ mod get_query_parts {
         assert_eq!(raw_query, raw_expected_value);
     }
 }
+
+#[cfg(test)]
+mod get_sort_order {
+    use super::*;
+
+    #[test]
+    fn is_default() {
+        let input = json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product"
+            }
+        });
+        let result = FieldDefinition::from_json(&input).unwrap();
+        assert!(result.get_sort_order().is_default());
+    }
+
+    #[test]
+    fn is_ok() {
+        let input = json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": {"count": 2}
+            },
+            "sortOrder": {"sortBy": "explicit", "values": ["pear", "apple"]}
+        });
+        let result = FieldDefinition::from_json(&input).unwrap();
+        let expected = SortOrder::Explicit {
+            values: vec![json!("pear"), json!("apple")],
+        };
+        assert_eq!(result.get_sort_order(), expected);
+        //Resolving the top N keeps the sort order.
+        let result = result.with_top_values(vec!["apple".to_string()]);
+        assert_eq!(result.get_sort_order(), expected);
+    }
+
+    #[test]
+    fn is_invalid() {
+        let input = json!({
+            "fieldDisplayName": "product",
+            "fieldDataType": 1,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": "product"
+            },
+            "sortOrder": {"sortBy": "size"}
+        });
+        match FieldDefinition::from_json(&input).err().unwrap() {
+            FromJsonError::InvalidSortOrder(_) => {}
+            _ => panic!("Expected InvalidSortOrder"),
+        }
+    }
+
+    #[test]
+    fn accumulated_is_not_sorted() {
+        let input = json!({
+            "fieldDisplayName": "total",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "sum",
+                "accumulatedFieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "sales"
+                }
+            },
+            "sortOrder": {"direction": "desc"}
+        });
+        match FieldDefinition::from_json(&input).err().unwrap() {
+            FromJsonError::InvalidSortOrder(data) => {
+                assert_eq!(data.data.unwrap()["field"], "total");
+            }
+            _ => panic!("Expected InvalidSortOrder"),
+        }
+    }
+}
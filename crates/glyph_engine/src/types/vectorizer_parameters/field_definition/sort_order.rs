@@ -0,0 +1,246 @@
+use super::AccumulatorType;
+use crate::types::vectorizer_parameters::field_definition::field_definition_errors::FromJsonError;
+use glyphx_core::GlyphxErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn from_string(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "asc" | "ascending" => Some(SortDirection::Ascending),
+            "desc" | "descending" => Some(SortDirection::Descending),
+            _ => None,
+        }
+    }
+}
+
+///How the distinct values of an axis are ordered, which sets their rank in the .vec file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SortOrder {
+    ///By the values themselves.  Bins are ordered by their edges rather than by their labels.
+    Value { direction: SortDirection },
+    ///By accumulating field_name over the rows that have each value.  COUNT without a field_name
+    ///counts the rows.  Values that have nothing to accumulate go last.
+    Accumulator {
+        accumulator: AccumulatorType,
+        field_name: Option<String>,
+        direction: SortDirection,
+    },
+    ///In the order that the values are listed, followed by any values that are not listed, in
+    ///value order.  Strings are matched against the text of a value and numbers against the value.
+    Explicit { values: Vec<Value> },
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::Value {
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+impl SortOrder {
+    ///Parses the sortOrder of a field definition, i.e.
+    ///{"sortBy": "accumulator", "accumulator": "sum", "fieldName": "sales", "direction": "desc"}
+    pub fn from_json(input: &Value) -> Result<Self, FromJsonError> {
+        if input.is_null() {
+            return Ok(SortOrder::default());
+        }
+        let direction = match input["direction"].as_str() {
+            Some(direction) => match SortDirection::from_string(direction) {
+                Some(direction) => direction,
+                None => {
+                    return Err(Self::invalid_sort_order(
+                        "The sortOrder direction must be asc or desc",
+                        input,
+                    ))
+                }
+            },
+            None => SortDirection::Ascending,
+        };
+        let sort_by = input["sortBy"]
+            .as_str()
+            .unwrap_or("value")
+            .trim()
+            .to_lowercase();
+        match sort_by.as_str() {
+            "value" => Ok(SortOrder::Value { direction }),
+            "accumulator" => {
+                let accumulator = input["accumulator"].as_str().unwrap_or("count");
+                if !["count", "sum", "avg", "min", "max"]
+                    .contains(&accumulator.trim().to_lowercase().as_str())
+                {
+                    return Err(Self::invalid_sort_order(
+                        "The sortOrder accumulator must be one of count, sum, avg, min or max",
+                        input,
+                    ));
+                }
+                let accumulator = AccumulatorType::from_str(accumulator);
+                let field_name = input["fieldName"].as_str().map(|field| field.to_string());
+                if field_name.is_none() && accumulator != AccumulatorType::COUNT {
+                    return Err(Self::invalid_sort_order(
+                        "The sortOrder fieldName is required by every accumulator but count",
+                        input,
+                    ));
+                }
+                Ok(SortOrder::Accumulator {
+                    accumulator,
+                    field_name,
+                    direction,
+                })
+            }
+            "explicit" => {
+                let values = input["values"].as_array();
+                if values.is_none()
+                    || values
+                        .unwrap()
+                        .iter()
+                        .any(|value| !value.is_string() && !value.is_number())
+                {
+                    return Err(Self::invalid_sort_order(
+                        "The sortOrder values must be an array of strings and numbers",
+                        input,
+                    ));
+                }
+                Ok(SortOrder::Explicit {
+                    values: values.unwrap().clone(),
+                })
+            }
+            _ => Err(Self::invalid_sort_order(
+                "The sortOrder sortBy must be one of value, accumulator or explicit",
+                input,
+            )),
+        }
+    }
+
+    fn invalid_sort_order(message: &str, input: &Value) -> FromJsonError {
+        let data = json!({ "field": "sortOrder", "sortOrder": input });
+        FromJsonError::InvalidSortOrder(GlyphxErrorData::new(message.to_string(), Some(data), None))
+    }
+
+    ///The values of an axis are ordered by value, ascending, unless told otherwise.  Any other
+    ///order makes the axis ordinal, so its vectors are placed by their rank.
+    pub fn is_default(&self) -> bool {
+        *self == SortOrder::default()
+    }
+
+    ///Returns the position of a value in an explicit order, or the number of listed values for a
+    ///value that is not listed, or that is being sorted some other way.
+    pub fn get_explicit_position(&self, value: &Value) -> usize {
+        let values = match self {
+            SortOrder::Explicit { values } => values,
+            _ => return 0,
+        };
+        let text = match value {
+            Value::String(string) => string.clone(),
+            _ => value.to_string(),
+        };
+        values
+            .iter()
+            .position(|listed| match listed {
+                Value::String(listed) => *listed == text,
+                _ => listed.as_f64().is_some() && listed.as_f64() == value.as_f64(),
+            })
+            .unwrap_or(values.len())
+    }
+}
+
+#[cfg(test)]
+mod from_json {
+    use super::*;
+
+    #[test]
+    fn is_default() {
+        let result = SortOrder::from_json(&Value::Null).unwrap();
+        assert!(result.is_default());
+        let result = SortOrder::from_json(&json!({"sortBy": "value"})).unwrap();
+        assert!(result.is_default());
+    }
+
+    #[test]
+    fn value() {
+        let result = SortOrder::from_json(&json!({"direction": "DESC"})).unwrap();
+        assert_eq!(
+            result,
+            SortOrder::Value {
+                direction: SortDirection::Descending
+            }
+        );
+        assert!(!result.is_default());
+    }
+
+    #[test]
+    fn accumulator() {
+        let result = SortOrder::from_json(&json!({
+            "sortBy": "accumulator",
+            "accumulator": "sum",
+            "fieldName": "sales",
+            "direction": "desc"
+        }))
+        .unwrap();
+        assert_eq!(
+            result,
+            SortOrder::Accumulator {
+                accumulator: AccumulatorType::SUM,
+                field_name: Some("sales".to_string()),
+                direction: SortDirection::Descending
+            }
+        );
+    }
+
+    #[test]
+    fn explicit() {
+        let result =
+            SortOrder::from_json(&json!({"sortBy": "explicit", "values": ["Jan", "Feb", 3]}))
+                .unwrap();
+        assert_eq!(
+            result,
+            SortOrder::Explicit {
+                values: vec![json!("Jan"), json!("Feb"), json!(3)]
+            }
+        );
+    }
+
+    #[test]
+    fn is_error() {
+        let inputs = [
+            json!({"sortBy": "random"}),
+            json!({"direction": "up"}),
+            json!({"sortBy": "accumulator", "accumulator": "median", "fieldName": "sales"}),
+            json!({"sortBy": "accumulator", "accumulator": "sum"}),
+            json!({"sortBy": "explicit"}),
+            json!({"sortBy": "explicit", "values": [true]}),
+        ];
+        for input in inputs.iter() {
+            match SortOrder::from_json(input).err().unwrap() {
+                FromJsonError::InvalidSortOrder(data) => {
+                    assert_eq!(data.data.unwrap()["field"], "sortOrder");
+                }
+                _ => panic!("Expected InvalidSortOrder"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_explicit_position {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let sort_order = SortOrder::Explicit {
+            values: vec![json!("Feb"), json!("Jan"), json!(3)],
+        };
+        assert_eq!(sort_order.get_explicit_position(&json!("Jan")), 1);
+        assert_eq!(sort_order.get_explicit_position(&json!(3.0)), 2);
+        assert_eq!(sort_order.get_explicit_position(&json!("Mar")), 3);
+    }
+}
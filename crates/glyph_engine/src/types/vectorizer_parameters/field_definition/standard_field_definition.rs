@@ -1,12 +1,103 @@
+use super::AccumulatorType;
 use crate::types::field_definition_type::FieldDefinitionType;
 use crate::types::vectorizer_parameters::field_definition::standard_field_definition_errors::FromJsonError;
 use crate::types::vectorizer_parameters::helper_functions::json_has_field;
+use glyphx_core::GlyphxErrorData;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+
+///Keeps the count values of a column that rank highest and collapses every other value into a
+///single bucket labeled other_label, so that a column with many distinct values still makes a
+///usable axis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopNDefinition {
+    pub count: usize,
+    ///How the values are ranked.  COUNT without a rank_by_field ranks them by how often they
+    ///occur, the others rank them by accumulating rank_by_field over the rows of each value.
+    pub accumulator: AccumulatorType,
+    pub rank_by_field: Option<String>,
+    pub other_label: String,
+    ///The values that are kept.  They depend on the data, so they are None until they have been
+    ///resolved with with_top_values.
+    pub values: Option<Vec<String>>,
+}
+
+impl TopNDefinition {
+    pub fn from_json(input: &Value) -> Result<Self, FromJsonError> {
+        let count = input["count"].as_u64().unwrap_or(0);
+        if count == 0 {
+            return Err(Self::invalid_top_n(
+                "The topN count must be a positive integer",
+                input,
+            ));
+        }
+        let accumulator = match input["accumulator"].as_str() {
+            Some(accumulator) => AccumulatorType::try_from_str(accumulator).ok(),
+            None => Some(AccumulatorType::COUNT),
+        };
+        if !matches!(
+            accumulator,
+            Some(
+                AccumulatorType::COUNT
+                    | AccumulatorType::SUM
+                    | AccumulatorType::AVG
+                    | AccumulatorType::MIN
+                    | AccumulatorType::MAX
+            )
+        ) {
+            return Err(Self::invalid_top_n(
+                "The topN accumulator must be one of count, sum, avg, min or max",
+                input,
+            ));
+        }
+        let accumulator = accumulator.unwrap();
+        let rank_by_field = input["rankByField"].as_str().map(|field| field.to_string());
+        if rank_by_field.is_none() && accumulator != AccumulatorType::COUNT {
+            return Err(Self::invalid_top_n(
+                "The topN rankByField is required by every accumulator but count",
+                input,
+            ));
+        }
+        let other_label = input["otherLabel"].as_str().unwrap_or("Other").to_string();
+        Ok(Self {
+            count: count as usize,
+            accumulator,
+            rank_by_field,
+            other_label,
+            values: None,
+        })
+    }
+
+    fn invalid_top_n(message: &str, input: &Value) -> FromJsonError {
+        let data = json!({ "field": "topN", "topN": input });
+        FromJsonError::InvalidTopN(GlyphxErrorData::new(message.to_string(), Some(data), None))
+    }
+
+    ///Returns the value that a row's value is plotted as: itself when it is kept, or null, and
+    ///otherwise the other bucket.
+    pub fn apply(&self, value: Value) -> Value {
+        let values = match &self.values {
+            Some(values) => values,
+            None => return value,
+        };
+        let key = match &value {
+            Value::Null => return value,
+            Value::String(string) => string.clone(),
+            _ => value.to_string(),
+        };
+        if values.contains(&key) {
+            value
+        } else {
+            json!(self.other_label)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StandardFieldDefinition {
     pub field_type: FieldDefinitionType,
     pub field_name: String,
+    pub top_n: Option<TopNDefinition>,
 }
 
 impl StandardFieldDefinition {
@@ -17,9 +108,19 @@ impl StandardFieldDefinition {
         }
         let field_name = input["fieldName"].as_str().unwrap().to_string();
         let field_type = FieldDefinitionType::Standard;
+        let top_n = if input["topN"].is_null() {
+            None
+        } else {
+            let top_n = TopNDefinition::from_json(&input["topN"]);
+            if top_n.is_err() {
+                return Err(top_n.err().unwrap());
+            }
+            Some(top_n.unwrap())
+        };
         Ok(Self {
             field_type,
             field_name,
+            top_n,
         })
     }
 
@@ -32,9 +133,53 @@ impl StandardFieldDefinition {
         Ok(())
     }
 
+    ///Returns true when the field has a top N whose values still need to be resolved.
+    pub fn needs_top_values(&self) -> bool {
+        match &self.top_n {
+            Some(top_n) => top_n.values.is_none(),
+            None => false,
+        }
+    }
+
+    pub fn with_top_values(&self, values: Vec<String>) -> Self {
+        let mut field_definition = self.clone();
+        if let Some(top_n) = field_definition.top_n.as_mut() {
+            top_n.values = Some(values);
+        }
+        field_definition
+    }
+
+    ///Returns the resolved top N, if there is one.
+    pub fn get_resolved_top_n(&self) -> Option<&TopNDefinition> {
+        match &self.top_n {
+            Some(top_n) if top_n.values.is_some() => Some(top_n),
+            _ => None,
+        }
+    }
+
     pub fn get_query(&self, display_name: &str) -> (String, String) {
         let field_name = self.field_name.clone();
-        let raw_query = format!(r#""{}""#, field_name);
+        let mut raw_query = format!(r#""{}""#, field_name);
+        if let Some(top_n) = self.get_resolved_top_n() {
+            let other_label = top_n.other_label.replace('\'', "''");
+            let values = top_n.values.as_ref().unwrap();
+            raw_query = if values.is_empty() {
+                format!(
+                    "CASE WHEN {} IS NULL THEN NULL ELSE '{}' END",
+                    raw_query, other_label
+                )
+            } else {
+                let values = values
+                    .iter()
+                    .map(|value| format!("'{}'", value.replace('\'', "''")))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(
+                    "CASE WHEN {} IS NULL THEN NULL WHEN {} IN ({}) THEN {} ELSE '{}' END",
+                    raw_query, raw_query, values, raw_query, other_label
+                )
+            };
+        }
         let query = format!(r#"{} as "{}""#, raw_query, display_name);
         (query, raw_query)
     }
@@ -119,3 +264,88 @@ mod from_json {
         }
     }
 }
+
+#[cfg(test)]
+mod top_n {
+    use super::*;
+
+    fn get_field_definition(top_n: Value) -> StandardFieldDefinition {
+        StandardFieldDefinition::from_json(&json!({
+            "fieldType": "standard",
+            "fieldName": "product",
+            "topN": top_n
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn from_json() {
+        let field_definition = get_field_definition(json!({"count": 2}));
+        let top_n = field_definition.top_n.as_ref().unwrap();
+        assert_eq!(top_n.count, 2);
+        assert_eq!(top_n.accumulator, AccumulatorType::COUNT);
+        assert!(top_n.rank_by_field.is_none());
+        assert_eq!(top_n.other_label, "Other");
+        assert!(field_definition.needs_top_values());
+
+        let field_definition = get_field_definition(
+            json!({"count": 5, "accumulator": "sum", "rankByField": "revenue", "otherLabel": "Rest"}),
+        );
+        let top_n = field_definition.top_n.unwrap();
+        assert_eq!(top_n.accumulator, AccumulatorType::SUM);
+        assert_eq!(top_n.rank_by_field.unwrap(), "revenue");
+        assert_eq!(top_n.other_label, "Rest");
+    }
+
+    #[test]
+    fn is_invalid() {
+        let inputs = vec![
+            json!({"count": 0}),
+            json!({"count": 5, "accumulator": "median", "rankByField": "revenue"}),
+            json!({"count": 5, "accumulator": "sum"}),
+        ];
+        for input in inputs {
+            let result = StandardFieldDefinition::from_json(&json!({
+                "fieldType": "standard",
+                "fieldName": "product",
+                "topN": input
+            }));
+            match result {
+                Err(FromJsonError::InvalidTopN(error_data)) => {
+                    assert_eq!(error_data.data.unwrap()["field"], "topN");
+                }
+                _ => panic!("Expected InvalidTopN"),
+            }
+        }
+    }
+
+    #[test]
+    fn get_query() {
+        let field_definition = get_field_definition(json!({"count": 2}));
+        let (_, raw_query) = field_definition.get_query("Product");
+        assert_eq!(raw_query, r#""product""#);
+
+        let field_definition =
+            field_definition.with_top_values(vec!["apple".to_string(), "pear's".to_string()]);
+        assert!(!field_definition.needs_top_values());
+        let (query, raw_query) = field_definition.get_query("Product");
+        assert_eq!(
+            raw_query,
+            r#"CASE WHEN "product" IS NULL THEN NULL WHEN "product" IN ('apple', 'pear''s') THEN "product" ELSE 'Other' END"#
+        );
+        assert_eq!(query, format!(r#"{} as "Product""#, raw_query));
+    }
+
+    #[test]
+    fn apply() {
+        let field_definition = get_field_definition(json!({"count": 1}));
+        let top_n = field_definition.top_n.as_ref().unwrap();
+        assert_eq!(top_n.apply(json!("pear")), json!("pear"));
+
+        let field_definition = field_definition.with_top_values(vec!["apple".to_string()]);
+        let top_n = field_definition.top_n.as_ref().unwrap();
+        assert_eq!(top_n.apply(json!("apple")), json!("apple"));
+        assert_eq!(top_n.apply(json!("pear")), json!("Other"));
+        assert_eq!(top_n.apply(Value::Null), Value::Null);
+    }
+}
@@ -5,6 +5,7 @@ use glyphx_core::GlyphxErrorData;
 #[error_definition("StandardFieldDefinition")]
 pub enum FromJsonError {
     FieldNotDefined(GlyphxErrorData),
+    InvalidTopN(GlyphxErrorData),
 }
 
 impl FromJsonError {
@@ -0,0 +1,765 @@
+use crate::types::vectorizer_parameters::filter_errors::{FromJsonError, ValidateError};
+use crate::types::vectorizer_parameters::helper_functions::{json_has_field, parse_iso_date};
+use glyphx_core::aws::athena_manager::{ColumnDataType, ColumnDescription};
+use glyphx_core::GlyphxErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComparisonOperator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl ComparisonOperator {
+    pub fn from_string(input: &str) -> Option<Self> {
+        match input.trim() {
+            "=" | "==" => Some(ComparisonOperator::Equal),
+            "!=" | "<>" => Some(ComparisonOperator::NotEqual),
+            "<" => Some(ComparisonOperator::LessThan),
+            "<=" => Some(ComparisonOperator::LessThanOrEqual),
+            ">" => Some(ComparisonOperator::GreaterThan),
+            ">=" => Some(ComparisonOperator::GreaterThanOrEqual),
+            _ => None,
+        }
+    }
+
+    pub fn get_sql(&self) -> &'static str {
+        match self {
+            ComparisonOperator::Equal => "=",
+            ComparisonOperator::NotEqual => "<>",
+            ComparisonOperator::LessThan => "<",
+            ComparisonOperator::LessThanOrEqual => "<=",
+            ComparisonOperator::GreaterThan => ">",
+            ComparisonOperator::GreaterThanOrEqual => ">=",
+        }
+    }
+}
+
+///A condition on the rows of the data table.  Unlike a raw SQL filter, it can be checked against
+///the columns of the table before we run anything, and every name and literal in it is quoted
+///when it is rendered, so it cannot change the shape of the query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FilterExpression {
+    Comparison {
+        field_name: String,
+        operator: ComparisonOperator,
+        value: Value,
+    },
+    In {
+        field_name: String,
+        values: Vec<Value>,
+    },
+    ///min and max are inclusive and either one may be left open.
+    Range {
+        field_name: String,
+        min: Option<Value>,
+        max: Option<Value>,
+    },
+    ///Dates are stored as epoch milliseconds.  from is inclusive and to is exclusive, so that
+    ///consecutive ranges do not overlap.
+    DateRange {
+        field_name: String,
+        from: Option<i64>,
+        to: Option<i64>,
+    },
+    IsNull {
+        field_name: String,
+    },
+    IsNotNull {
+        field_name: String,
+    },
+    And(Vec<FilterExpression>),
+    Or(Vec<FilterExpression>),
+    Not(Box<FilterExpression>),
+}
+
+impl FilterExpression {
+    ///Parses a filter, i.e. {"and": [{"fieldName": "region", "operator": "in", "values":
+    ///["east", "west"]}, {"not": {"fieldName": "sales", "operator": "isNull"}}]}
+    pub fn from_json(input: &Value) -> Result<Self, FromJsonError> {
+        if !input.is_object() {
+            return Err(Self::invalid_value("A filter must be an object", input));
+        }
+        if !input["and"].is_null() {
+            return Ok(FilterExpression::And(Self::from_json_list(&input["and"])?));
+        }
+        if !input["or"].is_null() {
+            return Ok(FilterExpression::Or(Self::from_json_list(&input["or"])?));
+        }
+        if !input["not"].is_null() {
+            let expression = Self::from_json(&input["not"])?;
+            return Ok(FilterExpression::Not(Box::new(expression)));
+        }
+
+        let has_field = json_has_field(input, "fieldName");
+        if has_field.is_err() {
+            return Err(FromJsonError::from_json_has_field_error(
+                has_field.err().unwrap(),
+            ));
+        }
+        let has_field = json_has_field(input, "operator");
+        if has_field.is_err() {
+            return Err(FromJsonError::from_json_has_field_error(
+                has_field.err().unwrap(),
+            ));
+        }
+        let field_name = input["fieldName"].as_str();
+        if field_name.is_none() {
+            return Err(Self::invalid_value("The fieldName must be a string", input));
+        }
+        let field_name = field_name.unwrap().to_string();
+        let operator = input["operator"]
+            .as_str()
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+
+        match operator.as_str() {
+            "in" => {
+                let values = input["values"].as_array();
+                if values.is_none() || values.unwrap().iter().any(|v| !Self::is_literal(v)) {
+                    return Err(Self::invalid_value(
+                        "The values of an in filter must be an array of strings and numbers",
+                        input,
+                    ));
+                }
+                Ok(FilterExpression::In {
+                    field_name,
+                    values: values.unwrap().clone(),
+                })
+            }
+            "between" => {
+                let min = Self::get_optional_literal(input, "min")?;
+                let max = Self::get_optional_literal(input, "max")?;
+                if min.is_none() && max.is_none() {
+                    return Err(Self::invalid_value(
+                        "A between filter needs a min, a max or both",
+                        input,
+                    ));
+                }
+                Ok(FilterExpression::Range {
+                    field_name,
+                    min,
+                    max,
+                })
+            }
+            "daterange" => {
+                let from = Self::get_optional_date(input, "from")?;
+                let to = Self::get_optional_date(input, "to")?;
+                if from.is_none() && to.is_none() {
+                    return Err(Self::invalid_value(
+                        "A dateRange filter needs a from, a to or both",
+                        input,
+                    ));
+                }
+                Ok(FilterExpression::DateRange {
+                    field_name,
+                    from,
+                    to,
+                })
+            }
+            "isnull" => Ok(FilterExpression::IsNull { field_name }),
+            "isnotnull" => Ok(FilterExpression::IsNotNull { field_name }),
+            _ => {
+                let comparison_operator = ComparisonOperator::from_string(&operator);
+                if comparison_operator.is_none() {
+                    let message = format!("The filter operator {} is not supported", operator);
+                    let data = json!({ "field": "operator", "filter": input });
+                    return Err(FromJsonError::InvalidOperator(GlyphxErrorData::new(
+                        message,
+                        Some(data),
+                        None,
+                    )));
+                }
+                if !Self::is_literal(&input["value"]) {
+                    return Err(Self::invalid_value(
+                        "The value of a comparison must be a string or a number",
+                        input,
+                    ));
+                }
+                Ok(FilterExpression::Comparison {
+                    field_name,
+                    operator: comparison_operator.unwrap(),
+                    value: input["value"].clone(),
+                })
+            }
+        }
+    }
+
+    fn from_json_list(input: &Value) -> Result<Vec<FilterExpression>, FromJsonError> {
+        let list = input.as_array();
+        if list.is_none() || list.unwrap().is_empty() {
+            return Err(Self::invalid_value(
+                "and and or must be a non empty array of filters",
+                input,
+            ));
+        }
+        let mut expressions: Vec<FilterExpression> = Vec::new();
+        for item in list.unwrap() {
+            expressions.push(Self::from_json(item)?);
+        }
+        Ok(expressions)
+    }
+
+    fn is_literal(value: &Value) -> bool {
+        value.is_string() || value.is_number()
+    }
+
+    fn get_optional_literal(input: &Value, key: &str) -> Result<Option<Value>, FromJsonError> {
+        let value = &input[key];
+        if value.is_null() {
+            return Ok(None);
+        }
+        if !Self::is_literal(value) {
+            let message = format!(
+                "The {} of a between filter must be a string or a number",
+                key
+            );
+            return Err(Self::invalid_value(&message, input));
+        }
+        Ok(Some(value.clone()))
+    }
+
+    fn get_optional_date(input: &Value, key: &str) -> Result<Option<i64>, FromJsonError> {
+        let value = &input[key];
+        if value.is_null() {
+            return Ok(None);
+        }
+        let epoch = match value {
+            Value::Number(number) => number.as_f64().map(|n| n as i64),
+            Value::String(text) => parse_iso_date(text),
+            _ => None,
+        };
+        if epoch.is_none() {
+            let message = format!(
+                "The {} of a dateRange filter must be epoch milliseconds or an ISO 8601 date",
+                key
+            );
+            return Err(Self::invalid_value(&message, input));
+        }
+        Ok(epoch)
+    }
+
+    fn invalid_value(message: &str, input: &Value) -> FromJsonError {
+        let data = json!({ "field": "filter", "filter": input });
+        FromJsonError::InvalidValue(GlyphxErrorData::new(message.to_string(), Some(data), None))
+    }
+
+    ///Renders the filter as an Athena boolean expression.
+    pub fn get_query(&self) -> String {
+        match self {
+            FilterExpression::Comparison {
+                field_name,
+                operator,
+                value,
+            } => format!(
+                "{} {} {}",
+                quote_identifier(field_name),
+                operator.get_sql(),
+                quote_literal(value)
+            ),
+            FilterExpression::In { field_name, values } => {
+                //IN () is not valid SQL, and a value can never be in an empty list.
+                if values.is_empty() {
+                    return "FALSE".to_string();
+                }
+                let values: Vec<String> = values.iter().map(quote_literal).collect();
+                format!(
+                    "{} IN ({})",
+                    quote_identifier(field_name),
+                    values.join(", ")
+                )
+            }
+            FilterExpression::Range {
+                field_name,
+                min,
+                max,
+            } => {
+                let field_name = quote_identifier(field_name);
+                match (min, max) {
+                    (Some(min), Some(max)) => format!(
+                        "{} BETWEEN {} AND {}",
+                        field_name,
+                        quote_literal(min),
+                        quote_literal(max)
+                    ),
+                    (Some(min), None) => format!("{} >= {}", field_name, quote_literal(min)),
+                    (None, Some(max)) => format!("{} <= {}", field_name, quote_literal(max)),
+                    (None, None) => "TRUE".to_string(),
+                }
+            }
+            FilterExpression::DateRange {
+                field_name,
+                from,
+                to,
+            } => {
+                let field_name = quote_identifier(field_name);
+                match (from, to) {
+                    (Some(from), Some(to)) => {
+                        format!("{} >= {} AND {} < {}", field_name, from, field_name, to)
+                    }
+                    (Some(from), None) => format!("{} >= {}", field_name, from),
+                    (None, Some(to)) => format!("{} < {}", field_name, to),
+                    (None, None) => "TRUE".to_string(),
+                }
+            }
+            FilterExpression::IsNull { field_name } => {
+                format!("{} IS NULL", quote_identifier(field_name))
+            }
+            FilterExpression::IsNotNull { field_name } => {
+                format!("{} IS NOT NULL", quote_identifier(field_name))
+            }
+            FilterExpression::And(expressions) => Self::join_queries(expressions, " AND "),
+            FilterExpression::Or(expressions) => Self::join_queries(expressions, " OR "),
+            FilterExpression::Not(expression) => format!("NOT ({})", expression.get_query()),
+        }
+    }
+
+    fn join_queries(expressions: &[FilterExpression], separator: &str) -> String {
+        let queries: Vec<String> = expressions
+            .iter()
+            .map(|expression| format!("({})", expression.get_query()))
+            .collect();
+        queries.join(separator)
+    }
+
+    ///Returns the names of the columns that the filter references.
+    pub fn get_field_names(&self) -> Vec<String> {
+        let mut field_names: Vec<String> = Vec::new();
+        self.collect_field_names(&mut field_names);
+        field_names
+    }
+
+    fn collect_field_names(&self, field_names: &mut Vec<String>) {
+        let field_name = match self {
+            FilterExpression::Comparison { field_name, .. }
+            | FilterExpression::In { field_name, .. }
+            | FilterExpression::Range { field_name, .. }
+            | FilterExpression::DateRange { field_name, .. }
+            | FilterExpression::IsNull { field_name }
+            | FilterExpression::IsNotNull { field_name } => field_name,
+            FilterExpression::And(expressions) | FilterExpression::Or(expressions) => {
+                for expression in expressions {
+                    expression.collect_field_names(field_names);
+                }
+                return;
+            }
+            FilterExpression::Not(expression) => {
+                expression.collect_field_names(field_names);
+                return;
+            }
+        };
+        if !field_names.contains(field_name) {
+            field_names.push(field_name.clone());
+        }
+    }
+
+    ///Checks that every column the filter references exists in the table and that its literals
+    ///match the column types.  Columns of an UNKNOWN type accept any literal.
+    pub fn validate(&self, columns: &[ColumnDescription]) -> Result<(), ValidateError> {
+        match self {
+            FilterExpression::Comparison {
+                field_name, value, ..
+            } => Self::validate_literals(field_name, &[value], columns),
+            FilterExpression::In { field_name, values } => {
+                let values: Vec<&Value> = values.iter().collect();
+                Self::validate_literals(field_name, &values, columns)
+            }
+            FilterExpression::Range {
+                field_name,
+                min,
+                max,
+            } => {
+                let values: Vec<&Value> = min.iter().chain(max.iter()).collect();
+                Self::validate_literals(field_name, &values, columns)
+            }
+            FilterExpression::DateRange { field_name, .. } => {
+                let column = Self::find_column(field_name, columns)?;
+                if matches!(column.data_type, ColumnDataType::STRING) {
+                    let message = format!(
+                        "The column {} is a string and cannot be filtered by a date range",
+                        field_name
+                    );
+                    let data = json!({ "fieldName": field_name });
+                    return Err(ValidateError::InvalidValueType(GlyphxErrorData::new(
+                        message,
+                        Some(data),
+                        None,
+                    )));
+                }
+                Ok(())
+            }
+            FilterExpression::IsNull { field_name }
+            | FilterExpression::IsNotNull { field_name } => {
+                Self::find_column(field_name, columns)?;
+                Ok(())
+            }
+            FilterExpression::And(expressions) | FilterExpression::Or(expressions) => {
+                for expression in expressions {
+                    expression.validate(columns)?;
+                }
+                Ok(())
+            }
+            FilterExpression::Not(expression) => expression.validate(columns),
+        }
+    }
+
+    fn find_column<'a>(
+        field_name: &str,
+        columns: &'a [ColumnDescription],
+    ) -> Result<&'a ColumnDescription, ValidateError> {
+        //Athena column names are case insensitive.
+        let column = columns
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(field_name));
+        if column.is_none() {
+            let message = format!("The column {} does not exist in the table", field_name);
+            let column_names: Vec<&str> =
+                columns.iter().map(|column| column.name.as_str()).collect();
+            let data = json!({ "fieldName": field_name, "columns": column_names });
+            return Err(ValidateError::ColumnNotFound(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )));
+        }
+        Ok(column.unwrap())
+    }
+
+    fn validate_literals(
+        field_name: &str,
+        values: &[&Value],
+        columns: &[ColumnDescription],
+    ) -> Result<(), ValidateError> {
+        let column = Self::find_column(field_name, columns)?;
+        let is_valid = match column.data_type {
+            ColumnDataType::STRING => values.iter().all(|value| value.is_string()),
+            ColumnDataType::NUMBER | ColumnDataType::INTEGER | ColumnDataType::DATE => {
+                values.iter().all(|value| value.is_number())
+            }
+            ColumnDataType::UNKNOWN => true,
+        };
+        if !is_valid {
+            let message = format!(
+                "The values compared to the column {} do not match its type, {:?}",
+                field_name, column.data_type
+            );
+            let data = json!({ "fieldName": field_name, "values": values });
+            return Err(ValidateError::InvalidValueType(GlyphxErrorData::new(
+                message,
+                Some(data),
+                None,
+            )));
+        }
+        Ok(())
+    }
+}
+
+///The filter of a model.  A raw filter is SQL that is used as is, so it can only be used when the
+///caller explicitly allows it with allow_raw_filter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Filter {
+    Raw(String),
+    Expression(FilterExpression),
+}
+
+impl Filter {
+    pub fn get_query(&self) -> String {
+        match self {
+            Filter::Raw(query) => query.clone(),
+            Filter::Expression(expression) => expression.get_query(),
+        }
+    }
+
+    ///Raw filters cannot be checked without running them, so they are always valid here.
+    pub fn validate(&self, columns: &[ColumnDescription]) -> Result<(), ValidateError> {
+        match self {
+            Filter::Raw(_) => Ok(()),
+            Filter::Expression(expression) => expression.validate(columns),
+        }
+    }
+}
+
+///Wraps an identifier in double quotes, doubling any quotes inside of it.
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+///Renders a literal, wrapping strings in single quotes and doubling any quotes inside of them.
+pub fn quote_literal(value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("'{}'", text.replace('\'', "''")),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string().to_uppercase(),
+        _ => "NULL".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod from_json {
+    use super::*;
+
+    #[test]
+    fn comparison() {
+        let result = FilterExpression::from_json(
+            &json!({"fieldName": "sales", "operator": ">=", "value": 10}),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            FilterExpression::Comparison {
+                field_name: "sales".to_string(),
+                operator: ComparisonOperator::GreaterThanOrEqual,
+                value: json!(10)
+            }
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let result = FilterExpression::from_json(&json!({"and": [
+            {"fieldName": "region", "operator": "in", "values": ["east", "west"]},
+            {"not": {"fieldName": "sales", "operator": "isNull"}},
+            {"or": [
+                {"fieldName": "sales", "operator": "between", "min": 1},
+                {"fieldName": "created", "operator": "dateRange", "from": "2023-01-01", "to": 1675209600000_i64}
+            ]}
+        ]}))
+        .unwrap();
+        assert_eq!(
+            result,
+            FilterExpression::And(vec![
+                FilterExpression::In {
+                    field_name: "region".to_string(),
+                    values: vec![json!("east"), json!("west")]
+                },
+                FilterExpression::Not(Box::new(FilterExpression::IsNull {
+                    field_name: "sales".to_string()
+                })),
+                FilterExpression::Or(vec![
+                    FilterExpression::Range {
+                        field_name: "sales".to_string(),
+                        min: Some(json!(1)),
+                        max: None
+                    },
+                    FilterExpression::DateRange {
+                        field_name: "created".to_string(),
+                        from: Some(1672531200000),
+                        to: Some(1675209600000)
+                    }
+                ])
+            ])
+        );
+    }
+
+    #[test]
+    fn field_not_defined() {
+        let result = FilterExpression::from_json(&json!({"operator": "=", "value": 1}));
+        match result.err().unwrap() {
+            FromJsonError::FieldNotDefined(data) => {
+                assert_eq!(data.data.unwrap()["field"], "fieldName");
+            }
+            _ => panic!("Expected FieldNotDefined"),
+        }
+    }
+
+    #[test]
+    fn invalid_operator() {
+        let result = FilterExpression::from_json(
+            &json!({"fieldName": "a", "operator": "like", "value": "x"}),
+        );
+        match result.err().unwrap() {
+            FromJsonError::InvalidOperator(data) => {
+                assert_eq!(data.data.unwrap()["field"], "operator");
+            }
+            _ => panic!("Expected InvalidOperator"),
+        }
+    }
+
+    #[test]
+    fn invalid_value() {
+        let inputs = [
+            json!("sales > 10"),
+            json!({"and": []}),
+            json!({"fieldName": "a", "operator": "=", "value": {"x": 1}}),
+            json!({"fieldName": "a", "operator": "=", "value": true}),
+            json!({"fieldName": "a", "operator": "in", "values": "x"}),
+            json!({"fieldName": "a", "operator": "between"}),
+            json!({"fieldName": "a", "operator": "dateRange", "from": "yesterday"}),
+        ];
+        for input in inputs.iter() {
+            match FilterExpression::from_json(input).err().unwrap() {
+                FromJsonError::InvalidValue(data) => {
+                    assert_eq!(data.data.unwrap()["field"], "filter");
+                }
+                _ => panic!("Expected InvalidValue"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_query {
+    use super::*;
+
+    #[test]
+    fn quotes_names_and_literals() {
+        let filter = FilterExpression::Comparison {
+            field_name: "my \"field\"".to_string(),
+            operator: ComparisonOperator::NotEqual,
+            value: json!("it's'; DROP TABLE x; --"),
+        };
+        assert_eq!(
+            filter.get_query(),
+            r#""my ""field""" <> 'it''s''; DROP TABLE x; --'"#
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let filter = FilterExpression::Or(vec![
+            FilterExpression::In {
+                field_name: "region".to_string(),
+                values: vec![json!("east"), json!(3)],
+            },
+            FilterExpression::Not(Box::new(FilterExpression::And(vec![
+                FilterExpression::IsNull {
+                    field_name: "sales".to_string(),
+                },
+                FilterExpression::Range {
+                    field_name: "sales".to_string(),
+                    min: Some(json!(1)),
+                    max: Some(json!(5)),
+                },
+            ]))),
+            FilterExpression::DateRange {
+                field_name: "created".to_string(),
+                from: Some(100),
+                to: Some(200),
+            },
+        ]);
+        assert_eq!(
+            filter.get_query(),
+            r#"("region" IN ('east', 3)) OR (NOT (("sales" IS NULL) AND ("sales" BETWEEN 1 AND 5))) OR ("created" >= 100 AND "created" < 200)"#
+        );
+    }
+
+    #[test]
+    fn empty_in() {
+        let filter = FilterExpression::In {
+            field_name: "region".to_string(),
+            values: vec![],
+        };
+        assert_eq!(filter.get_query(), "FALSE");
+    }
+
+    #[test]
+    fn open_ranges() {
+        let filter = FilterExpression::Range {
+            field_name: "sales".to_string(),
+            min: None,
+            max: Some(json!(5)),
+        };
+        assert_eq!(filter.get_query(), r#""sales" <= 5"#);
+        let filter = FilterExpression::DateRange {
+            field_name: "created".to_string(),
+            from: Some(100),
+            to: None,
+        };
+        assert_eq!(filter.get_query(), r#""created" >= 100"#);
+    }
+}
+
+#[cfg(test)]
+mod validate {
+    use super::*;
+
+    fn get_columns() -> Vec<ColumnDescription> {
+        vec![
+            ColumnDescription {
+                name: "Region".to_string(),
+                data_type: ColumnDataType::STRING,
+            },
+            ColumnDescription {
+                name: "sales".to_string(),
+                data_type: ColumnDataType::NUMBER,
+            },
+            ColumnDescription {
+                name: "other".to_string(),
+                data_type: ColumnDataType::UNKNOWN,
+            },
+        ]
+    }
+
+    #[test]
+    fn is_ok() {
+        let filter = FilterExpression::And(vec![
+            FilterExpression::In {
+                field_name: "region".to_string(),
+                values: vec![json!("east")],
+            },
+            FilterExpression::DateRange {
+                field_name: "sales".to_string(),
+                from: Some(1),
+                to: None,
+            },
+            FilterExpression::Comparison {
+                field_name: "other".to_string(),
+                operator: ComparisonOperator::Equal,
+                value: json!(1),
+            },
+        ]);
+        assert!(filter.validate(&get_columns()).is_ok());
+    }
+
+    #[test]
+    fn column_not_found() {
+        let filter = FilterExpression::Not(Box::new(FilterExpression::IsNull {
+            field_name: "missing".to_string(),
+        }));
+        match filter.validate(&get_columns()).err().unwrap() {
+            ValidateError::ColumnNotFound(data) => {
+                assert_eq!(data.data.unwrap()["fieldName"], "missing");
+            }
+            _ => panic!("Expected ColumnNotFound"),
+        }
+    }
+
+    #[test]
+    fn invalid_value_type() {
+        let filters = [
+            FilterExpression::Comparison {
+                field_name: "sales".to_string(),
+                operator: ComparisonOperator::Equal,
+                value: json!("10"),
+            },
+            FilterExpression::Range {
+                field_name: "Region".to_string(),
+                min: Some(json!("a")),
+                max: Some(json!(3)),
+            },
+            FilterExpression::DateRange {
+                field_name: "Region".to_string(),
+                from: Some(1),
+                to: None,
+            },
+        ];
+        for filter in filters.iter() {
+            match filter.validate(&get_columns()).err().unwrap() {
+                ValidateError::InvalidValueType(_) => {}
+                _ => panic!("Expected InvalidValueType"),
+            }
+        }
+    }
+
+    #[test]
+    fn raw_is_not_validated() {
+        let filter = Filter::Raw("missing = 1".to_string());
+        assert!(filter.validate(&get_columns()).is_ok());
+    }
+}
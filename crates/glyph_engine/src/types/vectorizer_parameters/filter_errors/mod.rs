@@ -0,0 +1,5 @@
+mod from_json_error;
+mod validate_error;
+
+pub use from_json_error::FromJsonError;
+pub use validate_error::ValidateError;
@@ -0,0 +1,9 @@
+use glyphx_core::GlyphxError;
+use glyphx_core::GlyphxErrorData;
+
+#[derive(Debug, Clone, GlyphxError)]
+#[error_definition("Filter")]
+pub enum ValidateError {
+    ColumnNotFound(GlyphxErrorData),
+    InvalidValueType(GlyphxErrorData),
+}
@@ -1,5 +1,7 @@
 mod helper_function_errors;
 mod json_has_field;
+mod parse_iso_date;
 
 pub use helper_function_errors::*;
 pub use json_has_field::json_has_field;
+pub use parse_iso_date::{parse_iso_date, parse_utc_offset};
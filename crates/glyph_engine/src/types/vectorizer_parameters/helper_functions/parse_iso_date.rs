@@ -0,0 +1,143 @@
+use time::{Date, Month};
+
+///Parses an ISO 8601 date or date time, i.e. 2023-01-31, 2023-01-31T12:30:00.250Z or
+///2023-01-31T12:30:00+05:30, into epoch milliseconds.  Times without an offset are taken to be UTC.
+pub fn parse_iso_date(input: &str) -> Option<i64> {
+    let input = input.trim();
+    let (date_part, time_part) = match input.find(['T', ' ']) {
+        Some(index) => (&input[..index], Some(&input[index + 1..])),
+        None => (input, None),
+    };
+
+    let date_parts: Vec<&str> = date_part.split('-').collect();
+    if date_parts.len() != 3 {
+        return None;
+    }
+    let year = date_parts[0].parse::<i32>().ok()?;
+    let month = Month::try_from(date_parts[1].parse::<u8>().ok()?).ok()?;
+    let day = date_parts[2].parse::<u8>().ok()?;
+    let date = Date::from_calendar_date(year, month, day).ok()?;
+
+    let (mut hour, mut minute, mut second, mut millisecond) = (0, 0, 0, 0);
+    let mut offset_seconds = 0;
+    if let Some(time_part) = time_part {
+        let time_part = time_part.trim();
+        let time_part = match time_part.find(['+', '-']) {
+            Some(index) => {
+                offset_seconds = parse_utc_offset(&time_part[index..])?;
+                &time_part[..index]
+            }
+            None => time_part.strip_suffix('Z').unwrap_or(time_part),
+        };
+        let (time_part, fraction) = match time_part.split_once('.') {
+            Some((time_part, fraction)) => (time_part, Some(fraction)),
+            None => (time_part, None),
+        };
+        let time_parts: Vec<&str> = time_part.split(':').collect();
+        if time_parts.len() < 2 || time_parts.len() > 3 {
+            return None;
+        }
+        hour = time_parts[0].parse::<u8>().ok()?;
+        minute = time_parts[1].parse::<u8>().ok()?;
+        if time_parts.len() == 3 {
+            second = time_parts[2].parse::<u8>().ok()?;
+        }
+        if let Some(fraction) = fraction {
+            let fraction = format!("{:0<3}", fraction);
+            millisecond = fraction.get(0..3)?.parse::<i64>().ok()?;
+        }
+    }
+    let date_time = date.with_hms(hour, minute, second).ok()?.assume_utc();
+    Some((date_time.unix_timestamp() - offset_seconds as i64) * 1000 + millisecond)
+}
+
+///Parses a fixed UTC offset, i.e. UTC, Z, +05:30, -0800 or -08, into a number of seconds east of
+///UTC.  Named time zones such as America/New_York are not fixed offsets and return None.
+pub fn parse_utc_offset(input: &str) -> Option<i32> {
+    let input = input.trim();
+    if ["UTC", "Z", "GMT", "Etc/UTC", "Etc/GMT"]
+        .iter()
+        .any(|zone| zone.eq_ignore_ascii_case(input))
+    {
+        return Some(0);
+    }
+    let sign = match input.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = input[1..].replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[0..2].parse::<i32>().ok()?,
+            digits[2..4].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if hours > 18 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(parse_iso_date("2023-01-01"), Some(1672531200000));
+        assert_eq!(parse_iso_date("2023-01-01T00:00:01Z"), Some(1672531201000));
+        assert_eq!(parse_iso_date("2023-01-01 00:01"), Some(1672531260000));
+        assert_eq!(
+            parse_iso_date("2023-01-01T00:00:00.25"),
+            Some(1672531200250)
+        );
+    }
+
+    #[test]
+    fn with_offset() {
+        assert_eq!(
+            parse_iso_date("2023-01-01T05:30:00+05:30"),
+            Some(1672531200000)
+        );
+        assert_eq!(
+            parse_iso_date("2022-12-31T16:00:00-0800"),
+            Some(1672531200000)
+        );
+    }
+
+    #[test]
+    fn is_none() {
+        assert!(parse_iso_date("2023-13-01").is_none());
+        assert!(parse_iso_date("2023/01/01").is_none());
+        assert!(parse_iso_date("2023-01-01T25:00").is_none());
+        assert!(parse_iso_date("2023-01-01T00:00+5").is_none());
+    }
+}
+
+#[cfg(test)]
+mod parse_utc_offset {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        assert_eq!(parse_utc_offset("UTC"), Some(0));
+        assert_eq!(parse_utc_offset("z"), Some(0));
+        assert_eq!(parse_utc_offset("+05:30"), Some(19800));
+        assert_eq!(parse_utc_offset("-0800"), Some(-28800));
+        assert_eq!(parse_utc_offset("-03"), Some(-10800));
+    }
+
+    #[test]
+    fn is_none() {
+        assert!(parse_utc_offset("America/New_York").is_none());
+        assert!(parse_utc_offset("+5").is_none());
+        assert!(parse_utc_offset("+19:00").is_none());
+        assert!(parse_utc_offset("").is_none());
+    }
+}
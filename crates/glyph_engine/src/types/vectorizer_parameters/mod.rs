@@ -1,16 +1,39 @@
 mod field_definition;
+mod filter;
+mod filter_errors;
 mod helper_functions;
+mod model_definition;
+mod model_definition_errors;
+mod validation;
 mod vectorizer_parameters_error;
 
 use crate::types::field_definition_type::FieldDefinitionType;
-use glyphx_core::GlyphxErrorData;
+use crate::types::{FieldType, GlyphChannel, SupportingField, SupportingFieldAggregation};
+use glyphx_core::{ErrorTypeParser, GlyphxErrorData};
 pub use helper_functions::*;
 use serde_json::{json, Value};
 
 pub use field_definition::{
-    DateFieldDefinition, DateGrouping, FieldDefinition, FieldDefinitionCollection,
-    StandardFieldDefinition,
+    AccumulatedFieldDefinition, AccumulatorFieldDefinition, AccumulatorType, BinnedFieldDefinition,
+    BinningMethod, DateFieldDefinition, DateGrouping, DateUnit, FieldDefinition,
+    FieldDefinitionCollection, FormulaExpression, FormulaFieldDefinition, FormulaFunction,
+    FormulaOperator, SortDirection, SortOrder, StandardFieldDefinition, TopNDefinition, Weekday,
 };
+pub use filter::{quote_identifier, quote_literal, ComparisonOperator, Filter, FilterExpression};
+pub use filter_errors::{
+    FromJsonError as FilterFromJsonError, ValidateError as FilterValidateError,
+};
+pub use model_definition::{
+    ModelAccumulatedFieldDefinition, ModelAccumulator, ModelAccumulatorFieldDefinition,
+    ModelBinnedFieldDefinition, ModelBinningMethod, ModelDateFieldDefinition, ModelDateGrouping,
+    ModelDateUnit, ModelDefinition, ModelField, ModelFieldDataType, ModelFieldDefinition,
+    ModelFilter, ModelFilterCondition, ModelFilterExpression, ModelFilterOperator,
+    ModelFormulaFieldDefinition, ModelLiteral, ModelRankAccumulator, ModelSortBy,
+    ModelSortDirection, ModelSortOrder, ModelStandardFieldDefinition, ModelSupportingField,
+    ModelSupportingFieldAggregation, ModelTopN, ModelWeekday,
+};
+pub use model_definition_errors::FromJsonValueError as ModelDefinitionFromJsonValueError;
+pub use validation::ValidationProblem;
 pub use vectorizer_parameters_error::{
     FromJsonStringError, FromJsonValueError, GetFieldDefinitionError, GetFieldDefinitionTypeError,
     GetFieldDefinitionsError,
@@ -23,7 +46,11 @@ pub struct VectorizerParameters {
     pub output_file_prefix: String,
     pub data_table_name: String,
     pub model_hash: String,
-    pub filter: Option<String>,
+    pub filter: Option<Filter>,
+    ///When true, the default, the filter is applied to the vector queries as well as the glyph
+    ///query so that the axes only hold values that survive it.
+    pub filter_vectors: bool,
+    pub include_supporting_fields: bool,
     raw_data: Value,
 }
 
@@ -79,13 +106,55 @@ impl VectorizerParameters {
             return Err(FromJsonValueError::new("model_hash"));
         }
 
+        let allow_raw_filter = match &input["allow_raw_filter"] {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            _ => {
+                return Err(FromJsonValueError::new("allow_raw_filter"));
+            }
+        };
+
+        //Raw SQL is passed straight to Athena, so it has to be asked for explicitly.
         let filter = match &input["filter"] {
             Value::Null => None,
-            Value::String(s) => Some(s.to_string()),
+            Value::String(s) if allow_raw_filter => Some(Filter::Raw(s.to_string())),
+            Value::String(_) => {
+                return Err(FromJsonValueError::raw_filter_not_allowed());
+            }
+            Value::Object(_) => {
+                let expression = FilterExpression::from_json(&input["filter"]);
+                if expression.is_err() {
+                    return Err(FromJsonValueError::from_filter_from_json_error(
+                        expression.err().unwrap(),
+                    ));
+                }
+                Some(Filter::Expression(expression.unwrap()))
+            }
             _ => {
                 return Err(FromJsonValueError::new("filter"));
             }
         };
+
+        let filter_vectors = match &input["filter_vectors"] {
+            Value::Null => true,
+            Value::Bool(b) => *b,
+            _ => {
+                return Err(FromJsonValueError::new("filter_vectors"));
+            }
+        };
+
+        let include_supporting_fields = match &input["include_supporting_fields"] {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            _ => {
+                return Err(FromJsonValueError::new("include_supporting_fields"));
+            }
+        };
+
+        let invalid_values = Self::get_invalid_values(input);
+        if !invalid_values.is_empty() {
+            return Err(FromJsonValueError::invalid_values(invalid_values));
+        }
         Ok(VectorizerParameters {
             workspace_id: workspace_id.as_str().unwrap().to_string(),
             project_id: project_id.as_str().unwrap().to_string(),
@@ -93,10 +162,97 @@ impl VectorizerParameters {
             output_file_prefix: output_file_prefix.as_str().unwrap().to_string(),
             model_hash: model_hash.as_str().unwrap().to_string(),
             filter,
+            filter_vectors,
+            include_supporting_fields,
             raw_data: input.clone(),
         })
     }
 
+    ///Checks the dateGrouping, accumulator and fieldDataType values of every axis, channel and
+    ///supporting field.  Their parsers would otherwise fall back to a default, so a typo would
+    ///quietly build the wrong model.  Every invalid value is returned, not just the first.
+    fn get_invalid_values(input: &Value) -> Vec<Value> {
+        let mut fields = vec![
+            ("xAxis".to_string(), &input["xAxis"]),
+            ("yAxis".to_string(), &input["yAxis"]),
+            ("zAxis".to_string(), &input["zAxis"]),
+        ];
+        for channel in GlyphChannel::all() {
+            let parameter_name = channel.get_parameter_name();
+            fields.push((parameter_name.to_string(), &input[parameter_name]));
+        }
+        if let Some(supporting_fields) = input["supportingFields"].as_array() {
+            for (index, field) in supporting_fields.iter().enumerate() {
+                fields.push((format!("supportingFields[{}]", index), field));
+            }
+        }
+
+        let mut errors = Vec::new();
+        for (path, field) in fields {
+            Self::add_invalid_values(field, &path, &mut errors);
+        }
+        errors
+    }
+
+    fn add_invalid_values(value: &Value, path: &str, errors: &mut Vec<Value>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let path = format!("{}.{}", path, key);
+                    let error_data = match key.as_str() {
+                        "dateGrouping" => DateGrouping::try_from_str(&Self::get_text(value))
+                            .err()
+                            .map(|err| err.get_glyphx_error_data().clone()),
+                        "accumulator" => AccumulatorType::try_from_str(&Self::get_text(value))
+                            .err()
+                            .map(|err| err.get_glyphx_error_data().clone()),
+                        "fieldDataType" => FieldType::try_from_json(value)
+                            .err()
+                            .map(|err| err.get_glyphx_error_data().clone()),
+                        _ => {
+                            Self::add_invalid_values(value, &path, errors);
+                            None
+                        }
+                    };
+                    if let Some(error_data) = error_data {
+                        let mut error = error_data.data.unwrap_or(json!({}));
+                        error["path"] = json!(path);
+                        error["message"] = json!(error_data.message);
+                        errors.push(error);
+                    }
+                }
+            }
+            Value::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    Self::add_invalid_values(value, &format!("{}[{}]", path, index), errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_text(value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            _ => value.to_string(),
+        }
+    }
+
+    ///Returns the model definition that these parameters were parsed from as typed structs.
+    pub fn get_model_definition(
+        &self,
+    ) -> Result<ModelDefinition, ModelDefinitionFromJsonValueError> {
+        ModelDefinition::from_json_value(&self.raw_data)
+    }
+
+    ///Returns the filter that the vector queries are run with, if any.
+    pub fn get_vector_filter(&self) -> Option<Filter> {
+        if !self.filter_vectors {
+            return None;
+        }
+        self.filter.clone()
+    }
+
     pub fn get_supporting_field_names(&self) -> Option<Vec<String>> {
         let mut result = Vec::new();
         let supporting_fields = &self.raw_data["supportingFields"];
@@ -151,6 +307,20 @@ impl VectorizerParameters {
                 }
                 v
             }
+            "glyphcolor" | "glyphsize" | "glyphshape" => {
+                let parameter_name = GlyphChannel::from_parameter_name(clean_field_name)
+                    .unwrap()
+                    .get_parameter_name();
+                let v = &self.raw_data[parameter_name];
+                if v.is_null() {
+                    let message = format!("{} is not defined", parameter_name);
+                    let data = json!({ "field": parameter_name });
+                    return Err(GetFieldDefinitionError::AxisNotDefined(
+                        GlyphxErrorData::new(message, Some(data), None),
+                    ));
+                }
+                v
+            }
             _ => {
                 let supporting_fields = &self.raw_data["supportingFields"];
                 if supporting_fields.is_null() {
@@ -244,6 +414,99 @@ impl VectorizerParameters {
         Ok(field_definition.unwrap())
     }
 
+    ///Returns the field definitions of the glyph channels that have been mapped to a field.  The
+    ///channels are optional, but one that is mapped must be an accumulated field since it is
+    ///calculated over the rows of each glyph.
+    pub fn get_channel_field_definitions(
+        &self,
+    ) -> Result<Vec<(GlyphChannel, FieldDefinition)>, GetFieldDefinitionError> {
+        let mut results = Vec::new();
+        for channel in GlyphChannel::all() {
+            let parameter_name = channel.get_parameter_name();
+            if self.raw_data[parameter_name].is_null() {
+                continue;
+            }
+            let field_definition = self.get_field_definition(parameter_name)?;
+            Self::check_channel_field_definition(parameter_name, &field_definition)?;
+            results.push((channel, field_definition));
+        }
+        Ok(results)
+    }
+
+    ///Returns the supporting fields along with how each is aggregated over the rows of a glyph.
+    ///A supporting field may set an aggregation of first, distinct_list, sum or avg, first is
+    ///used when it does not.  Supporting fields are read per row, so they can not be accumulated.
+    pub fn get_supporting_fields(&self) -> Result<Vec<SupportingField>, GetFieldDefinitionError> {
+        let mut results = Vec::new();
+        for field_name in self.get_supporting_field_names().unwrap_or_default() {
+            let field_definition = self.get_field_definition(&field_name)?;
+            //get_field_definition has already found the field, so this will not fail.
+            let json = self.get_field_json_value(&field_name).unwrap();
+            let aggregation =
+                Self::get_supporting_field_aggregation(json, &field_name, &field_definition)?;
+            results.push(SupportingField {
+                field_definition,
+                aggregation,
+            });
+        }
+        Ok(results)
+    }
+
+    fn check_channel_field_definition(
+        parameter_name: &str,
+        field_definition: &FieldDefinition,
+    ) -> Result<(), GetFieldDefinitionError> {
+        if !field_definition.is_accumulated() {
+            let message = format!("{} must be an accumulated field", parameter_name);
+            let data = json!({ "field": parameter_name });
+            return Err(GetFieldDefinitionError::JsonParsingError(
+                GlyphxErrorData::new(message, Some(data), None),
+            ));
+        }
+        Ok(())
+    }
+
+    fn get_supporting_field_aggregation(
+        json: &Value,
+        field_name: &str,
+        field_definition: &FieldDefinition,
+    ) -> Result<SupportingFieldAggregation, GetFieldDefinitionError> {
+        if field_definition.is_accumulated() {
+            let message = format!("The supporting field {} can not be accumulated", field_name);
+            let data = json!({ "field": field_name });
+            return Err(GetFieldDefinitionError::JsonParsingError(
+                GlyphxErrorData::new(message, Some(data), None),
+            ));
+        }
+        let aggregation = match &json["aggregation"] {
+            Value::Null => Some(SupportingFieldAggregation::First),
+            Value::String(aggregation) => SupportingFieldAggregation::from_string(aggregation),
+            _ => None,
+        };
+        if aggregation.is_none() {
+            let message = format!(
+                "The aggregation {} of the supporting field {} is not defined",
+                json["aggregation"], field_name
+            );
+            let data = json!({ "field": field_name, "aggregation": json["aggregation"] });
+            return Err(GetFieldDefinitionError::JsonParsingError(
+                GlyphxErrorData::new(message, Some(data), None),
+            ));
+        }
+        let aggregation = aggregation.unwrap();
+        if aggregation.is_numeric() && field_definition.get_field_type() == FieldType::String {
+            let message = format!(
+                "The supporting field {} is a string and can not be aggregated with {:?}",
+                field_name, aggregation
+            );
+            let data = json!({ "field": field_name, "aggregation": json["aggregation"] });
+            return Err(GetFieldDefinitionError::JsonParsingError(
+                GlyphxErrorData::new(message, Some(data), None),
+            ));
+        }
+        Ok(aggregation)
+    }
+
     pub fn get_field_definitions(
         &self,
     ) -> Result<FieldDefinitionCollection, GetFieldDefinitionsError> {
@@ -271,6 +534,19 @@ impl VectorizerParameters {
             return Err(err);
         }
         results.add_field_definition("zaxis".to_string(), z_axis.unwrap());
+
+        let channels = match self.get_channel_field_definitions() {
+            Ok(channels) => channels,
+            Err(err) => {
+                return Err(GetFieldDefinitionsError::from_get_field_definition_error(err));
+            }
+        };
+        for (channel, field_definition) in channels {
+            results.add_field_definition(
+                channel.get_parameter_name().to_lowercase(),
+                field_definition,
+            );
+        }
         for supporting_field_name in self.get_supporting_field_names().unwrap() {
             let supporting_field = self.get_field_definition(&supporting_field_name);
             if supporting_field.is_err() {
@@ -293,6 +569,8 @@ impl Default for VectorizerParameters {
             output_file_prefix: "".to_string(),
             model_hash: "".to_string(),
             filter: None,
+            filter_vectors: true,
+            include_supporting_fields: false,
             raw_data: json!({}),
         }
     }
@@ -312,7 +590,8 @@ mod from_json_value {
             "data_table_name": "my_table",
             "output_file_prefix": "test",
             "model_hash" : "test_hash",
-            "filter": "This is a filter"
+            "filter": "This is a filter",
+            "allow_raw_filter": true
         });
 
         let result = VectorizerParameters::from_json_value(&input);
@@ -322,7 +601,10 @@ mod from_json_value {
         assert_eq!(result.project_id, "5678");
         assert_eq!(result.data_table_name, "my_table");
         assert_eq!(result.output_file_prefix, "test");
-        assert_eq!(result.filter.unwrap(), "This is a filter");
+        assert_eq!(
+            result.filter.unwrap(),
+            Filter::Raw("This is a filter".to_string())
+        );
     }
 
     #[test]
@@ -484,6 +766,227 @@ mod from_json_value {
             }
         }
     }
+
+    #[test]
+    fn raw_filter_not_allowed() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "filter": "1 = 1; DROP TABLE my_table"
+        });
+
+        let result = VectorizerParameters::from_json_value(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FromJsonValueError::JsonValidationError(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["fieldName"].as_str().unwrap(), "filter");
+                assert!(error_data.message.contains("allow_raw_filter"));
+            }
+            _ => panic!("Expected JsonValidationError"),
+        }
+    }
+
+    #[test]
+    fn expression_filter() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "filter": {"fieldName": "region", "operator": "=", "value": "east"}
+        });
+
+        let result = VectorizerParameters::from_json_value(&input);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().filter.unwrap(),
+            Filter::Expression(FilterExpression::Comparison {
+                field_name: "region".to_string(),
+                operator: ComparisonOperator::Equal,
+                value: json!("east")
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_expression_filter() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "filter": {"fieldName": "region", "operator": "like", "value": "east"}
+        });
+
+        let result = VectorizerParameters::from_json_value(&input);
+        assert!(result.is_err());
+        match result.err().unwrap() {
+            FromJsonValueError::JsonValidationError(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["fieldName"].as_str().unwrap(), "filter");
+                assert!(error_data.inner_error.is_some());
+            }
+            _ => panic!("Expected JsonValidationError"),
+        }
+    }
+
+    #[test]
+    fn filter_vectors() {
+        let mut input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "filter": {"fieldName": "region", "operator": "isNotNull"}
+        });
+
+        let result = VectorizerParameters::from_json_value(&input).unwrap();
+        assert!(result.filter_vectors);
+        assert_eq!(result.get_vector_filter(), result.filter);
+
+        input["filter_vectors"] = json!(false);
+        let result = VectorizerParameters::from_json_value(&input).unwrap();
+        assert!(!result.filter_vectors);
+        assert!(result.filter.is_some());
+        assert!(result.get_vector_filter().is_none());
+
+        input["filter_vectors"] = json!("yes");
+        let result = VectorizerParameters::from_json_value(&input);
+        match result.err().unwrap() {
+            FromJsonValueError::JsonValidationError(error_data) => {
+                let data = error_data.data.unwrap();
+                assert_eq!(data["fieldName"].as_str().unwrap(), "filter_vectors");
+            }
+            _ => panic!("Expected JsonValidationError"),
+        }
+    }
+
+    #[test]
+    fn include_supporting_fields() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "include_supporting_fields": true
+        });
+
+        let result = VectorizerParameters::from_json_value(&input).unwrap();
+        assert!(result.include_supporting_fields);
+
+        let mut input = input;
+        input["include_supporting_fields"] = json!("yes");
+        let result = VectorizerParameters::from_json_value(&input);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invalid_values() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "xAxis": {
+                "fieldDisplayName": "sold",
+                "fieldDataType": 3,
+                "fieldDefinition": {
+                    "fieldType": "date",
+                    "fieldName": "sold",
+                    "dateGrouping": "qualified_day"
+                }
+            },
+            "zAxis": {
+                "fieldDisplayName": "price",
+                "fieldDataType": 7,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "summ",
+                    "accumulatedFieldDefinition": {
+                        "fieldType": "standard",
+                        "fieldName": "price"
+                    }
+                }
+            },
+            "supportingFields": [{
+                "fieldDisplayName": "region",
+                "fieldDataType": "string",
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "region"
+                }
+            }]
+        });
+
+        let result = VectorizerParameters::from_json_value(&input);
+        match result.err().unwrap() {
+            FromJsonValueError::InvalidValues(error_data) => {
+                let d = error_data.data.unwrap();
+                let errors = d["errors"].as_array().unwrap();
+                let paths: Vec<&str> = errors
+                    .iter()
+                    .map(|error| error["path"].as_str().unwrap())
+                    .collect();
+                assert_eq!(
+                    paths,
+                    vec![
+                        "xAxis.fieldDefinition.dateGrouping",
+                        "zAxis.fieldDataType",
+                        "zAxis.fieldDefinition.accumulator",
+                        "supportingFields[0].fieldDataType",
+                    ]
+                );
+                assert_eq!(errors[0]["value"], "qualified_day");
+                assert!(errors[0]["acceptedValues"].is_array());
+                assert_eq!(errors[2]["value"], "summ");
+                assert_eq!(errors[3]["value"], "string");
+            }
+            _ => panic!("Expected InvalidValues"),
+        }
+    }
+
+    #[test]
+    fn valid_values() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "xAxis": {
+                "fieldDisplayName": "sold",
+                "fieldDataType": 3,
+                "fieldDefinition": {
+                    "fieldType": "date",
+                    "fieldName": "sold",
+                    "dateGrouping": "Qualified_Quarter"
+                }
+            },
+            "zAxis": {
+                "fieldDisplayName": "price",
+                "fieldDataType": 0.0,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "median",
+                    "accumulatedFieldDefinition": {
+                        "fieldType": "standard",
+                        "fieldName": "price"
+                    }
+                }
+            }
+        });
+
+        assert!(VectorizerParameters::from_json_value(&input).is_ok());
+    }
 }
 
 #[cfg(test)]
@@ -892,6 +1395,34 @@ mod get_field_json_value {
         assert!(field_json_value.unwrap().is_object());
     }
 
+    #[test]
+    fn glyph_channel() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "glyphSize": {
+                "fieldDefinition": {
+                    "fieldType": "accumulated"
+                }
+            }
+        });
+
+        let result = VectorizerParameters::from_json_value(&input);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        let field_json_value = result.get_field_json_value("glyphSize");
+        assert!(field_json_value.is_ok());
+        assert!(field_json_value.unwrap().is_object());
+        let field_json_value = result.get_field_json_value("glyphColor");
+        match field_json_value.err().unwrap() {
+            GetFieldDefinitionError::AxisNotDefined(_) => {}
+            _ => panic!("Expected AxisNotDefined"),
+        }
+    }
+
     #[test]
     fn supporting_field() {
         let input = json!({
@@ -1121,7 +1652,9 @@ mod get_field_definition {
                 field_definition,
                 field_query,
                 raw_query,
+                sort_order,
             } => {
+                assert!(sort_order.is_default());
                 assert_eq!(field_display_name, "field1");
                 match field_data_type {
                     FieldType::String => assert!(true),
@@ -1176,7 +1709,9 @@ mod get_field_definition {
                 field_definition,
                 field_query,
                 raw_query,
+                sort_order,
             } => {
+                assert!(sort_order.is_default());
                 assert_eq!(field_display_name, "field1");
                 match field_data_type {
                     FieldType::String => assert!(true),
@@ -1242,6 +1777,192 @@ mod get_field_definition {
     }
 }
 
+#[cfg(test)]
+mod get_channel_field_definitions {
+    use super::*;
+    use serde_json::json;
+
+    fn get_accumulated_field(name: &str) -> Value {
+        json!({
+            "fieldDisplayName": name,
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "sum",
+                "accumulatedFieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": name
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn is_ok() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "glyphColor": get_accumulated_field("field1"),
+            "glyphShape": get_accumulated_field("field2")
+        });
+
+        let parameters = VectorizerParameters::from_json_value(&input).unwrap();
+        let channels = parameters.get_channel_field_definitions().unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].0, GlyphChannel::Color);
+        assert_eq!(channels[0].1.get_field_display_name(), "field1");
+        assert_eq!(channels[1].0, GlyphChannel::Shape);
+        assert_eq!(channels[1].1.get_field_display_name(), "field2");
+    }
+
+    #[test]
+    fn no_channels() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash"
+        });
+
+        let parameters = VectorizerParameters::from_json_value(&input).unwrap();
+        let channels = parameters.get_channel_field_definitions().unwrap();
+        assert!(channels.is_empty());
+    }
+
+    #[test]
+    fn not_accumulated() {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "glyphSize": {
+                "fieldDisplayName": "field1",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "field1"
+                }
+            }
+        });
+
+        let parameters = VectorizerParameters::from_json_value(&input).unwrap();
+        let result = parameters.get_channel_field_definitions();
+        match result.err().unwrap() {
+            GetFieldDefinitionError::JsonParsingError(_) => {}
+            _ => panic!("Expected JsonParsingError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod get_supporting_fields {
+    use super::*;
+    use serde_json::json;
+
+    fn get_parameters(supporting_fields: Value) -> VectorizerParameters {
+        let input = json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "include_supporting_fields": true,
+            "supportingFields": supporting_fields
+        });
+        VectorizerParameters::from_json_value(&input).unwrap()
+    }
+
+    fn get_standard_field(name: &str, field_data_type: usize) -> Value {
+        json!({
+            "fieldDisplayName": name,
+            "fieldDataType": field_data_type,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": name
+            }
+        })
+    }
+
+    #[test]
+    fn is_ok() {
+        let mut region = get_standard_field("region", 1);
+        region["aggregation"] = json!("distinct_list");
+        let parameters = get_parameters(json!([get_standard_field("customer", 1), region]));
+        let supporting_fields = parameters.get_supporting_fields().unwrap();
+        assert_eq!(supporting_fields.len(), 2);
+        assert_eq!(
+            supporting_fields[0]
+                .field_definition
+                .get_field_display_name(),
+            "customer"
+        );
+        assert_eq!(
+            supporting_fields[0].aggregation,
+            SupportingFieldAggregation::First
+        );
+        assert_eq!(
+            supporting_fields[1].aggregation,
+            SupportingFieldAggregation::DistinctList
+        );
+        assert_eq!(supporting_fields[1].get_column_name(), "supporting_region");
+    }
+
+    #[test]
+    fn no_supporting_fields() {
+        let parameters = get_parameters(Value::Null);
+        let supporting_fields = parameters.get_supporting_fields().unwrap();
+        assert!(supporting_fields.is_empty());
+    }
+
+    #[test]
+    fn invalid_aggregation() {
+        let mut field = get_standard_field("units", 0);
+        field["aggregation"] = json!("median");
+        let parameters = get_parameters(json!([field]));
+        match parameters.get_supporting_fields().err().unwrap() {
+            GetFieldDefinitionError::JsonParsingError(_) => {}
+            _ => panic!("Expected JsonParsingError"),
+        }
+    }
+
+    #[test]
+    fn sum_of_a_string() {
+        let mut field = get_standard_field("region", 1);
+        field["aggregation"] = json!("sum");
+        let parameters = get_parameters(json!([field]));
+        match parameters.get_supporting_fields().err().unwrap() {
+            GetFieldDefinitionError::JsonParsingError(_) => {}
+            _ => panic!("Expected JsonParsingError"),
+        }
+    }
+
+    #[test]
+    fn is_accumulated() {
+        let parameters = get_parameters(json!([{
+            "fieldDisplayName": "units",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "accumulated",
+                "accumulator": "sum",
+                "accumulatedFieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "units"
+                }
+            }
+        }]));
+        match parameters.get_supporting_fields().err().unwrap() {
+            GetFieldDefinitionError::JsonParsingError(_) => {}
+            _ => panic!("Expected JsonParsingError"),
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod get_field_definitions {
     use super::*;
@@ -1482,3 +2203,68 @@ pub mod get_field_definitions {
         }
     }
 }
+
+#[cfg(test)]
+mod get_model_definition {
+    use super::*;
+    use serde_json::json;
+
+    fn get_field(name: &str) -> Value {
+        json!({
+            "fieldDisplayName": name,
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "standard",
+                "fieldName": name
+            }
+        })
+    }
+
+    fn get_input() -> Value {
+        json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "xAxis" : get_field("field1"),
+            "yAxis" : get_field("field2"),
+            "zAxis" : {
+                "fieldDisplayName": "field3",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "sum",
+                    "accumulatedFieldDefinition": {
+                        "fieldType": "standard",
+                        "fieldName": "field3"
+                    }
+                }
+            },
+        })
+    }
+
+    #[test]
+    fn is_ok() {
+        let parameters = VectorizerParameters::from_json_value(&get_input()).unwrap();
+        let result = parameters.get_model_definition().unwrap();
+        assert_eq!(result.workspace_id, "1234");
+        assert_eq!(result.x_axis.field_display_name, "field1");
+        assert!(result.filter.is_none());
+        assert!(result.supporting_fields.is_none());
+    }
+
+    #[test]
+    fn missing_axis() {
+        let mut input = get_input();
+        input.as_object_mut().unwrap().remove("yAxis");
+        let parameters = VectorizerParameters::from_json_value(&input).unwrap();
+        let result = parameters.get_model_definition();
+        match result {
+            Err(ModelDefinitionFromJsonValueError::InvalidModelDefinition(error_data)) => {
+                assert!(error_data.message.contains("yAxis"));
+            }
+            _ => panic!("Expected InvalidModelDefinition"),
+        }
+    }
+}
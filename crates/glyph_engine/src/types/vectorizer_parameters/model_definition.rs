@@ -0,0 +1,679 @@
+use super::model_definition_errors::FromJsonValueError;
+use crate::types::field_type::ACCEPTED_VALUES as FIELD_TYPE_VALUES;
+use crate::types::FieldType;
+use glyphx_core::ErrorTypeParser;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Number, Value};
+
+///The model definition that the glyph engine is run with, as typed structs.  The JSON schema of
+///these structs, see ModelDefinition::get_json_schema, is what the TypeScript side validates its
+///requests against.  The schema only lists the canonical spellings of each value, the engine's
+///own parsers also accept them in any case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelDefinition {
+    pub workspace_id: String,
+    pub project_id: String,
+    pub data_table_name: String,
+    pub output_file_prefix: String,
+    pub model_hash: String,
+    ///A filter expression, or raw SQL when allow_raw_filter is true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ModelFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_raw_filter: Option<bool>,
+    ///Defaults to true, applying the filter to the axis vectors as well as the glyphs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_vectors: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_supporting_fields: Option<bool>,
+    #[serde(rename = "xAxis")]
+    pub x_axis: ModelField,
+    #[serde(rename = "yAxis")]
+    pub y_axis: ModelField,
+    ///Must be an accumulated field.
+    #[serde(rename = "zAxis")]
+    pub z_axis: ModelField,
+    ///Must be an accumulated field.
+    #[serde(
+        rename = "glyphColor",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub glyph_color: Option<ModelField>,
+    ///Must be an accumulated field.
+    #[serde(rename = "glyphSize", default, skip_serializing_if = "Option::is_none")]
+    pub glyph_size: Option<ModelField>,
+    ///Must be an accumulated field.
+    #[serde(
+        rename = "glyphShape",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub glyph_shape: Option<ModelField>,
+    #[serde(
+        rename = "supportingFields",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub supporting_fields: Option<Vec<ModelSupportingField>>,
+}
+
+impl ModelDefinition {
+    pub fn from_json_value(input: &Value) -> Result<Self, FromJsonValueError> {
+        let result = serde_path_to_error::deserialize(input);
+        if result.is_err() {
+            let err = result.err().unwrap();
+            return Err(FromJsonValueError::from_serde_error(err));
+        }
+        Ok(result.unwrap())
+    }
+
+    ///Returns the JSON schema of a model definition.
+    pub fn get_json_schema() -> Value {
+        let schema = schema_for!(ModelDefinition);
+        //A RootSchema is plain data, so it always serializes.
+        serde_json::to_value(schema).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelField {
+    pub field_display_name: String,
+    pub field_data_type: ModelFieldDataType,
+    pub field_definition: ModelFieldDefinition,
+    ///Accumulated fields can not be sorted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_order: Option<ModelSortOrder>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelSupportingField {
+    #[serde(flatten)]
+    pub field: ModelField,
+    ///Defaults to first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation: Option<ModelSupportingFieldAggregation>,
+}
+
+///The fieldDataType of a field, which is sent as a number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Value", into = "usize")]
+pub enum ModelFieldDataType {
+    Number,
+    String,
+    Integer,
+    Date,
+}
+
+impl TryFrom<Value> for ModelFieldDataType {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match FieldType::try_from_json(&value) {
+            Ok(FieldType::Number) => Ok(ModelFieldDataType::Number),
+            Ok(FieldType::String) => Ok(ModelFieldDataType::String),
+            Ok(FieldType::Integer) => Ok(ModelFieldDataType::Integer),
+            Ok(FieldType::Date) => Ok(ModelFieldDataType::Date),
+            Ok(FieldType::Unknown) => Err(format!("The fieldDataType {} is not defined", value)),
+            Err(err) => Err(err.get_glyphx_error_data().message.clone()),
+        }
+    }
+}
+
+impl From<ModelFieldDataType> for usize {
+    fn from(value: ModelFieldDataType) -> Self {
+        match value {
+            ModelFieldDataType::Number => 0,
+            ModelFieldDataType::String => 1,
+            ModelFieldDataType::Integer => 2,
+            ModelFieldDataType::Date => 3,
+        }
+    }
+}
+
+impl JsonSchema for ModelFieldDataType {
+    fn schema_name() -> String {
+        "ModelFieldDataType".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let names = FIELD_TYPE_VALUES
+            .iter()
+            .map(|(value, name)| format!("{} = {}", value, name))
+            .collect::<Vec<String>>()
+            .join(", ");
+        SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            enum_values: Some(
+                FIELD_TYPE_VALUES
+                    .iter()
+                    .map(|(value, _)| json!(value))
+                    .collect(),
+            ),
+            metadata: Some(Box::new(Metadata {
+                description: Some(names),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "fieldType", rename_all = "camelCase")]
+pub enum ModelFieldDefinition {
+    Standard(ModelStandardFieldDefinition),
+    Date(ModelDateFieldDefinition),
+    Formula(ModelFormulaFieldDefinition),
+    Accumulated(ModelAccumulatorFieldDefinition),
+    Binned(ModelBinnedFieldDefinition),
+}
+
+///The field that an accumulator is applied to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "fieldType", rename_all = "camelCase")]
+pub enum ModelAccumulatedFieldDefinition {
+    Standard(ModelStandardFieldDefinition),
+    Date(ModelDateFieldDefinition),
+    Formula(ModelFormulaFieldDefinition),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStandardFieldDefinition {
+    pub field_name: String,
+    #[serde(rename = "topN", default, skip_serializing_if = "Option::is_none")]
+    pub top_n: Option<ModelTopN>,
+}
+
+///Keeps the count values with the highest rank and plots every other value as other_label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTopN {
+    #[schemars(range(min = 1))]
+    pub count: u64,
+    ///Defaults to count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accumulator: Option<ModelRankAccumulator>,
+    ///Required by every accumulator but count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank_by_field: Option<String>,
+    ///Defaults to Other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other_label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDateFieldDefinition {
+    pub field_name: String,
+    pub date_grouping: ModelDateGrouping,
+    ///Defaults to milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_unit: Option<ModelDateUnit>,
+    ///An IANA time zone, i.e. America/New_York.  Defaults to UTC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    ///Defaults to monday.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub week_start: Option<ModelWeekday>,
+    ///The size of a minute_bucket in minutes.  Defaults to 15.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1, max = 1440))]
+    pub minute_bucket: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelFormulaFieldDefinition {
+    ///An arithmetic expression over the columns of the table, i.e. "revenue - cost".
+    pub formula: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelAccumulatorFieldDefinition {
+    pub accumulator: ModelAccumulator,
+    pub accumulated_field_definition: ModelAccumulatedFieldDefinition,
+    ///Used by percentile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<f64>,
+    ///Used by first and last.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_by_field: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBinnedFieldDefinition {
+    pub field_name: String,
+    pub binning_method: ModelBinningMethod,
+    ///Required by every binning method but explicit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub bin_count: Option<u64>,
+    ///Required by explicit, in ascending order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edges: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSortOrder {
+    ///Defaults to value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<ModelSortBy>,
+    ///Defaults to asc.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<ModelSortDirection>,
+    ///Used by accumulator, defaults to count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accumulator: Option<ModelRankAccumulator>,
+    ///Used by accumulator, required by every accumulator but count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_name: Option<String>,
+    ///Used by explicit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<ModelLiteral>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ModelFilter {
+    Expression(ModelFilterExpression),
+    Raw(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ModelFilterExpression {
+    And { and: Vec<ModelFilterExpression> },
+    Or { or: Vec<ModelFilterExpression> },
+    Not { not: Box<ModelFilterExpression> },
+    Condition(ModelFilterCondition),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFilterCondition {
+    pub field_name: String,
+    pub operator: ModelFilterOperator,
+    ///Used by the comparison operators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<ModelLiteral>,
+    ///Used by in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<ModelLiteral>>,
+    ///Used by between, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<ModelLiteral>,
+    ///Used by between, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<ModelLiteral>,
+    ///Used by dateRange, epoch milliseconds or an ISO 8601 date, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<ModelLiteral>,
+    ///Used by dateRange, epoch milliseconds or an ISO 8601 date, exclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<ModelLiteral>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ModelLiteral {
+    Text(String),
+    Number(Number),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ModelFilterOperator {
+    #[serde(rename = "=", alias = "==")]
+    Equal,
+    #[serde(rename = "!=", alias = "<>")]
+    NotEqual,
+    #[serde(rename = "<")]
+    LessThan,
+    #[serde(rename = "<=")]
+    LessThanOrEqual,
+    #[serde(rename = ">")]
+    GreaterThan,
+    #[serde(rename = ">=")]
+    GreaterThanOrEqual,
+    #[serde(rename = "in")]
+    In,
+    #[serde(rename = "between")]
+    Between,
+    #[serde(rename = "dateRange", alias = "daterange")]
+    DateRange,
+    #[serde(rename = "isNull", alias = "isnull")]
+    IsNull,
+    #[serde(rename = "isNotNull", alias = "isnotnull")]
+    IsNotNull,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelDateGrouping {
+    QualifiedDayOfYear,
+    DayOfYear,
+    QualifiedDayOfMonth,
+    YearDayOfMonth,
+    MonthDayOfMonth,
+    DayOfMonth,
+    QualifiedDayOfWeek,
+    DayOfWeek,
+    QualifiedWeekOfYear,
+    WeekOfYear,
+    QualifiedMonth,
+    #[serde(alias = "month")]
+    MonthOfYear,
+    Year,
+    QualifiedQuarter,
+    Quarter,
+    YearOfWeek,
+    #[serde(alias = "hour")]
+    HourOfDay,
+    QualifiedHour,
+    MinuteBucket,
+    WeekStarting,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelDateUnit {
+    #[serde(alias = "millis", alias = "ms")]
+    Milliseconds,
+    #[serde(alias = "s")]
+    Seconds,
+    #[serde(alias = "iso_string", alias = "iso8601")]
+    Iso,
+    Timestamp,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelWeekday {
+    #[serde(alias = "mon")]
+    Monday,
+    #[serde(alias = "tue")]
+    Tuesday,
+    #[serde(alias = "wed")]
+    Wednesday,
+    #[serde(alias = "thu")]
+    Thursday,
+    #[serde(alias = "fri")]
+    Friday,
+    #[serde(alias = "sat")]
+    Saturday,
+    #[serde(alias = "sun")]
+    Sunday,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelAccumulator {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Count,
+    CountDistinct,
+    Median,
+    Percentile,
+    Stddev,
+    Variance,
+    First,
+    Last,
+}
+
+///The accumulators that values can be ranked by, for topN and sortOrder.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelRankAccumulator {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelBinningMethod {
+    EqualWidth,
+    #[serde(alias = "equal_frequency")]
+    Quantile,
+    Explicit,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSortBy {
+    Value,
+    Accumulator,
+    Explicit,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ModelSortDirection {
+    #[serde(rename = "asc", alias = "ascending")]
+    Ascending,
+    #[serde(rename = "desc", alias = "descending")]
+    Descending,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSupportingFieldAggregation {
+    First,
+    DistinctList,
+    Sum,
+    Avg,
+}
+
+#[cfg(test)]
+mod from_json_value {
+    use super::*;
+
+    pub fn get_input() -> Value {
+        json!({
+            "workspace_id": "workspace",
+            "project_id": "project",
+            "data_table_name": "sales",
+            "output_file_prefix": "prefix",
+            "model_hash": "hash",
+            "filter": {"and": [
+                {"fieldName": "region", "operator": "in", "values": ["east", "west"]},
+                {"not": {"fieldName": "units", "operator": "isNull"}}
+            ]},
+            "xAxis": {
+                "fieldDisplayName": "region",
+                "fieldDataType": 1,
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "region",
+                    "topN": {"count": 5, "accumulator": "sum", "rankByField": "units"}
+                },
+                "sortOrder": {"sortBy": "accumulator", "accumulator": "count", "direction": "desc"}
+            },
+            "yAxis": {
+                "fieldDisplayName": "sold",
+                "fieldDataType": 3,
+                "fieldDefinition": {
+                    "fieldType": "date",
+                    "fieldName": "sold",
+                    "dateGrouping": "qualified_quarter",
+                    "timeZone": "America/New_York"
+                }
+            },
+            "zAxis": {
+                "fieldDisplayName": "units",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "percentile",
+                    "percentile": 0.9,
+                    "accumulatedFieldDefinition": {"fieldType": "standard", "fieldName": "units"}
+                }
+            },
+            "glyphColor": {
+                "fieldDisplayName": "margin",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "avg",
+                    "accumulatedFieldDefinition": {"fieldType": "formula", "formula": "revenue - cost"}
+                }
+            },
+            "supportingFields": [{
+                "fieldDisplayName": "price",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "binned",
+                    "fieldName": "price",
+                    "binningMethod": "equal_width",
+                    "binCount": 10
+                },
+                "aggregation": "distinct_list"
+            }]
+        })
+    }
+
+    #[test]
+    fn is_ok() {
+        let result = ModelDefinition::from_json_value(&get_input()).unwrap();
+        assert_eq!(result.data_table_name, "sales");
+        assert_eq!(result.x_axis.field_data_type, ModelFieldDataType::String);
+        match &result.x_axis.field_definition {
+            ModelFieldDefinition::Standard(field_definition) => {
+                assert_eq!(field_definition.top_n.as_ref().unwrap().count, 5);
+            }
+            _ => panic!("Expected Standard"),
+        }
+        match &result.y_axis.field_definition {
+            ModelFieldDefinition::Date(field_definition) => {
+                assert_eq!(
+                    field_definition.date_grouping,
+                    ModelDateGrouping::QualifiedQuarter
+                );
+            }
+            _ => panic!("Expected Date"),
+        }
+        match &result.z_axis.field_definition {
+            ModelFieldDefinition::Accumulated(field_definition) => {
+                assert_eq!(field_definition.accumulator, ModelAccumulator::Percentile);
+                assert_eq!(field_definition.percentile, Some(0.9));
+            }
+            _ => panic!("Expected Accumulated"),
+        }
+        assert!(result.glyph_color.is_some());
+        assert!(result.glyph_size.is_none());
+        let supporting_fields = result.supporting_fields.as_ref().unwrap();
+        assert_eq!(supporting_fields[0].field.field_display_name, "price");
+        assert_eq!(
+            supporting_fields[0].aggregation,
+            Some(ModelSupportingFieldAggregation::DistinctList)
+        );
+        match result.filter.as_ref().unwrap() {
+            ModelFilter::Expression(ModelFilterExpression::And { and }) => {
+                assert_eq!(and.len(), 2)
+            }
+            _ => panic!("Expected And"),
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let input = get_input();
+        let result = ModelDefinition::from_json_value(&input).unwrap();
+        let output = serde_json::to_value(&result).unwrap();
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn raw_filter() {
+        let mut input = get_input();
+        input["filter"] = json!("region = 'east'");
+        let result = ModelDefinition::from_json_value(&input).unwrap();
+        assert_eq!(
+            result.filter,
+            Some(ModelFilter::Raw("region = 'east'".to_string()))
+        );
+    }
+
+    #[test]
+    fn missing_axis() {
+        let mut input = get_input();
+        input.as_object_mut().unwrap().remove("zAxis");
+        let result = ModelDefinition::from_json_value(&input);
+        match result {
+            Err(FromJsonValueError::InvalidModelDefinition(error_data)) => {
+                assert!(error_data.message.contains("zAxis"));
+            }
+            _ => panic!("Expected InvalidModelDefinition"),
+        }
+    }
+
+    #[test]
+    fn invalid_field_data_type() {
+        let mut input = get_input();
+        input["supportingFields"][0]["fieldDataType"] = json!(7);
+        let result = ModelDefinition::from_json_value(&input);
+        match result {
+            Err(FromJsonValueError::InvalidModelDefinition(error_data)) => {
+                assert_eq!(error_data.data.unwrap()["path"], "supportingFields[0]");
+            }
+            _ => panic!("Expected InvalidModelDefinition"),
+        }
+    }
+
+    #[test]
+    fn invalid_date_grouping() {
+        let mut input = get_input();
+        input["yAxis"]["fieldDefinition"]["dateGrouping"] = json!("fortnight");
+        let result = ModelDefinition::from_json_value(&input);
+        match result {
+            Err(FromJsonValueError::InvalidModelDefinition(error_data)) => {
+                assert!(error_data.message.contains("fortnight"));
+                assert_eq!(error_data.data.unwrap()["path"], "yAxis.fieldDefinition");
+            }
+            _ => panic!("Expected InvalidModelDefinition"),
+        }
+    }
+
+    #[test]
+    fn unknown_field_type() {
+        let mut input = get_input();
+        input["xAxis"]["fieldDefinition"]["fieldType"] = json!("invalid");
+        let result = ModelDefinition::from_json_value(&input);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod get_json_schema {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let schema = ModelDefinition::get_json_schema();
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&json!("xAxis")));
+        assert!(required.contains(&json!("model_hash")));
+        assert!(!required.contains(&json!("filter")));
+        assert!(schema["properties"]["glyphColor"].is_object());
+
+        let definitions = &schema["definitions"];
+        let date_groupings = definitions["ModelDateGrouping"]["enum"].as_array().unwrap();
+        assert!(date_groupings.contains(&json!("qualified_quarter")));
+        assert_eq!(
+            definitions["ModelFieldDataType"]["enum"],
+            json!([0, 1, 2, 3])
+        );
+        assert!(definitions["ModelFieldDefinition"]["oneOf"].is_array());
+    }
+}
@@ -0,0 +1,51 @@
+use glyphx_core::GlyphxError;
+use glyphx_core::GlyphxErrorData;
+use serde_json::json;
+
+#[derive(Debug, Clone, GlyphxError)]
+#[error_definition("ModelDefinition")]
+pub enum FromJsonValueError {
+    InvalidModelDefinition(GlyphxErrorData),
+}
+
+impl FromJsonValueError {
+    pub fn from_serde_error(input: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = input.path().to_string();
+        let message = input.inner().to_string();
+        let data = json!({ "path": path });
+        Self::InvalidModelDefinition(GlyphxErrorData::new(message, Some(data), None))
+    }
+}
+
+#[cfg(test)]
+mod from_serde_error {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Outer {
+        inner: Vec<Inner>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Inner {
+        value: u32,
+    }
+
+    #[test]
+    fn is_ok() {
+        let input = json!({ "inner": [{ "value": 1 }, { "value": "two" }] });
+        let err = serde_path_to_error::deserialize::<_, Outer>(input).unwrap_err();
+
+        let result = FromJsonValueError::from_serde_error(err);
+        match result {
+            FromJsonValueError::InvalidModelDefinition(error_data) => {
+                assert!(error_data.message.contains("invalid type"));
+                assert_eq!(error_data.data.unwrap()["path"], "inner[1].value");
+                assert!(error_data.inner_error.is_none());
+            }
+        }
+    }
+}
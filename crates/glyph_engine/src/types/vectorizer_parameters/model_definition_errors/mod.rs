@@ -0,0 +1,3 @@
+mod from_json_value_error;
+
+pub use from_json_value_error::FromJsonValueError;
@@ -0,0 +1,518 @@
+use super::{AccumulatedFieldDefinition, FieldDefinition, FilterExpression, VectorizerParameters};
+use crate::types::{FieldType, GlyphChannel};
+use glyphx_core::aws::athena_manager::{ColumnDataType, ColumnDescription};
+use glyphx_core::{ErrorTypeParser, GlyphxErrorData};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+///A problem that validate found in a model definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationProblem {
+    ///The JSON path of the value with the problem, i.e. xAxis.fieldDefinition.dateGrouping or
+    ///supportingFields[0].
+    pub path: String,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl ValidationProblem {
+    pub fn new(path: &str, message: &str, data: Option<Value>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.to_string(),
+            data,
+        }
+    }
+
+    fn from_glyphx_error_data(path: &str, error_data: &GlyphxErrorData) -> Self {
+        Self::new(path, &error_data.message, error_data.data.clone())
+    }
+}
+
+///The keys that every model definition must hold a string for.
+const REQUIRED_KEYS: [&str; 5] = [
+    "workspace_id",
+    "project_id",
+    "data_table_name",
+    "output_file_prefix",
+    "model_hash",
+];
+
+///The keys that, when they are set, must be true or false.
+const FLAG_KEYS: [&str; 3] = [
+    "allow_raw_filter",
+    "filter_vectors",
+    "include_supporting_fields",
+];
+
+impl VectorizerParameters {
+    ///Checks the whole model definition and returns every problem that it finds, where
+    ///from_json_value stops at the first one and the field definitions are not built until the
+    ///model is processed.  When the columns of the data table are supplied, the columns that the
+    ///fields and the filter read are checked against them as well.  An empty result means that
+    ///the model definition is valid.
+    pub fn validate(
+        input: &Value,
+        columns: Option<&[ColumnDescription]>,
+    ) -> Vec<ValidationProblem> {
+        let mut problems = Vec::new();
+        if !input.is_object() {
+            problems.push(ValidationProblem::new(
+                "",
+                "The model definition must be a JSON object",
+                None,
+            ));
+            return problems;
+        }
+
+        for key in REQUIRED_KEYS {
+            if !input[key].is_string() {
+                let message = format!("{} must be a string", key);
+                let data = json!({ "value": input[key] });
+                problems.push(ValidationProblem::new(key, &message, Some(data)));
+            }
+        }
+        for key in FLAG_KEYS {
+            if !input[key].is_null() && !input[key].is_boolean() {
+                let message = format!("{} must be true or false", key);
+                let data = json!({ "value": input[key] });
+                problems.push(ValidationProblem::new(key, &message, Some(data)));
+            }
+        }
+        Self::validate_filter(input, columns, &mut problems);
+
+        for mut error in Self::get_invalid_values(input) {
+            let path = error["path"].as_str().unwrap_or_default().to_string();
+            let message = error["message"].as_str().unwrap_or_default().to_string();
+            let data = error.as_object_mut().unwrap();
+            data.remove("path");
+            data.remove("message");
+            problems.push(ValidationProblem::new(&path, &message, Some(error)));
+        }
+
+        for axis in ["xAxis", "yAxis", "zAxis"] {
+            if input[axis].is_null() {
+                let message = format!("{} is not defined", axis);
+                problems.push(ValidationProblem::new(axis, &message, None));
+                continue;
+            }
+            Self::validate_field(axis, &input[axis], columns, &mut problems);
+        }
+
+        for channel in GlyphChannel::all() {
+            let parameter_name = channel.get_parameter_name();
+            if input[parameter_name].is_null() {
+                continue;
+            }
+            let field_definition = Self::validate_field(
+                parameter_name,
+                &input[parameter_name],
+                columns,
+                &mut problems,
+            );
+            if let Some(field_definition) = field_definition {
+                let result =
+                    Self::check_channel_field_definition(parameter_name, &field_definition);
+                if let Err(err) = result {
+                    problems.push(ValidationProblem::from_glyphx_error_data(
+                        parameter_name,
+                        err.get_glyphx_error_data(),
+                    ));
+                }
+            }
+        }
+
+        match &input["supportingFields"] {
+            Value::Null => {}
+            Value::Array(supporting_fields) => {
+                for (index, field) in supporting_fields.iter().enumerate() {
+                    let path = format!("supportingFields[{}]", index);
+                    let field_definition =
+                        Self::validate_field(&path, field, columns, &mut problems);
+                    if let Some(field_definition) = field_definition {
+                        let result = Self::get_supporting_field_aggregation(
+                            field,
+                            field_definition.get_field_display_name(),
+                            &field_definition,
+                        );
+                        if let Err(err) = result {
+                            problems.push(ValidationProblem::from_glyphx_error_data(
+                                &path,
+                                err.get_glyphx_error_data(),
+                            ));
+                        }
+                    }
+                }
+            }
+            value => {
+                let data = json!({ "value": value });
+                problems.push(ValidationProblem::new(
+                    "supportingFields",
+                    "supportingFields must be an array",
+                    Some(data),
+                ));
+            }
+        }
+
+        problems
+    }
+
+    fn validate_filter(
+        input: &Value,
+        columns: Option<&[ColumnDescription]>,
+        problems: &mut Vec<ValidationProblem>,
+    ) {
+        let allow_raw_filter = input["allow_raw_filter"].as_bool().unwrap_or(false);
+        let expression = match &input["filter"] {
+            Value::Null => return,
+            //Raw filters cannot be checked without running them.
+            Value::String(_) if allow_raw_filter => return,
+            Value::String(_) => {
+                problems.push(ValidationProblem::new(
+                    "filter",
+                    "A raw SQL filter can only be used when allow_raw_filter is true",
+                    None,
+                ));
+                return;
+            }
+            Value::Object(_) => FilterExpression::from_json(&input["filter"]),
+            value => {
+                let data = json!({ "value": value });
+                problems.push(ValidationProblem::new(
+                    "filter",
+                    "The filter must be a filter expression",
+                    Some(data),
+                ));
+                return;
+            }
+        };
+        if expression.is_err() {
+            let err = expression.err().unwrap();
+            problems.push(ValidationProblem::from_glyphx_error_data(
+                "filter",
+                err.get_glyphx_error_data(),
+            ));
+            return;
+        }
+        if let Some(columns) = columns {
+            let result = expression.unwrap().validate(columns);
+            if result.is_err() {
+                let err = result.err().unwrap();
+                problems.push(ValidationProblem::from_glyphx_error_data(
+                    "filter",
+                    err.get_glyphx_error_data(),
+                ));
+            }
+        }
+    }
+
+    ///Builds the field definition at path, adding a problem for anything that is wrong with it.
+    ///Returns None when the field definition could not be built.
+    fn validate_field(
+        path: &str,
+        field: &Value,
+        columns: Option<&[ColumnDescription]>,
+        problems: &mut Vec<ValidationProblem>,
+    ) -> Option<FieldDefinition> {
+        //An invalid value inside of the field has already been reported, building the field
+        //would only report it again.
+        let prefix = format!("{}.", path);
+        if problems
+            .iter()
+            .any(|problem| problem.path.starts_with(&prefix))
+        {
+            return None;
+        }
+        if !field.is_object() {
+            let message = format!("{} must be a JSON object", path);
+            problems.push(ValidationProblem::new(path, &message, None));
+            return None;
+        }
+        let field_definition = FieldDefinition::from_json(field);
+        if field_definition.is_err() {
+            let err = field_definition.err().unwrap();
+            problems.push(ValidationProblem::from_glyphx_error_data(
+                path,
+                err.get_glyphx_error_data(),
+            ));
+            return None;
+        }
+        let field_definition = field_definition.unwrap();
+        Self::validate_field_types(path, &field_definition, columns, problems);
+        Some(field_definition)
+    }
+
+    fn validate_field_types(
+        path: &str,
+        field_definition: &FieldDefinition,
+        columns: Option<&[ColumnDescription]>,
+        problems: &mut Vec<ValidationProblem>,
+    ) {
+        let definition_path = format!("{}.fieldDefinition", path);
+        if let Some(columns) = columns {
+            for column_name in field_definition.get_column_names() {
+                if find_column(&column_name, columns).is_none() {
+                    let message = format!("The column {} does not exist in the table", column_name);
+                    let column_names: Vec<&str> =
+                        columns.iter().map(|column| column.name.as_str()).collect();
+                    let data = json!({ "fieldName": column_name, "columns": column_names });
+                    problems.push(ValidationProblem::new(
+                        &definition_path,
+                        &message,
+                        Some(data),
+                    ));
+                }
+            }
+        }
+
+        if let Some(accumulator) = field_definition.get_accumulator_field_definition() {
+            let accumulator_type = accumulator.accumulator_type;
+            //The column type is known when we have the columns, otherwise we go by the
+            //fieldDataType of the field.
+            let is_string = match (columns, &accumulator.accumulated_field_definition) {
+                (Some(columns), AccumulatedFieldDefinition::Standard(standard)) => {
+                    is_string_column(&standard.field_name, columns)
+                }
+                _ => field_definition.get_field_type() == FieldType::String,
+            };
+            if accumulator_type.is_numeric() && is_string {
+                let message = format!(
+                    "The {:?} accumulator can not be applied to the string field {}",
+                    accumulator_type,
+                    field_definition.get_field_display_name()
+                );
+                let data = json!({ "accumulator": format!("{:?}", accumulator_type) });
+                problems.push(ValidationProblem::new(
+                    &format!("{}.accumulator", definition_path),
+                    &message,
+                    Some(data),
+                ));
+            }
+        }
+
+        if let (Some(binned), Some(columns)) =
+            (field_definition.get_binned_field_definition(), columns)
+        {
+            if is_string_column(&binned.field_name, columns) {
+                let message = format!(
+                    "The column {} is a string and can not be binned",
+                    binned.field_name
+                );
+                let data = json!({ "fieldName": binned.field_name });
+                problems.push(ValidationProblem::new(
+                    &definition_path,
+                    &message,
+                    Some(data),
+                ));
+            }
+        }
+    }
+}
+
+//Athena column names are case insensitive.
+fn find_column<'a>(
+    column_name: &str,
+    columns: &'a [ColumnDescription],
+) -> Option<&'a ColumnDescription> {
+    columns
+        .iter()
+        .find(|column| column.name.eq_ignore_ascii_case(column_name))
+}
+
+fn is_string_column(column_name: &str, columns: &[ColumnDescription]) -> bool {
+    find_column(column_name, columns)
+        .map(|column| matches!(column.data_type, ColumnDataType::STRING))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod validate {
+    use super::*;
+
+    fn get_input() -> Value {
+        json!({
+            "workspace_id": "1234",
+            "project_id": "5678",
+            "data_table_name": "my_table",
+            "output_file_prefix": "test",
+            "model_hash" : "test_hash",
+            "xAxis": {
+                "fieldDisplayName": "region",
+                "fieldDataType": 1,
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "region"
+                }
+            },
+            "yAxis": {
+                "fieldDisplayName": "sold",
+                "fieldDataType": 3,
+                "fieldDefinition": {
+                    "fieldType": "date",
+                    "fieldName": "sold",
+                    "dateGrouping": "qualified_quarter"
+                }
+            },
+            "zAxis": {
+                "fieldDisplayName": "price",
+                "fieldDataType": 0,
+                "fieldDefinition": {
+                    "fieldType": "accumulated",
+                    "accumulator": "sum",
+                    "accumulatedFieldDefinition": {
+                        "fieldType": "standard",
+                        "fieldName": "price"
+                    }
+                }
+            },
+            "supportingFields": [{
+                "fieldDisplayName": "customer",
+                "fieldDataType": 1,
+                "fieldDefinition": {
+                    "fieldType": "standard",
+                    "fieldName": "customer"
+                }
+            }]
+        })
+    }
+
+    fn get_columns() -> Vec<ColumnDescription> {
+        [
+            ("region", ColumnDataType::STRING),
+            ("sold", ColumnDataType::NUMBER),
+            ("price", ColumnDataType::NUMBER),
+            ("customer", ColumnDataType::STRING),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnDescription {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect()
+    }
+
+    fn get_paths(problems: &[ValidationProblem]) -> Vec<&str> {
+        problems
+            .iter()
+            .map(|problem| problem.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn is_ok() {
+        let columns = get_columns();
+        assert!(VectorizerParameters::validate(&get_input(), None).is_empty());
+        assert!(VectorizerParameters::validate(&get_input(), Some(&columns)).is_empty());
+    }
+
+    #[test]
+    fn not_an_object() {
+        let problems = VectorizerParameters::validate(&json!([]), None);
+        assert_eq!(get_paths(&problems), vec![""]);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let mut input = get_input();
+        input.as_object_mut().unwrap().remove("workspace_id");
+        input.as_object_mut().unwrap().remove("zAxis");
+        input["model_hash"] = json!(5);
+        input["filter_vectors"] = json!("yes");
+        input["filter"] = json!("1 = 1");
+        input["yAxis"]["fieldDefinition"]["dateGrouping"] = json!("qualified_day");
+        input["supportingFields"][0]["aggregation"] = json!("sum");
+
+        let problems = VectorizerParameters::validate(&input, None);
+        assert_eq!(
+            get_paths(&problems),
+            vec![
+                "workspace_id",
+                "model_hash",
+                "filter_vectors",
+                "filter",
+                "yAxis.fieldDefinition.dateGrouping",
+                "zAxis",
+                "supportingFields[0]",
+            ]
+        );
+        assert_eq!(problems[4].data.as_ref().unwrap()["value"], "qualified_day");
+    }
+
+    #[test]
+    fn invalid_field_definition() {
+        let mut input = get_input();
+        input["xAxis"]["fieldDefinition"]
+            .as_object_mut()
+            .unwrap()
+            .remove("fieldName");
+        input["glyphColor"] = input["xAxis"].clone();
+        input["glyphColor"]["fieldDefinition"]["fieldName"] = json!("region");
+
+        let problems = VectorizerParameters::validate(&input, None);
+        assert_eq!(get_paths(&problems), vec!["xAxis", "glyphColor"]);
+        assert!(problems[1].message.contains("accumulated"));
+    }
+
+    #[test]
+    fn accumulator_on_a_string() {
+        let mut input = get_input();
+        input["zAxis"]["fieldDefinition"]["accumulatedFieldDefinition"]["fieldName"] =
+            json!("region");
+        let columns = get_columns();
+
+        let problems = VectorizerParameters::validate(&input, Some(&columns));
+        assert_eq!(
+            get_paths(&problems),
+            vec!["zAxis.fieldDefinition.accumulator"]
+        );
+        //Without the columns we can only go by the fieldDataType.
+        assert!(VectorizerParameters::validate(&input, None).is_empty());
+        input["zAxis"]["fieldDataType"] = json!(1);
+        let problems = VectorizerParameters::validate(&input, None);
+        assert_eq!(
+            get_paths(&problems),
+            vec!["zAxis.fieldDefinition.accumulator"]
+        );
+    }
+
+    #[test]
+    fn binned_string() {
+        let mut input = get_input();
+        //The fieldDataType claims that the column is a number.
+        input["xAxis"] = json!({
+            "fieldDisplayName": "region",
+            "fieldDataType": 0,
+            "fieldDefinition": {
+                "fieldType": "binned",
+                "fieldName": "region",
+                "binningMethod": "equal_width",
+                "binCount": 4
+            }
+        });
+        let columns = get_columns();
+
+        let problems = VectorizerParameters::validate(&input, Some(&columns));
+        assert_eq!(get_paths(&problems), vec!["xAxis.fieldDefinition"]);
+    }
+
+    #[test]
+    fn missing_columns() {
+        let mut input = get_input();
+        input["xAxis"]["fieldDefinition"]["fieldName"] = json!("country");
+        input["filter"] = json!({
+            "operator": "=",
+            "fieldName": "state",
+            "value": "NY"
+        });
+        let columns = get_columns();
+
+        let problems = VectorizerParameters::validate(&input, Some(&columns));
+        assert_eq!(
+            get_paths(&problems),
+            vec!["filter", "xAxis.fieldDefinition"]
+        );
+        assert_eq!(problems[1].data.as_ref().unwrap()["fieldName"], "country");
+        //The columns are only checked when we have them.
+        assert!(VectorizerParameters::validate(&input, None).is_empty());
+    }
+}
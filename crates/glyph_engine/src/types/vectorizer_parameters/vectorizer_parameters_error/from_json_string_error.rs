@@ -7,12 +7,14 @@ use glyphx_core::GlyphxErrorData;
 pub enum FromJsonStringError {
     JsonParseError(GlyphxErrorData),
     JsonValidationError(GlyphxErrorData),
+    InvalidValues(GlyphxErrorData),
 }
 
 impl FromJsonStringError {
     pub fn from_json_value_error(input: FromJsonValueError) -> Self {
         match input {
             FromJsonValueError::JsonValidationError(data) => Self::JsonValidationError(data),
+            FromJsonValueError::InvalidValues(data) => Self::InvalidValues(data),
         }
     }
 }
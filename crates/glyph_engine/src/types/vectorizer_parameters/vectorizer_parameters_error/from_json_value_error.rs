@@ -1,12 +1,14 @@
+use crate::types::vectorizer_parameters::filter_errors::FromJsonError as FilterFromJsonError;
 use glyphx_core::GlyphxError;
 use glyphx_core::GlyphxErrorData;
 
-use serde_json::json;
+use serde_json::{json, to_value, Value};
 
 #[derive(Debug, Clone, GlyphxError)]
 #[error_definition("VectorizerParameters")]
 pub enum FromJsonValueError {
     JsonValidationError(GlyphxErrorData),
+    InvalidValues(GlyphxErrorData),
 }
 
 impl FromJsonValueError {
@@ -16,6 +18,34 @@ impl FromJsonValueError {
         let error_data = GlyphxErrorData::new(message, Some(data), None);
         Self::JsonValidationError(error_data)
     }
+
+    pub fn raw_filter_not_allowed() -> Self {
+        let message = "A raw SQL filter can only be used when allow_raw_filter is true".to_string();
+        let data = json!({ "fieldName": "filter" });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::JsonValidationError(error_data)
+    }
+
+    ///errors holds one entry, with the path of the value, for each invalid value in the model
+    ///definition.
+    pub fn invalid_values(errors: Vec<Value>) -> Self {
+        let message = format!(
+            "The model definition has {} invalid value(s), see the errors for additional information",
+            errors.len()
+        );
+        let data = json!({ "errors": errors });
+        let error_data = GlyphxErrorData::new(message, Some(data), None);
+        Self::InvalidValues(error_data)
+    }
+
+    pub fn from_filter_from_json_error(input: FilterFromJsonError) -> Self {
+        let message =
+            "The filter is not valid, see the inner error for additional information".to_string();
+        let data = json!({ "fieldName": "filter" });
+        let inner_error = to_value(input.get_glyphx_error_data()).unwrap();
+        let error_data = GlyphxErrorData::new(message, Some(data), Some(inner_error));
+        Self::JsonValidationError(error_data)
+    }
 }
 
 #[cfg(test)]
@@ -34,6 +64,54 @@ mod constructor {
                 assert_eq!(field, field_name);
                 assert!(error_data.inner_error.is_none());
             }
+            _ => panic!("Expected JsonValidationError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod from_filter_from_json_error {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let message = "testMessage";
+        let data = json!({"field": "test"});
+        let data = GlyphxErrorData::new(message.to_string(), Some(data), None);
+        let input = FilterFromJsonError::InvalidOperator(data);
+
+        let result = FromJsonValueError::from_filter_from_json_error(input);
+        match result {
+            FromJsonValueError::JsonValidationError(error_data) => {
+                let d = error_data.data.unwrap();
+                assert_eq!(d["fieldName"].as_str().unwrap(), "filter");
+                let inner_error = error_data.inner_error.unwrap();
+                assert_eq!(inner_error["message"].as_str().unwrap(), message);
+            }
+            _ => panic!("Expected JsonValidationError"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod invalid_values {
+    use super::*;
+
+    #[test]
+    fn is_ok() {
+        let errors = vec![
+            json!({"path": "xAxis.fieldDefinition.dateGrouping", "value": "qualified_day"}),
+            json!({"path": "zAxis.fieldDefinition.accumulator", "value": "summ"}),
+        ];
+        let result = FromJsonValueError::invalid_values(errors);
+        match result {
+            FromJsonValueError::InvalidValues(error_data) => {
+                let d = error_data.data.unwrap();
+                let errors = d["errors"].as_array().unwrap();
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[1]["value"], "summ");
+            }
+            _ => panic!("Expected InvalidValues"),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::data_source::DataSourceError;
 use glyphx_core::{
     aws::{
         athena_manager::RunQueryError,
@@ -17,6 +18,8 @@ pub enum VectorCalculationError {
     AthenaQueryError(GlyphxErrorData),
     GetS3UploadStreamError(GlyphxErrorData),
     WriteUploadError(GlyphxErrorData),
+    DataSourceQueryError(GlyphxErrorData),
+    Cancelled(GlyphxErrorData),
 }
 unsafe impl Sync for VectorCalculationError {}
 impl From<RunQueryError> for VectorCalculationError {
@@ -27,6 +30,21 @@ impl From<RunQueryError> for VectorCalculationError {
     }
 }
 
+impl From<DataSourceError> for VectorCalculationError {
+    fn from(error: DataSourceError) -> Self {
+        //Athena errors keep their own variant so that callers do not need to care which data
+        //source produced them.
+        let is_athena_error = matches!(error, DataSourceError::AthenaQueryError(_));
+        let inner_error = to_value(error).unwrap();
+        let error_data = GlyphxErrorData::new("An unexpected error occurred while running the vector query.  See the inner error for additional information".to_string(),None, Some(inner_error));
+        if is_athena_error {
+            VectorCalculationError::AthenaQueryError(error_data)
+        } else {
+            VectorCalculationError::DataSourceQueryError(error_data)
+        }
+    }
+}
+
 impl From<GetUploadStreamError> for VectorCalculationError {
     fn from(error: GetUploadStreamError) -> Self {
         let inner_error = to_value(error).unwrap();
@@ -8,25 +8,26 @@ pub use task_status::*;
 pub use vector::*;
 pub use vector_origional_value::*;
 
-use crate::types::vectorizer_parameters::FieldDefinition;
-use glyphx_common::{AthenaConnection, S3Connection};
+use crate::data_source::{DataSource, DataSourceError, VectorQuery};
+use crate::file_format::{FileHeader, FileType, HeaderField, RecordEncoder};
+use crate::types::vectorizer_parameters::{DateFieldDefinition, FieldDefinition, Filter};
 use glyphx_core::{
     aws::{
-        athena_manager::RunQueryError,
         s3_manager::{GetUploadStreamError, UploadStreamFinishError, UploadStreamWriteError},
-        upload_stream::UploadStream,
+        ObjectStore, ObjectUploadStream,
     },
-    ErrorTypeParser, GlyphxErrorData, Singleton,
+    ErrorTypeParser, GlyphxErrorData,
 };
 
 use async_trait::async_trait;
-use bincode::serialize;
 use im::OrdMap;
 use log::error;
 use mockall::automock;
 use serde_json::{json, to_value, Value};
 use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
 use tokio::task::{spawn, JoinHandle};
+use tokio_util::sync::CancellationToken;
 
 /// This macro is used to handle functions that return Result<T, E>  in a consistent way in our
 /// tokio Tasks. If a code block returns the Result::Err(E) variant, the emited code will unwrap the error
@@ -76,44 +77,52 @@ macro_rules! handle_sync_task_error {
 #[automock]
 #[async_trait]
 pub trait ThreadOperations {
-    async fn run_athena_query(&self, query: &str) -> Result<Value, RunQueryError>;
+    async fn run_vector_query(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &VectorQuery,
+    ) -> Result<Value, DataSourceError>;
     async fn get_upload_stream(
         &self,
+        object_store: Arc<dyn ObjectStore>,
         s3_file_name: &str,
-    ) -> Result<UploadStream, GetUploadStreamError>;
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError>;
     async fn write_to_stream(
         &self,
-        stream: &mut UploadStream,
+        stream: &mut Box<dyn ObjectUploadStream>,
         data: Vec<u8>,
     ) -> Result<(), UploadStreamWriteError>;
 
-    async fn finish_stream(&self, stream: &mut UploadStream)
+    async fn finish_stream(&self, stream: &mut Box<dyn ObjectUploadStream>)
         -> Result<(), UploadStreamFinishError>;
+
+    async fn abort_stream(&self, stream: &mut Box<dyn ObjectUploadStream>) {
+        stream.abort().await
+    }
 }
 
 struct ThreadOperationsImpl;
 
 #[async_trait]
 impl ThreadOperations for ThreadOperationsImpl {
-    async fn run_athena_query(&self, query: &str) -> Result<Value, RunQueryError> {
-        let athena_connection = AthenaConnection::get_instance();
-        let athena_manager = athena_connection.get_athena_manager();
-        athena_manager
-            .run_query(&query, Some(300), Some(true))
-            .await
+    async fn run_vector_query(
+        &self,
+        data_source: Arc<dyn DataSource>,
+        query: &VectorQuery,
+    ) -> Result<Value, DataSourceError> {
+        data_source.get_vector_values(query).await
     }
     async fn get_upload_stream(
         &self,
+        object_store: Arc<dyn ObjectStore>,
         s3_file_name: &str,
-    ) -> Result<UploadStream, GetUploadStreamError> {
-        let s3_connection = S3Connection::get_instance();
-        let s3_manager = s3_connection.get_s3_manager();
-        let upload_stream = s3_manager.get_upload_stream(&s3_file_name).await;
+    ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
+        let upload_stream = object_store.get_upload_stream(s3_file_name).await;
         upload_stream
     }
     async fn write_to_stream(
         &self,
-        stream: &mut UploadStream,
+        stream: &mut Box<dyn ObjectUploadStream>,
         data: Vec<u8>,
     ) -> Result<(), UploadStreamWriteError> {
         let write_result = stream.write(Some(data)).await;
@@ -121,7 +130,7 @@ impl ThreadOperations for ThreadOperationsImpl {
     }
     async fn finish_stream(
         &self,
-        stream: &mut UploadStream,
+        stream: &mut Box<dyn ObjectUploadStream>,
     ) -> Result<(), UploadStreamFinishError> {
         let result = stream.finish().await;
         result
@@ -132,7 +141,12 @@ pub struct VectorProcesser {
     axis_name: String,
     table_name: String,
     s3_file_name: String,
+    model_hash: String,
     field_definition: FieldDefinition,
+    filter: Option<Filter>,
+    data_source: Arc<dyn DataSource>,
+    object_store: Arc<dyn ObjectStore>,
+    cancellation_token: CancellationToken,
     receiver: Option<Receiver<Result<Vector, VectorCalculationError>>>,
     vectors: OrdMap<VectorOrigionalValue, Vector>,
     join_handle: Option<JoinHandle<()>>,
@@ -262,64 +276,145 @@ impl VectorProcesser {
         axis_name: &str,
         table_name: &str,
         s3_file_name: &str,
+        model_hash: &str,
         field_definition: FieldDefinition,
+        data_source: Arc<dyn DataSource>,
+        object_store: Arc<dyn ObjectStore>,
     ) -> Self {
         Self {
             axis_name: axis_name.to_string(),
             table_name: table_name.to_string(),
             field_definition,
+            filter: None,
+            data_source,
+            object_store,
+            cancellation_token: CancellationToken::new(),
             receiver: None,
             vectors: OrdMap::new(),
             join_handle: None,
             task_status: TaskStatus::Pending,
             s3_file_name: s3_file_name.to_string(),
+            model_hash: model_hash.to_string(),
         }
     }
-    async fn run_sync_impl<T: ThreadOperations>(&mut self, thread_operations: &T) -> TaskStatus {
+    ///Limits the vectors to the values of the rows that pass the filter.
+    pub fn with_filter(mut self, filter: Option<Filter>) -> Self {
+        self.filter = filter;
+        self
+    }
+    ///Stops run_sync, and aborts the vector file, once the token has been cancelled.
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+    fn cancelled(&mut self) -> TaskStatus {
+        let data = json!({
+            "axis_name": self.axis_name,
+            "table_name": self.table_name,
+        });
+        let error_data = GlyphxErrorData::new(
+            "The vector processer was cancelled.".to_string(),
+            Some(data),
+            None,
+        );
+        self.task_status = TaskStatus::Errored(VectorCalculationError::Cancelled(error_data));
+        self.task_status.clone()
+    }
+    async fn run_sync_impl<T: ThreadOperations + Sync>(
+        &mut self,
+        thread_operations: &T,
+    ) -> TaskStatus {
         let (field_name, query) = self.build_query();
+        let is_ordinal = self.is_ordinal();
+        let date_field_definition = self.field_definition.get_date_field_definition().cloned();
         let s3_file_name = self.s3_file_name.clone();
         self.task_status = TaskStatus::Processing;
-        handle_sync_task_error!(let result = thread_operations.run_athena_query(&query).await);
-        handle_sync_task_error!(let upload_stream = thread_operations.get_upload_stream(&s3_file_name).await);
+        let cancellation_token = self.cancellation_token.clone();
+        let result = tokio::select! {
+            biased;
+            _ = cancellation_token.cancelled() => return self.cancelled(),
+            result = thread_operations.run_vector_query(self.data_source.clone(), &query) => result,
+        };
+        handle_sync_task_error!(let result = result);
+        handle_sync_task_error!(let upload_stream = thread_operations.get_upload_stream(self.object_store.clone(), &s3_file_name).await);
         let mut upload_stream = upload_stream;
+        handle_sync_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, self.build_file_header().encode()).await);
+        let mut encoder = RecordEncoder::new();
         let mut rank = 0;
         for row in result.as_array().unwrap() {
-            let vector = build_vector(row, &field_name, rank);
+            if cancellation_token.is_cancelled() {
+                thread_operations.abort_stream(&mut upload_stream).await;
+                return self.cancelled();
+            }
+            let vector = build_vector(
+                row,
+                &field_name,
+                rank,
+                is_ordinal,
+                date_field_definition.as_ref(),
+            );
             //Byte Serialize the vector
-            let ser_vector = serialize_vector(&vector);
+            let ser_vector = encoder.encode_record(&vector);
 
             handle_sync_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, ser_vector).await);
 
             self.vectors.insert(vector.orig_value.clone(), vector);
             rank += 1;
         }
+        handle_sync_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, encoder.get_footer().encode()).await);
         handle_sync_task_error!(let _result = thread_operations.finish_stream(&mut upload_stream).await);
         self.task_status = TaskStatus::Complete;
         TaskStatus::Complete
     }
-    fn build_query(&self) -> (String, String) {
-        let (field_name, field_value, _) = self.field_definition.get_query_parts();
-        let query = format!(
-            "SELECT DISTINCT {} FROM {} ORDER BY {}",
-            field_value, self.table_name, field_name
-        );
+    fn build_query(&self) -> (String, VectorQuery) {
+        let field_name = self.field_definition.get_field_display_name().to_string();
+        let query = VectorQuery {
+            table_name: self.table_name.clone(),
+            field_definition: self.field_definition.clone(),
+            filter: self.filter.clone(),
+        };
         (field_name, query)
     }
+    ///An axis that is not sorted by its values places them by their rank, so that their
+    ///positions follow the sort order.
+    fn is_ordinal(&self) -> bool {
+        !self.field_definition.get_sort_order().is_default()
+    }
+    fn build_file_header(&self) -> FileHeader {
+        FileHeader::new(
+            FileType::Vector,
+            &self.model_hash,
+            vec![HeaderField::new(&self.axis_name, &self.field_definition)],
+        )
+    }
     fn start_impl<T: ThreadOperations + Sync>(&mut self, thread_operations: &'static T) {
         let ( field_name, query) = self.build_query();
+        let is_ordinal = self.is_ordinal();
+        let date_field_definition = self.field_definition.get_date_field_definition().cloned();
         let s3_file_name = self.s3_file_name.clone();
+        let file_header = self.build_file_header();
+        let data_source = self.data_source.clone();
+        let object_store = self.object_store.clone();
         let (sender, receiver) = channel::<Result<Vector, VectorCalculationError>>();
         self.receiver = Some(receiver);
         let thread_handle = spawn(async move {
-            handle_task_error!(let result = thread_operations.run_athena_query(&query).await, sender);
+            handle_task_error!(let result = thread_operations.run_vector_query(data_source.clone(), &query).await, sender);
             let mut rank = 0;
-            handle_task_error!(let upload_stream = thread_operations.get_upload_stream(&s3_file_name).await, sender);
+            handle_task_error!(let upload_stream = thread_operations.get_upload_stream(object_store, &s3_file_name).await, sender);
             //handle_task_error does not unwrap as mut, we need to do that here
             let mut upload_stream = upload_stream;
+            handle_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, file_header.encode()).await, sender);
+            let mut encoder = RecordEncoder::new();
             for row in result.as_array().unwrap() {
-                let vector = build_vector(row, &field_name, rank);
+                let vector = build_vector(
+                    row,
+                    &field_name,
+                    rank,
+                    is_ordinal,
+                    date_field_definition.as_ref(),
+                );
                 //Byte Serialize the vector
-                let ser_vector = serialize_vector(&vector);
+                let ser_vector = encoder.encode_record(&vector);
 
                 handle_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, ser_vector).await, sender);
                 //This doesn't use our macro because the it is actually already sending the result
@@ -334,6 +429,7 @@ impl VectorProcesser {
                 }
                 rank += 1;
             }
+            handle_task_error!(let _write_result = thread_operations.write_to_stream(&mut upload_stream, encoder.get_footer().encode()).await, sender);
             handle_task_error!(let _result = thread_operations.finish_stream(&mut upload_stream).await, sender);
             //Send and empty vector to signal that the task is complete
             let send_result = sender.send(Ok(Vector::empty()));
@@ -352,10 +448,18 @@ impl VectorProcesser {
 
 ///These functions are run inside the tokio task and have no understading of Self as it is not
 ///copied into the clousure.  It is ok to place them here outside of the impl block.
-fn build_vector(row: &Value, field_name: &String, rank: u64) -> Vector {
+///Date groupings are numbers that encode the date, i.e. 20231 for 2023-Q1, so their vectors carry
+///a label that clients can display.
+fn build_vector(
+    row: &Value,
+    field_name: &String,
+    rank: u64,
+    is_ordinal: bool,
+    date_field_definition: Option<&DateFieldDefinition>,
+) -> Vector {
     let value = row.get(field_name).unwrap();
     let orig_value: VectorOrigionalValue;
-    let vector: f64;
+    let mut vector: f64;
     if value.is_string() {
         orig_value = VectorOrigionalValue::String(value.as_str().unwrap().to_string());
         vector = rank.clone() as f64;
@@ -368,23 +472,15 @@ fn build_vector(row: &Value, field_name: &String, rank: u64) -> Vector {
         vector = raw_value.clone() as f64;
         orig_value = VectorOrigionalValue::U64(raw_value);
     }
-    let vector = Vector::new(orig_value, vector, rank);
+    if is_ordinal {
+        vector = rank as f64;
+    }
+    let label = date_field_definition
+        .and_then(|date_field_definition| date_field_definition.get_label(value));
+    let vector = Vector::new(orig_value, vector, rank).with_label(label);
     vector
 }
 
-fn serialize_vector(vector: &Vector) -> Vec<u8> {
-    let vector_binary_size = vector.get_binary_size();
-    let mut ser_vector: Vec<u8> = Vec::with_capacity(8 + vector_binary_size);
-    //We are including the size of the vector so that when we write this to a file we
-    //will be able to determine the size of the vector when we read it back in.  This
-    //prevents us from having to store the vectors in two seperate blocks of memory,
-    //one here and one on the main thread.  In the future, we will stream the results
-    //back from athena so we will not hold them all ion memory at once.
-    ser_vector.append(serialize(&vector_binary_size).unwrap().as_mut());
-    ser_vector.append(serialize(vector).unwrap().as_mut());
-    ser_vector
-}
-
 //This allows us to build a vector processer from a json result set.  This is useful for testing
 #[cfg(test)]
 pub(super) fn build_vector_processer_from_json(
@@ -395,11 +491,29 @@ pub(super) fn build_vector_processer_from_json(
     result_set: Value,
     field_name: String,
 ) -> Box<dyn VectorValueProcesser> {
-    let mut vector_processer =
-        VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+    let mut vector_processer = VectorProcesser::new(
+        axis_name,
+        table_name,
+        s3_file_name,
+        "test_hash",
+        field_definition,
+        Arc::new(crate::data_source::MockDataSource::new()),
+        Arc::new(glyphx_core::aws::S3Manager::default()),
+    );
+    let is_ordinal = vector_processer.is_ordinal();
+    let date_field_definition = vector_processer
+        .field_definition
+        .get_date_field_definition()
+        .cloned();
     let mut rank = 0;
     for row in result_set.as_array().unwrap() {
-        let vector = build_vector(row, &field_name, rank);
+        let vector = build_vector(
+            row,
+            &field_name,
+            rank,
+            is_ordinal,
+            date_field_definition.as_ref(),
+        );
         vector_processer
             .vectors
             .insert(vector.orig_value.clone(), vector);
@@ -415,14 +529,21 @@ mod tests {
     mod helper_functions {
         use super::*;
         use crate::field_definition_type::FieldDefinitionType;
-        use crate::types::vectorizer_parameters::{FieldDefinition, StandardFieldDefinition};
+        use crate::data_source::MockDataSource;
+        use crate::types::vectorizer_parameters::{
+            FieldDefinition, SortOrder, StandardFieldDefinition,
+        };
         use crate::FieldType;
-        use glyphx_core::aws::S3Manager;
+        use glyphx_core::aws::{upload_stream::UploadStream, S3Manager};
 
         pub struct StringMocks1;
         #[async_trait]
         impl ThreadOperations for StringMocks1 {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test": "a"
@@ -444,15 +565,16 @@ mod tests {
             }
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
@@ -460,7 +582,7 @@ mod tests {
 
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -469,7 +591,11 @@ mod tests {
         pub struct StringMocks2;
         #[async_trait]
         impl ThreadOperations for StringMocks2 {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test2": "f"
@@ -491,22 +617,23 @@ mod tests {
             }
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -515,7 +642,11 @@ mod tests {
         pub struct NumberFloatMocks;
         #[async_trait]
         impl ThreadOperations for NumberFloatMocks {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test": 1.0
@@ -537,22 +668,23 @@ mod tests {
             }
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -561,7 +693,11 @@ mod tests {
         pub struct NumberIntMocks;
         #[async_trait]
         impl ThreadOperations for NumberIntMocks {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test": 1
@@ -583,22 +719,23 @@ mod tests {
             }
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -606,33 +743,38 @@ mod tests {
         pub struct MocksRunAthenaError;
         #[async_trait]
         impl ThreadOperations for MocksRunAthenaError {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let error_data = GlyphxErrorData::new(
                     "An unexpected error occurred while running the  query.".to_string(),
                     None,
                     None,
                 );
-                let error = RunQueryError::QueryFailed(error_data);
+                let error = DataSourceError::AthenaQueryError(error_data);
                 Err(error)
             }
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -641,7 +783,11 @@ mod tests {
         pub struct MocksStartUploadError;
         #[async_trait]
         impl ThreadOperations for MocksStartUploadError {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test2": "f"
@@ -664,8 +810,9 @@ mod tests {
 
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let error_data = GlyphxErrorData::new(
                     "An unexpected error occurred while starting the upload stream.".to_string(),
                     None,
@@ -676,14 +823,14 @@ mod tests {
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -692,7 +839,11 @@ mod tests {
         pub struct MocksWriteUploadError;
         #[async_trait]
         impl ThreadOperations for MocksWriteUploadError {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test": "f"
@@ -715,15 +866,16 @@ mod tests {
 
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 let error_data = GlyphxErrorData::new(
@@ -736,7 +888,7 @@ mod tests {
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 Ok(())
             }
@@ -745,7 +897,11 @@ mod tests {
         pub struct MocksFinishUploadError;
         #[async_trait]
         impl ThreadOperations for MocksFinishUploadError {
-            async fn run_athena_query(&self, _query: &str) -> Result<Value, RunQueryError> {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
                 let json = serde_json::json!([
                     {
                         "Test": "f"
@@ -768,22 +924,23 @@ mod tests {
 
             async fn get_upload_stream(
                 &self,
+                _object_store: Arc<dyn ObjectStore>,
                 _s3_file_name: &str,
-            ) -> Result<UploadStream, GetUploadStreamError> {
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
                 let s3_manager = S3Manager::default();
                 let client = s3_manager.get_client();
-                Ok(UploadStream::empty(client))
+                Ok(Box::new(UploadStream::empty(client)))
             }
             async fn write_to_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
                 _data: Vec<u8>,
             ) -> Result<(), UploadStreamWriteError> {
                 Ok(())
             }
             async fn finish_stream(
                 &self,
-                _stream: &mut UploadStream,
+                _stream: &mut Box<dyn ObjectUploadStream>,
             ) -> Result<(), UploadStreamFinishError> {
                 let error_data = GlyphxErrorData::new(
                     "An unexpected error occurred while writing to the upload stream.".to_string(),
@@ -794,6 +951,14 @@ mod tests {
                 Err(error)
             }
         }
+        pub fn get_data_source() -> Arc<dyn DataSource> {
+            Arc::new(MockDataSource::new())
+        }
+
+        pub fn get_object_store() -> Arc<dyn ObjectStore> {
+            Arc::new(S3Manager::default())
+        }
+
         pub fn get_standard_field_definition(
             display_name: &str,
             field_name: &str,
@@ -804,9 +969,11 @@ mod tests {
                 field_definition: StandardFieldDefinition {
                     field_name: field_name.to_string(),
                     field_type: FieldDefinitionType::Standard,
+                    top_n: None,
                 },
                 field_query: format!(r#""{}" as "{}""#, field_name, display_name),
                 raw_query: format!(r#""{}""#, field_name),
+                sort_order: SortOrder::default(),
             }
         }
     }
@@ -821,7 +988,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
             assert_eq!(vector_processer.axis_name, axis_name);
             assert_eq!(vector_processer.table_name, table_name);
             assert!(vector_processer.field_definition.is_standard());
@@ -829,6 +1004,27 @@ mod tests {
             assert!(vector_processer.vectors.is_empty());
             assert!(vector_processer.join_handle.is_none());
             assert_eq!(vector_processer.task_status, TaskStatus::Pending);
+            assert!(vector_processer.filter.is_none());
+        }
+
+        #[test]
+        fn with_filter() {
+            let field_definition =
+                helper_functions::get_standard_field_definition("Test", "field_name");
+            let filter = Filter::Raw("field_name > 1".to_string());
+            let vector_processer = VectorProcesser::new(
+                "test_axis",
+                "test_table",
+                "s3_file_name",
+                "test_hash",
+                field_definition,
+                helper_functions::get_data_source(),
+                helper_functions::get_object_store(),
+            )
+            .with_filter(Some(filter.clone()));
+            let (field_name, query) = vector_processer.build_query();
+            assert_eq!(field_name, "Test");
+            assert_eq!(query.filter, Some(filter));
         }
     }
 
@@ -844,7 +1040,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::StringMocks1);
             let final_status: TaskStatus;
@@ -887,7 +1091,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             let axis_name2 = "test_axis2";
             let table_name2 = "test_table2";
@@ -895,7 +1107,15 @@ mod tests {
             let field_definition2 =
                 helper_functions::get_standard_field_definition("Test2", "field_name2");
             let mut vector_processer2 =
-                VectorProcesser::new(axis_name2, table_name2, s3_file_name2, field_definition2);
+                VectorProcesser::new(
+                    axis_name2,
+                    table_name2,
+                    s3_file_name2,
+                    "test_hash",
+                    field_definition2,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::StringMocks1);
             vector_processer2.start_impl(&helper_functions::StringMocks2);
@@ -979,7 +1199,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::MocksRunAthenaError);
             let final_status: TaskStatus;
@@ -1003,7 +1231,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             let axis_name2 = "test_axis2";
             let table_name2 = "test_table2";
@@ -1011,7 +1247,15 @@ mod tests {
             let field_definition2 =
                 helper_functions::get_standard_field_definition("Test2", "field_name2");
             let mut vector_processer2 =
-                VectorProcesser::new(axis_name2, table_name2, s3_file_name2, field_definition2);
+                VectorProcesser::new(
+                    axis_name2,
+                    table_name2,
+                    s3_file_name2,
+                    "test_hash",
+                    field_definition2,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::MocksRunAthenaError);
             vector_processer2.start_impl(&helper_functions::StringMocks2);
@@ -1076,7 +1320,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::NumberFloatMocks);
             let final_status: TaskStatus;
@@ -1123,7 +1375,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::NumberIntMocks);
             let final_status: TaskStatus;
@@ -1170,7 +1430,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::MocksStartUploadError);
             let final_status: TaskStatus;
@@ -1198,7 +1466,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::MocksWriteUploadError);
             let final_status: TaskStatus;
@@ -1224,7 +1500,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
 
             vector_processer.start_impl(&helper_functions::MocksFinishUploadError);
             let final_status: TaskStatus;
@@ -1243,6 +1527,111 @@ mod tests {
         }
     }
 
+    mod run_sync {
+        use super::*;
+        use glyphx_core::aws::{upload_stream::UploadStream, S3Manager};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        //Cancels the token on the first write, as if process had been cancelled while the
+        //vectors were being written.
+        struct MocksCancelOnWrite {
+            cancellation_token: CancellationToken,
+            aborted: AtomicBool,
+        }
+        #[async_trait]
+        impl ThreadOperations for MocksCancelOnWrite {
+            async fn run_vector_query(
+                &self,
+                _data_source: Arc<dyn DataSource>,
+                _query: &VectorQuery,
+            ) -> Result<Value, DataSourceError> {
+                Ok(serde_json::json!([{ "Test": "a" }, { "Test": "b" }]))
+            }
+            async fn get_upload_stream(
+                &self,
+                _object_store: Arc<dyn ObjectStore>,
+                _s3_file_name: &str,
+            ) -> Result<Box<dyn ObjectUploadStream>, GetUploadStreamError> {
+                let s3_manager = S3Manager::default();
+                let client = s3_manager.get_client();
+                Ok(Box::new(UploadStream::empty(client)))
+            }
+            async fn write_to_stream(
+                &self,
+                _stream: &mut Box<dyn ObjectUploadStream>,
+                _data: Vec<u8>,
+            ) -> Result<(), UploadStreamWriteError> {
+                self.cancellation_token.cancel();
+                Ok(())
+            }
+            async fn finish_stream(
+                &self,
+                _stream: &mut Box<dyn ObjectUploadStream>,
+            ) -> Result<(), UploadStreamFinishError> {
+                Ok(())
+            }
+            async fn abort_stream(&self, _stream: &mut Box<dyn ObjectUploadStream>) {
+                self.aborted.store(true, Ordering::SeqCst);
+            }
+        }
+
+        fn get_vector_processer(cancellation_token: CancellationToken) -> VectorProcesser {
+            let field_definition =
+                helper_functions::get_standard_field_definition("Test", "field_name");
+            VectorProcesser::new(
+                "test_axis",
+                "test_table",
+                "s3_file_name",
+                "test_hash",
+                field_definition,
+                helper_functions::get_data_source(),
+                helper_functions::get_object_store(),
+            )
+            .with_cancellation_token(cancellation_token)
+        }
+
+        #[tokio::test]
+        async fn is_ok() {
+            let mut vector_processer = get_vector_processer(CancellationToken::new());
+            let status = vector_processer
+                .run_sync_impl(&helper_functions::StringMocks1)
+                .await;
+            assert_eq!(status, TaskStatus::Complete);
+            assert_eq!(vector_processer.get_statistics_vector().len(), 5);
+        }
+
+        #[tokio::test]
+        async fn cancelled_before_the_query() {
+            let cancellation_token = CancellationToken::new();
+            cancellation_token.cancel();
+            let mut vector_processer = get_vector_processer(cancellation_token);
+            let status = vector_processer
+                .run_sync_impl(&helper_functions::StringMocks1)
+                .await;
+            match status {
+                TaskStatus::Errored(VectorCalculationError::Cancelled(_)) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+        }
+
+        #[tokio::test]
+        async fn cancelled_while_writing() {
+            let cancellation_token = CancellationToken::new();
+            let mocks = MocksCancelOnWrite {
+                cancellation_token: cancellation_token.clone(),
+                aborted: AtomicBool::new(false),
+            };
+            let mut vector_processer = get_vector_processer(cancellation_token);
+            let status = vector_processer.run_sync_impl(&mocks).await;
+            match status {
+                TaskStatus::Errored(VectorCalculationError::Cancelled(_)) => {}
+                _ => panic!("Expected Cancelled"),
+            }
+            assert!(mocks.aborted.load(Ordering::SeqCst));
+            assert!(vector_processer.get_statistics_vector().is_empty());
+        }
+    }
+
     mod get_statistics_vector {
         use super::*;
 
@@ -1254,7 +1643,15 @@ mod tests {
             let field_definition =
                 helper_functions::get_standard_field_definition("Test", "field_name");
             let mut vector_processer =
-                VectorProcesser::new(axis_name, table_name, s3_file_name, field_definition);
+                VectorProcesser::new(
+                    axis_name,
+                    table_name,
+                    s3_file_name,
+                    "test_hash",
+                    field_definition,
+                    helper_functions::get_data_source(),
+                    helper_functions::get_object_store(),
+                );
             assert_eq!(vector_processer.axis_name, axis_name);
             assert_eq!(vector_processer.table_name, table_name);
             assert!(vector_processer.field_definition.is_standard());
@@ -1277,4 +1674,47 @@ mod tests {
             }
         }
     }
+
+    mod build_vector {
+        use super::*;
+
+        #[test]
+        fn number() {
+            let vector =
+                build_vector(&json!({"field": 12.5}), &"field".to_string(), 3, false, None);
+            assert_eq!(vector.orig_value, VectorOrigionalValue::F64(12.5));
+            assert_eq!(vector.vector, 12.5);
+            assert_eq!(vector.rank, 3);
+            assert!(vector.label.is_none());
+        }
+
+        #[test]
+        fn ordinal_number() {
+            let vector =
+                build_vector(&json!({"field": 12.5}), &"field".to_string(), 3, true, None);
+            assert_eq!(vector.orig_value, VectorOrigionalValue::F64(12.5));
+            assert_eq!(vector.vector, 3.0);
+            assert_eq!(vector.rank, 3);
+        }
+
+        #[test]
+        fn date_label() {
+            let date_field_definition = DateFieldDefinition::from_json(&json!({
+                "fieldType": "date",
+                "fieldName": "sold",
+                "dateGrouping": "qualified_quarter"
+            }))
+            .unwrap();
+            let vector = build_vector(
+                &json!({"field": 20231}),
+                &"field".to_string(),
+                0,
+                false,
+                Some(&date_field_definition),
+            );
+            assert_eq!(vector.orig_value, VectorOrigionalValue::U64(20231));
+            assert_eq!(vector.vector, 20231.0);
+            assert_eq!(vector.label.unwrap(), "2023-Q1");
+        }
+    }
 }
@@ -6,34 +6,83 @@ pub struct Vector {
     pub orig_value: VectorOrigionalValue,
     pub vector: f64,
     pub rank: u64,
+    ///A display label for orig_value when it is an encoded value, i.e. "2023-Q1" for the
+    ///QualifiedQuarter 20231.
+    pub label: Option<String>,
     #[serde(skip, default = "Option::default")]
     pub is_empty: Option<()>,
 }
 
+///The layout of a Vector before labels were added (format versions 1 and 2).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct VectorV2 {
+    pub orig_value: VectorOrigionalValue,
+    pub vector: f64,
+    pub rank: u64,
+}
+
+impl From<VectorV2> for Vector {
+    fn from(vector: VectorV2) -> Self {
+        Vector::new(vector.orig_value, vector.vector, vector.rank)
+    }
+}
+
 impl Vector {
     pub fn new(orig_value: VectorOrigionalValue, vector: f64, rank: u64) -> Self {
         Self {
             orig_value,
             vector,
             rank,
+            label: None,
             is_empty: None,
         }
     }
 
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
+    }
+
     pub fn empty() -> Self {
         Self {
             orig_value: VectorOrigionalValue::Empty,
             vector: 0.0,
             rank: 0,
+            label: None,
             is_empty: Some(()),
         }
     }
     pub fn get_binary_size(&self) -> usize {
+        //1 byte for the label flag plus the length and bytes of the label if there is one.
+        let label_size = match &self.label {
+            Some(label) => 1 + 8 + label.len(),
+            None => 1,
+        };
         28 + match &self.orig_value {
             // 4 bytes for the enum variant.
             VectorOrigionalValue::String(s) => s.len(), // 8 bytes for the vector f64
             _ => 0,                                     // 8 bytes for the rank u64
                                                          // 8 bytes for the orig value if it is a number or the length of the
         } //   string if it is a string.
+        + label_size
+    }
+}
+
+#[cfg(test)]
+mod get_binary_size {
+    use super::*;
+    use bincode::serialize;
+
+    #[test]
+    fn without_label() {
+        let vector = Vector::new(VectorOrigionalValue::String("abc".to_string()), 1.0, 2);
+        assert_eq!(vector.get_binary_size(), serialize(&vector).unwrap().len());
+    }
+
+    #[test]
+    fn with_label() {
+        let vector = Vector::new(VectorOrigionalValue::U64(20231), 1.0, 2)
+            .with_label(Some("2023-Q1".to_string()));
+        assert_eq!(vector.get_binary_size(), serialize(&vector).unwrap().len());
     }
 }
@@ -207,22 +207,24 @@ fn build_singleton_trait(
 ) -> proc_macro2::TokenStream {
     let output = if is_async {
         quote!(
-            static mut INSTANCE: Option<#ident> = None;
+            static INSTANCE: std::sync::OnceLock<#ident> = std::sync::OnceLock::new();
 
         #[glyphx_core::async_trait]
         impl glyphx_core::traits::Singleton<#ident, #error_ident> for #ident  {
             fn get_instance() -> &'static #ident {
-                unsafe { &INSTANCE.as_ref().unwrap() }
+                INSTANCE.get().unwrap()
             }
             async fn build_singleton() -> Result<&'static #ident, #error_ident> {
+               //The instance is only ever set once, so references to it stay valid.
+               if let Some(instance) = INSTANCE.get() {
+                   return Ok(instance);
+               }
                let secret_bound_object = #ident::bind_secrets().await;
                if secret_bound_object.is_err() {
                    return Err(secret_bound_object.err().unwrap());
                }
-               unsafe {
-                 INSTANCE = Some(secret_bound_object.ok().unwrap());
-                 Ok(&INSTANCE.as_ref().unwrap())
-               }
+               //If another caller built the instance while we bound our secrets, theirs wins.
+               Ok(INSTANCE.get_or_init(|| secret_bound_object.ok().unwrap()))
             }
         }
 
@@ -230,21 +232,23 @@ fn build_singleton_trait(
         )
     } else {
         quote!(
-            static mut INSTANCE: Option<#ident> = None;
+            static INSTANCE: std::sync::OnceLock<#ident> = std::sync::OnceLock::new();
 
         impl glyphx_core::traits::SyncSingleton<#ident, #error_ident> for #ident  {
             fn get_instance() -> &'static #ident {
-                unsafe { &INSTANCE.as_ref().unwrap() }
+                INSTANCE.get().unwrap()
             }
             fn build_singleton() -> Result<&'static #ident, #error_ident> {
+               //The instance is only ever set once, so references to it stay valid.
+               if let Some(instance) = INSTANCE.get() {
+                   return Ok(instance);
+               }
                let secret_bound_object = #ident::bind_secrets();
                if secret_bound_object.is_err() {
                    return Err(secret_bound_object.err().unwrap());
                }
-               unsafe {
-                 INSTANCE = Some(secret_bound_object.ok().unwrap());
-                 Ok(&INSTANCE.as_ref().unwrap())
-               }
+               //If another caller built the instance while we bound our secrets, theirs wins.
+               Ok(INSTANCE.get_or_init(|| secret_bound_object.ok().unwrap()))
             }
         }
 
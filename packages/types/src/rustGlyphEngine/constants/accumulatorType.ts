@@ -4,4 +4,11 @@ export enum AccumulatorType {
   MIN = 'min',
   MAX = 'max',
   COUNT = 'count',
+  COUNT_DISTINCT = 'count_distinct',
+  MEDIAN = 'median',
+  PERCENTILE = 'percentile',
+  STDDEV = 'stddev',
+  VARIANCE = 'variance',
+  FIRST = 'first',
+  LAST = 'last',
 }
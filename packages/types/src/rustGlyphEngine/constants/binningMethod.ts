@@ -0,0 +1,5 @@
+export enum BinningMethod {
+  EQUAL_WIDTH = 'equal_width',
+  QUANTILE = 'quantile',
+  EXPLICIT = 'explicit',
+}
@@ -15,4 +15,8 @@ export enum DateGrouping {
   QualifiedQuarter = 'qualified_quarter',
   Quarter = 'quarter',
   YearOfWeek = 'year_of_week',
+  HourOfDay = 'hour_of_day',
+  QualifiedHour = 'qualified_hour',
+  MinuteBucket = 'minute_bucket',
+  WeekStarting = 'week_starting',
 }
@@ -0,0 +1,6 @@
+export enum DateUnit {
+  Milliseconds = 'milliseconds',
+  Seconds = 'seconds',
+  IsoString = 'iso_string',
+  Timestamp = 'timestamp',
+}
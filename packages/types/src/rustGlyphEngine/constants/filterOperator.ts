@@ -0,0 +1,13 @@
+export enum FilterOperator {
+  EQUAL = '=',
+  NOT_EQUAL = '!=',
+  LESS_THAN = '<',
+  LESS_THAN_OR_EQUAL = '<=',
+  GREATER_THAN = '>',
+  GREATER_THAN_OR_EQUAL = '>=',
+  IN = 'in',
+  BETWEEN = 'between',
+  DATE_RANGE = 'dateRange',
+  IS_NULL = 'isNull',
+  IS_NOT_NULL = 'isNotNull',
+}
@@ -1,3 +1,10 @@
 export type {AccumulatorType} from './accumulatorType';
+export type {BinningMethod} from './binningMethod';
 export type {DateGrouping} from './dateGrouping';
+export type {DateUnit} from './dateUnit';
 export type {FieldDataType} from './fieldDataType';
+export type {FilterOperator} from './filterOperator';
+export type {SortBy} from './sortBy';
+export type {SortDirection} from './sortDirection';
+export type {SupportingFieldAggregation} from './supportingFieldAggregation';
+export type {Weekday} from './weekday';
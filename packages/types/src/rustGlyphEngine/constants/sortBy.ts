@@ -0,0 +1,5 @@
+export enum SortBy {
+  VALUE = 'value',
+  ACCUMULATOR = 'accumulator',
+  EXPLICIT = 'explicit',
+}
@@ -0,0 +1,4 @@
+export enum SortDirection {
+  ASC = 'asc',
+  DESC = 'desc',
+}
@@ -0,0 +1,6 @@
+export enum SupportingFieldAggregation {
+  FIRST = 'first',
+  DISTINCT_LIST = 'distinct_list',
+  SUM = 'sum',
+  AVG = 'avg',
+}
@@ -4,5 +4,9 @@ import {AccumulatorType} from './constants/accumulatorType';
 export interface IAccumulatedFieldDefinition {
   fieldType: 'accumulated';
   accumulatorType: AccumulatorType;
+  //Required by PERCENTILE, between 0 and 1
+  percentile?: number;
+  //Required by FIRST and LAST, the column that orders the rows
+  orderByField?: string;
   accumulatedField: IStandardFieldDefinition | IDateFieldDefinition;
 }
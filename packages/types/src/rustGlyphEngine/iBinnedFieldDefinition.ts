@@ -0,0 +1,10 @@
+import {BinningMethod} from './constants/binningMethod';
+export interface IBinnedFieldDefinition {
+  fieldType: 'binned';
+  fieldName: string;
+  binningMethod: BinningMethod;
+  //Required by EQUAL_WIDTH and QUANTILE
+  binCount?: number;
+  //Required by EXPLICIT, in ascending order
+  edges?: number[];
+}
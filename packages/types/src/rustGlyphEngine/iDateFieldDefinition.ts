@@ -1,6 +1,16 @@
 import {DateGrouping} from './constants/dateGrouping';
+import {DateUnit} from './constants/dateUnit';
+import {Weekday} from './constants/weekday';
 export interface IDateFieldDefinition {
   fieldType: 'date';
   fieldName: string;
   dateGrouping: DateGrouping;
+  //Defaults to Milliseconds
+  dateUnit?: DateUnit;
+  //A zone name such as America/New_York or a fixed offset such as +05:30, defaults to UTC
+  timeZone?: string;
+  //Used by WeekStarting, defaults to Monday
+  weekStart?: Weekday;
+  //Used by MinuteBucket, 1 - 1440 minutes, defaults to 15
+  minuteBucket?: number;
 }
@@ -2,8 +2,16 @@ import {FieldDataType} from './constants/fieldDataType';
 import {IStandardFieldDefinition} from './iStandardFieldDefinition';
 import {IDateFieldDefinition} from './iDateFieldDefinition';
 import {IAccumulatedFieldDefinition} from './iAccumulatedFieldDefinition';
+import {IBinnedFieldDefinition} from './iBinnedFieldDefinition';
+import {ISortOrder} from './iSortOrder';
 export interface IFieldDefinition {
   fieldDisplayName: string;
   fieldDataType: FieldDataType;
-  fieldDefinition: IStandardFieldDefinition | IDateFieldDefinition | IAccumulatedFieldDefinition;
+  fieldDefinition:
+    | IStandardFieldDefinition
+    | IDateFieldDefinition
+    | IAccumulatedFieldDefinition
+    | IBinnedFieldDefinition;
+  //The order of the values of an axis, not allowed on accumulated fields
+  sortOrder?: ISortOrder;
 }
@@ -0,0 +1,19 @@
+import {FilterOperator} from './constants/filterOperator';
+export type IFilter =
+  | {and: IFilter[]}
+  | {or: IFilter[]}
+  | {not: IFilter}
+  | {
+      fieldName: string;
+      operator: FilterOperator;
+      //Required by the comparison operators
+      value?: string | number;
+      //Required by IN
+      values?: (string | number)[];
+      //Used by BETWEEN, both are inclusive and at least one is required
+      min?: string | number;
+      max?: string | number;
+      //Used by DATE_RANGE as epoch milliseconds or ISO 8601 dates, from is inclusive, to is exclusive
+      from?: string | number;
+      to?: string | number;
+    };
@@ -1,4 +1,6 @@
 import {IFieldDefinition} from './iFieldDefinition';
+import {IFilter} from './iFilter';
+import {SupportingFieldAggregation} from './constants/supportingFieldAggregation';
 export interface IGlyphEngineArgs {
   workspace_id: string;
   project_id: string;
@@ -6,8 +8,20 @@ export interface IGlyphEngineArgs {
   output_file_prefix: string;
   data_table_name: string;
   model_hash: string;
-  filter?: string;
+  //A string is raw SQL and is only accepted when allow_raw_filter is true
+  filter?: IFilter | string;
+  allow_raw_filter?: boolean;
+  //Defaults to true, when false the vectors are built from every row of the table
+  filter_vectors?: boolean;
   xAxis: IFieldDefinition;
   yAxis: IFieldDefinition;
   zAxis: IFieldDefinition;
+  //Optional glyph channels, each must be an accumulated field
+  glyphColor?: IFieldDefinition;
+  glyphSize?: IFieldDefinition;
+  glyphShape?: IFieldDefinition;
+  //When true, the supporting fields are aggregated per glyph and written to a .sup file
+  include_supporting_fields?: boolean;
+  //Supporting fields can not be accumulated, sum and avg require a numeric field
+  supportingFields?: (IFieldDefinition & {aggregation?: SupportingFieldAggregation})[];
 }
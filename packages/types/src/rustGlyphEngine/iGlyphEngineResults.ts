@@ -3,4 +3,6 @@ export interface IGlyphEngineResults {
   y_axis_vectors_file_name: string;
   glyphs_file_name: string;
   statistics_file_name: string;
+  //Only set when supporting fields were included
+  supporting_file_name?: string;
 }
@@ -0,0 +1,15 @@
+import {AccumulatorType} from './constants/accumulatorType';
+import {SortBy} from './constants/sortBy';
+import {SortDirection} from './constants/sortDirection';
+export interface ISortOrder {
+  //Defaults to VALUE
+  sortBy?: SortBy;
+  //Defaults to ASC, not used by EXPLICIT
+  direction?: SortDirection;
+  //Used by ACCUMULATOR, one of COUNT, SUM, AVG, MIN or MAX, defaults to COUNT
+  accumulator?: AccumulatorType;
+  //Required by ACCUMULATOR for every accumulator other than COUNT
+  fieldName?: string;
+  //Required by EXPLICIT, values that are not listed follow in value order
+  values?: (string | number)[];
+}
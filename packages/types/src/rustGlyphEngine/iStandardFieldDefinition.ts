@@ -1,4 +1,7 @@
+import {ITopNDefinition} from './iTopNDefinition';
 export interface IStandardFieldDefinition {
   fieldType: 'standard';
   fieldName: string;
+  //Only allowed on string fields
+  topN?: ITopNDefinition;
 }
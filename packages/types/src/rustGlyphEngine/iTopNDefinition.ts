@@ -0,0 +1,10 @@
+import {AccumulatorType} from './constants/accumulatorType';
+export interface ITopNDefinition {
+  count: number;
+  //One of COUNT, SUM, AVG, MIN or MAX, defaults to COUNT
+  accumulator?: AccumulatorType;
+  //Required by every accumulator other than COUNT
+  rankByField?: string;
+  //Defaults to 'Other'
+  otherLabel?: string;
+}
@@ -1,7 +1,11 @@
 export * as constants from './constants';
 export type {IAccumulatedFieldDefinition} from './iAccumulatedFieldDefinition';
+export type {IBinnedFieldDefinition} from './iBinnedFieldDefinition';
 export type {IDateFieldDefinition} from './iDateFieldDefinition';
 export type {IFieldDefinition} from './iFieldDefinition';
+export type {IFilter} from './iFilter';
 export type {IGlyphEngineArgs} from './iGlyphEngineArgs';
 export type {IGlyphEngineResults} from './iGlyphEngineResults';
+export type {ISortOrder} from './iSortOrder';
 export type {IStandardFieldDefinition} from './iStandardFieldDefinition';
+export type {ITopNDefinition} from './iTopNDefinition';